@@ -5,7 +5,11 @@
 
 #![cfg(not(target_arch = "wasm32"))]
 
-use linera_sdk::test::{QueryOutcome, TestValidator};
+use assert_matches::assert_matches;
+use linera_sdk::{
+    linera_base_types::{ApplicationId, ChainId, ChainOwnership, Round},
+    test::{ActiveChain, QueryOutcome, ResourceControlPolicy, TestValidator, WorkerError},
+};
 
 /// Test setting a counter and testing its coherency across microchains.
 ///
@@ -35,3 +39,182 @@ async fn single_chain_test() {
     let state_value = response["value"].as_u64().expect("Failed to get the u64");
     assert_eq!(state_value, final_value);
 }
+
+/// Test that two validators with the same seed create the application with the same ID.
+///
+/// Runs the same setup twice with the same seed, and once with another seed, and compares
+/// the IDs of the chains and applications.
+#[tokio::test(flavor = "multi_thread")]
+async fn seeded_validator_test() {
+    async fn create_application(seed: u64) -> (ChainId, ApplicationId<counter::CounterAbi>) {
+        let validator = TestValidator::with_seed(seed).await;
+        let publisher = validator.new_chain().await;
+        let module_id = publisher
+            .publish_current_module::<counter::CounterAbi, (), u64>()
+            .await;
+        let mut creator = validator.new_chain().await;
+        let application_id = creator.create_application(module_id, (), 42, vec![]).await;
+        (creator.id(), application_id)
+    }
+
+    let (chain_id, application_id) = create_application(7).await;
+    assert_eq!(create_application(7).await, (chain_id, application_id));
+
+    let (other_chain_id, other_application_id) = create_application(8).await;
+    assert_ne!(other_chain_id, chain_id);
+    assert_ne!(other_application_id, application_id);
+}
+
+/// Test the resources used by a block incrementing the counter.
+///
+/// Measures the resource usage of an increment, then lowers the fuel limit of the blocks below
+/// it and checks that the same increment is rejected.
+#[tokio::test(flavor = "multi_thread")]
+async fn resource_usage_test() {
+    let (mut validator, application_id, chain) =
+        TestValidator::with_current_application::<counter::CounterAbi, (), u64>((), 0).await;
+
+    let (_certificate, usage) = chain
+        .add_block_with_resource_usage(|block| {
+            block.with_operation(application_id, 1);
+        })
+        .await;
+    assert!(usage.fuel > 0);
+    assert!(usage.fuel < 2_000_000);
+    assert_eq!(usage.operations, 1);
+    assert_eq!(usage.messages, 0);
+    assert!(usage.bytes_written > 0);
+
+    validator
+        .set_resource_policy(ResourceControlPolicy {
+            maximum_wasm_fuel_per_block: usage.fuel - 1,
+            ..ResourceControlPolicy::no_fees()
+        })
+        .await;
+    chain
+        .try_add_block(|block| {
+            block.with_operation(application_id, 1);
+        })
+        .await
+        .expect_err("A block exceeding the fuel limit should be rejected");
+    assert_eq!(counter_value(&chain, application_id).await, 1);
+}
+
+/// Test running several scenarios from the same deployment of the application.
+///
+/// Creates the application, takes a snapshot of the validator and then runs three scenarios,
+/// each starting from the snapshot: incrementing the counter once, incrementing it in two
+/// blocks after creating a new chain, and incrementing it after creating another chain, which
+/// requires the admin chain to be back at its height of the snapshot.
+#[tokio::test(flavor = "multi_thread")]
+async fn snapshot_scenarios_test() {
+    let (validator, application_id, chain) =
+        TestValidator::with_current_application::<counter::CounterAbi, (), u64>((), 42).await;
+    let deployment_height = chain.get_tip_height().await;
+    let snapshot = validator.snapshot().await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(application_id, 1);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 43);
+    assert_eq!(chain.get_tip_height().await, deployment_height + 1);
+
+    validator.restore(&snapshot).await;
+    assert_eq!(counter_value(&chain, application_id).await, 42);
+    validator.new_chain().await;
+    for _ in 0..2 {
+        chain
+            .add_block(|block| {
+                block.with_operation(application_id, 10);
+            })
+            .await;
+    }
+    assert_eq!(counter_value(&chain, application_id).await, 62);
+    assert_eq!(chain.get_tip_height().await, deployment_height + 2);
+
+    validator.restore(&snapshot).await;
+    assert_eq!(chain.get_tip_height().await, deployment_height);
+    validator.new_chain().await;
+    chain
+        .add_block(|block| {
+            block.with_operation(application_id, 5);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 47);
+}
+
+/// Queries the value of the counter `application_id` on `chain`.
+async fn counter_value(
+    chain: &ActiveChain,
+    application_id: ApplicationId<counter::CounterAbi>,
+) -> u64 {
+    let QueryOutcome { response, .. } =
+        chain.graphql_query(application_id, "query { value }").await;
+    response["value"].as_u64().expect("Failed to get the u64")
+}
+
+/// Test two owners of a chain alternately proposing blocks that increment the counter.
+///
+/// Creates a chain owned by two owners, who take turns to propose blocks in the current
+/// multi-leader round. An owner who doesn't own the chain cannot propose, and neither can the
+/// regular owners in the fast round.
+#[tokio::test(flavor = "multi_thread")]
+async fn multi_owner_chain_test() {
+    let (validator, module_id) =
+        TestValidator::with_current_module::<counter::CounterAbi, (), u64>().await;
+    let owner1 = validator.new_owner();
+    let owner2 = validator.new_owner();
+    let ownership = ChainOwnership::builder()
+        .owner(owner1)
+        .owner(owner2)
+        .multi_leader_rounds(10)
+        .build()
+        .expect("two distinct owners should make a valid ownership");
+    let mut chain = validator.new_chain_with_ownership(ownership).await;
+
+    let application_id = chain.create_application(module_id, (), 0, vec![]).await;
+
+    for owner in [owner1, owner2, owner1, owner2] {
+        let round = chain.current_round().await;
+        assert_eq!(round, Round::MultiLeader(0));
+        let certificate = chain
+            .add_block(|block| {
+                block
+                    .with_owner(owner)
+                    .with_round(round)
+                    .with_operation(application_id, 1);
+            })
+            .await;
+        assert_eq!(
+            certificate.inner().block().header.authenticated_signer,
+            Some(owner)
+        );
+    }
+
+    let other_owner = validator.new_owner();
+    let result = chain
+        .try_add_block(|block| {
+            block
+                .with_owner(other_owner)
+                .with_round(Round::MultiLeader(0))
+                .with_operation(application_id, 1);
+        })
+        .await;
+    assert_matches!(result, Err(WorkerError::InvalidOwner));
+
+    let result = chain
+        .try_add_block(|block| {
+            block
+                .with_owner(owner1)
+                .with_round(Round::Fast)
+                .with_operation(application_id, 1);
+        })
+        .await;
+    assert_matches!(result, Err(WorkerError::InvalidOwner));
+
+    let QueryOutcome { response, .. } =
+        chain.graphql_query(application_id, "query { value }").await;
+    assert_eq!(response["value"].as_u64(), Some(4));
+}
@@ -7,14 +7,15 @@
 
 use std::iter;
 
-use crowd_funding::{CrowdFundingAbi, InstantiationArgument, Operation};
+use assert_matches::assert_matches;
+use crowd_funding::{CrowdFundingAbi, InstantiationArgument, Message, Operation};
 use fungible::FungibleTokenAbi;
 use linera_sdk::{
     linera_base_types::{
         AccountOwner, AccountSecretKey, Amount, ApplicationId, Ed25519SecretKey,
-        Secp256k1SecretKey, Timestamp,
+        Secp256k1SecretKey, TimeDelta, Timestamp,
     },
-    test::TestValidator,
+    test::{ChainError, TestValidator, WorkerError},
 };
 
 /// Test creating a campaign and collecting pledges.
@@ -224,3 +225,220 @@ async fn cancel_successful_campaign() {
         );
     }
 }
+
+/// Test that a campaign can only be cancelled after its deadline.
+///
+/// Creates a campaign with a deadline one hour after the validator's current time, and pledges
+/// part of a backer's balance to it. Cancelling the campaign fails until the validator's clock is
+/// advanced past the deadline, after which the pledge is refunded. Blocks with a timestamp earlier
+/// than the previous block are rejected.
+#[tokio::test(flavor = "multi_thread")]
+async fn cancel_campaign_only_after_deadline() {
+    let initial_amount = Amount::from_tokens(100);
+    let pledge_amount = Amount::from_tokens(75);
+
+    let (validator, module_id) = TestValidator::with_current_module::<
+        CrowdFundingAbi,
+        ApplicationId<FungibleTokenAbi>,
+        InstantiationArgument,
+    >()
+    .await;
+    validator.set_system_time(Timestamp::from(1_000_000));
+
+    let fungible_publisher_chain = validator.new_chain().await;
+    let mut campaign_chain = validator.new_chain().await;
+    let campaign_account = AccountOwner::from(campaign_chain.public_key());
+
+    let fungible_module_id = fungible_publisher_chain
+        .publish_bytecode_files_in("../fungible")
+        .await;
+
+    let (token_id, backers) =
+        fungible::create_with_accounts(&validator, fungible_module_id, [initial_amount]).await;
+    let (backer_chain, backer_account, _balance) = &backers[0];
+
+    let deadline = validator
+        .clock()
+        .current_time()
+        .saturating_add(TimeDelta::from_secs(3600));
+    let campaign_state = InstantiationArgument {
+        owner: campaign_account,
+        deadline,
+        target: Amount::from_tokens(1_000),
+    };
+    let campaign_id = campaign_chain
+        .create_application(
+            module_id,
+            token_id,
+            campaign_state,
+            vec![token_id.forget_abi()],
+        )
+        .await;
+
+    let pledge_certificate = backer_chain
+        .add_block(|block| {
+            block.with_operation(
+                campaign_id,
+                Operation::Pledge {
+                    owner: *backer_account,
+                    amount: pledge_amount,
+                },
+            );
+        })
+        .await;
+    campaign_chain
+        .add_block(|block| {
+            block.with_messages_from(&pledge_certificate);
+        })
+        .await;
+
+    validator.advance_time(TimeDelta::from_secs(3599));
+    assert!(campaign_chain
+        .try_add_block(|block| {
+            block.with_operation(campaign_id, Operation::Cancel);
+        })
+        .await
+        .is_err());
+
+    validator.advance_time(TimeDelta::from_secs(1));
+    let cancel_certificate = campaign_chain
+        .add_block(|block| {
+            block.with_operation(campaign_id, Operation::Cancel);
+        })
+        .await;
+    assert_eq!(
+        cancel_certificate.inner().block().header.timestamp,
+        deadline
+    );
+    assert_eq!(
+        fungible::query_account(token_id, &campaign_chain, *backer_account).await,
+        Some(pledge_amount),
+    );
+
+    let result = campaign_chain
+        .try_add_block(|block| {
+            block.with_timestamp(Timestamp::from(0));
+        })
+        .await;
+    assert_matches!(
+        result,
+        Err(WorkerError::ChainError(error)) if matches!(*error, ChainError::InvalidBlockTimestamp)
+    );
+}
+
+/// Test a pledge that arrives after its campaign was cancelled.
+///
+/// Holds the messages while two backers pledge to a campaign, and delivers the second pledge
+/// first. The campaign is then cancelled, so accepting the first pledge fails and blocks the
+/// campaign chain's inbox. Rejecting it instead bounces the tokens back to the first backer.
+#[tokio::test(flavor = "multi_thread")]
+async fn late_pledge_is_bounced_after_cancellation() {
+    let initial_amount = Amount::from_tokens(100);
+    let pledge_amount = Amount::from_tokens(75);
+
+    let (validator, module_id) = TestValidator::with_current_module::<
+        CrowdFundingAbi,
+        ApplicationId<FungibleTokenAbi>,
+        InstantiationArgument,
+    >()
+    .await;
+
+    let fungible_publisher_chain = validator.new_chain().await;
+    let mut campaign_chain = validator.new_chain().await;
+    let campaign_account = AccountOwner::from(campaign_chain.public_key());
+
+    let fungible_module_id = fungible_publisher_chain
+        .publish_bytecode_files_in("../fungible")
+        .await;
+
+    let (token_id, backers) = fungible::create_with_accounts(
+        &validator,
+        fungible_module_id,
+        iter::repeat_n(initial_amount, 2),
+    )
+    .await;
+
+    let campaign_state = InstantiationArgument {
+        owner: campaign_account,
+        deadline: Timestamp::from(10),
+        target: Amount::from_tokens(1_000),
+    };
+    let campaign_id = campaign_chain
+        .create_application(
+            module_id,
+            token_id,
+            campaign_state,
+            vec![token_id.forget_abi()],
+        )
+        .await;
+
+    validator.pause_message_delivery();
+
+    for (backer_chain, backer_account, _balance) in &backers {
+        backer_chain
+            .add_block(|block| {
+                block.with_operation(
+                    campaign_id,
+                    Operation::Pledge {
+                        owner: *backer_account,
+                        amount: pledge_amount,
+                    },
+                );
+            })
+            .await;
+    }
+
+    let pledges = campaign_chain
+        .pending_messages()
+        .await
+        .into_iter()
+        .filter(|message| message.application_id() == Some(campaign_id.forget_abi()))
+        .collect::<Vec<_>>();
+    assert_eq!(pledges.len(), 2);
+    assert!(pledges.iter().all(|pledge| pledge.held));
+    assert_matches!(
+        pledges[0].user_message::<Message>(),
+        Some(Message::PledgeWithAccount { owner, amount })
+            if owner == backers[0].1 && amount == pledge_amount
+    );
+
+    campaign_chain.receive_message(pledges[1].id).await;
+
+    campaign_chain
+        .add_block(|block| {
+            block
+                .with_timestamp(Timestamp::from(20))
+                .with_operation(campaign_id, Operation::Cancel);
+        })
+        .await;
+
+    assert!(campaign_chain
+        .try_receive_message(pledges[0].id)
+        .await
+        .is_err());
+    campaign_chain.reject_message(pledges[0].id).await;
+
+    validator.resume_message_delivery().await;
+
+    let (first_backer_chain, first_backer_account, _balance) = &backers[0];
+    first_backer_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(token_id, first_backer_chain, *first_backer_account).await,
+        Some(initial_amount),
+    );
+    assert_eq!(
+        fungible::query_account(token_id, &campaign_chain, *first_backer_account).await,
+        None,
+    );
+
+    let (second_backer_chain, second_backer_account, _balance) = &backers[1];
+    assert_eq!(
+        fungible::query_account(token_id, second_backer_chain, *second_backer_account).await,
+        Some(initial_amount.saturating_sub(pledge_amount)),
+    );
+    assert_eq!(
+        fungible::query_account(token_id, &campaign_chain, *second_backer_account).await,
+        Some(pledge_amount),
+    );
+}
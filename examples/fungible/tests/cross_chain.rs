@@ -6,11 +6,12 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use fungible::{
-    Account, FungibleTokenAbi, InitialState, InitialStateBuilder, Operation, Parameters,
+    Account, FungibleTokenAbi, InitialState, InitialStateBuilder, Message, Operation, Parameters,
 };
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount},
-    test::{MessageAction, TestValidator},
+    bcs,
+    linera_base_types::{AccountOwner, Amount, ApplicationId},
+    test::{ActiveChain, InterceptAction, MessageAction, OutgoingMessageInfo, TestValidator},
 };
 
 /// Test transferring tokens across microchains.
@@ -134,3 +135,133 @@ async fn test_bouncing_tokens() {
         Some(initial_amount),
     );
 }
+
+/// Test a transfer message that is dropped on its way to the receiver.
+///
+/// The message is tracked, so instead of disappearing it is rejected by the receiver, and the
+/// tokens bounce back to the sender.
+#[tokio::test]
+async fn test_dropped_transfer_bounces() {
+    let (application_id, sender_chain, sender_account, receiver_chain, receiver_account) =
+        transfer_with_interceptor(|info| {
+            if info.message.is_tracked() {
+                InterceptAction::Drop
+            } else {
+                InterceptAction::Deliver
+            }
+        })
+        .await;
+
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &receiver_chain, receiver_account).await,
+        None,
+    );
+
+    sender_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &sender_chain, sender_account).await,
+        Some(Amount::from_tokens(20)),
+    );
+}
+
+/// Test a transfer message that is replayed on its way to the receiver, who is then credited
+/// twice.
+#[tokio::test]
+async fn test_duplicated_transfer_message() {
+    let (application_id, _, _, receiver_chain, receiver_account) =
+        transfer_with_interceptor(|_| InterceptAction::Duplicate).await;
+
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &receiver_chain, receiver_account).await,
+        Some(Amount::from_tokens(10)),
+    );
+}
+
+/// Test a transfer message whose amount is modified on its way to the receiver.
+#[tokio::test]
+async fn test_modified_transfer_message() {
+    let (application_id, _, _, receiver_chain, receiver_account) =
+        transfer_with_interceptor(|info| {
+            let bytes = info
+                .bytes()
+                .expect("Only the transfer message should be sent");
+            let Message::Credit { target, source, .. } =
+                bcs::from_bytes(bytes).expect("Failed to deserialize transfer message")
+            else {
+                panic!("Unexpected message");
+            };
+            let message = Message::Credit {
+                target,
+                amount: Amount::from_tokens(1),
+                source,
+            };
+            InterceptAction::DeliverModified(bcs::to_bytes(&message).unwrap())
+        })
+        .await;
+
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &receiver_chain, receiver_account).await,
+        Some(Amount::from_tokens(1)),
+    );
+}
+
+/// Creates the application on a sender chain, with 20 tokens for its owner, and transfers 5 of
+/// them to the owner of a new receiver chain, with the `interceptor` handling the messages.
+///
+/// Returns the application ID, and the chains with their owners' accounts.
+async fn transfer_with_interceptor(
+    interceptor: impl Fn(OutgoingMessageInfo) -> InterceptAction + Send + Sync + 'static,
+) -> (
+    ApplicationId<FungibleTokenAbi>,
+    ActiveChain,
+    AccountOwner,
+    ActiveChain,
+    AccountOwner,
+) {
+    let (validator, module_id) =
+        TestValidator::with_current_module::<FungibleTokenAbi, Parameters, InitialState>().await;
+    let mut sender_chain = validator.new_chain().await;
+    let sender_account = AccountOwner::from(sender_chain.public_key());
+
+    let initial_state =
+        InitialStateBuilder::default().with_account(sender_account, Amount::from_tokens(20));
+    let params = Parameters::new("INT");
+    let application_id = sender_chain
+        .create_application(module_id, params, initial_state.build(), vec![])
+        .await;
+
+    let receiver_chain = validator.new_chain().await;
+    let receiver_account = AccountOwner::from(receiver_chain.public_key());
+
+    validator.set_message_interceptor(interceptor);
+    sender_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Transfer {
+                    owner: sender_account,
+                    amount: Amount::from_tokens(5),
+                    target_account: Account {
+                        chain_id: receiver_chain.id(),
+                        owner: receiver_account,
+                    },
+                },
+            );
+        })
+        .await;
+
+    (
+        application_id,
+        sender_chain,
+        sender_account,
+        receiver_chain,
+        receiver_account,
+    )
+}
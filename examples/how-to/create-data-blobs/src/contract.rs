@@ -0,0 +1,62 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+use how_to_create_data_blobs::{Abi, Operation};
+use linera_sdk::{linera_base_types::WithContractAbi, ContractRuntime, DataBlobHash};
+
+pub struct Contract {
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(Contract);
+
+impl WithContractAbi for Contract {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Contract for Contract {
+    type Message = ();
+    type InstantiationArgument = ();
+    type Parameters = ();
+    type EventValue = ();
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        Contract { runtime }
+    }
+
+    async fn instantiate(&mut self, (): Self::InstantiationArgument) {}
+
+    async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        match operation {
+            Operation::CreateBlob(content) => self.create_blob(content),
+            Operation::CheckBlob {
+                hash,
+                expected_content,
+            } => self.check_blob(hash, expected_content),
+        }
+    }
+
+    async fn execute_message(&mut self, (): Self::Message) {
+        panic!("This application doesn't support any cross-chain messages");
+    }
+
+    async fn store(self) {}
+}
+
+impl Contract {
+    /// Creates a data blob with the given `content`.
+    ///
+    /// The blob is published by the current block, and can be read from subsequent blocks.
+    fn create_blob(&mut self, content: Vec<u8>) {
+        self.runtime
+            .create_data_blob(&content)
+            .expect("The blob content should not exceed the maximum blob size");
+    }
+
+    /// Reads the data blob with the given `hash` and checks that it has the `expected_content`.
+    fn check_blob(&mut self, hash: DataBlobHash, expected_content: Vec<u8>) {
+        assert_eq!(self.runtime.read_data_blob(hash), expected_content);
+    }
+}
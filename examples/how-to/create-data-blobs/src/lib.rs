@@ -0,0 +1,35 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ABI of the Data Blob Creation Example Application
+
+use linera_sdk::{
+    abi::{ContractAbi, ServiceAbi},
+    DataBlobHash,
+};
+use serde::{Deserialize, Serialize};
+
+/// The marker type that connects the types used to interface with the application.
+pub struct Abi;
+
+impl ContractAbi for Abi {
+    type Operation = Operation;
+    type Response = ();
+}
+
+impl ServiceAbi for Abi {
+    type Query = DataBlobHash;
+    type QueryResponse = Vec<u8>;
+}
+
+/// Operations that the contract can handle.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Operation {
+    /// Creates a data blob with the given content.
+    CreateBlob(Vec<u8>),
+    /// Reads a data blob and checks that it has the expected content.
+    CheckBlob {
+        hash: DataBlobHash,
+        expected_content: Vec<u8>,
+    },
+}
@@ -0,0 +1,30 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+use how_to_create_data_blobs::Abi;
+use linera_sdk::{linera_base_types::WithServiceAbi, DataBlobHash, ServiceRuntime};
+
+pub struct Service {
+    runtime: ServiceRuntime<Self>,
+}
+
+linera_sdk::service!(Service);
+
+impl WithServiceAbi for Service {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Service for Service {
+    type Parameters = ();
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        Service { runtime }
+    }
+
+    /// Returns the content of the data blob with the requested hash.
+    async fn handle_query(&self, hash: DataBlobHash) -> Vec<u8> {
+        self.runtime.read_data_blob(hash)
+    }
+}
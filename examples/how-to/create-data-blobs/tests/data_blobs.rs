@@ -0,0 +1,74 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests that create data blobs and read them back in later blocks.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use how_to_create_data_blobs::{Abi, Operation};
+use linera_sdk::{
+    test::{QueryOutcome, TestValidator},
+    DataBlobHash,
+};
+
+/// Tests if a data blob created by the contract in one block can be read by the contract in a
+/// subsequent block, and by the service.
+#[tokio::test(flavor = "multi_thread")]
+async fn data_blob_created_in_one_block_is_read_in_the_next() {
+    let content = b"Hello, blob!".to_vec();
+    let hash = DataBlobHash::from_content(&content);
+
+    let (_validator, application_id, chain) =
+        TestValidator::with_current_application::<Abi, _, _>((), ()).await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::CreateBlob(content.clone()));
+        })
+        .await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::CheckBlob {
+                    hash,
+                    expected_content: content.clone(),
+                },
+            );
+        })
+        .await;
+
+    let QueryOutcome { response, .. } = chain.query(application_id, hash).await;
+
+    assert_eq!(response, content);
+}
+
+/// Tests if a data blob published by the test itself can be read by the contract and the
+/// service.
+#[tokio::test(flavor = "multi_thread")]
+async fn published_data_blob_is_read() {
+    let content = b"Published blob".to_vec();
+
+    let (validator, application_id, chain) =
+        TestValidator::with_current_application::<Abi, _, _>((), ()).await;
+
+    let hash = validator.publish_data_blob(content.clone()).await;
+    assert_eq!(hash, DataBlobHash::from_content(&content));
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::CheckBlob {
+                    hash,
+                    expected_content: content.clone(),
+                },
+            );
+        })
+        .await;
+
+    let QueryOutcome { response, .. } = chain.query(application_id, hash).await;
+
+    assert_eq!(response, content);
+}
@@ -0,0 +1,123 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use how_to_manage_child_chains::{Abi, Message, Operation};
+use linera_sdk::{
+    linera_base_types::{
+        Amount, ApplicationPermissions, ChainOwnership, ModuleId, WithContractAbi,
+    },
+    views::{RootView, View},
+    ContractRuntime,
+};
+
+use self::state::ChildChainsState;
+
+pub struct Contract {
+    state: ChildChainsState,
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(Contract);
+
+impl WithContractAbi for Contract {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Contract for Contract {
+    type Message = Message;
+    type InstantiationArgument = ();
+    type Parameters = ();
+    type EventValue = ();
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = ChildChainsState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        Contract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, (): Self::InstantiationArgument) {}
+
+    async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        match operation {
+            Operation::OpenChild {
+                ownership,
+                balance,
+                greeting,
+            } => self.open_child(ownership, balance, greeting),
+            Operation::CloseChain => self.close_chain(),
+            Operation::ChangePermissions(permissions) => self.change_permissions(permissions),
+            Operation::CreateInstance(module_id) => self.create_instance(module_id),
+        }
+    }
+
+    async fn execute_message(&mut self, message: Self::Message) {
+        match message {
+            Message::Greet(greeting) => self.state.greeting.set(Some(greeting)),
+        }
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl Contract {
+    /// Opens a child chain with the given `ownership` and `balance`, and sends it the
+    /// `greeting`.
+    ///
+    /// Only this application may execute operations on the child chain, and it must be used
+    /// in every block there.
+    fn open_child(&mut self, ownership: ChainOwnership, balance: Amount, greeting: String) {
+        let application_id = self.runtime.application_id().forget_abi();
+        let permissions = ApplicationPermissions::new_single(application_id);
+        let chain_id = self.runtime.open_chain(ownership, permissions, balance);
+        self.state.children.push(chain_id);
+        // The new chain exists as soon as this block is confirmed, so it can receive messages
+        // from this same block.
+        self.runtime
+            .send_message(chain_id, Message::Greet(greeting));
+    }
+
+    /// Closes the current chain.
+    ///
+    /// If this application isn't allowed to close the chain, the block still succeeds, and the
+    /// error is recorded so that it can be reported to the user.
+    fn close_chain(&mut self) {
+        let error = self.runtime.close_chain().err();
+        self.state
+            .last_error
+            .set(error.map(|error| error.to_string()));
+    }
+
+    /// Changes the application permissions of the current chain.
+    ///
+    /// This is only allowed if this application is listed in the current permissions'
+    /// `change_application_permissions`. Otherwise the error is recorded, like in
+    /// [`Contract::close_chain`].
+    fn change_permissions(&mut self, permissions: ApplicationPermissions) {
+        let error = self
+            .runtime
+            .change_application_permissions(permissions)
+            .err();
+        self.state
+            .last_error
+            .set(error.map(|error| error.to_string()));
+    }
+
+    /// Creates a new instance of the application from the module with the given `module_id`.
+    ///
+    /// The new application is created and instantiated by the current block. Its ID only depends
+    /// on the current chain, the block height and the number of applications created before it
+    /// in the block, so it is the same on every validator.
+    fn create_instance(&mut self, module_id: ModuleId) {
+        let application_id =
+            self.runtime
+                .create_application::<Abi, (), ()>(module_id, &(), &(), vec![]);
+        self.state.instances.push(application_id.forget_abi());
+    }
+}
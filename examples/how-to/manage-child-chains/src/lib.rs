@@ -0,0 +1,64 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ABI of the Child Chain Management Example Application
+
+use linera_sdk::{
+    abi::{ContractAbi, ServiceAbi},
+    linera_base_types::{
+        Amount, ApplicationId, ApplicationPermissions, ChainId, ChainOwnership, ModuleId,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// The marker type that connects the types used to interface with the application.
+pub struct Abi;
+
+impl ContractAbi for Abi {
+    type Operation = Operation;
+    type Response = ();
+}
+
+impl ServiceAbi for Abi {
+    type Query = ();
+    type QueryResponse = Status;
+}
+
+/// Operations that the contract can handle.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Operation {
+    /// Opens a child chain with the given `ownership`, transfers `balance` tokens from the
+    /// current chain to it, and sends it the `greeting`.
+    OpenChild {
+        ownership: ChainOwnership,
+        balance: Amount,
+        greeting: String,
+    },
+    /// Tries to close the current chain, and records the error if that isn't possible.
+    CloseChain,
+    /// Tries to change the application permissions of the current chain, and records the error
+    /// if that isn't possible.
+    ChangePermissions(ApplicationPermissions),
+    /// Creates a new instance of the application from its published `ModuleId`.
+    CreateInstance(ModuleId),
+}
+
+/// Messages that the contract sends to itself on its child chains.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Message {
+    /// Greets a child chain right after it was opened.
+    Greet(String),
+}
+
+/// The state of the application on a chain, as reported by the service.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Status {
+    /// The child chains opened from this chain.
+    pub children: Vec<ChainId>,
+    /// The greeting received from the parent chain, if this is a child chain.
+    pub greeting: Option<String>,
+    /// The error reported by the last operation that failed to manage the current chain.
+    pub last_error: Option<String>,
+    /// The instances of the application created from this chain.
+    pub instances: Vec<ApplicationId>,
+}
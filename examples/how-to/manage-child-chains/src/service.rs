@@ -0,0 +1,37 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use how_to_manage_child_chains::{Abi, Status};
+use linera_sdk::{linera_base_types::WithServiceAbi, views::View, ServiceRuntime};
+
+use self::state::ChildChainsState;
+
+pub struct Service {
+    state: ChildChainsState,
+}
+
+linera_sdk::service!(Service);
+
+impl WithServiceAbi for Service {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Service for Service {
+    type Parameters = ();
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = ChildChainsState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        Service { state }
+    }
+
+    /// Returns the child chains opened from this chain and the greeting it received.
+    async fn handle_query(&self, (): ()) -> Status {
+        self.state.status().await
+    }
+}
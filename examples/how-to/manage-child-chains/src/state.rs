@@ -0,0 +1,43 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use how_to_manage_child_chains::Status;
+use linera_sdk::{
+    linera_base_types::{ApplicationId, ChainId},
+    views::{linera_views, LogView, RegisterView, RootView, ViewStorageContext},
+};
+
+/// The application state.
+#[derive(RootView)]
+#[view(context = ViewStorageContext)]
+pub struct ChildChainsState {
+    /// The child chains opened from this chain.
+    pub children: LogView<ChainId>,
+    /// The greeting received from the parent chain, if this is a child chain.
+    pub greeting: RegisterView<Option<String>>,
+    /// The error reported by the last operation that failed to manage the current chain.
+    pub last_error: RegisterView<Option<String>>,
+    /// The instances of the application created from this chain.
+    pub instances: LogView<ApplicationId>,
+}
+
+#[allow(dead_code)]
+impl ChildChainsState {
+    /// Returns the [`Status`] reported by the service.
+    pub async fn status(&self) -> Status {
+        Status {
+            children: self
+                .children
+                .read(..)
+                .await
+                .expect("Failed to read child chains"),
+            greeting: self.greeting.get().clone(),
+            last_error: self.last_error.get().clone(),
+            instances: self
+                .instances
+                .read(..)
+                .await
+                .expect("Failed to read application instances"),
+        }
+    }
+}
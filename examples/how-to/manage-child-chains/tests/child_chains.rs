@@ -0,0 +1,272 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests that open child chains from a contract and manage them.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use how_to_manage_child_chains::{Abi, Operation};
+use linera_sdk::{
+    linera_base_types::{
+        AccountOwner, AccountSecretKey, Amount, ApplicationId, ApplicationPermissions, BlobType,
+        ChainDescription, ChainOwnership, TimeDelta,
+    },
+    test::{ActiveChain, QueryOutcome, Recipient, TestValidator},
+};
+
+/// Tests if a contract opens a child chain with several weighted owners and custom timeouts,
+/// funds it from the parent chain and sends it a message right away.
+#[tokio::test(flavor = "multi_thread")]
+async fn open_multi_owner_child_chain_and_greet_it() {
+    let (validator, application_id, parent) =
+        TestValidator::with_current_application::<Abi, _, _>((), ()).await;
+    fund(&validator, &parent, Amount::from_tokens(10)).await;
+
+    let key_pair1 = AccountSecretKey::generate();
+    let key_pair2 = AccountSecretKey::generate();
+    let ownership = ChainOwnership::builder()
+        .owner_with_weight(key_pair1.public().into(), 1)
+        .owner_with_weight(key_pair2.public().into(), 3)
+        .base_timeout(TimeDelta::from_secs(5))
+        .timeout_increment(TimeDelta::from_secs(2))
+        .build()
+        .expect("The ownership should be valid");
+
+    let child = open_child(
+        &validator,
+        application_id,
+        &parent,
+        key_pair1,
+        ownership,
+        Amount::from_tokens(4),
+        "Hello, child!",
+    )
+    .await;
+
+    assert_eq!(parent.chain_balance().await, Amount::from_tokens(6));
+    assert_eq!(child.chain_balance().await, Amount::from_tokens(4));
+
+    let QueryOutcome { response, .. } = parent.query(application_id, ()).await;
+    assert_eq!(response.children, vec![child.id()]);
+    assert_eq!(response.greeting, None);
+
+    let QueryOutcome { response, .. } = child.query(application_id, ()).await;
+    assert_eq!(response.children, vec![]);
+    assert_eq!(response.greeting.as_deref(), Some("Hello, child!"));
+}
+
+/// Tests if a contract that isn't allowed to close its chain handles the error, and if it can
+/// close a child chain where it is allowed to.
+#[tokio::test(flavor = "multi_thread")]
+async fn close_chain_reports_missing_permission() {
+    let (validator, application_id, parent) =
+        TestValidator::with_current_application::<Abi, _, _>((), ()).await;
+
+    parent
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::CloseChain);
+        })
+        .await;
+
+    assert!(!parent.is_closed().await);
+    let QueryOutcome { response, .. } = parent.query(application_id, ()).await;
+    assert_eq!(
+        response.last_error.as_deref(),
+        Some("Unauthorized attempt to close the chain")
+    );
+
+    let key_pair = AccountSecretKey::generate();
+    let ownership = ChainOwnership::single(key_pair.public().into());
+    let child = open_child(
+        &validator,
+        application_id,
+        &parent,
+        key_pair,
+        ownership,
+        Amount::ZERO,
+        "Goodbye, child!",
+    )
+    .await;
+
+    child
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::CloseChain);
+        })
+        .await;
+
+    assert!(child.is_closed().await);
+}
+
+/// Tests if a contract can only change the application permissions of a chain where it is
+/// listed in `change_application_permissions`.
+#[tokio::test(flavor = "multi_thread")]
+async fn change_application_permissions_from_contract() {
+    let (validator, application_id, parent) =
+        TestValidator::with_current_application::<Abi, _, _>((), ()).await;
+    let single_application_permissions =
+        ApplicationPermissions::new_single(application_id.forget_abi());
+
+    parent
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::ChangePermissions(single_application_permissions.clone()),
+            );
+        })
+        .await;
+
+    let QueryOutcome { response, .. } = parent.query(application_id, ()).await;
+    assert_eq!(
+        response.last_error.as_deref(),
+        Some("Unauthorized attempt to change the application permissions")
+    );
+
+    let key_pair = AccountSecretKey::generate();
+    let ownership = ChainOwnership::single(key_pair.public().into());
+    let child = open_child(
+        &validator,
+        application_id,
+        &parent,
+        key_pair,
+        ownership,
+        Amount::ZERO,
+        "Hello, child!",
+    )
+    .await;
+
+    // Keep every permission, except the one to close the chain.
+    let permissions = ApplicationPermissions {
+        close_chain: vec![],
+        ..single_application_permissions
+    };
+    child
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::ChangePermissions(permissions));
+        })
+        .await;
+
+    let QueryOutcome { response, .. } = child.query(application_id, ()).await;
+    assert_eq!(response.last_error, None);
+
+    child
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::CloseChain);
+        })
+        .await;
+
+    assert!(!child.is_closed().await);
+    let QueryOutcome { response, .. } = child.query(application_id, ()).await;
+    assert_eq!(
+        response.last_error.as_deref(),
+        Some("Unauthorized attempt to close the chain")
+    );
+}
+
+/// Tests if a contract creates a new instance of an application from its published module, with
+/// the ID derived from the description published by the block.
+#[tokio::test(flavor = "multi_thread")]
+async fn create_application_instance_from_contract() {
+    let (validator, module_id) = TestValidator::with_current_module::<Abi, (), ()>().await;
+    let mut chain = validator.new_chain().await;
+    let factory_id = chain.create_application(module_id, (), (), vec![]).await;
+
+    let certificate = chain
+        .add_block(|block| {
+            block.with_operation(
+                factory_id,
+                Operation::CreateInstance(module_id.forget_abi()),
+            );
+        })
+        .await;
+
+    let QueryOutcome { response, .. } = chain.query(factory_id, ()).await;
+    assert_eq!(response.instances.len(), 1);
+    let instance_id = response.instances[0];
+    assert_ne!(instance_id, factory_id.forget_abi());
+
+    let description_hashes = certificate
+        .inner()
+        .block()
+        .created_blobs()
+        .into_keys()
+        .filter(|blob_id| blob_id.blob_type == BlobType::ApplicationDescription)
+        .map(|blob_id| blob_id.hash)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        description_hashes,
+        vec![instance_id.application_description_hash]
+    );
+
+    // The new instance was instantiated, and has its own state.
+    let QueryOutcome { response, .. } = chain.query(instance_id.with_abi::<Abi>(), ()).await;
+    assert!(response.instances.is_empty());
+}
+
+/// Transfers `amount` tokens from the admin chain to the `chain`.
+async fn fund(validator: &TestValidator, chain: &ActiveChain, amount: Amount) {
+    let admin_chain = validator.get_chain(&validator.admin_chain_id());
+    let certificate = admin_chain
+        .add_block(|block| {
+            block.with_native_token_transfer(
+                AccountOwner::CHAIN,
+                Recipient::chain(chain.id()),
+                amount,
+            );
+        })
+        .await;
+    chain
+        .add_block(|block| {
+            block.with_messages_from(&certificate);
+        })
+        .await;
+}
+
+/// Opens a child chain of `parent` with the `ownership` and `balance`, and greets it.
+///
+/// Returns the child chain, on which blocks are proposed by `key_pair`, after it received the
+/// greeting.
+async fn open_child(
+    validator: &TestValidator,
+    application_id: ApplicationId<Abi>,
+    parent: &ActiveChain,
+    key_pair: AccountSecretKey,
+    ownership: ChainOwnership,
+    balance: Amount,
+    greeting: &str,
+) -> ActiveChain {
+    let certificate = parent
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::OpenChild {
+                    ownership,
+                    balance,
+                    greeting: greeting.to_owned(),
+                },
+            );
+        })
+        .await;
+
+    let description = certificate
+        .inner()
+        .block()
+        .created_blobs()
+        .into_iter()
+        .find_map(|(blob_id, blob)| {
+            (blob_id.blob_type == BlobType::ChainDescription).then(|| {
+                bcs::from_bytes::<ChainDescription>(blob.content().bytes())
+                    .expect("Failed to deserialize the chain description")
+            })
+        })
+        .expect("The block should have opened a chain");
+    let child = ActiveChain::new(key_pair, description, validator.clone());
+    validator.add_chain(child.clone());
+
+    child
+        .add_block(|block| {
+            block.with_messages_from(&certificate);
+        })
+        .await;
+
+    child
+}
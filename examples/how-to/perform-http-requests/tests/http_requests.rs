@@ -7,9 +7,13 @@
 
 use assert_matches::assert_matches;
 use axum::{routing::get, Router};
-use how_to_perform_http_requests::Abi;
-use linera_sdk::test::{
-    ExecutionError, HttpServer, QueryOutcome, TestValidator, WasmExecutionError,
+use how_to_perform_http_requests::{Abi, Operation};
+use linera_sdk::{
+    http,
+    linera_base_types::{ChainOwnership, Round},
+    test::{
+        ExecutionError, HttpServer, QueryOutcome, TestValidator, WasmExecutionError, WorkerError,
+    },
 };
 
 /// Tests if service query performs HTTP request to allowed host.
@@ -59,6 +63,79 @@ async fn service_query_performs_http_request() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests if service query receives the response mocked by the test validator instead of
+/// reaching the network.
+#[test_log::test(tokio::test)]
+async fn service_query_receives_mocked_http_response() {
+    const HTTP_RESPONSE_BODY: &str = "Mocked response";
+    let url = "http://example.com/";
+
+    let (mut validator, application_id, chain) =
+        TestValidator::with_current_application::<Abi, _, _>(url.to_owned(), ()).await;
+
+    validator
+        .change_resource_control_policy(|policy| {
+            policy
+                .http_request_allow_list
+                .insert("example.com".to_owned());
+        })
+        .await;
+    validator.expect_http_request(
+        move |requested_url| requested_url == url,
+        http::Response::ok(HTTP_RESPONSE_BODY),
+    );
+
+    let QueryOutcome { response, .. } = chain
+        .graphql_query(application_id, "query { performHttpRequest }")
+        .await;
+
+    let bytes = response["performHttpRequest"]
+        .as_array()
+        .expect("Expected a list of bytes representing the response body")
+        .iter()
+        .map(|value| {
+            value
+                .as_u64()
+                .and_then(|integer| u8::try_from(integer).ok())
+                .expect("Expected a byte")
+        })
+        .collect::<Vec<u8>>();
+
+    assert_eq!(bytes, HTTP_RESPONSE_BODY.as_bytes());
+}
+
+/// Tests if service query fails when it makes an HTTP request that the test validator doesn't
+/// expect.
+#[test_log::test(tokio::test)]
+async fn service_query_fails_on_unexpected_http_request() {
+    let url = "http://example.com/";
+
+    let (mut validator, application_id, chain) =
+        TestValidator::with_current_application::<Abi, _, _>(url.to_owned(), ()).await;
+
+    validator
+        .change_resource_control_policy(|policy| {
+            policy
+                .http_request_allow_list
+                .insert("example.com".to_owned());
+        })
+        .await;
+    validator.expect_http_request(
+        |requested_url| requested_url == "http://example.com/other",
+        http::Response::ok("Other response"),
+    );
+
+    let error = chain
+        .try_graphql_query(application_id, "query { performHttpRequest }")
+        .await
+        .expect_err("Expected GraphQL query to fail");
+
+    assert_matches!(
+        error.expect_execution_error(),
+        ExecutionError::UnexpectedHttpRequest(attempted_url) if attempted_url == url
+    );
+}
+
 /// Tests if service query can't perform HTTP requests to hosts that aren't allowed.
 #[test_log::test(tokio::test)]
 async fn service_query_cant_send_http_request_to_unauthorized_host() {
@@ -266,3 +343,49 @@ async fn contract_rejects_invalid_http_response_from_oracle() {
         ExecutionError::WasmError(WasmExecutionError::ExecuteModule(_))
     );
 }
+
+/// Tests if a block performing an HTTP request in the contract is rejected in the fast round,
+/// where oracles can't be used, and accepted in a later round.
+#[test_log::test(tokio::test)]
+async fn contract_cant_perform_http_request_in_fast_round() {
+    let url = "http://example.com/";
+
+    let (mut validator, module_id) = TestValidator::with_current_module::<Abi, String, ()>().await;
+    let owner = validator.new_owner();
+    let mut chain = validator
+        .new_chain_with_ownership(ChainOwnership::single_super(owner))
+        .await;
+    let application_id = chain
+        .create_application(module_id, url.to_owned(), (), vec![])
+        .await;
+
+    validator
+        .change_resource_control_policy(|policy| {
+            policy
+                .http_request_allow_list
+                .insert("example.com".to_owned());
+        })
+        .await;
+    validator.expect_http_request(
+        move |requested_url| requested_url == url,
+        http::Response::ok("Hello, world!"),
+    );
+
+    assert_eq!(chain.current_round().await, Round::Fast);
+    let result = chain
+        .try_add_block(|block| {
+            block
+                .with_round(Round::Fast)
+                .with_operation(application_id, Operation::PerformHttpRequest);
+        })
+        .await;
+    assert_matches!(result, Err(WorkerError::FastBlockUsingOracles));
+
+    chain
+        .add_block(|block| {
+            block
+                .with_round(Round::MultiLeader(0))
+                .with_operation(application_id, Operation::PerformHttpRequest);
+        })
+        .await;
+}
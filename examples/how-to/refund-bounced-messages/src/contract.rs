@@ -0,0 +1,89 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use how_to_refund_bounced_messages::{Abi, Message, Operation};
+use linera_sdk::{
+    linera_base_types::{Amount, WithContractAbi},
+    views::{RootView, View},
+    ContractRuntime,
+};
+
+use self::state::RefundState;
+
+pub struct Contract {
+    state: RefundState,
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(Contract);
+
+impl WithContractAbi for Contract {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Contract for Contract {
+    type Message = Message;
+    type InstantiationArgument = Amount;
+    type Parameters = ();
+    type EventValue = ();
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = RefundState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        Contract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, initial_balance: Amount) {
+        let owner = self
+            .runtime
+            .require_authenticated_signer()
+            .expect("The application must be created by a signed block");
+        self.state.credit(owner, initial_balance).await;
+    }
+
+    async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        match operation {
+            Operation::Send {
+                destination,
+                amount,
+            } => {
+                let owner = self
+                    .runtime
+                    .require_authenticated_signer()
+                    .expect("Sending tokens requires an authenticated signer");
+                self.state.debit(owner, amount).await;
+                self.runtime
+                    .prepare_message(Message::Deposit { amount })
+                    .with_authentication()
+                    .with_tracking()
+                    .send_to(destination);
+            }
+        }
+    }
+
+    async fn execute_message(&mut self, message: Self::Message) {
+        match message {
+            Message::Deposit { amount } => {
+                let recipient = self
+                    .runtime
+                    .refund_recipient()
+                    .expect("Deposits are always authenticated");
+                if self.runtime.is_bouncing() {
+                    // The tokens couldn't be delivered, so they are returned to the sender,
+                    // whose account is on this chain.
+                    assert_eq!(recipient.chain_id, self.runtime.chain_id());
+                }
+                self.state.credit(recipient.owner, amount).await;
+            }
+        }
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ABI of the Bounced Message Refund Example Application
+
+use linera_sdk::{
+    abi::{ContractAbi, ServiceAbi},
+    linera_base_types::{AccountOwner, Amount, ChainId},
+};
+use serde::{Deserialize, Serialize};
+
+/// The marker type that connects the types used to interface with the application.
+pub struct Abi;
+
+impl ContractAbi for Abi {
+    type Operation = Operation;
+    type Response = ();
+}
+
+impl ServiceAbi for Abi {
+    type Query = AccountOwner;
+    type QueryResponse = Amount;
+}
+
+/// Operations that the contract can handle.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Operation {
+    /// Moves `amount` tokens of the signer to the same owner's account on the `destination`
+    /// chain.
+    Send {
+        destination: ChainId,
+        amount: Amount,
+    },
+}
+
+/// Messages that the contract sends to itself on other chains.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Message {
+    /// Credits `amount` tokens to the message's authenticated signer, or refunds them if the
+    /// message bounces.
+    Deposit { amount: Amount },
+}
@@ -0,0 +1,41 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use how_to_refund_bounced_messages::Abi;
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount, WithServiceAbi},
+    views::View,
+    ServiceRuntime,
+};
+
+use self::state::RefundState;
+
+pub struct Service {
+    state: RefundState,
+}
+
+linera_sdk::service!(Service);
+
+impl WithServiceAbi for Service {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Service for Service {
+    type Parameters = ();
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = RefundState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        Service { state }
+    }
+
+    /// Returns the balance of the requested owner on this chain.
+    async fn handle_query(&self, owner: AccountOwner) -> Amount {
+        self.state.balance(&owner).await
+    }
+}
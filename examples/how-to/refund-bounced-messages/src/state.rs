@@ -0,0 +1,47 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount},
+    views::{linera_views, MapView, RootView, ViewStorageContext},
+};
+
+/// The application state.
+#[derive(RootView)]
+#[view(context = ViewStorageContext)]
+pub struct RefundState {
+    /// The token balance of each owner on this chain.
+    pub balances: MapView<AccountOwner, Amount>,
+}
+
+#[allow(dead_code)]
+impl RefundState {
+    /// Returns the balance of the `owner`.
+    pub async fn balance(&self, owner: &AccountOwner) -> Amount {
+        self.balances
+            .get(owner)
+            .await
+            .expect("Failed to read balance")
+            .unwrap_or_default()
+    }
+
+    /// Credits `amount` tokens to the `owner`.
+    pub async fn credit(&mut self, owner: AccountOwner, amount: Amount) {
+        let balance = self.balance(&owner).await.saturating_add(amount);
+        self.balances
+            .insert(&owner, balance)
+            .expect("Failed to write balance");
+    }
+
+    /// Debits `amount` tokens from the `owner`, panicking if the balance is insufficient.
+    pub async fn debit(&mut self, owner: AccountOwner, amount: Amount) {
+        let balance = self
+            .balance(&owner)
+            .await
+            .try_sub(amount)
+            .expect("Insufficient balance");
+        self.balances
+            .insert(&owner, balance)
+            .expect("Failed to write balance");
+    }
+}
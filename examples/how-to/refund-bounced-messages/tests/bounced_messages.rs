@@ -0,0 +1,113 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests that send tokens to another chain, and refund them if the message bounces.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use how_to_refund_bounced_messages::{Abi, Operation};
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount, ApplicationId},
+    test::{ActiveChain, MessageAction, QueryOutcome, TestValidator},
+};
+
+/// Tests if tokens sent to another chain are credited to the same owner there.
+#[tokio::test(flavor = "multi_thread")]
+async fn delivered_tokens_are_credited_to_the_sender() {
+    let initial_balance = Amount::from_tokens(10);
+    let amount = Amount::from_tokens(3);
+
+    let (validator, module_id) = TestValidator::with_current_module::<Abi, (), Amount>().await;
+    let mut sender_chain = validator.new_chain().await;
+    let owner = AccountOwner::from(sender_chain.public_key());
+    let application_id = sender_chain
+        .create_application(module_id, (), initial_balance, vec![])
+        .await;
+
+    let receiver_chain = validator.new_chain().await;
+
+    sender_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Send {
+                    destination: receiver_chain.id(),
+                    amount,
+                },
+            );
+        })
+        .await;
+
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        balance(&sender_chain, application_id, owner).await,
+        initial_balance.saturating_sub(amount)
+    );
+    assert_eq!(
+        balance(&receiver_chain, application_id, owner).await,
+        amount
+    );
+}
+
+/// Tests if tokens sent to another chain are refunded to the sender if the receiver chain
+/// rejects the message.
+#[tokio::test(flavor = "multi_thread")]
+async fn bounced_tokens_are_refunded_to_the_sender() {
+    let initial_balance = Amount::from_tokens(10);
+    let amount = Amount::from_tokens(3);
+
+    let (validator, module_id) = TestValidator::with_current_module::<Abi, (), Amount>().await;
+    let mut sender_chain = validator.new_chain().await;
+    let owner = AccountOwner::from(sender_chain.public_key());
+    let application_id = sender_chain
+        .create_application(module_id, (), initial_balance, vec![])
+        .await;
+
+    let receiver_chain = validator.new_chain().await;
+
+    let certificate = sender_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Send {
+                    destination: receiver_chain.id(),
+                    amount,
+                },
+            );
+        })
+        .await;
+
+    assert_eq!(
+        balance(&sender_chain, application_id, owner).await,
+        initial_balance.saturating_sub(amount)
+    );
+
+    receiver_chain
+        .add_block(move |block| {
+            block.with_messages_from_by_action(&certificate, MessageAction::Reject);
+        })
+        .await;
+
+    assert_eq!(
+        balance(&receiver_chain, application_id, owner).await,
+        Amount::ZERO
+    );
+
+    sender_chain.handle_received_messages().await;
+
+    assert_eq!(
+        balance(&sender_chain, application_id, owner).await,
+        initial_balance
+    );
+}
+
+/// Queries the balance of the `owner` on the `chain`.
+async fn balance(
+    chain: &ActiveChain,
+    application_id: ApplicationId<Abi>,
+    owner: AccountOwner,
+) -> Amount {
+    let QueryOutcome { response, .. } = chain.query(application_id, owner).await;
+    response
+}
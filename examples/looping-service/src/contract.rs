@@ -0,0 +1,36 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+use linera_sdk::{linera_base_types::WithContractAbi, ContractRuntime};
+use looping_service::Abi;
+
+pub struct Contract;
+
+linera_sdk::contract!(Contract);
+
+impl WithContractAbi for Contract {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Contract for Contract {
+    type Message = ();
+    type InstantiationArgument = ();
+    type Parameters = ();
+    type EventValue = ();
+
+    async fn load(_runtime: ContractRuntime<Self>) -> Self {
+        Contract
+    }
+
+    async fn instantiate(&mut self, (): Self::InstantiationArgument) {}
+
+    async fn execute_operation(&mut self, (): Self::Operation) -> Self::Response {}
+
+    async fn execute_message(&mut self, (): Self::Message) {
+        panic!("This application doesn't support any cross-chain messages");
+    }
+
+    async fn store(self) {}
+}
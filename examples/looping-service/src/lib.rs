@@ -0,0 +1,29 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ABI of the Looping Service Example Application
+
+use linera_sdk::abi::{ContractAbi, ServiceAbi};
+use serde::{Deserialize, Serialize};
+
+/// The marker type that connects the types used to interface with the application.
+pub struct Abi;
+
+impl ContractAbi for Abi {
+    type Operation = ();
+    type Response = ();
+}
+
+impl ServiceAbi for Abi {
+    type Query = Query;
+    type QueryResponse = u64;
+}
+
+/// Queries that the service can handle.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Query {
+    /// Returns the sum of the numbers from 1 to the given one, computed in a loop.
+    SumUpTo(u64),
+    /// Loops forever, until the query is stopped.
+    Loop,
+}
@@ -0,0 +1,35 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+use std::hint::black_box;
+
+use linera_sdk::{linera_base_types::WithServiceAbi, ServiceRuntime};
+use looping_service::{Abi, Query};
+
+pub struct Service;
+
+linera_sdk::service!(Service);
+
+impl WithServiceAbi for Service {
+    type Abi = Abi;
+}
+
+impl linera_sdk::Service for Service {
+    type Parameters = ();
+
+    async fn new(_runtime: ServiceRuntime<Self>) -> Self {
+        Service
+    }
+
+    async fn handle_query(&self, query: Query) -> u64 {
+        match query {
+            // The values go through `black_box` so that the loop is not optimized away.
+            Query::SumUpTo(number) => (1..=number).map(black_box).sum(),
+            Query::Loop => loop {
+                black_box(());
+            },
+        }
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the queries of the looping service.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::test::{QueryOutcome, TestValidator};
+use looping_service::{Abi, Query};
+
+/// Tests if the service computes the sum in a loop.
+#[tokio::test(flavor = "multi_thread")]
+async fn sum_up_to() {
+    let (_validator, application_id, chain) =
+        TestValidator::with_current_application::<Abi, _, _>((), ()).await;
+
+    let QueryOutcome { response, .. } = chain.query(application_id, Query::SumUpTo(10)).await;
+
+    assert_eq!(response, 55);
+}
@@ -10,13 +10,9 @@ use linera_sdk::{
     views::{RootView, View},
     Contract, ContractRuntime,
 };
-use serde::{Deserialize, Serialize};
-use social::{Comment, Key, Operation, OwnPost, Post, SocialAbi};
+use social::{Comment, Event, Key, Operation, OwnPost, Post, SocialAbi, STREAM_NAME};
 use state::SocialState;
 
-/// The stream name the application uses for events about posts, likes and comments.
-const STREAM_NAME: &[u8] = b"posts";
-
 pub struct SocialContract {
     state: SocialState,
     runtime: ContractRuntime<Self>,
@@ -80,9 +76,9 @@ impl Contract for SocialContract {
                 self.runtime.application_id().forget_abi().into()
             );
             for index in update.new_indices() {
-                let event = self
-                    .runtime
-                    .read_event(update.chain_id, STREAM_NAME.into(), index);
+                let event: Event =
+                    self.runtime
+                        .read_event_value(update.chain_id, STREAM_NAME.into(), index);
                 match event {
                     Event::Post { post, index } => {
                         self.execute_post_event(update.chain_id, index, post);
@@ -184,13 +180,3 @@ impl SocialContract {
             .expect("Failed to insert received post");
     }
 }
-
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-pub enum Event {
-    /// A new post was created
-    Post { post: OwnPost, index: u32 },
-    /// A user liked a post
-    Like { key: Key },
-    /// A user commented on a post
-    Comment { key: Key, comment: String },
-}
@@ -41,6 +41,20 @@ pub enum Operation {
     Comment { key: Key, comment: String },
 }
 
+/// The stream name the application uses for events about posts, likes and comments.
+pub const STREAM_NAME: &[u8] = b"posts";
+
+/// An event emitted by the application into its [`STREAM_NAME`] stream.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    /// A new post was created
+    Post { post: OwnPost, index: u32 },
+    /// A user liked a post
+    Like { key: Key },
+    /// A user commented on a post
+    Comment { key: Key, comment: String },
+}
+
 /// A post's text and timestamp, to use in contexts where author and index are known.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct OwnPost {
@@ -5,8 +5,8 @@
 
 #![cfg(not(target_arch = "wasm32"))]
 
-use linera_sdk::test::{QueryOutcome, TestValidator};
-use social::Operation;
+use linera_sdk::test::{BlockOutcome, QueryOutcome, TestValidator};
+use social::{Event, Operation, STREAM_NAME};
 
 /// Test posting messages across microchains.
 ///
@@ -63,3 +63,59 @@ async fn test_cross_chain_posting() {
     let author = response["receivedPosts"]["keys"][0]["author"].clone();
     assert_eq!(author, chain2.id().to_string());
 }
+
+/// Test the events emitted by posting and liking.
+///
+/// Posts twice in one block, then likes the first post, and checks the events emitted into the
+/// posts stream by each block.
+#[tokio::test]
+async fn test_posting_emits_events() {
+    let (validator, module_id) =
+        TestValidator::with_current_module::<social::SocialAbi, (), ()>().await;
+    let mut chain = validator.new_chain().await;
+    let application_id = chain.create_application(module_id, (), (), vec![]).await;
+
+    let post_certificate = chain
+        .add_block(|block| {
+            for text in ["First post", "Second post"] {
+                block.with_operation(
+                    application_id,
+                    Operation::Post {
+                        text: text.to_string(),
+                        image_url: None,
+                    },
+                );
+            }
+        })
+        .await;
+
+    let events = post_certificate.events();
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|(event_application_id, stream, _, _)| *event_application_id
+            == application_id.forget_abi()
+            && stream.0 == STREAM_NAME));
+    let Event::Post { post, index } = post_certificate.expect_event(STREAM_NAME, 1) else {
+        panic!("Expected a post event");
+    };
+    assert_eq!(post.text, "Second post");
+    assert_eq!(index, 1);
+    post_certificate.assert_no_events("likes");
+
+    let key = social::Key {
+        timestamp: post.timestamp,
+        author: chain.id(),
+        index: 0,
+    };
+    let like_certificate = chain
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::Like { key: key.clone() });
+        })
+        .await;
+
+    assert_eq!(
+        like_certificate.expect_event::<Event>(STREAM_NAME, 2),
+        Event::Like { key }
+    );
+}
@@ -11,7 +11,16 @@ use serde::{de::DeserializeOwned, Serialize};
 // ANCHOR: abi
 /// A trait that includes all the types exported by a Linera application (both contract
 /// and service).
-pub trait Abi: ContractAbi + ServiceAbi {}
+pub trait Abi: ContractAbi + ServiceAbi {
+    /// Returns the registry of the types named in the contract and service ABIs, to
+    /// generate their BCS (de)serialization code in other languages.
+    #[cfg(feature = "schema")]
+    fn type_registry() -> Result<crate::schema::Registry, crate::schema::SchemaError> {
+        let mut tracer = crate::schema::TypeTracer::new()?;
+        tracer.trace_abi::<Self>()?;
+        tracer.registry()
+    }
+}
 // ANCHOR_END: abi
 
 // T::Parameters is duplicated for simplicity but it must match.
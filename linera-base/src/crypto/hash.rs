@@ -36,9 +36,14 @@ pub struct CryptoHash(B256);
 impl CryptoHash {
     /// Computes a hash.
     pub fn new<'de, T: BcsHashable<'de>>(value: &T) -> Self {
-        let mut hasher = Keccak256Ext(Keccak256::new());
-        value.write(&mut hasher);
-        CryptoHash(hasher.0.finalize())
+        let mut hasher = CryptoHash::hasher();
+        hasher.update_bcs(value);
+        hasher.finalize()
+    }
+
+    /// Returns a [`CryptoHasher`] to compute a hash incrementally.
+    pub fn hasher() -> CryptoHasher {
+        CryptoHasher(Keccak256::new())
     }
 
     /// Reads the bytes of the hash value.
@@ -60,10 +65,32 @@ impl CryptoHash {
     }
 }
 
-/// Temporary struct to extend `Keccak256` with `io::Write`.
-struct Keccak256Ext(Keccak256);
+/// Computes a [`CryptoHash`] from data provided in several parts, without having to
+/// concatenate them in memory first.
+pub struct CryptoHasher(Keccak256);
+
+impl CryptoHasher {
+    /// Adds `bytes` to the hashed data.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    /// Adds the BCS serialization of `value` to the hashed data, prefixed with its type name
+    /// as in [`CryptoHash::new`].
+    ///
+    /// The serialized bytes are written to the hasher directly instead of being collected
+    /// in a buffer.
+    pub fn update_bcs<'de, T: BcsHashable<'de>>(&mut self, value: &T) {
+        value.write(self);
+    }
+
+    /// Returns the hash of all the data added so far.
+    pub fn finalize(self) -> CryptoHash {
+        CryptoHash(self.0.finalize())
+    }
+}
 
-impl io::Write for Keccak256Ext {
+impl io::Write for CryptoHasher {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.0.update(buf);
         Ok(buf.len())
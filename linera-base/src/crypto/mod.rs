@@ -536,4 +536,24 @@ mod tests {
             Secp256k1KeyPair::generate().secret_key,
         ));
     }
+
+    #[test]
+    fn hasher_matches_one_shot_hash() {
+        let test_string = TestString::new("test");
+        let mut hasher = CryptoHash::hasher();
+        hasher.update_bcs(&test_string);
+        assert_eq!(hasher.finalize(), CryptoHash::new(&test_string));
+
+        // The hash is the Keccak256 of the type name and the BCS bytes, in any number of parts.
+        let mut bytes = b"TestString::".to_vec();
+        bytes.extend(bcs::to_bytes(&test_string).unwrap());
+        let expected = CryptoHash::try_from(&alloy_primitives::keccak256(&bytes)[..]).unwrap();
+        assert_eq!(CryptoHash::new(&test_string), expected);
+        for split in 0..=bytes.len() {
+            let mut hasher = CryptoHash::hasher();
+            hasher.update(&bytes[..split]);
+            hasher.update(&bytes[split..]);
+            assert_eq!(hasher.finalize(), expected);
+        }
+    }
 }
@@ -4,8 +4,6 @@
 
 //! Core data-types used in the Linera protocol.
 
-#[cfg(with_testing)]
-use std::ops;
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
 use std::{
@@ -15,10 +13,12 @@ use std::{
     hash::Hash,
     io, iter,
     num::ParseIntError,
+    ops,
     path::Path,
     str::FromStr,
 };
 
+use alloy_primitives::U256;
 use async_graphql::{InputObject, SimpleObject};
 use custom_debug_derive::Debug;
 use linera_witty::{WitLoad, WitStore, WitType};
@@ -35,7 +35,8 @@ use crate::{
     crypto::{BcsHashable, CryptoError, CryptoHash},
     doc_scalar, hex_debug, http,
     identifiers::{
-        ApplicationId, BlobId, BlobType, ChainId, EventId, GenericApplicationId, ModuleId, StreamId,
+        AccountOwner, ApplicationId, BlobId, BlobType, ChainId, EventId, GenericApplicationId,
+        ModuleId, StreamId,
     },
     limited_writer::{LimitedWriter, LimitedWriterError},
     ownership::ChainOwnership,
@@ -86,6 +87,9 @@ impl<'de> Deserialize<'de> for Amount {
 }
 
 /// A block height to identify blocks in a chain.
+///
+/// In JSON and other human-readable formats, it is serialized as a number. For compatibility
+/// with the clients that send it as a string, a string of decimal digits is accepted too.
 #[derive(
     Eq,
     PartialEq,
@@ -97,7 +101,6 @@ impl<'de> Deserialize<'de> for Amount {
     Default,
     Debug,
     Serialize,
-    Deserialize,
     WitType,
     WitLoad,
     WitStore,
@@ -105,9 +108,46 @@ impl<'de> Deserialize<'de> for Amount {
 #[cfg_attr(with_testing, derive(test_strategy::Arbitrary))]
 pub struct BlockHeight(pub u64);
 
+#[derive(Deserialize)]
+#[serde(rename = "BlockHeight")]
+struct BlockHeightU64(u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BlockHeightNumberOrString {
+    Number(u64),
+    String(String),
+}
+
+impl<'de> Deserialize<'de> for BlockHeight {
+    fn deserialize<D: serde::de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            match BlockHeightNumberOrString::deserialize(deserializer)? {
+                BlockHeightNumberOrString::Number(height) => Ok(BlockHeight(height)),
+                BlockHeightNumberOrString::String(s) => s.parse().map_err(serde::de::Error::custom),
+            }
+        } else {
+            Ok(BlockHeight(BlockHeightU64::deserialize(deserializer)?.0))
+        }
+    }
+}
+
 /// An identifier for successive attempts to decide a value in a consensus protocol.
 #[derive(
-    Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Default, Debug, Serialize, Deserialize,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Copy,
+    Clone,
+    Hash,
+    Default,
+    Debug,
+    Serialize,
+    Deserialize,
+    WitType,
+    WitLoad,
+    WitStore,
 )]
 pub enum Round {
     /// The initial fast round.
@@ -156,6 +196,21 @@ impl TimeDelta {
         TimeDelta(secs.saturating_mul(1_000_000))
     }
 
+    /// Returns the given number of minutes as a [`TimeDelta`], capped to [`TimeDelta::MAX`].
+    pub const fn from_mins(mins: u64) -> Self {
+        TimeDelta(mins.saturating_mul(60_000_000))
+    }
+
+    /// Returns the given number of hours as a [`TimeDelta`], capped to [`TimeDelta::MAX`].
+    pub const fn from_hours(hours: u64) -> Self {
+        TimeDelta(hours.saturating_mul(3_600_000_000))
+    }
+
+    /// Returns the given number of days as a [`TimeDelta`], capped to [`TimeDelta::MAX`].
+    pub const fn from_days(days: u64) -> Self {
+        TimeDelta(days.saturating_mul(86_400_000_000))
+    }
+
     /// Returns the given duration, rounded to the nearest microsecond and capped to the maximum
     /// [`TimeDelta`] value.
     pub fn from_duration(duration: Duration) -> Self {
@@ -216,6 +271,16 @@ impl Timestamp {
         TimeDelta::from_micros(self.0.saturating_sub(other.0))
     }
 
+    /// Returns the [`TimeDelta`] between `other` and `self`, or an error if `other` is later
+    /// than `self`.
+    pub fn try_delta_since(&self, other: Timestamp) -> Result<TimeDelta, ArithmeticError> {
+        let micros = self
+            .0
+            .checked_sub(other.0)
+            .ok_or(ArithmeticError::Underflow)?;
+        Ok(TimeDelta(micros))
+    }
+
     /// Returns the [`Duration`] between `other` and `self`, or zero if `other` is not
     /// earlier than `self`.
     pub const fn duration_since(&self, other: Timestamp) -> Duration {
@@ -232,6 +297,26 @@ impl Timestamp {
         Timestamp(self.0.saturating_sub(duration.0))
     }
 
+    /// Returns the timestamp that is `duration` later than `self`, or an error if it would
+    /// overflow.
+    pub fn try_add(&self, duration: TimeDelta) -> Result<Timestamp, ArithmeticError> {
+        let micros = self
+            .0
+            .checked_add(duration.0)
+            .ok_or(ArithmeticError::Overflow)?;
+        Ok(Timestamp(micros))
+    }
+
+    /// Returns the timestamp that is `duration` earlier than `self`, or an error if it would be
+    /// before the Unix epoch.
+    pub fn try_sub(&self, duration: TimeDelta) -> Result<Timestamp, ArithmeticError> {
+        let micros = self
+            .0
+            .checked_sub(duration.0)
+            .ok_or(ArithmeticError::Underflow)?;
+        Ok(Timestamp(micros))
+    }
+
     /// Returns a timestamp `micros` microseconds later than `self`, or the highest possible value
     /// if it would overflow.
     pub const fn saturating_add_micros(&self, micros: u64) -> Timestamp {
@@ -253,16 +338,77 @@ impl From<u64> for Timestamp {
 
 impl Display for Timestamp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(date_time) = chrono::DateTime::from_timestamp(
-            (self.0 / 1_000_000) as i64,
-            ((self.0 % 1_000_000) * 1_000) as u32,
-        ) {
+        if let Some(date_time) = self.to_date_time() {
             return date_time.naive_utc().fmt(f);
         }
         self.0.fmt(f)
     }
 }
 
+impl Timestamp {
+    /// Returns the UTC date and time of this timestamp, or `None` if it is too far in the
+    /// future to be represented.
+    fn to_date_time(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(
+            (self.0 / 1_000_000) as i64,
+            ((self.0 % 1_000_000) * 1_000) as u32,
+        )
+    }
+}
+
+/// Converts a date and time to a timestamp, truncated to the microsecond. Dates before the
+/// Unix epoch saturate to the epoch.
+impl From<chrono::DateTime<chrono::Utc>> for Timestamp {
+    fn from(date_time: chrono::DateTime<chrono::Utc>) -> Timestamp {
+        let secs = u64::try_from(date_time.timestamp()).unwrap_or(0);
+        let micros = if date_time.timestamp() < 0 {
+            0
+        } else {
+            u64::from(date_time.timestamp_subsec_micros())
+        };
+        Timestamp(
+            secs.checked_mul(1_000_000)
+                .and_then(|micros_of_secs| micros_of_secs.checked_add(micros))
+                .unwrap_or(u64::MAX),
+        )
+    }
+}
+
+/// Converts a timestamp to a date and time. Timestamps beyond the latest representable date
+/// saturate to [`chrono::DateTime::<chrono::Utc>::MAX_UTC`].
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    fn from(timestamp: Timestamp) -> chrono::DateTime<chrono::Utc> {
+        timestamp
+            .to_date_time()
+            .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC)
+    }
+}
+
+impl Display for TimeDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0 / 1_000_000;
+        let micros = self.0 % 1_000_000;
+        let (days, hours, mins, secs) = (
+            secs / 86_400,
+            (secs / 3_600) % 24,
+            (secs / 60) % 60,
+            secs % 60,
+        );
+        let mut parts = [(days, "d"), (hours, "h"), (mins, "m")]
+            .into_iter()
+            .filter(|(value, _)| *value > 0)
+            .map(|(value, unit)| format!("{value}{unit}"))
+            .collect::<Vec<_>>();
+        if micros > 0 {
+            let micros = format!("{micros:06}");
+            parts.push(format!("{secs}.{}s", micros.trim_end_matches('0')));
+        } else if secs > 0 || parts.is_empty() {
+            parts.push(format!("{secs}s"));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
 /// Resources that an application may spend during the execution of transaction or an
 /// application call.
 #[derive(
@@ -339,6 +485,34 @@ where
     }
 }
 
+/// Errors reported by the host when a service fails to query another application.
+#[derive(Clone, Copy, Debug, Error, WitStore, WitType)]
+pub enum ApplicationQueryError {
+    /// The queried application trapped while handling the query.
+    #[error("Queried application trapped while handling the query")]
+    Trapped,
+    /// The queried application is not registered on the current chain.
+    #[error("Queried application was not found on the current chain")]
+    NotFound,
+}
+
+/// Errors reported by the host when a contract fails to call another application.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq, WitStore, WitType)]
+pub enum CalleeError {
+    /// The called application trapped while handling the call. All its changes were rolled
+    /// back.
+    #[error("Called application trapped while handling the call")]
+    Trapped,
+}
+
+/// Errors reported by the host when a contract fails to create a data blob.
+#[derive(Clone, Copy, Debug, Error, WitStore, WitType)]
+pub enum CreateDataBlobError {
+    /// The blob content exceeds the maximum blob size allowed by the committee's policy.
+    #[error("Data blob exceeds the maximum blob size")]
+    TooLarge,
+}
+
 /// An error type for arithmetic errors.
 #[derive(Debug, Error)]
 #[allow(missing_docs)]
@@ -347,6 +521,8 @@ pub enum ArithmeticError {
     Overflow,
     #[error("Number underflow")]
     Underflow,
+    #[error("Division by zero")]
+    DivisionByZero,
 }
 
 macro_rules! impl_wrapped_number {
@@ -550,14 +726,50 @@ pub enum ParseAmountError {
     Parse,
     #[error("cannot represent amount: number too high")]
     TooHigh,
-    #[error("cannot represent amount: too many decimal places after the point")]
-    TooManyDigits,
+    #[error("cannot represent amount: more than {0} decimal places after the point")]
+    TooManyDigits(u8),
+    #[error("cannot parse amount: unknown unit {0:?}")]
+    UnknownUnit(String),
 }
 
 impl FromStr for Amount {
     type Err = ParseAmountError;
 
     fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Amount::parse_with_decimal_places(src, Amount::DECIMAL_PLACES)
+    }
+}
+
+impl Amount {
+    /// The units accepted by [`Amount::from_str_with_units`], with the number of decimal
+    /// places of the amounts they designate.
+    const UNITS: [(&'static str, u8); 5] = [
+        ("LINERA", Amount::DECIMAL_PLACES),
+        ("milli", Amount::DECIMAL_PLACES - 3),
+        ("micro", Amount::DECIMAL_PLACES - 6),
+        ("nano", Amount::DECIMAL_PLACES - 9),
+        ("atto", 0),
+    ];
+
+    /// Parses an amount followed by an optional unit, e.g. `"1.5 LINERA"` or `"1500 milli"`.
+    ///
+    /// Without a unit, the amount is in tokens, as with [`Amount::from_str`]. Amounts that
+    /// are not a whole number of attotokens are rejected.
+    pub fn from_str_with_units(src: &str) -> Result<Self, ParseAmountError> {
+        let src = src.trim();
+        let Some((number, unit)) = src.rsplit_once(char::is_whitespace) else {
+            return Amount::from_str(src);
+        };
+        let decimal_places = Amount::UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, decimal_places)| *decimal_places)
+            .ok_or_else(|| ParseAmountError::UnknownUnit(unit.to_owned()))?;
+        Amount::parse_with_decimal_places(number, decimal_places)
+    }
+
+    /// Parses a decimal number of units, each worth `10.pow(decimal_places)` attotokens.
+    fn parse_with_decimal_places(src: &str, decimal_places: u8) -> Result<Self, ParseAmountError> {
         let mut result: u128 = 0;
         let mut decimals: Option<u8> = None;
         let mut chars = src.trim().chars().peekable();
@@ -568,11 +780,13 @@ impl FromStr for Amount {
             match char {
                 '_' => {}
                 '.' if decimals.is_some() => return Err(ParseAmountError::Parse),
-                '.' => decimals = Some(Amount::DECIMAL_PLACES),
+                '.' => decimals = Some(decimal_places),
                 char => {
                     let digit = u128::from(char.to_digit(10).ok_or(ParseAmountError::Parse)?);
                     if let Some(d) = &mut decimals {
-                        *d = d.checked_sub(1).ok_or(ParseAmountError::TooManyDigits)?;
+                        *d = d
+                            .checked_sub(1)
+                            .ok_or(ParseAmountError::TooManyDigits(decimal_places))?;
                     }
                     result = result
                         .checked_mul(10)
@@ -582,7 +796,7 @@ impl FromStr for Amount {
             }
         }
         result = result
-            .checked_mul(10u128.pow(decimals.unwrap_or(Amount::DECIMAL_PLACES) as u32))
+            .checked_mul(10u128.pow(decimals.unwrap_or(decimal_places) as u32))
             .ok_or(ParseAmountError::TooHigh)?;
         Ok(Amount(result))
     }
@@ -602,6 +816,91 @@ impl FromStr for BlockHeight {
     }
 }
 
+impl BlockHeight {
+    /// Returns the height `count` blocks after this one, or an error if it would overflow.
+    pub fn try_add_blocks(self, count: u64) -> Result<Self, ArithmeticError> {
+        let height = self.0.checked_add(count).ok_or(ArithmeticError::Overflow)?;
+        Ok(BlockHeight(height))
+    }
+
+    /// Returns the height `count` blocks before this one, or an error if it would underflow.
+    pub fn try_sub_blocks(self, count: u64) -> Result<Self, ArithmeticError> {
+        let height = self
+            .0
+            .checked_sub(count)
+            .ok_or(ArithmeticError::Underflow)?;
+        Ok(BlockHeight(height))
+    }
+
+    /// Returns the height `count` blocks after this one, or [`BlockHeight::MAX`] if it would
+    /// overflow.
+    pub const fn saturating_add_blocks(self, count: u64) -> Self {
+        BlockHeight(self.0.saturating_add(count))
+    }
+
+    /// Returns the height `count` blocks before this one, or [`BlockHeight::ZERO`] if it would
+    /// underflow.
+    pub const fn saturating_sub_blocks(self, count: u64) -> Self {
+        BlockHeight(self.0.saturating_sub(count))
+    }
+
+    /// Returns an iterator over the heights in `range`, in increasing order.
+    ///
+    /// Since [`std::iter::Step`] is not stable yet, this is the way to iterate over a range of
+    /// heights, e.g. `BlockHeight::range(start..end)`.
+    pub fn range(
+        range: impl ops::RangeBounds<BlockHeight>,
+    ) -> impl DoubleEndedIterator<Item = BlockHeight> {
+        let start = match range.start_bound() {
+            ops::Bound::Included(height) => Some(height.0),
+            ops::Bound::Excluded(height) => height.0.checked_add(1),
+            ops::Bound::Unbounded => Some(0),
+        };
+        let end = match range.end_bound() {
+            ops::Bound::Included(height) => Some(height.0),
+            ops::Bound::Excluded(height) => height.0.checked_sub(1),
+            ops::Bound::Unbounded => Some(u64::MAX),
+        };
+        let heights = match (start, end) {
+            (Some(start), Some(end)) => start..=end,
+            _ => 1..=0,
+        };
+        heights.map(BlockHeight)
+    }
+}
+
+/// Adds a number of blocks to a height. This panics on overflow in debug builds, like
+/// integer addition.
+impl ops::Add<u64> for BlockHeight {
+    type Output = Self;
+
+    fn add(self, count: u64) -> Self {
+        BlockHeight(self.0 + count)
+    }
+}
+
+impl ops::AddAssign<u64> for BlockHeight {
+    fn add_assign(&mut self, count: u64) {
+        self.0 += count;
+    }
+}
+
+/// Subtracts a number of blocks from a height. This panics on underflow in debug builds, like
+/// integer subtraction.
+impl ops::Sub<u64> for BlockHeight {
+    type Output = Self;
+
+    fn sub(self, count: u64) -> Self {
+        BlockHeight(self.0 - count)
+    }
+}
+
+impl ops::SubAssign<u64> for BlockHeight {
+    fn sub_assign(&mut self, count: u64) {
+        self.0 -= count;
+    }
+}
+
 impl Display for Round {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -657,6 +956,12 @@ impl<'a> iter::Sum<&'a Amount> for Amount {
     }
 }
 
+impl iter::Sum for Amount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Self::saturating_add)
+    }
+}
+
 impl Amount {
     /// The base-10 exponent representing how much a token can be divided.
     pub const DECIMAL_PLACES: u8 = 18;
@@ -689,6 +994,56 @@ impl Amount {
         Amount(attotokens)
     }
 
+    /// Returns the number of attotokens in this amount.
+    pub const fn to_attos(self) -> u128 {
+        self.0
+    }
+
+    /// Formats the amount in tokens with `precision` digits after the decimal point.
+    ///
+    /// Further digits are truncated. If `trim_trailing_zeros` is set, the zeros at the end of
+    /// the fractional part are removed, together with the point if no digits remain.
+    pub fn format_with(self, precision: usize, trim_trailing_zeros: bool) -> String {
+        self.format_with_separator(precision, trim_trailing_zeros, None)
+    }
+
+    /// Formats the amount like [`Amount::format_with`], inserting `separator` between each
+    /// group of three digits of the integer part, if any.
+    pub fn format_with_separator(
+        self,
+        precision: usize,
+        trim_trailing_zeros: bool,
+        separator: Option<char>,
+    ) -> String {
+        let places = Amount::DECIMAL_PLACES as usize;
+        let divisor = Amount::ONE.0;
+        let integer_digits = (self.0 / divisor).to_string();
+        let fractional_digits = format!("{:0places$}", self.0 % divisor);
+        let mut fractional_part = if precision <= places {
+            fractional_digits[..precision].to_owned()
+        } else {
+            format!("{fractional_digits:0<precision$}")
+        };
+        if trim_trailing_zeros {
+            fractional_part.truncate(fractional_part.trim_end_matches('0').len());
+        }
+
+        let mut result = String::new();
+        for (i, digit) in integer_digits.chars().enumerate() {
+            if let Some(separator) = separator {
+                if i > 0 && (integer_digits.len() - i) % 3 == 0 {
+                    result.push(separator);
+                }
+            }
+            result.push(digit);
+        }
+        if !fractional_part.is_empty() {
+            result.push('.');
+            result.push_str(&fractional_part);
+        }
+        result
+    }
+
     /// Helper function to obtain the 64 most significant bits of the balance.
     pub const fn upper_half(self) -> u64 {
         (self.0 >> 64) as u64
@@ -704,6 +1059,38 @@ impl Amount {
         self.0.checked_div(other.0).unwrap_or(u128::MAX)
     }
 
+    /// Checked division by an integer, rounding down.
+    pub fn try_div(self, divisor: u128) -> Result<Amount, ArithmeticError> {
+        let val = self
+            .0
+            .checked_div(divisor)
+            .ok_or(ArithmeticError::DivisionByZero)?;
+        Ok(Amount(val))
+    }
+
+    /// Returns this amount multiplied by `numerator / denominator`, rounding down.
+    ///
+    /// The intermediate product is computed with 256 bits, so this only fails if the
+    /// `denominator` is 0 or if the result doesn't fit in an `Amount`.
+    pub fn try_mul_ratio(
+        self,
+        numerator: u128,
+        denominator: u128,
+    ) -> Result<Amount, ArithmeticError> {
+        if denominator == 0 {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        let product = U256::from(self.0) * U256::from(numerator);
+        let quotient = product / U256::from(denominator);
+        let val = u128::try_from(quotient).map_err(|_| ArithmeticError::Overflow)?;
+        Ok(Amount(val))
+    }
+
+    /// Returns the given number of thousandths of this amount, rounding down.
+    pub fn try_mul_per_mille(self, per_mille: u128) -> Result<Amount, ArithmeticError> {
+        self.try_mul_ratio(per_mille, 1000)
+    }
+
     /// Returns whether this amount is 0.
     pub fn is_zero(&self) -> bool {
         *self == Amount::ZERO
@@ -735,7 +1122,9 @@ impl ChainOrigin {
 }
 
 /// A number identifying the configuration of the chain (aka the committee).
-#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Default, Debug)]
+#[derive(
+    Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Default, Debug, WitType, WitLoad, WitStore,
+)]
 pub struct Epoch(pub u32);
 
 impl Epoch {
@@ -812,6 +1201,33 @@ impl Epoch {
     }
 }
 
+/// A summary of the committee of validators in charge of a chain's current epoch.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, WitLoad, WitStore, WitType,
+)]
+pub struct CommitteeSummary {
+    /// The number of validators in the committee.
+    pub validator_count: u32,
+    /// The sum of all validators' voting rights.
+    pub total_votes: u64,
+    /// The voting rights needed to form a quorum.
+    pub quorum_threshold: u64,
+}
+
+/// A summary of the header of a confirmed block, as seen by applications.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, WitLoad, WitStore, WitType)]
+pub struct BlockHeaderSummary {
+    /// The block height.
+    pub height: BlockHeight,
+    /// The timestamp when the block was created.
+    pub timestamp: Timestamp,
+    /// The epoch the block was created in.
+    pub epoch: Epoch,
+    /// The user who signed the block, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub authenticated_signer: Option<AccountOwner>,
+}
+
 /// The initial configuration for a new chain.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct InitialChainConfig {
@@ -991,6 +1407,10 @@ pub enum OracleResponse {
     Round(Option<u32>),
     /// An event was read.
     Event(EventId, Vec<u8>),
+    /// The consensus round in which the block was validated, if known.
+    ConsensusRound(Option<Round>),
+    /// A blob whose existence was checked, and that didn't exist.
+    MissingBlob(BlobId),
 }
 
 impl BcsHashable<'_> for OracleResponse {}
@@ -1041,6 +1461,34 @@ impl ApplicationDescription {
     pub fn service_bytecode_blob_id(&self) -> BlobId {
         self.module_id.service_bytecode_blob_id()
     }
+
+    /// Returns the parts of this description that are exposed to applications.
+    pub fn summary(&self) -> ApplicationDescriptionSummary {
+        ApplicationDescriptionSummary {
+            application_id: ApplicationId::from(self),
+            contract_blob_hash: self.module_id.contract_blob_hash,
+            service_blob_hash: self.module_id.service_blob_hash,
+            creator_chain_id: self.creator_chain_id,
+            parameters: self.parameters.clone(),
+        }
+    }
+}
+
+/// A summary of a user application's description, as seen by applications.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, WitLoad, WitStore, WitType)]
+pub struct ApplicationDescriptionSummary {
+    /// The ID of the application.
+    pub application_id: ApplicationId,
+    /// The hash of the blob containing the contract bytecode.
+    pub contract_blob_hash: CryptoHash,
+    /// The hash of the blob containing the service bytecode.
+    pub service_blob_hash: CryptoHash,
+    /// The chain that created the application.
+    pub creator_chain_id: ChainId,
+    /// The parameters of the application.
+    #[serde(with = "serde_bytes")]
+    #[debug(with = "hex_debug")]
+    pub parameters: Vec<u8>,
 }
 
 /// A WebAssembly module's bytecode.
@@ -1100,27 +1548,68 @@ pub struct CompressedBytecode {
     pub compressed_bytes: Vec<u8>,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
 impl CompressedBytecode {
+    /// The header that compressed bytes start with: the magic number of a Zstandard frame.
+    ///
+    /// Bytes without this header are loaded as uncompressed bytecode. Wasm modules can't be
+    /// mistaken for compressed ones, since they start with `\0asm`. EVM bytecode could start
+    /// with the header, as `0x28` is the `PUSH9` opcode, but compilers emit a different
+    /// prologue, e.g. `PUSH1 0x80` for Solidity. Uncompressed bytecode that happens to start
+    /// with the header fails to load as invalid compressed bytecode, so it has to be
+    /// published compressed.
+    pub const COMPRESSED_HEADER: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+    /// Returns `true` if the `bytes` are compressed, i.e. start with
+    /// [`CompressedBytecode::COMPRESSED_HEADER`].
+    pub fn is_compressed(bytes: &[u8]) -> bool {
+        bytes.starts_with(&Self::COMPRESSED_HEADER)
+    }
+
     /// Returns `true` if the decompressed size does not exceed the limit.
     pub fn decompressed_size_at_most(
         compressed_bytes: &[u8],
         limit: u64,
     ) -> Result<bool, DecompressionError> {
-        let mut decoder = zstd::stream::Decoder::new(compressed_bytes)?;
+        Ok(Self::decompressed_size(compressed_bytes, limit)?.is_some())
+    }
+
+    /// Returns the decompressed size of the `compressed_bytes`, or `None` if it exceeds the
+    /// limit.
+    pub fn decompressed_size(
+        compressed_bytes: &[u8],
+        limit: u64,
+    ) -> Result<Option<u64>, DecompressionError> {
+        if !Self::is_compressed(compressed_bytes) {
+            let size = compressed_bytes.len() as u64;
+            return Ok((size <= limit).then_some(size));
+        }
         let limit = usize::try_from(limit).unwrap_or(usize::MAX);
         let mut writer = LimitedWriter::new(io::sink(), limit);
-        match io::copy(&mut decoder, &mut writer) {
-            Ok(_) => Ok(true),
+        match Self::decode_into(compressed_bytes, &mut writer) {
+            Ok(size) => Ok(Some(size)),
             Err(error) => {
                 error.downcast::<LimitedWriterError>()?;
-                Ok(false)
+                Ok(None)
             }
         }
     }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CompressedBytecode {
+    /// Decodes the `compressed_bytes` into the `writer`, and returns the decompressed size.
+    fn decode_into(compressed_bytes: &[u8], writer: &mut impl io::Write) -> io::Result<u64> {
+        let mut decoder = zstd::stream::Decoder::new(compressed_bytes)?;
+        io::copy(&mut decoder, writer)
+    }
 
     /// Decompresses a [`CompressedBytecode`] into a [`Bytecode`].
+    ///
+    /// Uncompressed bytes are returned unchanged.
     pub fn decompress(&self) -> Result<Bytecode, DecompressionError> {
+        if !Self::is_compressed(&self.compressed_bytes) {
+            return Ok(Bytecode::new(self.compressed_bytes.clone()));
+        }
         #[cfg(with_metrics)]
         let _decompression_latency = BYTECODE_DECOMPRESSION_LATENCY.measure_latency();
         let bytes = zstd::stream::decode_all(&*self.compressed_bytes)?;
@@ -1131,30 +1620,25 @@ impl CompressedBytecode {
 
 #[cfg(target_arch = "wasm32")]
 impl CompressedBytecode {
-    /// Returns `true` if the decompressed size does not exceed the limit.
-    pub fn decompressed_size_at_most(
-        compressed_bytes: &[u8],
-        limit: u64,
-    ) -> Result<bool, DecompressionError> {
-        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
-        let mut writer = LimitedWriter::new(io::sink(), limit);
+    /// Decodes the `compressed_bytes` into the `writer`, and returns the decompressed size.
+    fn decode_into(compressed_bytes: &[u8], writer: &mut impl io::Write) -> io::Result<u64> {
         let mut decoder = ruzstd::streaming_decoder::StreamingDecoder::new(compressed_bytes)
             .map_err(io::Error::other)?;
 
         // TODO(#2710): Decode multiple frames, if present
-        match io::copy(&mut decoder, &mut writer) {
-            Ok(_) => Ok(true),
-            Err(error) => {
-                error.downcast::<LimitedWriterError>()?;
-                Ok(false)
-            }
-        }
+        io::copy(&mut decoder, writer)
     }
 
     /// Decompresses a [`CompressedBytecode`] into a [`Bytecode`].
+    ///
+    /// Uncompressed bytes are returned unchanged.
     pub fn decompress(&self) -> Result<Bytecode, DecompressionError> {
         use ruzstd::{io::Read, streaming_decoder::StreamingDecoder};
 
+        if !Self::is_compressed(&self.compressed_bytes) {
+            return Ok(Bytecode::new(self.compressed_bytes.clone()));
+        }
+
         #[cfg(with_metrics)]
         let _decompression_latency = BYTECODE_DECOMPRESSION_LATENCY.measure_latency();
 
@@ -1198,6 +1682,17 @@ impl BlobContent {
         BlobContent::new(BlobType::Data, bytes)
     }
 
+    /// Returns the hash of the data [`BlobContent`] with the provided bytes, without copying
+    /// them.
+    pub fn hash_data(bytes: &[u8]) -> CryptoHash {
+        let mut hasher = CryptoHash::hasher();
+        hasher.update_bcs(&BlobContentRef {
+            blob_type: BlobType::Data,
+            bytes,
+        });
+        hasher.finalize()
+    }
+
     /// Creates a new contract bytecode [`BlobContent`] from the provided bytes.
     pub fn new_contract_bytecode(compressed_bytecode: CompressedBytecode) -> Self {
         BlobContent::new(
@@ -1253,6 +1748,17 @@ impl BlobContent {
     }
 }
 
+/// A [`BlobContent`] borrowing its bytes, with the same serialization and hash.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "BlobContent")]
+struct BlobContentRef<'a> {
+    blob_type: BlobType,
+    #[serde(borrow, with = "serde_bytes")]
+    bytes: &'a [u8],
+}
+
+impl<'a> BcsHashable<'a> for BlobContentRef<'a> {}
+
 impl From<Blob> for BlobContent {
     fn from(blob: Blob) -> BlobContent {
         blob.content
@@ -1497,9 +2003,17 @@ static BYTECODE_DECOMPRESSION_LATENCY: LazyLock<HistogramVec> = LazyLock::new(||
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{collections::BTreeMap, str::FromStr};
 
-    use super::Amount;
+    use assert_matches::assert_matches;
+    use num_bigint::BigUint;
+    use test_strategy::proptest;
+
+    use super::{
+        Amount, ArithmeticError, Blob, BlobContent, BlockHeight, Bytecode, CompressedBytecode,
+        ParseAmountError, TimeDelta, Timestamp,
+    };
+    use crate::crypto::CryptoHash;
 
     #[test]
     fn display_amount() {
@@ -1526,4 +2040,375 @@ mod tests {
             format!("{:~^+9.1}", Amount::from_str("12.34").unwrap())
         );
     }
+
+    #[test]
+    fn amount_division() {
+        let amount = Amount::from_attos(10);
+        assert_eq!(amount.try_div(3).unwrap(), Amount::from_attos(3));
+        assert_matches!(amount.try_div(0), Err(ArithmeticError::DivisionByZero));
+        assert_matches!(
+            amount.try_mul_ratio(2, 0),
+            Err(ArithmeticError::DivisionByZero)
+        );
+        assert_eq!(
+            Amount::MAX.try_mul_ratio(u128::MAX, u128::MAX).unwrap(),
+            Amount::MAX
+        );
+        assert_matches!(
+            Amount::MAX.try_mul_ratio(3, 2),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            Amount::from_tokens(2).try_mul_per_mille(15).unwrap(),
+            Amount::from_millis(30)
+        );
+    }
+
+    #[test]
+    fn amount_sum() {
+        let amounts = [Amount::from_tokens(1), Amount::from_tokens(2)];
+        assert_eq!(amounts.iter().sum::<Amount>(), Amount::from_tokens(3));
+        assert_eq!(amounts.into_iter().sum::<Amount>(), Amount::from_tokens(3));
+        assert_eq!(
+            [Amount::MAX, Amount::ONE].into_iter().sum::<Amount>(),
+            Amount::MAX
+        );
+    }
+
+    #[proptest]
+    fn mul_ratio_matches_big_integers(attos: u128, numerator: u128, denominator: u128) {
+        let result = Amount::from_attos(attos).try_mul_ratio(numerator, denominator);
+        if denominator == 0 {
+            assert_matches!(result, Err(ArithmeticError::DivisionByZero));
+        } else {
+            let expected =
+                BigUint::from(attos) * BigUint::from(numerator) / BigUint::from(denominator);
+            match u128::try_from(expected) {
+                Ok(expected) => assert_eq!(result.unwrap(), Amount::from_attos(expected)),
+                Err(_) => assert_matches!(result, Err(ArithmeticError::Overflow)),
+            }
+        }
+    }
+
+    #[proptest]
+    fn mul_ratio_below_one_never_overflows(attos: u128, numerator: u128, denominator: u128) {
+        let (numerator, denominator) = if numerator <= denominator {
+            (numerator, denominator.max(1))
+        } else {
+            (denominator, numerator)
+        };
+        let result = Amount::from_attos(attos)
+            .try_mul_ratio(numerator, denominator)
+            .unwrap();
+        assert!(result <= Amount::from_attos(attos));
+    }
+
+    #[test]
+    fn parse_amount_with_units() {
+        assert_eq!(
+            Amount::from_str_with_units("1.5 LINERA").unwrap(),
+            Amount::from_millis(1500)
+        );
+        assert_eq!(
+            Amount::from_str_with_units("1500 milli").unwrap(),
+            Amount::from_millis(1500)
+        );
+        assert_eq!(
+            Amount::from_str_with_units("2.5 micro").unwrap(),
+            Amount::from_nanos(2500)
+        );
+        assert_eq!(
+            Amount::from_str_with_units("7 atto").unwrap(),
+            Amount::from_attos(7)
+        );
+        assert_eq!(
+            Amount::from_str_with_units("1.5").unwrap(),
+            Amount::from_millis(1500)
+        );
+        assert_matches!(
+            Amount::from_str_with_units("1.5 atto"),
+            Err(ParseAmountError::TooManyDigits(0))
+        );
+        assert_matches!(
+            Amount::from_str_with_units("1 mega"),
+            Err(ParseAmountError::UnknownUnit(unit)) if unit == "mega"
+        );
+    }
+
+    #[test]
+    fn parse_amount_rejects_too_many_decimal_places() {
+        assert_eq!(
+            Amount::from_str("0.000000000000000001").unwrap(),
+            Amount::from_attos(1)
+        );
+        let error = Amount::from_str("0.0000000000000000001").unwrap_err();
+        assert_matches!(error, ParseAmountError::TooManyDigits(18));
+        assert_eq!(
+            error.to_string(),
+            "cannot represent amount: more than 18 decimal places after the point"
+        );
+    }
+
+    #[test]
+    fn format_amount_with_precision() {
+        let amount = Amount::from_str("1234567.891").unwrap();
+        assert_eq!(amount.format_with(2, false), "1234567.89");
+        assert_eq!(amount.format_with(5, false), "1234567.89100");
+        assert_eq!(amount.format_with(5, true), "1234567.891");
+        assert_eq!(amount.format_with(0, false), "1234567");
+        assert_eq!(
+            amount.format_with(20, false),
+            "1234567.89100000000000000000"
+        );
+        assert_eq!(Amount::ONE.format_with(3, true), "1");
+        assert_eq!(Amount::ZERO.format_with(2, false), "0.00");
+        assert_eq!(
+            amount.format_with_separator(1, false, Some(',')),
+            "1,234,567.8"
+        );
+        assert_eq!(
+            Amount::from_tokens(123).format_with_separator(0, false, Some(',')),
+            "123"
+        );
+    }
+
+    #[proptest]
+    fn amount_format_and_parse_round_trip(attos: u128, trim_trailing_zeros: bool) {
+        let amount = Amount::from_attos(attos);
+        assert_eq!(amount.to_attos(), attos);
+        assert_eq!(Amount::from_str(&amount.to_string()).unwrap(), amount);
+        let places = Amount::DECIMAL_PLACES as usize;
+        let formatted = amount.format_with(places, trim_trailing_zeros);
+        assert_eq!(Amount::from_str(&formatted).unwrap(), amount);
+        let formatted = amount.format_with_separator(places, trim_trailing_zeros, Some('_'));
+        assert_eq!(Amount::from_str(&formatted).unwrap(), amount);
+        let formatted = format!("{} LINERA", amount.format_with(places, true));
+        assert_eq!(Amount::from_str_with_units(&formatted).unwrap(), amount);
+        let formatted = format!("{attos} atto");
+        assert_eq!(Amount::from_str_with_units(&formatted).unwrap(), amount);
+    }
+
+    #[proptest]
+    fn amount_format_truncates(attos: u128, #[strategy(0usize..=18)] precision: usize) {
+        let amount = Amount::from_attos(attos);
+        let truncated = Amount::from_str(&amount.format_with(precision, false)).unwrap();
+        let unit = 10u128.pow(Amount::DECIMAL_PLACES as u32 - precision as u32);
+        assert_eq!(truncated.to_attos(), attos - attos % unit);
+    }
+
+    #[test]
+    fn timestamp_arithmetic() {
+        let timestamp = Timestamp::from(1_000);
+        let delta = TimeDelta::from_micros(300);
+        assert_eq!(timestamp.try_add(delta).unwrap(), Timestamp::from(1_300));
+        assert_eq!(timestamp.try_sub(delta).unwrap(), Timestamp::from(700));
+        assert_eq!(
+            timestamp.try_delta_since(Timestamp::from(700)).unwrap(),
+            delta
+        );
+        assert_matches!(
+            Timestamp::from(700).try_delta_since(timestamp),
+            Err(ArithmeticError::Underflow)
+        );
+        assert_eq!(Timestamp::from(700).delta_since(timestamp), TimeDelta::ZERO);
+
+        // At the boundaries, the checked operations fail and the saturating ones saturate.
+        let max = Timestamp::from(u64::MAX);
+        assert_matches!(max.try_add(delta), Err(ArithmeticError::Overflow));
+        assert_eq!(max.saturating_add(delta), max);
+        let min = Timestamp::from(0);
+        assert_matches!(min.try_sub(delta), Err(ArithmeticError::Underflow));
+        assert_eq!(min.saturating_sub(delta), min);
+    }
+
+    #[test]
+    fn time_delta_units() {
+        assert_eq!(TimeDelta::from_mins(2), TimeDelta::from_secs(120));
+        assert_eq!(TimeDelta::from_hours(3), TimeDelta::from_mins(180));
+        assert_eq!(TimeDelta::from_days(1), TimeDelta::from_hours(24));
+        assert_eq!(TimeDelta::from_days(u64::MAX), TimeDelta::MAX);
+        assert_eq!(TimeDelta::from_hours(u64::MAX / 1_000), TimeDelta::MAX);
+    }
+
+    #[test]
+    fn display_time_delta() {
+        assert_eq!(TimeDelta::ZERO.to_string(), "0s");
+        assert_eq!(TimeDelta::from_micros(1_500_000).to_string(), "1.5s");
+        assert_eq!(TimeDelta::from_micros(1).to_string(), "0.000001s");
+        assert_eq!(TimeDelta::from_hours(25).to_string(), "1d 1h");
+        assert_eq!(TimeDelta::from_secs(90_061).to_string(), "1d 1h 1m 1s");
+    }
+
+    #[test]
+    fn timestamp_chrono_conversions() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let date_time = date
+            .and_hms_nano_opt(7, 8, 9, 123_456_789)
+            .unwrap()
+            .and_utc();
+        let timestamp = Timestamp::from(date_time);
+        assert_eq!(timestamp.micros(), 1_714_979_289_123_456);
+        assert_eq!(timestamp.to_string(), "2024-05-06 07:08:09.123456");
+        assert_eq!(
+            chrono::DateTime::<chrono::Utc>::from(timestamp),
+            date.and_hms_micro_opt(7, 8, 9, 123_456).unwrap().and_utc()
+        );
+
+        let before_epoch = chrono::DateTime::from_timestamp(-1, 500_000_000).unwrap();
+        assert_eq!(Timestamp::from(before_epoch), Timestamp::from(0));
+        assert_eq!(
+            chrono::DateTime::<chrono::Utc>::from(Timestamp::from(u64::MAX)),
+            chrono::DateTime::<chrono::Utc>::MAX_UTC
+        );
+    }
+
+    #[proptest]
+    fn timestamp_chrono_round_trip(micros: u64) {
+        let timestamp = Timestamp::from(micros);
+        let date_time = chrono::DateTime::<chrono::Utc>::from(timestamp);
+        if date_time != chrono::DateTime::<chrono::Utc>::MAX_UTC {
+            assert_eq!(Timestamp::from(date_time), timestamp);
+        }
+    }
+
+    #[proptest]
+    fn hash_data_matches_blob_hash(bytes: Vec<u8>) {
+        assert_eq!(
+            BlobContent::hash_data(&bytes),
+            Blob::new_data(bytes.clone()).id().hash
+        );
+        assert_eq!(
+            BlobContent::hash_data(&bytes),
+            CryptoHash::new(&BlobContent::new_data(bytes))
+        );
+    }
+
+    #[test]
+    fn block_height_arithmetic() {
+        let height = BlockHeight(5);
+        assert_eq!(height + 2, BlockHeight(7));
+        assert_eq!(height - 2, BlockHeight(3));
+        let mut other = height;
+        other += 3;
+        other -= 1;
+        assert_eq!(other, BlockHeight(7));
+
+        assert_eq!(height.try_add_blocks(2).unwrap(), BlockHeight(7));
+        assert_eq!(height.try_sub_blocks(5).unwrap(), BlockHeight::ZERO);
+        assert_matches!(
+            BlockHeight::MAX.try_add_blocks(1),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_matches!(height.try_sub_blocks(6), Err(ArithmeticError::Underflow));
+        assert_eq!(BlockHeight::MAX.saturating_add_blocks(1), BlockHeight::MAX);
+        assert_eq!(height.saturating_sub_blocks(6), BlockHeight::ZERO);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn block_height_addition_overflow_panics() {
+        let _ = BlockHeight::MAX + 1;
+    }
+
+    #[test]
+    fn block_height_ranges() {
+        let heights = |range| BlockHeight::range(range).collect::<Vec<_>>();
+        assert_eq!(
+            heights(BlockHeight(2)..BlockHeight(5)),
+            [BlockHeight(2), BlockHeight(3), BlockHeight(4)]
+        );
+        assert_eq!(
+            BlockHeight::range(BlockHeight(2)..=BlockHeight(4))
+                .rev()
+                .collect::<Vec<_>>(),
+            [BlockHeight(4), BlockHeight(3), BlockHeight(2)]
+        );
+        assert!(heights(BlockHeight(5)..BlockHeight(5)).is_empty());
+        assert!(heights(BlockHeight(6)..BlockHeight(5)).is_empty());
+        assert!(BlockHeight::range(..BlockHeight::ZERO).next().is_none());
+        assert_eq!(
+            BlockHeight::range(BlockHeight(u64::MAX - 1)..).collect::<Vec<_>>(),
+            [BlockHeight(u64::MAX - 1), BlockHeight::MAX]
+        );
+        assert_eq!(
+            BlockHeight(3).max(BlockHeight(7)).min(BlockHeight(5)),
+            BlockHeight(5)
+        );
+    }
+
+    #[test]
+    fn block_height_json_is_a_number() {
+        assert_eq!(serde_json::to_string(&BlockHeight(42)).unwrap(), "42");
+        assert_eq!(
+            serde_json::from_str::<BlockHeight>("42").unwrap(),
+            BlockHeight(42)
+        );
+        // Heights sent as strings are still accepted.
+        assert_eq!(
+            serde_json::from_str::<BlockHeight>("\"42\"").unwrap(),
+            BlockHeight(42)
+        );
+        assert!(serde_json::from_str::<BlockHeight>("\"forty-two\"").is_err());
+        let heights =
+            serde_json::from_str::<BTreeMap<BlockHeight, u8>>(r#"{"1": 2, "3": 4}"#).unwrap();
+        assert_eq!(
+            heights,
+            BTreeMap::from([(BlockHeight(1), 2), (BlockHeight(3), 4)])
+        );
+        assert_eq!(
+            bcs::from_bytes::<BlockHeight>(&bcs::to_bytes(&BlockHeight(42)).unwrap()).unwrap(),
+            BlockHeight(42)
+        );
+    }
+
+    #[test]
+    fn compressed_bytecode_round_trip() {
+        let bytecode = Bytecode::new(b"\0asm".repeat(1_000));
+        let compressed = bytecode.compress();
+        let bytes = &compressed.compressed_bytes;
+
+        assert!(CompressedBytecode::is_compressed(bytes));
+        assert!(bytes.len() < bytecode.bytes.len());
+        assert_eq!(compressed.decompress().unwrap(), bytecode);
+        assert_eq!(
+            CompressedBytecode::decompressed_size(bytes, 4_000).unwrap(),
+            Some(4_000)
+        );
+        assert_eq!(
+            CompressedBytecode::decompressed_size(bytes, 3_999).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn uncompressed_bytecode_is_loaded_unchanged() {
+        let bytes = b"\0asm\x01\0\0\0".to_vec();
+        let uncompressed = CompressedBytecode {
+            compressed_bytes: bytes.clone(),
+        };
+
+        assert!(!CompressedBytecode::is_compressed(&bytes));
+        assert_eq!(
+            uncompressed.decompress().unwrap(),
+            Bytecode::new(bytes.clone())
+        );
+        assert_eq!(
+            CompressedBytecode::decompressed_size(&bytes, 8).unwrap(),
+            Some(8)
+        );
+        assert!(!CompressedBytecode::decompressed_size_at_most(&bytes, 7).unwrap());
+    }
+
+    #[test]
+    fn invalid_compressed_bytecode_is_rejected() {
+        let mut bytes = CompressedBytecode::COMPRESSED_HEADER.to_vec();
+        bytes.extend_from_slice(b"garbage");
+        let invalid = CompressedBytecode {
+            compressed_bytes: bytes.clone(),
+        };
+
+        assert!(invalid.decompress().is_err());
+        assert!(CompressedBytecode::decompressed_size(&bytes, u64::MAX).is_err());
+    }
 }
@@ -5,7 +5,8 @@
 
 use custom_debug_derive::Debug;
 use linera_witty::{WitLoad, WitStore, WitType};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::hex_debug;
 
@@ -69,6 +70,51 @@ impl Request {
     }
 }
 
+/// Limits that an application requests for a single HTTP request.
+///
+/// The limits can only be tighter than the ones from the validators' resource control policy,
+/// so larger values are capped to the policy's limits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, WitLoad, WitStore, WitType)]
+#[witty(name = "http-request-options")]
+pub struct RequestOptions {
+    /// The maximum time to wait for the response, in milliseconds.
+    pub timeout_ms: Option<u64>,
+
+    /// The maximum size of the response, in bytes, including its headers.
+    pub max_response_bytes: Option<u64>,
+}
+
+impl RequestOptions {
+    /// Sets the maximum time to wait for the response, in milliseconds.
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Sets the maximum size of the response, in bytes, including its headers.
+    pub fn with_max_response_bytes(mut self, max_response_bytes: u64) -> Self {
+        self.max_response_bytes = Some(max_response_bytes);
+        self
+    }
+}
+
+/// Errors reported when an HTTP request is refused or exceeds the limits it was made with.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq, WitLoad, WitStore, WitType)]
+#[witty(name = "http-request-error")]
+pub enum RequestError {
+    /// No response was received before the timeout.
+    #[error("HTTP request timed out")]
+    Timeout,
+
+    /// The response was larger than the maximum response size.
+    #[error("HTTP response exceeds the size limit")]
+    ResponseTooLarge,
+
+    /// The host is not in the list of hosts the application is allowed to send requests to.
+    #[error("HTTP requests to this host are not allowed")]
+    NotAllowed,
+}
+
 /// The method used in an HTTP request.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, WitLoad, WitStore, WitType)]
 #[witty(name = "http-method")]
@@ -162,6 +208,32 @@ impl Response {
         }
     }
 
+    /// Returns `true` if this [`Response`] has a successful (2xx) status code.
+    pub fn status_is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Returns the body of this [`Response`] as a UTF-8 string.
+    pub fn body_as_string(&self) -> Result<String, std::string::FromUtf8Error> {
+        String::from_utf8(self.body.clone())
+    }
+
+    /// Deserializes the body of this [`Response`] from JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// Returns the size of this [`Response`]'s headers and body, in bytes, as counted against
+    /// the response size limits.
+    pub fn size(&self) -> u64 {
+        let headers_size = self
+            .headers
+            .iter()
+            .map(|header| (header.name.len() + header.value.len()) as u64)
+            .sum::<u64>();
+        headers_size + self.body.len() as u64
+    }
+
     /// Adds a header to this [`Response`].
     pub fn with_header(mut self, name: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
         self.headers.push(Header::new(name, value));
@@ -191,3 +263,65 @@ impl Header {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+    struct Payload {
+        name: String,
+        amounts: Vec<u64>,
+        labels: BTreeMap<String, bool>,
+    }
+
+    fn payload() -> Payload {
+        Payload {
+            name: "transfer ✓".to_owned(),
+            amounts: vec![0, 1, u64::MAX],
+            labels: BTreeMap::from([("urgent".to_owned(), true), ("internal".to_owned(), false)]),
+        }
+    }
+
+    #[test]
+    fn test_post_json_sets_content_type() {
+        let request = Request::post_json("http://localhost/api", &payload())
+            .unwrap()
+            .with_header("Authorization", b"Bearer token");
+
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(request.url, "http://localhost/api");
+        assert_eq!(
+            request.headers,
+            vec![
+                Header::new("Content-Type", b"application/json"),
+                Header::new("Authorization", b"Bearer token"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let request = Request::post_json("http://localhost/api", &payload()).unwrap();
+        let response = Response::ok(request.body);
+
+        assert!(response.status_is_success());
+        assert_eq!(response.json::<Payload>().unwrap(), payload());
+        assert!(Response::ok(b"not json".to_vec())
+            .json::<Payload>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_response_helpers() {
+        let response = Response::ok("hello ✓");
+        assert_eq!(response.body_as_string().unwrap(), "hello ✓");
+        assert!(Response::ok(vec![0xff]).body_as_string().is_err());
+
+        assert!(Response::new(204).status_is_success());
+        assert!(!Response::new(302).status_is_success());
+        assert!(!Response::unauthorized().status_is_success());
+    }
+}
@@ -4,6 +4,7 @@
 //! Core identifiers used by the Linera protocol.
 
 use std::{
+    convert::Infallible,
     fmt::{self, Display},
     hash::{Hash, Hasher},
     marker::PhantomData,
@@ -49,6 +50,19 @@ impl AccountOwner {
     pub fn is_chain(&self) -> bool {
         self == &AccountOwner::CHAIN
     }
+
+    /// Returns the account owner with the given EVM address.
+    pub const fn from_evm_address(address: [u8; 20]) -> AccountOwner {
+        AccountOwner::Address20(address)
+    }
+
+    /// Returns the EVM address of this account owner, if it is an [`AccountOwner::Address20`].
+    pub const fn to_evm_address(&self) -> Option<[u8; 20]> {
+        match self {
+            AccountOwner::Address20(address) => Some(*address),
+            AccountOwner::Reserved(_) | AccountOwner::Address32(_) => None,
+        }
+    }
 }
 
 #[cfg(with_testing)]
@@ -420,6 +434,14 @@ where
     }
 }
 
+impl FromStr for StreamName {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Ok(StreamName::new(string))
+    }
+}
+
 /// An event stream ID.
 #[derive(
     Clone,
@@ -477,6 +499,15 @@ pub struct EventId {
 }
 
 impl StreamName {
+    /// Creates a stream name from the given bytes.
+    ///
+    /// Stream names can't be built in a `const` context, but this accepts a `&'static [u8]` or
+    /// `&'static str`, so applications can keep their stream names in constants, e.g.
+    /// `const STREAM_NAME: &[u8] = b"posts";`, and call `StreamName::new(STREAM_NAME)`.
+    pub fn new(name: impl Into<Vec<u8>>) -> Self {
+        StreamName(name.into())
+    }
+
     /// Turns the stream name into bytes.
     pub fn into_bytes(self) -> Vec<u8> {
         self.0
@@ -886,7 +917,10 @@ impl Display for AccountOwner {
                 write!(f, "0x{}", hex::encode(&value.to_be_bytes()[..]))?
             }
             AccountOwner::Address32(value) => write!(f, "0x{}", value)?,
-            AccountOwner::Address20(value) => write!(f, "0x{}", hex::encode(&value[..]))?,
+            AccountOwner::Address20(value) => {
+                // EIP-55 checksummed, including the `0x` prefix.
+                write!(f, "{}", Address::from(*value).to_checksum(None))?
+            }
         };
 
         Ok(())
@@ -897,34 +931,55 @@ impl FromStr for AccountOwner {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(s) = s.strip_prefix("0x") {
-            if s.len() == 64 {
-                if let Ok(hash) = CryptoHash::from_str(s) {
-                    return Ok(AccountOwner::Address32(hash));
-                }
-            } else if s.len() == 40 {
-                let address = hex::decode(s)?;
-                if address.len() != 20 {
-                    anyhow::bail!("Invalid address length: {}", s);
-                }
-                let address = <[u8; 20]>::try_from(address.as_slice()).unwrap();
-                return Ok(AccountOwner::Address20(address));
+        let Some(digits) = s.strip_prefix("0x") else {
+            anyhow::bail!("Invalid address value {s:?}: addresses must start with \"0x\"");
+        };
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid address value {s:?}: expected hexadecimal digits after \"0x\"");
+        }
+        match digits.len() {
+            2 => {
+                let [value] = <[u8; 1]>::try_from(hex::decode(digits)?).expect("one byte");
+                Ok(AccountOwner::Reserved(value))
             }
-            if s.len() == 2 {
-                let bytes = hex::decode(s)?;
-                if bytes.len() == 1 {
-                    let value = u8::from_be_bytes(bytes.try_into().expect("one byte"));
-                    return Ok(AccountOwner::Reserved(value));
+            40 => {
+                // Mixed-case addresses must have a valid EIP-55 checksum. Addresses in a single
+                // case have no checksum.
+                let is_mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+                    && digits.chars().any(|c| c.is_ascii_uppercase());
+                if is_mixed_case && Address::parse_checksummed(s, None).is_err() {
+                    anyhow::bail!("Invalid address value {s:?}: wrong EIP-55 checksum");
                 }
+                let address = <[u8; 20]>::try_from(hex::decode(digits)?).expect("20 bytes");
+                Ok(AccountOwner::Address20(address))
             }
+            64 => Ok(AccountOwner::Address32(CryptoHash::from_str(digits)?)),
+            length => anyhow::bail!(
+                "Invalid address value {s:?}: expected 2, 40 or 64 hexadecimal digits after \"0x\", \
+                found {length}"
+            ),
         }
-        anyhow::bail!("Invalid address value: {}", s);
+    }
+}
+
+impl ChainId {
+    /// The number of hexadecimal digits of the short form of a chain ID.
+    pub const SHORT_LENGTH: usize = 8;
+
+    /// Returns the first [`ChainId::SHORT_LENGTH`] hexadecimal digits of the chain ID, to
+    /// make logs and command-line output readable. This is also what `{:#}` displays.
+    pub fn short(&self) -> String {
+        format!("{:.*}", Self::SHORT_LENGTH, self.0)
     }
 }
 
 impl Display for ChainId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Display::fmt(&self.0, f)
+        if f.alternate() {
+            f.write_str(&self.short())
+        } else {
+            Display::fmt(&self.0, f)
+        }
     }
 }
 
@@ -993,8 +1048,9 @@ mod tests {
 
     use assert_matches::assert_matches;
 
-    use super::{AccountOwner, BlobType};
+    use super::{AccountOwner, BlobType, ChainId, StreamName};
     use crate::{
+        crypto::CryptoHash,
         data_types::{Amount, ChainDescription, ChainOrigin, Epoch, InitialChainConfig, Timestamp},
         ownership::ChainOwnership,
     };
@@ -1022,6 +1078,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn chain_id_short_form() {
+        let chain_id =
+            ChainId::from_str("0e94923a8f72ef1d0e69e6958e655002f2b1883bac9a67a4704d2f4125825217")
+                .unwrap();
+        assert_eq!(chain_id.short(), "0e94923a");
+        assert_eq!(format!("{chain_id:#}"), "0e94923a");
+        assert_eq!(format!("{chain_id}"), chain_id.0.to_string());
+    }
+
     #[test]
     fn blob_types() {
         assert_eq!("ContractBytecode", BlobType::ContractBytecode.to_string());
@@ -1031,6 +1097,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn stream_names() {
+        const STREAM_NAME: &[u8] = b"posts";
+
+        assert_eq!(StreamName::new(STREAM_NAME), StreamName(b"posts".to_vec()));
+        assert_eq!(
+            StreamName::from_str("posts").unwrap(),
+            StreamName::new(STREAM_NAME)
+        );
+    }
+
     #[test]
     fn addresses() {
         assert_eq!(&AccountOwner::Reserved(0).to_string(), "0x00");
@@ -1053,7 +1130,7 @@ mod tests {
         assert_matches!(address, AccountOwner::Address20(_));
         assert_eq!(
             address.to_string(),
-            "0x6e0ab7f37b667b7228d3a03116ca21be83213823"
+            "0x6E0ab7F37b667b7228D3a03116Ca21Be83213823"
         );
 
         assert!(AccountOwner::from_str("0x5487b7").is_err());
@@ -1063,4 +1140,45 @@ mod tests {
         )
         .is_err());
     }
+
+    #[test]
+    fn evm_addresses() {
+        // The test vectors of EIP-55.
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let owner = AccountOwner::from_str(checksummed).unwrap();
+            assert_eq!(owner.to_string(), checksummed);
+            let lowercase = AccountOwner::from_str(&checksummed.to_lowercase()).unwrap();
+            assert_eq!(lowercase, owner);
+            let uppercase = format!("0x{}", checksummed[2..].to_uppercase());
+            assert_eq!(AccountOwner::from_str(&uppercase).unwrap(), owner);
+
+            let address = owner.to_evm_address().unwrap();
+            assert_eq!(AccountOwner::from_evm_address(address), owner);
+            let json = serde_json::to_string(&owner).unwrap();
+            assert_eq!(json, format!("{checksummed:?}"));
+            assert_eq!(serde_json::from_str::<AccountOwner>(&json).unwrap(), owner);
+        }
+
+        let error =
+            AccountOwner::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").unwrap_err();
+        assert!(error.to_string().contains("checksum"), "{error}");
+        let error = AccountOwner::from_str("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap_err();
+        assert!(error.to_string().contains("must start with"), "{error}");
+        let error = AccountOwner::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").unwrap_err();
+        assert!(error.to_string().contains("found 38"), "{error}");
+        let error =
+            AccountOwner::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg").unwrap_err();
+        assert!(error.to_string().contains("hexadecimal digits"), "{error}");
+
+        assert_eq!(AccountOwner::CHAIN.to_evm_address(), None);
+        assert_eq!(
+            AccountOwner::from(CryptoHash::test_hash("owner")).to_evm_address(),
+            None
+        );
+    }
 }
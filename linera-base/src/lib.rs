@@ -32,6 +32,8 @@ pub mod ownership;
 pub mod port;
 #[cfg(with_metrics)]
 pub mod prometheus_util;
+#[cfg(feature = "schema")]
+pub mod schema;
 #[cfg(not(chain))]
 pub mod task;
 pub mod vm;
@@ -15,7 +15,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    data_types::{Round, TimeDelta},
+    data_types::{Amount, Round, TimeDelta},
     doc_scalar,
     identifiers::AccountOwner,
 };
@@ -70,6 +70,12 @@ pub struct ChainOwnership {
 }
 
 impl ChainOwnership {
+    /// Returns a [`ChainOwnershipBuilder`] without any owners, with two multi-leader rounds
+    /// and the default timeouts.
+    pub fn builder() -> ChainOwnershipBuilder {
+        ChainOwnershipBuilder::default()
+    }
+
     /// Creates a `ChainOwnership` with a single super owner.
     pub fn single_super(owner: AccountOwner) -> Self {
         ChainOwnership {
@@ -181,12 +187,201 @@ impl ChainOwnership {
     }
 }
 
+/// A builder for a [`ChainOwnership`] that checks that the chain can make progress.
+///
+/// ```
+/// # use linera_base::{
+/// #     data_types::TimeDelta, identifiers::AccountOwner, ownership::ChainOwnership,
+/// # };
+/// # let (alice, bob) = (AccountOwner::Reserved(1), AccountOwner::Reserved(2));
+/// let ownership = ChainOwnership::builder()
+///     .super_owner(alice)
+///     .owner_with_weight(bob, 100)
+///     .multi_leader_rounds(3)
+///     .base_timeout(TimeDelta::from_secs(5))
+///     .build()
+///     .unwrap();
+/// assert!(ownership.verify_owner(&bob));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChainOwnershipBuilder {
+    super_owners: Vec<AccountOwner>,
+    owners: Vec<(AccountOwner, u64)>,
+    multi_leader_rounds: u32,
+    open_multi_leader_rounds: bool,
+    timeout_config: TimeoutConfig,
+}
+
+impl Default for ChainOwnershipBuilder {
+    fn default() -> Self {
+        ChainOwnershipBuilder {
+            super_owners: Vec::new(),
+            owners: Vec::new(),
+            multi_leader_rounds: 2,
+            open_multi_leader_rounds: false,
+            timeout_config: TimeoutConfig::default(),
+        }
+    }
+}
+
+impl ChainOwnershipBuilder {
+    /// The weight of the owners added with [`ChainOwnershipBuilder::owner`].
+    pub const DEFAULT_WEIGHT: u64 = 100;
+
+    /// Adds a super owner.
+    pub fn super_owner(mut self, owner: AccountOwner) -> Self {
+        self.super_owners.push(owner);
+        self
+    }
+
+    /// Adds a regular owner with the default weight.
+    pub fn owner(self, owner: AccountOwner) -> Self {
+        self.owner_with_weight(owner, Self::DEFAULT_WEIGHT)
+    }
+
+    /// Adds a regular owner with the given weight, which determines how often it is round
+    /// leader.
+    pub fn owner_with_weight(mut self, owner: AccountOwner, weight: u64) -> Self {
+        self.owners.push((owner, weight));
+        self
+    }
+
+    /// Sets the number of rounds in which all owners are allowed to propose blocks.
+    pub fn multi_leader_rounds(mut self, multi_leader_rounds: u32) -> Self {
+        self.multi_leader_rounds = multi_leader_rounds;
+        self
+    }
+
+    /// Sets whether the multi-leader rounds are unrestricted, i.e. not limited to chain
+    /// owners.
+    pub fn open_multi_leader_rounds(mut self, open_multi_leader_rounds: bool) -> Self {
+        self.open_multi_leader_rounds = open_multi_leader_rounds;
+        self
+    }
+
+    /// Sets the whole timeout configuration.
+    pub fn timeout_config(mut self, timeout_config: TimeoutConfig) -> Self {
+        self.timeout_config = timeout_config;
+        self
+    }
+
+    /// Sets the duration of the fast round.
+    pub fn fast_round_duration(mut self, duration: TimeDelta) -> Self {
+        self.timeout_config.fast_round_duration = Some(duration);
+        self
+    }
+
+    /// Sets the duration of the first single-leader and all multi-leader rounds.
+    pub fn base_timeout(mut self, timeout: TimeDelta) -> Self {
+        self.timeout_config.base_timeout = timeout;
+        self
+    }
+
+    /// Sets the duration by which the timeout increases after each single-leader round.
+    pub fn timeout_increment(mut self, increment: TimeDelta) -> Self {
+        self.timeout_config.timeout_increment = increment;
+        self
+    }
+
+    /// Sets the age of an incoming tracked or protected message after which the validators
+    /// start transitioning the chain to fallback mode.
+    pub fn fallback_duration(mut self, duration: TimeDelta) -> Self {
+        self.timeout_config.fallback_duration = duration;
+        self
+    }
+
+    /// Returns the [`ChainOwnership`], or an error if the chain could not make progress
+    /// with it.
+    pub fn build(self) -> Result<ChainOwnership, OwnershipError> {
+        let ChainOwnershipBuilder {
+            super_owners: super_owner_list,
+            owners: owner_list,
+            multi_leader_rounds,
+            open_multi_leader_rounds,
+            timeout_config,
+        } = self;
+        let mut super_owners = BTreeSet::new();
+        for owner in super_owner_list {
+            if !super_owners.insert(owner) {
+                return Err(OwnershipError::DuplicateOwner(owner));
+            }
+        }
+        let mut owners = BTreeMap::new();
+        let mut total_weight = 0u64;
+        for (owner, weight) in owner_list {
+            if super_owners.contains(&owner) || owners.insert(owner, weight).is_some() {
+                return Err(OwnershipError::DuplicateOwner(owner));
+            }
+            total_weight = total_weight
+                .checked_add(weight)
+                .ok_or(OwnershipError::WeightOverflow)?;
+        }
+        if super_owners.is_empty()
+            && owners.is_empty()
+            && !open_multi_leader_rounds
+            && timeout_config.fallback_duration != TimeDelta::ZERO
+        {
+            return Err(OwnershipError::NoOwners);
+        }
+        if !owners.is_empty() && total_weight == 0 {
+            return Err(OwnershipError::ZeroTotalWeight);
+        }
+        if timeout_config.base_timeout == TimeDelta::ZERO {
+            return Err(OwnershipError::ZeroBaseTimeout);
+        }
+        if timeout_config.fast_round_duration == Some(TimeDelta::ZERO) {
+            return Err(OwnershipError::ZeroFastRoundDuration);
+        }
+        if timeout_config.fast_round_duration.is_some() && super_owners.is_empty() {
+            return Err(OwnershipError::FastRoundWithoutSuperOwners);
+        }
+        Ok(ChainOwnership {
+            super_owners,
+            owners,
+            multi_leader_rounds,
+            open_multi_leader_rounds,
+            timeout_config,
+        })
+    }
+}
+
+/// Errors that make a [`ChainOwnershipBuilder`] fail to build a [`ChainOwnership`].
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum OwnershipError {
+    /// An owner was added more than once, possibly both as a super owner and a regular owner.
+    #[error("Owner {0} was added more than once")]
+    DuplicateOwner(AccountOwner),
+    /// Nobody would be allowed to propose blocks.
+    #[error(
+        "The chain needs at least one owner, open multi-leader rounds or a zero fallback duration"
+    )]
+    NoOwners,
+    /// The weights of the regular owners add up to zero, so none can be round leader.
+    #[error("The weights of the regular owners must not add up to zero")]
+    ZeroTotalWeight,
+    /// The weights of the regular owners add up to more than `u64::MAX`.
+    #[error("The weights of the regular owners add up to more than {}", u64::MAX)]
+    WeightOverflow,
+    /// The rounds with a timeout would end immediately.
+    #[error("The base timeout must not be zero")]
+    ZeroBaseTimeout,
+    /// The fast round would end immediately.
+    #[error("The fast round duration must not be zero")]
+    ZeroFastRoundDuration,
+    /// Only super owners can propose blocks in the fast round.
+    #[error("A fast round duration was set, but there are no super owners")]
+    FastRoundWithoutSuperOwners,
+}
+
 /// Errors that can happen when attempting to close a chain.
 #[derive(Clone, Copy, Debug, Error, WitStore, WitType)]
 pub enum CloseChainError {
     /// The application wasn't allowed to close the chain.
     #[error("Unauthorized attempt to close the chain")]
     NotPermitted,
+    /// The chain was already closed.
+    #[error("The chain is already closed")]
+    AlreadyClosed,
 }
 
 /// Errors that can happen when attempting to change the application permissions.
@@ -206,6 +401,17 @@ pub enum AccountPermissionError {
     NotPermitted(AccountOwner),
 }
 
+/// Errors that can happen when attempting to transfer or claim tokens.
+#[derive(Clone, Copy, Debug, Error, WitStore, WitType)]
+pub enum TransferError {
+    /// The application wasn't allowed to move tokens out of the source account.
+    #[error("Unauthorized attempt to access account owned by {0}")]
+    NotPermitted(AccountOwner),
+    /// The source account's balance, given here, is lower than the requested amount.
+    #[error("Insufficient balance for transfer: {0}")]
+    InsufficientFunding(Amount),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +459,105 @@ mod tests {
             Some(TimeDelta::from_secs(18))
         );
     }
+
+    #[test]
+    fn test_ownership_builder() {
+        let (alice, bob) = (AccountOwner::Reserved(1), AccountOwner::Reserved(2));
+        let ownership = ChainOwnership::builder()
+            .super_owner(alice)
+            .owner_with_weight(bob, 50)
+            .multi_leader_rounds(3)
+            .fast_round_duration(TimeDelta::from_secs(1))
+            .build()
+            .unwrap();
+        assert_eq!(ownership.super_owners, BTreeSet::from([alice]));
+        assert_eq!(ownership.owners, BTreeMap::from([(bob, 50)]));
+        assert_eq!(ownership.multi_leader_rounds, 3);
+        assert_eq!(
+            ownership.timeout_config.fast_round_duration,
+            Some(TimeDelta::from_secs(1))
+        );
+
+        let ownership = ChainOwnership::builder().owner(bob).build().unwrap();
+        assert_eq!(ownership, ChainOwnership::single(bob));
+    }
+
+    #[test]
+    fn test_ownership_builder_rejects_duplicate_owners() {
+        let (alice, bob) = (AccountOwner::Reserved(1), AccountOwner::Reserved(2));
+        let result = ChainOwnership::builder()
+            .super_owner(alice)
+            .super_owner(alice)
+            .build();
+        assert_eq!(result, Err(OwnershipError::DuplicateOwner(alice)));
+        let result = ChainOwnership::builder()
+            .owner(bob)
+            .owner(alice)
+            .owner(bob)
+            .build();
+        assert_eq!(result, Err(OwnershipError::DuplicateOwner(bob)));
+        let result = ChainOwnership::builder()
+            .super_owner(bob)
+            .owner(bob)
+            .build();
+        assert_eq!(result, Err(OwnershipError::DuplicateOwner(bob)));
+    }
+
+    #[test]
+    fn test_ownership_builder_rejects_missing_owners() {
+        assert_eq!(
+            ChainOwnership::builder().build(),
+            Err(OwnershipError::NoOwners)
+        );
+        assert!(ChainOwnership::builder()
+            .open_multi_leader_rounds(true)
+            .build()
+            .is_ok());
+        assert!(ChainOwnership::builder()
+            .fallback_duration(TimeDelta::ZERO)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_ownership_builder_rejects_invalid_weights() {
+        let (alice, bob) = (AccountOwner::Reserved(1), AccountOwner::Reserved(2));
+        let result = ChainOwnership::builder()
+            .owner_with_weight(alice, 0)
+            .owner_with_weight(bob, 0)
+            .build();
+        assert_eq!(result, Err(OwnershipError::ZeroTotalWeight));
+        let result = ChainOwnership::builder()
+            .owner_with_weight(alice, u64::MAX)
+            .owner_with_weight(bob, 1)
+            .build();
+        assert_eq!(result, Err(OwnershipError::WeightOverflow));
+        assert!(ChainOwnership::builder()
+            .owner_with_weight(alice, 0)
+            .owner_with_weight(bob, 1)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_ownership_builder_rejects_invalid_timeouts() {
+        let alice = AccountOwner::Reserved(1);
+        let result = ChainOwnership::builder()
+            .owner(alice)
+            .base_timeout(TimeDelta::ZERO)
+            .build();
+        assert_eq!(result, Err(OwnershipError::ZeroBaseTimeout));
+        let result = ChainOwnership::builder()
+            .super_owner(alice)
+            .fast_round_duration(TimeDelta::ZERO)
+            .build();
+        assert_eq!(result, Err(OwnershipError::ZeroFastRoundDuration));
+        let result = ChainOwnership::builder()
+            .owner(alice)
+            .fast_round_duration(TimeDelta::from_secs(1))
+            .build();
+        assert_eq!(result, Err(OwnershipError::FastRoundWithoutSuperOwners));
+    }
 }
 
 doc_scalar!(ChainOwnership, "Represents the owner(s) of a chain");
@@ -5,7 +5,8 @@
 
 use prometheus::{
     exponential_buckets, histogram_opts, linear_buckets, register_histogram_vec,
-    register_int_counter_vec, HistogramVec, IntCounterVec, Opts,
+    register_int_counter_vec, register_int_gauge_vec, Histogram, HistogramVec, IntCounterVec,
+    IntGaugeVec, Opts,
 };
 
 use crate::time::Instant;
@@ -22,6 +23,12 @@ pub fn register_int_counter_vec(
     register_int_counter_vec!(counter_opts, label_names).expect("IntCounter can be created")
 }
 
+/// Wrapper around Prometheus register_int_gauge_vec! macro which also sets the `linera` namespace
+pub fn register_int_gauge_vec(name: &str, description: &str, label_names: &[&str]) -> IntGaugeVec {
+    let gauge_opts = Opts::new(name, description).namespace(LINERA_NAMESPACE);
+    register_int_gauge_vec!(gauge_opts, label_names).expect("IntGauge can be created")
+}
+
 /// Wrapper around Prometheus `register_histogram_vec!` macro which also sets the `linera` namespace
 pub fn register_histogram_vec(
     name: &str,
@@ -145,6 +152,19 @@ impl MeasureLatency for HistogramVec {
     }
 }
 
+impl MeasureLatency for Histogram {
+    fn measure_latency(&self) -> ActiveMeasurementGuard<'_, Self> {
+        ActiveMeasurementGuard {
+            start: Instant::now(),
+            metric: Some(self),
+        }
+    }
+
+    fn finish_measurement(&self, milliseconds: f64) {
+        self.observe(milliseconds);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,284 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Registries of the types that applications exchange in BCS, so that clients in other
+//! languages can generate their (de)serialization code, e.g. with `serde-generate`.
+//!
+//! ```
+//! # use linera_base::{abi::{Abi, ContractAbi, ServiceAbi}, identifiers::AccountOwner};
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Debug, Deserialize, Serialize)]
+//! enum Operation {
+//!     Transfer { to: AccountOwner, amount: u64 },
+//! }
+//!
+//! struct TokenAbi;
+//!
+//! impl ContractAbi for TokenAbi {
+//!     type Operation = Operation;
+//!     type Response = ();
+//! }
+//!
+//! impl ServiceAbi for TokenAbi {
+//!     type Query = async_graphql::Request;
+//!     type QueryResponse = async_graphql::Response;
+//! }
+//!
+//! let registry = TokenAbi::type_registry().unwrap();
+//! assert!(registry.contains_key("Operation"));
+//! ```
+
+use std::{any::TypeId, io};
+
+use serde::{de::DeserializeOwned, Serialize};
+pub use serde_reflection::Registry;
+use serde_reflection::{Samples, Tracer, TracerConfig};
+use thiserror::Error;
+
+use crate::{
+    abi::{ContractAbi, ServiceAbi},
+    identifiers::AccountOwner,
+};
+
+/// Errors that can happen when building or writing a [`Registry`].
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    /// A type could not be traced, e.g. because it has variants that were never reached.
+    #[error("failed to trace the types: {0}")]
+    Tracing(#[from] serde_reflection::Error),
+    /// The registry could not be written as YAML.
+    #[error("failed to write the registry as YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    /// The registry could not be written as JSON.
+    #[error("failed to write the registry as JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The formats in which a [`Registry`] can be written.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RegistryFormat {
+    /// YAML, as used by the `serdegen` tool of `serde-generate`.
+    #[default]
+    Yaml,
+    /// JSON.
+    Json,
+}
+
+/// Records the formats of the types exchanged with an application.
+///
+/// Enums nested in the traced types only have their first variant explored, and make
+/// [`TypeTracer::registry`] fail, unless they are traced separately with
+/// [`TypeTracer::trace_type`]. This is already done for the enums of this crate that
+/// applications commonly use: [`AccountOwner`] and, on native targets,
+/// [`AccountPublicKey`](crate::crypto::AccountPublicKey).
+pub struct TypeTracer {
+    tracer: Tracer,
+    samples: Samples,
+}
+
+impl TypeTracer {
+    /// Creates a tracer with samples for the types of this crate that need valid values.
+    pub fn new() -> Result<Self, SchemaError> {
+        let tracer = Tracer::new(
+            TracerConfig::default()
+                .record_samples_for_newtype_structs(true)
+                .record_samples_for_tuple_structs(true),
+        );
+        let mut type_tracer = TypeTracer {
+            tracer,
+            samples: Samples::new(),
+        };
+        // The deserializers of these keys reject the values generated by serde-reflection.
+        #[cfg(with_getrandom)]
+        {
+            let mut rng = rand::rngs::OsRng;
+            let secp256k1_key = crate::crypto::Secp256k1SecretKey::generate_from(&mut rng);
+            let evm_key = crate::crypto::EvmSecretKey::generate_from(&mut rng);
+            type_tracer
+                .trace_value(&secp256k1_key.public())?
+                .trace_value(&evm_key.public())?
+                .trace_type::<crate::crypto::AccountPublicKey>()?;
+        }
+        type_tracer.trace_type::<AccountOwner>()?;
+        Ok(type_tracer)
+    }
+
+    /// Records the format of `value`, which is then used as a sample of its type.
+    pub fn trace_value<T: Serialize>(&mut self, value: &T) -> Result<&mut Self, SchemaError> {
+        self.tracer.trace_value(&mut self.samples, value)?;
+        Ok(self)
+    }
+
+    /// Records the format of the type `T`, and of the types it contains.
+    ///
+    /// GraphQL requests and responses are skipped: their contents are described by the
+    /// GraphQL schema of the service instead.
+    pub fn trace_type<T: DeserializeOwned + 'static>(&mut self) -> Result<&mut Self, SchemaError> {
+        let type_id = TypeId::of::<T>();
+        if type_id != TypeId::of::<async_graphql::Request>()
+            && type_id != TypeId::of::<async_graphql::Response>()
+        {
+            self.tracer.trace_type::<T>(&self.samples)?;
+        }
+        Ok(self)
+    }
+
+    /// Records the formats of the types named in the contract and service ABIs of `A`.
+    pub fn trace_abi<A: ContractAbi + ServiceAbi + ?Sized>(
+        &mut self,
+    ) -> Result<&mut Self, SchemaError> {
+        self.trace_type::<A::Operation>()?
+            .trace_type::<A::Response>()?
+            .trace_type::<A::Query>()?
+            .trace_type::<A::QueryResponse>()
+    }
+
+    /// Returns the registry of all the types traced so far.
+    pub fn registry(self) -> Result<Registry, SchemaError> {
+        Ok(self.tracer.registry()?)
+    }
+}
+
+/// Writes the `registry` to `writer` in the given `format`.
+pub fn write_registry(
+    registry: &Registry,
+    format: RegistryFormat,
+    writer: impl io::Write,
+) -> Result<(), SchemaError> {
+    match format {
+        RegistryFormat::Yaml => serde_yaml::to_writer(writer, registry)?,
+        RegistryFormat::Json => serde_json::to_writer_pretty(writer, registry)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
+
+    use super::*;
+    use crate::{
+        abi::Abi,
+        crypto::AccountPublicKey,
+        data_types::Amount,
+        identifiers::{ApplicationId, ChainId},
+    };
+
+    #[derive(Debug, Deserialize, Serialize)]
+    enum Operation {
+        Transfer { owner: AccountOwner, amount: Amount },
+        Subscribe(ChainId),
+        Register(Member),
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Member {
+        key: AccountPublicKey,
+        application_id: ApplicationId,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Response(Vec<Amount>);
+
+    struct SampleAbi;
+
+    impl ContractAbi for SampleAbi {
+        type Operation = Operation;
+        type Response = Response;
+    }
+
+    impl ServiceAbi for SampleAbi {
+        type Query = async_graphql::Request;
+        type QueryResponse = async_graphql::Response;
+    }
+
+    #[test]
+    fn test_sample_abi_registry() {
+        let registry = SampleAbi::type_registry().unwrap();
+
+        for name in [
+            "Operation",
+            "Member",
+            "Response",
+            "AccountOwner",
+            "AccountPublicKey",
+            "Amount",
+            "ChainId",
+            "ApplicationId",
+            "CryptoHash",
+        ] {
+            assert!(registry.contains_key(name), "{name} is missing");
+        }
+        assert!(!registry.contains_key("Request"));
+
+        let Some(ContainerFormat::Enum(variants)) = registry.get("Operation") else {
+            panic!("Operation should be an enum");
+        };
+        let names = variants
+            .values()
+            .map(|variant| variant.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Transfer", "Subscribe", "Register"]);
+        assert_eq!(
+            variants[&1].value,
+            VariantFormat::NewType(Box::new(Format::TypeName("ChainId".into())))
+        );
+        assert_eq!(
+            registry["Member"],
+            ContainerFormat::Struct(vec![
+                Named {
+                    name: "key".into(),
+                    value: Format::TypeName("AccountPublicKey".into()),
+                },
+                Named {
+                    name: "application_id".into(),
+                    value: Format::TypeName("ApplicationId".into()),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_untraced_nested_enum_is_reported() {
+        #[derive(Debug, Deserialize, Serialize)]
+        enum Inner {
+            First,
+            Second,
+        }
+
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Outer(Inner);
+
+        let mut tracer = TypeTracer::new().unwrap();
+        tracer.trace_type::<Outer>().unwrap();
+        assert!(tracer.registry().is_err());
+
+        let mut tracer = TypeTracer::new().unwrap();
+        tracer
+            .trace_type::<Inner>()
+            .unwrap()
+            .trace_type::<Outer>()
+            .unwrap();
+        let registry = tracer.registry().unwrap();
+        assert_eq!(
+            registry["Outer"],
+            ContainerFormat::NewTypeStruct(Box::new(Format::TypeName("Inner".into())))
+        );
+    }
+
+    #[test]
+    fn test_write_registry() {
+        let registry = SampleAbi::type_registry().unwrap();
+
+        let mut yaml = Vec::new();
+        write_registry(&registry, RegistryFormat::Yaml, &mut yaml).unwrap();
+        let from_yaml: Registry = serde_yaml::from_slice(&yaml).unwrap();
+        assert_eq!(from_yaml, registry);
+
+        let mut json = Vec::new();
+        write_registry(&registry, RegistryFormat::Json, &mut json).unwrap();
+        let from_json: Registry = serde_json::from_slice(&json).unwrap();
+        assert_eq!(from_json, registry);
+    }
+}
@@ -8,12 +8,15 @@ use std::{
     fmt::Debug,
 };
 
-use async_graphql::SimpleObject;
+use async_graphql::{
+    connection::{Connection, Edge},
+    ComplexObject, OutputType, SimpleObject,
+};
 use linera_base::{
     crypto::{BcsHashable, CryptoHash},
-    data_types::{Blob, BlockHeight, Epoch, Event, OracleResponse, Timestamp},
+    data_types::{Blob, BlockHeaderSummary, BlockHeight, Epoch, Event, OracleResponse, Timestamp},
     hashed::Hashed,
-    identifiers::{AccountOwner, BlobId, BlobType, ChainId},
+    identifiers::{AccountOwner, ApplicationId, BlobId, BlobType, ChainId},
 };
 use linera_execution::{BlobState, Operation, OutgoingMessage};
 use serde::{ser::SerializeStruct, Deserialize, Serialize};
@@ -21,12 +24,16 @@ use thiserror::Error;
 
 use crate::{
     data_types::{
-        BlockExecutionOutcome, IncomingBundle, MessageBundle, OperationResult, OutgoingMessageExt,
-        ProposedBlock,
+        BlockExecutionOutcome, IncomingBundle, MessageAction, MessageBundle, OperationResult,
+        OutgoingMessageExt, ProposedBlock,
     },
     types::CertificateValue,
 };
 
+#[cfg(test)]
+#[path = "unit_tests/block_tests.rs"]
+mod block_tests;
+
 /// Wrapper around a `Block` that has been validated.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -336,6 +343,7 @@ pub struct BlockHeader {
 
 /// The body of a block containing all the data included in the block.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct BlockBody {
     /// A selection of incoming messages to be executed first. Successive messages of the same
     /// sender and height are grouped together for conciseness.
@@ -356,6 +364,109 @@ pub struct BlockBody {
     pub operation_results: Vec<OperationResult>,
 }
 
+#[ComplexObject]
+impl BlockBody {
+    /// The incoming bundles, by pages. The cursor of a bundle is the index of its
+    /// transaction.
+    async fn incoming_bundles_connection(
+        &self,
+        after: Option<String>,
+        first: Option<usize>,
+    ) -> Result<Connection<String, IncomingBundle>, async_graphql::Error> {
+        let bundles = self.incoming_bundles.iter().enumerate();
+        paginate(
+            bundles.map(|(index, bundle)| (index.to_string(), bundle)),
+            after,
+            first,
+        )
+    }
+
+    /// The operations, by pages. The cursor of an operation is the index of its
+    /// transaction.
+    async fn operations_connection(
+        &self,
+        after: Option<String>,
+        first: Option<usize>,
+    ) -> Result<Connection<String, Operation>, async_graphql::Error> {
+        let offset = self.incoming_bundles.len();
+        let operations = self.operations.iter().enumerate();
+        paginate(
+            operations.map(|(index, operation)| ((offset + index).to_string(), operation)),
+            after,
+            first,
+        )
+    }
+
+    /// The outgoing messages of all transactions, by pages. The cursor of a message is the
+    /// index of its transaction and its index in the transaction, separated by a dash.
+    async fn messages_connection(
+        &self,
+        after: Option<String>,
+        first: Option<usize>,
+    ) -> Result<Connection<String, OutgoingMessage>, async_graphql::Error> {
+        let messages = self
+            .messages
+            .iter()
+            .enumerate()
+            .flat_map(|(txn_index, messages)| {
+                messages
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, message)| (format!("{txn_index}-{index}"), message))
+            });
+        paginate(messages, after, first)
+    }
+}
+
+/// The number of items of a page of a connection if `first` is not specified.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// The maximal number of items of a page of a connection.
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Returns the page of at most `first` items following the one with the cursor `after`.
+/// The page has `DEFAULT_PAGE_SIZE` items if `first` is not specified, and never more than
+/// `MAX_PAGE_SIZE`. Only the items of the page are cloned.
+fn paginate<'a, T>(
+    items: impl IntoIterator<Item = (String, &'a T)>,
+    after: Option<String>,
+    first: Option<usize>,
+) -> Result<Connection<String, T>, async_graphql::Error>
+where
+    T: OutputType + Clone + 'a,
+{
+    let mut items = items.into_iter();
+    if let Some(after) = &after {
+        if !items.by_ref().any(|(cursor, _)| cursor == *after) {
+            return Err(async_graphql::Error::new(format!(
+                "Invalid cursor: {after}"
+            )));
+        }
+    }
+    let page = items
+        .by_ref()
+        .take(first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE))
+        .collect::<Vec<_>>();
+    let mut connection = Connection::new(after.is_some(), items.next().is_some());
+    connection.edges = page
+        .into_iter()
+        .map(|(cursor, item)| Edge::new(cursor, item.clone()))
+        .collect();
+    Ok(connection)
+}
+
+impl BlockHeader {
+    /// Returns the parts of this header that are exposed to applications.
+    pub fn summary(&self) -> BlockHeaderSummary {
+        BlockHeaderSummary {
+            height: self.height,
+            timestamp: self.timestamp,
+            epoch: self.epoch,
+            authenticated_signer: self.authenticated_signer,
+        }
+    }
+}
+
 impl Block {
     pub fn new(block: ProposedBlock, outcome: BlockExecutionOutcome) -> Self {
         let bundles_hash = hashing::hash_vec(&block.incoming_bundles);
@@ -506,6 +617,39 @@ impl Block {
         required_blob_ids
     }
 
+    /// Returns the IDs of the user applications whose state this block may have changed:
+    /// the ones executing its operations and accepted incoming messages, and the ones that
+    /// sent messages or emitted events. Applications that were only called by other
+    /// applications are not included.
+    pub fn changed_application_ids(&self) -> BTreeSet<ApplicationId> {
+        let operations = self.body.operations.iter().map(Operation::application_id);
+        let incoming_messages = self
+            .body
+            .incoming_bundles
+            .iter()
+            .filter(|bundle| bundle.action == MessageAction::Accept)
+            .flat_map(|bundle| &bundle.bundle.messages)
+            .map(|posted_message| posted_message.message.application_id());
+        let outgoing_messages = self
+            .body
+            .messages
+            .iter()
+            .flatten()
+            .map(|message| message.message.application_id());
+        let events = self
+            .body
+            .events
+            .iter()
+            .flatten()
+            .map(|event| event.stream_id.application_id);
+        operations
+            .chain(incoming_messages)
+            .chain(outgoing_messages)
+            .chain(events)
+            .filter_map(|application_id| application_id.user_application_id().copied())
+            .collect()
+    }
+
     /// Returns reference to the outgoing messages in the block.
     pub fn messages(&self) -> &Vec<Vec<OutgoingMessage>> {
         &self.body.messages
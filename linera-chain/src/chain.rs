@@ -13,7 +13,7 @@ use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlockHeight,
-        Epoch, OracleResponse, Timestamp,
+        Epoch, OracleResponse, Round, Timestamp,
     },
     ensure,
     identifiers::{AccountOwner, ApplicationId, BlobType, ChainId, MessageId},
@@ -21,7 +21,7 @@ use linera_base::{
 };
 use linera_execution::{
     committee::Committee, ExecutionRuntimeContext, ExecutionStateView, Message, MessageContext,
-    Operation, OperationContext, OutgoingMessage, Query, QueryContext, QueryOutcome,
+    Operation, OperationContext, OutgoingMessage, Query, QueryContext, QueryLimits, QueryOutcome,
     ResourceController, ResourceTracker, ServiceRuntimeEndpoint, TransactionTracker,
 };
 use linera_views::{
@@ -362,6 +362,7 @@ where
         &mut self,
         local_time: Timestamp,
         query: Query,
+        limits: QueryLimits,
         service_runtime_endpoint: Option<&mut ServiceRuntimeEndpoint>,
     ) -> Result<QueryOutcome, ChainError> {
         let context = QueryContext {
@@ -370,7 +371,7 @@ where
             local_time,
         };
         self.execution_state
-            .query_application(context, query, service_runtime_endpoint)
+            .query_application_with_limits(context, query, limits, service_runtime_endpoint)
             .await
             .with_execution_context(ChainExecutionContext::Query)
     }
@@ -688,6 +689,8 @@ where
 
     /// Executes a block: first the incoming messages, then the main operation.
     /// Does not update chain state other than the execution state.
+    ///
+    /// Also returns the resources used by the block.
     #[expect(clippy::too_many_arguments)]
     async fn execute_block_inner(
         chain: &mut ExecutionStateView<C>,
@@ -695,10 +698,10 @@ where
         previous_message_blocks_view: &MapView<C, ChainId, BlockHeight>,
         block: &ProposedBlock,
         local_time: Timestamp,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: &[Blob],
         replaying_oracle_responses: Option<Vec<Vec<OracleResponse>>>,
-    ) -> Result<BlockExecutionOutcome, ChainError> {
+    ) -> Result<(BlockExecutionOutcome, ResourceTracker), ChainError> {
         #[cfg(with_metrics)]
         let _execution_latency = BLOCK_EXECUTION_LATENCY.measure_latency();
 
@@ -753,6 +756,20 @@ where
         }
         Self::check_app_permissions(chain.system.application_permissions.get(), block)?;
 
+        // The applications' states start being read in the background. The cache keeps the
+        // values written by the block in the meantime.
+        let application_ids = block
+            .operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::User { application_id, .. } => Some(application_id),
+                Operation::System(_) => None,
+            })
+            .collect::<BTreeSet<_>>();
+        chain
+            .prefetch_application_states(application_ids)
+            .with_execution_context(ChainExecutionContext::Block)?;
+
         // Execute each incoming bundle as a transaction, then each operation.
         // Collect messages, events and oracle responses, each as one list per transaction.
         let mut replaying_oracle_responses = replaying_oracle_responses.map(Vec::into_iter);
@@ -811,6 +828,7 @@ where
                         chain_id: block.chain_id,
                         height: block.height,
                         round,
+                        block_proposer: block.authenticated_signer,
                         authenticated_signer: block.authenticated_signer,
                         authenticated_caller_id: None,
                         timestamp: block.timestamp,
@@ -928,7 +946,7 @@ where
             chain.crypto_hash().await?
         };
 
-        Ok(BlockExecutionOutcome {
+        let outcome = BlockExecutionOutcome {
             messages,
             previous_message_blocks,
             state_hash,
@@ -936,7 +954,8 @@ where
             events,
             blobs,
             operation_results,
-        })
+        };
+        Ok((outcome, resource_controller.tracker))
     }
 
     /// Executes a block: first the incoming messages, then the main operation.
@@ -945,10 +964,32 @@ where
         &mut self,
         block: &ProposedBlock,
         local_time: Timestamp,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: &[Blob],
         replaying_oracle_responses: Option<Vec<Vec<OracleResponse>>>,
     ) -> Result<BlockExecutionOutcome, ChainError> {
+        let (outcome, _) = self
+            .execute_block_with_resource_tracker(
+                block,
+                local_time,
+                round,
+                published_blobs,
+                replaying_oracle_responses,
+            )
+            .await?;
+        Ok(outcome)
+    }
+
+    /// Executes a block like [`ChainStateView::execute_block`], and also returns the
+    /// resources used by its execution.
+    pub async fn execute_block_with_resource_tracker(
+        &mut self,
+        block: &ProposedBlock,
+        local_time: Timestamp,
+        round: Option<Round>,
+        published_blobs: &[Blob],
+        replaying_oracle_responses: Option<Vec<Vec<OracleResponse>>>,
+    ) -> Result<(BlockExecutionOutcome, ResourceTracker), ChainError> {
         assert_eq!(
             block.chain_id,
             self.execution_state.context().extra().chain_id()
@@ -1019,7 +1060,7 @@ where
         posted_message: &PostedMessage,
         incoming_bundle: &IncomingBundle,
         block: &ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         txn_tracker: &mut TransactionTracker,
         resource_controller: &mut ResourceController<Option<AccountOwner>>,
     ) -> Result<(), ChainError> {
@@ -1030,6 +1071,7 @@ where
             is_bouncing: posted_message.is_bouncing(),
             height: block.height,
             round,
+            block_proposer: block.authenticated_signer,
             message_id,
             authenticated_signer: posted_message.authenticated_signer,
             refund_grant_to: posted_message.refund_grant_to,
@@ -0,0 +1,127 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    data_types::Amount,
+    identifiers::{GenericApplicationId, StreamId, StreamName},
+};
+use linera_execution::{Message, MessageKind};
+
+use super::*;
+use crate::{
+    data_types::PostedMessage,
+    test::{make_first_block, BlockTestExt},
+};
+
+fn dummy_application_id(name: &str) -> ApplicationId {
+    ApplicationId::new(CryptoHash::test_hash(name))
+}
+
+fn user_message(application_id: ApplicationId) -> Message {
+    Message::User {
+        application_id,
+        bytes: vec![],
+    }
+}
+
+fn incoming_bundle(application_id: ApplicationId, action: MessageAction) -> IncomingBundle {
+    IncomingBundle {
+        origin: ChainId(CryptoHash::test_hash("sender")),
+        bundle: MessageBundle {
+            height: BlockHeight::ZERO,
+            timestamp: Timestamp::default(),
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            transaction_index: 0,
+            messages: vec![PostedMessage {
+                authenticated_signer: None,
+                grant: Amount::ZERO,
+                refund_grant_to: None,
+                kind: MessageKind::Simple,
+                index: 0,
+                message: user_message(application_id),
+            }],
+        },
+        action,
+    }
+}
+
+#[test]
+fn test_changed_application_ids() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let operation_app = dummy_application_id("operation");
+    let accepted_app = dummy_application_id("accepted");
+    let rejected_app = dummy_application_id("rejected");
+    let sender_app = dummy_application_id("sender");
+    let emitter_app = dummy_application_id("emitter");
+
+    let proposed_block = make_first_block(chain_id)
+        .with_incoming_bundle(incoming_bundle(accepted_app, MessageAction::Accept))
+        .with_incoming_bundle(incoming_bundle(rejected_app, MessageAction::Reject))
+        .with_operation(Operation::User {
+            application_id: operation_app,
+            bytes: vec![],
+        })
+        .with_simple_transfer(chain_id, Amount::ONE);
+    let outgoing_message = OutgoingMessage {
+        destination: chain_id,
+        authenticated_signer: None,
+        grant: Amount::ZERO,
+        refund_grant_to: None,
+        kind: MessageKind::Simple,
+        message: user_message(sender_app),
+    };
+    let event = Event {
+        stream_id: StreamId {
+            application_id: GenericApplicationId::User(emitter_app),
+            stream_name: StreamName(b"stream".to_vec()),
+        },
+        index: 0,
+        value: vec![],
+    };
+    let system_event = Event {
+        stream_id: StreamId::system("system"),
+        index: 0,
+        value: vec![],
+    };
+    let block = BlockExecutionOutcome {
+        messages: vec![vec![], vec![], vec![outgoing_message], vec![]],
+        previous_message_blocks: BTreeMap::new(),
+        state_hash: CryptoHash::test_hash("state"),
+        oracle_responses: vec![vec![]; 4],
+        events: vec![vec![], vec![], vec![event], vec![system_event]],
+        blobs: vec![vec![]; 4],
+        operation_results: vec![OperationResult::default(); 2],
+    }
+    .with(proposed_block);
+
+    assert_eq!(
+        block.changed_application_ids(),
+        BTreeSet::from([operation_app, accepted_app, sender_app, emitter_app])
+    );
+}
+
+#[test]
+fn test_paginate_limits_page_size() {
+    let items = (0..2000u64).collect::<Vec<_>>();
+    let entries = || items.iter().map(|item| (item.to_string(), item));
+
+    let page = paginate(entries(), None, None).unwrap();
+    assert_eq!(page.edges.len(), DEFAULT_PAGE_SIZE);
+    assert!(page.has_next_page);
+
+    let page = paginate(entries(), None, Some(usize::MAX)).unwrap();
+    assert_eq!(page.edges.len(), MAX_PAGE_SIZE);
+    assert!(page.has_next_page);
+
+    let page = paginate(entries(), Some("1994".to_string()), Some(10)).unwrap();
+    let cursors = page
+        .edges
+        .iter()
+        .map(|edge| edge.cursor.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(cursors, ["1995", "1996", "1997", "1998", "1999"]);
+    assert!(page.has_previous_page);
+    assert!(!page.has_next_page);
+
+    assert!(paginate(entries(), Some("2000".to_string()), None).is_err());
+}
@@ -3,14 +3,17 @@
 
 #[cfg(with_testing)]
 use std::num::NonZeroUsize;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use futures::Future;
 use linera_base::{
     crypto::{CryptoHash, Signer, ValidatorPublicKey},
     data_types::{BlockHeight, ChainDescription, Timestamp},
-    identifiers::{Account, AccountOwner, BlobId, BlobType, ChainId},
+    identifiers::{Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId},
     ownership::ChainOwnership,
     time::{Duration, Instant},
 };
@@ -35,7 +38,6 @@ use {
     linera_base::{
         crypto::AccountPublicKey,
         data_types::{Amount, Epoch},
-        identifiers::ApplicationId,
     },
     linera_core::client::ChainClientError,
     linera_execution::{
@@ -65,7 +67,8 @@ use crate::{
     chain_listener,
     client_options::{ChainOwnershipConfig, ClientContextOptions},
     config::WalletState,
-    error, util,
+    error,
+    util::{self, IdPrefix},
     wallet::{UserChain, Wallet},
     Error,
 };
@@ -262,6 +265,52 @@ where
             .expect("No chain specified in wallet with no default chain")
     }
 
+    /// Returns the chain of the wallet whose ID starts with `id`, or `id` itself if it is a
+    /// full chain ID.
+    pub fn resolve_chain_id(&self, id: &IdPrefix) -> Result<ChainId, Error> {
+        id.resolve("chain", self.wallet.chain_ids())
+    }
+
+    /// Returns the chain designated by `id` like [`ClientContext::resolve_chain_id`], or the
+    /// default chain if `id` is `None`.
+    pub fn resolve_chain_id_or_default(&self, id: Option<&IdPrefix>) -> Result<ChainId, Error> {
+        match id {
+            Some(id) => self.resolve_chain_id(id),
+            None => Ok(self.default_chain()),
+        }
+    }
+
+    /// Returns the applications whose IDs start with each of the `ids`, among the
+    /// applications known locally on the chains of the wallet. Full application IDs are
+    /// returned as is.
+    pub async fn resolve_application_ids(
+        &self,
+        ids: &[IdPrefix],
+    ) -> Result<Vec<ApplicationId>, Error> {
+        let mut known_application_ids = None;
+        let mut application_ids = Vec::with_capacity(ids.len());
+        for id in ids {
+            if !id.is_full() && known_application_ids.is_none() {
+                known_application_ids = Some(self.known_application_ids().await?);
+            }
+            let candidates = known_application_ids.iter().flatten().copied();
+            application_ids.push(id.resolve("application", candidates)?);
+        }
+        Ok(application_ids)
+    }
+
+    /// Returns the IDs of the applications registered on the chains of the wallet, as
+    /// stored locally.
+    async fn known_application_ids(&self) -> Result<BTreeSet<ApplicationId>, Error> {
+        let mut application_ids = BTreeSet::new();
+        for chain_id in self.wallet.chain_ids() {
+            let chain = self.client.storage_client().load_chain(chain_id).await?;
+            let applications = chain.execution_state.list_applications().await?;
+            application_ids.extend(applications.into_iter().map(|(id, _)| id));
+        }
+        Ok(application_ids)
+    }
+
     pub async fn make_chain_client(&self, chain_id: ChainId) -> Result<ChainClient<Env>, Error> {
         // We only create clients for chains we have in the wallet, or for the admin chain.
         let chain: UserChain = match self.wallet.get(chain_id) {
@@ -695,7 +744,7 @@ where
         .await?;
 
         info!("{}", "Data blob published successfully!");
-        Ok(CryptoHash::new(&BlobContent::new_data(blob_bytes)))
+        Ok(BlobContent::hash_data(&blob_bytes))
     }
 
     // TODO(#2490): Consider removing or renaming this.
@@ -6,7 +6,7 @@ use std::{collections::HashSet, fmt, iter, num::NonZeroUsize, path::PathBuf};
 use linera_base::{
     data_types::{ApplicationPermissions, TimeDelta},
     identifiers::{AccountOwner, ApplicationId, ChainId},
-    ownership::{ChainOwnership, TimeoutConfig},
+    ownership::{ChainOwnership, ChainOwnershipBuilder, OwnershipError, TimeoutConfig},
     time::Duration,
 };
 use linera_core::{client::BlanketMessagePolicy, DEFAULT_GRACE_PERIOD};
@@ -24,6 +24,8 @@ pub enum Error {
     NonexistentWallet,
     #[error("there are {public_keys} public keys but {weights} weights")]
     MisalignedWeights { public_keys: usize, weights: usize },
+    #[error("invalid chain ownership: {0}")]
+    Ownership(#[from] OwnershipError),
     #[error("persistence error: {0}")]
     Persistence(#[from] Box<dyn std::error::Error + Send + Sync>),
     #[error("config error: {0}")]
@@ -216,25 +218,27 @@ impl TryFrom<ChainOwnershipConfig> for ChainOwnership {
                 weights: owner_weights.len(),
             });
         }
-        let super_owners = super_owners.into_iter().collect();
-        let owners = owners
+        let mut builder = ChainOwnership::builder();
+        for owner in super_owners {
+            builder = builder.super_owner(owner);
+        }
+        let weights = owner_weights
             .into_iter()
-            .zip(owner_weights.into_iter().chain(iter::repeat(100)))
-            .collect();
-        let multi_leader_rounds = multi_leader_rounds.unwrap_or(u32::MAX);
+            .chain(iter::repeat(ChainOwnershipBuilder::DEFAULT_WEIGHT));
+        for (owner, weight) in owners.into_iter().zip(weights) {
+            builder = builder.owner_with_weight(owner, weight);
+        }
         let timeout_config = TimeoutConfig {
             fast_round_duration,
             base_timeout,
             timeout_increment,
             fallback_duration,
         };
-        Ok(ChainOwnership {
-            super_owners,
-            owners,
-            multi_leader_rounds,
-            open_multi_leader_rounds,
-            timeout_config,
-        })
+        Ok(builder
+            .multi_leader_rounds(multi_leader_rounds.unwrap_or(u32::MAX))
+            .open_multi_leader_rounds(open_multi_leader_rounds)
+            .timeout_config(timeout_config)
+            .build()?)
     }
 }
 
@@ -24,6 +24,8 @@ pub(crate) enum Inner {
     Persistence(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("view error: {0}")]
     View(#[from] linera_views::views::ViewError),
+    #[error("execution error: {0}")]
+    Execution(#[from] linera_execution::ExecutionError),
     #[error("non-existent chain: {0:?}")]
     NonexistentChain(linera_base::identifiers::ChainId),
     #[error("no keypair found for chain: {0:?}")]
@@ -36,6 +38,17 @@ pub(crate) enum Inner {
     Arithmetic(#[from] linera_base::data_types::ArithmeticError),
     #[error("incorrect chain ownership")]
     ChainOwnership,
+    #[error("no known {kind} ID starts with {prefix:?}")]
+    UnknownIdPrefix { kind: &'static str, prefix: String },
+    #[error(
+        "the {kind} ID prefix {prefix:?} is ambiguous; it matches: {}",
+        candidates.join(", ")
+    )]
+    AmbiguousIdPrefix {
+        kind: &'static str,
+        prefix: String,
+        candidates: Vec<String>,
+    },
     #[cfg(feature = "benchmark")]
     #[error("Benchmark error: {0}")]
     Benchmark(#[from] BenchmarkError),
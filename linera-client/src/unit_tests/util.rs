@@ -36,3 +36,66 @@ pub fn make_genesis_config(builder: &TestBuilder<MemoryStorageBuilder>) -> Genes
     genesis_config.chains.extend(builder.genesis_chains());
     genesis_config
 }
+
+#[test]
+fn test_id_prefix_resolution() {
+    use std::str::FromStr as _;
+
+    use linera_base::{crypto::CryptoHash, identifiers::ChainId};
+
+    use crate::util::IdPrefix;
+
+    let chain_ids = ["abc1", "abc2", "def3"].map(|name| ChainId(CryptoHash::test_hash(name)));
+    let prefix_of = |chain_id: &ChainId, length: usize| {
+        IdPrefix::from_str(&chain_id.to_string()[..length]).unwrap()
+    };
+
+    for chain_id in &chain_ids {
+        let full = prefix_of(chain_id, 64);
+        assert!(full.is_full());
+        assert_eq!(full.resolve("chain", chain_ids).unwrap(), *chain_id);
+        assert_eq!(full.resolve("chain", []).unwrap(), *chain_id);
+        let short = IdPrefix::from_str(&chain_id.short().to_uppercase()).unwrap();
+        assert!(!short.is_full());
+        assert_eq!(short.resolve("chain", chain_ids).unwrap(), *chain_id);
+    }
+
+    let unknown = IdPrefix::from_str(&chain_ids[0].short()).unwrap();
+    let error = unknown
+        .resolve("chain", chain_ids[1..].to_vec())
+        .unwrap_err();
+    assert!(error.to_string().contains("no known chain ID starts with"));
+
+    assert!(IdPrefix::from_str("").is_err());
+    assert!(IdPrefix::from_str("xyz").is_err());
+    assert!(IdPrefix::from_str(&"0".repeat(65)).is_err());
+
+    // A chain ID sharing the first 60 digits of another one makes these digits ambiguous.
+    let mut ambiguous = chain_ids.to_vec();
+    ambiguous.push(ChainId(
+        CryptoHash::from_str(&format!("{:.60}0000", chain_ids[0].0)).unwrap(),
+    ));
+    let error = prefix_of(&chain_ids[0], 60)
+        .resolve("chain", ambiguous)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("is ambiguous"), "{error}");
+    assert!(error.contains(&chain_ids[0].to_string()), "{error}");
+}
+
+#[test]
+fn test_parse_application_http_host() {
+    use linera_base::{crypto::CryptoHash, identifiers::ApplicationId};
+
+    use crate::util::parse_application_http_host;
+
+    let application_id = ApplicationId::new(CryptoHash::test_hash("application"));
+
+    assert_eq!(
+        parse_application_http_host(&format!("{application_id}=example.com")),
+        Ok((application_id, "example.com".to_owned()))
+    );
+    assert!(parse_application_http_host("example.com").is_err());
+    assert!(parse_application_http_host("xyz=example.com").is_err());
+    assert!(parse_application_http_host(&format!("{application_id}=")).is_err());
+}
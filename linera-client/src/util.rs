@@ -1,18 +1,25 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashSet, num::ParseIntError, str::FromStr};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+    num::ParseIntError,
+    str::FromStr,
+};
 
 use futures::future;
 use linera_base::{
     crypto::CryptoError,
     data_types::{TimeDelta, Timestamp},
-    identifiers::ChainId,
+    identifiers::{ApplicationId, ChainId},
     time::Duration,
 };
 use linera_core::{data_types::RoundTimeout, node::NotificationStream, worker::Reason};
 use tokio_stream::StreamExt as _;
 
+use crate::{error, Error};
+
 pub fn parse_millis(s: &str) -> Result<Duration, ParseIntError> {
     Ok(Duration::from_millis(s.parse()?))
 }
@@ -32,6 +39,92 @@ pub fn parse_chain_set(s: &str) -> Result<HashSet<ChainId>, CryptoError> {
     }
 }
 
+/// Parses an `APPLICATION_ID=HOST` pair, allowing an application to send HTTP requests to
+/// the host.
+pub fn parse_application_http_host(s: &str) -> Result<(ApplicationId, String), &'static str> {
+    let (application_id, host) = s
+        .split_once('=')
+        .ok_or("Expecting an application ID and a host, separated by `=`")?;
+    let application_id =
+        ApplicationId::from_str(application_id.trim()).map_err(|_| "Invalid application ID")?;
+    match host.trim() {
+        "" => Err("Expecting a host after `=`"),
+        host => Ok((application_id, host.to_owned())),
+    }
+}
+
+/// A chain or application ID given on the command line, possibly abbreviated to a prefix of
+/// its hexadecimal representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdPrefix(String);
+
+/// The number of hexadecimal digits of a full chain or application ID.
+const FULL_ID_LENGTH: usize = 64;
+
+impl FromStr for IdPrefix {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > FULL_ID_LENGTH {
+            return Err("Expecting between 1 and 64 hexadecimal digits");
+        }
+        if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Expecting hexadecimal digits");
+        }
+        Ok(IdPrefix(s.to_ascii_lowercase()))
+    }
+}
+
+impl fmt::Display for IdPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl IdPrefix {
+    /// Returns whether this is a full ID rather than a prefix.
+    pub fn is_full(&self) -> bool {
+        self.0.len() == FULL_ID_LENGTH
+    }
+
+    /// Returns the ID designated by this prefix among the `candidates`, whose kind (e.g.
+    /// `"chain"`) is used in error messages.
+    ///
+    /// A full ID is returned as is, even if it is not one of the `candidates`.
+    pub fn resolve<T>(
+        &self,
+        kind: &'static str,
+        candidates: impl IntoIterator<Item = T>,
+    ) -> Result<T, Error>
+    where
+        T: fmt::Display + FromStr + Ord,
+    {
+        if self.is_full() {
+            if let Ok(id) = T::from_str(&self.0) {
+                return Ok(id);
+            }
+        }
+        let mut matches = candidates
+            .into_iter()
+            .filter(|id| id.to_string().starts_with(&self.0))
+            .collect::<BTreeSet<_>>();
+        match matches.len() {
+            0 => Err(error::Inner::UnknownIdPrefix {
+                kind,
+                prefix: self.0.clone(),
+            }
+            .into()),
+            1 => Ok(matches.pop_first().expect("one match")),
+            _ => Err(error::Inner::AmbiguousIdPrefix {
+                kind,
+                prefix: self.0.clone(),
+                candidates: matches.iter().map(ToString::to_string).collect(),
+            }
+            .into()),
+        }
+    }
+}
+
 pub fn parse_ascii_alphanumeric_string(s: &str) -> Result<String, &'static str> {
     if s.chars().all(|x| x.is_ascii_alphanumeric()) {
         Ok(s.to_string())
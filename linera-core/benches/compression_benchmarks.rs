@@ -0,0 +1,115 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use linera_base::data_types::{Bytecode, CompressedBytecode};
+
+/// Compiled Wasm modules of the counter example application.
+const WASM_SAMPLES: [(&str, &[u8]); 2] = [
+    (
+        "counter_contract_wasm",
+        include_bytes!("../../linera-execution/tests/fixtures/counter_contract.wasm"),
+    ),
+    (
+        "counter_service_wasm",
+        include_bytes!("../../linera-execution/tests/fixtures/counter_service.wasm"),
+    ),
+];
+
+/// Compiled Solidity contracts, as JSON artifacts with the EVM bytecode.
+const EVM_SAMPLES: [(&str, &str); 2] = [
+    (
+        "simple_token_evm",
+        include_str!("../../linera-ethereum/contracts/SimpleToken.json"),
+    ),
+    (
+        "event_numerics_evm",
+        include_str!("../../linera-ethereum/contracts/EventNumerics.json"),
+    ),
+];
+
+/// Returns bytes that compress roughly like a Wasm module, to measure larger sizes.
+fn synthetic_bytecode(size: usize) -> Bytecode {
+    let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+    let mut state = 0x2545_f491_u32;
+    while bytes.len() < size {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        // Mostly a small set of opcodes, with some arbitrary immediates.
+        let byte = match state % 4 {
+            0 => (state >> 8) as u8,
+            _ => [0x20, 0x21, 0x41, 0x6a, 0x10, 0x0b][(state >> 8) as usize % 6],
+        };
+        bytes.push(byte);
+    }
+    Bytecode::new(bytes)
+}
+
+/// Returns the deployment bytecode of a compiled Solidity contract.
+fn evm_bytecode(artifact: &str) -> Bytecode {
+    let artifact = serde_json::from_str::<serde_json::Value>(artifact)
+        .expect("the artifact should be valid JSON");
+    let object = artifact["bytecode"]["object"]
+        .as_str()
+        .expect("the artifact should contain the bytecode");
+    let bytes = alloy_primitives::hex::decode(object).expect("the bytecode should be hexadecimal");
+    Bytecode::new(bytes)
+}
+
+/// Returns the named bytecode samples to benchmark.
+fn samples() -> Vec<(String, Bytecode)> {
+    let wasm = WASM_SAMPLES
+        .into_iter()
+        .map(|(name, bytes)| (name.to_string(), Bytecode::new(bytes.to_vec())));
+    let evm = EVM_SAMPLES
+        .into_iter()
+        .map(|(name, artifact)| (name.to_string(), evm_bytecode(artifact)));
+    let synthetic = [100_000, 1_000_000]
+        .into_iter()
+        .map(|size| (format!("synthetic_{size}"), synthetic_bytecode(size)));
+    wasm.chain(evm).chain(synthetic).collect()
+}
+
+fn compression_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bytecode_compression");
+    for (name, bytecode) in samples() {
+        let compressed = bytecode.compress();
+        let uncompressed = CompressedBytecode {
+            compressed_bytes: bytecode.bytes.clone(),
+        };
+        group.throughput(Throughput::Bytes(bytecode.bytes.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("compress", &name),
+            &bytecode,
+            |b, bytecode| b.iter(|| black_box(bytecode).compress()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("decompress", &name),
+            &compressed,
+            |b, compressed| b.iter(|| black_box(compressed).decompress().unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("decompressed_size", &name),
+            &compressed,
+            |b, compressed| {
+                b.iter(|| {
+                    CompressedBytecode::decompressed_size(
+                        black_box(&compressed.compressed_bytes),
+                        u64::MAX,
+                    )
+                    .unwrap()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("load_uncompressed", &name),
+            &uncompressed,
+            |b, uncompressed| b.iter(|| black_box(uncompressed).decompress().unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compression_benchmark);
+criterion_main!(benches);
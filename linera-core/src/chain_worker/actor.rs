@@ -12,7 +12,7 @@ use std::{
 use custom_debug_derive::Debug;
 use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
-    data_types::{ApplicationDescription, Blob, BlockHeight, Epoch, Timestamp},
+    data_types::{ApplicationDescription, Blob, BlockHeight, Epoch, Round, Timestamp},
     hashed::Hashed,
     identifiers::{ApplicationId, BlobId, ChainId},
 };
@@ -22,7 +22,7 @@ use linera_chain::{
     ChainStateView,
 };
 use linera_execution::{
-    ExecutionStateView, Query, QueryContext, QueryOutcome, ServiceRuntimeEndpoint,
+    ExecutionStateView, Query, QueryContext, QueryLimits, QueryOutcome, ServiceRuntimeEndpoint,
     ServiceSyncRuntime,
 };
 use linera_storage::Storage;
@@ -30,6 +30,8 @@ use tokio::sync::{mpsc, oneshot, OwnedRwLockReadGuard};
 use tracing::{debug, instrument, trace, warn, Instrument as _};
 
 use super::{config::ChainWorkerConfig, state::ChainWorkerState, DeliveryNotifier};
+#[cfg(with_metrics)]
+use crate::worker::CHAIN_WORKER_QUEUE_DEPTH;
 use crate::{
     data_types::{ChainInfoQuery, ChainInfoResponse},
     value_cache::ValueCache,
@@ -71,6 +73,7 @@ where
     /// Query an application's state.
     QueryApplication {
         query: Query,
+        limits: QueryLimits,
         #[debug(skip)]
         callback: oneshot::Sender<Result<QueryOutcome, WorkerError>>,
     },
@@ -85,7 +88,7 @@ where
     /// Execute a block but discard any changes to the chain state.
     StageBlockExecution {
         block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: Vec<Blob>,
         #[debug(skip)]
         callback: oneshot::Sender<Result<(Block, ChainInfoResponse), WorkerError>>,
@@ -215,7 +218,11 @@ where
             match load_result {
                 Ok(actor) => break actor,
                 Err(error) => match incoming_requests.recv().await {
-                    Some((request, _span)) => request.send_error(error),
+                    Some((request, _span)) => {
+                        #[cfg(with_metrics)]
+                        CHAIN_WORKER_QUEUE_DEPTH.with_label_values(&[]).dec();
+                        request.send_error(error)
+                    }
                     None => return,
                 },
             }
@@ -308,6 +315,8 @@ where
         trace!("Starting `ChainWorkerActor`");
 
         while let Some((request, span)) = incoming_requests.recv().await {
+            #[cfg(with_metrics)]
+            CHAIN_WORKER_QUEUE_DEPTH.with_label_values(&[]).dec();
             Box::pin(self.handle_request(request).instrument(span)).await;
         }
 
@@ -345,8 +354,12 @@ where
             ChainWorkerRequest::GetChainStateView { callback } => {
                 callback.send(self.worker.chain_state_view().await).is_ok()
             }
-            ChainWorkerRequest::QueryApplication { query, callback } => callback
-                .send(self.worker.query_application(query).await)
+            ChainWorkerRequest::QueryApplication {
+                query,
+                limits,
+                callback,
+            } => callback
+                .send(self.worker.query_application(query, limits).await)
                 .is_ok(),
             ChainWorkerRequest::DescribeApplication {
                 application_id,
@@ -371,7 +371,7 @@ where
                 .execute_block(
                     &proposed_block,
                     local_time,
-                    None,
+                    Some(certificate.round),
                     &published_blobs,
                     oracle_responses,
                 )
@@ -14,7 +14,7 @@ use std::{
 
 use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
-    data_types::{ApplicationDescription, Blob, BlockHeight, Epoch},
+    data_types::{ApplicationDescription, Blob, BlockHeight, Epoch, Round},
     ensure,
     hashed::Hashed,
     identifiers::{ApplicationId, BlobId, BlobType, ChainId},
@@ -26,7 +26,7 @@ use linera_chain::{
     ChainError, ChainStateView,
 };
 use linera_execution::{
-    ExecutionStateView, Query, QueryContext, QueryOutcome, ServiceRuntimeEndpoint,
+    ExecutionStateView, Query, QueryContext, QueryLimits, QueryOutcome, ServiceRuntimeEndpoint,
 };
 use linera_storage::{Clock as _, Storage};
 use linera_views::views::{ClonableView, ViewError};
@@ -81,6 +81,8 @@ where
         chain_id: ChainId,
         service_runtime_endpoint: Option<ServiceRuntimeEndpoint>,
     ) -> Result<Self, WorkerError> {
+        // The chain worker executes blocks, so it must not read from stale replicas.
+        let storage = storage.with_bounded_staleness(false);
         let chain = storage.load_chain(chain_id).await?;
 
         Ok(ChainWorkerState {
@@ -162,10 +164,11 @@ where
     pub(super) async fn query_application(
         &mut self,
         query: Query,
+        limits: QueryLimits,
     ) -> Result<QueryOutcome, WorkerError> {
         ChainWorkerStateWithTemporaryChanges::new(self)
             .await
-            .query_application(query)
+            .query_application(query, limits)
             .await
     }
 
@@ -184,7 +187,7 @@ where
     pub(super) async fn stage_block_execution(
         &mut self,
         block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: &[Blob],
     ) -> Result<(Block, ChainInfoResponse), WorkerError> {
         let (block, response) = ChainWorkerStateWithTemporaryChanges::new(self)
@@ -4,7 +4,7 @@
 //! Operations that don't persist any changes to the chain state.
 
 use linera_base::{
-    data_types::{ApplicationDescription, ArithmeticError, Blob, Timestamp},
+    data_types::{ApplicationDescription, ArithmeticError, Blob, Round, Timestamp},
     ensure,
     identifiers::{AccountOwner, ApplicationId},
 };
@@ -16,7 +16,7 @@ use linera_chain::{
     manager,
     types::Block,
 };
-use linera_execution::{Query, QueryOutcome};
+use linera_execution::{Query, QueryLimits, QueryOutcome};
 use linera_storage::{Clock as _, Storage};
 use linera_views::views::{ClonableView, View};
 #[cfg(with_testing)]
@@ -95,13 +95,19 @@ where
     pub(super) async fn query_application(
         &mut self,
         query: Query,
+        limits: QueryLimits,
     ) -> Result<QueryOutcome, WorkerError> {
         self.0.ensure_is_active().await?;
         let local_time = self.0.storage.clock().current_time();
         let outcome = self
             .0
             .chain
-            .query_application(local_time, query, self.0.service_runtime_endpoint.as_mut())
+            .query_application(
+                local_time,
+                query,
+                limits,
+                self.0.service_runtime_endpoint.as_mut(),
+            )
             .await?;
         Ok(outcome)
     }
@@ -120,7 +126,7 @@ where
     pub(super) async fn stage_block_execution(
         &mut self,
         block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: &[Blob],
     ) -> Result<(Block, ChainInfoResponse), WorkerError> {
         self.0.ensure_is_active().await?;
@@ -217,7 +223,7 @@ where
         let outcome = if let Some(outcome) = outcome {
             outcome.clone()
         } else {
-            self.execute_block(block, local_time, round.multi_leader(), published_blobs)
+            self.execute_block(block, local_time, Some(*round), published_blobs)
                 .await?
         };
 
@@ -314,7 +320,7 @@ where
         &mut self,
         block: &ProposedBlock,
         local_time: Timestamp,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: &[Blob],
     ) -> Result<BlockExecutionOutcome, WorkerError> {
         let outcome =
@@ -57,7 +57,8 @@ use linera_execution::{
         AdminOperation, OpenChainConfig, Recipient, SystemOperation, EPOCH_STREAM_NAME,
         REMOVED_EPOCH_STREAM_NAME,
     },
-    ExecutionError, Operation, Query, QueryOutcome, QueryResponse, SystemQuery, SystemResponse,
+    ExecutionError, Operation, Query, QueryLimits, QueryOutcome, QueryResponse, SystemQuery,
+    SystemResponse,
 };
 use linera_storage::{Clock as _, Storage as _};
 use linera_views::views::ViewError;
@@ -1807,6 +1808,24 @@ impl<Env: Environment> ChainClient<Env> {
         .await
     }
 
+    /// Sends money, with a memo that is stored in the operation.
+    #[instrument(level = "trace")]
+    pub async fn transfer_with_memo(
+        &self,
+        owner: AccountOwner,
+        amount: Amount,
+        recipient: Recipient,
+        memo: String,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, ChainClientError> {
+        self.execute_operation(SystemOperation::TransferWithMemo {
+            owner,
+            recipient,
+            amount,
+            memo,
+        })
+        .await
+    }
+
     /// Verify if a data blob is readable from storage.
     // TODO(#2490): Consider removing or renaming this.
     #[instrument(level = "trace")]
@@ -2170,7 +2189,7 @@ impl<Env: Environment> ChainClient<Env> {
     async fn stage_block_execution_and_discard_failing_messages(
         &self,
         mut block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: Vec<Blob>,
     ) -> Result<(Block, ChainInfoResponse), ChainClientError> {
         loop {
@@ -2215,7 +2234,7 @@ impl<Env: Environment> ChainClient<Env> {
     async fn stage_block_execution(
         &self,
         block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: Vec<Blob>,
     ) -> Result<(Block, ChainInfoResponse), ChainClientError> {
         loop {
@@ -2362,7 +2381,7 @@ impl<Env: Environment> ChainClient<Env> {
         // Accessing the round number in single-leader rounds where we are not the leader
         // is not currently supported.
         let round = match Self::round_for_new_proposal(&info, &identity, true)? {
-            Either::Left(round) => round.multi_leader(),
+            Either::Left(round) => Some(round),
             Either::Right(_) => None,
         };
         let (block, _) = self
@@ -2399,11 +2418,22 @@ impl<Env: Environment> ChainClient<Env> {
     /// Queries an application.
     #[instrument(level = "trace", skip(query))]
     pub async fn query_application(&self, query: Query) -> Result<QueryOutcome, ChainClientError> {
+        self.query_application_with_limits(query, QueryLimits::default())
+            .await
+    }
+
+    /// Queries an application, stopping its services once they exceed the given `limits`.
+    #[instrument(level = "trace", skip(query))]
+    pub async fn query_application_with_limits(
+        &self,
+        query: Query,
+        limits: QueryLimits,
+    ) -> Result<QueryOutcome, ChainClientError> {
         loop {
             let result = self
                 .client
                 .local_node
-                .query_application(self.chain_id, query.clone())
+                .query_application_with_limits(self.chain_id, query.clone(), limits)
                 .await;
             if let Err(LocalNodeError::BlobsNotFound(blob_ids)) = &result {
                 self.receive_certificates_for_blobs(blob_ids.clone())
@@ -2717,7 +2747,7 @@ impl<Env: Environment> ChainClient<Env> {
             // Using the round number during execution counts as an oracle.
             let proposed_block = pending_proposal.block;
             let round = match Self::round_for_new_proposal(&info, &owner, true)? {
-                Either::Left(round) => round.multi_leader(),
+                Either::Left(round) => Some(round),
                 Either::Right(_) => None,
             };
             let (block, _) = self
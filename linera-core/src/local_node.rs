@@ -10,7 +10,7 @@ use std::{
 use futures::{future::Either, stream, StreamExt as _, TryStreamExt as _};
 use linera_base::{
     crypto::ValidatorPublicKey,
-    data_types::{ApplicationDescription, ArithmeticError, Blob, BlockHeight},
+    data_types::{ApplicationDescription, ArithmeticError, Blob, BlockHeight, Round},
     identifiers::{ApplicationId, BlobId, ChainId},
 };
 use linera_chain::{
@@ -18,7 +18,7 @@ use linera_chain::{
     types::{Block, ConfirmedBlockCertificate, GenericCertificate, LiteCertificate},
     ChainStateView,
 };
-use linera_execution::{Query, QueryOutcome};
+use linera_execution::{Query, QueryLimits, QueryOutcome};
 use linera_storage::Storage;
 use linera_views::views::ViewError;
 use thiserror::Error;
@@ -175,7 +175,7 @@ where
     pub async fn stage_block_execution(
         &self,
         block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: Vec<Blob>,
     ) -> Result<(Block, ChainInfoResponse), LocalNodeError> {
         Ok(self
@@ -276,7 +276,22 @@ where
         chain_id: ChainId,
         query: Query,
     ) -> Result<QueryOutcome, LocalNodeError> {
-        let outcome = self.node.state.query_application(chain_id, query).await?;
+        self.query_application_with_limits(chain_id, query, QueryLimits::default())
+            .await
+    }
+
+    #[instrument(level = "trace", skip(self, query))]
+    pub async fn query_application_with_limits(
+        &self,
+        chain_id: ChainId,
+        query: Query,
+        limits: QueryLimits,
+    ) -> Result<QueryOutcome, LocalNodeError> {
+        let outcome = self
+            .node
+            .state
+            .query_application_with_limits(chain_id, query, limits)
+            .await?;
         Ok(outcome)
     }
 
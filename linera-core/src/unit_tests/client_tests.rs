@@ -26,6 +26,8 @@ use linera_execution::{
     ExecutionError, Message, MessageKind, Operation, QueryOutcome, ResourceControlPolicy,
     SystemMessage, SystemQuery, SystemResponse,
 };
+use linera_storage::{copy_chain, ChainCopySummary, Storage as _};
+use linera_views::views::CryptoHashView as _;
 use rand::Rng;
 use test_case::test_case;
 use test_helpers::{
@@ -2445,3 +2447,81 @@ where
     assert_eq!(client.local_balance().await.unwrap(), expected_balance);
     Ok(())
 }
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_copy_chain_between_storages<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, &mut signer).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let chain_id = sender.chain_id();
+    for _ in 0..3 {
+        sender
+            .transfer_to_account(
+                AccountOwner::CHAIN,
+                Amount::ONE,
+                Account::chain(receiver.chain_id()),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    let source = sender.storage_client();
+    let target = builder.make_storage().await?;
+
+    // Only copy the first two blocks: the chain state is left as it is.
+    let summary = copy_chain(source, &target, chain_id, Some(BlockHeight(1))).await?;
+    assert_eq!(summary.certificates_copied, 2);
+    assert!(!summary.chain_state_copied);
+    assert_eq!(
+        target
+            .load_chain(chain_id)
+            .await?
+            .tip_state
+            .get()
+            .next_block_height,
+        BlockHeight::ZERO
+    );
+
+    // Copying again resumes from the third block.
+    let summary = copy_chain(source, &target, chain_id, None).await?;
+    assert_eq!(summary.certificates_skipped, 2);
+    assert_eq!(summary.certificates_copied, 1);
+    assert!(summary.chain_state_copied);
+
+    let source_chain = source.load_chain(chain_id).await?;
+    let target_chain = target.load_chain(chain_id).await?;
+    assert_eq!(
+        source_chain.execution_state.crypto_hash().await?,
+        target_chain.execution_state.crypto_hash().await?
+    );
+    assert_eq!(source_chain.tip_state.get(), target_chain.tip_state.get());
+    let hashes = source_chain.confirmed_log.read(..).await?;
+    assert_eq!(hashes.len(), 3);
+    assert_eq!(hashes, target_chain.confirmed_log.read(..).await?);
+    assert_eq!(
+        source.read_certificates(hashes.clone()).await?,
+        target.read_certificates(hashes).await?
+    );
+
+    // Copying a chain that is already up to date only rewrites its state.
+    let summary = copy_chain(source, &target, chain_id, None).await?;
+    assert_eq!(
+        summary,
+        ChainCopySummary {
+            certificates_copied: 0,
+            certificates_skipped: 3,
+            blobs_copied: 0,
+            chain_state_copied: true,
+        }
+    );
+    Ok(())
+}
@@ -887,6 +887,7 @@ async fn test_memory_fuel_limit(wasm_runtime: WasmRuntime) -> anyhow::Result<()>
         blob_published: Amount::from_attos(100),
         blob_byte_read: Amount::from_tokens(10), // Should not be charged.
         blob_byte_published: Amount::from_attos(1),
+        bytecode_byte_published: Amount::from_attos(3),
         ..ResourceControlPolicy::default()
     };
     let mut keys = InMemorySigner::new(None);
@@ -898,13 +899,13 @@ async fn test_memory_fuel_limit(wasm_runtime: WasmRuntime) -> anyhow::Result<()>
     let (contract_path, service_path) =
         linera_execution::wasm_test::get_example_bytecode_paths("counter")?;
 
+    let contract_bytecode = Bytecode::load_from_file(contract_path).await?;
+    let service_bytecode = Bytecode::load_from_file(service_path).await?;
+    let decompressed_size = contract_bytecode.bytes.len() + service_bytecode.bytes.len();
+
     let mut expected_balance = publisher.local_balance().await?;
     let (module_id, _cert) = publisher
-        .publish_module(
-            Bytecode::load_from_file(contract_path).await?,
-            Bytecode::load_from_file(service_path).await?,
-            vm_runtime,
-        )
+        .publish_module(contract_bytecode, service_bytecode, vm_runtime)
         .await
         .unwrap()
         .unwrap();
@@ -922,7 +923,8 @@ async fn test_memory_fuel_limit(wasm_runtime: WasmRuntime) -> anyhow::Result<()>
         - policy.blob_published * 2
         - policy.blob_byte_published
             * (blobs.next().unwrap().bytes().len() as u128
-                + blobs.next().unwrap().bytes().len() as u128);
+                + blobs.next().unwrap().bytes().len() as u128)
+        - policy.bytecode_byte_published * decompressed_size as u128;
     assert_eq!(publisher.local_balance().await?, expected_balance);
 
     let initial_value = 10_u64;
@@ -16,7 +16,7 @@ use assert_matches::assert_matches;
 use linera_base::{
     crypto::AccountSecretKey,
     data_types::{
-        Amount, ApplicationDescription, Blob, BlockHeight, Bytecode, Epoch, OracleResponse,
+        Amount, ApplicationDescription, Blob, BlockHeight, Bytecode, Epoch, OracleResponse, Round,
         Timestamp,
     },
     identifiers::ModuleId,
@@ -272,7 +272,8 @@ where
         authenticated_signer: None,
         authenticated_caller_id: None,
         height: run_block.height,
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         timestamp: Timestamp::from(3),
     };
     let mut controller = ResourceController::default();
@@ -31,7 +31,7 @@ use linera_chain::{
     },
     ChainError, ChainStateView,
 };
-use linera_execution::{ExecutionError, ExecutionStateView, Query, QueryOutcome};
+use linera_execution::{ExecutionError, ExecutionStateView, Query, QueryLimits, QueryOutcome};
 use linera_storage::Storage;
 use linera_views::views::ViewError;
 use lru::LruCache;
@@ -43,8 +43,9 @@ use tracing::{error, instrument, trace, warn};
 use {
     linera_base::prometheus_util::{
         exponential_bucket_interval, register_histogram_vec, register_int_counter_vec,
+        register_int_gauge_vec,
     },
-    prometheus::{HistogramVec, IntCounterVec},
+    prometheus::{HistogramVec, IntCounterVec, IntGaugeVec},
     std::sync::LazyLock,
 };
 
@@ -98,6 +99,16 @@ static CERTIFICATES_SIGNED: LazyLock<IntCounterVec> = LazyLock::new(|| {
     )
 });
 
+/// The number of requests sent to chain workers that they haven't started handling yet.
+#[cfg(with_metrics)]
+pub(crate) static CHAIN_WORKER_QUEUE_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec(
+        "chain_worker_queue_depth",
+        "Number of requests waiting to be handled by the chain workers",
+        &[],
+    )
+});
+
 /// Instruct the networking layer to send cross-chain requests and/or push notifications.
 #[derive(Default, Debug)]
 pub struct NetworkActions {
@@ -278,7 +289,8 @@ where
 {
     /// A name used for logging
     nickname: String,
-    /// Access to local persistent storage.
+    /// Access to local persistent storage. Its reads are never served by stale read
+    /// replicas, since the worker checks which blobs and certificates exist with them.
     storage: StorageClient,
     /// Configuration options for the [`ChainWorker`]s.
     chain_worker_config: ChainWorkerConfig,
@@ -335,7 +347,7 @@ where
     ) -> Self {
         WorkerState {
             nickname,
-            storage,
+            storage: storage.with_bounded_staleness(false),
             chain_worker_config: ChainWorkerConfig::default().with_key_pair(key_pair),
             block_cache: Arc::new(ValueCache::default()),
             execution_state_cache: Arc::new(ValueCache::default()),
@@ -355,7 +367,7 @@ where
     ) -> Self {
         WorkerState {
             nickname,
-            storage,
+            storage: storage.with_bounded_staleness(false),
             chain_worker_config: ChainWorkerConfig::default(),
             block_cache: Arc::new(ValueCache::default()),
             execution_state_cache: Arc::new(ValueCache::default()),
@@ -425,6 +437,14 @@ where
         &self.storage
     }
 
+    /// Stops the [`ChainWorkerActor`]s, so that the chain states are loaded again from
+    /// storage, e.g. after tests replaced its data.
+    #[instrument(level = "trace", skip(self))]
+    #[cfg(with_testing)]
+    pub fn clear_chain_workers(&self) {
+        self.chain_workers.lock().unwrap().clear();
+    }
+
     #[instrument(level = "trace", skip(self, key_pair))]
     #[cfg(test)]
     pub(crate) async fn with_key_pair(mut self, key_pair: Option<Arc<ValidatorSecretKey>>) -> Self {
@@ -538,7 +558,7 @@ where
     pub async fn stage_block_execution(
         &self,
         block: ProposedBlock,
-        round: Option<u32>,
+        round: Option<Round>,
         published_blobs: Vec<Blob>,
     ) -> Result<(Block, ChainInfoResponse), WorkerError> {
         self.query_chain_worker(block.chain_id, move |callback| {
@@ -558,9 +578,26 @@ where
         &self,
         chain_id: ChainId,
         query: Query,
+    ) -> Result<QueryOutcome, WorkerError> {
+        self.query_application_with_limits(chain_id, query, QueryLimits::default())
+            .await
+    }
+
+    /// Executes a [`Query`] for an application's state on a specific chain, stopping the
+    /// services once they exceed the given `limits`.
+    #[instrument(level = "trace", skip(self, chain_id, query))]
+    pub async fn query_application_with_limits(
+        &self,
+        chain_id: ChainId,
+        query: Query,
+        limits: QueryLimits,
     ) -> Result<QueryOutcome, WorkerError> {
         self.query_chain_worker(chain_id, move |callback| {
-            ChainWorkerRequest::QueryApplication { query, callback }
+            ChainWorkerRequest::QueryApplication {
+                query,
+                limits,
+                callback,
+            }
         })
         .await
     }
@@ -700,6 +737,8 @@ where
         let chain_actor = self.get_chain_worker_endpoint(chain_id).await?;
         let (callback, response) = oneshot::channel();
 
+        #[cfg(with_metrics)]
+        CHAIN_WORKER_QUEUE_DEPTH.with_label_values(&[]).inc();
         chain_actor
             .send((request_builder(callback), tracing::Span::current()))
             .expect("`ChainWorkerActor` stopped executing unexpectedly");
@@ -5,7 +5,10 @@
 use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
 
 use async_graphql::InputObject;
-use linera_base::crypto::{AccountPublicKey, CryptoError, ValidatorPublicKey};
+use linera_base::{
+    crypto::{AccountPublicKey, CryptoError, ValidatorPublicKey},
+    data_types::CommitteeSummary,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::policy::ResourceControlPolicy;
@@ -300,6 +303,15 @@ impl Committee {
         self.total_votes
     }
 
+    /// Returns a [`CommitteeSummary`] with the size and voting thresholds of this committee.
+    pub fn summary(&self) -> CommitteeSummary {
+        CommitteeSummary {
+            validator_count: self.validators.len() as u32,
+            total_votes: self.total_votes,
+            quorum_threshold: self.quorum_threshold,
+        }
+    }
+
     pub fn policy(&self) -> &ResourceControlPolicy {
         &self.policy
     }
@@ -8,7 +8,7 @@ use std::{
 
 use futures::{FutureExt, StreamExt};
 use linera_base::{
-    data_types::{Amount, BlockHeight, StreamUpdate},
+    data_types::{Amount, BlockHeight, Round, StreamUpdate},
     identifiers::{Account, AccountOwner, StreamId},
 };
 use linera_views::{
@@ -34,8 +34,8 @@ use crate::{
     resources::ResourceController, system::SystemExecutionStateView, ApplicationDescription,
     ApplicationId, ContractSyncRuntime, ExecutionError, ExecutionRuntimeConfig,
     ExecutionRuntimeContext, Message, MessageContext, MessageKind, Operation, OperationContext,
-    OutgoingMessage, ProcessStreamsContext, Query, QueryContext, QueryOutcome, ServiceSyncRuntime,
-    SystemMessage, Timestamp, TransactionTracker,
+    OutgoingMessage, ProcessStreamsContext, Query, QueryContext, QueryLimits, QueryOutcome,
+    ServiceSyncRuntime, SystemMessage, Timestamp, TransactionTracker,
 };
 
 /// A view accessing the execution state of a chain.
@@ -80,6 +80,7 @@ where
             authenticated_caller_id: None,
             height: application_description.block_height,
             round: None,
+            block_proposer: None,
             timestamp: local_time,
         };
 
@@ -165,7 +166,7 @@ impl UserAction {
         }
     }
 
-    pub(crate) fn round(&self) -> Option<u32> {
+    pub(crate) fn round(&self) -> Option<Round> {
         match self {
             UserAction::Instantiate(context, _) => context.round,
             UserAction::Operation(context, _) => context.round,
@@ -174,6 +175,15 @@ impl UserAction {
         }
     }
 
+    pub(crate) fn block_proposer(&self) -> Option<AccountOwner> {
+        match self {
+            UserAction::Instantiate(context, _) => context.block_proposer,
+            UserAction::Operation(context, _) => context.block_proposer,
+            UserAction::ProcessStreams(context, _) => context.block_proposer,
+            UserAction::Message(context, _) => context.block_proposer,
+        }
+    }
+
     pub(crate) fn timestamp(&self) -> Timestamp {
         match self {
             UserAction::Instantiate(context, _) => context.timestamp,
@@ -198,7 +208,10 @@ where
         txn_tracker: &mut TransactionTracker,
         resource_controller: &mut ResourceController<Option<AccountOwner>>,
     ) -> Result<(), ExecutionError> {
-        let ExecutionRuntimeConfig {} = self.context().extra().execution_runtime_config();
+        let ExecutionRuntimeConfig {
+            #[cfg(with_testing)]
+                http_request_handler: _,
+        } = self.context().extra().execution_runtime_config();
         self.run_user_action_with_runtime(
             application_id,
             action,
@@ -254,8 +267,10 @@ where
 
         contract_runtime_task.send(code)?;
 
+        let mut checkpoints = Vec::new();
         while let Some(request) = execution_state_receiver.next().await {
-            self.handle_request(request, resource_controller).await?;
+            self.handle_request(request, resource_controller, &mut checkpoints)
+                .await?;
         }
 
         let (result, controller, txn_tracker_moved) = contract_runtime_task.join().await?;
@@ -403,6 +418,18 @@ where
         context: QueryContext,
         query: Query,
         endpoint: Option<&mut ServiceRuntimeEndpoint>,
+    ) -> Result<QueryOutcome, ExecutionError> {
+        self.query_application_with_limits(context, query, QueryLimits::default(), endpoint)
+            .await
+    }
+
+    /// Queries an application, stopping its services once they exceed the given `limits`.
+    pub async fn query_application_with_limits(
+        &mut self,
+        context: QueryContext,
+        query: Query,
+        limits: QueryLimits,
+        endpoint: Option<&mut ServiceRuntimeEndpoint>,
     ) -> Result<QueryOutcome, ExecutionError> {
         assert_eq!(context.chain_id, self.context().extra().chain_id());
         match query {
@@ -414,12 +441,16 @@ where
                 application_id,
                 bytes,
             } => {
-                let ExecutionRuntimeConfig {} = self.context().extra().execution_runtime_config();
+                let ExecutionRuntimeConfig {
+                    #[cfg(with_testing)]
+                        http_request_handler: _,
+                } = self.context().extra().execution_runtime_config();
                 let outcome = match endpoint {
                     Some(endpoint) => {
                         self.query_user_application_with_long_lived_service(
                             application_id,
                             context,
+                            limits,
                             bytes,
                             &mut endpoint.incoming_execution_requests,
                             &mut endpoint.runtime_request_sender,
@@ -427,7 +458,7 @@ where
                         .await?
                     }
                     None => {
-                        self.query_user_application(application_id, context, bytes)
+                        self.query_user_application(application_id, context, limits, bytes)
                             .await?
                     }
                 };
@@ -440,6 +471,7 @@ where
         &mut self,
         application_id: ApplicationId,
         context: QueryContext,
+        limits: QueryLimits,
         query: Vec<u8>,
     ) -> Result<QueryOutcome<Vec<u8>>, ExecutionError> {
         let (execution_state_sender, mut execution_state_receiver) =
@@ -452,6 +484,7 @@ where
             async move {
                 let code = codes.next().await.expect("we send this immediately below");
                 runtime.preload_service(application_id, code, description)?;
+                runtime.set_query_limits(limits);
                 runtime.run_query(application_id, query)
             }
         })
@@ -460,7 +493,7 @@ where
         service_runtime_task.send(code)?;
 
         while let Some(request) = execution_state_receiver.next().await {
-            self.handle_request(request, &mut ResourceController::default())
+            self.handle_request(request, &mut ResourceController::default(), &mut Vec::new())
                 .await?;
         }
 
@@ -471,6 +504,7 @@ where
         &mut self,
         application_id: ApplicationId,
         context: QueryContext,
+        limits: QueryLimits,
         query: Vec<u8>,
         incoming_execution_requests: &mut futures::channel::mpsc::UnboundedReceiver<
             ExecutionRequest,
@@ -484,6 +518,7 @@ where
             .send(ServiceRuntimeRequest::Query {
                 application_id,
                 context,
+                limits,
                 query,
                 callback: outcome_sender,
             })
@@ -493,7 +528,12 @@ where
             futures::select! {
                 maybe_request = incoming_execution_requests.next() => {
                     if let Some(request) = maybe_request {
-                        self.handle_request(request, &mut ResourceController::default()).await?;
+                        self.handle_request(
+                            request,
+                            &mut ResourceController::default(),
+                            &mut Vec::new(),
+                        )
+                        .await?;
                     }
                 }
                 outcome = &mut outcome_receiver => {
@@ -503,6 +543,15 @@ where
         }
     }
 
+    /// Starts reading the states of the given applications in the background, if the
+    /// storage has a cache.
+    pub fn prefetch_application_states<'a>(
+        &self,
+        application_ids: impl IntoIterator<Item = &'a ApplicationId>,
+    ) -> Result<(), ExecutionError> {
+        Ok(self.users.prefetch_entries(application_ids)?)
+    }
+
     pub async fn list_applications(
         &self,
     ) -> Result<Vec<(ApplicationId, ApplicationDescription)>, ExecutionError> {
@@ -3,26 +3,34 @@
 
 //! Handle requests from the synchronous execution thread of user applications.
 
+use std::ops::Range;
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
 #[cfg(not(web))]
 use std::time::Duration;
 
 use custom_debug_derive::Debug;
-use futures::{channel::mpsc, StreamExt as _};
+use futures::{channel::mpsc, future, stream, StreamExt as _, TryStreamExt as _};
 #[cfg(with_metrics)]
 use linera_base::prometheus_util::{
     exponential_bucket_latencies, register_histogram_vec, MeasureLatency as _,
 };
 use linera_base::{
+    crypto::CryptoHash,
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, BlobContent, BlockHeight, Timestamp,
+        Amount, ApplicationDescriptionSummary, ApplicationPermissions, ArithmeticError,
+        BlobContent, BlockHeaderSummary, BlockHeight, CommitteeSummary, Epoch, Timestamp,
     },
     ensure, hex_debug, hex_vec_debug, http,
     identifiers::{Account, AccountOwner, BlobId, BlobType, ChainId, EventId, StreamId},
     ownership::ChainOwnership,
 };
-use linera_views::{batch::Batch, context::Context, views::View};
+use linera_views::{
+    batch::Batch,
+    common::paginate,
+    context::Context,
+    views::{ClonableView as _, View},
+};
 use oneshot::Sender;
 #[cfg(with_metrics)]
 use prometheus::HistogramVec;
@@ -32,8 +40,8 @@ use crate::{
     system::{CreateApplicationResult, OpenChainConfig, Recipient},
     util::RespondExt,
     ApplicationDescription, ApplicationId, ExecutionError, ExecutionRuntimeContext,
-    ExecutionStateView, ModuleId, OutgoingMessage, ResourceController, TransactionTracker,
-    UserContractCode, UserServiceCode,
+    ExecutionStateView, ModuleId, OutgoingMessage, ResourceController, ResourceTracker,
+    TransactionTracker, UserContractCode, UserServiceCode, MAX_REGISTERED_APPLICATIONS_PER_PAGE,
 };
 
 #[cfg(with_metrics)]
@@ -60,6 +68,15 @@ static LOAD_SERVICE_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
 
 pub(crate) type ExecutionStateSender = mpsc::UnboundedSender<ExecutionRequest>;
 
+/// A copy of the execution state saved before a cross-application call, so that the call's
+/// changes can be rolled back.
+pub(crate) struct ExecutionStateCheckpoint<C> {
+    /// The execution state before the call.
+    state: ExecutionStateView<C>,
+    /// The resources paid for by the execution state before the call.
+    tracker: ResourceTracker,
+}
+
 impl<C> ExecutionStateView<C>
 where
     C: Context + Clone + Send + Sync + 'static,
@@ -123,6 +140,7 @@ where
         &mut self,
         request: ExecutionRequest,
         resource_controller: &mut ResourceController<Option<AccountOwner>>,
+        checkpoints: &mut Vec<ExecutionStateCheckpoint<C>>,
     ) -> Result<(), ExecutionError> {
         use ExecutionRequest::*;
         match request {
@@ -141,8 +159,8 @@ where
                 callback,
                 mut txn_tracker,
             } => {
-                let (code, description) = self.load_service(id, Some(&mut txn_tracker)).await?;
-                callback.respond((code, description, txn_tracker))
+                let result = self.load_service(id, Some(&mut txn_tracker)).await;
+                callback.respond((result, txn_tracker))
             }
 
             ChainBalance { callback } => {
@@ -160,6 +178,17 @@ where
                 callback.respond(balances.into_iter().collect());
             }
 
+            OwnerBalancesFor { owners, callback } => {
+                let balances = future::try_join_all(
+                    owners.iter().map(|owner| self.system.balances.get(owner)),
+                )
+                .await?
+                .into_iter()
+                .map(Option::unwrap_or_default)
+                .collect();
+                callback.respond(balances);
+            }
+
             BalanceOwners { callback } => {
                 let owners = self.system.balances.indices().await?;
                 callback.respond(owners);
@@ -172,17 +201,24 @@ where
                 signer,
                 application_id,
                 callback,
-            } => callback.respond(
-                self.system
-                    .transfer(
-                        signer,
-                        Some(application_id),
-                        source,
-                        Recipient::Account(destination),
-                        amount,
-                    )
-                    .await?,
-            ),
+            } => match self
+                .system
+                .transfer(
+                    signer,
+                    Some(application_id),
+                    source,
+                    Recipient::Account(destination),
+                    amount,
+                )
+                .await
+            {
+                Ok(maybe_message) => callback.respond(Ok(maybe_message)),
+                Err(
+                    error @ (ExecutionError::UnauthenticatedTransferOwner
+                    | ExecutionError::InsufficientFunding { .. }),
+                ) => callback.respond(Err(error)),
+                Err(error) => return Err(error),
+            },
 
             Claim {
                 source,
@@ -191,18 +227,24 @@ where
                 signer,
                 application_id,
                 callback,
-            } => callback.respond(
-                self.system
-                    .claim(
-                        signer,
-                        Some(application_id),
-                        source.owner,
-                        source.chain_id,
-                        Recipient::Account(destination),
-                        amount,
-                    )
-                    .await?,
-            ),
+            } => match self
+                .system
+                .claim(
+                    signer,
+                    Some(application_id),
+                    source.owner,
+                    source.chain_id,
+                    Recipient::Account(destination),
+                    amount,
+                )
+                .await
+            {
+                Ok(message) => callback.respond(Ok(message)),
+                Err(error @ ExecutionError::UnauthenticatedClaimOwner) => {
+                    callback.respond(Err(error))
+                }
+                Err(error) => return Err(error),
+            },
 
             SystemTimestamp { callback } => {
                 let timestamp = *self.system.timestamp.get();
@@ -214,6 +256,19 @@ where
                 callback.respond(ownership);
             }
 
+            Epoch { callback } => {
+                let epoch = (*self.system.epoch.get()).ok_or(ExecutionError::InactiveChain)?;
+                callback.respond(epoch);
+            }
+
+            CurrentCommittee { callback } => {
+                let (_epoch, committee) = self
+                    .system
+                    .current_committee()
+                    .ok_or(ExecutionError::InactiveChain)?;
+                callback.respond(committee.summary());
+            }
+
             ContainsKey { id, key, callback } => {
                 let view = self.users.try_load_entry(&id).await?;
                 let result = match view {
@@ -276,6 +331,72 @@ where
                 callback.respond(result);
             }
 
+            FindKeysByPrefixPaginated {
+                id,
+                key_prefix,
+                start_after,
+                limit,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                // One more key is read, to know if there is another page.
+                let keys = match view {
+                    Some(view) => {
+                        view.find_keys_by_prefix_after(
+                            &key_prefix,
+                            start_after.as_deref(),
+                            limit as usize + 1,
+                        )
+                        .await?
+                    }
+                    None => Vec::new(),
+                };
+                let loaded_bytes = keys.iter().map(Vec::len).sum::<usize>() as u64;
+                let (keys, continuation) =
+                    paginate(keys, Vec::as_slice, start_after.as_deref(), limit);
+                callback.respond((keys, continuation, loaded_bytes));
+            }
+
+            FindKeyValuesByPrefixPaginated {
+                id,
+                key_prefix,
+                start_after,
+                limit,
+                callback,
+            } => {
+                let Some(view) = self.users.try_load_entry(&id).await? else {
+                    callback.respond((Vec::new(), None, 0));
+                    return Ok(());
+                };
+                // Only the values in the page are loaded, with one more key to know if there
+                // is another page.
+                let keys = view
+                    .find_keys_by_prefix_after(
+                        &key_prefix,
+                        start_after.as_deref(),
+                        limit as usize + 1,
+                    )
+                    .await?;
+                let mut loaded_bytes = keys.iter().map(Vec::len).sum::<usize>() as u64;
+                let (keys, continuation) =
+                    paginate(keys, Vec::as_slice, start_after.as_deref(), limit);
+                let full_keys = keys
+                    .iter()
+                    .map(|key| [key_prefix.as_slice(), key].concat())
+                    .collect();
+                let values = view.multi_get(full_keys).await?;
+                let key_values = keys
+                    .into_iter()
+                    .zip(values)
+                    .filter_map(|(key, value)| Some((key, value?)))
+                    .collect::<Vec<_>>();
+                loaded_bytes += key_values
+                    .iter()
+                    .map(|(_, value)| value.len())
+                    .sum::<usize>() as u64;
+                callback.respond((key_values, continuation, loaded_bytes));
+            }
+
             WriteBatch {
                 id,
                 batch,
@@ -315,6 +436,8 @@ where
                 let app_permissions = self.system.application_permissions.get();
                 if !app_permissions.can_close_chain(&application_id) {
                     callback.respond(Err(ExecutionError::UnauthorizedApplication(application_id)));
+                } else if *self.system.closed.get() {
+                    callback.respond(Err(ExecutionError::ChainAlreadyClosed));
                 } else {
                     self.system.close_chain().await?;
                     callback.respond(Ok(()));
@@ -360,70 +483,138 @@ where
                 callback.respond(Ok(create_application_result));
             }
 
+            AuthorizeHttpRequest {
+                application_id,
+                url,
+                callback,
+            } => {
+                callback.respond(self.authorize_http_request(application_id, &url));
+            }
+
             PerformHttpRequest {
                 request,
+                options,
                 http_responses_are_oracle_responses,
                 callback,
             } => {
-                let headers = request
-                    .headers
-                    .into_iter()
-                    .map(|http::Header { name, value }| Ok((name.parse()?, value.try_into()?)))
-                    .collect::<Result<HeaderMap, ExecutionError>>()?;
-
-                let url = Url::parse(&request.url)?;
-                let host = url
-                    .host_str()
-                    .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
+                callback.respond(
+                    self.perform_http_request(
+                        request,
+                        options,
+                        http_responses_are_oracle_responses,
+                    )
+                    .await?,
+                );
+            }
 
-                let (_epoch, committee) = self
+            PerformHttpRequests {
+                requests,
+                http_responses_are_oracle_responses,
+                callback,
+            } => {
+                let concurrency_limit = self
                     .system
                     .current_committee()
-                    .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
-                let allowed_hosts = &committee.policy().http_request_allow_list;
-
-                ensure!(
-                    allowed_hosts.contains(host),
-                    ExecutionError::UnauthorizedHttpRequest(url)
-                );
+                    .map_or(1, |(_epoch, committee)| {
+                        committee.policy().maximum_concurrent_http_requests
+                    });
+                let concurrency_limit = usize::try_from(concurrency_limit)
+                    .unwrap_or(usize::MAX)
+                    .max(1);
+                // `buffered` keeps the responses in the same order as the requests.
+                let responses = stream::iter(requests)
+                    .map(|request| {
+                        self.perform_http_request(
+                            request,
+                            http::RequestOptions::default(),
+                            http_responses_are_oracle_responses,
+                        )
+                    })
+                    .buffered(concurrency_limit)
+                    .try_collect()
+                    .await?;
+                callback.respond(responses);
+            }
 
-                #[cfg_attr(web, allow(unused_mut))]
-                let mut request = Client::new()
-                    .request(request.method.into(), url)
-                    .body(request.body)
-                    .headers(headers);
-                #[cfg(not(web))]
-                {
-                    request = request.timeout(Duration::from_millis(
-                        committee.policy().http_request_timeout_ms,
-                    ));
+            ReadBlobContent { blob_id, callback } => {
+                let blob = self.system.read_blob_content(blob_id).await?;
+                if blob_id.blob_type == BlobType::Data {
+                    resource_controller
+                        .with_state(&mut self.system)
+                        .await?
+                        .track_blob_read(blob.bytes().len() as u64)?;
                 }
+                let is_new = self.system.blob_used(None, blob_id).await?;
+                callback.respond((blob, is_new))
+            }
 
-                let response = request.send().await?;
-
-                let mut response_size_limit = committee.policy().maximum_http_response_bytes;
-
-                if http_responses_are_oracle_responses {
-                    response_size_limit =
-                        response_size_limit.min(committee.policy().maximum_oracle_response_bytes);
+            ReadBlobContents { blob_ids, callback } => {
+                let results = future::join_all(
+                    blob_ids
+                        .iter()
+                        .map(|blob_id| self.system.read_blob_content(*blob_id)),
+                )
+                .await;
+                let mut blobs = Vec::with_capacity(blob_ids.len());
+                for (blob_id, result) in blob_ids.into_iter().zip(results) {
+                    let blob = match result {
+                        Ok(blob) => blob,
+                        Err(ExecutionError::BlobsNotFound(_)) => {
+                            blobs.push(None);
+                            continue;
+                        }
+                        Err(error) => return Err(error),
+                    };
+                    if blob_id.blob_type == BlobType::Data {
+                        resource_controller
+                            .with_state(&mut self.system)
+                            .await?
+                            .track_blob_read(blob.bytes().len() as u64)?;
+                    }
+                    let is_new = self.system.blob_used(None, blob_id).await?;
+                    blobs.push(Some((blob, is_new)));
                 }
+                callback.respond(blobs)
+            }
 
-                callback.respond(
-                    self.receive_http_response(response, response_size_limit)
-                        .await?,
-                );
+            ReadBlobRange {
+                blob_id,
+                offset,
+                length,
+                callback,
+            } => {
+                let blob = self.system.read_blob_content(blob_id).await?;
+                let bytes = blob.bytes();
+                let start = usize::try_from(offset)
+                    .unwrap_or(usize::MAX)
+                    .min(bytes.len());
+                let end = start
+                    .saturating_add(usize::try_from(length).unwrap_or(usize::MAX))
+                    .min(bytes.len());
+                let range = bytes[start..end].to_vec();
+                // The whole blob is loaded from storage, so it is charged in full.
+                if blob_id.blob_type == BlobType::Data {
+                    resource_controller
+                        .with_state(&mut self.system)
+                        .await?
+                        .track_blob_read(bytes.len() as u64)?;
+                }
+                let is_new = self.system.blob_used(None, blob_id).await?;
+                callback.respond((range, is_new))
             }
 
-            ReadBlobContent { blob_id, callback } => {
+            BlobSize { blob_id, callback } => {
                 let blob = self.system.read_blob_content(blob_id).await?;
+                let size = blob.bytes().len() as u64;
+                // The whole blob is loaded from storage, so it is charged in full.
                 if blob_id.blob_type == BlobType::Data {
                     resource_controller
                         .with_state(&mut self.system)
                         .await?
-                        .track_blob_read(blob.bytes().len() as u64)?;
+                        .track_blob_read(size)?;
                 }
                 let is_new = self.system.blob_used(None, blob_id).await?;
-                callback.respond((blob, is_new))
+                callback.respond((size, is_new))
             }
 
             AssertBlobExists { blob_id, callback } => {
@@ -438,6 +629,23 @@ where
                 callback.respond(self.system.blob_used(None, blob_id).await?)
             }
 
+            BlobExists { blob_id, callback } => {
+                match self.system.assert_blob_exists(blob_id).await {
+                    Ok(()) => {
+                        // Treating this as reading a size-0 blob for fee purposes.
+                        if blob_id.blob_type == BlobType::Data {
+                            resource_controller
+                                .with_state(&mut self.system)
+                                .await?
+                                .track_blob_read(0)?;
+                        }
+                        callback.respond(Some(self.system.blob_used(None, blob_id).await?))
+                    }
+                    Err(ExecutionError::BlobsNotFound(_)) => callback.respond(None),
+                    Err(error) => return Err(error),
+                }
+            }
+
             NextEventIndex {
                 stream_id,
                 callback,
@@ -451,11 +659,60 @@ where
                 callback.respond(index)
             }
 
+            StreamEventCount {
+                chain_id,
+                stream_id,
+                callback,
+            } => {
+                let count = if chain_id == self.context().extra().chain_id() {
+                    self.stream_event_counts.get(&stream_id).await?
+                } else {
+                    self.system
+                        .event_subscriptions
+                        .get(&(chain_id, stream_id))
+                        .await?
+                        .map(|subscriptions| subscriptions.next_index)
+                };
+                callback.respond(count.unwrap_or(0));
+            }
+
             ReadEvent { event_id, callback } => {
                 let event_value = self.context().extra().get_event(event_id).await?;
                 callback.respond(event_value);
             }
 
+            BlockHashes { range, callback } => {
+                let hashes = self.context().extra().get_block_hashes(range).await?;
+                callback.respond(hashes);
+            }
+
+            BlockHeader { height, callback } => {
+                let hashes = self
+                    .context()
+                    .extra()
+                    .get_block_hashes(height..height.try_add_one()?)
+                    .await?;
+                let header = match hashes.first() {
+                    Some(hash) => Some(self.context().extra().get_block_header(*hash).await?),
+                    None => None,
+                };
+                callback.respond(header);
+            }
+
+            RegisteredApplications {
+                start_after,
+                callback,
+            } => {
+                let applications = self
+                    .system
+                    .registered_applications(start_after, MAX_REGISTERED_APPLICATIONS_PER_PAGE)
+                    .await?
+                    .iter()
+                    .map(ApplicationDescription::summary)
+                    .collect();
+                callback.respond(applications);
+            }
+
             SubscribeToEvents {
                 chain_id,
                 stream_id,
@@ -498,6 +755,35 @@ where
                 let app_permissions = self.system.application_permissions.get();
                 callback.respond(app_permissions.clone());
             }
+
+            SaveCheckpoint { callback } => {
+                checkpoints.push(ExecutionStateCheckpoint {
+                    state: self.clone_unchecked()?,
+                    tracker: resource_controller.tracker,
+                });
+                callback.respond(());
+            }
+
+            RollBackToCheckpoint { callback } => {
+                let checkpoint = checkpoints.pop().ok_or(ExecutionError::InternalError(
+                    "No execution state checkpoint to roll back to",
+                ))?;
+                *self = checkpoint.state;
+                // The blobs read since the checkpoint are still tracked, so they must be paid
+                // for again from the restored balances.
+                resource_controller
+                    .with_state(&mut self.system)
+                    .await?
+                    .track_blob_reads_since(&checkpoint.tracker)?;
+                callback.respond(());
+            }
+
+            DiscardCheckpoint { callback } => {
+                checkpoints.pop().ok_or(ExecutionError::InternalError(
+                    "No execution state checkpoint to discard",
+                ))?;
+                callback.respond(());
+            }
         }
 
         Ok(())
@@ -509,13 +795,122 @@ where
     C: Context + Clone + Send + Sync + 'static,
     C::Extra: ExecutionRuntimeContext,
 {
+    /// Checks that the resource control policy allows the application to send an HTTP request
+    /// to the host of `url`.
+    ///
+    /// This only depends on the current committee, so validators replaying a block reach the
+    /// same result as the proposer.
+    fn authorize_http_request(
+        &self,
+        application_id: ApplicationId,
+        url: &str,
+    ) -> Result<(), ExecutionError> {
+        let url = Url::parse(url)?;
+        let (_epoch, committee) = self
+            .system
+            .current_committee()
+            .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
+
+        ensure!(
+            committee
+                .policy()
+                .is_http_host_allowed(&application_id, host),
+            ExecutionError::UnauthorizedHttpRequest(url)
+        );
+        Ok(())
+    }
+
+    /// Performs an HTTP request, enforcing the resource control policy and the limits from
+    /// `options`.
+    ///
+    /// The request must have been authorized with [`Self::authorize_http_request`] first. In
+    /// tests, if the HTTP request handler of the execution runtime answers the request, the
+    /// response is obtained from it instead of the network.
+    async fn perform_http_request(
+        &self,
+        request: http::Request,
+        options: http::RequestOptions,
+        http_responses_are_oracle_responses: bool,
+    ) -> Result<http::Response, ExecutionError> {
+        let url = Url::parse(&request.url)?;
+
+        let (_epoch, committee) = self
+            .system
+            .current_committee()
+            .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
+
+        let timeout_ms = options
+            .timeout_ms
+            .map_or(committee.policy().http_request_timeout_ms, |timeout_ms| {
+                timeout_ms.min(committee.policy().http_request_timeout_ms)
+            });
+
+        let mut response_size_limit = committee.policy().maximum_http_response_bytes;
+
+        if http_responses_are_oracle_responses {
+            response_size_limit =
+                response_size_limit.min(committee.policy().maximum_oracle_response_bytes);
+        }
+        if let Some(max_response_bytes) = options.max_response_bytes {
+            response_size_limit = response_size_limit.min(max_response_bytes);
+        }
+
+        #[cfg(with_testing)]
+        if let Some(response) = self
+            .context()
+            .extra()
+            .execution_runtime_config()
+            .http_request_handler
+            .and_then(|handler| handler(&request))
+        {
+            let response = response?;
+            let size = response.size();
+            ensure!(
+                size <= response_size_limit,
+                ExecutionError::HttpResponseSizeLimitExceeded {
+                    limit: response_size_limit,
+                    size,
+                }
+            );
+            return Ok(response);
+        }
+
+        let headers = request
+            .headers
+            .into_iter()
+            .map(|http::Header { name, value }| Ok((name.parse()?, value.try_into()?)))
+            .collect::<Result<HeaderMap, ExecutionError>>()?;
+
+        #[cfg_attr(web, allow(unused_mut))]
+        let mut request = Client::new()
+            .request(request.method.into(), url)
+            .body(request.body)
+            .headers(headers);
+        #[cfg(not(web))]
+        {
+            request = request.timeout(Duration::from_millis(timeout_ms));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|error| Self::http_error(error, timeout_ms))?;
+
+        self.receive_http_response(response, response_size_limit, timeout_ms)
+            .await
+    }
+
     /// Receives an HTTP response, returning the prepared [`http::Response`] instance.
     ///
     /// Ensures that the response does not exceed the provided `size_limit`.
     async fn receive_http_response(
-        &mut self,
+        &self,
         response: reqwest::Response,
         size_limit: u64,
+        timeout_ms: u64,
     ) -> Result<http::Response, ExecutionError> {
         let status = response.status().as_u16();
         let maybe_content_length = response.content_length();
@@ -550,7 +945,12 @@ where
         let mut body = Vec::with_capacity(maybe_content_length.unwrap_or(0) as usize);
         let mut body_stream = response.bytes_stream();
 
-        while let Some(bytes) = body_stream.next().await.transpose()? {
+        while let Some(bytes) = body_stream
+            .next()
+            .await
+            .transpose()
+            .map_err(|error| Self::http_error(error, timeout_ms))?
+        {
             remaining_bytes = remaining_bytes.checked_sub(bytes.len() as u64).ok_or(
                 ExecutionError::HttpResponseSizeLimitExceeded {
                     limit: size_limit,
@@ -567,6 +967,15 @@ where
             body,
         })
     }
+
+    /// Converts a [`reqwest::Error`] into an [`ExecutionError`], reporting timeouts separately.
+    fn http_error(error: reqwest::Error, timeout_ms: u64) -> ExecutionError {
+        if error.is_timeout() {
+            ExecutionError::HttpRequestTimedOut { timeout_ms }
+        } else {
+            error.into()
+        }
+    }
 }
 
 /// Requests to the execution state.
@@ -585,7 +994,10 @@ pub enum ExecutionRequest {
     LoadService {
         id: ApplicationId,
         #[debug(skip)]
-        callback: Sender<(UserServiceCode, ApplicationDescription, TransactionTracker)>,
+        callback: Sender<(
+            Result<(UserServiceCode, ApplicationDescription), ExecutionError>,
+            TransactionTracker,
+        )>,
         #[debug(skip)]
         txn_tracker: TransactionTracker,
     },
@@ -606,6 +1018,12 @@ pub enum ExecutionRequest {
         callback: Sender<Vec<(AccountOwner, Amount)>>,
     },
 
+    OwnerBalancesFor {
+        owners: Vec<AccountOwner>,
+        #[debug(skip)]
+        callback: Sender<Vec<Amount>>,
+    },
+
     BalanceOwners {
         #[debug(skip)]
         callback: Sender<Vec<AccountOwner>>,
@@ -619,7 +1037,7 @@ pub enum ExecutionRequest {
         signer: Option<AccountOwner>,
         application_id: ApplicationId,
         #[debug(skip)]
-        callback: Sender<Option<OutgoingMessage>>,
+        callback: Sender<Result<Option<OutgoingMessage>, ExecutionError>>,
     },
 
     Claim {
@@ -630,7 +1048,7 @@ pub enum ExecutionRequest {
         signer: Option<AccountOwner>,
         application_id: ApplicationId,
         #[debug(skip)]
-        callback: Sender<OutgoingMessage>,
+        callback: Sender<Result<OutgoingMessage, ExecutionError>>,
     },
 
     SystemTimestamp {
@@ -643,6 +1061,16 @@ pub enum ExecutionRequest {
         callback: Sender<ChainOwnership>,
     },
 
+    Epoch {
+        #[debug(skip)]
+        callback: Sender<Epoch>,
+    },
+
+    CurrentCommittee {
+        #[debug(skip)]
+        callback: Sender<CommitteeSummary>,
+    },
+
     ReadValueBytes {
         id: ApplicationId,
         #[debug(with = hex_debug)]
@@ -689,6 +1117,31 @@ pub enum ExecutionRequest {
         callback: Sender<Vec<(Vec<u8>, Vec<u8>)>>,
     },
 
+    FindKeysByPrefixPaginated {
+        id: ApplicationId,
+        #[debug(with = hex_debug)]
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+        /// The keys in the page, the key to continue from, and the number of bytes loaded.
+        #[debug(skip)]
+        #[expect(clippy::type_complexity)]
+        callback: Sender<(Vec<Vec<u8>>, Option<Vec<u8>>, u64)>,
+    },
+
+    FindKeyValuesByPrefixPaginated {
+        id: ApplicationId,
+        #[debug(with = hex_debug)]
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+        /// The key-value pairs in the page, the key to continue from, and the number of bytes
+        /// loaded.
+        #[debug(skip)]
+        #[expect(clippy::type_complexity)]
+        callback: Sender<(Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>, u64)>,
+    },
+
     WriteBatch {
         id: ApplicationId,
         batch: Batch,
@@ -735,36 +1188,103 @@ pub enum ExecutionRequest {
         callback: Sender<Result<CreateApplicationResult, ExecutionError>>,
     },
 
+    AuthorizeHttpRequest {
+        application_id: ApplicationId,
+        url: String,
+        #[debug(skip)]
+        callback: Sender<Result<(), ExecutionError>>,
+    },
+
     PerformHttpRequest {
         request: http::Request,
+        options: http::RequestOptions,
         http_responses_are_oracle_responses: bool,
         #[debug(skip)]
         callback: Sender<http::Response>,
     },
 
+    PerformHttpRequests {
+        requests: Vec<http::Request>,
+        http_responses_are_oracle_responses: bool,
+        #[debug(skip)]
+        callback: Sender<Vec<http::Response>>,
+    },
+
     ReadBlobContent {
         blob_id: BlobId,
         #[debug(skip)]
         callback: Sender<(BlobContent, bool)>,
     },
 
+    ReadBlobContents {
+        blob_ids: Vec<BlobId>,
+        #[debug(skip)]
+        callback: Sender<Vec<Option<(BlobContent, bool)>>>,
+    },
+
+    ReadBlobRange {
+        blob_id: BlobId,
+        offset: u64,
+        length: u64,
+        #[debug(skip)]
+        callback: Sender<(Vec<u8>, bool)>,
+    },
+
+    BlobSize {
+        blob_id: BlobId,
+        #[debug(skip)]
+        callback: Sender<(u64, bool)>,
+    },
+
     AssertBlobExists {
         blob_id: BlobId,
         #[debug(skip)]
         callback: Sender<bool>,
     },
 
+    BlobExists {
+        blob_id: BlobId,
+        #[debug(skip)]
+        callback: Sender<Option<bool>>,
+    },
+
     NextEventIndex {
         stream_id: StreamId,
         #[debug(skip)]
         callback: Sender<u32>,
     },
 
+    StreamEventCount {
+        chain_id: ChainId,
+        stream_id: StreamId,
+        #[debug(skip)]
+        callback: Sender<u32>,
+    },
+
     ReadEvent {
         event_id: EventId,
         callback: oneshot::Sender<Vec<u8>>,
     },
 
+    BlockHashes {
+        range: Range<BlockHeight>,
+        #[debug(skip)]
+        callback: Sender<Vec<CryptoHash>>,
+    },
+
+    BlockHeader {
+        height: BlockHeight,
+        #[debug(skip)]
+        callback: Sender<Option<BlockHeaderSummary>>,
+    },
+
+    RegisteredApplications {
+        #[debug(skip_if = Option::is_none)]
+        start_after: Option<ApplicationId>,
+        #[debug(skip)]
+        callback: Sender<Vec<ApplicationDescriptionSummary>>,
+    },
+
     SubscribeToEvents {
         chain_id: ChainId,
         stream_id: StreamId,
@@ -785,4 +1305,19 @@ pub enum ExecutionRequest {
         #[debug(skip)]
         callback: Sender<ApplicationPermissions>,
     },
+
+    SaveCheckpoint {
+        #[debug(skip)]
+        callback: Sender<()>,
+    },
+
+    RollBackToCheckpoint {
+        #[debug(skip)]
+        callback: Sender<()>,
+    },
+
+    DiscardCheckpoint {
+        #[debug(skip)]
+        callback: Sender<()>,
+    },
 }
@@ -22,7 +22,7 @@ mod transaction_tracker;
 mod util;
 mod wasm;
 
-use std::{any::Any, fmt, str::FromStr, sync::Arc};
+use std::{any::Any, fmt, ops::Range, str::FromStr, sync::Arc};
 
 use async_graphql::SimpleObject;
 use async_trait::async_trait;
@@ -35,8 +35,9 @@ use linera_base::{
     abi::Abi,
     crypto::{BcsHashable, CryptoHash},
     data_types::{
-        Amount, ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlockHeight,
-        DecompressionError, Epoch, SendMessageRequest, StreamUpdate, Timestamp,
+        Amount, ApplicationDescription, ApplicationDescriptionSummary, ApplicationPermissions,
+        ArithmeticError, Blob, BlockHeaderSummary, BlockHeight, CalleeError, CommitteeSummary,
+        DecompressionError, Epoch, Resources, Round, SendMessageRequest, StreamUpdate, Timestamp,
     },
     doc_scalar, hex_debug, http,
     identifiers::{
@@ -45,6 +46,7 @@ use linera_base::{
     },
     ownership::ChainOwnership,
     task,
+    time::Duration,
     vm::VmRuntime,
 };
 use linera_views::{batch::Batch, views::ViewError};
@@ -84,6 +86,15 @@ pub const LINERA_SOL: &str = include_str!("../solidity/linera.sol");
 /// The maximum length of a stream name.
 const MAX_STREAM_NAME_LEN: usize = 64;
 
+/// The maximum length of the memo of a transfer, in bytes.
+pub const MAX_TRANSFER_MEMO_LEN: usize = 256;
+
+/// The maximum number of block hashes returned by a single query for block hashes.
+pub const MAX_BLOCK_HASHES_PER_QUERY: u64 = 1_000;
+
+/// The maximum number of applications returned by a single query for registered applications.
+const MAX_REGISTERED_APPLICATIONS_PER_PAGE: usize = 100;
+
 /// An implementation of [`UserContractModule`].
 #[derive(Clone)]
 pub struct UserContractCode(Box<dyn UserContractModule>);
@@ -238,6 +249,8 @@ pub enum ExecutionError {
     BlockTooLarge,
     #[error("HTTP response exceeds the size limit of {limit} bytes, having at least {size} bytes")]
     HttpResponseSizeLimitExceeded { limit: u64, size: u64 },
+    #[error("HTTP request timed out after {timeout_ms} ms")]
+    HttpRequestTimedOut { timeout_ms: u64 },
     #[error("Runtime failed to respond to application")]
     MissingRuntimeResponse,
     #[error("Module ID {0:?} is invalid")]
@@ -246,6 +259,8 @@ pub enum ExecutionError {
     OwnerIsNone,
     #[error("Application is not authorized to perform system operations on this chain: {0:}")]
     UnauthorizedApplication(ApplicationId),
+    #[error("The chain is already closed")]
+    ChainAlreadyClosed,
     #[error("Failed to make network reqwest: {0}")]
     ReqwestError(#[from] reqwest::Error),
     #[error("Encountered I/O error: {0}")]
@@ -264,6 +279,10 @@ pub enum ExecutionError {
         local_time: Timestamp,
     },
 
+    #[error("The query timed out")]
+    QueryTimedOut,
+    #[error("The query ran out of fuel")]
+    QueryOutOfFuel,
     #[error("Stream names can be at most {MAX_STREAM_NAME_LEN} bytes.")]
     StreamNameTooLong,
     #[error("Blob exceeds size limit")]
@@ -272,6 +291,8 @@ pub enum ExecutionError {
     BytecodeTooLarge,
     #[error("Attempt to perform an HTTP request to an unauthorized host: {0:?}")]
     UnauthorizedHttpRequest(reqwest::Url),
+    #[error("No mocked response for the HTTP request to {0}")]
+    UnexpectedHttpRequest(String),
     #[error("Attempt to perform an HTTP request to an invalid URL")]
     InvalidUrlForHttpRequest(#[from] url::ParseError),
     #[error("Failed to send contract code to worker thread: {0:?}")]
@@ -294,6 +315,8 @@ pub enum ExecutionError {
     InvalidEpoch { chain_id: ChainId, epoch: Epoch },
     #[error("Transfer must have positive amount")]
     IncorrectTransferAmount,
+    #[error("Transfer memos can be at most {MAX_TRANSFER_MEMO_LEN} bytes.")]
+    TransferMemoTooLong,
     #[error("Transfer from owned account must be authenticated by the right signer")]
     UnauthenticatedTransferOwner,
     #[error("The transferred amount must not exceed the balance of the current account {account}: {balance}")]
@@ -372,9 +395,20 @@ pub trait UserService {
     fn handle_query(&mut self, argument: Vec<u8>) -> Result<Vec<u8>, ExecutionError>;
 }
 
+/// A function answering the HTTP requests of applications in place of the network. It
+/// returns `None` for the requests that should be sent over the network.
+#[cfg(with_testing)]
+pub type HttpRequestHandler =
+    Arc<dyn Fn(&http::Request) -> Option<Result<http::Response, ExecutionError>> + Send + Sync>;
+
 /// Configuration options for the execution runtime available to applications.
-#[derive(Clone, Copy, Default)]
-pub struct ExecutionRuntimeConfig {}
+#[derive(Clone, Default)]
+pub struct ExecutionRuntimeConfig {
+    /// Answers the HTTP requests of applications instead of sending them, so that tests
+    /// can run without network access.
+    #[cfg(with_testing)]
+    pub http_request_handler: Option<HttpRequestHandler>,
+}
 
 /// Requirements for the `extra` field in our state views (and notably the
 /// [`ExecutionStateView`]).
@@ -407,6 +441,16 @@ pub trait ExecutionRuntimeContext {
 
     async fn contains_event(&self, event_id: EventId) -> Result<bool, ViewError>;
 
+    /// Returns the hashes of the chain's confirmed blocks with heights in `range`, stopping at
+    /// the last confirmed block.
+    async fn get_block_hashes(
+        &self,
+        range: Range<BlockHeight>,
+    ) -> Result<Vec<CryptoHash>, ViewError>;
+
+    /// Returns a summary of the header of the confirmed block with the given hash.
+    async fn get_block_header(&self, hash: CryptoHash) -> Result<BlockHeaderSummary, ViewError>;
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
@@ -433,8 +477,11 @@ pub struct OperationContext {
     pub authenticated_caller_id: Option<ApplicationId>,
     /// The current block height.
     pub height: BlockHeight,
-    /// The consensus round number, if this is a block that gets validated in a multi-leader round.
-    pub round: Option<u32>,
+    /// The consensus round, if this is a block that gets validated in a known round.
+    pub round: Option<Round>,
+    /// The owner who proposed the block, i.e. its authenticated signer, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub block_proposer: Option<AccountOwner>,
     /// The timestamp of the block containing the operation.
     pub timestamp: Timestamp,
 }
@@ -453,8 +500,11 @@ pub struct MessageContext {
     pub refund_grant_to: Option<Account>,
     /// The current block height.
     pub height: BlockHeight,
-    /// The consensus round number, if this is a block that gets validated in a multi-leader round.
-    pub round: Option<u32>,
+    /// The consensus round, if this is a block that gets validated in a known round.
+    pub round: Option<Round>,
+    /// The owner who proposed the block, i.e. its authenticated signer, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub block_proposer: Option<AccountOwner>,
     /// The timestamp of the block executing the message.
     pub timestamp: Timestamp,
     /// The ID of the message (based on the operation height and index in the remote
@@ -468,8 +518,11 @@ pub struct ProcessStreamsContext {
     pub chain_id: ChainId,
     /// The current block height.
     pub height: BlockHeight,
-    /// The consensus round number, if this is a block that gets validated in a multi-leader round.
-    pub round: Option<u32>,
+    /// The consensus round, if this is a block that gets validated in a known round.
+    pub round: Option<Round>,
+    /// The owner who proposed the block, i.e. its authenticated signer, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub block_proposer: Option<AccountOwner>,
     /// The timestamp of the current block.
     pub timestamp: Timestamp,
 }
@@ -480,6 +533,7 @@ impl From<MessageContext> for ProcessStreamsContext {
             chain_id: context.chain_id,
             height: context.height,
             round: context.round,
+            block_proposer: context.block_proposer,
             timestamp: context.timestamp,
         }
     }
@@ -491,6 +545,7 @@ impl From<OperationContext> for ProcessStreamsContext {
             chain_id: context.chain_id,
             height: context.height,
             round: context.round,
+            block_proposer: context.block_proposer,
             timestamp: context.timestamp,
         }
     }
@@ -505,8 +560,8 @@ pub struct FinalizeContext {
     pub authenticated_signer: Option<AccountOwner>,
     /// The current block height.
     pub height: BlockHeight,
-    /// The consensus round number, if this is a block that gets validated in a multi-leader round.
-    pub round: Option<u32>,
+    /// The consensus round, if this is a block that gets validated in a known round.
+    pub round: Option<Round>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -519,6 +574,17 @@ pub struct QueryContext {
     pub local_time: Timestamp,
 }
 
+/// Limits on the execution of the services answering a query, so that a misbehaving service
+/// cannot keep the chain worker busy forever. The services stop cooperatively once a limit
+/// is reached.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QueryLimits {
+    /// How long the services may run, if limited.
+    pub timeout: Option<Duration>,
+    /// How much Wasm fuel the services may consume, if limited.
+    pub fuel: Option<u64>,
+}
+
 pub trait BaseRuntime {
     type Read: fmt::Debug + Send + Sync;
     type ContainsKey: fmt::Debug + Send + Sync;
@@ -527,6 +593,8 @@ pub trait BaseRuntime {
     type ReadValueBytes: fmt::Debug + Send + Sync;
     type FindKeysByPrefix: fmt::Debug + Send + Sync;
     type FindKeyValuesByPrefix: fmt::Debug + Send + Sync;
+    type FindKeysByPrefixPaginated: fmt::Debug + Send + Sync;
+    type FindKeyValuesByPrefixPaginated: fmt::Debug + Send + Sync;
 
     /// The current chain ID.
     fn chain_id(&mut self) -> Result<ChainId, ExecutionError>;
@@ -546,6 +614,19 @@ pub trait BaseRuntime {
     /// Reads the system timestamp.
     fn read_system_timestamp(&mut self) -> Result<Timestamp, ExecutionError>;
 
+    /// Returns the consensus round in which the current block is validated, if known.
+    ///
+    /// The round is not part of the block, so outside of the fast round it is recorded as an
+    /// oracle response. In the fast round, this returns [`Round::Fast`] without recording
+    /// anything. Services always get `None`.
+    fn round(&mut self) -> Result<Option<Round>, ExecutionError>;
+
+    /// Returns the owner who proposed the current block, i.e. the block's authenticated signer.
+    ///
+    /// This is part of the block itself, so it is not an oracle and can be used in fast rounds.
+    /// Services always get `None`.
+    fn block_proposer(&mut self) -> Result<Option<AccountOwner>, ExecutionError>;
+
     /// Reads the balance of the chain.
     fn read_chain_balance(&mut self) -> Result<Amount, ExecutionError>;
 
@@ -555,12 +636,27 @@ pub trait BaseRuntime {
     /// Reads the balances from all owners.
     fn read_owner_balances(&mut self) -> Result<Vec<(AccountOwner, Amount)>, ExecutionError>;
 
+    /// Reads the balances of the given owners, in the same order.
+    fn read_owner_balances_for(
+        &mut self,
+        owners: &[AccountOwner],
+    ) -> Result<Vec<Amount>, ExecutionError>;
+
     /// Reads balance owners.
     fn read_balance_owners(&mut self) -> Result<Vec<AccountOwner>, ExecutionError>;
 
     /// Reads the current ownership configuration for this chain.
     fn chain_ownership(&mut self) -> Result<ChainOwnership, ExecutionError>;
 
+    /// Reads the current application permissions for this chain.
+    fn application_permissions(&mut self) -> Result<ApplicationPermissions, ExecutionError>;
+
+    /// Reads the current epoch of this chain.
+    fn read_epoch(&mut self) -> Result<Epoch, ExecutionError>;
+
+    /// Reads a summary of the committee in charge of the current epoch.
+    fn read_current_committee(&mut self) -> Result<CommitteeSummary, ExecutionError>;
+
     /// Tests whether a key exists in the key-value store
     #[cfg(feature = "test")]
     fn contains_key(&mut self, key: Vec<u8>) -> Result<bool, ExecutionError> {
@@ -670,12 +766,66 @@ pub trait BaseRuntime {
         promise: &Self::FindKeyValuesByPrefix,
     ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ExecutionError>;
 
+    /// Creates the promise to access at most `limit` keys having a specific prefix, starting
+    /// strictly after the `start_after` key suffix
+    fn find_keys_by_prefix_paginated_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<Self::FindKeysByPrefixPaginated, ExecutionError>;
+
+    /// Resolves the promise to access a page of keys having a specific prefix, returning the
+    /// key to continue from if more keys remain
+    #[expect(clippy::type_complexity)]
+    fn find_keys_by_prefix_paginated_wait(
+        &mut self,
+        promise: &Self::FindKeysByPrefixPaginated,
+    ) -> Result<(Vec<Vec<u8>>, Option<Vec<u8>>), ExecutionError>;
+
+    /// Creates the promise to access at most `limit` key/values having a specific prefix,
+    /// starting strictly after the `start_after` key suffix
+    fn find_key_values_by_prefix_paginated_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<Self::FindKeyValuesByPrefixPaginated, ExecutionError>;
+
+    /// Resolves the promise to access a page of key/values having a specific prefix, returning
+    /// the key to continue from if more key/values remain
+    #[expect(clippy::type_complexity)]
+    fn find_key_values_by_prefix_paginated_wait(
+        &mut self,
+        promise: &Self::FindKeyValuesByPrefixPaginated,
+    ) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>), ExecutionError>;
+
     /// Makes an HTTP request to the given URL and returns the answer, if any.
     fn perform_http_request(
         &mut self,
         request: http::Request,
     ) -> Result<http::Response, ExecutionError>;
 
+    /// Makes an HTTP request to the given URL with the limits from `options`, and returns the
+    /// answer, if any.
+    ///
+    /// The limits are capped by the resource control policy. Fails with
+    /// [`ExecutionError::HttpRequestTimedOut`] or [`ExecutionError::HttpResponseSizeLimitExceeded`]
+    /// if they are exceeded, and with [`ExecutionError::UnauthorizedHttpRequest`] if the policy
+    /// doesn't allow the application to send requests to the URL's host.
+    fn perform_http_request_with_options(
+        &mut self,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<http::Response, ExecutionError>;
+
+    /// Makes several HTTP requests, which the host may perform concurrently, and returns the
+    /// answers in the same order as the requests.
+    fn perform_http_requests(
+        &mut self,
+        requests: Vec<http::Request>,
+    ) -> Result<Vec<http::Response>, ExecutionError>;
+
     /// Ensures that the current time at block validation is `< timestamp`. Note that block
     /// validation happens at or after the block timestamp, but isn't necessarily the same.
     ///
@@ -686,8 +836,38 @@ pub trait BaseRuntime {
     /// Reads a data blob specified by a given hash.
     fn read_data_blob(&mut self, hash: &CryptoHash) -> Result<Vec<u8>, ExecutionError>;
 
+    /// Reads the data blobs specified by the given hashes, in order.
+    ///
+    /// Fails with [`ExecutionError::BlobsNotFound`] listing all missing blobs if any of them
+    /// don't exist.
+    fn read_data_blobs(&mut self, hashes: &[CryptoHash]) -> Result<Vec<Vec<u8>>, ExecutionError>;
+
+    /// Reads up to `length` bytes starting at `offset` from the data blob with the given hash.
+    ///
+    /// The range is truncated at the end of the blob, so the result may be shorter than
+    /// `length`, and is empty if `offset` is past the end. The fee is the same as for reading
+    /// the whole blob.
+    fn read_data_blob_range(
+        &mut self,
+        hash: &CryptoHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, ExecutionError>;
+
+    /// Returns the size in bytes of the data blob with the given hash.
+    ///
+    /// The fee is the same as for reading the whole blob.
+    fn data_blob_size(&mut self, hash: &CryptoHash) -> Result<u64, ExecutionError>;
+
     /// Asserts the existence of a data blob with the given hash.
     fn assert_data_blob_exists(&mut self, hash: &CryptoHash) -> Result<(), ExecutionError>;
+
+    /// Returns whether the data blob specified by a given hash exists in storage.
+    ///
+    /// If it exists, the blob is used the same way as by [`Self::assert_data_blob_exists`].
+    /// Otherwise, that it is missing is recorded as an oracle response, so that the block is
+    /// replayed the same way even if the blob is published later.
+    fn data_blob_exists(&mut self, hash: &CryptoHash) -> Result<bool, ExecutionError>;
 }
 
 pub trait ServiceRuntime: BaseRuntime {
@@ -698,11 +878,74 @@ pub trait ServiceRuntime: BaseRuntime {
         argument: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError>;
 
+    /// Reads the data blobs specified by the given hashes, in order, returning `None` for
+    /// the ones that don't exist.
+    fn try_read_data_blobs(
+        &mut self,
+        hashes: &[CryptoHash],
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError>;
+
+    /// Reads an event from one of the given application's streams. Returns the event's value.
+    ///
+    /// Returns an error if the event doesn't exist.
+    fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: StreamName,
+        index: u32,
+    ) -> Result<Vec<u8>, ExecutionError>;
+
+    /// Reads the events with indices from `start` (inclusive) to `end` (exclusive) from one of
+    /// the given application's streams.
+    ///
+    /// Returns an error if any of the events doesn't exist.
+    fn read_events_in_range(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: StreamName,
+        start: u32,
+        end: u32,
+    ) -> Result<Vec<Vec<u8>>, ExecutionError>;
+
+    /// Returns the hashes of this chain's confirmed blocks with heights from `start` (inclusive)
+    /// to `end` (exclusive), stopping at the last confirmed block and after at most
+    /// [`MAX_BLOCK_HASHES_PER_QUERY`] blocks.
+    fn block_hashes(
+        &mut self,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, ExecutionError>;
+
+    /// Returns a summary of the header of this chain's confirmed block at `height`, if any.
+    fn block_header(
+        &mut self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockHeaderSummary>, ExecutionError>;
+
+    /// Returns a page of summaries of the applications registered on this chain, sorted by
+    /// application ID and starting after `start_after`, if provided.
+    fn registered_applications(
+        &mut self,
+        start_after: Option<ApplicationId>,
+    ) -> Result<Vec<ApplicationDescriptionSummary>, ExecutionError>;
+
     /// Schedules an operation to be included in the block proposed after execution.
-    fn schedule_operation(&mut self, operation: Vec<u8>) -> Result<(), ExecutionError>;
+    ///
+    /// Returns the zero-based index of the operation among all the operations scheduled during
+    /// this query, including those scheduled by other applications queried in the process.
+    fn schedule_operation(&mut self, operation: Vec<u8>) -> Result<u32, ExecutionError>;
+
+    /// Returns the operations the current application has scheduled so far during this query,
+    /// in the order they were scheduled.
+    fn scheduled_operations(&mut self) -> Result<Vec<Vec<u8>>, ExecutionError>;
 
     /// Checks if the service has exceeded its execution time limit.
     fn check_execution_time(&mut self) -> Result<(), ExecutionError>;
+
+    /// Consumes some of the fuel of the current query, failing if it runs out.
+    fn consume_query_fuel(&mut self, fuel: u64) -> Result<(), ExecutionError>;
 }
 
 pub trait ContractRuntime: BaseRuntime {
@@ -732,6 +975,19 @@ pub trait ContractRuntime: BaseRuntime {
     /// Schedules a message to be sent.
     fn send_message(&mut self, message: SendMessageRequest<Vec<u8>>) -> Result<(), ExecutionError>;
 
+    /// Schedules the same `message` to be sent to each of the `destinations`.
+    ///
+    /// Each copy is a separate outgoing message: it is charged as such, and forwards its own
+    /// `grant`.
+    fn send_message_to_many(
+        &mut self,
+        destinations: Vec<ChainId>,
+        authenticated: bool,
+        is_tracked: bool,
+        grant: Resources,
+        message: Vec<u8>,
+    ) -> Result<(), ExecutionError>;
+
     /// Transfers amount from source to destination.
     fn transfer(
         &mut self,
@@ -757,12 +1013,26 @@ pub trait ContractRuntime: BaseRuntime {
         argument: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError>;
 
+    /// Calls another application, rolling back all the changes made during the call if the
+    /// callee traps.
+    ///
+    /// The call can only be rolled back if it doesn't execute any application that was already
+    /// loaded in this transaction, because the in-memory state of that application can't be
+    /// restored. Otherwise, or if the call fails for another reason, the error is returned as
+    /// is. The resources consumed by a call that is rolled back remain charged.
+    fn try_call_application_with_rollback(
+        &mut self,
+        authenticated: bool,
+        callee_id: ApplicationId,
+        argument: Vec<u8>,
+    ) -> Result<Result<Vec<u8>, CalleeError>, ExecutionError>;
+
     /// Adds a new item to an event stream. Returns the new event's index in the stream.
     fn emit(&mut self, name: StreamName, value: Vec<u8>) -> Result<u32, ExecutionError>;
 
     /// Reads an event from a stream. Returns the event's value.
     ///
-    /// Returns an error if the event doesn't exist.
+    /// The event is recorded as an oracle response. Returns an error if the event doesn't exist.
     fn read_event(
         &mut self,
         chain_id: ChainId,
@@ -770,6 +1040,16 @@ pub trait ContractRuntime: BaseRuntime {
         index: u32,
     ) -> Result<Vec<u8>, ExecutionError>;
 
+    /// Returns the index the next event in one of the current application's streams will have.
+    ///
+    /// For the current chain, this is the number of events emitted so far. For other chains, it
+    /// is the number of events this chain has been notified about.
+    fn next_event_index(
+        &mut self,
+        chain_id: ChainId,
+        stream_name: StreamName,
+    ) -> Result<u32, ExecutionError>;
+
     /// Subscribes this application to an event stream.
     fn subscribe_to_events(
         &mut self,
@@ -819,6 +1099,11 @@ pub trait ContractRuntime: BaseRuntime {
         required_application_ids: Vec<ApplicationId>,
     ) -> Result<ApplicationId, ExecutionError>;
 
+    /// Creates a new data blob with the given content and returns its hash.
+    ///
+    /// The blob is published by the current block and can be read from subsequent blocks.
+    fn create_data_blob(&mut self, bytes: Vec<u8>) -> Result<CryptoHash, ExecutionError>;
+
     /// Returns the round in which this block was validated.
     fn validation_round(&mut self) -> Result<Option<u32>, ExecutionError>;
 
@@ -1001,6 +1286,8 @@ pub struct TestExecutionRuntimeContext {
     user_services: Arc<DashMap<ApplicationId, UserServiceCode>>,
     blobs: Arc<DashMap<BlobId, Blob>>,
     events: Arc<DashMap<EventId, Vec<u8>>>,
+    block_hashes: Arc<DashMap<BlockHeight, CryptoHash>>,
+    block_headers: Arc<DashMap<CryptoHash, BlockHeaderSummary>>,
 }
 
 #[cfg(with_testing)]
@@ -1013,8 +1300,16 @@ impl TestExecutionRuntimeContext {
             user_services: Arc::default(),
             blobs: Arc::default(),
             events: Arc::default(),
+            block_hashes: Arc::default(),
+            block_headers: Arc::default(),
         }
     }
+
+    /// Adds a confirmed block with the given hash and header to the chain's history.
+    pub fn add_confirmed_block(&self, hash: CryptoHash, header: BlockHeaderSummary) {
+        self.block_hashes.insert(header.height, hash);
+        self.block_headers.insert(hash, header);
+    }
 }
 
 #[cfg(with_testing)]
@@ -1026,7 +1321,7 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
     }
 
     fn execution_runtime_config(&self) -> ExecutionRuntimeConfig {
-        self.execution_runtime_config
+        self.execution_runtime_config.clone()
     }
 
     fn user_contracts(&self) -> &Arc<DashMap<ApplicationId, UserContractCode>> {
@@ -1089,6 +1384,29 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
         Ok(self.events.contains_key(&event_id))
     }
 
+    async fn get_block_hashes(
+        &self,
+        range: Range<BlockHeight>,
+    ) -> Result<Vec<CryptoHash>, ViewError> {
+        let mut hashes = Vec::new();
+        let mut height = range.start;
+        while height < range.end {
+            let Some(hash) = self.block_hashes.get(&height) else {
+                break;
+            };
+            hashes.push(*hash);
+            height.0 += 1;
+        }
+        Ok(hashes)
+    }
+
+    async fn get_block_header(&self, hash: CryptoHash) -> Result<BlockHeaderSummary, ViewError> {
+        Ok(*self
+            .block_headers
+            .get(&hash)
+            .ok_or_else(|| ViewError::not_found("block header for hash", hash))?)
+    }
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
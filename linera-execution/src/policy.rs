@@ -3,13 +3,16 @@
 
 //! This module contains types related to fees and pricing.
 
-use std::{collections::BTreeSet, fmt};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
 
 use async_graphql::InputObject;
 use linera_base::{
     data_types::{Amount, ArithmeticError, BlobContent, CompressedBytecode, Resources},
     ensure,
-    identifiers::BlobType,
+    identifiers::{ApplicationId, BlobType},
     vm::VmRuntime,
 };
 use serde::{Deserialize, Serialize};
@@ -41,6 +44,9 @@ pub struct ResourceControlPolicy {
     pub blob_byte_read: Amount,
     /// The price to publish a blob, per byte.
     pub blob_byte_published: Amount,
+    /// The additional price to publish contract, service or EVM bytecode, per byte of the
+    /// decompressed bytecode.
+    pub bytecode_byte_published: Amount,
     /// The price of increasing storage by a byte.
     // TODO(#1536): This is not fully supported.
     pub byte_stored: Amount,
@@ -86,8 +92,14 @@ pub struct ResourceControlPolicy {
     pub maximum_http_response_bytes: u64,
     /// The maximum amount of time allowed to wait for an HTTP response.
     pub http_request_timeout_ms: u64,
+    /// The maximum number of HTTP requests from a single batch that are performed concurrently.
+    pub maximum_concurrent_http_requests: u64,
     /// The list of hosts that contracts and services can send HTTP requests to.
     pub http_request_allow_list: BTreeSet<String>,
+    /// Additional hosts that the contract and service of specific applications can send HTTP
+    /// requests to.
+    #[graphql(skip)]
+    pub http_request_allow_list_per_application: BTreeMap<ApplicationId, BTreeSet<String>>,
 }
 
 impl fmt::Display for ResourceControlPolicy {
@@ -104,6 +116,7 @@ impl fmt::Display for ResourceControlPolicy {
             blob_published,
             blob_byte_read,
             blob_byte_published,
+            bytecode_byte_published,
             byte_stored,
             operation,
             operation_byte,
@@ -124,7 +137,9 @@ impl fmt::Display for ResourceControlPolicy {
             maximum_oracle_response_bytes,
             maximum_http_response_bytes,
             http_request_allow_list,
+            http_request_allow_list_per_application,
             http_request_timeout_ms,
+            maximum_concurrent_http_requests,
         } = self;
         write!(
             f,
@@ -140,6 +155,7 @@ impl fmt::Display for ResourceControlPolicy {
             {blob_published:.2} base cost per published blob\n\
             {blob_byte_read:.2} cost of reading blobs, per byte\n\
             {blob_byte_published:.2} cost of publishing blobs, per byte\n\
+            {bytecode_byte_published:.2} cost of publishing bytecode, per decompressed byte\n\
             {byte_stored:.2} cost per byte stored\n\
             {operation:.2} per operation\n\
             {operation_byte:.2} per byte in the argument of an operation\n\
@@ -161,7 +177,10 @@ impl fmt::Display for ResourceControlPolicy {
             {maximum_oracle_response_bytes} maximum number of bytes of an oracle response\n\
             {maximum_http_response_bytes} maximum number of bytes of an HTTP response\n\
             {http_request_timeout_ms} ms timeout for HTTP requests\n\
-            HTTP hosts allowed for contracts and services: {http_request_allow_list:#?}\n",
+            {maximum_concurrent_http_requests} maximum number of concurrent HTTP requests\n\
+            HTTP hosts allowed for contracts and services: {http_request_allow_list:#?}\n\
+            HTTP hosts allowed for specific applications: \
+                {http_request_allow_list_per_application:#?}\n",
         )?;
         Ok(())
     }
@@ -190,6 +209,7 @@ impl ResourceControlPolicy {
             blob_published: Amount::ZERO,
             blob_byte_read: Amount::ZERO,
             blob_byte_published: Amount::ZERO,
+            bytecode_byte_published: Amount::ZERO,
             byte_stored: Amount::ZERO,
             operation: Amount::ZERO,
             operation_byte: Amount::ZERO,
@@ -210,7 +230,9 @@ impl ResourceControlPolicy {
             maximum_oracle_response_bytes: u64::MAX,
             maximum_http_response_bytes: u64::MAX,
             http_request_timeout_ms: u64::MAX,
+            maximum_concurrent_http_requests: u64::MAX,
             http_request_allow_list: BTreeSet::new(),
+            http_request_allow_list_per_application: BTreeMap::new(),
         }
     }
 
@@ -260,6 +282,7 @@ impl ResourceControlPolicy {
             blob_published: Amount::from_nanos(10),
             blob_byte_read: Amount::from_attos(100),
             blob_byte_published: Amount::from_attos(1_000),
+            bytecode_byte_published: Amount::from_attos(10),
             operation: Amount::from_attos(10),
             operation_byte: Amount::from_attos(1),
             message: Amount::from_attos(10),
@@ -281,6 +304,7 @@ impl ResourceControlPolicy {
             blob_published: Amount::from_nanos(1000),
             blob_byte_read: Amount::from_nanos(10),
             blob_byte_published: Amount::from_nanos(100),
+            bytecode_byte_published: Amount::ZERO,
             read_operation: Amount::from_micros(10),
             write_operation: Amount::from_micros(20),
             byte_stored: Amount::from_nanos(10),
@@ -303,10 +327,24 @@ impl ResourceControlPolicy {
             maximum_oracle_response_bytes: 10_000,
             maximum_http_response_bytes: 10_000,
             http_request_timeout_ms: 20_000,
+            maximum_concurrent_http_requests: 4,
             http_request_allow_list: BTreeSet::new(),
+            http_request_allow_list_per_application: BTreeMap::new(),
         }
     }
 
+    /// Returns whether the given application is allowed to send HTTP requests to `host`.
+    ///
+    /// This is the case if the host is in the global allow list, or in the list of hosts
+    /// allowed for that application.
+    pub fn is_http_host_allowed(&self, application_id: &ApplicationId, host: &str) -> bool {
+        self.http_request_allow_list.contains(host)
+            || self
+                .http_request_allow_list_per_application
+                .get(application_id)
+                .is_some_and(|hosts| hosts.contains(host))
+    }
+
     pub fn block_price(&self) -> Amount {
         self.block
     }
@@ -378,6 +416,14 @@ impl ResourceControlPolicy {
             .try_add(self.blob_published)
     }
 
+    pub(crate) fn bytecode_published_price(
+        &self,
+        decompressed_size: u64,
+    ) -> Result<Amount, ArithmeticError> {
+        self.bytecode_byte_published
+            .try_mul(decompressed_size as u128)
+    }
+
     // TODO(#1536): This is not fully implemented.
     #[allow(dead_code)]
     pub(crate) fn bytes_stored_price(&self, count: u64) -> Result<Amount, ArithmeticError> {
@@ -418,6 +464,18 @@ impl ResourceControlPolicy {
     }
 
     pub fn check_blob_size(&self, content: &BlobContent) -> Result<(), ExecutionError> {
+        self.checked_bytecode_size(content)?;
+        Ok(())
+    }
+
+    /// Checks that the blob is not too large, and returns the size of its decompressed
+    /// bytecode, or `None` if it is not a bytecode blob.
+    ///
+    /// Bytecode published without compression is accepted, and its size is its length.
+    pub(crate) fn checked_bytecode_size(
+        &self,
+        content: &BlobContent,
+    ) -> Result<Option<u64>, ExecutionError> {
         ensure!(
             u64::try_from(content.bytes().len())
                 .ok()
@@ -426,19 +484,17 @@ impl ResourceControlPolicy {
         );
         match content.blob_type() {
             BlobType::ContractBytecode | BlobType::ServiceBytecode | BlobType::EvmBytecode => {
-                ensure!(
-                    CompressedBytecode::decompressed_size_at_most(
-                        content.bytes(),
-                        self.maximum_bytecode_size
-                    )?,
-                    ExecutionError::BytecodeTooLarge
-                );
+                let size = CompressedBytecode::decompressed_size(
+                    content.bytes(),
+                    self.maximum_bytecode_size,
+                )?
+                .ok_or(ExecutionError::BytecodeTooLarge)?;
+                Ok(Some(size))
             }
             BlobType::Data
             | BlobType::ApplicationDescription
             | BlobType::Committee
-            | BlobType::ChainDescription => {}
+            | BlobType::ChainDescription => Ok(None),
         }
-        Ok(())
     }
 }
@@ -322,9 +322,30 @@ where
         Ok(())
     }
 
+    /// Charges again for the blobs read since the `checkpoint` of the tracker was taken.
+    ///
+    /// This is needed when the balances that paid for the reads are rolled back to the state
+    /// they had at the checkpoint.
+    pub(crate) fn track_blob_reads_since(
+        &mut self,
+        checkpoint: &ResourceTracker,
+    ) -> Result<(), ExecutionError> {
+        let tracker = self.tracker.as_ref();
+        let blobs_read = tracker.blobs_read.saturating_sub(checkpoint.blobs_read);
+        let blob_bytes_read = tracker
+            .blob_bytes_read
+            .saturating_sub(checkpoint.blob_bytes_read);
+        let price = self
+            .policy
+            .blob_byte_read
+            .try_mul(u128::from(blob_bytes_read))?
+            .try_add(self.policy.blob_read.try_mul(u128::from(blobs_read))?)?;
+        self.update_balance(price)
+    }
+
     /// Tracks a number of blob bytes published.
     pub fn track_blob_published(&mut self, content: &BlobContent) -> Result<(), ExecutionError> {
-        self.policy.check_blob_size(content)?;
+        let bytecode_size = self.policy.checked_bytecode_size(content)?;
         let size = content.bytes().len() as u64;
         {
             let tracker = self.tracker.as_mut();
@@ -338,6 +359,9 @@ where
                 .ok_or(ArithmeticError::Overflow)?;
         }
         self.update_balance(self.policy.blob_published_price(size)?)?;
+        if let Some(bytecode_size) = bytecode_size {
+            self.update_balance(self.policy.bytecode_published_price(bytecode_size)?)?;
+        }
         Ok(())
     }
 
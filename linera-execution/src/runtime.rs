@@ -13,8 +13,9 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, BlockHeight, OracleResponse,
-        SendMessageRequest, Timestamp,
+        Amount, ApplicationDescriptionSummary, ApplicationPermissions, ArithmeticError, Blob,
+        BlockHeaderSummary, BlockHeight, CalleeError, CommitteeSummary, Epoch, OracleResponse,
+        Resources, Round, SendMessageRequest, Timestamp,
     },
     ensure, http,
     identifiers::{
@@ -32,18 +33,32 @@ use crate::{
     execution_state_actor::{ExecutionRequest, ExecutionStateSender},
     resources::ResourceController,
     system::CreateApplicationResult,
+    transaction_tracker::TransactionTrackerCheckpoint,
     util::{ReceiverExt, UnboundedSenderExt},
     ApplicationDescription, ApplicationId, BaseRuntime, ContractRuntime, ExecutionError,
     FinalizeContext, Message, MessageContext, MessageKind, ModuleId, Operation, OperationContext,
-    OutgoingMessage, QueryContext, QueryOutcome, ServiceRuntime, TransactionTracker,
+    OutgoingMessage, QueryContext, QueryLimits, QueryOutcome, ServiceRuntime, TransactionTracker,
     UserContractCode, UserContractInstance, UserServiceCode, UserServiceInstance,
-    MAX_STREAM_NAME_LEN,
+    MAX_BLOCK_HASHES_PER_QUERY, MAX_STREAM_NAME_LEN,
 };
 
 #[cfg(test)]
 #[path = "unit_tests/runtime_tests.rs"]
 mod tests;
 
+/// Returns whether `error` is a trap raised by the code of a called application, as opposed to
+/// a failure reported by the host, e.g. running out of fuel or failing to access storage.
+///
+/// Only these errors are deterministic enough for the caller to recover from them.
+fn is_callee_trap(error: &ExecutionError) -> bool {
+    match error {
+        ExecutionError::UserError(_) => true,
+        #[cfg(with_wasm_runtime)]
+        ExecutionError::WasmError(wasm_error) => wasm_error.is_guest_trap(),
+        _ => false,
+    }
+}
+
 pub trait WithContext {
     type UserContext;
 }
@@ -87,8 +102,11 @@ pub struct SyncRuntimeInternal<UserInstance: WithContext> {
     /// The height of the next block that will be added to this chain. During operations
     /// and messages, this is the current block height.
     height: BlockHeight,
-    /// The current consensus round. Only available during block validation in multi-leader rounds.
-    round: Option<u32>,
+    /// The current consensus round. Only available during block validation.
+    round: Option<Round>,
+    /// The owner who proposed the current block, if any.
+    #[debug(skip_if = Option::is_none)]
+    block_proposer: Option<AccountOwner>,
     /// The authenticated signer of the operation or message, if any.
     #[debug(skip_if = Option::is_none)]
     authenticated_signer: Option<AccountOwner>,
@@ -124,6 +142,10 @@ pub struct SyncRuntimeInternal<UserInstance: WithContext> {
     ///
     /// Used to limit the execution time of services running as oracles.
     deadline: Option<Instant>,
+    /// The deadline of the query this runtime is executing, if its execution time is limited.
+    query_deadline: Option<Instant>,
+    /// The fuel the query this runtime is executing may still consume, if limited.
+    remaining_query_fuel: Option<u64>,
 
     /// Where to send a refund for the unused part of the grant after execution, if any.
     #[debug(skip_if = Option::is_none)]
@@ -132,6 +154,9 @@ pub struct SyncRuntimeInternal<UserInstance: WithContext> {
     resource_controller: ResourceController,
     /// Additional context for the runtime.
     user_context: UserInstance::UserContext,
+    /// The checkpoints saved before the cross-application calls in progress that can be rolled
+    /// back, innermost last.
+    call_checkpoints: Vec<CallCheckpoint>,
 }
 
 /// The runtime status of an application.
@@ -175,6 +200,23 @@ impl<Instance> Clone for LoadedApplication<Instance> {
     }
 }
 
+/// The runtime state saved before a cross-application call, so that the call's changes can be
+/// rolled back if the callee traps.
+#[derive(Debug)]
+struct CallCheckpoint {
+    /// The applications that were loaded before the call.
+    loaded_applications: HashSet<ApplicationId>,
+    /// The number of applications that needed to be finalized before the call.
+    applications_to_finalize: usize,
+    /// The depth of the call stack before the call.
+    call_stack_depth: usize,
+    /// The state of the transaction tracker before the call.
+    transaction_tracker: TransactionTrackerCheckpoint,
+    /// Whether the call executed an application that was loaded before it. The in-memory state
+    /// of such an application can't be restored, so the call can't be rolled back.
+    reached_loaded_application: bool,
+}
+
 #[derive(Debug)]
 enum Promise<T> {
     Ready(T),
@@ -250,6 +292,10 @@ impl<T> QueryManager<T> {
 type Keys = Vec<Vec<u8>>;
 type Value = Vec<u8>;
 type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+type KeysPage = (Keys, Option<Vec<u8>>);
+type KeyValuesPage = (KeyValues, Option<Vec<u8>>);
+/// A page, together with the number of bytes loaded from storage to produce it.
+type LoadedPage<T> = (T, Option<Vec<u8>>, u64);
 
 #[derive(Debug, Default)]
 struct ViewUserState {
@@ -265,6 +311,10 @@ struct ViewUserState {
     find_keys_queries: QueryManager<Keys>,
     /// The find-key-values queries in progress.
     find_key_values_queries: QueryManager<KeyValues>,
+    /// The paginated find-keys queries in progress.
+    find_keys_paginated_queries: QueryManager<LoadedPage<Keys>>,
+    /// The paginated find-key-values queries in progress.
+    find_key_values_paginated_queries: QueryManager<LoadedPage<KeyValues>>,
 }
 
 impl ViewUserState {
@@ -275,6 +325,8 @@ impl ViewUserState {
         self.read_multi_values_queries.force_all()?;
         self.find_keys_queries.force_all()?;
         self.find_key_values_queries.force_all()?;
+        self.find_keys_paginated_queries.force_all()?;
+        self.find_key_values_paginated_queries.force_all()?;
         Ok(())
     }
 }
@@ -312,7 +364,8 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
     fn new(
         chain_id: ChainId,
         height: BlockHeight,
-        round: Option<u32>,
+        round: Option<Round>,
+        block_proposer: Option<AccountOwner>,
         authenticated_signer: Option<AccountOwner>,
         executing_message: Option<ExecutingMessage>,
         execution_state_sender: ExecutionStateSender,
@@ -326,6 +379,7 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
             chain_id,
             height,
             round,
+            block_proposer,
             authenticated_signer,
             executing_message,
             execution_state_sender,
@@ -336,11 +390,14 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
             active_applications: HashSet::new(),
             view_user_states: BTreeMap::new(),
             deadline,
+            query_deadline: None,
+            remaining_query_fuel: None,
             refund_grant_to,
             resource_controller,
             transaction_tracker,
             scheduled_operations: Vec::new(),
             user_context,
+            call_checkpoints: Vec::new(),
         }
     }
 
@@ -394,6 +451,109 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
         );
         Ok(())
     }
+
+    /// Reads an event from one of the given application's streams on the given chain.
+    fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: StreamName,
+        index: u32,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        ensure!(
+            stream_name.0.len() <= MAX_STREAM_NAME_LEN,
+            ExecutionError::StreamNameTooLong
+        );
+        let application_id = GenericApplicationId::User(application_id);
+        let stream_id = StreamId {
+            stream_name,
+            application_id,
+        };
+        let event_id = EventId {
+            stream_id,
+            index,
+            chain_id,
+        };
+        let event = self
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::ReadEvent {
+                event_id: event_id.clone(),
+                callback,
+            })?
+            .recv_response()?;
+        // TODO(#365): Consider separate event fee categories.
+        self.resource_controller
+            .track_bytes_read(event.len() as u64)?;
+        self.transaction_tracker
+            .replay_oracle_response(OracleResponse::Event(event_id, event.clone()))?;
+        Ok(event)
+    }
+
+    /// Ensures that the current application is allowed to make HTTP requests on this chain.
+    fn ensure_can_make_http_requests(&mut self) -> Result<(), ExecutionError> {
+        let app_permissions = self
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::GetApplicationPermissions { callback })?
+            .recv_response()?;
+
+        let app_id = self.current_application().id;
+        ensure!(
+            app_permissions.can_make_http_requests(&app_id),
+            ExecutionError::UnauthorizedApplication(app_id)
+        );
+        Ok(())
+    }
+
+    /// Ensures that the current application is allowed to send an HTTP request to the host
+    /// of `url`.
+    ///
+    /// This is checked when replaying oracle responses, too, so that a refused request fails
+    /// the same way for all validators.
+    fn ensure_http_request_is_allowed(&mut self, url: &str) -> Result<(), ExecutionError> {
+        let application_id = self.current_application().id;
+        self.execution_state_sender
+            .send_request(|callback| ExecutionRequest::AuthorizeHttpRequest {
+                application_id,
+                url: url.to_owned(),
+                callback,
+            })?
+            .recv_response()?
+    }
+
+    /// Reads the data blobs with the given hashes, returning `None` for the ones that are
+    /// missing.
+    ///
+    /// All blobs are requested from storage at once.
+    fn read_data_blobs_if_present(
+        &mut self,
+        hashes: &[CryptoHash],
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        let blob_ids = hashes
+            .iter()
+            .map(|hash| BlobId::new(*hash, BlobType::Data))
+            .collect::<Vec<_>>();
+        let blobs = self
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::ReadBlobContents {
+                blob_ids: blob_ids.clone(),
+                callback,
+            })?
+            .recv_response()?;
+        blob_ids
+            .into_iter()
+            .zip(blobs)
+            .map(|(blob_id, maybe_blob)| {
+                let Some((blob_content, is_new)) = maybe_blob else {
+                    return Ok(None);
+                };
+                if is_new {
+                    self.transaction_tracker
+                        .replay_oracle_response(OracleResponse::Blob(blob_id))?;
+                }
+                Ok(Some(blob_content.into_bytes().into_vec()))
+            })
+            .collect()
+    }
 }
 
 impl SyncRuntimeInternal<UserContractInstance> {
@@ -445,6 +605,12 @@ impl SyncRuntimeInternal<UserContractInstance> {
     ) -> Result<(Arc<Mutex<UserContractInstance>>, OperationContext), ExecutionError> {
         self.check_for_reentrancy(callee_id)?;
 
+        for checkpoint in &mut self.call_checkpoints {
+            if checkpoint.loaded_applications.contains(&callee_id) {
+                checkpoint.reached_loaded_application = true;
+            }
+        }
+
         ensure!(
             !self.is_finalizing,
             ExecutionError::CrossApplicationCallInFinalize {
@@ -472,6 +638,7 @@ impl SyncRuntimeInternal<UserContractInstance> {
             authenticated_caller_id,
             height: self.height,
             round: self.round,
+            block_proposer: self.block_proposer,
             timestamp,
         };
         self.push_application(ApplicationStatus {
@@ -490,6 +657,56 @@ impl SyncRuntimeInternal<UserContractInstance> {
         Ok(())
     }
 
+    /// Saves a checkpoint of the runtime and of the execution state before a cross-application
+    /// call, so that the call can be rolled back.
+    fn save_call_checkpoint(&mut self) -> Result<(), ExecutionError> {
+        self.execution_state_sender
+            .send_request(|callback| ExecutionRequest::SaveCheckpoint { callback })?
+            .recv_response()?;
+        self.call_checkpoints.push(CallCheckpoint {
+            loaded_applications: self.loaded_applications.keys().copied().collect(),
+            applications_to_finalize: self.applications_to_finalize.len(),
+            call_stack_depth: self.call_stack.len(),
+            transaction_tracker: self.transaction_tracker.checkpoint(),
+            reached_loaded_application: false,
+        });
+        Ok(())
+    }
+
+    /// Discards the latest checkpoint of the execution state, keeping the changes made since it
+    /// was saved.
+    fn discard_call_checkpoint(&mut self) -> Result<(), ExecutionError> {
+        self.execution_state_sender
+            .send_request(|callback| ExecutionRequest::DiscardCheckpoint { callback })?
+            .recv_response()
+    }
+
+    /// Rolls back the changes made since the `checkpoint` was saved.
+    ///
+    /// The instances of the applications loaded since then are dropped, so they are loaded
+    /// again from storage if they are called later. The resources consumed since then remain
+    /// charged.
+    fn roll_back_to_call_checkpoint(
+        &mut self,
+        checkpoint: CallCheckpoint,
+    ) -> Result<(), ExecutionError> {
+        self.execution_state_sender
+            .send_request(|callback| ExecutionRequest::RollBackToCheckpoint { callback })?
+            .recv_response()?;
+        while self.call_stack.len() > checkpoint.call_stack_depth {
+            self.pop_application();
+        }
+        self.applications_to_finalize
+            .truncate(checkpoint.applications_to_finalize);
+        self.loaded_applications
+            .retain(|id, _| checkpoint.loaded_applications.contains(id));
+        self.view_user_states
+            .retain(|id, _| checkpoint.loaded_applications.contains(id));
+        self.transaction_tracker
+            .roll_back_to(checkpoint.transaction_tracker);
+        Ok(())
+    }
+
     /// Runs the service in a separate thread as an oracle.
     fn run_service_oracle_query(
         &mut self,
@@ -554,7 +771,7 @@ impl SyncRuntimeInternal<UserServiceInstance> {
             #[cfg(not(web))]
             hash_map::Entry::Vacant(entry) => {
                 let txn_tracker_moved = mem::take(&mut self.transaction_tracker);
-                let (code, description, txn_tracker_moved) = self
+                let (result, txn_tracker_moved) = self
                     .execution_state_sender
                     .send_request(move |callback| ExecutionRequest::LoadService {
                         id,
@@ -563,6 +780,7 @@ impl SyncRuntimeInternal<UserServiceInstance> {
                     })?
                     .recv_response()?;
                 self.transaction_tracker = txn_tracker_moved;
+                let (code, description) = result?;
 
                 let instance = code.instantiate(this)?;
                 Ok(entry
@@ -613,6 +831,8 @@ where
     type ReadMultiValuesBytes = u32;
     type FindKeysByPrefix = u32;
     type FindKeyValuesByPrefix = u32;
+    type FindKeysByPrefixPaginated = u32;
+    type FindKeyValuesByPrefixPaginated = u32;
 
     fn chain_id(&mut self) -> Result<ChainId, ExecutionError> {
         Ok(self.inner().chain_id)
@@ -650,6 +870,32 @@ where
             .recv_response()
     }
 
+    fn round(&mut self) -> Result<Option<Round>, ExecutionError> {
+        let mut this = self.inner();
+        // A block executed in the fast round is never re-proposed with the same outcome in a
+        // later round, so the round doesn't need to be recorded there, and the fast round can
+        // still be used. In later rounds, the outcome of a locked block is reused as is.
+        if this.round == Some(Round::Fast) {
+            return Ok(this.round);
+        }
+        let round =
+            if let Some(response) = this.transaction_tracker.next_replayed_oracle_response()? {
+                match response {
+                    OracleResponse::ConsensusRound(round) => round,
+                    _ => return Err(ExecutionError::OracleResponseMismatch),
+                }
+            } else {
+                this.round
+            };
+        this.transaction_tracker
+            .add_oracle_response(OracleResponse::ConsensusRound(round));
+        Ok(round)
+    }
+
+    fn block_proposer(&mut self) -> Result<Option<AccountOwner>, ExecutionError> {
+        Ok(self.inner().block_proposer)
+    }
+
     fn read_chain_balance(&mut self) -> Result<Amount, ExecutionError> {
         self.inner()
             .execution_state_sender
@@ -671,6 +917,19 @@ where
             .recv_response()
     }
 
+    fn read_owner_balances_for(
+        &mut self,
+        owners: &[AccountOwner],
+    ) -> Result<Vec<Amount>, ExecutionError> {
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::OwnerBalancesFor {
+                owners: owners.to_vec(),
+                callback,
+            })?
+            .recv_response()
+    }
+
     fn read_balance_owners(&mut self) -> Result<Vec<AccountOwner>, ExecutionError> {
         self.inner()
             .execution_state_sender
@@ -685,6 +944,27 @@ where
             .recv_response()
     }
 
+    fn application_permissions(&mut self) -> Result<ApplicationPermissions, ExecutionError> {
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::GetApplicationPermissions { callback })?
+            .recv_response()
+    }
+
+    fn read_epoch(&mut self) -> Result<Epoch, ExecutionError> {
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::Epoch { callback })?
+            .recv_response()
+    }
+
+    fn read_current_committee(&mut self) -> Result<CommitteeSummary, ExecutionError> {
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::CurrentCommittee { callback })?
+            .recv_response()
+    }
+
     fn contains_key_new(&mut self, key: Vec<u8>) -> Result<Self::ContainsKey, ExecutionError> {
         let mut this = self.inner();
         let id = this.current_application().id;
@@ -863,34 +1143,114 @@ where
         Ok(key_values)
     }
 
+    fn find_keys_by_prefix_paginated_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<Self::FindKeysByPrefixPaginated, ExecutionError> {
+        let mut this = self.inner();
+        let id = this.current_application().id;
+        this.resource_controller.track_read_operations(1)?;
+        let receiver = this.execution_state_sender.send_request(move |callback| {
+            ExecutionRequest::FindKeysByPrefixPaginated {
+                id,
+                key_prefix,
+                start_after,
+                limit,
+                callback,
+            }
+        })?;
+        let state = this.view_user_states.entry(id).or_default();
+        state.find_keys_paginated_queries.register(receiver)
+    }
+
+    fn find_keys_by_prefix_paginated_wait(
+        &mut self,
+        promise: &Self::FindKeysByPrefixPaginated,
+    ) -> Result<KeysPage, ExecutionError> {
+        let mut this = self.inner();
+        let id = this.current_application().id;
+        let (keys, continuation, loaded_bytes) = {
+            let state = this.view_user_states.entry(id).or_default();
+            state.find_keys_paginated_queries.wait(*promise)?
+        };
+        this.resource_controller.track_bytes_read(loaded_bytes)?;
+        Ok((keys, continuation))
+    }
+
+    fn find_key_values_by_prefix_paginated_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<Self::FindKeyValuesByPrefixPaginated, ExecutionError> {
+        let mut this = self.inner();
+        let id = this.current_application().id;
+        this.resource_controller.track_read_operations(1)?;
+        let receiver = this.execution_state_sender.send_request(move |callback| {
+            ExecutionRequest::FindKeyValuesByPrefixPaginated {
+                id,
+                key_prefix,
+                start_after,
+                limit,
+                callback,
+            }
+        })?;
+        let state = this.view_user_states.entry(id).or_default();
+        state.find_key_values_paginated_queries.register(receiver)
+    }
+
+    fn find_key_values_by_prefix_paginated_wait(
+        &mut self,
+        promise: &Self::FindKeyValuesByPrefixPaginated,
+    ) -> Result<KeyValuesPage, ExecutionError> {
+        let mut this = self.inner();
+        let id = this.current_application().id;
+        let state = this.view_user_states.entry(id).or_default();
+        let (key_values, continuation, loaded_bytes) =
+            state.find_key_values_paginated_queries.wait(*promise)?;
+        this.resource_controller.track_bytes_read(loaded_bytes)?;
+        Ok((key_values, continuation))
+    }
+
     fn perform_http_request(
         &mut self,
         request: http::Request,
     ) -> Result<http::Response, ExecutionError> {
-        let mut this = self.inner();
-        let app_permissions = this
-            .execution_state_sender
-            .send_request(|callback| ExecutionRequest::GetApplicationPermissions { callback })?
-            .recv_response()?;
-
-        let app_id = this.current_application().id;
-        ensure!(
-            app_permissions.can_make_http_requests(&app_id),
-            ExecutionError::UnauthorizedApplication(app_id)
-        );
+        self.perform_http_request_with_options(request, http::RequestOptions::default())
+    }
 
+    fn perform_http_request_with_options(
+        &mut self,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<http::Response, ExecutionError> {
+        let mut this = self.inner();
+        this.ensure_can_make_http_requests()?;
+        this.ensure_http_request_is_allowed(&request.url)?;
         this.resource_controller.track_http_request()?;
 
         let response =
             if let Some(response) = this.transaction_tracker.next_replayed_oracle_response()? {
-                match response {
-                    OracleResponse::Http(response) => response,
-                    _ => return Err(ExecutionError::OracleResponseMismatch),
+                let OracleResponse::Http(response) = response else {
+                    return Err(ExecutionError::OracleResponseMismatch);
+                };
+                // The recorded response was received within the policy's limits, but the
+                // application's own size limit must be enforced the same way by all validators.
+                if let Some(limit) = options.max_response_bytes {
+                    let size = response.size();
+                    ensure!(
+                        size <= limit,
+                        ExecutionError::HttpResponseSizeLimitExceeded { limit, size }
+                    );
                 }
+                response
             } else {
                 this.execution_state_sender
                     .send_request(|callback| ExecutionRequest::PerformHttpRequest {
                         request,
+                        options,
                         http_responses_are_oracle_responses:
                             Self::LIMIT_HTTP_RESPONSE_SIZE_TO_ORACLE_RESPONSE_SIZE,
                         callback,
@@ -902,6 +1262,51 @@ where
         Ok(response)
     }
 
+    fn perform_http_requests(
+        &mut self,
+        requests: Vec<http::Request>,
+    ) -> Result<Vec<http::Response>, ExecutionError> {
+        let mut this = self.inner();
+        this.ensure_can_make_http_requests()?;
+        for request in &requests {
+            this.ensure_http_request_is_allowed(&request.url)?;
+            this.resource_controller.track_http_request()?;
+        }
+
+        let mut replayed_responses = Vec::with_capacity(requests.len());
+        for _ in &requests {
+            match this.transaction_tracker.next_replayed_oracle_response() {
+                Ok(Some(OracleResponse::Http(response))) => replayed_responses.push(response),
+                Ok(Some(_)) => return Err(ExecutionError::OracleResponseMismatch),
+                Ok(None) => break,
+                // The recorded responses must cover either all of the requests or none.
+                Err(ExecutionError::MissingOracleResponse) if !replayed_responses.is_empty() => {
+                    return Err(ExecutionError::OracleResponseMismatch)
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        let responses = if replayed_responses.len() == requests.len() {
+            replayed_responses
+        } else if !replayed_responses.is_empty() {
+            return Err(ExecutionError::OracleResponseMismatch);
+        } else {
+            this.execution_state_sender
+                .send_request(|callback| ExecutionRequest::PerformHttpRequests {
+                    requests,
+                    http_responses_are_oracle_responses:
+                        Self::LIMIT_HTTP_RESPONSE_SIZE_TO_ORACLE_RESPONSE_SIZE,
+                    callback,
+                })?
+                .recv_response()?
+        };
+        for response in &responses {
+            this.transaction_tracker
+                .add_oracle_response(OracleResponse::Http(response.clone()));
+        }
+        Ok(responses)
+    }
+
     fn assert_before(&mut self, timestamp: Timestamp) -> Result<(), ExecutionError> {
         let mut this = self.inner();
         if !this
@@ -935,6 +1340,59 @@ where
         Ok(blob_content.into_bytes().into_vec())
     }
 
+    fn read_data_blobs(&mut self, hashes: &[CryptoHash]) -> Result<Vec<Vec<u8>>, ExecutionError> {
+        let blobs = self.inner().read_data_blobs_if_present(hashes)?;
+        let missing_blobs = hashes
+            .iter()
+            .zip(&blobs)
+            .filter(|(_, maybe_blob)| maybe_blob.is_none())
+            .map(|(hash, _)| BlobId::new(*hash, BlobType::Data))
+            .collect::<Vec<_>>();
+        ensure!(
+            missing_blobs.is_empty(),
+            ExecutionError::BlobsNotFound(missing_blobs)
+        );
+        Ok(blobs.into_iter().flatten().collect())
+    }
+
+    fn read_data_blob_range(
+        &mut self,
+        hash: &CryptoHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        let mut this = self.inner();
+        let blob_id = BlobId::new(*hash, BlobType::Data);
+        let (bytes, is_new) = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::ReadBlobRange {
+                blob_id,
+                offset,
+                length,
+                callback,
+            })?
+            .recv_response()?;
+        if is_new {
+            this.transaction_tracker
+                .replay_oracle_response(OracleResponse::Blob(blob_id))?;
+        }
+        Ok(bytes)
+    }
+
+    fn data_blob_size(&mut self, hash: &CryptoHash) -> Result<u64, ExecutionError> {
+        let mut this = self.inner();
+        let blob_id = BlobId::new(*hash, BlobType::Data);
+        let (size, is_new) = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::BlobSize { blob_id, callback })?
+            .recv_response()?;
+        if is_new {
+            this.transaction_tracker
+                .replay_oracle_response(OracleResponse::Blob(blob_id))?;
+        }
+        Ok(size)
+    }
+
     fn assert_data_blob_exists(&mut self, hash: &CryptoHash) -> Result<(), ExecutionError> {
         let mut this = self.inner();
         let blob_id = BlobId::new(*hash, BlobType::Data);
@@ -948,6 +1406,33 @@ where
         }
         Ok(())
     }
+
+    fn data_blob_exists(&mut self, hash: &CryptoHash) -> Result<bool, ExecutionError> {
+        let mut this = self.inner();
+        let blob_id = BlobId::new(*hash, BlobType::Data);
+        // A blob that was missing when the block was first executed is still treated as missing
+        // when replaying, even if it was published in the meantime.
+        let missing = OracleResponse::MissingBlob(blob_id);
+        if this
+            .transaction_tracker
+            .replay_oracle_response_if_next(&missing)
+        {
+            return Ok(false);
+        }
+        let maybe_is_new = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::BlobExists { blob_id, callback })?
+            .recv_response()?;
+        let Some(is_new) = maybe_is_new else {
+            this.transaction_tracker.replay_oracle_response(missing)?;
+            return Ok(false);
+        };
+        if is_new {
+            this.transaction_tracker
+                .replay_oracle_response(OracleResponse::Blob(blob_id))?;
+        }
+        Ok(true)
+    }
 }
 
 /// An extension trait to determine in compile time the different behaviors between contract and
@@ -989,6 +1474,7 @@ impl ContractSyncRuntime {
                 chain_id,
                 action.height(),
                 action.round(),
+                action.block_proposer(),
                 action.signer(),
                 if let UserAction::Message(context, _) = action {
                     Some(context.into())
@@ -1229,6 +1715,26 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(())
     }
 
+    fn send_message_to_many(
+        &mut self,
+        destinations: Vec<ChainId>,
+        authenticated: bool,
+        is_tracked: bool,
+        grant: Resources,
+        message: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        for destination in destinations {
+            self.send_message(SendMessageRequest {
+                destination,
+                authenticated,
+                is_tracked,
+                grant,
+                message: message.clone(),
+            })?;
+        }
+        Ok(())
+    }
+
     fn transfer(
         &mut self,
         source: AccountOwner,
@@ -1250,7 +1756,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
                 application_id,
                 callback,
             })?
-            .recv_response()?;
+            .recv_response()??;
 
         this.transaction_tracker
             .add_outgoing_messages(maybe_message)?;
@@ -1278,7 +1784,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
                 application_id,
                 callback,
             })?
-            .recv_response()?;
+            .recv_response()??;
         this.transaction_tracker.add_outgoing_message(message)?;
         Ok(())
     }
@@ -1303,6 +1809,33 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(value)
     }
 
+    fn try_call_application_with_rollback(
+        &mut self,
+        authenticated: bool,
+        callee_id: ApplicationId,
+        argument: Vec<u8>,
+    ) -> Result<Result<Vec<u8>, CalleeError>, ExecutionError> {
+        self.inner().save_call_checkpoint()?;
+
+        let result = self.try_call_application(authenticated, callee_id, argument);
+
+        let mut this = self.inner();
+        let checkpoint = this
+            .call_checkpoints
+            .pop()
+            .expect("The checkpoint saved before the call should not have been removed");
+        match result {
+            Err(error) if is_callee_trap(&error) && !checkpoint.reached_loaded_application => {
+                this.roll_back_to_call_checkpoint(checkpoint)?;
+                Ok(Err(CalleeError::Trapped))
+            }
+            result => {
+                this.discard_call_checkpoint()?;
+                result.map(Ok)
+            }
+        }
+    }
+
     fn emit(&mut self, stream_name: StreamName, value: Vec<u8>) -> Result<u32, ExecutionError> {
         let mut this = self.inner();
         ensure!(
@@ -1335,33 +1868,31 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         index: u32,
     ) -> Result<Vec<u8>, ExecutionError> {
         let mut this = self.inner();
+        let application_id = this.current_application().id;
+        this.read_event(chain_id, application_id, stream_name, index)
+    }
+
+    fn next_event_index(
+        &mut self,
+        chain_id: ChainId,
+        stream_name: StreamName,
+    ) -> Result<u32, ExecutionError> {
+        let this = self.inner();
         ensure!(
             stream_name.0.len() <= MAX_STREAM_NAME_LEN,
             ExecutionError::StreamNameTooLong
         );
-        let application_id = GenericApplicationId::User(this.current_application().id);
         let stream_id = StreamId {
             stream_name,
-            application_id,
-        };
-        let event_id = EventId {
-            stream_id,
-            index,
-            chain_id,
+            application_id: GenericApplicationId::User(this.current_application().id),
         };
-        let event = this
-            .execution_state_sender
-            .send_request(|callback| ExecutionRequest::ReadEvent {
-                event_id: event_id.clone(),
+        this.execution_state_sender
+            .send_request(|callback| ExecutionRequest::StreamEventCount {
+                chain_id,
+                stream_id,
                 callback,
             })?
-            .recv_response()?;
-        // TODO(#365): Consider separate event fee categories.
-        this.resource_controller
-            .track_bytes_read(event.len() as u64)?;
-        this.transaction_tracker
-            .replay_oracle_response(OracleResponse::Event(event_id, event.clone()))?;
-        Ok(event)
+            .recv_response()
     }
 
     fn subscribe_to_events(
@@ -1564,6 +2095,19 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(app_id)
     }
 
+    fn create_data_blob(&mut self, bytes: Vec<u8>) -> Result<CryptoHash, ExecutionError> {
+        let mut this = self.inner();
+        let blob = Blob::new_data(bytes);
+        // The blob is charged when the block publishes it; only the size is checked here so
+        // that contracts can handle oversized blobs.
+        this.resource_controller
+            .policy
+            .check_blob_size(blob.content())?;
+        let hash = blob.id().hash;
+        this.transaction_tracker.add_created_blob(blob);
+        Ok(hash)
+    }
+
     fn validation_round(&mut self) -> Result<Option<u32>, ExecutionError> {
         let mut this = self.inner();
         let round =
@@ -1573,7 +2117,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
                     _ => return Err(ExecutionError::OracleResponseMismatch),
                 }
             } else {
-                this.round
+                this.round.and_then(|round| round.multi_leader())
             };
         this.transaction_tracker
             .add_oracle_response(OracleResponse::Round(round));
@@ -1626,6 +2170,7 @@ impl ServiceSyncRuntime {
                 None,
                 None,
                 None,
+                None,
                 execution_state_sender,
                 deadline,
                 None,
@@ -1674,16 +2219,25 @@ impl ServiceSyncRuntime {
             let ServiceRuntimeRequest::Query {
                 application_id,
                 context,
+                limits,
                 query,
                 callback,
             } = request;
 
             self.prepare_for_query(context);
+            self.set_query_limits(limits);
 
             let _ = callback.send(self.run_query(application_id, query));
         }
     }
 
+    /// Limits the execution of the queries from now on.
+    pub(crate) fn set_query_limits(&mut self, limits: QueryLimits) {
+        let mut this = self.handle_mut().inner();
+        this.query_deadline = limits.timeout.map(|timeout| Instant::now() + timeout);
+        this.remaining_query_fuel = limits.fuel;
+    }
+
     /// Prepares the runtime to query an application.
     pub(crate) fn prepare_for_query(&mut self, new_context: QueryContext) {
         let expected_context = QueryContext {
@@ -1750,29 +2304,140 @@ impl ServiceRuntime for ServiceSyncRuntimeHandle {
         let response = service
             .try_lock()
             .expect("Applications should not have reentrant calls")
-            .handle_query(argument)?;
-        self.inner().pop_application();
-        Ok(response)
+            .handle_query(argument);
+        let mut this = self.inner();
+        this.pop_application();
+        if response.is_err() {
+            // The instance may have been left in an inconsistent state, so it is dropped and
+            // reloaded if it is queried again.
+            this.loaded_applications.remove(&queried_id);
+        }
+        response
+    }
+
+    fn try_read_data_blobs(
+        &mut self,
+        hashes: &[CryptoHash],
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        self.inner().read_data_blobs_if_present(hashes)
+    }
+
+    fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: StreamName,
+        index: u32,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        self.inner()
+            .read_event(chain_id, application_id, stream_name, index)
     }
 
-    fn schedule_operation(&mut self, operation: Vec<u8>) -> Result<(), ExecutionError> {
+    fn read_events_in_range(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: StreamName,
+        start: u32,
+        end: u32,
+    ) -> Result<Vec<Vec<u8>>, ExecutionError> {
+        let mut this = self.inner();
+        (start..end)
+            .map(|index| this.read_event(chain_id, application_id, stream_name.clone(), index))
+            .collect()
+    }
+
+    fn block_hashes(
+        &mut self,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, ExecutionError> {
+        let end = end.min(BlockHeight(
+            start.0.saturating_add(MAX_BLOCK_HASHES_PER_QUERY),
+        ));
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::BlockHashes {
+                range: start..end,
+                callback,
+            })?
+            .recv_response()
+    }
+
+    fn block_header(
+        &mut self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockHeaderSummary>, ExecutionError> {
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::BlockHeader { height, callback })?
+            .recv_response()
+    }
+
+    fn registered_applications(
+        &mut self,
+        start_after: Option<ApplicationId>,
+    ) -> Result<Vec<ApplicationDescriptionSummary>, ExecutionError> {
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::RegisteredApplications {
+                start_after,
+                callback,
+            })?
+            .recv_response()
+    }
+
+    fn schedule_operation(&mut self, operation: Vec<u8>) -> Result<u32, ExecutionError> {
         let mut this = self.inner();
         let application_id = this.current_application().id;
+        let index = u32::try_from(this.scheduled_operations.len())
+            .map_err(|_| ArithmeticError::Overflow)?;
 
         this.scheduled_operations.push(Operation::User {
             application_id,
             bytes: operation,
         });
 
-        Ok(())
+        Ok(index)
+    }
+
+    fn scheduled_operations(&mut self) -> Result<Vec<Vec<u8>>, ExecutionError> {
+        let this = self.inner();
+        let current_application_id = this.current_application().id;
+
+        Ok(this
+            .scheduled_operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::User {
+                    application_id,
+                    bytes,
+                } if *application_id == current_application_id => Some(bytes.clone()),
+                _ => None,
+            })
+            .collect())
     }
 
     fn check_execution_time(&mut self) -> Result<(), ExecutionError> {
-        if let Some(deadline) = self.inner().deadline {
+        let this = self.inner();
+        if let Some(deadline) = this.deadline {
             if Instant::now() >= deadline {
                 return Err(ExecutionError::MaximumServiceOracleExecutionTimeExceeded);
             }
         }
+        if let Some(deadline) = this.query_deadline {
+            ensure!(Instant::now() < deadline, ExecutionError::QueryTimedOut);
+        }
+        Ok(())
+    }
+
+    fn consume_query_fuel(&mut self, fuel: u64) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        if let Some(remaining_fuel) = &mut this.remaining_query_fuel {
+            *remaining_fuel = remaining_fuel
+                .checked_sub(fuel)
+                .ok_or(ExecutionError::QueryOutOfFuel)?;
+        }
         Ok(())
     }
 }
@@ -1782,6 +2447,7 @@ pub enum ServiceRuntimeRequest {
     Query {
         application_id: ApplicationId,
         context: QueryContext,
+        limits: QueryLimits,
         query: Vec<u8>,
         callback: oneshot::Sender<Result<QueryOutcome<Vec<u8>>, ExecutionError>>,
     },
@@ -40,7 +40,7 @@ use crate::test_utils::SystemExecutionState;
 use crate::{
     committee::Committee, ApplicationDescription, ApplicationId, ExecutionError,
     ExecutionRuntimeContext, MessageContext, MessageKind, OperationContext, OutgoingMessage,
-    QueryContext, QueryOutcome, ResourceController, TransactionTracker,
+    QueryContext, QueryOutcome, ResourceController, TransactionTracker, MAX_TRANSFER_MEMO_LEN,
 };
 
 /// The event stream name for new epochs and committees.
@@ -201,6 +201,15 @@ pub enum SystemOperation {
     ProcessRemovedEpoch(Epoch),
     /// Updates the event stream trackers.
     UpdateStreams(Vec<(ChainId, StreamId, u32)>),
+    /// Transfers `amount` units of value like [`SystemOperation::Transfer`], with some user
+    /// data about the transfer, e.g. for reconciliation.
+    TransferWithMemo {
+        owner: AccountOwner,
+        recipient: Recipient,
+        amount: Amount,
+        /// The user data, of at most `MAX_TRANSFER_MEMO_LEN` bytes.
+        memo: String,
+    },
 }
 
 /// Operations that are only allowed on the admin chain.
@@ -392,6 +401,21 @@ where
                     .await?;
                 txn_tracker.add_outgoing_messages(maybe_message)?;
             }
+            TransferWithMemo {
+                owner,
+                amount,
+                recipient,
+                memo,
+            } => {
+                ensure!(
+                    memo.len() <= MAX_TRANSFER_MEMO_LEN,
+                    ExecutionError::TransferMemoTooLong
+                );
+                let maybe_message = self
+                    .transfer(context.authenticated_signer, None, owner, recipient, amount)
+                    .await?;
+                txn_tracker.add_outgoing_messages(maybe_message)?;
+            }
             Claim {
                 owner,
                 target_id,
@@ -914,6 +938,57 @@ where
         Ok(description)
     }
 
+    /// Returns the descriptions of at most `limit` applications registered on this chain, i.e.
+    /// those whose description blob has been used here, sorted by application ID and starting
+    /// after `start_after`, if provided.
+    ///
+    /// Only the description blobs that can be on the page are read.
+    pub async fn registered_applications(
+        &self,
+        start_after: Option<ApplicationId>,
+        limit: usize,
+    ) -> Result<Vec<ApplicationDescription>, ExecutionError> {
+        // An application ID is the hash of its description blob, with the last bytes cleared for
+        // EVM applications. So the IDs are sorted like the hashes with these bytes cleared.
+        let sort_key = |mut hash: CryptoHash| {
+            hash.make_evm_compatible();
+            hash
+        };
+        let start_key = start_after.map(|id| sort_key(id.application_description_hash));
+        let mut hashes = Vec::new();
+        self.used_blobs
+            .for_each_index(|blob_id| {
+                if blob_id.blob_type == BlobType::ApplicationDescription
+                    && start_key.is_none_or(|start_key| sort_key(blob_id.hash) >= start_key)
+                {
+                    hashes.push(blob_id.hash);
+                }
+                Ok(())
+            })
+            .await?;
+        hashes.sort_by_key(|hash| sort_key(*hash));
+
+        let mut descriptions = Vec::new();
+        let mut last_key = None;
+        for hash in hashes {
+            // Hashes with the same key can have their applications in any order, so they are
+            // all read before stopping.
+            if descriptions.len() >= limit && last_key != Some(sort_key(hash)) {
+                break;
+            }
+            let blob_id = BlobId::new(hash, BlobType::ApplicationDescription);
+            let content = self.read_blob_content(blob_id).await?;
+            let description = bcs::from_bytes::<ApplicationDescription>(content.bytes())?;
+            if start_after.is_none_or(|id| ApplicationId::from(&description) > id) {
+                descriptions.push(description);
+                last_key = Some(sort_key(hash));
+            }
+        }
+        descriptions.sort_by_cached_key(ApplicationId::from);
+        descriptions.truncate(limit);
+        Ok(descriptions)
+    }
+
     /// Retrieves the recursive dependencies of applications and applies a topological sort.
     pub async fn find_dependencies(
         &mut self,
@@ -16,7 +16,7 @@ use linera_base::{
     crypto::{AccountPublicKey, BcsSignable, CryptoHash, ValidatorPublicKey},
     data_types::{
         Amount, Blob, BlockHeight, ChainDescription, ChainOrigin, CompressedBytecode, Epoch,
-        InitialChainConfig, OracleResponse, Timestamp,
+        InitialChainConfig, OracleResponse, Round, Timestamp,
     },
     identifiers::{AccountOwner, ApplicationId, BlobId, BlobType, ChainId, MessageId, ModuleId},
     ownership::ChainOwnership,
@@ -122,7 +122,8 @@ pub fn create_dummy_operation_context(chain_id: ChainId) -> OperationContext {
     OperationContext {
         chain_id,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         authenticated_signer: None,
         authenticated_caller_id: None,
         timestamp: Default::default(),
@@ -140,7 +141,8 @@ pub fn create_dummy_message_context(
         authenticated_signer,
         refund_grant_to: None,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         message_id: MessageId {
             chain_id,
             height: BlockHeight(0),
@@ -10,7 +10,10 @@ use std::{
 use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, ApplicationPermissions, Blob, ChainDescription, Epoch, Timestamp},
+    data_types::{
+        Amount, ApplicationPermissions, Blob, BlockHeaderSummary, ChainDescription, Epoch,
+        Timestamp,
+    },
     identifiers::{AccountOwner, ApplicationId, BlobId, ChainId},
     ownership::ChainOwnership,
 };
@@ -46,6 +49,8 @@ pub struct SystemExecutionState {
     pub application_permissions: ApplicationPermissions,
     #[debug(skip_if = Vec::is_empty)]
     pub extra_blobs: Vec<Blob>,
+    #[debug(skip_if = Vec::is_empty)]
+    pub confirmed_blocks: Vec<(CryptoHash, BlockHeaderSummary)>,
     #[debug(skip_if = BTreeMap::is_empty)]
     pub mock_applications: BTreeMap<ApplicationId, MockApplication>,
 }
@@ -79,6 +84,20 @@ impl SystemExecutionState {
         }
     }
 
+    /// Adds `hosts` to the HTTP request allow list of every committee's policy.
+    pub fn with_http_request_allow_list<'a>(
+        mut self,
+        hosts: impl IntoIterator<Item = &'a str> + Clone,
+    ) -> Self {
+        for committee in self.committees.values_mut() {
+            committee
+                .policy_mut()
+                .http_request_allow_list
+                .extend(hosts.clone().into_iter().map(str::to_owned));
+        }
+        self
+    }
+
     pub fn dummy_chain_state(index: u32) -> (Self, ChainId) {
         let description = dummy_chain_description(index);
         let chain_id = description.id();
@@ -121,6 +140,7 @@ impl SystemExecutionState {
             closed,
             application_permissions,
             extra_blobs,
+            confirmed_blocks,
             mock_applications,
         } = self;
 
@@ -129,6 +149,9 @@ impl SystemExecutionState {
             .add_blobs(extra_blobs)
             .await
             .expect("Adding blobs to the `TestExecutionRuntimeContext` should not fail");
+        for (hash, header) in confirmed_blocks {
+            extra.add_confirmed_block(hash, header);
+        }
         for (id, mock_application) in mock_applications {
             extra
                 .user_contracts()
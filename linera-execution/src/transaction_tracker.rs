@@ -41,6 +41,21 @@ pub struct TransactionTracker {
     streams_to_process: BTreeMap<ApplicationId, AppStreamUpdates>,
 }
 
+/// The state of a [`TransactionTracker`] that is restored when a cross-application call is
+/// rolled back.
+///
+/// Oracle responses are not part of it: they are still needed to replay the call.
+#[derive(Debug)]
+pub(crate) struct TransactionTrackerCheckpoint {
+    outgoing_messages: usize,
+    next_message_index: u32,
+    next_application_index: u32,
+    next_chain_index: u32,
+    events: usize,
+    blobs: BTreeMap<BlobId, Blob>,
+    streams_to_process: BTreeMap<ApplicationId, AppStreamUpdates>,
+}
+
 /// The [`TransactionTracker`] contents after a transaction has finished.
 #[derive(Debug, Default)]
 pub struct TransactionOutcome {
@@ -150,6 +165,31 @@ impl TransactionTracker {
         &self.blobs
     }
 
+    /// Saves the current state, so that the changes made afterwards can be rolled back.
+    pub(crate) fn checkpoint(&self) -> TransactionTrackerCheckpoint {
+        TransactionTrackerCheckpoint {
+            outgoing_messages: self.outgoing_messages.len(),
+            next_message_index: self.next_message_index,
+            next_application_index: self.next_application_index,
+            next_chain_index: self.next_chain_index,
+            events: self.events.len(),
+            blobs: self.blobs.clone(),
+            streams_to_process: self.streams_to_process.clone(),
+        }
+    }
+
+    /// Discards the messages, events, blobs and stream updates added since the `checkpoint`.
+    pub(crate) fn roll_back_to(&mut self, checkpoint: TransactionTrackerCheckpoint) {
+        self.outgoing_messages
+            .truncate(checkpoint.outgoing_messages);
+        self.next_message_index = checkpoint.next_message_index;
+        self.next_application_index = checkpoint.next_application_index;
+        self.next_chain_index = checkpoint.next_chain_index;
+        self.events.truncate(checkpoint.events);
+        self.blobs = checkpoint.blobs;
+        self.streams_to_process = checkpoint.streams_to_process;
+    }
+
     pub fn add_oracle_response(&mut self, oracle_response: OracleResponse) {
         self.oracle_responses.push(oracle_response);
     }
@@ -233,6 +273,21 @@ impl TransactionTracker {
         Ok(replaying)
     }
 
+    /// Consumes the next replayed oracle response if it is the given one, and records it.
+    ///
+    /// Returns `false` if not in replay mode or if the next replayed response is another one.
+    pub fn replay_oracle_response_if_next(&mut self, oracle_response: &OracleResponse) -> bool {
+        let Some(responses) = &mut self.replaying_oracle_responses else {
+            return false;
+        };
+        if responses.as_slice().first() != Some(oracle_response) {
+            return false;
+        }
+        responses.next();
+        self.add_oracle_response(oracle_response.clone());
+        true
+    }
+
     /// If in replay mode, returns the next oracle response, or an error if it is missing.
     ///
     /// If not in replay mode, `None` is returned, and the caller must execute the actual oracle
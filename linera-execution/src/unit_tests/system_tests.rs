@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use linera_base::data_types::{Blob, BlockHeight, Bytecode};
+use linera_base::data_types::{Blob, BlockHeight, Bytecode, Round};
 #[cfg(with_testing)]
 use linera_base::vm::VmRuntime;
 use linera_views::context::MemoryContext;
@@ -21,7 +21,8 @@ async fn new_view_and_context() -> (
         authenticated_signer: None,
         authenticated_caller_id: None,
         height: BlockHeight::from(7),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         timestamp: Default::default(),
     };
     let state = SystemExecutionState {
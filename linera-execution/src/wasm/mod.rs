@@ -146,6 +146,7 @@ impl WasmServiceModule {
         service_bytecode: Bytecode,
         runtime: WasmRuntime,
     ) -> Result<Self, WasmExecutionError> {
+        let service_bytecode = add_service_metering(service_bytecode)?;
         match runtime {
             #[cfg(with_wasmer)]
             WasmRuntime::Wasmer => Self::from_wasmer(service_bytecode).await,
@@ -194,8 +195,27 @@ impl UserServiceModule for WasmServiceModule {
     }
 }
 
-/// Instrument the [`Bytecode`] to add fuel metering.
+/// Instrument the [`Bytecode`] of a contract to add fuel metering.
 pub fn add_metering(bytecode: Bytecode) -> Result<Bytecode, WasmExecutionError> {
+    instrument(bytecode, "linera:app/contract-runtime-api", "consume-fuel")
+}
+
+/// Instrument the [`Bytecode`] of a service to check its execution time and fuel limits.
+pub fn add_service_metering(bytecode: Bytecode) -> Result<Bytecode, WasmExecutionError> {
+    instrument(
+        bytecode,
+        "linera:app/service-runtime-api",
+        "check-execution-time",
+    )
+}
+
+/// Instrument the [`Bytecode`] to call the host function `function` of `module` with the
+/// fuel consumed by each block of instructions.
+fn instrument(
+    bytecode: Bytecode,
+    module: &str,
+    function: &str,
+) -> Result<Bytecode, WasmExecutionError> {
     struct WasmtimeRules;
 
     impl gas_metering::Rules for WasmtimeRules {
@@ -226,10 +246,7 @@ pub fn add_metering(bytecode: Bytecode) -> Result<Bytecode, WasmExecutionError>
 
     let instrumented_module = gas_metering::inject(
         parity_wasm::deserialize_buffer(&bytecode.bytes)?,
-        gas_metering::host_function::Injector::new(
-            "linera:app/contract-runtime-api",
-            "consume-fuel",
-        ),
+        gas_metering::host_function::Injector::new(module, function),
         &WasmtimeRules,
     )
     .map_err(|_| WasmExecutionError::InstrumentModule)?;
@@ -331,6 +348,48 @@ pub enum WasmExecutionError {
     IncorrectPromise,
 }
 
+impl WasmExecutionError {
+    /// Converts an error that stopped a service's query into an [`ExecutionError`].
+    ///
+    /// If the query was stopped by the runtime because it exceeded its limits, the
+    /// corresponding error is extracted from the errors wrapping it, so that callers can tell
+    /// that case apart from a failing service.
+    fn into_query_error(self) -> ExecutionError {
+        let query_limit_error =
+            std::iter::successors(Some(&self as &(dyn std::error::Error + 'static)), |error| {
+                error.source()
+            })
+            .find_map(|error| match error.downcast_ref::<ExecutionError>()? {
+                ExecutionError::QueryTimedOut => Some(ExecutionError::QueryTimedOut),
+                ExecutionError::QueryOutOfFuel => Some(ExecutionError::QueryOutOfFuel),
+                _ => None,
+            });
+        query_limit_error.unwrap_or(ExecutionError::WasmError(self))
+    }
+
+    /// Returns whether this error is a trap raised by the guest code itself, e.g. a panic,
+    /// rather than an error reported by the host while the guest was running.
+    ///
+    /// Errors from host functions, such as running out of fuel or failing to read from
+    /// storage, are wrapped in the trap that stops the guest, so they are found by walking the
+    /// chain of error sources.
+    pub(crate) fn is_guest_trap(&self) -> bool {
+        let stopped_execution = match self {
+            #[cfg(with_wasmer)]
+            WasmExecutionError::ExecuteModuleInWasmer(_) => true,
+            #[cfg(with_wasmtime)]
+            WasmExecutionError::ExecuteModuleInWasmtime(_) => true,
+            WasmExecutionError::ExecuteModule(_) => true,
+            _ => false,
+        };
+        stopped_execution
+            && std::iter::successors(Some(self as &(dyn std::error::Error + 'static)), |error| {
+                error.source()
+            })
+            .all(|error| error.downcast_ref::<ExecutionError>().is_none())
+    }
+}
+
 #[cfg(with_wasmer)]
 impl From<::wasmer::InstantiationError> for WasmExecutionError {
     fn from(instantiation_error: ::wasmer::InstantiationError) -> Self {
@@ -5,10 +5,16 @@ use std::{any::Any, collections::HashMap, marker::PhantomData};
 
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, ApplicationPermissions, BlockHeight, SendMessageRequest, Timestamp},
+    data_types::{
+        Amount, ApplicationDescriptionSummary, ApplicationPermissions, ApplicationQueryError,
+        BlockHeaderSummary, BlockHeight, CalleeError, CommitteeSummary, CreateDataBlobError, Epoch,
+        Resources, Round, SendMessageRequest, Timestamp,
+    },
     http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, MessageId, StreamName},
-    ownership::{ChainOwnership, ChangeApplicationPermissionsError, CloseChainError},
+    ownership::{
+        ChainOwnership, ChangeApplicationPermissionsError, CloseChainError, TransferError,
+    },
     vm::VmRuntime,
 };
 use linera_views::batch::{Batch, WriteOperation};
@@ -136,6 +142,35 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Retrieves the application permissions for the current chain.
+    fn get_application_permissions(
+        caller: &mut Caller,
+    ) -> Result<ApplicationPermissions, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .application_permissions()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Retrieves the current epoch of the chain.
+    fn get_epoch(caller: &mut Caller) -> Result<Epoch, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .read_epoch()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Retrieves a summary of the committee in charge of the chain's current epoch.
+    fn get_current_committee(caller: &mut Caller) -> Result<CommitteeSummary, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .read_current_committee()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Retrieves the current system time, i.e. the timestamp of the block in which this is called.
     fn read_system_timestamp(caller: &mut Caller) -> Result<Timestamp, RuntimeError> {
         caller
@@ -145,6 +180,24 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Returns the consensus round in which the current block is validated, if known.
+    fn get_round(caller: &mut Caller) -> Result<Option<Round>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .round()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns the owner who proposed the current block, if any.
+    fn get_block_proposer(caller: &mut Caller) -> Result<Option<AccountOwner>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .block_proposer()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Returns the current chain balance.
     fn read_chain_balance(caller: &mut Caller) -> Result<Amount, RuntimeError> {
         caller
@@ -177,6 +230,18 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Returns the balances of the given accounts on the chain, in the same order.
+    fn read_owner_balances_for(
+        caller: &mut Caller,
+        owners: Vec<AccountOwner>,
+    ) -> Result<Vec<Amount>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .read_owner_balances_for(&owners)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Returns the owners of accounts on this chain.
     fn read_balance_owners(caller: &mut Caller) -> Result<Vec<AccountOwner>, RuntimeError> {
         caller
@@ -198,6 +263,21 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Makes several HTTP requests as oracles and returns the HTTP responses in the same order.
+    ///
+    /// The requests may be performed concurrently, up to the limit from the resource control
+    /// policy.
+    fn perform_http_requests(
+        caller: &mut Caller,
+        requests: Vec<http::Request>,
+    ) -> Result<Vec<http::Response>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .perform_http_requests(requests)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Rejects the transaction if the current time at block validation is `>= timestamp`. Note
     /// that block validation happens at or after the block timestamp, but isn't necessarily the
     /// same.
@@ -218,6 +298,41 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Reads several data blobs from storage, returning them in the same order as the hashes.
+    fn read_data_blobs(
+        caller: &mut Caller,
+        hashes: Vec<CryptoHash>,
+    ) -> Result<Vec<Vec<u8>>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .read_data_blobs(&hashes)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Reads up to `length` bytes starting at `offset` from a data blob in storage.
+    fn read_data_blob_range(
+        caller: &mut Caller,
+        hash: CryptoHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .read_data_blob_range(&hash, offset, length)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns the size in bytes of a data blob in storage.
+    fn data_blob_size(caller: &mut Caller, hash: CryptoHash) -> Result<u64, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .data_blob_size(&hash)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Asserts the existence of a data blob with the given hash.
     fn assert_data_blob_exists(caller: &mut Caller, hash: CryptoHash) -> Result<(), RuntimeError> {
         caller
@@ -227,6 +342,15 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Returns whether the data blob with the given hash exists in storage.
+    fn data_blob_exists(caller: &mut Caller, hash: CryptoHash) -> Result<bool, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .data_blob_exists(&hash)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Logs a `message` with the provided information `level`.
     fn log(_caller: &mut Caller, message: String, level: log::Level) -> Result<(), RuntimeError> {
         match level {
@@ -377,6 +501,69 @@ where
             .find_key_values_by_prefix_wait(&promise)
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
+
+    /// Creates a new promise to search for at most `limit` keys that start with the
+    /// `key_prefix`, skipping the keys up to and including `start_after`.
+    fn find_keys_paginated_new(
+        caller: &mut Caller,
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<u32, RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let promise = data
+            .runtime
+            .find_keys_by_prefix_paginated_new(key_prefix, start_after, limit)
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+
+        data.register_promise(promise)
+    }
+
+    /// Waits for the promise to search for a page of keys that start with the `key_prefix`.
+    #[expect(clippy::type_complexity)]
+    fn find_keys_paginated_wait(
+        caller: &mut Caller,
+        promise_id: u32,
+    ) -> Result<(Vec<Vec<u8>>, Option<Vec<u8>>), RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let promise = data.take_promise(promise_id)?;
+
+        data.runtime
+            .find_keys_by_prefix_paginated_wait(&promise)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Creates a new promise to search for at most `limit` entries whose keys start with the
+    /// `key_prefix`, skipping the keys up to and including `start_after`.
+    fn find_key_values_paginated_new(
+        caller: &mut Caller,
+        key_prefix: Vec<u8>,
+        start_after: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<u32, RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let promise = data
+            .runtime
+            .find_key_values_by_prefix_paginated_new(key_prefix, start_after, limit)
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+
+        data.register_promise(promise)
+    }
+
+    /// Waits for the promise to search for a page of entries whose keys start with the
+    /// `key_prefix`.
+    #[expect(clippy::type_complexity)]
+    fn find_key_values_paginated_wait(
+        caller: &mut Caller,
+        promise_id: u32,
+    ) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>), RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let promise = data.take_promise(promise_id)?;
+
+        data.runtime
+            .find_key_values_by_prefix_paginated_wait(&promise)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
 }
 
 /// An implementation of the system API made available to contracts.
@@ -440,6 +627,22 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Schedules the same message to be sent to this application on each of the `destinations`.
+    fn send_message_to_many(
+        caller: &mut Caller,
+        destinations: Vec<ChainId>,
+        authenticated: bool,
+        is_tracked: bool,
+        grant: Resources,
+        message: Vec<u8>,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .send_message_to_many(destinations, authenticated, is_tracked, grant, message)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Transfers an `amount` of native tokens from `source` owner account (or the current chain's
     /// balance) to `destination`.
     fn transfer(
@@ -469,6 +672,55 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Transfers an `amount` of native tokens from `source` owner account (or the current chain's
+    /// balance) to `destination`. Returns an error if the application isn't allowed to access
+    /// the `source` account or if its balance is too low.
+    fn try_transfer(
+        caller: &mut Caller,
+        source: AccountOwner,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<Result<(), TransferError>, RuntimeError> {
+        match caller
+            .user_data_mut()
+            .runtime
+            .transfer(source, destination, amount)
+        {
+            Ok(()) => Ok(Ok(())),
+            Err(ExecutionError::UnauthenticatedTransferOwner) => {
+                Ok(Err(TransferError::NotPermitted(source)))
+            }
+            Err(ExecutionError::InsufficientFunding { balance, .. }) => {
+                Ok(Err(TransferError::InsufficientFunding(balance)))
+            }
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
+    /// Claims an `amount` of native tokens from a `source` account to a `destination` account.
+    /// Returns an error if the application isn't allowed to access the `source` account.
+    ///
+    /// The `source` account's balance is only checked on its chain, so a claim exceeding it
+    /// doesn't fail here.
+    fn try_claim(
+        caller: &mut Caller,
+        source: Account,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<Result<(), TransferError>, RuntimeError> {
+        match caller
+            .user_data_mut()
+            .runtime
+            .claim(source, destination, amount)
+        {
+            Ok(()) => Ok(Ok(())),
+            Err(ExecutionError::UnauthenticatedClaimOwner) => {
+                Ok(Err(TransferError::NotPermitted(source.owner)))
+            }
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
     /// Opens a new chain, configuring it with the provided `chain_ownership`,
     /// `application_permissions` and initial `balance` (debited from the current chain).
     fn open_chain(
@@ -485,13 +737,14 @@ where
     }
 
     /// Closes the current chain. Returns an error if the application doesn't have
-    /// permission to do so.
+    /// permission to do so, or if the chain is already closed.
     fn close_chain(caller: &mut Caller) -> Result<Result<(), CloseChainError>, RuntimeError> {
         match caller.user_data_mut().runtime.close_chain() {
             Ok(()) => Ok(Ok(())),
             Err(ExecutionError::UnauthorizedApplication(_)) => {
                 Ok(Err(CloseChainError::NotPermitted))
             }
+            Err(ExecutionError::ChainAlreadyClosed) => Ok(Err(CloseChainError::AlreadyClosed)),
             Err(error) => Err(RuntimeError::Custom(error.into())),
         }
     }
@@ -531,6 +784,19 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Creates a new data blob with the given content and returns its hash. Returns an error
+    /// if the blob is too large.
+    fn create_data_blob(
+        caller: &mut Caller,
+        bytes: Vec<u8>,
+    ) -> Result<Result<CryptoHash, CreateDataBlobError>, RuntimeError> {
+        match caller.user_data_mut().runtime.create_data_blob(bytes) {
+            Ok(hash) => Ok(Ok(hash)),
+            Err(ExecutionError::BlobTooLarge) => Ok(Err(CreateDataBlobError::TooLarge)),
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
     /// Calls another application.
     fn try_call_application(
         caller: &mut Caller,
@@ -545,6 +811,21 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Calls another application, rolling back all the changes made during the call if the
+    /// callee traps. Returns an error if the call was rolled back.
+    fn try_call_application_with_rollback(
+        caller: &mut Caller,
+        authenticated: bool,
+        callee_id: ApplicationId,
+        argument: Vec<u8>,
+    ) -> Result<Result<Vec<u8>, CalleeError>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .try_call_application_with_rollback(authenticated, callee_id, argument)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Adds a new item to an event stream. Returns the new event's index in the stream.
     fn emit(caller: &mut Caller, name: StreamName, value: Vec<u8>) -> Result<u32, RuntimeError> {
         caller
@@ -570,6 +851,19 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Returns the index the next event in a stream will have.
+    fn next_event_index(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        name: StreamName,
+    ) -> Result<u32, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .next_event_index(chain_id, name)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Subscribes this application to an event stream.
     fn subscribe_to_events(
         caller: &mut Caller,
@@ -611,6 +905,31 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Makes an HTTP request as an oracle with the limits from `options`, and returns the HTTP
+    /// response. Returns an error if the request times out, if the response is too large, or if
+    /// the host is not allowed.
+    fn try_perform_http_request(
+        caller: &mut Caller,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<Result<http::Response, http::RequestError>, RuntimeError> {
+        match caller
+            .user_data_mut()
+            .runtime
+            .perform_http_request_with_options(request, options)
+        {
+            Ok(response) => Ok(Ok(response)),
+            Err(ExecutionError::HttpRequestTimedOut { .. }) => Ok(Err(http::RequestError::Timeout)),
+            Err(ExecutionError::HttpResponseSizeLimitExceeded { .. }) => {
+                Ok(Err(http::RequestError::ResponseTooLarge))
+            }
+            Err(ExecutionError::UnauthorizedHttpRequest(_)) => {
+                Ok(Err(http::RequestError::NotAllowed))
+            }
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
     /// Consume some fuel.
     ///
     /// This is intended for the metering instrumentation, but if the user wants to donate
@@ -623,6 +942,15 @@ where
             .map_err(|e| RuntimeError::Custom(e.into()))
     }
 
+    /// Returns the amount of execution fuel remaining before execution is aborted.
+    fn remaining_fuel(caller: &mut Caller) -> Result<u64, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .remaining_fuel(VmRuntime::Wasm)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Returns the round in which this block was validated.
     fn validation_round(caller: &mut Caller) -> Result<Option<u32>, RuntimeError> {
         caller
@@ -656,7 +984,9 @@ where
     Runtime: ServiceRuntime + 'static,
 {
     /// Schedules an operation to be included in the block being built by this query.
-    fn schedule_operation(caller: &mut Caller, operation: Vec<u8>) -> Result<(), RuntimeError> {
+    ///
+    /// Returns the index of the operation among the operations scheduled by this query.
+    fn schedule_operation(caller: &mut Caller, operation: Vec<u8>) -> Result<u32, RuntimeError> {
         caller
             .user_data_mut()
             .runtime
@@ -664,28 +994,156 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
-    /// Queries another application.
+    /// Returns the operations scheduled so far by the current application during this query.
+    fn scheduled_operations(caller: &mut Caller) -> Result<Vec<Vec<u8>>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .scheduled_operations()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Queries another application. Returns an error if the queried application doesn't exist
+    /// on the chain or if it traps while handling the query.
     fn try_query_application(
         caller: &mut Caller,
         application: ApplicationId,
         argument: Vec<u8>,
-    ) -> Result<Vec<u8>, RuntimeError> {
-        caller
+    ) -> Result<Result<Vec<u8>, ApplicationQueryError>, RuntimeError> {
+        match caller
             .user_data_mut()
             .runtime
             .try_query_application(application, argument)
+        {
+            Ok(response) => Ok(Ok(response)),
+            Err(ExecutionError::BlobsNotFound(_) | ExecutionError::UnknownApplicationId(_)) => {
+                Ok(Err(ApplicationQueryError::NotFound))
+            }
+            Err(ExecutionError::UserError(_) | ExecutionError::WasmError(_)) => {
+                Ok(Err(ApplicationQueryError::Trapped))
+            }
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
+    /// Makes an HTTP request as an oracle with the limits from `options`, and returns the HTTP
+    /// response. Returns an error if the request times out, if the response is too large, or if
+    /// the host is not allowed.
+    fn try_perform_http_request(
+        caller: &mut Caller,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<Result<http::Response, http::RequestError>, RuntimeError> {
+        match caller
+            .user_data_mut()
+            .runtime
+            .perform_http_request_with_options(request, options)
+        {
+            Ok(response) => Ok(Ok(response)),
+            Err(ExecutionError::HttpRequestTimedOut { .. }) => Ok(Err(http::RequestError::Timeout)),
+            Err(ExecutionError::HttpResponseSizeLimitExceeded { .. }) => {
+                Ok(Err(http::RequestError::ResponseTooLarge))
+            }
+            Err(ExecutionError::UnauthorizedHttpRequest(_)) => {
+                Ok(Err(http::RequestError::NotAllowed))
+            }
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
+    /// Reads several data blobs from storage, returning `None` for the ones that don't exist.
+    fn try_read_data_blobs(
+        caller: &mut Caller,
+        hashes: Vec<CryptoHash>,
+    ) -> Result<Vec<Option<Vec<u8>>>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .try_read_data_blobs(&hashes)
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
-    /// Checks if the service has exceeded its execution time limit.
+    /// Reads an event from a stream of the given application. Returns the event's value.
     ///
-    /// This is called by the metering instrumentation, but the fuel consumed argument is
-    /// ignored.
-    fn check_execution_time(caller: &mut Caller, _fuel_consumed: u64) -> Result<(), RuntimeError> {
+    /// Returns an error if the event doesn't exist.
+    fn read_event(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        index: u32,
+    ) -> Result<Vec<u8>, RuntimeError> {
         caller
             .user_data_mut()
-            .runtime_mut()
-            .check_execution_time()
+            .runtime
+            .read_event(chain_id, application_id, name, index)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Reads the events with indices from `start` (inclusive) to `end` (exclusive) from a
+    /// stream of the given application.
+    fn read_events_in_range(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        start: u32,
+        end: u32,
+    ) -> Result<Vec<Vec<u8>>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .read_events_in_range(chain_id, application_id, name, start, end)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns the hashes of the chain's confirmed blocks with heights in `start..end`.
+    fn block_hashes(
+        caller: &mut Caller,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .block_hashes(start, end)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns a summary of the header of the chain's confirmed block at `height`, if any.
+    fn block_header(
+        caller: &mut Caller,
+        height: BlockHeight,
+    ) -> Result<Option<BlockHeaderSummary>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .block_header(height)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns a page of summaries of the applications registered on the chain, starting after
+    /// `start_after`, if provided.
+    fn registered_applications(
+        caller: &mut Caller,
+        start_after: Option<ApplicationId>,
+    ) -> Result<Vec<ApplicationDescriptionSummary>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .registered_applications(start_after)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Checks if the service has exceeded its execution time or fuel limit.
+    ///
+    /// This is called by the metering instrumentation, so that a looping service stops
+    /// cooperatively.
+    fn check_execution_time(caller: &mut Caller, fuel_consumed: u64) -> Result<(), RuntimeError> {
+        let runtime = caller.user_data_mut().runtime_mut();
+        runtime
+            .consume_query_fuel(fuel_consumed)
+            .and_then(|()| runtime.check_execution_time())
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 }
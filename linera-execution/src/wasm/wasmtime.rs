@@ -174,6 +174,6 @@ where
     fn handle_query(&mut self, argument: Vec<u8>) -> Result<Vec<u8>, ExecutionError> {
         Ok(ServiceEntrypoints::new(&mut self.instance)
             .handle_query(argument)
-            .map_err(WasmExecutionError::from)?)
+            .map_err(|error| WasmExecutionError::from(error).into_query_error())?)
     }
 }
@@ -5,6 +5,7 @@
 
 use std::{
     collections::{BTreeMap, BTreeSet},
+    sync::Arc,
     vec,
 };
 
@@ -13,10 +14,13 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, Blob, BlockHeight,
-        CompressedBytecode, OracleResponse,
+        CompressedBytecode, OracleResponse, Resources, Round, SendMessageRequest, Timestamp,
     },
     http,
-    identifiers::{Account, AccountOwner, ApplicationId, ModuleId},
+    identifiers::{
+        Account, AccountOwner, ApplicationId, BlobId, BlobType, EventId, GenericApplicationId,
+        ModuleId, StreamId, StreamName,
+    },
     ownership::ChainOwnership,
     vm::VmRuntime,
 };
@@ -24,13 +28,17 @@ use linera_execution::{
     test_utils::{
         create_dummy_message_context, create_dummy_operation_context, dummy_chain_description,
         dummy_chain_description_with_ownership_and_balance, test_accounts_strategy, ExpectedCall,
-        RegisterMockApplication, SystemExecutionState,
+        MockApplication, RegisterMockApplication, SystemExecutionState,
     },
-    BaseRuntime, ContractRuntime, ExecutionError, Message, MessageContext, Operation,
-    OperationContext, ResourceController, SystemExecutionStateView, TestExecutionRuntimeContext,
-    TransactionOutcome, TransactionTracker,
+    BaseRuntime, ContractRuntime, ExecutionError, ExecutionRuntimeContext, Message, MessageContext,
+    MessageKind, Operation, OperationContext, ResourceControlPolicy, ResourceController,
+    SystemExecutionStateView, TestExecutionRuntimeContext, TransactionOutcome, TransactionTracker,
+};
+use linera_views::{
+    context::{Context as _, MemoryContext},
+    views::View,
+    ViewError,
 };
-use linera_views::context::MemoryContext;
 use test_case::{test_case, test_matrix};
 use test_strategy::proptest;
 
@@ -191,6 +199,69 @@ async fn test_unauthorized_transfer_system_api(
     Ok(())
 }
 
+/// Tests that a transfer exceeding the balance returns an error the contract can recover from,
+/// so that it can fall back to a smaller transfer.
+#[test_case(TransferTestEndpoint::Chain; "from chain")]
+#[test_case(TransferTestEndpoint::User; "from user")]
+#[test_case(TransferTestEndpoint::Application; "from application")]
+#[test_log::test(tokio::test)]
+async fn test_transfer_with_insufficient_funds_is_recoverable(
+    sender: TransferTestEndpoint,
+) -> anyhow::Result<()> {
+    let amount = Amount::ONE;
+
+    let state = sender.create_system_state(amount);
+    let chain_id = state.description.unwrap().id();
+    let mut view = sender.create_system_state(amount).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let destination = Account {
+        owner: TransferTestEndpoint::recipient_owner(),
+        chain_id: dummy_chain_description(1).id(),
+    };
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let source = sender.sender_account_owner();
+            assert_matches!(
+                runtime.transfer(source, destination, amount.saturating_mul(2)),
+                Err(ExecutionError::InsufficientFunding { balance, account })
+                    if balance == amount && account == source
+            );
+            runtime.transfer(source, destination, amount)?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = OperationContext {
+        authenticated_signer: sender.signer(),
+        ..create_dummy_operation_context(chain_id)
+    };
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying_blobs([
+        app_desc_blob_id,
+        contract_blob_id,
+        service_blob_id,
+    ]);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    let TransactionOutcome {
+        outgoing_messages, ..
+    } = tracker.into_outcome()?;
+    assert_eq!(outgoing_messages.len(), 1);
+    assert_eq!(outgoing_messages[0].destination, destination.chain_id);
+
+    Ok(())
+}
+
 /// Tests the contract system API to claim tokens from a remote account.
 #[test_matrix(
     [TransferTestEndpoint::User, TransferTestEndpoint::Application],
@@ -690,6 +761,25 @@ impl TransferTestEndpoint {
         })
     }
 
+    /// Registers the sender application in the `view`, and returns its ID, its mock and the IDs
+    /// of its description, contract and service blobs.
+    async fn register_sender_application(
+        view: &mut impl RegisterMockApplication,
+    ) -> anyhow::Result<(ApplicationId, MockApplication, [BlobId; 3])> {
+        let contract_blob = Self::sender_application_contract_blob();
+        let service_blob = Self::sender_application_service_blob();
+        let description = Self::sender_application_description();
+        let blob_ids = [
+            Blob::new_application_description(&description).id(),
+            contract_blob.id(),
+            service_blob.id(),
+        ];
+        let (application_id, application) = view
+            .register_mock_application_with(description, contract_blob, service_blob)
+            .await?;
+        Ok((application_id, application, blob_ids))
+    }
+
     /// Returns the [`Owner`] used to represent a recipient that's a user.
     fn recipient_owner() -> AccountOwner {
         AccountOwner::from(CryptoHash::test_hash("recipient"))
@@ -893,6 +983,7 @@ async fn test_perform_http_request(authorized_apps: Option<Vec<()>>) -> Result<(
         balances: BTreeMap::new(),
         ..SystemExecutionState::new(description)
     }
+    .with_http_request_allow_list(["localhost"])
     .into_view()
     .await;
 
@@ -950,3 +1041,915 @@ async fn test_perform_http_request(authorized_apps: Option<Vec<()>>) -> Result<(
 
     Ok(())
 }
+
+/// Tests that batched HTTP requests are answered in order and recorded as separate oracle
+/// responses.
+#[test_log::test(tokio::test)]
+async fn test_perform_http_requests() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description)
+        .with_http_request_allow_list(["localhost"])
+        .into_view()
+        .await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let responses = vec![
+        http::Response::ok(b"first".to_vec()),
+        http::Response::ok(b"second".to_vec()),
+        http::Response::unauthorized(),
+    ];
+    let expected_responses = responses.clone();
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let requests = ["first", "second", "third"]
+                .into_iter()
+                .map(|path| http::Request::get(format!("http://localhost/{path}")))
+                .collect();
+            assert_eq!(runtime.perform_http_requests(requests)?, expected_responses);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+    ];
+    oracle_responses.extend(responses.into_iter().map(OracleResponse::Http));
+    let mut txn_tracker = TransactionTracker::new_replaying(oracle_responses.clone());
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    let txn_outcome = txn_tracker.into_outcome()?;
+    assert_eq!(txn_outcome.oracle_responses, oracle_responses);
+    assert_eq!(controller.tracker.http_requests, 3);
+    Ok(())
+}
+
+/// Tests that batched HTTP requests fail if the recorded responses cover only some of them.
+#[test_log::test(tokio::test)]
+async fn test_perform_http_requests_with_partial_replay() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description)
+        .with_http_request_allow_list(["localhost"])
+        .into_view()
+        .await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let requests = ["first", "second", "third"]
+                .into_iter()
+                .map(|path| http::Request::get(format!("http://localhost/{path}")))
+                .collect();
+            assert_matches!(
+                runtime.perform_http_requests(requests),
+                Err(ExecutionError::OracleResponseMismatch)
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+        OracleResponse::Http(http::Response::ok(b"first".to_vec())),
+        OracleResponse::Http(http::Response::ok(b"second".to_vec())),
+    ];
+    let mut txn_tracker = TransactionTracker::new_replaying(oracle_responses);
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+    Ok(())
+}
+
+/// Tests that checking if a data blob exists doesn't fail for missing blobs, and that both
+/// missing and existing blobs are recorded as oracle responses.
+#[test_log::test(tokio::test)]
+async fn test_data_blob_exists() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let data_blob = Blob::new_data(b"metadata".to_vec());
+    let data_blob_id = data_blob.id();
+    let missing_hash = CryptoHash::test_hash("missing blob");
+    let mut view = SystemExecutionState {
+        extra_blobs: vec![data_blob],
+        ..SystemExecutionState::new(description)
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert!(!runtime.data_blob_exists(&missing_hash)?);
+            assert!(runtime.data_blob_exists(&data_blob_id.hash)?);
+            assert!(runtime.data_blob_exists(&data_blob_id.hash)?);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+        OracleResponse::MissingBlob(BlobId::new(missing_hash, BlobType::Data)),
+        OracleResponse::Blob(data_blob_id),
+    ];
+    let mut txn_tracker = TransactionTracker::new_replaying(oracle_responses.clone());
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    let txn_outcome = txn_tracker.into_outcome()?;
+    assert_eq!(txn_outcome.oracle_responses, oracle_responses);
+    Ok(())
+}
+
+/// Tests that a data blob that was missing when a block was proposed is still reported as
+/// missing when the block is replayed, even if it was published in the meantime.
+#[test_log::test(tokio::test)]
+async fn test_data_blob_exists_replays_missing_blobs() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let data_blob = Blob::new_data(b"published later".to_vec());
+    let data_blob_id = data_blob.id();
+    let mut view = SystemExecutionState {
+        extra_blobs: vec![data_blob],
+        ..SystemExecutionState::new(description)
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert!(!runtime.data_blob_exists(&data_blob_id.hash)?);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+        OracleResponse::MissingBlob(data_blob_id),
+    ];
+    let mut txn_tracker = TransactionTracker::new_replaying(oracle_responses.clone());
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    let txn_outcome = txn_tracker.into_outcome()?;
+    assert_eq!(txn_outcome.oracle_responses, oracle_responses);
+    Ok(())
+}
+
+/// Tests that two operations in the same block emitting into the same stream get consecutive
+/// event indices.
+#[test_log::test(tokio::test)]
+async fn test_emit_into_same_stream_from_two_operations() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let stream_name = StreamName::new(b"receipts");
+    let stream_id = StreamId {
+        application_id: GenericApplicationId::User(application_id),
+        stream_name: stream_name.clone(),
+    };
+
+    for (expected_index, value) in [(0, b"first"), (1, b"other")] {
+        let name = stream_name.clone();
+        application.expect_call(ExpectedCall::execute_operation(
+            move |runtime, _operation| {
+                assert_eq!(runtime.emit(name, value.to_vec())?, expected_index);
+                Ok(vec![])
+            },
+        ));
+        application.expect_call(ExpectedCall::default_finalize());
+
+        let context = create_dummy_operation_context(chain_id);
+        let mut controller = ResourceController::default();
+        let operation = Operation::User {
+            application_id,
+            bytes: vec![],
+        };
+        let mut tracker = TransactionTracker::new_replaying_blobs([
+            app_desc_blob_id,
+            contract_blob_id,
+            service_blob_id,
+        ]);
+        view.execute_operation(context, operation, &mut tracker, &mut controller)
+            .await?;
+
+        let TransactionOutcome { events, .. } = tracker.into_outcome()?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].stream_id, stream_id);
+        assert_eq!(events[0].index, expected_index);
+        assert_eq!(events[0].value, value.to_vec());
+    }
+
+    assert_eq!(view.stream_event_counts.get(&stream_id).await?, Some(2));
+    Ok(())
+}
+
+/// Tests that a message sent to several chains in a single call results in one outgoing message
+/// per destination.
+#[test_log::test(tokio::test)]
+async fn test_send_message_to_many() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let destinations = vec![
+        dummy_chain_description(1).id(),
+        dummy_chain_description(2).id(),
+        dummy_chain_description(3).id(),
+    ];
+    let message = b"airdrop".to_vec();
+
+    application.expect_call(ExpectedCall::execute_operation({
+        let destinations = destinations.clone();
+        let message = message.clone();
+        move |runtime, _operation| {
+            runtime.send_message_to_many(
+                destinations,
+                false,
+                true,
+                Resources::default(),
+                message,
+            )?;
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying_blobs([
+        app_desc_blob_id,
+        contract_blob_id,
+        service_blob_id,
+    ]);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    let TransactionOutcome {
+        outgoing_messages, ..
+    } = tracker.into_outcome()?;
+    assert_eq!(
+        outgoing_messages
+            .iter()
+            .map(|outgoing_message| outgoing_message.destination)
+            .collect::<Vec<_>>(),
+        destinations
+    );
+    for outgoing_message in outgoing_messages {
+        assert_eq!(outgoing_message.kind, MessageKind::Tracked);
+        assert_eq!(
+            outgoing_message.message,
+            Message::User {
+                application_id,
+                bytes: message.clone(),
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Tests that a data blob created by a contract is registered as created by the transaction.
+#[test_log::test(tokio::test)]
+async fn test_create_data_blob() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let expected_blob = Blob::new_data(b"blob content".to_vec());
+    let expected_hash = expected_blob.id().hash;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let hash = runtime.create_data_blob(b"blob content".to_vec())?;
+            assert_eq!(hash, expected_hash);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying_blobs([
+        app_desc_blob_id,
+        contract_blob_id,
+        service_blob_id,
+    ]);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    let TransactionOutcome { blobs, .. } = tracker.into_outcome()?;
+    assert_eq!(blobs, vec![expected_blob]);
+    Ok(())
+}
+
+/// Tests that creating a data blob larger than the maximum blob size is reported to the
+/// contract, which can handle the error.
+#[test_log::test(tokio::test)]
+async fn test_create_data_blob_too_large() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_matches!(
+                runtime.create_data_blob(b"too large".to_vec()),
+                Err(ExecutionError::BlobTooLarge)
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController {
+        policy: Arc::new(ResourceControlPolicy {
+            maximum_blob_size: 4,
+            ..ResourceControlPolicy::default()
+        }),
+        ..ResourceController::default()
+    };
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying_blobs([
+        app_desc_blob_id,
+        contract_blob_id,
+        service_blob_id,
+    ]);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    let TransactionOutcome { blobs, .. } = tracker.into_outcome()?;
+    assert!(blobs.is_empty());
+    Ok(())
+}
+
+/// Tests that a contract can use the remaining fuel to stop processing items before exhausting
+/// the block's fuel limit, and schedule the rest of the work with a message to its own chain.
+#[test_log::test(tokio::test)]
+async fn test_remaining_fuel_allows_scheduling_a_continuation() -> anyhow::Result<()> {
+    const ITEMS: u64 = 10;
+    const FUEL_PER_ITEM: u64 = 150;
+    const FUEL_THRESHOLD: u64 = 300;
+
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let mut remaining_items = ITEMS;
+            while remaining_items > 0 && runtime.remaining_fuel(VmRuntime::Wasm)? >= FUEL_THRESHOLD
+            {
+                runtime.consume_fuel(FUEL_PER_ITEM, VmRuntime::Wasm)?;
+                remaining_items -= 1;
+            }
+            if remaining_items > 0 {
+                runtime.send_message(SendMessageRequest {
+                    destination: chain_id,
+                    authenticated: false,
+                    is_tracked: false,
+                    grant: Resources::default(),
+                    message: remaining_items.to_le_bytes().to_vec(),
+                })?;
+            }
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController {
+        policy: Arc::new(ResourceControlPolicy {
+            maximum_wasm_fuel_per_block: 1_000,
+            ..ResourceControlPolicy::default()
+        }),
+        ..ResourceController::default()
+    };
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying_blobs([
+        app_desc_blob_id,
+        contract_blob_id,
+        service_blob_id,
+    ]);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    // Five items are processed before the remaining fuel drops from 1000 to 250.
+    assert_eq!(controller.tracker.wasm_fuel, 5 * FUEL_PER_ITEM);
+
+    let TransactionOutcome {
+        outgoing_messages, ..
+    } = tracker.into_outcome()?;
+    assert_eq!(outgoing_messages.len(), 1);
+    assert_eq!(outgoing_messages[0].destination, chain_id);
+    assert_eq!(
+        outgoing_messages[0].message,
+        Message::User {
+            application_id,
+            bytes: 5_u64.to_le_bytes().to_vec(),
+        }
+    );
+    Ok(())
+}
+
+/// Tests that the consensus round is recorded as an oracle response, while the block proposer
+/// is read from the block itself.
+#[test_log::test(tokio::test)]
+async fn test_round_and_block_proposer() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, _) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let round = Round::SingleLeader(3);
+    let proposer = AccountOwner::from(CryptoHash::test_hash("proposer"));
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(runtime.round()?, Some(round));
+            assert_eq!(runtime.block_proposer()?, Some(proposer));
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = OperationContext {
+        round: Some(round),
+        block_proposer: Some(proposer),
+        ..create_dummy_operation_context(chain_id)
+    };
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new(Timestamp::from(0), 0, 0, 0, 0, None);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    let TransactionOutcome {
+        oracle_responses, ..
+    } = tracker.into_outcome()?;
+    assert_eq!(
+        oracle_responses.last(),
+        Some(&OracleResponse::ConsensusRound(Some(round)))
+    );
+    Ok(())
+}
+
+/// Tests that the consensus round is taken from the recorded oracle responses when replaying a
+/// block, regardless of the round the block is executed in.
+#[test_log::test(tokio::test)]
+async fn test_round_is_replayed_from_oracle_responses() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let recorded_round = Round::MultiLeader(2);
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(runtime.round()?, Some(recorded_round));
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying(vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+        OracleResponse::ConsensusRound(Some(recorded_round)),
+    ]);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+    Ok(())
+}
+
+/// Tests that reading the fast round isn't recorded as an oracle response, so that the block can
+/// still be confirmed in the fast round.
+#[test_log::test(tokio::test)]
+async fn test_fast_round_is_not_recorded() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(runtime.round()?, Some(Round::Fast));
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = OperationContext {
+        round: Some(Round::Fast),
+        ..create_dummy_operation_context(chain_id)
+    };
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new(Timestamp::from(0), 0, 0, 0, 0, None);
+    view.execute_operation(context, operation, &mut tracker, &mut controller)
+        .await?;
+
+    let TransactionOutcome {
+        oracle_responses, ..
+    } = tracker.into_outcome()?;
+    assert_eq!(
+        oracle_responses,
+        vec![
+            OracleResponse::Blob(app_desc_blob_id),
+            OracleResponse::Blob(contract_blob_id),
+            OracleResponse::Blob(service_blob_id),
+        ]
+    );
+    Ok(())
+}
+
+/// Tests that events from the current chain and from other chains are recorded as oracle
+/// responses, and that the next event indices are reported for both.
+#[test_log::test(tokio::test)]
+async fn test_read_event_and_next_event_index() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let remote_chain_id = dummy_chain_description(1).id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let stream_name = StreamName::new(b"updates");
+    let stream_id = StreamId {
+        application_id: GenericApplicationId::User(application_id),
+        stream_name: stream_name.clone(),
+    };
+    let local_event_id = EventId {
+        chain_id,
+        stream_id: stream_id.clone(),
+        index: 0,
+    };
+    let remote_event_id = EventId {
+        chain_id: remote_chain_id,
+        stream_id: stream_id.clone(),
+        index: 2,
+    };
+    view.context()
+        .extra()
+        .add_events([
+            (local_event_id.clone(), b"local".to_vec()),
+            (remote_event_id.clone(), b"remote".to_vec()),
+        ])
+        .await?;
+    view.stream_event_counts.insert(&stream_id, 1)?;
+    view.system
+        .event_subscriptions
+        .get_mut_or_default(&(remote_chain_id, stream_id))
+        .await?
+        .next_index = 3;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(
+                runtime.read_event(chain_id, stream_name.clone(), 0)?,
+                b"local"
+            );
+            assert_eq!(
+                runtime.read_event(remote_chain_id, stream_name.clone(), 2)?,
+                b"remote"
+            );
+            assert_eq!(runtime.next_event_index(chain_id, stream_name.clone())?, 1);
+            assert_eq!(
+                runtime.next_event_index(remote_chain_id, stream_name.clone())?,
+                3
+            );
+            let other_stream = StreamName::new(b"other");
+            assert_eq!(runtime.next_event_index(remote_chain_id, other_stream)?, 0);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+        OracleResponse::Event(local_event_id, b"local".to_vec()),
+        OracleResponse::Event(remote_event_id, b"remote".to_vec()),
+    ];
+    let mut txn_tracker = TransactionTracker::new_replaying(oracle_responses.clone());
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    let txn_outcome = txn_tracker.into_outcome()?;
+    assert_eq!(txn_outcome.oracle_responses, oracle_responses);
+    Ok(())
+}
+
+/// Tests that reading an event with an index that doesn't exist yet fails the operation.
+#[test_log::test(tokio::test)]
+async fn test_read_event_that_does_not_exist_yet() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let remote_chain_id = dummy_chain_description(1).id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+
+    let stream_name = StreamName::new(b"updates");
+    let missing_event_id = EventId {
+        chain_id: remote_chain_id,
+        stream_id: StreamId {
+            application_id: GenericApplicationId::User(application_id),
+            stream_name: stream_name.clone(),
+        },
+        index: 0,
+    };
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(
+                runtime.next_event_index(remote_chain_id, stream_name.clone())?,
+                0
+            );
+            runtime.read_event(remote_chain_id, stream_name, 0)?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut txn_tracker = TransactionTracker::new_replaying_blobs([
+        app_desc_blob_id,
+        contract_blob_id,
+        service_blob_id,
+    ]);
+
+    let result = view
+        .execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await;
+
+    assert_matches!(
+        result,
+        Err(ExecutionError::ViewError(ViewError::EventsNotFound(event_ids)))
+            if event_ids == [missing_event_id]
+    );
+    Ok(())
+}
+
+/// Tests that replaying an HTTP response that exceeds the application's size limit fails the
+/// same way every time, so that all validators agree on the outcome.
+#[test_case(None => matches Ok(()); "without a limit")]
+#[test_case(Some(10) => matches Ok(()); "when the response fits")]
+#[test_case(
+    Some(9) => matches Err(ExecutionError::HttpResponseSizeLimitExceeded { limit: 9, size: 10 });
+    "when the response is too large"
+)]
+#[test_log::test(tokio::test)]
+async fn test_perform_http_request_with_size_limit(
+    max_response_bytes: Option<u64>,
+) -> Result<(), ExecutionError> {
+    let first_outcome = replay_http_request_with_size_limit(max_response_bytes).await;
+    let second_outcome = replay_http_request_with_size_limit(max_response_bytes).await;
+
+    assert_eq!(format!("{first_outcome:?}"), format!("{second_outcome:?}"));
+    first_outcome
+}
+
+/// Executes an operation that makes an HTTP request limited to `max_response_bytes`, replaying
+/// a recorded response with a 10-byte body.
+async fn replay_http_request_with_size_limit(
+    max_response_bytes: Option<u64>,
+) -> Result<(), ExecutionError> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description)
+        .with_http_request_allow_list(["localhost"])
+        .into_view()
+        .await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view)
+            .await
+            .expect("should register mock application");
+
+    let options = http::RequestOptions {
+        max_response_bytes,
+        ..http::RequestOptions::default()
+    };
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            runtime.perform_http_request_with_options(
+                http::Request::get("http://localhost"),
+                options,
+            )?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.execute_operation(
+        context,
+        operation,
+        &mut TransactionTracker::new_replaying(vec![
+            OracleResponse::Blob(app_desc_blob_id),
+            OracleResponse::Blob(contract_blob_id),
+            OracleResponse::Blob(service_blob_id),
+            OracleResponse::Http(http::Response::ok(b"0123456789".to_vec())),
+        ]),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests that an HTTP request to a host that isn't allowed fails with an error the contract can
+/// handle, also when replaying, and that hosts can be allowed for a single application.
+#[test_log::test(tokio::test)]
+async fn test_http_request_allow_list_per_application() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut state = SystemExecutionState::new(description);
+    for committee in state.committees.values_mut() {
+        committee
+            .policy_mut()
+            .http_request_allow_list_per_application
+            .insert(
+                TransferTestEndpoint::sender_application_id(),
+                BTreeSet::from(["localhost".to_owned()]),
+            );
+    }
+    let mut view = state.into_view().await;
+
+    let (application_id, application, [app_desc_blob_id, contract_blob_id, service_blob_id]) =
+        TransferTestEndpoint::register_sender_application(&mut view).await?;
+    assert_eq!(
+        application_id,
+        TransferTestEndpoint::sender_application_id()
+    );
+
+    let response = http::Response::ok(b"allowed".to_vec());
+    let expected_response = response.clone();
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let options = http::RequestOptions::default();
+            assert_matches!(
+                runtime.perform_http_request_with_options(
+                    http::Request::get("http://refused.example"),
+                    options,
+                ),
+                Err(ExecutionError::UnauthorizedHttpRequest(_))
+            );
+            assert_eq!(
+                runtime.perform_http_request_with_options(
+                    http::Request::get("http://localhost"),
+                    options,
+                )?,
+                expected_response
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+        OracleResponse::Http(response),
+    ];
+    let mut txn_tracker = TransactionTracker::new_replaying(oracle_responses.clone());
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    let txn_outcome = txn_tracker.into_outcome()?;
+    assert_eq!(txn_outcome.oracle_responses, oracle_responses);
+    assert_eq!(controller.tracker.http_requests, 1);
+    Ok(())
+}
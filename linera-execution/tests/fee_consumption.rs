@@ -5,11 +5,15 @@
 
 #![allow(clippy::items_after_test_module)]
 
-use std::{collections::BTreeSet, sync::Arc, vec};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec,
+};
 
 use linera_base::{
     crypto::AccountPublicKey,
-    data_types::{Amount, BlockHeight, OracleResponse},
+    data_types::{Amount, BlockHeight, OracleResponse, Round},
     http,
     identifiers::{Account, AccountOwner, MessageId},
     vm::VmRuntime,
@@ -190,8 +194,15 @@ async fn test_fee_consumption(
 ) -> anyhow::Result<()> {
     let chain_description = dummy_chain_description(0);
     let chain_id = chain_description.id();
+    // The committee is only needed to allow the HTTP requests.
+    let SystemExecutionState {
+        epoch, committees, ..
+    } = SystemExecutionState::new(chain_description.clone())
+        .with_http_request_allow_list(["dummy.url"]);
     let mut state = SystemExecutionState {
         description: Some(chain_description.clone()),
+        epoch,
+        committees,
         ..SystemExecutionState::default()
     };
     let (application_id, application, blobs) = state.register_mock_application(0).await?;
@@ -233,11 +244,14 @@ async fn test_fee_consumption(
         maximum_oracle_response_bytes: 79,
         maximum_http_response_bytes: 83,
         http_request_timeout_ms: 89,
+        maximum_concurrent_http_requests: 109,
         blob_read: Amount::from_tokens(97),
         blob_published: Amount::from_tokens(101),
         blob_byte_read: Amount::from_tokens(103),
         blob_byte_published: Amount::from_tokens(107),
+        bytecode_byte_published: Amount::from_tokens(113),
         http_request_allow_list: BTreeSet::new(),
+        http_request_allow_list_per_application: BTreeMap::new(),
     };
 
     let consumed_fees = spends
@@ -279,7 +293,8 @@ async fn test_fee_consumption(
         authenticated_signer,
         refund_grant_to,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         message_id: MessageId::default(),
         timestamp: Default::default(),
     };
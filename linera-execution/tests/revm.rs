@@ -7,7 +7,7 @@ use std::sync::Arc;
 
 use alloy_sol_types::{sol, SolCall, SolValue};
 use linera_base::{
-    data_types::{Amount, Blob, BlockHeight, Timestamp},
+    data_types::{Amount, Blob, BlockHeight, Round, Timestamp},
     vm::EvmQuery,
 };
 use linera_execution::{
@@ -84,7 +84,8 @@ async fn test_fuel_for_counter_revm_application() -> anyhow::Result<()> {
     let operation_context = OperationContext {
         chain_id,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         authenticated_signer: None,
         authenticated_caller_id: None,
         timestamp: Default::default(),
@@ -211,7 +212,8 @@ async fn test_terminate_execute_operation_by_lack_of_fuel() -> anyhow::Result<()
     let operation_context = OperationContext {
         chain_id,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         authenticated_signer: None,
         authenticated_caller_id: None,
         timestamp: Default::default(),
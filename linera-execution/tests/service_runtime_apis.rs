@@ -3,16 +3,30 @@
 
 #![allow(clippy::field_reassign_with_default)]
 
-use std::{collections::BTreeMap, vec};
+use std::{collections::BTreeMap, time::Duration, vec};
 
-use linera_base::{data_types::Amount, identifiers::AccountOwner};
+use assert_matches::assert_matches;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{
+        Amount, ApplicationPermissions, Blob, BlockHeaderSummary, BlockHeight, Epoch, Timestamp,
+    },
+    identifiers::{
+        AccountOwner, ApplicationId, BlobId, BlobType, EventId, GenericApplicationId, StreamId,
+        StreamName,
+    },
+    ownership::ChainOwnership,
+};
 use linera_execution::{
     test_utils::{
-        create_dummy_query_context, dummy_chain_description, test_accounts_strategy, ExpectedCall,
-        RegisterMockApplication, SystemExecutionState,
+        create_dummy_query_context, create_dummy_user_application_description,
+        dummy_chain_description, test_accounts_strategy, ExpectedCall, RegisterMockApplication,
+        SystemExecutionState,
     },
-    BaseRuntime, Query,
+    BaseRuntime, ExecutionError, ExecutionRuntimeContext as _, Operation, Query, QueryLimits,
+    ServiceRuntime, MAX_BLOCK_HASHES_PER_QUERY,
 };
+use linera_views::context::Context as _;
 use test_strategy::proptest;
 
 /// Tests the contract system API to read the chain balance.
@@ -132,6 +146,46 @@ async fn test_read_owner_balances_system_api(
     view.query_application(context, query, None).await.unwrap();
 }
 
+/// Tests the system API to read the balances of a subset of the accounts.
+#[proptest(async = "tokio")]
+async fn test_read_owner_balances_for_system_api(
+    #[strategy(test_accounts_strategy())] accounts: BTreeMap<AccountOwner, Amount>,
+    missing_account: AccountOwner,
+) {
+    let mut view = SystemExecutionState {
+        balances: accounts.clone(),
+        ..SystemExecutionState::new(dummy_chain_description(0))
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await.unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        let mut owners = accounts.keys().rev().copied().collect::<Vec<_>>();
+        let mut expected_balances = accounts.values().rev().copied().collect::<Vec<_>>();
+        if !accounts.contains_key(&missing_account) {
+            owners.push(missing_account);
+            expected_balances.push(Amount::ZERO);
+        }
+
+        assert_eq!(
+            runtime.read_owner_balances_for(&owners).unwrap(),
+            expected_balances
+        );
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await.unwrap();
+}
+
 /// Tests the contract system API to read all account owners.
 #[proptest(async = "tokio")]
 async fn test_read_balance_owners_system_api(
@@ -163,3 +217,535 @@ async fn test_read_balance_owners_system_api(
 
     view.query_application(context, query, None).await.unwrap();
 }
+
+/// Tests if a query to another application that fails is reported to the caller, which can then
+/// continue executing.
+#[tokio::test]
+async fn test_failed_query_to_another_application() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (caller_id, caller_application, _) = view.register_mock_application(0).await?;
+    let (target_id, target_application, _) = view.register_mock_application(1).await?;
+
+    caller_application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_matches!(
+            runtime.try_query_application(target_id, vec![]),
+            Err(ExecutionError::UserError(_))
+        );
+        assert_eq!(runtime.application_id()?, caller_id);
+        Ok(vec![])
+    }));
+    target_application.expect_call(ExpectedCall::handle_query(|_runtime, _query| {
+        Err(ExecutionError::UserError(
+            "Failed to handle query".to_owned(),
+        ))
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id: caller_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests if a query to an application that isn't registered on the chain is reported to the
+/// caller, which can then continue executing.
+#[tokio::test]
+async fn test_query_to_missing_application() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (caller_id, caller_application, _) = view.register_mock_application(0).await?;
+    let missing_id = ApplicationId::new(CryptoHash::test_hash("missing application"));
+
+    caller_application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_matches!(
+            runtime.try_query_application(missing_id, vec![]),
+            Err(ExecutionError::BlobsNotFound(_))
+        );
+        assert_eq!(runtime.application_id()?, caller_id);
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id: caller_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests the system API to read the current epoch and a summary of its committee.
+#[tokio::test]
+async fn test_read_epoch_and_current_committee_system_api() -> anyhow::Result<()> {
+    let state = SystemExecutionState::new(dummy_chain_description(0));
+    let expected_committee = state.committees[&Epoch::ZERO].summary();
+    assert_eq!(expected_committee.validator_count, 1);
+    let mut view = state.into_view().await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(runtime.read_epoch()?, Epoch::ZERO);
+        assert_eq!(runtime.read_current_committee()?, expected_committee);
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests the system API to read the chain ownership and the application permissions.
+#[tokio::test]
+async fn test_read_chain_ownership_and_application_permissions_system_api() -> anyhow::Result<()> {
+    let owner = AccountOwner::from(CryptoHash::test_hash("owner"));
+    let ownership = ChainOwnership::single(owner);
+    let permitted_application_id = ApplicationId::new(CryptoHash::test_hash("permitted"));
+    let application_permissions = ApplicationPermissions {
+        make_http_requests: Some(vec![permitted_application_id]),
+        ..ApplicationPermissions::default()
+    };
+
+    let mut view = SystemExecutionState {
+        ownership: ownership.clone(),
+        application_permissions: application_permissions.clone(),
+        ..SystemExecutionState::new(dummy_chain_description(0))
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(runtime.chain_ownership()?, ownership);
+        assert_eq!(runtime.application_permissions()?, application_permissions);
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests if a service can read the hashes and headers of the chain's confirmed blocks.
+#[tokio::test]
+async fn test_block_hashes_and_headers_system_api() -> anyhow::Result<()> {
+    let signer = AccountOwner::from(CryptoHash::test_hash("signer"));
+    let first_hash = CryptoHash::test_hash("first block");
+    let second_hash = CryptoHash::test_hash("second block");
+    let first_header = BlockHeaderSummary {
+        height: BlockHeight(0),
+        timestamp: Timestamp::from(1_000),
+        epoch: Epoch::ZERO,
+        authenticated_signer: None,
+    };
+    let second_header = BlockHeaderSummary {
+        height: BlockHeight(1),
+        timestamp: Timestamp::from(2_000),
+        epoch: Epoch::ZERO,
+        authenticated_signer: Some(signer),
+    };
+
+    let mut view = SystemExecutionState {
+        confirmed_blocks: vec![(first_hash, first_header), (second_hash, second_header)],
+        ..SystemExecutionState::new(dummy_chain_description(0))
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(
+            runtime.block_hashes(BlockHeight(0), BlockHeight(10))?,
+            vec![first_hash, second_hash]
+        );
+        assert_eq!(
+            runtime.block_hashes(BlockHeight(1), BlockHeight(2))?,
+            vec![second_hash]
+        );
+        assert_eq!(runtime.block_header(BlockHeight(1))?, Some(second_header));
+        assert_eq!(runtime.block_header(BlockHeight(2))?, None);
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests that a service reads at most `MAX_BLOCK_HASHES_PER_QUERY` block hashes at once.
+#[tokio::test]
+async fn test_block_hashes_system_api_is_capped() -> anyhow::Result<()> {
+    let confirmed_blocks = (0..=MAX_BLOCK_HASHES_PER_QUERY)
+        .map(|height| {
+            let header = BlockHeaderSummary {
+                height: BlockHeight(height),
+                timestamp: Timestamp::from(height),
+                epoch: Epoch::ZERO,
+                authenticated_signer: None,
+            };
+            (CryptoHash::test_hash(format!("block {height}")), header)
+        })
+        .collect::<Vec<_>>();
+    let expected_hashes = confirmed_blocks
+        .iter()
+        .map(|(hash, _)| *hash)
+        .collect::<Vec<_>>();
+
+    let mut view = SystemExecutionState {
+        confirmed_blocks,
+        ..SystemExecutionState::new(dummy_chain_description(0))
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        let max = usize::try_from(MAX_BLOCK_HASHES_PER_QUERY).unwrap();
+        assert_eq!(
+            runtime.block_hashes(BlockHeight(0), BlockHeight::MAX)?,
+            expected_hashes[..max]
+        );
+        assert_eq!(
+            runtime.block_hashes(BlockHeight(1), BlockHeight::MAX)?,
+            expected_hashes[1..]
+        );
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests if a service can list the applications registered on its chain, one page at a time.
+#[tokio::test]
+async fn test_registered_applications_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let mut summaries = Vec::new();
+    let mut applications = Vec::new();
+    for index in 0..3 {
+        let (description, _, _) = create_dummy_user_application_description(index);
+        let (application_id, application, [description_blob_id, _, _]) =
+            view.register_mock_application(index).await?;
+        view.system.used_blobs.insert(&description_blob_id)?;
+        summaries.push(description.summary());
+        applications.push((application_id, application));
+    }
+    summaries.sort_by_key(|summary| summary.application_id);
+
+    let (application_id, application) = applications.swap_remove(0);
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(runtime.registered_applications(None)?, summaries);
+        assert_eq!(
+            runtime.registered_applications(Some(summaries[0].application_id))?,
+            summaries[1..]
+        );
+        assert_eq!(
+            runtime.registered_applications(Some(summaries[2].application_id))?,
+            vec![]
+        );
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests if a service can read several data blobs at once, and if missing blobs are reported.
+#[tokio::test]
+async fn test_read_data_blobs_system_api() -> anyhow::Result<()> {
+    let first_blob = Blob::new_data(b"first".to_vec());
+    let second_blob = Blob::new_data(b"second".to_vec());
+    let first_hash = first_blob.id().hash;
+    let second_hash = second_blob.id().hash;
+    let missing_hash = CryptoHash::test_hash("missing blob");
+
+    let mut view = SystemExecutionState {
+        extra_blobs: vec![first_blob, second_blob],
+        ..SystemExecutionState::new(dummy_chain_description(0))
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(
+            runtime.read_data_blobs(&[second_hash, first_hash])?,
+            vec![b"second".to_vec(), b"first".to_vec()]
+        );
+        assert_eq!(
+            runtime.try_read_data_blobs(&[first_hash, missing_hash, second_hash])?,
+            vec![Some(b"first".to_vec()), None, Some(b"second".to_vec())]
+        );
+        assert_matches!(
+            runtime.read_data_blobs(&[first_hash, missing_hash]),
+            Err(ExecutionError::BlobsNotFound(blob_ids))
+                if blob_ids == vec![BlobId::new(missing_hash, BlobType::Data)]
+        );
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests if a service can read a data blob in chunks.
+#[tokio::test]
+async fn test_read_data_blob_range_system_api() -> anyhow::Result<()> {
+    let blob = Blob::new_data(b"0123456789".to_vec());
+    let hash = blob.id().hash;
+
+    let mut view = SystemExecutionState {
+        extra_blobs: vec![blob],
+        ..SystemExecutionState::new(dummy_chain_description(0))
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(runtime.data_blob_size(&hash)?, 10);
+        assert_eq!(runtime.read_data_blob_range(&hash, 0, 4)?, b"0123".to_vec());
+        assert_eq!(runtime.read_data_blob_range(&hash, 8, 4)?, b"89".to_vec());
+        assert_eq!(
+            runtime.read_data_blob_range(&hash, 10, 4)?,
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            runtime.read_data_blob_range(&hash, u64::MAX, u64::MAX)?,
+            Vec::<u8>::new()
+        );
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
+
+/// Tests if a service can inspect the operations it has scheduled during a query.
+#[tokio::test]
+async fn test_scheduled_operations_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(runtime.scheduled_operations()?, Vec::<Vec<u8>>::new());
+        assert_eq!(runtime.schedule_operation(b"first".to_vec())?, 0);
+        assert_eq!(runtime.schedule_operation(b"second".to_vec())?, 1);
+        assert_eq!(
+            runtime.scheduled_operations()?,
+            vec![b"first".to_vec(), b"second".to_vec()]
+        );
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let outcome = view.query_application(context, query, None).await?;
+    assert_eq!(
+        outcome.operations,
+        vec![
+            Operation::User {
+                application_id,
+                bytes: b"first".to_vec(),
+            },
+            Operation::User {
+                application_id,
+                bytes: b"second".to_vec(),
+            },
+        ]
+    );
+    Ok(())
+}
+
+/// Tests if a looping service is stopped once it runs out of the fuel of its query.
+#[tokio::test]
+async fn test_query_out_of_fuel() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(|runtime, _query| {
+        let mut iterations = 0;
+        let error = loop {
+            if let Err(error) = runtime.consume_query_fuel(100) {
+                break error;
+            }
+            iterations += 1;
+        };
+        assert_eq!(iterations, 10);
+        Err(error)
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+    let limits = QueryLimits {
+        fuel: Some(1_000),
+        ..QueryLimits::default()
+    };
+
+    let result = view
+        .query_application_with_limits(context, query, limits, None)
+        .await;
+    assert_matches!(result, Err(ExecutionError::QueryOutOfFuel));
+    Ok(())
+}
+
+/// Tests if a looping service is stopped once its query times out.
+#[tokio::test]
+async fn test_query_timed_out() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::handle_query(|runtime, _query| loop {
+        runtime.check_execution_time()?;
+        std::thread::sleep(Duration::from_millis(1));
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+    let limits = QueryLimits {
+        timeout: Some(Duration::from_millis(50)),
+        ..QueryLimits::default()
+    };
+
+    let result = view
+        .query_application_with_limits(context, query, limits, None)
+        .await;
+    assert_matches!(result, Err(ExecutionError::QueryTimedOut));
+    Ok(())
+}
+
+/// Tests if a service can read the events of another application's stream.
+#[tokio::test]
+async fn test_read_event_of_other_application_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await?;
+    let stream_owner = ApplicationId::new(CryptoHash::test_hash("stream owner"));
+    let remote_chain_id = dummy_chain_description(1).id();
+    let stream_name = StreamName::new(b"updates");
+    let events = (0..3).map(|index| {
+        let event_id = EventId {
+            chain_id: remote_chain_id,
+            stream_id: StreamId {
+                application_id: GenericApplicationId::User(stream_owner),
+                stream_name: stream_name.clone(),
+            },
+            index,
+        };
+        (event_id, vec![index as u8])
+    });
+    view.context().extra().add_events(events).await?;
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        assert_eq!(
+            runtime.read_event(remote_chain_id, stream_owner, stream_name.clone(), 0)?,
+            vec![0]
+        );
+        assert_eq!(
+            runtime.read_events_in_range(
+                remote_chain_id,
+                stream_owner,
+                stream_name.clone(),
+                1,
+                3
+            )?,
+            vec![vec![1], vec![2]]
+        );
+        // The querying application has no events in a stream with the same name.
+        assert!(runtime
+            .read_event(remote_chain_id, application_id, stream_name, 0)
+            .is_err());
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await?;
+    Ok(())
+}
@@ -9,19 +9,21 @@ use assert_matches::assert_matches;
 use linera_base::{
     crypto::{AccountPublicKey, ValidatorPublicKey},
     data_types::{
-        Amount, ApplicationPermissions, Blob, BlockHeight, ChainDescription, ChainOrigin, Epoch,
-        InitialChainConfig, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationDescription, ApplicationPermissions, Blob, BlockHeight, CalleeError,
+        ChainDescription, ChainOrigin, Epoch, InitialChainConfig, Resources, SendMessageRequest,
+        TimeDelta, Timestamp,
     },
     identifiers::{Account, AccountOwner, BlobType},
-    ownership::ChainOwnership,
+    ownership::{ChainOwnership, TimeoutConfig},
+    vm::VmRuntime,
 };
 use linera_execution::{
     committee::Committee,
     test_utils::{
         blob_oracle_responses, create_dummy_message_context, create_dummy_operation_context,
-        create_dummy_user_application_registrations, dummy_chain_description,
-        dummy_chain_description_with_ownership_and_balance, ExpectedCall, RegisterMockApplication,
-        SystemExecutionState,
+        create_dummy_user_application_description, create_dummy_user_application_registrations,
+        dummy_chain_description, dummy_chain_description_with_ownership_and_balance, ExpectedCall,
+        RegisterMockApplication, SystemExecutionState,
     },
     BaseRuntime, ContractRuntime, ExecutionError, ExecutionRuntimeContext, Message, Operation,
     OperationContext, OutgoingMessage, Query, QueryContext, QueryOutcome, QueryResponse,
@@ -299,6 +301,64 @@ async fn test_simulated_session() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that paginated prefix scans return at most the requested number of entries, with a
+/// continuation key only when more entries remain.
+#[tokio::test]
+async fn test_paginated_prefix_scan() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+
+    let (application_id, application, blobs) = view.register_mock_application(0).await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let mut batch = Batch::new();
+            for key in [b"a1", b"a2", b"a3", b"b1"] {
+                batch.put_key_value_bytes(key.to_vec(), key.to_vec());
+            }
+            runtime.write_batch(batch)?;
+
+            let promise = runtime.find_keys_by_prefix_paginated_new(b"a".to_vec(), None, 2)?;
+            let (keys, continuation) = runtime.find_keys_by_prefix_paginated_wait(&promise)?;
+            assert_eq!(keys, vec![b"1".to_vec(), b"2".to_vec()]);
+            assert_eq!(continuation, Some(b"2".to_vec()));
+
+            let promise =
+                runtime.find_keys_by_prefix_paginated_new(b"a".to_vec(), continuation, 2)?;
+            let (keys, continuation) = runtime.find_keys_by_prefix_paginated_wait(&promise)?;
+            assert_eq!(keys, vec![b"3".to_vec()]);
+            assert_eq!(continuation, None);
+
+            let promise = runtime.find_key_values_by_prefix_paginated_new(vec![], None, 4)?;
+            let (key_values, continuation) =
+                runtime.find_key_values_by_prefix_paginated_wait(&promise)?;
+            assert_eq!(key_values.len(), 4);
+            assert_eq!(key_values[3], (b"b1".to_vec(), b"b1".to_vec()));
+            assert_eq!(continuation, None);
+
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new_replaying_blobs(blobs);
+    view.execute_operation(
+        context,
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    // All the keys with the scanned prefix are charged for, not only the returned ones.
+    assert!(controller.tracker.bytes_read >= 2 * 3 + 4 * 2 + 4 * 2);
+    Ok(())
+}
+
 /// Tests if execution fails if a simulated session isn't properly closed.
 #[tokio::test]
 async fn test_simulated_session_leak() -> anyhow::Result<()> {
@@ -366,6 +426,209 @@ async fn test_simulated_session_leak() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests if the changes made by a called application that fails are rolled back.
+#[tokio::test]
+async fn test_call_application_with_rollback_reverts_failed_call() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application, caller_blobs) = view.register_mock_application(0).await?;
+    let (target_id, target_application, target_blobs) = view.register_mock_application(1).await?;
+
+    let state_key = vec![];
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let result = runtime.try_call_application_with_rollback(false, target_id, vec![])?;
+            assert_eq!(result, Err(CalleeError::Trapped));
+            let response = runtime.try_call_application_with_rollback(false, target_id, vec![1])?;
+            assert_eq!(response, Ok(vec![]));
+            Ok(vec![])
+        },
+    ));
+
+    target_application.expect_call(ExpectedCall::execute_operation({
+        let state_key = state_key.clone();
+        move |runtime, _argument| {
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(state_key, vec![1]);
+            runtime.write_batch(batch)?;
+            runtime.send_message(SendMessageRequest {
+                destination: chain_id,
+                authenticated: false,
+                is_tracked: false,
+                grant: Resources::default(),
+                message: b"lost".to_vec(),
+            })?;
+            Err(ExecutionError::UserError("Callee failed".to_owned()))
+        }
+    }));
+
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _argument| Ok(runtime.read_value_bytes(state_key)?.unwrap_or_default()),
+    ));
+
+    target_application.expect_call(ExpectedCall::default_finalize());
+    caller_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let mut txn_tracker =
+        TransactionTracker::new_replaying_blobs(caller_blobs.iter().chain(&target_blobs));
+    view.execute_operation(
+        context,
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let txn_outcome = txn_tracker.into_outcome().unwrap();
+    assert!(txn_outcome.outgoing_messages.is_empty());
+    Ok(())
+}
+
+/// Tests if an error reported by the host while a called application runs aborts the
+/// transaction instead of being rolled back.
+#[tokio::test]
+async fn test_call_application_with_rollback_does_not_catch_host_errors() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application, caller_blobs) = view.register_mock_application(0).await?;
+    let (target_id, target_application, target_blobs) = view.register_mock_application(1).await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            runtime.try_call_application_with_rollback(false, target_id, vec![])?;
+            panic!("The host error should not be returned to the caller");
+        },
+    ));
+    target_application.expect_call(ExpectedCall::execute_operation(|_runtime, _argument| {
+        Err(ExecutionError::MaximumFuelExceeded(VmRuntime::Wasm))
+    }));
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let mut txn_tracker =
+        TransactionTracker::new_replaying_blobs(caller_blobs.iter().chain(&target_blobs));
+    let result = view
+        .execute_operation(
+            context,
+            Operation::User {
+                application_id: caller_id,
+                bytes: vec![],
+            },
+            &mut txn_tracker,
+            &mut controller,
+        )
+        .await;
+
+    assert_matches!(
+        result,
+        Err(ExecutionError::MaximumFuelExceeded(VmRuntime::Wasm))
+    );
+    Ok(())
+}
+
+/// Tests if the changes made by a called application that succeeds are kept.
+#[tokio::test]
+async fn test_call_application_with_rollback_keeps_successful_call() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application, caller_blobs) = view.register_mock_application(0).await?;
+    let (target_id, target_application, target_blobs) = view.register_mock_application(1).await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let response = runtime.try_call_application_with_rollback(false, target_id, vec![])?;
+            assert_eq!(response, Ok(b"done".to_vec()));
+            Ok(vec![])
+        },
+    ));
+
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _argument| {
+            runtime.send_message(SendMessageRequest {
+                destination: chain_id,
+                authenticated: false,
+                is_tracked: false,
+                grant: Resources::default(),
+                message: b"kept".to_vec(),
+            })?;
+            Ok(b"done".to_vec())
+        },
+    ));
+
+    target_application.expect_call(ExpectedCall::default_finalize());
+    caller_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let mut txn_tracker =
+        TransactionTracker::new_replaying_blobs(caller_blobs.iter().chain(&target_blobs));
+    view.execute_operation(
+        context,
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let txn_outcome = txn_tracker.into_outcome().unwrap();
+    assert_eq!(txn_outcome.outgoing_messages.len(), 1);
+    Ok(())
+}
+
+/// Tests if a failed call can't be rolled back if the called application was already loaded.
+#[tokio::test]
+async fn test_call_application_with_rollback_of_loaded_application() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application, caller_blobs) = view.register_mock_application(0).await?;
+    let (target_id, target_application, target_blobs) = view.register_mock_application(1).await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            runtime.try_call_application(false, target_id, vec![])?;
+            runtime.try_call_application_with_rollback(false, target_id, vec![])?;
+            Ok(vec![])
+        },
+    ));
+
+    let error_message = "Callee failed";
+
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |_runtime, _argument| Ok(vec![]),
+    ));
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |_runtime, _argument| Err(ExecutionError::UserError(error_message.to_owned())),
+    ));
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let result = view
+        .execute_operation(
+            context,
+            Operation::User {
+                application_id: caller_id,
+                bytes: vec![],
+            },
+            &mut TransactionTracker::new_replaying_blobs(caller_blobs.iter().chain(&target_blobs)),
+            &mut controller,
+        )
+        .await;
+
+    assert_matches!(result, Err(ExecutionError::UserError(message)) if message == error_message);
+    Ok(())
+}
+
 /// Tests if `finalize` can cause execution to fail.
 #[tokio::test]
 async fn test_rejecting_block_from_finalize() -> anyhow::Result<()> {
@@ -1273,6 +1536,186 @@ async fn test_open_chain() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that `open_chain` keeps a weighted multi-owner configuration with custom timeouts and
+/// restricted application permissions, debits the balance from the parent chain, and that the
+/// new chain can be sent a message right away.
+#[tokio::test]
+async fn test_open_chain_with_multi_owner_configuration() -> anyhow::Result<()> {
+    let chain_key = AccountPublicKey::test_key(1);
+    let ownership = ChainOwnership::single(chain_key.into());
+    let root_description =
+        dummy_chain_description_with_ownership_and_balance(0, ownership, Amount::from_tokens(5));
+    let mut view = SystemExecutionState::new(root_description.clone())
+        .into_view()
+        .await;
+    view.context()
+        .extra()
+        .add_blobs([Blob::new_chain_description(&root_description)])
+        .await?;
+    let (application_id, application, blobs) = view.register_mock_application(0).await?;
+
+    let timeout_config = TimeoutConfig {
+        fast_round_duration: Some(TimeDelta::from_secs(1)),
+        base_timeout: TimeDelta::from_secs(20),
+        timeout_increment: TimeDelta::from_secs(2),
+        fallback_duration: TimeDelta::from_secs(3600),
+    };
+    let child_ownership = ChainOwnership::multiple(
+        [
+            (AccountPublicKey::test_key(2).into(), 100),
+            (AccountPublicKey::test_key(3).into(), 50),
+        ],
+        5,
+        timeout_config,
+    );
+    let child_application_permissions = ApplicationPermissions::new_single(application_id);
+
+    application.expect_call(ExpectedCall::execute_operation({
+        let child_ownership = child_ownership.clone();
+        let application_permissions = child_application_permissions.clone();
+        move |runtime, _operation| {
+            let chain_id = runtime.open_chain(
+                child_ownership,
+                application_permissions,
+                Amount::from_tokens(2),
+            )?;
+            runtime.send_message(SendMessageRequest {
+                destination: chain_id,
+                authenticated: false,
+                is_tracked: false,
+                grant: Resources::default(),
+                message: b"welcome".to_vec(),
+            })?;
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = OperationContext {
+        height: BlockHeight(1),
+        authenticated_signer: Some(chain_key.into()),
+        ..create_dummy_operation_context(root_description.id())
+    };
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut txn_tracker = TransactionTracker::new(
+        Timestamp::from(0),
+        1,
+        0,
+        0,
+        0,
+        Some(blob_oracle_responses(blobs.iter())),
+    );
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    assert_eq!(*view.system.balance.get(), Amount::from_tokens(3));
+    let txn_outcome = txn_tracker.into_outcome()?;
+    let [new_blob] = &txn_outcome.blobs[..] else {
+        panic!(
+            "expected exactly one created blob, got {:?}",
+            txn_outcome.blobs
+        );
+    };
+    assert_eq!(new_blob.id().blob_type, BlobType::ChainDescription);
+    let created_description: ChainDescription = bcs::from_bytes(&new_blob.clone().into_bytes())?;
+    let child_id = created_description.id();
+    assert_eq!(created_description.config().balance, Amount::from_tokens(2));
+    assert_eq!(created_description.config().ownership, child_ownership);
+    assert_eq!(
+        created_description.config().application_permissions,
+        child_application_permissions
+    );
+
+    let [outgoing_message] = &txn_outcome.outgoing_messages[..] else {
+        panic!(
+            "expected exactly one outgoing message, got {:?}",
+            txn_outcome.outgoing_messages
+        );
+    };
+    assert_eq!(outgoing_message.destination, child_id);
+    assert_eq!(
+        outgoing_message.message,
+        Message::User {
+            application_id,
+            bytes: b"welcome".to_vec(),
+        }
+    );
+
+    Ok(())
+}
+
+/// Tests the system API call `create_application`, used by an application to create another one
+/// from an already published module.
+#[tokio::test]
+async fn test_create_application_from_contract() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+    let (factory_id, factory, _) = view.register_mock_application(0).await?;
+
+    let context = OperationContext {
+        height: BlockHeight(3),
+        ..create_dummy_operation_context(chain_id)
+    };
+
+    // The child's ID only depends on the creating context, so the mock code can be registered
+    // for it in advance.
+    let (template, contract_blob, service_blob) = create_dummy_user_application_description(1);
+    let module_id = template.module_id;
+    let child_description = ApplicationDescription {
+        module_id,
+        creator_chain_id: chain_id,
+        block_height: context.height,
+        application_index: 0,
+        parameters: b"parameters".to_vec(),
+        required_application_ids: vec![factory_id],
+    };
+    let (expected_child_id, child) = view
+        .register_mock_application_with(child_description.clone(), contract_blob, service_blob)
+        .await?;
+
+    factory.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let child_id = runtime.create_application(
+                module_id,
+                b"parameters".to_vec(),
+                b"argument".to_vec(),
+                vec![factory_id],
+            )?;
+            assert_eq!(child_id, expected_child_id);
+            Ok(vec![])
+        },
+    ));
+    factory.expect_call(ExpectedCall::default_finalize());
+    child.expect_call(ExpectedCall::instantiate(move |runtime, argument| {
+        assert_eq!(argument, b"argument");
+        assert_eq!(runtime.application_id()?, expected_child_id);
+        assert_eq!(runtime.application_creator_chain_id()?, chain_id);
+        Ok(())
+    }));
+    child.expect_call(ExpectedCall::default_finalize());
+
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id: factory_id,
+        bytes: vec![],
+    };
+    let mut txn_tracker = TransactionTracker::new(Timestamp::from(0), 0, 0, 0, 0, None);
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    let txn_outcome = txn_tracker.into_outcome()?;
+    assert_eq!(txn_outcome.next_application_index, 1);
+    assert!(txn_outcome
+        .blobs
+        .contains(&Blob::new_application_description(&child_description)));
+
+    Ok(())
+}
+
 /// Tests the system API call `close_chain`.
 #[tokio::test]
 async fn test_close_chain() -> anyhow::Result<()> {
@@ -1328,6 +1771,10 @@ async fn test_close_chain() -> anyhow::Result<()> {
     application.expect_call(ExpectedCall::execute_operation(
         move |runtime, _operation| {
             runtime.close_chain()?;
+            assert_matches!(
+                runtime.close_chain(),
+                Err(ExecutionError::ChainAlreadyClosed)
+            );
             Ok(vec![])
         },
     ));
@@ -1349,6 +1796,99 @@ async fn test_close_chain() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests the system API call `change_application_permissions`.
+#[tokio::test]
+async fn test_change_application_permissions() -> anyhow::Result<()> {
+    let ownership = ChainOwnership::single(AccountPublicKey::test_key(1).into());
+    let description = dummy_chain_description_with_ownership_and_balance(
+        0,
+        ownership.clone(),
+        Amount::from_tokens(5),
+    );
+    let chain_id = description.id();
+    let state = SystemExecutionState::new(description);
+    let mut view = state.into_view().await;
+    let (application_id, application, blobs) = view.register_mock_application(0).await?;
+
+    // Restricting operations to the application doesn't allow it to change the permissions.
+    let restricted = ApplicationPermissions {
+        execute_operations: Some(vec![application_id]),
+        ..ApplicationPermissions::default()
+    };
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = SystemOperation::ChangeApplicationPermissions(restricted.clone());
+    view.execute_operation(
+        context,
+        operation.into(),
+        &mut TransactionTracker::new_replaying(Vec::new()),
+        &mut controller,
+    )
+    .await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_matches!(
+                runtime.change_application_permissions(ApplicationPermissions::default()),
+                Err(ExecutionError::UnauthorizedApplication(id)) if id == application_id
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    view.execute_operation(
+        context,
+        operation,
+        &mut TransactionTracker::new_replaying_blobs(blobs),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(*view.system.application_permissions.get(), restricted);
+
+    // Once it is listed in `change_application_permissions`, the application can hand the
+    // chain over to the default permissions.
+    let permissions = ApplicationPermissions::new_single(application_id);
+    let operation = SystemOperation::ChangeApplicationPermissions(permissions);
+    view.execute_operation(
+        context,
+        operation.into(),
+        &mut TransactionTracker::new_replaying(Vec::new()),
+        &mut controller,
+    )
+    .await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            runtime.change_application_permissions(ApplicationPermissions::default())?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    view.execute_operation(
+        context,
+        operation,
+        &mut TransactionTracker::new_replaying(Vec::new()),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(
+        *view.system.application_permissions.get(),
+        ApplicationPermissions::default()
+    );
+
+    Ok(())
+}
+
 /// Tests an application attempting to transfer the tokens in the chain's balance while executing
 /// messages.
 #[test_case(
@@ -3,9 +3,10 @@
 
 #![allow(clippy::field_reassign_with_default)]
 
+use assert_matches::assert_matches;
 use linera_base::{
     crypto::AccountSecretKey,
-    data_types::{Amount, BlockHeight, Timestamp},
+    data_types::{Amount, BlockHeight, Round, Timestamp},
     identifiers::{AccountOwner, MessageId},
     ownership::ChainOwnership,
 };
@@ -15,9 +16,9 @@ use linera_execution::{
         dummy_chain_description, dummy_chain_description_with_ownership_and_balance,
         SystemExecutionState,
     },
-    Message, MessageContext, Operation, OperationContext, Query, QueryContext, QueryOutcome,
-    QueryResponse, ResourceController, SystemMessage, SystemOperation, SystemQuery, SystemResponse,
-    TransactionTracker,
+    ExecutionError, Message, MessageContext, Operation, OperationContext, Query, QueryContext,
+    QueryOutcome, QueryResponse, ResourceController, SystemMessage, SystemOperation, SystemQuery,
+    SystemResponse, TransactionTracker, MAX_TRANSFER_MEMO_LEN,
 };
 
 #[tokio::test]
@@ -47,7 +48,8 @@ async fn test_simple_system_operation() -> anyhow::Result<()> {
     let context = OperationContext {
         chain_id,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         authenticated_signer: Some(owner),
         authenticated_caller_id: None,
         timestamp: Default::default(),
@@ -68,6 +70,65 @@ async fn test_simple_system_operation() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_transfer_memo_length() -> anyhow::Result<()> {
+    let owner_key_pair = AccountSecretKey::generate();
+    let owner = AccountOwner::from(owner_key_pair.public());
+    let ownership = ChainOwnership {
+        super_owners: [owner].into_iter().collect(),
+        ..ChainOwnership::default()
+    };
+    let balance = Amount::from_tokens(4);
+    let description =
+        dummy_chain_description_with_ownership_and_balance(0, ownership.clone(), balance);
+    let chain_id = description.id();
+    let state = SystemExecutionState {
+        description: Some(description),
+        balance,
+        ownership,
+        ..SystemExecutionState::default()
+    };
+    let mut view = state.into_view().await;
+    let context = OperationContext {
+        chain_id,
+        height: BlockHeight(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
+        authenticated_signer: Some(owner),
+        authenticated_caller_id: None,
+        timestamp: Default::default(),
+    };
+    let transfer = |memo: String| SystemOperation::TransferWithMemo {
+        owner: AccountOwner::CHAIN,
+        amount: Amount::ONE,
+        recipient: Recipient::Burn,
+        memo,
+    };
+
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new_replaying(Vec::new());
+    view.execute_operation(
+        context,
+        Operation::system(transfer("a".repeat(MAX_TRANSFER_MEMO_LEN))),
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(view.system.balance.get(), &Amount::from_tokens(3));
+
+    let mut txn_tracker = TransactionTracker::new_replaying(Vec::new());
+    let result = view
+        .execute_operation(
+            context,
+            Operation::system(transfer("a".repeat(MAX_TRANSFER_MEMO_LEN + 1))),
+            &mut txn_tracker,
+            &mut controller,
+        )
+        .await;
+    assert_matches!(result, Err(ExecutionError::TransferMemoTooLong));
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_simple_system_message() -> anyhow::Result<()> {
     let mut state = SystemExecutionState::default();
@@ -84,7 +145,8 @@ async fn test_simple_system_message() -> anyhow::Result<()> {
         chain_id,
         is_bouncing: false,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         message_id: MessageId {
             chain_id: dummy_chain_description(1).id(),
             height: BlockHeight(0),
@@ -5,14 +5,16 @@
 
 use std::sync::Arc;
 
-use linera_base::data_types::{Amount, Blob, BlockHeight, Timestamp};
+use linera_base::data_types::{Amount, Blob, BlockHeight, CalleeError, Round, Timestamp};
 use linera_execution::{
     test_utils::{
-        create_dummy_user_application_description, dummy_chain_description, SystemExecutionState,
+        create_dummy_operation_context, create_dummy_user_application_description,
+        dummy_chain_description, ExpectedCall, RegisterMockApplication, SystemExecutionState,
     },
-    ExecutionRuntimeConfig, ExecutionRuntimeContext, Operation, OperationContext, Query,
-    QueryContext, QueryOutcome, QueryResponse, ResourceControlPolicy, ResourceController,
-    ResourceTracker, TransactionTracker, WasmContractModule, WasmRuntime, WasmServiceModule,
+    ContractRuntime as _, ExecutionRuntimeConfig, ExecutionRuntimeContext, Operation,
+    OperationContext, Query, QueryContext, QueryOutcome, QueryResponse, ResourceControlPolicy,
+    ResourceController, ResourceTracker, TransactionTracker, WasmContractModule, WasmRuntime,
+    WasmServiceModule,
 };
 use linera_views::{context::Context as _, views::View};
 use serde_json::json;
@@ -70,7 +72,8 @@ async fn test_fuel_for_counter_wasm_application(
     let context = OperationContext {
         chain_id,
         height: BlockHeight(0),
-        round: Some(0),
+        round: Some(Round::MultiLeader(0)),
+        block_proposer: None,
         authenticated_signer: None,
         authenticated_caller_id: None,
         timestamp: Default::default(),
@@ -148,3 +151,61 @@ async fn test_fuel_for_counter_wasm_application(
     assert!(operations.is_empty());
     Ok(())
 }
+
+/// Tests that a trap in a called Wasm application is reported to the caller with its message.
+#[cfg_attr(with_wasmer, test_case(WasmRuntime::Wasmer; "wasmer"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::Wasmtime; "wasmtime"))]
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn test_trap_in_called_wasm_application(wasm_runtime: WasmRuntime) -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+    let (caller_id, caller_application, caller_blobs) = view.register_mock_application(0).await?;
+
+    let (app_desc, contract_blob, service_blob) = create_dummy_user_application_description(1);
+    let counter_id = From::from(&app_desc);
+    let counter_blobs = [
+        Blob::new_application_description(&app_desc).id(),
+        contract_blob.id(),
+        service_blob.id(),
+    ];
+    let contract =
+        WasmContractModule::from_file("tests/fixtures/counter_contract.wasm", wasm_runtime).await?;
+    view.context()
+        .extra()
+        .user_contracts()
+        .insert(counter_id, contract.into());
+    view.context()
+        .extra()
+        .add_blobs([
+            contract_blob,
+            service_blob,
+            Blob::new_application_description(&app_desc),
+        ])
+        .await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            // An empty argument is not a valid counter operation, so the counter panics.
+            let result = runtime.try_call_application_with_rollback(false, counter_id, vec![])?;
+            assert_eq!(result, Err(CalleeError::Trapped));
+            Ok(vec![])
+        },
+    ));
+    caller_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let mut txn_tracker =
+        TransactionTracker::new_replaying_blobs(caller_blobs.iter().chain(&counter_blobs));
+    view.execute_operation(
+        context,
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    Ok(())
+}
@@ -7,7 +7,7 @@
 
 use linera_base::crypto::ValidatorPublicKey;
 use linera_client::config::GenesisConfig;
-use linera_faucet::ClaimOutcome;
+use linera_faucet::{ClaimOutcome, ProofOfWork};
 use linera_version::VersionInfo;
 use thiserror_context::Context;
 
@@ -43,6 +43,16 @@ impl Faucet {
         &self,
         query: &str,
     ) -> Result<Response, Error> {
+        self.try_query(query)
+            .await?
+            .map_err(|errors| ErrorInner::GraphQl(errors).into())
+    }
+
+    /// Executes a query, returning the GraphQL errors separately from the other errors.
+    async fn try_query<Response: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+    ) -> Result<Result<Response, Vec<serde_json::Value>>, Error> {
         #[derive(serde::Deserialize)]
         struct GraphQlResponse<T> {
             data: Option<T>,
@@ -69,11 +79,11 @@ impl Faucet {
             .await?;
 
         if let Some(errors) = response.errors {
-            Err(ErrorInner::GraphQl(errors).into())
+            Ok(Err(errors))
         } else {
-            Ok(response
+            Ok(Ok(response
                 .data
-                .expect("no errors present but no data returned"))
+                .expect("no errors present but no data returned")))
         }
     }
 
@@ -99,21 +109,36 @@ impl Faucet {
         Ok(self.query::<Response>("query { version }").await?.version)
     }
 
+    /// Claims a new chain for the given owner.
+    ///
+    /// If the faucet requires a proof of work, it is computed and the claim is sent again.
     pub async fn claim(
         &self,
         owner: &linera_base::identifiers::AccountOwner,
     ) -> Result<ClaimOutcome, Error> {
-        let query = format!(
-            "mutation {{ claim(owner: \"{owner}\") {{ \
-                chainId certificateHash \
-            }} }}"
-        );
-
         #[derive(serde::Deserialize)]
         struct Response {
             claim: ClaimOutcome,
         }
 
+        let query = format!(
+            "mutation {{ claim(owner: \"{owner}\") {{ \
+                chainId certificateHash \
+            }} }}"
+        );
+        let errors = match self.try_query::<Response>(&query).await? {
+            Ok(response) => return Ok(response.claim),
+            Err(errors) => errors,
+        };
+        let Some(difficulty) = proof_of_work_difficulty(&errors) else {
+            return Err(ErrorInner::GraphQl(errors).into());
+        };
+        let nonce = ProofOfWork::solve(*owner, difficulty).nonce;
+        let query = format!(
+            "mutation {{ claim(owner: \"{owner}\", nonce: {nonce}) {{ \
+                chainId certificateHash \
+            }} }}"
+        );
         Ok(self.query::<Response>(&query).await?.claim)
     }
 
@@ -140,3 +165,15 @@ impl Faucet {
             .collect())
     }
 }
+
+/// Returns the required difficulty if the errors say that a proof of work is missing.
+fn proof_of_work_difficulty(errors: &[serde_json::Value]) -> Option<u32> {
+    errors.iter().find_map(|error| {
+        let extensions = error.get("extensions")?;
+        if extensions.get("code")?.as_str()? != "PROOF_OF_WORK_REQUIRED" {
+            return None;
+        }
+        let difficulty = u32::try_from(extensions.get("difficulty")?.as_u64()?).ok()?;
+        (difficulty <= ProofOfWork::MAX_DIFFICULTY).then_some(difficulty)
+    })
+}
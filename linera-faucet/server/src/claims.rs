@@ -0,0 +1,172 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The history of the claims sent to the faucet, so that clients can poll their status.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use async_graphql::{Enum, Error, SimpleObject};
+use linera_base::{
+    crypto::CryptoHash,
+    identifiers::{AccountOwner, ChainId},
+};
+use rand::{rngs::OsRng, Rng as _};
+
+use crate::ClaimOutcome;
+
+/// The number of claims whose status is retained.
+const HISTORY_SIZE: usize = 10_000;
+
+/// The maximum number of claims that can be pending at the same time.
+pub const MAX_PENDING_CLAIMS: usize = 1_000;
+
+/// Whether a claim was processed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+pub enum ClaimState {
+    /// The claim is waiting for the earlier claims to be processed.
+    Pending,
+    /// The new chain was created.
+    Completed,
+    /// The claim failed or was rejected.
+    Rejected,
+}
+
+/// The status of a claim.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct ClaimStatus {
+    /// The ID that was returned when the claim was submitted.
+    pub request_id: String,
+    /// The owner of the new chain.
+    pub owner: AccountOwner,
+    /// Whether the claim was processed.
+    pub state: ClaimState,
+    /// The number of earlier claims that are still pending, if this one is pending.
+    pub queue_position: Option<u64>,
+    /// The ID of the new chain, if the claim is completed.
+    pub chain_id: Option<ChainId>,
+    /// The hash of the certificate that created the new chain, if the claim is completed.
+    pub certificate_hash: Option<CryptoHash>,
+    /// Why the claim failed, if it was rejected.
+    pub reason: Option<String>,
+}
+
+/// The most recent claims.
+#[derive(Default)]
+pub struct ClaimHistory(Mutex<HistoryState>);
+
+#[derive(Default)]
+struct HistoryState {
+    /// The claims by their sequence number, i.e. in the order they were submitted.
+    claims: BTreeMap<u64, ClaimStatus>,
+    /// The sequence numbers of the claims by request ID.
+    sequence_numbers: HashMap<String, u64>,
+    next_sequence_number: u64,
+    /// The number of pending claims.
+    pending: usize,
+}
+
+impl ClaimHistory {
+    /// Records a new pending claim, and returns its request ID, or `None` if there are already
+    /// too many pending claims.
+    ///
+    /// The request IDs are random, so that they can't be guessed to query other users' claims.
+    pub fn insert(&self, owner: AccountOwner) -> Option<String> {
+        let mut state = self.0.lock().unwrap();
+        if state.pending >= MAX_PENDING_CLAIMS {
+            return None;
+        }
+        let request_id = format!("{:032x}", OsRng.gen::<u128>());
+        let sequence_number = state.next_sequence_number;
+        state.next_sequence_number += 1;
+        state.pending += 1;
+        state
+            .sequence_numbers
+            .insert(request_id.clone(), sequence_number);
+        state.claims.insert(
+            sequence_number,
+            ClaimStatus {
+                request_id: request_id.clone(),
+                owner,
+                state: ClaimState::Pending,
+                queue_position: None,
+                chain_id: None,
+                certificate_hash: None,
+                reason: None,
+            },
+        );
+        if state.claims.len() > HISTORY_SIZE {
+            if let Some((_, oldest)) = state.claims.pop_first() {
+                state.sequence_numbers.remove(&oldest.request_id);
+                if oldest.state == ClaimState::Pending {
+                    state.pending -= 1;
+                }
+            }
+        }
+        Some(request_id)
+    }
+
+    /// Records the result of a claim.
+    pub fn finish(&self, request_id: &str, result: &Result<ClaimOutcome, Error>) {
+        let mut state = self.0.lock().unwrap();
+        let Some(&sequence_number) = state.sequence_numbers.get(request_id) else {
+            return;
+        };
+        let Some(status) = state.claims.get_mut(&sequence_number) else {
+            return;
+        };
+        if status.state != ClaimState::Pending {
+            return;
+        }
+        match result {
+            Ok(outcome) => {
+                status.state = ClaimState::Completed;
+                status.chain_id = Some(outcome.chain_id);
+                status.certificate_hash = Some(outcome.certificate_hash);
+            }
+            Err(error) => {
+                status.state = ClaimState::Rejected;
+                status.reason = Some(error.message.clone());
+            }
+        }
+        state.pending -= 1;
+    }
+
+    /// Returns the status of the claim with the given request ID, if it is retained.
+    pub fn status(&self, request_id: &str) -> Option<ClaimStatus> {
+        let state = self.0.lock().unwrap();
+        let sequence_number = *state.sequence_numbers.get(request_id)?;
+        let status = state.claims.get(&sequence_number)?;
+        Some(state.with_queue_position(sequence_number, status))
+    }
+
+    /// Returns the status of the retained claims of the given owner, oldest first.
+    pub fn claims_of(&self, owner: AccountOwner) -> Vec<ClaimStatus> {
+        let state = self.0.lock().unwrap();
+        state
+            .claims
+            .iter()
+            .filter(|(_, status)| status.owner == owner)
+            .map(|(sequence_number, status)| state.with_queue_position(*sequence_number, status))
+            .collect()
+    }
+}
+
+impl HistoryState {
+    /// Returns the status of the claim with the given sequence number, with its queue position
+    /// if it is pending.
+    fn with_queue_position(&self, sequence_number: u64, status: &ClaimStatus) -> ClaimStatus {
+        let mut status = status.clone();
+        if status.state == ClaimState::Pending {
+            let earlier_pending = self
+                .claims
+                .range(..sequence_number)
+                .filter(|(_, earlier)| earlier.state == ClaimState::Pending)
+                .count();
+            status.queue_position = Some(earlier_pending as u64);
+        }
+        status
+    }
+}
@@ -3,15 +3,26 @@
 
 //! The server component of the Linera faucet.
 
-use std::{future::IntoFuture, net::SocketAddr, num::NonZeroU16, sync::Arc};
+mod claims;
+mod rate_limit;
 
-use async_graphql::{EmptySubscription, Error, Schema, SimpleObject};
+use std::{
+    future::IntoFuture,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZeroU16,
+    sync::Arc,
+};
+
+use async_graphql::{EmptySubscription, Error, ErrorExtensions as _, Schema, SimpleObject};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{Extension, Router};
+use async_trait::async_trait;
+use axum::{extract::ConnectInfo, http::HeaderMap, Extension, Router};
 use futures::{lock::Mutex, FutureExt as _};
+#[cfg(with_metrics)]
+use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
-    data_types::{Amount, ApplicationPermissions, Timestamp},
+    data_types::{Amount, ApplicationPermissions, TimeDelta, Timestamp},
     identifiers::{AccountOwner, ChainId},
     ownership::ChainOwnership,
 };
@@ -20,11 +31,60 @@ use linera_client::{
     config::GenesisConfig,
 };
 use linera_core::data_types::ClientOutcome;
+use linera_faucet::ProofOfWork;
 use linera_storage::{Clock as _, Storage};
+use linera_views::views::View as _;
 use serde::Deserialize;
 use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
-use tracing::info;
+use tracing::{info, warn};
+
+use self::{
+    claims::ClaimHistory,
+    rate_limit::{FaucetStateView, RateLimiter, Reservation},
+};
+pub use self::{
+    claims::{ClaimState, ClaimStatus},
+    rate_limit::RateLimitConfig,
+};
+
+/// The name of the faucet's state in the storage.
+const STATE_NAME: &str = "faucet";
+
+#[cfg(with_metrics)]
+mod metrics {
+    use std::sync::LazyLock;
+
+    use linera_base::prometheus_util::{
+        exponential_bucket_latencies, register_histogram_vec, register_int_counter_vec,
+    };
+    use prometheus::{HistogramVec, IntCounterVec};
+
+    pub static CLAIMS_SERVED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "faucet_claims_served",
+            "Number of claims that created a new chain",
+            &[],
+        )
+    });
+
+    pub static CLAIMS_REJECTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "faucet_claims_rejected",
+            "Number of claims that were rejected or failed",
+            &[],
+        )
+    });
+
+    pub static CLAIM_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec(
+            "faucet_claim_latency",
+            "Time to process a claim, including the wait for the earlier ones",
+            &[],
+            exponential_bucket_latencies(60_000.0),
+        )
+    });
+}
 
 /// Returns an HTML response constructing the GraphiQL web page for the given URI.
 pub(crate) async fn graphiql(uri: axum::http::Uri) -> impl axum::response::IntoResponse {
@@ -44,6 +104,8 @@ pub struct QueryRoot<C> {
     context: Arc<Mutex<C>>,
     genesis_config: Arc<GenesisConfig>,
     chain_id: ChainId,
+    amount: Amount,
+    claim_history: Arc<ClaimHistory>,
 }
 
 /// The root GraphQL mutation type.
@@ -54,10 +116,56 @@ pub struct MutationRoot<C> {
     end_timestamp: Timestamp,
     start_timestamp: Timestamp,
     start_balance: Amount,
+    rate_limits: RateLimitConfig,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    proof_of_work_difficulty: Option<u32>,
+    claim_verifier: Option<Arc<dyn ClaimVerifier>>,
+    claim_history: Arc<ClaimHistory>,
+    /// Held while a claim is executed, so that the claims are executed one at a time.
+    claim_lock: Arc<Mutex<()>>,
+}
+
+impl<C> Clone for MutationRoot<C> {
+    fn clone(&self) -> Self {
+        Self {
+            chain_id: self.chain_id,
+            context: Arc::clone(&self.context),
+            amount: self.amount,
+            end_timestamp: self.end_timestamp,
+            start_timestamp: self.start_timestamp,
+            start_balance: self.start_balance,
+            rate_limits: self.rate_limits,
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            proof_of_work_difficulty: self.proof_of_work_difficulty,
+            claim_verifier: self.claim_verifier.clone(),
+            claim_history: Arc::clone(&self.claim_history),
+            claim_lock: Arc::clone(&self.claim_lock),
+        }
+    }
+}
+
+/// A request for a new chain.
+#[derive(Clone, Debug)]
+pub struct ClaimRequest {
+    /// The owner of the new chain.
+    pub owner: AccountOwner,
+    /// The IP address the request was sent from, if known.
+    pub ip: Option<IpAddr>,
+    /// The nonce of the proof of work, if any.
+    pub nonce: Option<u64>,
+    /// The verification token, e.g. a captcha response, if any.
+    pub verification: Option<String>,
+}
+
+/// Decides whether a claim is legitimate, e.g. by checking a captcha response or an allowlist.
+#[async_trait]
+pub trait ClaimVerifier: Send + Sync {
+    /// Returns an error with the reason if the claim must be rejected.
+    async fn verify(&self, request: &ClaimRequest) -> Result<(), String>;
 }
 
 /// The result of a successful `claim` mutation.
-#[derive(SimpleObject)]
+#[derive(Clone, Debug, SimpleObject)]
 pub struct ClaimOutcome {
     /// The ID of the new chain.
     pub chain_id: ChainId,
@@ -104,6 +212,42 @@ where
             })
             .collect())
     }
+
+    /// Returns the balance of the faucet chain.
+    async fn balance(&self) -> Result<Amount, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(self.chain_id)
+            .await?;
+        Ok(client.local_balance().await?)
+    }
+
+    /// Returns the number of tokens given with each new chain.
+    async fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// Returns an estimate of the number of claims the faucet can still serve with its
+    /// current balance.
+    async fn remaining_claims(&self) -> Result<u64, Error> {
+        let balance = self.balance().await?;
+        let remaining = u128::from(balance)
+            .checked_div(u128::from(self.amount))
+            .unwrap_or(u128::MAX);
+        Ok(u64::try_from(remaining).unwrap_or(u64::MAX))
+    }
+
+    /// Returns the status of the claim with the given request ID, unless it is too old.
+    async fn claim_status(&self, request_id: String) -> Option<ClaimStatus> {
+        self.claim_history.status(&request_id)
+    }
+
+    /// Returns the status of the recent claims for the given owner, oldest first.
+    async fn claim_history(&self, owner: AccountOwner) -> Vec<ClaimStatus> {
+        self.claim_history.claims_of(owner)
+    }
 }
 
 #[async_graphql::Object(cache_control(no_cache))]
@@ -112,8 +256,47 @@ where
     C: ClientContext,
 {
     /// Creates a new chain with the given authentication key, and transfers tokens to it.
-    async fn claim(&self, owner: AccountOwner) -> Result<ClaimOutcome, Error> {
-        self.do_claim(owner).await
+    ///
+    /// If the faucet requires a proof of work, `nonce` must make it valid for the owner. The
+    /// `verification` token is checked by the faucet's claim verifier, if it has one.
+    async fn claim(
+        &self,
+        context: &async_graphql::Context<'_>,
+        owner: AccountOwner,
+        nonce: Option<u64>,
+        verification: Option<String>,
+    ) -> Result<ClaimOutcome, Error> {
+        let request = ClaimRequest {
+            owner,
+            ip: context.data_opt::<IpAddr>().copied(),
+            nonce,
+            verification,
+        };
+        self.do_claim(&request).await
+    }
+
+    /// Submits a claim like `claim`, but returns its request ID without waiting for it to be
+    /// processed. Its status can then be queried with `claimStatus`.
+    ///
+    /// The claim is checked against the requirements and the rate limits before it is queued.
+    async fn submit_claim(
+        &self,
+        context: &async_graphql::Context<'_>,
+        owner: AccountOwner,
+        nonce: Option<u64>,
+        verification: Option<String>,
+    ) -> Result<String, Error> {
+        let request = ClaimRequest {
+            owner,
+            ip: context.data_opt::<IpAddr>().copied(),
+            nonce,
+            verification,
+        };
+        let (request_id, reservation) = self.queue_claim(&request).await?;
+        let root = self.clone();
+        let id = request_id.clone();
+        tokio::spawn(async move { root.process_claim(&id, &request, reservation).await });
+        Ok(request_id)
     }
 }
 
@@ -121,35 +304,110 @@ impl<C> MutationRoot<C>
 where
     C: ClientContext,
 {
-    async fn do_claim(&self, owner: AccountOwner) -> Result<ClaimOutcome, Error> {
+    async fn do_claim(&self, request: &ClaimRequest) -> Result<ClaimOutcome, Error> {
+        let (request_id, reservation) = self.queue_claim(request).await?;
+        self.process_claim(&request_id, request, reservation).await
+    }
+
+    /// Checks a claim and reserves its share of the rate limits, then records it as pending.
+    ///
+    /// Returns its request ID and reservation.
+    async fn queue_claim(&self, request: &ClaimRequest) -> Result<(String, Reservation), Error> {
+        self.check_claim(request).await?;
+        let now = self.context.lock().await.storage().clock().current_time();
+        // Claims from unknown IP addresses, e.g. through the websocket, share a bucket.
+        let ip = request.ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let mut rate_limiter = self.rate_limiter.lock().await;
+        let reservation = match rate_limiter
+            .reserve(&self.rate_limits, ip, request.owner, self.amount, now)
+            .await?
+        {
+            Ok(reservation) => reservation,
+            Err(retry_after) => return Err(rejected(rate_limited_error(retry_after))),
+        };
+        let Some(request_id) = self.claim_history.insert(request.owner) else {
+            rate_limiter.release(reservation);
+            return Err(rejected(faucet_busy_error()));
+        };
+        Ok((request_id, reservation))
+    }
+
+    /// Checks the proof of work and asks the claim verifier, if required.
+    async fn check_claim(&self, request: &ClaimRequest) -> Result<(), Error> {
+        if let Some(difficulty) = self.proof_of_work_difficulty {
+            let owner = request.owner;
+            let proof = request.nonce.map(|nonce| ProofOfWork { owner, nonce });
+            if !proof.is_some_and(|proof| proof.is_valid(difficulty)) {
+                return Err(rejected(proof_of_work_required_error(difficulty)));
+            }
+        }
+        if let Some(verifier) = &self.claim_verifier {
+            if let Err(reason) = verifier.verify(request).await {
+                return Err(rejected(claim_rejected_error(reason)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes a queued claim, updates the rate limits and records its result in the history.
+    async fn process_claim(
+        &self,
+        request_id: &str,
+        request: &ClaimRequest,
+        reservation: Reservation,
+    ) -> Result<ClaimOutcome, Error> {
+        #[cfg(with_metrics)]
+        let _latency = metrics::CLAIM_LATENCY.measure_latency();
+        let result = {
+            let _claim_guard = self.claim_lock.lock().await;
+            self.execute_claim(request).await.map_err(rejected)
+        };
+        let now = self.context.lock().await.storage().clock().current_time();
+        let mut rate_limiter = self.rate_limiter.lock().await;
+        if result.is_ok() {
+            #[cfg(with_metrics)]
+            metrics::CLAIMS_SERVED.with_label_values(&[]).inc();
+            if let Err(error) = rate_limiter.commit(reservation, now).await {
+                warn!("Failed to save the rate limits after a claim: {error}");
+            }
+            if let Err(error) = rate_limiter.prune(&self.rate_limits, now).await {
+                warn!("Failed to remove the full rate limit buckets: {error}");
+            }
+        } else {
+            rate_limiter.release(reservation);
+        }
+        self.claim_history.finish(request_id, &result);
+        result
+    }
+
+    async fn execute_claim(&self, request: &ClaimRequest) -> Result<ClaimOutcome, Error> {
+        let owner = request.owner;
         let client = self
             .context
             .lock()
             .await
             .make_chain_client(self.chain_id)
             .await?;
+        let local_time = client.storage_client().clock().current_time();
 
-        if self.start_timestamp < self.end_timestamp {
-            let local_time = client.storage_client().clock().current_time();
-            if local_time < self.end_timestamp {
-                let full_duration = self
-                    .end_timestamp
-                    .delta_since(self.start_timestamp)
-                    .as_micros();
-                let remaining_duration = self.end_timestamp.delta_since(local_time).as_micros();
-                let balance = client.local_balance().await?;
-                let Ok(remaining_balance) = balance.try_sub(self.amount) else {
-                    return Err(Error::new("The faucet is empty."));
-                };
-                // The tokens unlock linearly, e.g. if 1/3 of the time is left, then 1/3 of the
-                // tokens remain locked, so the remaining balance must be at least 1/3 of the start
-                // balance. In general:
-                // start_balance / full_duration <= remaining_balance / remaining_duration.
-                if Self::multiply(u128::from(self.start_balance), remaining_duration)
-                    > Self::multiply(u128::from(remaining_balance), full_duration)
-                {
-                    return Err(Error::new("Not enough unlocked balance; try again later."));
-                }
+        if self.start_timestamp < self.end_timestamp && local_time < self.end_timestamp {
+            let full_duration = self
+                .end_timestamp
+                .delta_since(self.start_timestamp)
+                .as_micros();
+            let remaining_duration = self.end_timestamp.delta_since(local_time).as_micros();
+            let balance = client.local_balance().await?;
+            let Ok(remaining_balance) = balance.try_sub(self.amount) else {
+                return Err(Error::new("The faucet is empty."));
+            };
+            // The tokens unlock linearly, e.g. if 1/3 of the time is left, then 1/3 of the
+            // tokens remain locked, so the remaining balance must be at least 1/3 of the start
+            // balance. In general:
+            // start_balance / full_duration <= remaining_balance / remaining_duration.
+            if Self::multiply(u128::from(self.start_balance), remaining_duration)
+                > Self::multiply(u128::from(remaining_balance), full_duration)
+            {
+                return Err(Error::new("Not enough unlocked balance; try again later."));
             }
         }
 
@@ -175,6 +433,50 @@ where
     }
 }
 
+/// Counts the given error as a rejected claim.
+fn rejected(error: Error) -> Error {
+    #[cfg(with_metrics)]
+    metrics::CLAIMS_REJECTED.with_label_values(&[]).inc();
+    error
+}
+
+/// Returns the error for a claim that exceeds the rate limits.
+fn rate_limited_error(retry_after: TimeDelta) -> Error {
+    let retry_after_secs = retry_after.as_micros().div_ceil(1_000_000);
+    Error::new(format!(
+        "Too many claims; try again in {retry_after_secs} seconds."
+    ))
+    .extend_with(|_, extensions| {
+        extensions.set("code", "RATE_LIMITED");
+        extensions.set("retryAfterSecs", retry_after_secs);
+    })
+}
+
+/// Returns the error for a claim that can't be queued because too many claims are pending.
+fn faucet_busy_error() -> Error {
+    Error::new("The faucet is busy; try again later.").extend_with(|_, extensions| {
+        extensions.set("code", "FAUCET_BUSY");
+    })
+}
+
+/// Returns the error for a claim without a valid proof of work.
+fn proof_of_work_required_error(difficulty: u32) -> Error {
+    Error::new(format!(
+        "A proof of work with difficulty {difficulty} is required."
+    ))
+    .extend_with(|_, extensions| {
+        extensions.set("code", "PROOF_OF_WORK_REQUIRED");
+        extensions.set("difficulty", difficulty);
+    })
+}
+
+/// Returns the error for a claim that the claim verifier rejected.
+fn claim_rejected_error(reason: String) -> Error {
+    Error::new(format!("The claim was rejected: {reason}")).extend_with(|_, extensions| {
+        extensions.set("code", "CLAIM_REJECTED");
+    })
+}
+
 impl<C> MutationRoot<C> {
     /// Multiplies a `u128` with a `u64` and returns the result as a 192-bit number.
     fn multiply(a: u128, b: u64) -> [u64; 3] {
@@ -202,6 +504,13 @@ where
     end_timestamp: Timestamp,
     start_timestamp: Timestamp,
     start_balance: Amount,
+    rate_limits: RateLimitConfig,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    proof_of_work_difficulty: Option<u32>,
+    claim_verifier: Option<Arc<dyn ClaimVerifier>>,
+    claim_history: Arc<ClaimHistory>,
+    claim_lock: Arc<Mutex<()>>,
+    trusted_proxies: Vec<IpAddr>,
 }
 
 impl<C> Clone for FaucetService<C>
@@ -220,6 +529,13 @@ where
             end_timestamp: self.end_timestamp,
             start_timestamp: self.start_timestamp,
             start_balance: self.start_balance,
+            rate_limits: self.rate_limits,
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            proof_of_work_difficulty: self.proof_of_work_difficulty,
+            claim_verifier: self.claim_verifier.clone(),
+            claim_history: Arc::clone(&self.claim_history),
+            claim_lock: Arc::clone(&self.claim_lock),
+            trusted_proxies: self.trusted_proxies.clone(),
         }
     }
 }
@@ -245,6 +561,8 @@ where
         let start_timestamp = client.storage_client().clock().current_time();
         client.process_inbox().await?;
         let start_balance = client.local_balance().await?;
+        let state = FaucetStateView::load(storage.named_context(STATE_NAME).await?).await?;
+        let rate_limiter = Arc::new(Mutex::new(RateLimiter::new(state)));
         Ok(Self {
             chain_id,
             context,
@@ -256,9 +574,54 @@ where
             end_timestamp,
             start_timestamp,
             start_balance,
+            rate_limits: RateLimitConfig::default(),
+            rate_limiter,
+            proof_of_work_difficulty: None,
+            claim_verifier: None,
+            claim_history: Arc::default(),
+            claim_lock: Arc::default(),
+            trusted_proxies: Vec::new(),
         })
     }
 
+    /// Limits the claims per IP address and the tokens per owner.
+    ///
+    /// Returns an error if the maximum amount per day is zero or less than the amount per claim.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimitConfig) -> anyhow::Result<Self> {
+        if let Some(max_amount_per_day) = rate_limits.max_amount_per_day {
+            anyhow::ensure!(
+                max_amount_per_day > Amount::ZERO,
+                "The maximum amount per day must be positive"
+            );
+            anyhow::ensure!(
+                self.amount <= max_amount_per_day,
+                "The amount per claim must not exceed the maximum amount per day"
+            );
+        }
+        self.rate_limits = rate_limits;
+        Ok(self)
+    }
+
+    /// Requires each claim to come with a proof of work of the given difficulty, i.e. number of
+    /// leading zero bits.
+    pub fn with_proof_of_work_difficulty(mut self, difficulty: Option<u32>) -> Self {
+        self.proof_of_work_difficulty = difficulty;
+        self
+    }
+
+    /// Rejects the claims that the given verifier doesn't accept.
+    pub fn with_claim_verifier(mut self, verifier: impl ClaimVerifier + 'static) -> Self {
+        self.claim_verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Trusts the `X-Forwarded-For` header of the requests from the given reverse proxies, to
+    /// know the IP addresses of the clients behind them.
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
     pub fn schema(&self) -> Schema<QueryRoot<C>, MutationRoot<C>, EmptySubscription> {
         let mutation_root = MutationRoot {
             chain_id: self.chain_id,
@@ -267,11 +630,19 @@ where
             end_timestamp: self.end_timestamp,
             start_timestamp: self.start_timestamp,
             start_balance: self.start_balance,
+            rate_limits: self.rate_limits,
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            proof_of_work_difficulty: self.proof_of_work_difficulty,
+            claim_verifier: self.claim_verifier.clone(),
+            claim_history: Arc::clone(&self.claim_history),
+            claim_lock: Arc::clone(&self.claim_lock),
         };
         let query_root = QueryRoot {
             genesis_config: Arc::clone(&self.genesis_config),
             context: Arc::clone(&self.context),
             chain_id: self.chain_id,
+            amount: self.amount,
+            claim_history: Arc::clone(&self.claim_history),
         };
         Schema::build(query_root, mutation_root, EmptySubscription).finish()
     }
@@ -295,7 +666,11 @@ where
             ChainListener::new(self.config, self.context, self.storage, cancellation_token).run();
         let tcp_listener =
             tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?;
-        let server = axum::serve(tcp_listener, app).into_future();
+        let server = axum::serve(
+            tcp_listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .into_future();
         futures::select! {
             result = Box::pin(chain_listener).fuse() => result?,
             result = Box::pin(server).fuse() => result?,
@@ -305,8 +680,40 @@ where
     }
 
     /// Executes a GraphQL query and generates a response for our `Schema`.
-    async fn index_handler(service: Extension<Self>, request: GraphQLRequest) -> GraphQLResponse {
+    async fn index_handler(
+        service: Extension<Self>,
+        ConnectInfo(address): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+        request: GraphQLRequest,
+    ) -> GraphQLResponse {
         let schema = service.0.schema();
-        schema.execute(request.into_inner()).await.into()
+        let ip = client_ip(address.ip(), &headers, &service.0.trusted_proxies);
+        let request = request.into_inner().data(ip);
+        schema.execute(request).await.into()
+    }
+}
+
+/// Returns the IP address of the client of a request from `peer`.
+///
+/// If the peer is a trusted proxy, the `X-Forwarded-For` header is read from right to left,
+/// since each proxy appends the address it received the request from: the client is the
+/// first address that isn't a trusted proxy itself. The addresses further left could have
+/// been made up by the client. If the header ends too early or is malformed, the last
+/// trusted proxy found is used instead.
+fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let mut client = peer;
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .flat_map(|value| value.to_str().unwrap_or_default().split(','));
+    for address in forwarded.rev() {
+        if !trusted_proxies.contains(&client) {
+            break;
+        }
+        let Ok(address) = address.trim().parse() else {
+            break;
+        };
+        client = address;
     }
+    client
 }
@@ -0,0 +1,489 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rate limiting of the faucet's claims, per IP address and per owner.
+//!
+//! Each IP address and each owner has a token bucket, which is refilled continuously and holds
+//! at most a period's worth of claims or tokens. The buckets are persisted in the faucet's
+//! storage, so that restarting the faucet doesn't reset them. Full buckets are removed from time
+//! to time, since they are the same as missing ones.
+//!
+//! A claim reserves its units when it is queued, so that the pending claims can't exceed the
+//! limits together. They are only taken from the stored buckets if the claim succeeds.
+
+use std::{collections::HashMap, net::IpAddr, num::NonZeroU32};
+
+use async_trait::async_trait;
+use linera_base::{
+    data_types::{Amount, TimeDelta, Timestamp},
+    identifiers::AccountOwner,
+};
+use linera_views::{
+    context::Context,
+    map_view::MapView,
+    views::{RootView, View, ViewError},
+};
+use serde::{Deserialize, Serialize};
+
+/// The period in which an IP address can make at most `max_claims_per_ip_per_hour` claims.
+const IP_PERIOD: TimeDelta = TimeDelta::from_hours(1);
+
+/// The period in which an owner can receive at most `max_amount_per_day` tokens.
+const OWNER_PERIOD: TimeDelta = TimeDelta::from_days(1);
+
+/// How often the full buckets are removed from the storage.
+const PRUNE_INTERVAL: TimeDelta = TimeDelta::from_hours(1);
+
+/// The limits on the claims of the faucet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RateLimitConfig {
+    /// The maximum number of claims from the same IP address per hour.
+    pub max_claims_per_ip_per_hour: Option<NonZeroU32>,
+    /// The maximum amount of tokens given to the same owner per day. Must not be zero.
+    pub max_amount_per_day: Option<Amount>,
+}
+
+impl RateLimitConfig {
+    /// Returns the capacity and the refill period of the bucket with the given key, or `None`
+    /// if there is no such limit.
+    fn bucket_limit(&self, key: &BucketKey) -> Option<(u128, TimeDelta)> {
+        match key {
+            BucketKey::Ip(_) => self
+                .max_claims_per_ip_per_hour
+                .map(|max_claims| (u128::from(max_claims.get()), IP_PERIOD)),
+            BucketKey::Owner(_) => self
+                .max_amount_per_day
+                .map(|max_amount| (u128::from(max_amount), OWNER_PERIOD)),
+        }
+    }
+}
+
+/// The key of a token bucket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BucketKey {
+    /// The bucket of the claims of an IP address.
+    Ip(IpAddr),
+    /// The bucket of the tokens given to an owner.
+    Owner(AccountOwner),
+}
+
+/// A token bucket that holds at most `capacity` units and is refilled with `capacity` units
+/// per `period`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBucket {
+    /// The units in the bucket at the time of its last update.
+    level: u128,
+    /// The time of the last update.
+    updated_at: Timestamp,
+}
+
+impl TokenBucket {
+    /// Returns the units in the bucket at time `now`.
+    fn level_at(&self, capacity: u128, period: TimeDelta, now: Timestamp) -> u128 {
+        let elapsed = u128::from(now.delta_since(self.updated_at).as_micros());
+        let period = u128::from(period.as_micros());
+        if elapsed >= period {
+            return capacity;
+        }
+        // This is `capacity * elapsed / period`, without overflowing.
+        let refill = capacity / period * elapsed + capacity % period * elapsed / period;
+        self.level.saturating_add(refill).min(capacity)
+    }
+
+    /// Takes up to `cost` units from the bucket.
+    fn take(&mut self, cost: u128, capacity: u128, period: TimeDelta, now: Timestamp) {
+        *self = TokenBucket {
+            level: self.level_at(capacity, period, now).saturating_sub(cost),
+            updated_at: now,
+        };
+    }
+
+    /// Takes `cost` units from the bucket, or returns how long to wait until they are
+    /// available. The `cost` must not exceed the `capacity`, which must be positive.
+    fn try_take(
+        &mut self,
+        cost: u128,
+        capacity: u128,
+        period: TimeDelta,
+        now: Timestamp,
+    ) -> Result<(), TimeDelta> {
+        let level = self.level_at(capacity, period, now);
+        if let Some(level) = level.checked_sub(cost) {
+            *self = TokenBucket {
+                level,
+                updated_at: now,
+            };
+            return Ok(());
+        }
+        let deficit = cost - level;
+        let period = u128::from(period.as_micros());
+        // Waiting a full period is always enough, since the cost doesn't exceed the capacity.
+        let wait = deficit
+            .checked_mul(period)
+            .map_or(period, |wait| wait.div_ceil(capacity));
+        Err(TimeDelta::from_micros(
+            u64::try_from(wait).unwrap_or(u64::MAX),
+        ))
+    }
+}
+
+/// The persistent state of the faucet.
+#[derive(RootView)]
+pub struct FaucetStateView<C> {
+    /// The token buckets of the IP addresses and owners that claimed tokens.
+    pub buckets: MapView<C, BucketKey, TokenBucket>,
+}
+
+/// Where the token buckets are stored.
+#[async_trait]
+pub trait BucketStore: Send + Sync {
+    /// Returns the bucket with the given key, if it was ever saved.
+    async fn bucket(&self, key: &BucketKey) -> Result<Option<TokenBucket>, ViewError>;
+
+    /// Returns all saved buckets.
+    async fn buckets(&self) -> Result<Vec<(BucketKey, TokenBucket)>, ViewError>;
+
+    /// Saves the given buckets.
+    async fn save_buckets(
+        &mut self,
+        buckets: Vec<(BucketKey, TokenBucket)>,
+    ) -> Result<(), ViewError>;
+
+    /// Removes the buckets with the given keys.
+    async fn remove_buckets(&mut self, keys: Vec<BucketKey>) -> Result<(), ViewError>;
+}
+
+#[async_trait]
+impl<C> BucketStore for FaucetStateView<C>
+where
+    C: Context + Clone + Send + Sync + 'static,
+{
+    async fn bucket(&self, key: &BucketKey) -> Result<Option<TokenBucket>, ViewError> {
+        self.buckets.get(key).await
+    }
+
+    async fn buckets(&self) -> Result<Vec<(BucketKey, TokenBucket)>, ViewError> {
+        self.buckets.index_values().await
+    }
+
+    async fn save_buckets(
+        &mut self,
+        buckets: Vec<(BucketKey, TokenBucket)>,
+    ) -> Result<(), ViewError> {
+        for (key, bucket) in buckets {
+            self.buckets.insert(&key, bucket)?;
+        }
+        self.save().await
+    }
+
+    async fn remove_buckets(&mut self, keys: Vec<BucketKey>) -> Result<(), ViewError> {
+        for key in keys {
+            self.buckets.remove(&key)?;
+        }
+        self.save().await
+    }
+}
+
+/// The units reserved by a claim, to be committed if it succeeds or released otherwise.
+#[must_use]
+pub struct Reservation(Vec<ReservedUnits>);
+
+/// The units reserved in one bucket.
+struct ReservedUnits {
+    key: BucketKey,
+    cost: u128,
+    capacity: u128,
+    period: TimeDelta,
+}
+
+/// Checks claims against a [`RateLimitConfig`], using the stored token buckets.
+pub struct RateLimiter {
+    store: Box<dyn BucketStore>,
+    /// The units reserved by the pending claims, by bucket.
+    reserved: HashMap<BucketKey, u128>,
+    /// When the full buckets were last removed, if they were since the faucet started.
+    pruned_at: Option<Timestamp>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter with the given buckets.
+    pub fn new(store: impl BucketStore + 'static) -> Self {
+        Self {
+            store: Box::new(store),
+            reserved: HashMap::new(),
+            pruned_at: None,
+        }
+    }
+
+    /// Checks that a claim of `amount` tokens by `owner` from `ip` is within the limits, taking
+    /// the pending claims into account.
+    ///
+    /// Returns the reserved units to [`commit`](RateLimiter::commit) if the claim succeeds or
+    /// [`release`](RateLimiter::release) otherwise, or how long to wait before the claim can be
+    /// retried.
+    pub async fn reserve(
+        &mut self,
+        config: &RateLimitConfig,
+        ip: IpAddr,
+        owner: AccountOwner,
+        amount: Amount,
+        now: Timestamp,
+    ) -> Result<Result<Reservation, TimeDelta>, ViewError> {
+        let costs = [
+            (BucketKey::Ip(ip), 1),
+            (BucketKey::Owner(owner), u128::from(amount)),
+        ];
+        let mut reservation = Vec::new();
+        let mut wait = None;
+        for (key, cost) in costs {
+            let Some((capacity, period)) = config.bucket_limit(&key) else {
+                continue;
+            };
+            let cost = cost.min(capacity);
+            let level = match self.store.bucket(&key).await? {
+                Some(bucket) => bucket.level_at(capacity, period, now),
+                None => capacity,
+            };
+            let reserved = self.reserved.get(&key).copied().unwrap_or_default();
+            let mut available = TokenBucket {
+                level: level.saturating_sub(reserved),
+                updated_at: now,
+            };
+            match available.try_take(cost, capacity, period, now) {
+                Ok(()) => reservation.push(ReservedUnits {
+                    key,
+                    cost,
+                    capacity,
+                    period,
+                }),
+                Err(delta) => wait = wait.max(Some(delta)),
+            }
+        }
+        if let Some(wait) = wait {
+            return Ok(Err(wait));
+        }
+        for units in &reservation {
+            *self.reserved.entry(units.key).or_default() += units.cost;
+        }
+        Ok(Ok(Reservation(reservation)))
+    }
+
+    /// Takes the units reserved by a successful claim from the stored buckets, at time `now`.
+    pub async fn commit(
+        &mut self,
+        reservation: Reservation,
+        now: Timestamp,
+    ) -> Result<(), ViewError> {
+        let mut buckets = Vec::new();
+        for units in self.unreserve(reservation) {
+            let mut bucket = self.store.bucket(&units.key).await?.unwrap_or(TokenBucket {
+                level: units.capacity,
+                updated_at: now,
+            });
+            bucket.take(units.cost, units.capacity, units.period, now);
+            buckets.push((units.key, bucket));
+        }
+        if buckets.is_empty() {
+            return Ok(());
+        }
+        self.store.save_buckets(buckets).await
+    }
+
+    /// Releases the units reserved by a failed claim.
+    pub fn release(&mut self, reservation: Reservation) {
+        self.unreserve(reservation);
+    }
+
+    /// Removes the units of the given reservation from the reserved ones, and returns them.
+    fn unreserve(&mut self, reservation: Reservation) -> Vec<ReservedUnits> {
+        for units in &reservation.0 {
+            if let Some(reserved) = self.reserved.get_mut(&units.key) {
+                *reserved -= units.cost;
+                if *reserved == 0 {
+                    self.reserved.remove(&units.key);
+                }
+            }
+        }
+        reservation.0
+    }
+
+    /// Removes the buckets that are full at time `now`, unless that was done recently.
+    pub async fn prune(
+        &mut self,
+        config: &RateLimitConfig,
+        now: Timestamp,
+    ) -> Result<(), ViewError> {
+        if self
+            .pruned_at
+            .is_some_and(|pruned_at| now.delta_since(pruned_at) < PRUNE_INTERVAL)
+        {
+            return Ok(());
+        }
+        let full_keys = self
+            .store
+            .buckets()
+            .await?
+            .into_iter()
+            .filter(|(key, bucket)| match config.bucket_limit(key) {
+                Some((capacity, period)) => bucket.level_at(capacity, period, now) == capacity,
+                None => true,
+            })
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        if !full_keys.is_empty() {
+            self.store.remove_buckets(full_keys).await?;
+        }
+        self.pruned_at = Some(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use linera_base::{
+        crypto::AccountPublicKey,
+        data_types::{Amount, TimeDelta, Timestamp},
+    };
+    use linera_views::{context::MemoryContext, views::View as _};
+
+    use super::{
+        BucketKey, FaucetStateView, RateLimitConfig, RateLimiter, TokenBucket, IP_PERIOD,
+        OWNER_PERIOD,
+    };
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let mut bucket = TokenBucket {
+            level: 3,
+            updated_at: Timestamp::from(0),
+        };
+        let period = TimeDelta::from_micros(3_000);
+        assert!(bucket.try_take(3, 3, period, Timestamp::from(0)).is_ok());
+        // One unit is refilled every 1000 microseconds.
+        assert_eq!(
+            bucket.try_take(2, 3, period, Timestamp::from(1_000)),
+            Err(TimeDelta::from_micros(1_000))
+        );
+        assert!(bucket
+            .try_take(2, 3, period, Timestamp::from(2_000))
+            .is_ok());
+        assert_eq!(bucket.level_at(3, period, Timestamp::from(100_000)), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_survive_reloading() -> anyhow::Result<()> {
+        let context = MemoryContext::new_for_testing(());
+        let config = RateLimitConfig {
+            max_claims_per_ip_per_hour: NonZeroU32::new(2),
+            max_amount_per_day: Some(Amount::from_tokens(10)),
+        };
+        let ip = "1.2.3.4".parse()?;
+        let owner = AccountPublicKey::test_key(0).into();
+        let amount = Amount::from_tokens(1);
+        let now = Timestamp::from(0);
+
+        let mut limiter = RateLimiter::new(FaucetStateView::load(context.clone()).await?);
+        for _ in 0..2 {
+            let reservation = limiter.reserve(&config, ip, owner, amount, now).await?;
+            limiter.commit(reservation.unwrap(), now).await?;
+        }
+        let wait = limiter.reserve(&config, ip, owner, amount, now).await?;
+        assert_eq!(
+            wait.err(),
+            Some(TimeDelta::from_micros(IP_PERIOD.as_micros() / 2))
+        );
+
+        // A claim that isn't committed doesn't count.
+        let other_ip = "5.6.7.8".parse()?;
+        let _ = limiter
+            .reserve(&config, other_ip, owner, amount, now)
+            .await?;
+
+        let mut limiter = RateLimiter::new(FaucetStateView::load(context).await?);
+        let reservation = limiter.reserve(&config, ip, owner, amount, now).await?;
+        assert!(reservation.is_err());
+        let reservation = limiter
+            .reserve(&config, other_ip, owner, amount, now)
+            .await?;
+        assert!(reservation.is_ok());
+        // The owner already received two of their ten tokens for the day, and one is reserved.
+        let large_amount = Amount::from_tokens(8);
+        let reservation = limiter
+            .reserve(&config, other_ip, owner, large_amount, now)
+            .await?;
+        assert!(reservation.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_full_buckets_are_pruned() -> anyhow::Result<()> {
+        let context = MemoryContext::new_for_testing(());
+        let config = RateLimitConfig {
+            max_claims_per_ip_per_hour: NonZeroU32::new(2),
+            max_amount_per_day: Some(Amount::from_tokens(10)),
+        };
+        let ip = "1.2.3.4".parse()?;
+        let owner = AccountPublicKey::test_key(0).into();
+        let amount = Amount::from_tokens(1);
+        let start = Timestamp::from(0);
+
+        let mut limiter = RateLimiter::new(FaucetStateView::load(context.clone()).await?);
+        let reservation = limiter.reserve(&config, ip, owner, amount, start).await?;
+        limiter.commit(reservation.unwrap(), start).await?;
+        limiter.prune(&config, start).await?;
+        let state = FaucetStateView::load(context.clone()).await?;
+        assert_eq!(state.buckets.count().await?, 2);
+
+        // After an hour, the IP address's bucket is full again, but not the owner's.
+        limiter
+            .prune(&config, start.saturating_add(IP_PERIOD))
+            .await?;
+        let state = FaucetStateView::load(context.clone()).await?;
+        assert_eq!(state.buckets.count().await?, 1);
+
+        limiter
+            .prune(&config, start.saturating_add(OWNER_PERIOD))
+            .await?;
+        let state = FaucetStateView::load(context).await?;
+        assert_eq!(state.buckets.count().await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pending_claims_are_reserved() -> anyhow::Result<()> {
+        let context = MemoryContext::new_for_testing(());
+        let config = RateLimitConfig {
+            max_claims_per_ip_per_hour: NonZeroU32::new(2),
+            max_amount_per_day: None,
+        };
+        let ip = "1.2.3.4".parse()?;
+        let owner = AccountPublicKey::test_key(0).into();
+        let amount = Amount::from_tokens(1);
+        let now = Timestamp::from(0);
+
+        let mut limiter = RateLimiter::new(FaucetStateView::load(context.clone()).await?);
+        let first = limiter.reserve(&config, ip, owner, amount, now).await?;
+        let second = limiter.reserve(&config, ip, owner, amount, now).await?;
+        let third = limiter.reserve(&config, ip, owner, amount, now).await?;
+        assert!(third.is_err());
+
+        // A released reservation can be used by another claim.
+        limiter.release(first.unwrap());
+        let third = limiter.reserve(&config, ip, owner, amount, now).await?;
+        limiter.commit(second.unwrap(), now).await?;
+        limiter.commit(third.unwrap(), now).await?;
+        let wait = limiter.reserve(&config, ip, owner, amount, now).await?;
+        assert_eq!(
+            wait.err(),
+            Some(TimeDelta::from_micros(IP_PERIOD.as_micros() / 2))
+        );
+
+        // Only the committed claims were saved.
+        let state = FaucetStateView::load(context).await?;
+        let bucket = state.buckets.get(&BucketKey::Ip(ip)).await?.unwrap();
+        assert_eq!(bucket.level, 0);
+        Ok(())
+    }
+}
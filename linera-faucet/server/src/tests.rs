@@ -3,12 +3,14 @@
 
 #![allow(clippy::large_futures)]
 
-use std::sync::Arc;
+use std::{net::IpAddr, num::NonZeroU32, sync::Arc, time::Duration};
 
+use async_graphql::{EmptySubscription, Request, Schema};
 use async_trait::async_trait;
+use axum::http::HeaderMap;
 use futures::lock::Mutex;
 use linera_base::{
-    crypto::{AccountPublicKey, InMemorySigner},
+    crypto::{AccountPublicKey, CryptoHash, InMemorySigner},
     data_types::{Amount, Timestamp},
     identifiers::{AccountOwner, ChainId},
 };
@@ -18,8 +20,17 @@ use linera_core::{
     environment,
     test_utils::{FaultType, MemoryStorageBuilder, StorageBuilder as _, TestBuilder},
 };
+use linera_faucet::ProofOfWork;
+use linera_storage::TestClock;
+use linera_views::{context::MemoryContext, views::View as _};
 
-use super::MutationRoot;
+use super::{
+    claims::{ClaimHistory, MAX_PENDING_CLAIMS},
+    client_ip,
+    rate_limit::{FaucetStateView, RateLimiter},
+    ClaimOutcome, ClaimRequest, ClaimState, ClaimStatus, ClaimVerifier, MutationRoot,
+    RateLimitConfig,
+};
 
 struct ClientContext {
     client: ChainClient<environment::Test>,
@@ -69,70 +80,327 @@ impl chain_listener::ClientContext for ClientContext {
     }
 }
 
+fn claim_request(key_index: u8) -> ClaimRequest {
+    ClaimRequest {
+        owner: AccountPublicKey::test_key(key_index).into(),
+        ip: Some("1.2.3.4".parse().unwrap()),
+        nonce: None,
+        verification: None,
+    }
+}
+
+async fn new_rate_limiter() -> Arc<Mutex<RateLimiter>> {
+    let context = MemoryContext::new_for_testing(());
+    let state = FaucetStateView::load(context).await.unwrap();
+    Arc::new(Mutex::new(RateLimiter::new(state)))
+}
+
+/// A faucet that gives one token per claim, with its validators and clock.
+struct TestFaucet {
+    builder: TestBuilder<MemoryStorageBuilder>,
+    clock: TestClock,
+    root: MutationRoot<ClientContext>,
+}
+
+impl TestFaucet {
+    /// Creates a faucet on a new chain with the `balance`, that starts at time 0 and unlocks
+    /// its whole balance by `end_timestamp`. It has no rate limits or other requirements.
+    async fn new(balance: Amount, end_timestamp: Timestamp) -> Self {
+        let storage_builder = MemoryStorageBuilder::default();
+        let mut keys = InMemorySigner::new(None);
+        let clock = storage_builder.clock().clone();
+        clock.set(Timestamp::from(0));
+        let mut builder = TestBuilder::new(storage_builder, 4, 1, &mut keys)
+            .await
+            .unwrap();
+        let client = builder.add_root_chain(1, balance).await.unwrap();
+        let chain_id = client.chain_id();
+        let context = ClientContext {
+            client,
+            update_calls: 0,
+        };
+        let root = MutationRoot {
+            chain_id,
+            context: Arc::new(Mutex::new(context)),
+            amount: Amount::from_tokens(1),
+            end_timestamp,
+            start_timestamp: Timestamp::from(0),
+            start_balance: balance,
+            rate_limits: RateLimitConfig::default(),
+            rate_limiter: new_rate_limiter().await,
+            proof_of_work_difficulty: None,
+            claim_verifier: None,
+            claim_history: Arc::default(),
+            claim_lock: Arc::default(),
+        };
+        Self {
+            builder,
+            clock,
+            root,
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_faucet_rate_limiting() {
-    let storage_builder = MemoryStorageBuilder::default();
-    let mut keys = InMemorySigner::new(None);
-    let clock = storage_builder.clock().clone();
-    clock.set(Timestamp::from(0));
-    let mut builder = TestBuilder::new(storage_builder, 4, 1, &mut keys)
-        .await
-        .unwrap();
-    let client = builder
-        .add_root_chain(1, Amount::from_tokens(6))
-        .await
-        .unwrap();
-    let chain_id = client.chain_id();
-    let context = ClientContext {
-        client,
-        update_calls: 0,
-    };
-    let context = Arc::new(Mutex::new(context));
-    let root = MutationRoot {
-        chain_id,
-        context: context.clone(),
-        amount: Amount::from_tokens(1),
-        end_timestamp: Timestamp::from(6000),
-        start_timestamp: Timestamp::from(0),
-        start_balance: Amount::from_tokens(6),
-    };
+    let TestFaucet {
+        mut builder,
+        clock,
+        root,
+    } = TestFaucet::new(Amount::from_tokens(6), Timestamp::from(6000)).await;
     // The faucet is releasing one token every 1000 microseconds. So at 1000 one claim should
     // succeed. At 3000, two more should have been unlocked.
     clock.set(Timestamp::from(999));
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(0).into())
-        .await
-        .is_err());
+    assert!(root.do_claim(&claim_request(0)).await.is_err());
     clock.set(Timestamp::from(1000));
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(1).into())
-        .await
-        .is_ok());
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(2).into())
-        .await
-        .is_err());
+    assert!(root.do_claim(&claim_request(1)).await.is_ok());
+    assert!(root.do_claim(&claim_request(2)).await.is_err());
     clock.set(Timestamp::from(3000));
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(3).into())
-        .await
-        .is_ok());
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(4).into())
-        .await
-        .is_ok());
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(5).into())
-        .await
-        .is_err());
+    assert!(root.do_claim(&claim_request(3)).await.is_ok());
+    assert!(root.do_claim(&claim_request(4)).await.is_ok());
+    assert!(root.do_claim(&claim_request(5)).await.is_err());
     // If a validator is offline, it will create a pending block and then fail.
     clock.set(Timestamp::from(6000));
     builder.set_fault_type([0, 1], FaultType::Offline).await;
-    assert!(root
-        .do_claim(AccountPublicKey::test_key(6).into())
-        .await
-        .is_err());
-    assert_eq!(context.lock().await.update_calls, 4); // Also called in the last error case.
+    assert!(root.do_claim(&claim_request(6)).await.is_err());
+    assert_eq!(root.context.lock().await.update_calls, 4); // Also called in the last error case.
+}
+
+#[tokio::test]
+async fn test_faucet_claim_limits() {
+    let mut faucet = TestFaucet::new(Amount::from_tokens(10), Timestamp::from(0)).await;
+    faucet.root.rate_limits = RateLimitConfig {
+        max_claims_per_ip_per_hour: NonZeroU32::new(2),
+        max_amount_per_day: Some(Amount::from_tokens(1)),
+    };
+    faucet.root.proof_of_work_difficulty = Some(4);
+    let root = &faucet.root;
+
+    // Claims need a valid proof of work.
+    let mut request = claim_request(0);
+    let error = root.do_claim(&request).await.unwrap_err();
+    assert_eq!(
+        error.message,
+        "A proof of work with difficulty 4 is required."
+    );
+    request.nonce = Some(ProofOfWork::solve(request.owner, 4).nonce);
+    assert!(root.do_claim(&request).await.is_ok());
+
+    // Each owner gets at most one token per day.
+    let error = root.do_claim(&request).await.unwrap_err();
+    assert_eq!(
+        error.message,
+        "Too many claims; try again in 86400 seconds."
+    );
+
+    // Each IP address makes at most two claims per hour.
+    let mut request = claim_request(1);
+    request.nonce = Some(ProofOfWork::solve(request.owner, 4).nonce);
+    assert!(root.do_claim(&request).await.is_ok());
+    let mut request = claim_request(2);
+    request.nonce = Some(ProofOfWork::solve(request.owner, 4).nonce);
+    assert!(root.do_claim(&request).await.is_err());
+    request.ip = Some("5.6.7.8".parse().unwrap());
+    assert!(root.do_claim(&request).await.is_ok());
+}
+
+/// Accepts the claims with the verification token `"valid"` from the IP address `1.2.3.4`.
+struct TokenVerifier;
+
+#[async_trait]
+impl ClaimVerifier for TokenVerifier {
+    async fn verify(&self, request: &ClaimRequest) -> Result<(), String> {
+        if request.ip != Some("1.2.3.4".parse().unwrap()) {
+            return Err("unknown IP address".to_string());
+        }
+        match request.verification.as_deref() {
+            Some("valid") => Ok(()),
+            Some(_) => Err("invalid token".to_string()),
+            None => Err("missing token".to_string()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_faucet_claim_verifier() {
+    let mut faucet = TestFaucet::new(Amount::from_tokens(10), Timestamp::from(0)).await;
+    faucet.root.claim_verifier = Some(Arc::new(TokenVerifier));
+    let root = &faucet.root;
+
+    let mut request = claim_request(0);
+    let error = root.do_claim(&request).await.unwrap_err();
+    assert_eq!(error.message, "The claim was rejected: missing token");
+    let code = error
+        .extensions
+        .as_ref()
+        .and_then(|extensions| extensions.get("code"));
+    assert_eq!(code, Some(&async_graphql::Value::from("CLAIM_REJECTED")));
+    request.verification = Some("forged".to_string());
+    let error = root.do_claim(&request).await.unwrap_err();
+    assert_eq!(error.message, "The claim was rejected: invalid token");
+
+    // The verifier sees the client's IP address.
+    request.verification = Some("valid".to_string());
+    request.ip = Some("5.6.7.8".parse().unwrap());
+    let error = root.do_claim(&request).await.unwrap_err();
+    assert_eq!(error.message, "The claim was rejected: unknown IP address");
+    request.ip = Some("1.2.3.4".parse().unwrap());
+    assert!(root.do_claim(&request).await.is_ok());
+}
+
+/// A query root that only returns the status of the claims, to test the mutations.
+struct ClaimStatusQuery(Arc<ClaimHistory>);
+
+#[async_graphql::Object]
+impl ClaimStatusQuery {
+    async fn claim_status(&self, request_id: String) -> Option<ClaimStatus> {
+        self.0.status(&request_id)
+    }
+}
+
+#[tokio::test]
+async fn test_faucet_submit_claim() {
+    let mut faucet = TestFaucet::new(Amount::from_tokens(10), Timestamp::from(0)).await;
+    faucet.root.rate_limits = RateLimitConfig {
+        max_claims_per_ip_per_hour: NonZeroU32::new(1),
+        max_amount_per_day: None,
+    };
+    faucet.root.claim_verifier = Some(Arc::new(TokenVerifier));
+    let history = Arc::clone(&faucet.root.claim_history);
+    let schema = Schema::build(
+        ClaimStatusQuery(Arc::clone(&history)),
+        faucet.root,
+        EmptySubscription,
+    )
+    .finish();
+    let submit_claim = |key_index: u8, verification: &str| {
+        let owner = AccountOwner::from(AccountPublicKey::test_key(key_index));
+        let mutation = format!(
+            r#"mutation {{ submitClaim(owner: "{owner}", verification: "{verification}") }}"#
+        );
+        Request::new(mutation).data("1.2.3.4".parse::<IpAddr>().unwrap())
+    };
+    let error_code = |request: Request| {
+        let schema = &schema;
+        async move {
+            let response = schema.execute(request).await;
+            let extensions = response.errors[0].extensions.as_ref().unwrap();
+            extensions.get("code").cloned()
+        }
+    };
+
+    // Claims that the verifier rejects are not queued.
+    let code = error_code(submit_claim(0, "forged")).await;
+    assert_eq!(code, Some(async_graphql::Value::from("CLAIM_REJECTED")));
+    let owner = AccountPublicKey::test_key(0).into();
+    assert!(history.claims_of(owner).is_empty());
+
+    let response = schema.execute(submit_claim(0, "valid")).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    let request_id = data["submitClaim"].as_str().unwrap().to_owned();
+
+    // The rate limits are checked before the claim is queued, too.
+    let code = error_code(submit_claim(1, "valid")).await;
+    assert_eq!(code, Some(async_graphql::Value::from("RATE_LIMITED")));
+    let other_owner = AccountPublicKey::test_key(1).into();
+    assert!(history.claims_of(other_owner).is_empty());
+
+    let query =
+        format!(r#"query {{ claimStatus(requestId: "{request_id}") {{ state chainId }} }}"#);
+    loop {
+        let data = schema
+            .execute(query.as_str())
+            .await
+            .data
+            .into_json()
+            .unwrap();
+        let status = &data["claimStatus"];
+        if status["state"] == "COMPLETED" {
+            assert!(status["chainId"].is_string());
+            break;
+        }
+        assert_eq!(status["state"], "PENDING");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(history.claims_of(owner).len(), 1);
+}
+
+#[test]
+fn test_client_ip_behind_trusted_proxies() {
+    let ip = |address: &str| address.parse::<IpAddr>().unwrap();
+    let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+    let headers = |values: &[&str]| {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", value.parse().unwrap());
+        }
+        headers
+    };
+
+    // The header of an untrusted peer is ignored.
+    let forged = headers(&["1.1.1.1"]);
+    assert_eq!(client_ip(ip("2.2.2.2"), &forged, &proxies), ip("2.2.2.2"));
+    // The client is the last address that isn't a trusted proxy.
+    let forwarded = headers(&["1.1.1.1, 2.2.2.2", "10.0.0.2"]);
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &forwarded, &proxies),
+        ip("2.2.2.2")
+    );
+    // Without a client address, the last trusted proxy is used.
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &headers(&[]), &proxies),
+        ip("10.0.0.1")
+    );
+    let malformed = headers(&["1.1.1.1, not an address, 10.0.0.2"]);
+    assert_eq!(
+        client_ip(ip("10.0.0.1"), &malformed, &proxies),
+        ip("10.0.0.2")
+    );
+}
+
+#[test]
+fn test_claim_history() {
+    let history = ClaimHistory::default();
+    let owner = AccountPublicKey::test_key(0).into();
+    let first = history.insert(owner).unwrap();
+    let second = history.insert(owner).unwrap();
+    let third = history.insert(owner).unwrap();
+    assert_eq!(history.status(&third).unwrap().queue_position, Some(2));
+
+    let outcome = ClaimOutcome {
+        chain_id: ChainId(CryptoHash::test_hash("new chain")),
+        certificate_hash: CryptoHash::test_hash("certificate"),
+    };
+    history.finish(&first, &Ok(outcome.clone()));
+    history.finish(
+        &second,
+        &Err(async_graphql::Error::new("The faucet is empty.")),
+    );
+    let status = history.status(&first).unwrap();
+    assert_eq!(status.state, ClaimState::Completed);
+    assert_eq!(status.chain_id, Some(outcome.chain_id));
+    assert_eq!(status.queue_position, None);
+    let status = history.status(&second).unwrap();
+    assert_eq!(status.state, ClaimState::Rejected);
+    assert_eq!(status.reason.as_deref(), Some("The faucet is empty."));
+    assert_eq!(history.status(&third).unwrap().queue_position, Some(0));
+    assert!(history.status("unknown").is_none());
+}
+
+#[test]
+fn test_claim_history_limits_pending_claims() {
+    let history = ClaimHistory::default();
+    let owner = AccountPublicKey::test_key(0).into();
+    let request_ids = (0..MAX_PENDING_CLAIMS)
+        .map(|_| history.insert(owner).unwrap())
+        .collect::<Vec<_>>();
+    assert!(history.insert(owner).is_none());
+    history.finish(
+        &request_ids[0],
+        &Err(async_graphql::Error::new("The faucet is empty.")),
+    );
+    assert!(history.insert(owner).is_some());
 }
 
 #[test]
@@ -5,7 +5,11 @@
 Common definitions for the Linera faucet.
 */
 
-use linera_base::{crypto::CryptoHash, identifiers::ChainId};
+use linera_base::{
+    crypto::{BcsHashable, CryptoHash},
+    identifiers::{AccountOwner, ChainId},
+};
+use serde::{Deserialize, Serialize};
 
 /// The result of a successful `claim` mutation.
 #[cfg_attr(feature = "async-graphql", derive(async_graphql::SimpleObject))]
@@ -19,3 +23,44 @@ pub struct ClaimOutcome {
     /// The hash of the parent chain's certificate containing the `OpenChain` operation.
     pub certificate_hash: CryptoHash,
 }
+
+/// A proof of work that a faucet may require with each claim, to make draining it costly.
+///
+/// It is valid if the hash of the owner and the nonce starts with at least as many zero bits
+/// as the faucet's difficulty.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ProofOfWork {
+    /// The owner of the new chain.
+    pub owner: AccountOwner,
+    /// The nonce found by the client.
+    pub nonce: u64,
+}
+
+impl BcsHashable<'_> for ProofOfWork {}
+
+impl ProofOfWork {
+    /// The highest difficulty, i.e. the number of bits of the hash.
+    pub const MAX_DIFFICULTY: u32 = 256;
+
+    /// Returns whether this proof of work has at least `difficulty` leading zero bits.
+    pub fn is_valid(&self, difficulty: u32) -> bool {
+        let hash = CryptoHash::new(self);
+        let mut leading_zeros = 0;
+        for byte in hash.as_bytes().iter() {
+            leading_zeros += byte.leading_zeros();
+            if *byte != 0 {
+                break;
+            }
+        }
+        leading_zeros >= difficulty
+    }
+
+    /// Finds a valid proof of work for the given owner and difficulty, which must not exceed
+    /// [`ProofOfWork::MAX_DIFFICULTY`].
+    pub fn solve(owner: AccountOwner, difficulty: u32) -> Self {
+        (0..)
+            .map(|nonce| ProofOfWork { owner, nonce })
+            .find(|proof| proof.is_valid(difficulty))
+            .expect("a nonce should be found before the range is exhausted")
+    }
+}
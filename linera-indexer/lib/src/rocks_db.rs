@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use clap::Parser as _;
 use linera_views::{
-    lru_caching::StorageCacheConfig,
+    lru_caching::{CachePolicyKind, StorageCacheConfig},
     rocks_db::{PathWithGuard, RocksDbSpawnMode, RocksDbStore, RocksDbStoreConfig},
     store::{AdminKeyValueStore, CommonStoreConfig},
 };
@@ -44,6 +44,10 @@ pub struct RocksDbConfig {
     /// The maximal number of entries in the storage cache.
     #[arg(long, default_value = "1000")]
     pub max_cache_entries: usize,
+
+    /// The eviction policy of the storage cache: lru, lfu or segmented.
+    #[arg(long, default_value = "lru")]
+    pub cache_policy: CachePolicyKind,
 }
 
 pub type RocksDbRunner = Runner<RocksDbStore, RocksDbConfig>;
@@ -55,6 +59,7 @@ impl RocksDbRunner {
             max_cache_size: config.client.max_cache_size,
             max_entry_size: config.client.max_entry_size,
             max_cache_entries: config.client.max_cache_entries,
+            cache_policy: config.client.cache_policy,
         };
         let common_config = CommonStoreConfig {
             max_concurrent_queries: config.client.max_concurrent_queries,
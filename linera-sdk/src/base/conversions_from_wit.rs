@@ -5,7 +5,10 @@
 
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, BlockHeight, TimeDelta, Timestamp},
+    data_types::{
+        Amount, ApplicationPermissions, BlockHeight, CommitteeSummary, Epoch, Round, TimeDelta,
+        Timestamp,
+    },
     http,
     identifiers::{AccountOwner, ApplicationId, ChainId},
     ownership::{ChainOwnership, TimeoutConfig},
@@ -79,6 +82,17 @@ macro_rules! impl_from_wit {
             }
         }
 
+        impl From<$wit_base_api::Round> for Round {
+            fn from(round: $wit_base_api::Round) -> Self {
+                match round {
+                    $wit_base_api::Round::Fast => Round::Fast,
+                    $wit_base_api::Round::MultiLeader(number) => Round::MultiLeader(number),
+                    $wit_base_api::Round::SingleLeader(number) => Round::SingleLeader(number),
+                    $wit_base_api::Round::Validator(number) => Round::Validator(number),
+                }
+            }
+        }
+
         impl From<$wit_base_api::Timestamp> for Timestamp {
             fn from(timestamp: $wit_base_api::Timestamp) -> Self {
                 Timestamp::from(timestamp.inner0)
@@ -130,6 +144,52 @@ macro_rules! impl_from_wit {
             }
         }
 
+        impl From<$wit_base_api::ApplicationPermissions> for ApplicationPermissions {
+            fn from(guest: $wit_base_api::ApplicationPermissions) -> ApplicationPermissions {
+                let $wit_base_api::ApplicationPermissions {
+                    execute_operations,
+                    mandatory_applications,
+                    close_chain,
+                    change_application_permissions,
+                    call_service_as_oracle,
+                    make_http_requests,
+                } = guest;
+                ApplicationPermissions {
+                    execute_operations: execute_operations
+                        .map(|app_ids| app_ids.into_iter().map(Into::into).collect()),
+                    mandatory_applications: mandatory_applications
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                    close_chain: close_chain.into_iter().map(Into::into).collect(),
+                    change_application_permissions: change_application_permissions
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                    call_service_as_oracle: call_service_as_oracle
+                        .map(|app_ids| app_ids.into_iter().map(Into::into).collect()),
+                    make_http_requests: make_http_requests
+                        .map(|app_ids| app_ids.into_iter().map(Into::into).collect()),
+                }
+            }
+        }
+
+        impl From<$wit_base_api::Epoch> for Epoch {
+            fn from(epoch: $wit_base_api::Epoch) -> Self {
+                Epoch(epoch.inner0)
+            }
+        }
+
+        impl From<$wit_base_api::CommitteeSummary> for CommitteeSummary {
+            fn from(committee: $wit_base_api::CommitteeSummary) -> Self {
+                CommitteeSummary {
+                    validator_count: committee.validator_count,
+                    total_votes: committee.total_votes,
+                    quorum_threshold: committee.quorum_threshold,
+                }
+            }
+        }
+
         impl From<$wit_base_api::HttpResponse> for http::Response {
             fn from(response: $wit_base_api::HttpResponse) -> http::Response {
                 http::Response {
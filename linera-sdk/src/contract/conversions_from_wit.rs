@@ -5,12 +5,13 @@
 
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, BlockHeight, StreamUpdate},
+    data_types::{Amount, BlockHeight, CalleeError, CreateDataBlobError, StreamUpdate},
+    http,
     identifiers::{
         AccountOwner, ApplicationId, ChainId, GenericApplicationId, MessageId, ModuleId, StreamId,
         StreamName,
     },
-    ownership::{ChangeApplicationPermissionsError, CloseChainError},
+    ownership::{ChangeApplicationPermissionsError, CloseChainError, TransferError},
     vm::VmRuntime,
 };
 
@@ -107,6 +108,23 @@ impl From<wit_contract_api::CloseChainError> for CloseChainError {
     fn from(guest: wit_contract_api::CloseChainError) -> Self {
         match guest {
             wit_contract_api::CloseChainError::NotPermitted => CloseChainError::NotPermitted,
+            wit_contract_api::CloseChainError::AlreadyClosed => CloseChainError::AlreadyClosed,
+        }
+    }
+}
+
+impl From<wit_contract_api::CalleeError> for CalleeError {
+    fn from(guest: wit_contract_api::CalleeError) -> Self {
+        match guest {
+            wit_contract_api::CalleeError::Trapped => CalleeError::Trapped,
+        }
+    }
+}
+
+impl From<wit_contract_api::CreateDataBlobError> for CreateDataBlobError {
+    fn from(guest: wit_contract_api::CreateDataBlobError) -> Self {
+        match guest {
+            wit_contract_api::CreateDataBlobError::TooLarge => CreateDataBlobError::TooLarge,
         }
     }
 }
@@ -123,6 +141,51 @@ impl From<wit_contract_api::ChangeApplicationPermissionsError>
     }
 }
 
+impl From<wit_contract_api::TransferError> for TransferError {
+    fn from(guest: wit_contract_api::TransferError) -> Self {
+        match guest {
+            wit_contract_api::TransferError::NotPermitted(owner) => {
+                TransferError::NotPermitted(owner.into())
+            }
+            wit_contract_api::TransferError::InsufficientFunding(balance) => {
+                TransferError::InsufficientFunding(balance.into())
+            }
+        }
+    }
+}
+
+impl From<wit_contract_api::HttpRequestError> for http::RequestError {
+    fn from(error: wit_contract_api::HttpRequestError) -> Self {
+        match error {
+            wit_contract_api::HttpRequestError::Timeout => http::RequestError::Timeout,
+            wit_contract_api::HttpRequestError::ResponseTooLarge => {
+                http::RequestError::ResponseTooLarge
+            }
+            wit_contract_api::HttpRequestError::NotAllowed => http::RequestError::NotAllowed,
+        }
+    }
+}
+
+impl From<wit_contract_api::HttpResponse> for http::Response {
+    fn from(response: wit_contract_api::HttpResponse) -> http::Response {
+        http::Response {
+            status: response.status,
+            headers: response
+                .headers
+                .into_iter()
+                .map(http::Header::from)
+                .collect(),
+            body: response.body,
+        }
+    }
+}
+
+impl From<wit_contract_api::HttpHeader> for http::Header {
+    fn from(header: wit_contract_api::HttpHeader) -> http::Header {
+        http::Header::new(header.name, header.value)
+    }
+}
+
 impl From<wit_entrypoints::CryptoHash> for CryptoHash {
     fn from(crypto_hash: wit_entrypoints::CryptoHash) -> Self {
         CryptoHash::from([
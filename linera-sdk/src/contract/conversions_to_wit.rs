@@ -8,6 +8,7 @@ use linera_base::{
     data_types::{
         Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, TimeDelta,
     },
+    http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, MessageId, ModuleId, StreamName},
     ownership::{ChainOwnership, TimeoutConfig},
     vm::VmRuntime,
@@ -245,6 +246,55 @@ impl From<ChainOwnership> for wit_contract_api::ChainOwnership {
     }
 }
 
+impl From<http::Request> for wit_contract_api::HttpRequest {
+    fn from(request: http::Request) -> Self {
+        wit_contract_api::HttpRequest {
+            method: request.method.into(),
+            url: request.url,
+            headers: request
+                .headers
+                .into_iter()
+                .map(http::Header::into)
+                .collect(),
+            body: request.body,
+        }
+    }
+}
+
+impl From<http::Method> for wit_contract_api::HttpMethod {
+    fn from(method: http::Method) -> Self {
+        match method {
+            http::Method::Get => wit_contract_api::HttpMethod::Get,
+            http::Method::Post => wit_contract_api::HttpMethod::Post,
+            http::Method::Put => wit_contract_api::HttpMethod::Put,
+            http::Method::Delete => wit_contract_api::HttpMethod::Delete,
+            http::Method::Head => wit_contract_api::HttpMethod::Head,
+            http::Method::Options => wit_contract_api::HttpMethod::Options,
+            http::Method::Connect => wit_contract_api::HttpMethod::Connect,
+            http::Method::Patch => wit_contract_api::HttpMethod::Patch,
+            http::Method::Trace => wit_contract_api::HttpMethod::Trace,
+        }
+    }
+}
+
+impl From<http::Header> for wit_contract_api::HttpHeader {
+    fn from(header: http::Header) -> Self {
+        wit_contract_api::HttpHeader {
+            name: header.name,
+            value: header.value,
+        }
+    }
+}
+
+impl From<http::RequestOptions> for wit_contract_api::HttpRequestOptions {
+    fn from(options: http::RequestOptions) -> Self {
+        wit_contract_api::HttpRequestOptions {
+            timeout_ms: options.timeout_ms,
+            max_response_bytes: options.max_response_bytes,
+        }
+    }
+}
+
 impl From<WriteOperation> for wit_contract_api::WriteOperation {
     fn from(write_operation: WriteOperation) -> Self {
         match write_operation {
@@ -12,10 +12,12 @@ mod test_runtime;
 #[doc(hidden)]
 pub mod wit;
 
+use thiserror::Error;
+
 #[cfg(not(with_testing))]
 pub use self::runtime::ContractRuntime;
 #[cfg(with_testing)]
-pub use self::test_runtime::MockContractRuntime;
+pub use self::test_runtime::{ApplicationCall, MockContractRuntime, Transfer};
 #[doc(hidden)]
 pub use self::wit::export_contract;
 use crate::{log::ContractLogger, util::BlockingWait};
@@ -24,6 +26,22 @@ use crate::{log::ContractLogger, util::BlockingWait};
 #[cfg(with_testing)]
 pub type ContractRuntime<Application> = MockContractRuntime<Application>;
 
+/// Errors that can happen when a contract queries a service as an oracle.
+///
+/// Failures of the service itself are not included: the service's response is recorded as an
+/// oracle response, so if the service can't answer, the whole transaction fails.
+#[derive(Debug, Error)]
+pub enum QueryServiceError {
+    /// The response of the service could not be deserialized.
+    #[error("Failed to deserialize service response: {0}")]
+    ResponseDeserialization(#[from] serde_json::Error),
+}
+
+/// Error returned when the current execution has no authenticated signer.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+#[error("The current execution is not authenticated by a signer")]
+pub struct Unauthorized;
+
 /// Declares an implementation of the [`Contract`][`crate::Contract`] trait, exporting it from the
 /// Wasm module.
 ///
@@ -6,18 +6,23 @@
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationPermissions, BlockHeight, CalleeError, CommitteeSummary,
+        CreateDataBlobError, Epoch, Resources, Round, SendMessageRequest, TimeDelta, Timestamp,
     },
     ensure, http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, MessageId, ModuleId, StreamName},
     ownership::{
         AccountPermissionError, ChainOwnership, ChangeApplicationPermissionsError, CloseChainError,
+        TransferError,
     },
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
-use super::wit::{base_runtime_api as base_wit, contract_runtime_api as contract_wit};
-use crate::{Contract, DataBlobHash, KeyValueStore, ViewStorageContext};
+use super::{
+    wit::{base_runtime_api as base_wit, contract_runtime_api as contract_wit},
+    QueryServiceError, Unauthorized,
+};
+use crate::{Contract, DataBlobHash, KeyValueStore, ViewStorageContext, ViewStorageExtra};
 
 /// The common runtime to interface with the host executing the contract.
 ///
@@ -37,6 +42,8 @@ where
     message_id: Option<Option<MessageId>>,
     authenticated_caller_id: Option<Option<ApplicationId>>,
     timestamp: Option<Timestamp>,
+    round: Option<Option<Round>>,
+    block_proposer: Option<Option<AccountOwner>>,
 }
 
 impl<Application> ContractRuntime<Application>
@@ -56,6 +63,8 @@ where
             message_id: None,
             authenticated_caller_id: None,
             timestamp: None,
+            round: None,
+            block_proposer: None,
         }
     }
 
@@ -66,7 +75,15 @@ where
 
     /// Returns a storage context suitable for a root view.
     pub fn root_view_storage_context(&self) -> ViewStorageContext {
-        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), ())
+        let extra = ViewStorageExtra {
+            block_height: self
+                .block_height
+                .unwrap_or_else(|| base_wit::get_block_height().into()),
+            timestamp: self
+                .timestamp
+                .unwrap_or_else(|| base_wit::read_system_timestamp().into()),
+        };
+        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), extra)
     }
 }
 
@@ -75,14 +92,42 @@ where
     Application: Contract,
 {
     /// Returns the application parameters provided when the application was created.
+    ///
+    /// Panics if the parameters can't be deserialized, see
+    /// [`ContractRuntime::try_application_parameters`] for a non-panicking alternative.
     pub fn application_parameters(&mut self) -> Application::Parameters {
-        self.application_parameters
-            .get_or_insert_with(|| {
-                let bytes = base_wit::application_parameters();
-                serde_json::from_slice(&bytes)
-                    .expect("Application parameters must be deserializable")
-            })
-            .clone()
+        self.try_application_parameters().unwrap_or_else(|error| {
+            panic!(
+                "Failed to deserialize application parameters {:?}: {error}",
+                String::from_utf8_lossy(&self.application_parameters_bytes()),
+            )
+        })
+    }
+
+    /// Returns the application parameters provided when the application was created, or the
+    /// error encountered while deserializing them.
+    ///
+    /// The parameters are cached only if they are deserialized successfully.
+    pub fn try_application_parameters(
+        &mut self,
+    ) -> Result<Application::Parameters, serde_json::Error> {
+        if let Some(parameters) = &self.application_parameters {
+            return Ok(parameters.clone());
+        }
+
+        let parameters: Application::Parameters =
+            serde_json::from_slice(&self.application_parameters_bytes())?;
+        self.application_parameters = Some(parameters.clone());
+        Ok(parameters)
+    }
+
+    /// Returns the raw JSON bytes of the application parameters provided when the application
+    /// was created.
+    ///
+    /// Useful for applications that need their own fallback parsing, for instance to migrate
+    /// parameters serialized by an older version of the application.
+    pub fn application_parameters_bytes(&mut self) -> Vec<u8> {
+        base_wit::application_parameters()
     }
 
     /// Returns the ID of the current application.
@@ -99,6 +144,11 @@ where
             .get_or_insert_with(|| base_wit::get_application_creator_chain_id().into())
     }
 
+    /// Returns `true` if the current chain is the one that created the current application.
+    pub fn is_creator_chain(&mut self) -> bool {
+        self.chain_id() == self.application_creator_chain_id()
+    }
+
     /// Returns the ID of the current chain.
     pub fn chain_id(&mut self) -> ChainId {
         *self
@@ -120,6 +170,27 @@ where
             .get_or_insert_with(|| base_wit::read_system_timestamp().into())
     }
 
+    /// Returns the consensus round in which the current block is validated, if known.
+    ///
+    /// The round isn't part of the block, so outside of the fast round reading it counts as an
+    /// oracle. In the fast round, it returns [`Round::Fast`] and doesn't count as an oracle, so
+    /// the block can still be confirmed in that round.
+    pub fn round(&mut self) -> Option<Round> {
+        *self
+            .round
+            .get_or_insert_with(|| base_wit::get_round().map(Round::from))
+    }
+
+    /// Returns the owner who proposed the current block, if the block is signed.
+    ///
+    /// This is the block's authenticated signer, so unlike [`Self::round`] it doesn't count as
+    /// an oracle and can be used in fast rounds.
+    pub fn block_proposer(&mut self) -> Option<AccountOwner> {
+        *self
+            .block_proposer
+            .get_or_insert_with(|| base_wit::get_block_proposer().map(AccountOwner::from))
+    }
+
     /// Returns the current chain balance.
     pub fn chain_balance(&mut self) -> Amount {
         base_wit::read_chain_balance().into()
@@ -135,6 +206,21 @@ where
         base_wit::get_chain_ownership().into()
     }
 
+    /// Retrieves the application permissions for the current chain.
+    pub fn application_permissions(&mut self) -> ApplicationPermissions {
+        base_wit::get_application_permissions().into()
+    }
+
+    /// Returns the current epoch of the chain.
+    pub fn epoch(&mut self) -> Epoch {
+        base_wit::get_epoch().into()
+    }
+
+    /// Returns a summary of the committee of validators in charge of the current epoch.
+    pub fn current_committee(&mut self) -> CommitteeSummary {
+        base_wit::get_current_committee().into()
+    }
+
     /// Makes an HTTP `request` as an oracle and returns the HTTP response.
     ///
     /// Should only be used with queries where it is very likely that all validators will receive
@@ -146,6 +232,51 @@ where
         base_wit::perform_http_request(&request.into()).into()
     }
 
+    /// Makes several HTTP `requests` as oracles and returns the HTTP responses in the same
+    /// order.
+    ///
+    /// All requests are sent in a single call to the host, which performs them concurrently up to
+    /// the limit set by the validators' resource control policy.
+    ///
+    /// Should only be used with queries where it is very likely that all validators will receive
+    /// the same responses, otherwise most block proposals will fail.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn http_requests(&mut self, requests: Vec<http::Request>) -> Vec<http::Response> {
+        let requests = requests
+            .into_iter()
+            .map(base_wit::HttpRequest::from)
+            .collect::<Vec<_>>();
+        base_wit::perform_http_requests(&requests)
+            .into_iter()
+            .map(http::Response::from)
+            .collect()
+    }
+
+    /// Makes an HTTP request to the given URL as an oracle with the limits from `options`, and
+    /// returns the answer.
+    ///
+    /// The limits are capped by the validators' resource control policy. Returns an error if
+    /// the request times out, if the response exceeds the maximum size, or if the policy doesn't
+    /// allow this application to send requests to the URL's host. Validators check the allowed
+    /// hosts when replaying the block, too, so refused requests can be handled by the contract.
+    ///
+    /// Should only be used with queries where it is very likely that all validators will receive
+    /// the same response, otherwise most block proposals will fail.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn http_request_with_options(
+        &mut self,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<http::Response, http::RequestError> {
+        contract_wit::try_perform_http_request(&request.into(), options.into())
+            .map(http::Response::from)
+            .map_err(http::RequestError::from)
+    }
+
     /// Panics if the current time at block validation is `>= timestamp`. Note that block
     /// validation happens at or after the block timestamp, but isn't necessarily the same.
     ///
@@ -155,15 +286,63 @@ where
         base_wit::assert_before(timestamp.into());
     }
 
+    /// Returns whether the block in which this is called has a timestamp at or after
+    /// `timestamp`.
+    ///
+    /// This only depends on the block timestamp, not on the validators' clocks, so unlike
+    /// [`Self::assert_before`] it can be used in fast blocks.
+    pub fn has_reached(&mut self, timestamp: Timestamp) -> bool {
+        self.system_time() >= timestamp
+    }
+
+    /// Returns the time from the block timestamp until `timestamp`, or `None` if it has been
+    /// reached already.
+    ///
+    /// Like [`Self::has_reached`], this only depends on the block timestamp.
+    pub fn remaining_until(&mut self, timestamp: Timestamp) -> Option<TimeDelta> {
+        let now = self.system_time();
+        (now < timestamp).then(|| timestamp.delta_since(now))
+    }
+
     /// Reads a data blob with the given hash from storage.
     pub fn read_data_blob(&mut self, hash: DataBlobHash) -> Vec<u8> {
         base_wit::read_data_blob(hash.0.into())
     }
 
+    /// Reads up to `length` bytes starting at `offset` from the data blob with the given hash.
+    ///
+    /// The range is truncated at the end of the blob, so large blobs can be read in chunks
+    /// without copying all of their contents at once. The fee is the same as for reading
+    /// the whole blob.
+    pub fn read_data_blob_range(
+        &mut self,
+        hash: DataBlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Vec<u8> {
+        base_wit::read_data_blob_range(hash.0.into(), offset, length)
+    }
+
+    /// Returns the size in bytes of the data blob with the given hash.
+    ///
+    /// The fee is the same as for reading the whole blob.
+    pub fn data_blob_size(&mut self, hash: DataBlobHash) -> u64 {
+        base_wit::data_blob_size(hash.0.into())
+    }
+
     /// Asserts that a data blob with the given hash exists in storage.
     pub fn assert_data_blob_exists(&mut self, hash: DataBlobHash) {
         base_wit::assert_data_blob_exists(hash.0.into())
     }
+
+    /// Returns whether a data blob with the given hash exists in storage.
+    ///
+    /// Unlike [`Self::assert_data_blob_exists`], the block doesn't fail if the blob is missing.
+    /// If the blob exists, it is registered as used by the block, the same way as when asserting
+    /// its existence.
+    pub fn data_blob_exists(&mut self, hash: DataBlobHash) -> bool {
+        base_wit::data_blob_exists(hash.0.into())
+    }
 }
 
 impl<Application> ContractRuntime<Application>
@@ -193,6 +372,40 @@ where
             .get_or_insert_with(contract_wit::message_is_bouncing)
     }
 
+    /// Returns the chain, block height and index of the incoming message that is being handled,
+    /// or [`None`] if not executing an incoming message.
+    ///
+    /// For a bouncing message, this is the chain that rejected the message, not the one that
+    /// originally sent it.
+    pub fn message_origin(&mut self) -> Option<(ChainId, BlockHeight, u32)> {
+        self.message_id()
+            .map(|message_id| (message_id.chain_id, message_id.height, message_id.index))
+    }
+
+    /// Returns [`true`] if an incoming message is being handled, and it was rejected from the
+    /// original destination and is now bouncing back.
+    pub fn is_bouncing(&mut self) -> bool {
+        self.message_is_bouncing().unwrap_or(false)
+    }
+
+    /// Returns the account that should be refunded if the incoming message that is being handled
+    /// carries tokens that can't be delivered.
+    ///
+    /// This is the account of the message's authenticated signer on the chain that originally
+    /// sent the message: the current chain if the message is bouncing, or the message's origin
+    /// otherwise. Returns [`None`] if not executing an incoming message, or if the message isn't
+    /// authenticated.
+    pub fn refund_recipient(&mut self) -> Option<Account> {
+        let (origin_chain_id, _, _) = self.message_origin()?;
+        let owner = self.authenticated_signer()?;
+        let chain_id = if self.is_bouncing() {
+            self.chain_id()
+        } else {
+            origin_chain_id
+        };
+        Some(Account { chain_id, owner })
+    }
+
     /// Returns the authenticated caller ID, if the caller configured it and if the current context
     /// is executing a cross-application call.
     pub fn authenticated_caller_id(&mut self) -> Option<ApplicationId> {
@@ -201,6 +414,17 @@ where
             .get_or_insert_with(|| contract_wit::authenticated_caller_id().map(ApplicationId::from))
     }
 
+    /// Returns the authenticated signer for this execution, or an error if there is none.
+    pub fn require_authenticated_signer(&mut self) -> Result<AccountOwner, Unauthorized> {
+        self.authenticated_signer().ok_or(Unauthorized)
+    }
+
+    /// Returns [`true`] if the current execution is a cross-application call authenticated by
+    /// the application `app`.
+    pub fn is_called_by(&mut self, app: ApplicationId) -> bool {
+        self.authenticated_caller_id() == Some(app)
+    }
+
     /// Verifies that the current execution context authorizes operations on a given account.
     pub fn check_account_permission(
         &mut self,
@@ -219,6 +443,18 @@ where
         self.prepare_message(message).send_to(destination)
     }
 
+    /// Schedules the same message to be sent to this application on each of the
+    /// `destinations`.
+    ///
+    /// Use [`Self::prepare_message`] to configure the flags or a grant for all copies.
+    pub fn send_message_to_many(
+        &mut self,
+        destinations: impl IntoIterator<Item = ChainId>,
+        message: Application::Message,
+    ) {
+        self.prepare_message(message).send_to_many(destinations)
+    }
+
     /// Returns a `MessageBuilder` to prepare a message to be sent.
     pub fn prepare_message(
         &mut self,
@@ -238,6 +474,37 @@ where
         contract_wit::claim(source.into(), destination.into(), amount.into())
     }
 
+    /// Transfers an `amount` of native tokens from `source` owner account (or the current chain's
+    /// balance) to `destination`.
+    ///
+    /// Unlike [`Self::transfer`], this returns an error instead of failing the block if the
+    /// application isn't allowed to access the `source` account or if its balance is too low.
+    pub fn try_transfer(
+        &mut self,
+        source: AccountOwner,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<(), TransferError> {
+        contract_wit::try_transfer(source.into(), destination.into(), amount.into())
+            .map_err(|error| error.into())
+    }
+
+    /// Claims an `amount` of native tokens from a `source` account to a `destination` account.
+    ///
+    /// Unlike [`Self::claim`], this returns an error instead of failing the block if the
+    /// application isn't allowed to access the `source` account. The balance of the `source`
+    /// account is only checked on its own chain, so [`TransferError::InsufficientFunding`] is
+    /// never returned here.
+    pub fn try_claim(
+        &mut self,
+        source: Account,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<(), TransferError> {
+        contract_wit::try_claim(source.into(), destination.into(), amount.into())
+            .map_err(|error| error.into())
+    }
+
     /// Calls another application.
     pub fn call_application<A: ContractAbi + Send>(
         &mut self,
@@ -248,37 +515,118 @@ where
         let call_bytes = A::serialize_operation(call)
             .expect("Failed to serialize `Operation` in cross-application call");
 
-        let response_bytes = contract_wit::try_call_application(
-            authenticated,
-            application.forget_abi().into(),
-            &call_bytes,
-        );
+        let response_bytes =
+            self.call_application_raw(authenticated, application.forget_abi(), &call_bytes);
 
         A::deserialize_response(response_bytes)
             .expect("Failed to deserialize `Response` in cross-application call")
     }
 
-    /// Adds a new item to an event stream. Returns the new event's index in the stream.
-    pub fn emit(&mut self, name: StreamName, value: &Application::EventValue) -> u32 {
+    /// Calls another application with an opaque blob of bytes, returning the raw response.
+    ///
+    /// Unlike [`Self::call_application`], the call and response aren't serialized, so this can
+    /// be used to forward calls to applications whose ABI isn't known.
+    pub fn call_application_raw(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call_bytes: &[u8],
+    ) -> Vec<u8> {
+        contract_wit::try_call_application(authenticated, application.into(), call_bytes)
+    }
+
+    /// Calls another application, rolling back everything the callee did if it traps.
+    ///
+    /// If the callee panics, its state changes, outgoing messages, events and created blobs are
+    /// discarded, and [`CalleeError::Trapped`] is returned so that the caller can fall back to
+    /// another code path. Resources already consumed by the callee remain charged. Failures
+    /// reported by the host, e.g. running out of fuel, still abort the transaction.
+    ///
+    /// The call can only be rolled back if the callee (and every application it calls) wasn't
+    /// already part of the current transaction. Otherwise, the failure aborts the transaction
+    /// as with [`Self::call_application`].
+    pub fn try_call_application<A: ContractAbi + Send>(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId<A>,
+        call: &A::Operation,
+    ) -> Result<A::Response, CalleeError> {
+        let call_bytes = A::serialize_operation(call)
+            .expect("Failed to serialize `Operation` in cross-application call");
+
+        let response_bytes =
+            self.try_call_application_raw(authenticated, application.forget_abi(), &call_bytes)?;
+
+        Ok(A::deserialize_response(response_bytes)
+            .expect("Failed to deserialize `Response` in cross-application call"))
+    }
+
+    /// Calls another application with an opaque blob of bytes, rolling back everything the
+    /// callee did if it traps.
+    ///
+    /// See [`Self::try_call_application`] for details.
+    pub fn try_call_application_raw(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call_bytes: &[u8],
+    ) -> Result<Vec<u8>, CalleeError> {
+        contract_wit::try_call_application_with_rollback(
+            authenticated,
+            application.into(),
+            call_bytes,
+        )
+        .map_err(CalleeError::from)
+    }
+
+    /// Adds a new item to an event stream, serialized with BCS. Returns the new event's index in
+    /// the stream.
+    ///
+    /// Indices are assigned per stream and keep increasing across operations and blocks, so two
+    /// operations in the same block emitting into the same stream get consecutive indices. The
+    /// event value should usually be the application's [`Contract::EventValue`], so that it can
+    /// be read back by subscribers.
+    pub fn emit<E: Serialize>(&mut self, name: StreamName, value: &E) -> u32 {
         contract_wit::emit(
             &name.into(),
             &bcs::to_bytes(value).expect("Failed to serialize event"),
         )
     }
 
-    /// Reads an event from a stream. Returns the event's value.
+    /// Reads an event from one of this application's streams on the given chain. Returns the
+    /// event's raw value.
+    ///
+    /// The event is recorded as an oracle response, so that the block can be re-executed.
+    ///
+    /// Fails the block if the event doesn't exist.
+    pub fn read_event(&mut self, chain_id: ChainId, name: StreamName, index: u32) -> Vec<u8> {
+        contract_wit::read_event(chain_id.into(), &name.into(), index)
+    }
+
+    /// Reads an event from one of this application's streams on the given chain, and
+    /// deserializes its value using BCS.
     ///
     /// Fails the block if the event doesn't exist.
-    pub fn read_event(
+    pub fn read_event_value<T: DeserializeOwned>(
         &mut self,
         chain_id: ChainId,
         name: StreamName,
         index: u32,
-    ) -> Application::EventValue {
-        let event = contract_wit::read_event(chain_id.into(), &name.into(), index);
+    ) -> T {
+        let event = self.read_event(chain_id, name, index);
         bcs::from_bytes(&event).expect("Failed to deserialize event")
     }
 
+    /// Returns the index the next event in one of this application's streams on the given chain
+    /// will have.
+    ///
+    /// For the current chain, this is the number of events emitted so far. For other chains, it
+    /// is the number of events this chain has been notified about, so all events with lower
+    /// indices can be read.
+    pub fn next_event_index(&mut self, chain_id: ChainId, name: StreamName) -> u32 {
+        contract_wit::next_event_index(chain_id.into(), &name.into())
+    }
+
     /// Subscribes this application to an event stream.
     pub fn subscribe_to_events(
         &mut self,
@@ -301,23 +649,55 @@ where
 
     /// Queries an application service as an oracle and returns the response.
     ///
+    /// The query and the response are serialized as JSON, the same way as in
+    /// `ServiceRuntime::query_application`. The response is recorded in the block as an oracle
+    /// response.
+    ///
     /// Should only be used with queries where it is very likely that all validators will compute
     /// the same result, otherwise most block proposals will fail.
     ///
     /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
     /// owner, not a super owner.
-    pub fn query_service<A: ServiceAbi + Send>(
+    ///
+    /// # Panics
+    ///
+    /// If the response can't be deserialized. Use [`Self::try_query_service`] to handle this
+    /// failure.
+    pub fn query_service<A: ServiceAbi>(
         &mut self,
         application_id: ApplicationId<A>,
-        query: A::Query,
+        query: &A::Query,
     ) -> A::QueryResponse {
-        let query = serde_json::to_vec(&query).expect("Failed to serialize service query");
+        self.try_query_service(application_id, query)
+            .unwrap_or_else(|error| panic!("Failed to query service: {error}"))
+    }
+
+    /// Queries an application service as an oracle, returning an error instead of panicking if
+    /// the response can't be deserialized.
+    ///
+    /// If the service itself fails, e.g. because it traps, the transaction still fails, since
+    /// there is no response to record.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn try_query_service<A: ServiceAbi>(
+        &mut self,
+        application_id: ApplicationId<A>,
+        query: &A::Query,
+    ) -> Result<A::QueryResponse, QueryServiceError> {
+        let query = serde_json::to_vec(query).expect("Failed to serialize service query");
         let response = contract_wit::query_service(application_id.forget_abi().into(), &query);
-        serde_json::from_slice(&response).expect("Failed to deserialize service response")
+        Ok(serde_json::from_slice(&response)?)
     }
 
     /// Opens a new chain, configuring it with the provided `chain_ownership`,
     /// `application_permissions` and initial `balance` (debited from the current chain).
+    ///
+    /// The `chain_ownership` can have multiple weighted owners and its own timeout
+    /// configuration, and is best created with [`ChainOwnership::builder`], which checks
+    /// that the new chain can make progress. The new chain is created from a chain
+    /// description blob rather than a message, so only its [`ChainId`] is returned.
+    /// Messages can be sent to it right away.
     pub fn open_chain(
         &mut self,
         chain_ownership: ChainOwnership,
@@ -333,7 +713,7 @@ where
     }
 
     /// Closes the current chain. Returns an error if the application doesn't have
-    /// permission to do so.
+    /// permission to do so, or if the chain is already closed.
     pub fn close_chain(&mut self) -> Result<(), CloseChainError> {
         contract_wit::close_chain().map_err(|error| error.into())
     }
@@ -348,6 +728,11 @@ where
     }
 
     /// Creates a new on-chain application, based on the supplied module and parameters.
+    ///
+    /// The new application is instantiated with the `argument` before this returns. Its ID is
+    /// derived from its description, which includes the current chain, the block height and the
+    /// number of applications created earlier in the block, so it is the same on every
+    /// validator.
     pub fn create_application<Abi, Parameters, InstantiationArgument>(
         &mut self,
         module_id: ModuleId,
@@ -378,14 +763,48 @@ where
         ApplicationId::from(application_id).with_abi::<Abi>()
     }
 
+    /// Creates a new data blob with the given content and returns its hash.
+    ///
+    /// The blob is published by the current block, which pays for its size. It can be read
+    /// using [`ContractRuntime::read_data_blob`] from subsequent blocks. Returns an error if
+    /// the blob exceeds the maximum blob size.
+    pub fn create_data_blob(&mut self, bytes: &[u8]) -> Result<DataBlobHash, CreateDataBlobError> {
+        contract_wit::create_data_blob(bytes)
+            .map(|hash| DataBlobHash(hash.into()))
+            .map_err(|error| error.into())
+    }
+
     /// Returns the round in which this block was validated.
     pub fn validation_round(&mut self) -> Option<u32> {
         contract_wit::validation_round()
     }
+
+    /// Returns the amount of execution fuel remaining before execution is aborted.
+    ///
+    /// Fuel metering is deterministic, so this is the same on every validator. Long-running
+    /// operations can use it to stop early and schedule the rest of the work in a later block.
+    pub fn remaining_fuel(&mut self) -> u64 {
+        contract_wit::remaining_fuel()
+    }
 }
 
 /// A helper type that uses the builder pattern to configure how a message is sent, and then
 /// sends the message once it is dropped.
+///
+/// ```no_run
+/// # use linera_sdk::{linera_base_types::ChainId, Contract, ContractRuntime};
+/// fn notify<Application: Contract>(
+///     runtime: &mut ContractRuntime<Application>,
+///     message: Application::Message,
+///     subscribers: &[ChainId],
+/// ) {
+///     runtime
+///         .prepare_message(message)
+///         .with_tracking()
+///         .with_authentication()
+///         .send_to_many(subscribers.iter().copied());
+/// }
+/// ```
 #[must_use]
 pub struct MessageBuilder<Message>
 where
@@ -445,4 +864,26 @@ where
 
         contract_wit::send_message(&raw_message.into())
     }
+
+    /// Schedules this `Message` to be sent to each of the `destinations`, with the same flags.
+    ///
+    /// The message is only serialized once, and passed to the host in a single call. Each copy
+    /// is still charged as a separate message, and if a grant was configured, each copy forwards
+    /// its own grant.
+    pub fn send_to_many(self, destinations: impl IntoIterator<Item = ChainId>) {
+        let destinations = destinations
+            .into_iter()
+            .map(contract_wit::ChainId::from)
+            .collect::<Vec<_>>();
+        let serialized_message =
+            bcs::to_bytes(&self.message).expect("Failed to serialize message to be sent");
+
+        contract_wit::send_message_to_many(
+            &destinations,
+            self.authenticated,
+            self.is_tracked,
+            self.grant.into(),
+            &serialized_message,
+        )
+    }
 }
@@ -5,23 +5,29 @@
 
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Debug,
+    mem,
     sync::{Arc, Mutex, MutexGuard},
 };
 
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationPermissions, Blob, BlockHeight, CalleeError, CommitteeSummary,
+        CreateDataBlobError, Epoch, Resources, Round, SendMessageRequest, TimeDelta, Timestamp,
     },
     ensure, http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, MessageId, ModuleId, StreamName},
     ownership::{
         AccountPermissionError, ChainOwnership, ChangeApplicationPermissionsError, CloseChainError,
+        TransferError,
     },
 };
-use serde::Serialize;
+use linera_views::test_utils::faulty_store::FaultyMemoryStore;
+use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{Contract, DataBlobHash, KeyValueStore, ViewStorageContext};
+use super::{QueryServiceError, Unauthorized};
+use crate::{Contract, DataBlobHash, KeyValueStore, ViewStorageContext, ViewStorageExtra};
 
 struct ExpectedCreateApplicationCall {
     module_id: ModuleId,
@@ -37,12 +43,16 @@ where
     Application: Contract,
 {
     application_parameters: Option<Application::Parameters>,
+    application_parameters_bytes: Option<Vec<u8>>,
     application_id: Option<ApplicationId<Application::Abi>>,
     application_creator_chain_id: Option<ChainId>,
     chain_id: Option<ChainId>,
     authenticated_signer: Option<Option<AccountOwner>>,
     block_height: Option<BlockHeight>,
     round: Option<u32>,
+    consensus_round: Option<Option<Round>>,
+    block_proposer: Option<Option<AccountOwner>>,
+    remaining_fuel: Option<u64>,
     message_id: Option<Option<MessageId>>,
     message_is_bouncing: Option<Option<bool>>,
     authenticated_caller_id: Option<Option<ApplicationId>>,
@@ -50,20 +60,37 @@ where
     chain_balance: Option<Amount>,
     owner_balances: Option<HashMap<AccountOwner, Amount>>,
     chain_ownership: Option<ChainOwnership>,
+    application_permissions: Option<ApplicationPermissions>,
+    epoch: Option<Epoch>,
+    current_committee: Option<CommitteeSummary>,
     can_close_chain: Option<bool>,
+    chain_closed: bool,
     can_change_application_permissions: Option<bool>,
     call_application_handler: Option<CallApplicationHandler>,
+    try_call_application_handler: Option<TryCallApplicationHandler>,
     send_message_requests: Arc<Mutex<Vec<SendMessageRequest<Application::Message>>>>,
     outgoing_transfers: HashMap<Account, Amount>,
+    transfers: Vec<Transfer>,
+    application_calls: Vec<ApplicationCall>,
+    expected_transfers: Vec<Transfer>,
+    expected_messages: Vec<(ChainId, Vec<u8>)>,
+    fail_on_unexpected_calls: bool,
     created_events: BTreeMap<StreamName, Vec<Vec<u8>>>,
     events: BTreeMap<(ChainId, StreamName, u32), Vec<u8>>,
     claim_requests: Vec<ClaimRequest>,
     expected_service_queries: VecDeque<(ApplicationId, String, String)>,
-    expected_http_requests: VecDeque<(http::Request, http::Response)>,
+    expected_http_requests: VecDeque<(http::Request, Result<http::Response, http::RequestError>)>,
+    http_request_handler: Option<HttpRequestHandler>,
     expected_read_data_blob_requests: VecDeque<(DataBlobHash, Vec<u8>)>,
+    expected_read_data_blob_range_requests: VecDeque<((DataBlobHash, u64, u64), Vec<u8>)>,
+    expected_data_blob_size_requests: VecDeque<(DataBlobHash, u64)>,
     expected_assert_data_blob_exists_requests: VecDeque<(DataBlobHash, Option<()>)>,
+    expected_data_blob_exists_requests: VecDeque<(DataBlobHash, bool)>,
     expected_open_chain_calls: VecDeque<(ChainOwnership, ApplicationPermissions, Amount, ChainId)>,
     expected_create_application_calls: VecDeque<ExpectedCreateApplicationCall>,
+    maximum_blob_size: Option<u64>,
+    created_data_blobs: HashMap<DataBlobHash, Vec<u8>>,
+    data_blobs: HashMap<DataBlobHash, Vec<u8>>,
     key_value_store: KeyValueStore,
 }
 
@@ -84,12 +111,16 @@ where
     pub fn new() -> Self {
         MockContractRuntime {
             application_parameters: None,
+            application_parameters_bytes: None,
             application_id: None,
             application_creator_chain_id: None,
             chain_id: None,
             authenticated_signer: None,
             block_height: None,
             round: None,
+            consensus_round: None,
+            block_proposer: None,
+            remaining_fuel: None,
             message_id: None,
             message_is_bouncing: None,
             authenticated_caller_id: None,
@@ -97,20 +128,37 @@ where
             chain_balance: None,
             owner_balances: None,
             chain_ownership: None,
+            application_permissions: None,
+            epoch: None,
+            current_committee: None,
             can_close_chain: None,
+            chain_closed: false,
             can_change_application_permissions: None,
             call_application_handler: None,
+            try_call_application_handler: None,
             send_message_requests: Arc::default(),
             outgoing_transfers: HashMap::new(),
+            transfers: Vec::new(),
+            application_calls: Vec::new(),
+            expected_transfers: Vec::new(),
+            expected_messages: Vec::new(),
+            fail_on_unexpected_calls: false,
             created_events: BTreeMap::new(),
             events: BTreeMap::new(),
             claim_requests: Vec::new(),
             expected_service_queries: VecDeque::new(),
             expected_http_requests: VecDeque::new(),
+            http_request_handler: None,
             expected_read_data_blob_requests: VecDeque::new(),
+            expected_read_data_blob_range_requests: VecDeque::new(),
+            expected_data_blob_size_requests: VecDeque::new(),
             expected_assert_data_blob_exists_requests: VecDeque::new(),
+            expected_data_blob_exists_requests: VecDeque::new(),
             expected_open_chain_calls: VecDeque::new(),
             expected_create_application_calls: VecDeque::new(),
+            maximum_blob_size: None,
+            created_data_blobs: HashMap::new(),
+            data_blobs: HashMap::new(),
             key_value_store: KeyValueStore::mock().to_mut(),
         }
     }
@@ -120,9 +168,31 @@ where
         self.key_value_store.clone()
     }
 
+    /// Configures the storage to keep its contents in `store`, so that the faults and latency
+    /// programmed in `store` apply to the application's views.
+    pub fn with_faulty_store(mut self, store: FaultyMemoryStore) -> Self {
+        self.set_faulty_store(store);
+        self
+    }
+
+    /// Configures the storage to keep its contents in `store`, so that the faults and latency
+    /// programmed in `store` apply to the application's views.
+    pub fn set_faulty_store(&mut self, store: FaultyMemoryStore) -> &mut Self {
+        self.key_value_store = KeyValueStore::faulty_mock(store).to_mut();
+        self
+    }
+
     /// Returns a storage context suitable for a root view.
+    ///
+    /// The block height and the timestamp of the context are the ones configured with
+    /// [`MockContractRuntime::set_block_height`] and
+    /// [`MockContractRuntime::set_system_time`], or zero.
     pub fn root_view_storage_context(&self) -> ViewStorageContext {
-        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), ())
+        let extra = ViewStorageExtra {
+            block_height: self.block_height.unwrap_or_default(),
+            timestamp: self.timestamp.unwrap_or_default(),
+        };
+        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), extra)
     }
 
     /// Configures the application parameters to return during the test.
@@ -143,12 +213,62 @@ where
         self
     }
 
+    /// Configures the raw JSON bytes of the application parameters to return during the test.
+    ///
+    /// Takes precedence over parameters configured with
+    /// [`MockContractRuntime::set_application_parameters`], so that tests can exercise the
+    /// handling of parameters that fail to deserialize.
+    pub fn with_application_parameters_bytes(mut self, bytes: Vec<u8>) -> Self {
+        self.application_parameters_bytes = Some(bytes);
+        self
+    }
+
+    /// Configures the raw JSON bytes of the application parameters to return during the test.
+    ///
+    /// Takes precedence over parameters configured with
+    /// [`MockContractRuntime::set_application_parameters`], so that tests can exercise the
+    /// handling of parameters that fail to deserialize.
+    pub fn set_application_parameters_bytes(&mut self, bytes: Vec<u8>) -> &mut Self {
+        self.application_parameters_bytes = Some(bytes);
+        self
+    }
+
     /// Returns the application parameters provided when the application was created.
     pub fn application_parameters(&mut self) -> Application::Parameters {
-        self.application_parameters.clone().expect(
+        self.try_application_parameters().unwrap_or_else(|error| {
+            panic!(
+                "Failed to deserialize application parameters {:?}: {error}",
+                String::from_utf8_lossy(&self.application_parameters_bytes()),
+            )
+        })
+    }
+
+    /// Returns the application parameters provided when the application was created, or the
+    /// error encountered while deserializing the mocked raw bytes.
+    pub fn try_application_parameters(
+        &mut self,
+    ) -> Result<Application::Parameters, serde_json::Error> {
+        match &self.application_parameters_bytes {
+            Some(bytes) => serde_json::from_slice(bytes),
+            None => Ok(self.application_parameters.clone().expect(
+                "Application parameters have not been mocked, \
+                please call `MockContractRuntime::set_application_parameters` first",
+            )),
+        }
+    }
+
+    /// Returns the raw JSON bytes of the application parameters provided when the application
+    /// was created.
+    pub fn application_parameters_bytes(&mut self) -> Vec<u8> {
+        if let Some(bytes) = &self.application_parameters_bytes {
+            return bytes.clone();
+        }
+
+        let parameters = self.application_parameters.as_ref().expect(
             "Application parameters have not been mocked, \
             please call `MockContractRuntime::set_application_parameters` first",
-        )
+        );
+        serde_json::to_vec(parameters).expect("Failed to serialize application parameters")
     }
 
     /// Configures the application ID to return during the test.
@@ -194,6 +314,11 @@ where
         )
     }
 
+    /// Returns `true` if the current chain is the one that created the current application.
+    pub fn is_creator_chain(&mut self) -> bool {
+        self.chain_id() == self.application_creator_chain_id()
+    }
+
     /// Configures the chain ID to return during the test.
     pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
         self.chain_id = Some(chain_id);
@@ -320,6 +445,40 @@ where
         )
     }
 
+    /// Returns the chain, block height and index of the incoming message that is being handled,
+    /// or [`None`] if not executing an incoming message.
+    ///
+    /// For a bouncing message, this is the chain that rejected the message, not the one that
+    /// originally sent it.
+    pub fn message_origin(&mut self) -> Option<(ChainId, BlockHeight, u32)> {
+        self.message_id()
+            .map(|message_id| (message_id.chain_id, message_id.height, message_id.index))
+    }
+
+    /// Returns [`true`] if an incoming message is being handled, and it was rejected from the
+    /// original destination and is now bouncing back.
+    pub fn is_bouncing(&mut self) -> bool {
+        self.message_is_bouncing().unwrap_or(false)
+    }
+
+    /// Returns the account that should be refunded if the incoming message that is being handled
+    /// carries tokens that can't be delivered.
+    ///
+    /// This is the account of the message's authenticated signer on the chain that originally
+    /// sent the message: the current chain if the message is bouncing, or the message's origin
+    /// otherwise. Returns [`None`] if not executing an incoming message, or if the message isn't
+    /// authenticated.
+    pub fn refund_recipient(&mut self) -> Option<Account> {
+        let (origin_chain_id, _, _) = self.message_origin()?;
+        let owner = self.authenticated_signer()?;
+        let chain_id = if self.is_bouncing() {
+            self.chain_id()
+        } else {
+            origin_chain_id
+        };
+        Some(Account { chain_id, owner })
+    }
+
     /// Configures the authenticated caller ID to return during the test.
     pub fn with_authenticated_caller_id(
         mut self,
@@ -347,6 +506,17 @@ where
         )
     }
 
+    /// Returns the authenticated signer for this execution, or an error if there is none.
+    pub fn require_authenticated_signer(&mut self) -> Result<AccountOwner, Unauthorized> {
+        self.authenticated_signer().ok_or(Unauthorized)
+    }
+
+    /// Returns [`true`] if the current execution is a cross-application call authenticated by
+    /// the application `app`.
+    pub fn is_called_by(&mut self, app: ApplicationId) -> bool {
+        self.authenticated_caller_id() == Some(app)
+    }
+
     /// Verifies that the current execution context authorizes operations on a given account.
     pub fn check_account_permission(
         &mut self,
@@ -380,6 +550,49 @@ where
         )
     }
 
+    /// Configures the consensus round to return during the test.
+    pub fn with_consensus_round(mut self, round: impl Into<Option<Round>>) -> Self {
+        self.consensus_round = Some(round.into());
+        self
+    }
+
+    /// Configures the consensus round to return during the test.
+    pub fn set_consensus_round(&mut self, round: impl Into<Option<Round>>) -> &mut Self {
+        self.consensus_round = Some(round.into());
+        self
+    }
+
+    /// Returns the consensus round in which the current block is validated, if known.
+    pub fn round(&mut self) -> Option<Round> {
+        self.consensus_round.expect(
+            "Consensus round has not been mocked, \
+            please call `MockContractRuntime::set_consensus_round` first",
+        )
+    }
+
+    /// Configures the block proposer to return during the test.
+    pub fn with_block_proposer(mut self, block_proposer: impl Into<Option<AccountOwner>>) -> Self {
+        self.block_proposer = Some(block_proposer.into());
+        self
+    }
+
+    /// Configures the block proposer to return during the test.
+    pub fn set_block_proposer(
+        &mut self,
+        block_proposer: impl Into<Option<AccountOwner>>,
+    ) -> &mut Self {
+        self.block_proposer = Some(block_proposer.into());
+        self
+    }
+
+    /// Returns the owner who proposed the current block, if the block is signed.
+    pub fn block_proposer(&mut self) -> Option<AccountOwner> {
+        self.block_proposer.expect(
+            "Block proposer has not been mocked, \
+            please call `MockContractRuntime::set_block_proposer` first",
+        )
+    }
+
     /// Configures the chain balance to return during the test.
     pub fn with_chain_balance(mut self, chain_balance: Amount) -> Self {
         self.chain_balance = Some(chain_balance);
@@ -465,6 +678,18 @@ where
         self.prepare_message(message).send_to(destination)
     }
 
+    /// Schedules the same message to be sent to this application on each of the
+    /// `destinations`.
+    ///
+    /// Use [`Self::prepare_message`] to configure the flags or a grant for all copies.
+    pub fn send_message_to_many(
+        &mut self,
+        destinations: impl IntoIterator<Item = ChainId>,
+        message: Application::Message,
+    ) {
+        self.prepare_message(message).send_to_many(destinations)
+    }
+
     /// Returns a `MessageBuilder` to prepare a message to be sent.
     pub fn prepare_message(
         &mut self,
@@ -482,10 +707,30 @@ where
             .expect("Unit test should be single-threaded")
     }
 
+    /// Returns the messages sent so far during the test, with their contents serialized using
+    /// BCS.
+    pub fn sent_messages(&self) -> Vec<SendMessageRequest<Vec<u8>>> {
+        self.created_send_message_requests()
+            .iter()
+            .map(|request| SendMessageRequest {
+                destination: request.destination,
+                authenticated: request.authenticated,
+                is_tracked: request.is_tracked,
+                grant: request.grant,
+                message: bcs::to_bytes(&request.message).expect("Failed to serialize message"),
+            })
+            .collect()
+    }
+
     /// Transfers an `amount` of native tokens from `source` owner account (or the current chain's
     /// balance) to `destination`.
     pub fn transfer(&mut self, source: AccountOwner, destination: Account, amount: Amount) {
         self.debit(source, amount);
+        self.transfers.push(Transfer {
+            source,
+            destination,
+            amount,
+        });
 
         if Some(destination.chain_id) == self.chain_id {
             self.credit(destination.owner, amount);
@@ -497,6 +742,30 @@ where
         }
     }
 
+    /// Transfers an `amount` of native tokens from `source` owner account (or the current chain's
+    /// balance) to `destination`, returning an error instead of panicking if the balance is too
+    /// low.
+    ///
+    /// The mock doesn't check if the application is allowed to access the `source` account.
+    pub fn try_transfer(
+        &mut self,
+        source: AccountOwner,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<(), TransferError> {
+        let balance = if source == AccountOwner::CHAIN {
+            *self.chain_balance_mut()
+        } else {
+            *self.owner_balance_mut(source)
+        };
+        ensure!(
+            balance >= amount,
+            TransferError::InsufficientFunding(balance)
+        );
+        self.transfer(source, destination, amount);
+        Ok(())
+    }
+
     /// Debits an `amount` of native tokens from a `source` owner account (or the current
     /// chain's balance).
     fn debit(&mut self, source: AccountOwner, amount: Amount) {
@@ -530,6 +799,81 @@ where
         &self.outgoing_transfers
     }
 
+    /// Returns all the transfers made during the test so far, in order, including the ones to
+    /// accounts on the current chain.
+    pub fn transfers(&self) -> &[Transfer] {
+        &self.transfers
+    }
+
+    /// Expects a transfer of `amount` from `source` to `destination` to be made during the
+    /// test.
+    ///
+    /// The expectations are checked by [`Self::verify_expectations`], or when the runtime is
+    /// dropped.
+    pub fn expect_transfer(
+        &mut self,
+        source: AccountOwner,
+        destination: Account,
+        amount: Amount,
+    ) -> &mut Self {
+        self.expected_transfers.push(Transfer {
+            source,
+            destination,
+            amount,
+        });
+        self
+    }
+
+    /// Expects `message` to be sent to `destination` during the test.
+    ///
+    /// The expectations are checked by [`Self::verify_expectations`], or when the runtime is
+    /// dropped.
+    pub fn expect_message(
+        &mut self,
+        destination: ChainId,
+        message: &Application::Message,
+    ) -> &mut Self {
+        let bytes = bcs::to_bytes(message).expect("Failed to serialize expected message");
+        self.expected_messages.push((destination, bytes));
+        self
+    }
+
+    /// Makes [`Self::verify_expectations`] also fail if a transfer was made or a message was
+    /// sent without being expected.
+    pub fn fail_on_unexpected_calls(&mut self) -> &mut Self {
+        self.fail_on_unexpected_calls = true;
+        self
+    }
+
+    /// Checks that all expected transfers were made and all expected messages were sent, and,
+    /// if [`Self::fail_on_unexpected_calls`] was used, that there were no others.
+    ///
+    /// Panics with the list of missing and unexpected interactions otherwise. The expectations
+    /// are cleared, so that they aren't checked again when the runtime is dropped.
+    pub fn verify_expectations(&mut self) {
+        let expected_transfers = mem::take(&mut self.expected_transfers);
+        let expected_messages = mem::take(&mut self.expected_messages);
+        let sent_messages = self
+            .sent_messages()
+            .into_iter()
+            .map(|request| (request.destination, request.message))
+            .collect::<Vec<_>>();
+        let mut report = String::new();
+        let (missing, unexpected) = diff_interactions(expected_transfers, &self.transfers);
+        report_interactions(&mut report, "Missing transfers", &missing);
+        let (missing_messages, unexpected_messages) =
+            diff_interactions(expected_messages, &sent_messages);
+        report_interactions(&mut report, "Missing messages", &missing_messages);
+        if self.fail_on_unexpected_calls {
+            report_interactions(&mut report, "Unexpected transfers", &unexpected);
+            report_interactions(&mut report, "Unexpected messages", &unexpected_messages);
+        }
+        assert!(
+            report.is_empty(),
+            "Expectations of `MockContractRuntime` are not met:\n{report}"
+        );
+    }
+
     /// Claims an `amount` of native tokens from a `source` account to a `destination` account.
     pub fn claim(&mut self, source: Account, destination: Account, amount: Amount) {
         if Some(source.chain_id) == self.chain_id {
@@ -547,6 +891,20 @@ where
         });
     }
 
+    /// Claims an `amount` of native tokens from a `source` account to a `destination` account.
+    ///
+    /// The mock doesn't check if the application is allowed to access the `source` account, so
+    /// this never fails.
+    pub fn try_claim(
+        &mut self,
+        source: Account,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<(), TransferError> {
+        self.claim(source, destination, amount);
+        Ok(())
+    }
+
     /// Returns the list of claims made during the test so far.
     pub fn claim_requests(&self) -> &[ClaimRequest] {
         &self.claim_requests
@@ -572,6 +930,72 @@ where
         )
     }
 
+    /// Configures the application permissions to return during the test.
+    pub fn with_application_permissions(
+        mut self,
+        application_permissions: ApplicationPermissions,
+    ) -> Self {
+        self.application_permissions = Some(application_permissions);
+        self
+    }
+
+    /// Configures the application permissions to return during the test.
+    pub fn set_application_permissions(
+        &mut self,
+        application_permissions: ApplicationPermissions,
+    ) -> &mut Self {
+        self.application_permissions = Some(application_permissions);
+        self
+    }
+
+    /// Retrieves the application permissions for the current chain.
+    pub fn application_permissions(&mut self) -> ApplicationPermissions {
+        self.application_permissions.clone().expect(
+            "Application permissions have not been mocked, \
+            please call `MockContractRuntime::set_application_permissions` first",
+        )
+    }
+
+    /// Configures the epoch to return during the test.
+    pub fn with_epoch(mut self, epoch: Epoch) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    /// Configures the epoch to return during the test.
+    pub fn set_epoch(&mut self, epoch: Epoch) -> &mut Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    /// Returns the current epoch of the chain.
+    pub fn epoch(&mut self) -> Epoch {
+        self.epoch.expect(
+            "Epoch has not been mocked, \
+            please call `MockContractRuntime::set_epoch` first",
+        )
+    }
+
+    /// Configures the summary of the current committee to return during the test.
+    pub fn with_current_committee(mut self, committee: CommitteeSummary) -> Self {
+        self.current_committee = Some(committee);
+        self
+    }
+
+    /// Configures the summary of the current committee to return during the test.
+    pub fn set_current_committee(&mut self, committee: CommitteeSummary) -> &mut Self {
+        self.current_committee = Some(committee);
+        self
+    }
+
+    /// Returns a summary of the committee of validators in charge of the current epoch.
+    pub fn current_committee(&mut self) -> CommitteeSummary {
+        self.current_committee.expect(
+            "Current committee has not been mocked, \
+            please call `MockContractRuntime::set_current_committee` first",
+        )
+    }
+
     /// Configures if the application being tested is allowed to close the chain its in.
     pub fn with_can_close_chain(mut self, can_close_chain: bool) -> Self {
         self.can_close_chain = Some(can_close_chain);
@@ -605,18 +1029,22 @@ where
     }
 
     /// Closes the current chain. Returns an error if the application doesn't have
-    /// permission to do so.
+    /// permission to do so, or if the chain is already closed.
     pub fn close_chain(&mut self) -> Result<(), CloseChainError> {
         let authorized = self.can_close_chain.expect(
             "Authorization to close the chain has not been mocked, \
             please call `MockContractRuntime::set_can_close_chain` first",
         );
 
-        if authorized {
-            Ok(())
-        } else {
-            Err(CloseChainError::NotPermitted)
-        }
+        ensure!(authorized, CloseChainError::NotPermitted);
+        ensure!(!self.chain_closed, CloseChainError::AlreadyClosed);
+        self.chain_closed = true;
+        Ok(())
+    }
+
+    /// Returns if the chain was closed during the test.
+    pub fn is_chain_closed(&self) -> bool {
+        self.chain_closed
     }
 
     /// Changes the application permissions on the current chain. Returns an error if the
@@ -638,6 +1066,7 @@ where
             self.can_close_chain = Some(application_permissions.can_close_chain(&application_id));
             self.can_change_application_permissions =
                 Some(application_permissions.can_change_application_permissions(&application_id));
+            self.application_permissions = Some(application_permissions);
             Ok(())
         } else {
             Err(ChangeApplicationPermissionsError::NotPermitted)
@@ -740,6 +1169,36 @@ where
         application_id.with_abi::<Abi>()
     }
 
+    /// Configures the maximum size of the data blobs the application can create.
+    pub fn with_maximum_blob_size(mut self, maximum_blob_size: u64) -> Self {
+        self.maximum_blob_size = Some(maximum_blob_size);
+        self
+    }
+
+    /// Configures the maximum size of the data blobs the application can create.
+    pub fn set_maximum_blob_size(&mut self, maximum_blob_size: u64) -> &mut Self {
+        self.maximum_blob_size = Some(maximum_blob_size);
+        self
+    }
+
+    /// Creates a new data blob with the given content and returns its hash. Returns an error
+    /// if the blob exceeds the configured maximum blob size.
+    pub fn create_data_blob(&mut self, bytes: &[u8]) -> Result<DataBlobHash, CreateDataBlobError> {
+        ensure!(
+            self.maximum_blob_size
+                .is_none_or(|maximum_blob_size| bytes.len() as u64 <= maximum_blob_size),
+            CreateDataBlobError::TooLarge
+        );
+        let hash = DataBlobHash::from_content(bytes);
+        self.created_data_blobs.insert(hash, bytes.to_vec());
+        Ok(hash)
+    }
+
+    /// Returns the data blobs created so far during the test, indexed by their hashes.
+    pub fn created_data_blobs(&self) -> &HashMap<DataBlobHash, Vec<u8>> {
+        &self.created_data_blobs
+    }
+
     /// Configures the handler for cross-application calls made during the test.
     pub fn with_call_application_handler(
         mut self,
@@ -768,43 +1227,179 @@ where
         let call_bytes = A::serialize_operation(call)
             .expect("Failed to serialize `Operation` in test runtime cross-application call");
 
+        let response_bytes =
+            self.call_application_raw(authenticated, application.forget_abi(), &call_bytes);
+
+        A::deserialize_response(response_bytes)
+            .expect("Failed to deserialize `Response` in test runtime cross-application call")
+    }
+
+    /// Calls another application with an opaque blob of bytes, returning the raw response.
+    pub fn call_application_raw(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call_bytes: &[u8],
+    ) -> Vec<u8> {
+        self.record_application_call(authenticated, application, call_bytes);
+        self.handle_application_call(authenticated, application, call_bytes)
+    }
+
+    /// Returns the cross-application calls made during the test so far, in order.
+    pub fn application_calls(&self) -> &[ApplicationCall] {
+        &self.application_calls
+    }
+
+    fn record_application_call(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call_bytes: &[u8],
+    ) {
+        self.application_calls.push(ApplicationCall {
+            authenticated,
+            application_id: application,
+            bytes: call_bytes.to_vec(),
+        });
+    }
+
+    fn handle_application_call(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call_bytes: &[u8],
+    ) -> Vec<u8> {
         let handler = self.call_application_handler.as_mut().expect(
             "Handler for `call_application` has not been mocked, \
             please call `MockContractRuntime::set_call_application_handler` first",
         );
-        let response_bytes = handler(authenticated, application.forget_abi(), call_bytes);
+        handler(authenticated, application, call_bytes.to_vec())
+    }
 
-        A::deserialize_response(response_bytes)
-            .expect("Failed to deserialize `Response` in test runtime cross-application call")
+    /// Configures the handler for cross-application calls that may fail, made during the test.
+    ///
+    /// If this isn't configured, calls to [`Self::try_call_application`] use the handler
+    /// configured with [`Self::set_call_application_handler`], and always succeed.
+    pub fn with_try_call_application_handler(
+        mut self,
+        handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Result<Vec<u8>, CalleeError> + 'static,
+    ) -> Self {
+        self.try_call_application_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Configures the handler for cross-application calls that may fail, made during the test.
+    ///
+    /// If this isn't configured, calls to [`Self::try_call_application`] use the handler
+    /// configured with [`Self::set_call_application_handler`], and always succeed.
+    pub fn set_try_call_application_handler(
+        &mut self,
+        handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Result<Vec<u8>, CalleeError> + 'static,
+    ) -> &mut Self {
+        self.try_call_application_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Calls another application, returning an error if the callee traps.
+    pub fn try_call_application<A: ContractAbi + Send>(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId<A>,
+        call: &A::Operation,
+    ) -> Result<A::Response, CalleeError> {
+        let call_bytes = A::serialize_operation(call)
+            .expect("Failed to serialize `Operation` in test runtime cross-application call");
+
+        let response_bytes =
+            self.try_call_application_raw(authenticated, application.forget_abi(), &call_bytes)?;
+
+        Ok(A::deserialize_response(response_bytes)
+            .expect("Failed to deserialize `Response` in test runtime cross-application call"))
+    }
+
+    /// Calls another application with an opaque blob of bytes, returning the raw response or an
+    /// error if the callee traps.
+    pub fn try_call_application_raw(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call_bytes: &[u8],
+    ) -> Result<Vec<u8>, CalleeError> {
+        self.record_application_call(authenticated, application, call_bytes);
+        match self.try_call_application_handler.as_mut() {
+            Some(handler) => handler(authenticated, application, call_bytes.to_vec()),
+            None => Ok(self.handle_application_call(authenticated, application, call_bytes)),
+        }
     }
 
-    /// Adds a new item to an event stream. Returns the new event's index in the stream.
-    pub fn emit(&mut self, name: StreamName, value: &Application::EventValue) -> u32 {
+    /// Adds a new item to an event stream, serialized with BCS. Returns the new event's index in
+    /// the stream.
+    pub fn emit<E: Serialize>(&mut self, name: StreamName, value: &E) -> u32 {
         let value = bcs::to_bytes(value).expect("Failed to serialize event value");
         let entry = self.created_events.entry(name).or_default();
         entry.push(value);
         entry.len() as u32 - 1
     }
 
+    /// Returns the events emitted with `emit` so far during the test, as tuples of the stream
+    /// name, the index in the stream and the value serialized with BCS, sorted by stream name and
+    /// index.
+    pub fn recorded_events(&self) -> Vec<(StreamName, u32, Vec<u8>)> {
+        self.created_events
+            .iter()
+            .flat_map(|(name, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (name.clone(), index as u32, value.clone()))
+            })
+            .collect()
+    }
+
     /// Adds an event to a stream, so that it can be read using `read_event`.
     pub fn add_event(&mut self, chain_id: ChainId, name: StreamName, index: u32, value: &[u8]) {
         self.events.insert((chain_id, name, index), value.to_vec());
     }
 
-    /// Reads an event from a stream. Returns the event's value.
+    /// Reads an event from a stream. Returns the event's raw value.
     ///
     /// Panics if the event doesn't exist.
-    pub fn read_event(
+    pub fn read_event(&mut self, chain_id: ChainId, name: StreamName, index: u32) -> Vec<u8> {
+        self.events
+            .get(&(chain_id, name, index))
+            .expect("Event not found")
+            .clone()
+    }
+
+    /// Reads an event from a stream, and deserializes its value using BCS.
+    ///
+    /// Panics if the event doesn't exist.
+    pub fn read_event_value<T: DeserializeOwned>(
         &mut self,
         chain_id: ChainId,
         name: StreamName,
         index: u32,
-    ) -> Application::EventValue {
-        let value = self
+    ) -> T {
+        let value = self.read_event(chain_id, name, index);
+        bcs::from_bytes(&value).expect("Failed to deserialize event value")
+    }
+
+    /// Returns the index the next event in a stream will have.
+    ///
+    /// This is one more than the highest index added with `add_event`, or, for the current
+    /// chain, the number of events emitted with `emit` if that is larger.
+    pub fn next_event_index(&mut self, chain_id: ChainId, name: StreamName) -> u32 {
+        let emitted = if self.chain_id == Some(chain_id) {
+            self.created_events.get(&name).map_or(0, Vec::len) as u32
+        } else {
+            0
+        };
+        let added = self
             .events
-            .get(&(chain_id, name, index))
-            .expect("Event not found");
-        bcs::from_bytes(value).expect("Failed to deserialize event value")
+            .range((chain_id, name.clone(), 0)..=(chain_id, name, u32::MAX))
+            .next_back()
+            .map_or(0, |((_, _, index), _)| index + 1);
+        emitted.max(added)
     }
 
     /// Subscribes this application to an event stream.
@@ -842,7 +1437,56 @@ where
 
     /// Adds an expected `http_request` call, and the response it should return in the test.
     pub fn add_expected_http_request(&mut self, request: http::Request, response: http::Response) {
-        self.expected_http_requests.push_back((request, response));
+        self.expected_http_requests
+            .push_back((request, Ok(response)));
+    }
+
+    /// Adds an expected `http_request_with_options` call that fails with `error` in the test,
+    /// for example because the host is not allowed.
+    pub fn add_expected_http_request_error(
+        &mut self,
+        request: http::Request,
+        error: http::RequestError,
+    ) {
+        self.expected_http_requests.push_back((request, Err(error)));
+    }
+
+    /// Configures the handler answering the HTTP requests made during the test once all the
+    /// expected requests have been made.
+    pub fn with_http_request_handler(
+        mut self,
+        handler: impl FnMut(http::Request) -> http::Response + 'static,
+    ) -> Self {
+        self.http_request_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Configures the handler answering the HTTP requests made during the test once all the
+    /// expected requests have been made.
+    pub fn set_http_request_handler(
+        &mut self,
+        handler: impl FnMut(http::Request) -> http::Response + 'static,
+    ) -> &mut Self {
+        self.http_request_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Configures a data blob that can be read during the test.
+    ///
+    /// The blob is used by the data blob methods for which no request is expected. Its
+    /// `hash` is usually computed with [`DataBlobHash::from_content`].
+    pub fn with_data_blob(mut self, hash: DataBlobHash, bytes: impl Into<Vec<u8>>) -> Self {
+        self.set_data_blob(hash, bytes);
+        self
+    }
+
+    /// Configures a data blob that can be read during the test.
+    ///
+    /// The blob is used by the data blob methods for which no request is expected. Its
+    /// `hash` is usually computed with [`DataBlobHash::from_content`].
+    pub fn set_data_blob(&mut self, hash: DataBlobHash, bytes: impl Into<Vec<u8>>) -> &mut Self {
+        self.data_blobs.insert(hash, bytes.into());
+        self
     }
 
     /// Adds an expected `read_data_blob` call, and the response it should return in the test.
@@ -851,6 +1495,25 @@ where
             .push_back((hash, response));
     }
 
+    /// Adds an expected `read_data_blob_range` call, and the response it should return in the
+    /// test.
+    pub fn add_expected_read_data_blob_range_requests(
+        &mut self,
+        hash: DataBlobHash,
+        offset: u64,
+        length: u64,
+        response: Vec<u8>,
+    ) {
+        self.expected_read_data_blob_range_requests
+            .push_back(((hash, offset, length), response));
+    }
+
+    /// Adds an expected `data_blob_size` call, and the response it should return in the test.
+    pub fn add_expected_data_blob_size_requests(&mut self, hash: DataBlobHash, response: u64) {
+        self.expected_data_blob_size_requests
+            .push_back((hash, response));
+    }
+
     /// Adds an expected `assert_data_blob_exists` call, and the response it should return in the test.
     pub fn add_expected_assert_data_blob_exists_requests(
         &mut self,
@@ -861,6 +1524,12 @@ where
             .push_back((hash, response));
     }
 
+    /// Adds an expected `data_blob_exists` call, and the response it should return in the test.
+    pub fn add_expected_data_blob_exists_requests(&mut self, hash: DataBlobHash, response: bool) {
+        self.expected_data_blob_exists_requests
+            .push_back((hash, response));
+    }
+
     /// Queries an application service as an oracle and returns the response.
     ///
     /// Should only be used with queries where it is very likely that all validators will compute
@@ -868,18 +1537,37 @@ where
     ///
     /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
     /// owner, not a super owner.
-    pub fn query_service<A: ServiceAbi + Send>(
+    ///
+    /// # Panics
+    ///
+    /// If the response can't be deserialized. Use [`Self::try_query_service`] to handle this
+    /// failure.
+    pub fn query_service<A: ServiceAbi>(
         &mut self,
         application_id: ApplicationId<A>,
-        query: A::Query,
+        query: &A::Query,
     ) -> A::QueryResponse {
+        self.try_query_service(application_id, query)
+            .unwrap_or_else(|error| panic!("Failed to query service: {error}"))
+    }
+
+    /// Queries an application service as an oracle, returning an error instead of panicking if
+    /// the response can't be deserialized.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn try_query_service<A: ServiceAbi>(
+        &mut self,
+        application_id: ApplicationId<A>,
+        query: &A::Query,
+    ) -> Result<A::QueryResponse, QueryServiceError> {
         let maybe_query = self.expected_service_queries.pop_front();
         let (expected_id, expected_query, response) =
             maybe_query.expect("Unexpected service query");
         assert_eq!(application_id.forget_abi(), expected_id);
-        let query = serde_json::to_string(&query).expect("Failed to serialize query");
+        let query = serde_json::to_string(query).expect("Failed to serialize query");
         assert_eq!(query, expected_query);
-        serde_json::from_str(&response).expect("Failed to deserialize response")
+        Ok(serde_json::from_str(&response)?)
     }
 
     /// Makes an HTTP `request` as an oracle and returns the HTTP response.
@@ -890,10 +1578,52 @@ where
     /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
     /// owner, not a super owner.
     pub fn http_request(&mut self, request: http::Request) -> http::Response {
-        let maybe_request = self.expected_http_requests.pop_front();
-        let (expected_request, response) = maybe_request.expect("Unexpected HTTP request");
-        assert_eq!(request, expected_request);
-        response
+        self.http_request_with_options(request, http::RequestOptions::default())
+            .expect("HTTP request failed")
+    }
+
+    /// Makes several HTTP `requests` as oracles and returns the HTTP responses in the same
+    /// order.
+    ///
+    /// Each request must have been added with [`Self::add_expected_http_request`], or be
+    /// answered by the handler configured with [`Self::set_http_request_handler`].
+    pub fn http_requests(&mut self, requests: Vec<http::Request>) -> Vec<http::Response> {
+        requests
+            .into_iter()
+            .map(|request| self.http_request(request))
+            .collect()
+    }
+
+    /// Makes an HTTP `request` as an oracle with the limits from `options`, and returns the HTTP
+    /// response.
+    ///
+    /// Returns the error added with [`Self::add_expected_http_request_error`], if any. The mocked
+    /// response is checked against the maximum response size, the same way validators check a
+    /// recorded response. Timeouts are never reported.
+    pub fn http_request_with_options(
+        &mut self,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<http::Response, http::RequestError> {
+        let response = match self.expected_http_requests.pop_front() {
+            Some((expected_request, response)) => {
+                assert_eq!(request, expected_request);
+                response?
+            }
+            None => {
+                let Some(handler) = self.http_request_handler.as_mut() else {
+                    panic!("Unexpected HTTP request to {}", request.url);
+                };
+                handler(request)
+            }
+        };
+        if options
+            .max_response_bytes
+            .is_some_and(|limit| response.size() > limit)
+        {
+            return Err(http::RequestError::ResponseTooLarge);
+        }
+        Ok(response)
     }
 
     /// Panics if the current time at block validation is `>= timestamp`. Note that block
@@ -905,34 +1635,159 @@ where
         assert!(self.timestamp.is_some_and(|t| t < timestamp))
     }
 
+    /// Returns whether the block in which this is called has a timestamp at or after
+    /// `timestamp`.
+    ///
+    /// This only depends on the block timestamp, not on the validators' clocks, so unlike
+    /// [`Self::assert_before`] it can be used in fast blocks.
+    pub fn has_reached(&mut self, timestamp: Timestamp) -> bool {
+        self.system_time() >= timestamp
+    }
+
+    /// Returns the time from the block timestamp until `timestamp`, or `None` if it has been
+    /// reached already.
+    ///
+    /// Like [`Self::has_reached`], this only depends on the block timestamp.
+    pub fn remaining_until(&mut self, timestamp: Timestamp) -> Option<TimeDelta> {
+        let now = self.system_time();
+        (now < timestamp).then(|| timestamp.delta_since(now))
+    }
+
     /// Reads a data blob with the given hash from storage.
     pub fn read_data_blob(&mut self, hash: &DataBlobHash) -> Vec<u8> {
-        let maybe_request = self.expected_read_data_blob_requests.pop_front();
-        let (expected_hash, response) = maybe_request.expect("Unexpected read_data_blob request");
-        assert_eq!(*hash, expected_hash);
-        response
+        match self.expected_read_data_blob_requests.pop_front() {
+            Some((expected_hash, response)) => {
+                assert_eq!(*hash, expected_hash);
+                response
+            }
+            None => self.mocked_data_blob(hash).to_vec(),
+        }
+    }
+
+    /// Reads up to `length` bytes starting at `offset` from the data blob with the given hash.
+    pub fn read_data_blob_range(
+        &mut self,
+        hash: DataBlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Vec<u8> {
+        match self.expected_read_data_blob_range_requests.pop_front() {
+            Some((expected_request, response)) => {
+                assert_eq!((hash, offset, length), expected_request);
+                response
+            }
+            None => {
+                let bytes = self.mocked_data_blob(&hash);
+                let start = usize::try_from(offset)
+                    .unwrap_or(usize::MAX)
+                    .min(bytes.len());
+                let end = start
+                    .saturating_add(usize::try_from(length).unwrap_or(usize::MAX))
+                    .min(bytes.len());
+                bytes[start..end].to_vec()
+            }
+        }
+    }
+
+    /// Returns the size in bytes of the data blob with the given hash.
+    pub fn data_blob_size(&mut self, hash: DataBlobHash) -> u64 {
+        match self.expected_data_blob_size_requests.pop_front() {
+            Some((expected_hash, response)) => {
+                assert_eq!(hash, expected_hash);
+                response
+            }
+            None => self.mocked_data_blob(&hash).len() as u64,
+        }
     }
 
     /// Asserts that a blob with the given hash exists in storage.
     pub fn assert_data_blob_exists(&mut self, hash: DataBlobHash) {
-        let maybe_request = self.expected_assert_data_blob_exists_requests.pop_front();
-        let (expected_blob_hash, response) =
-            maybe_request.expect("Unexpected assert_data_blob_exists request");
-        assert_eq!(hash, expected_blob_hash);
-        response.expect("Blob does not exist!");
+        match self.expected_assert_data_blob_exists_requests.pop_front() {
+            Some((expected_blob_hash, response)) => {
+                assert_eq!(hash, expected_blob_hash);
+                response.expect("Blob does not exist!");
+            }
+            None => {
+                self.mocked_data_blob(&hash);
+            }
+        }
+    }
+
+    /// Returns whether a blob with the given hash exists in storage.
+    pub fn data_blob_exists(&mut self, hash: DataBlobHash) -> bool {
+        match self.expected_data_blob_exists_requests.pop_front() {
+            Some((expected_blob_hash, response)) => {
+                assert_eq!(hash, expected_blob_hash);
+                response
+            }
+            None => self.data_blobs.contains_key(&hash),
+        }
+    }
+
+    /// Returns the content of a data blob configured with
+    /// [`MockContractRuntime::set_data_blob`].
+    fn mocked_data_blob(&self, hash: &DataBlobHash) -> &[u8] {
+        self.data_blobs.get(hash).unwrap_or_else(|| {
+            panic!(
+                "Data blob for hash {hash:?} has not been mocked, \
+                please call `MockContractRuntime::set_data_blob` first"
+            )
+        })
     }
 
     /// Returns the round in which this block was validated.
     pub fn validation_round(&mut self) -> Option<u32> {
         self.round
     }
+
+    /// Configures the amount of execution fuel remaining to return during the test.
+    pub fn with_remaining_fuel(mut self, remaining_fuel: u64) -> Self {
+        self.remaining_fuel = Some(remaining_fuel);
+        self
+    }
+
+    /// Configures the amount of execution fuel remaining to return during the test.
+    pub fn set_remaining_fuel(&mut self, remaining_fuel: u64) -> &mut Self {
+        self.remaining_fuel = Some(remaining_fuel);
+        self
+    }
+
+    /// Returns the amount of execution fuel remaining before execution is aborted.
+    pub fn remaining_fuel(&mut self) -> u64 {
+        self.remaining_fuel.expect(
+            "Remaining fuel has not been mocked, \
+            please call `MockContractRuntime::set_remaining_fuel` first",
+        )
+    }
 }
 
 /// A type alias for the handler for cross-application calls.
 pub type CallApplicationHandler = Box<dyn FnMut(bool, ApplicationId, Vec<u8>) -> Vec<u8>>;
 
+/// A type alias for the handler for HTTP requests.
+pub type HttpRequestHandler = Box<dyn FnMut(http::Request) -> http::Response>;
+
+/// A type alias for the handler for cross-application calls that may fail.
+pub type TryCallApplicationHandler =
+    Box<dyn FnMut(bool, ApplicationId, Vec<u8>) -> Result<Vec<u8>, CalleeError>>;
+
 /// A helper type that uses the builder pattern to configure how a message is sent, and then
 /// sends the message once it is dropped.
+///
+/// ```no_run
+/// # use linera_sdk::{linera_base_types::ChainId, Contract, ContractRuntime};
+/// fn notify<Application: Contract>(
+///     runtime: &mut ContractRuntime<Application>,
+///     message: Application::Message,
+///     subscribers: &[ChainId],
+/// ) {
+///     runtime
+///         .prepare_message(message)
+///         .with_tracking()
+///         .with_authentication()
+///         .send_to_many(subscribers.iter().copied());
+/// }
+/// ```
 #[must_use]
 pub struct MessageBuilder<Message>
 where
@@ -997,6 +1852,98 @@ where
             .expect("Unit test should be single-threaded")
             .push(request);
     }
+
+    /// Schedules this `Message` to be sent to each of the `destinations`, with the same flags.
+    ///
+    /// The message is only serialized once, and each recorded request holds a copy deserialized
+    /// from those bytes. If a grant was configured, each copy of the message forwards its own
+    /// grant.
+    pub fn send_to_many(self, destinations: impl IntoIterator<Item = ChainId>)
+    where
+        Message: DeserializeOwned,
+    {
+        let serialized_message =
+            bcs::to_bytes(&self.message).expect("Failed to serialize message to be sent");
+        let mut send_message_requests = self
+            .send_message_requests
+            .try_lock()
+            .expect("Unit test should be single-threaded");
+
+        for destination in destinations {
+            send_message_requests.push(SendMessageRequest {
+                destination,
+                authenticated: self.authenticated,
+                is_tracked: self.is_tracked,
+                grant: self.grant,
+                message: bcs::from_bytes(&serialized_message)
+                    .expect("Failed to deserialize message to be sent"),
+            });
+        }
+    }
+}
+
+impl<Application> Drop for MockContractRuntime<Application>
+where
+    Application: Contract,
+{
+    fn drop(&mut self) {
+        let has_expectations = !self.expected_transfers.is_empty()
+            || !self.expected_messages.is_empty()
+            || self.fail_on_unexpected_calls;
+        if has_expectations && !std::thread::panicking() {
+            self.verify_expectations();
+        }
+    }
+}
+
+/// Returns the `expected` interactions that were not `recorded`, and the `recorded`
+/// interactions that were not `expected`.
+fn diff_interactions<T: Clone + PartialEq>(expected: Vec<T>, recorded: &[T]) -> (Vec<T>, Vec<T>) {
+    let mut missing = expected;
+    let mut unexpected = Vec::new();
+    for interaction in recorded {
+        match missing.iter().position(|expected| expected == interaction) {
+            Some(index) => {
+                missing.remove(index);
+            }
+            None => unexpected.push(interaction.clone()),
+        }
+    }
+    (missing, unexpected)
+}
+
+/// Appends the `interactions` to the `report` under a `title`, if there are any.
+fn report_interactions<T: Debug>(report: &mut String, title: &str, interactions: &[T]) {
+    if interactions.is_empty() {
+        return;
+    }
+    report.push_str(title);
+    report.push_str(":\n");
+    for interaction in interactions {
+        report.push_str(&format!("  - {interaction:?}\n"));
+    }
+}
+
+/// A transfer of native tokens made during this test.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Transfer {
+    /// The owner of the debited account on the current chain.
+    pub source: AccountOwner,
+    /// The credited account.
+    pub destination: Account,
+    /// The amount transferred.
+    pub amount: Amount,
+}
+
+/// A call to another application made during this test.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApplicationCall {
+    /// Whether the authenticated signer was forwarded to the callee.
+    pub authenticated: bool,
+    /// The called application.
+    pub application_id: ApplicationId,
+    /// The serialized operation passed to the callee.
+    pub bytes: Vec<u8>,
 }
 
 /// A claim request that was scheduled to be sent during this test.
@@ -1006,3 +1953,554 @@ pub struct ClaimRequest {
     destination: Account,
     amount: Amount,
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use linera_base::{abi::WithContractAbi, crypto::CryptoHash};
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// A dummy contract used to instantiate the [`MockContractRuntime`].
+    struct DummyContract;
+
+    /// The ABI of the dummy contract.
+    struct DummyAbi;
+
+    impl ContractAbi for DummyAbi {
+        type Operation = ();
+        type Response = ();
+    }
+
+    impl ServiceAbi for DummyAbi {
+        type Query = String;
+        type QueryResponse = u64;
+    }
+
+    /// A service ABI whose responses can't be deserialized as the [`DummyAbi`]'s.
+    struct TextAbi;
+
+    impl ServiceAbi for TextAbi {
+        type Query = String;
+        type QueryResponse = String;
+    }
+
+    impl WithContractAbi for DummyContract {
+        type Abi = DummyAbi;
+    }
+
+    /// The message sent by the dummy contract.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    struct Ping(u64);
+
+    impl Contract for DummyContract {
+        type Message = Ping;
+        type Parameters = ();
+        type InstantiationArgument = ();
+        type EventValue = ();
+
+        async fn load(_runtime: MockContractRuntime<Self>) -> Self {
+            DummyContract
+        }
+
+        async fn instantiate(&mut self, _argument: ()) {}
+
+        async fn execute_operation(&mut self, _operation: ()) {}
+
+        async fn execute_message(&mut self, _message: Ping) {}
+
+        async fn store(self) {}
+    }
+
+    fn chain_id(name: &str) -> ChainId {
+        ChainId(CryptoHash::test_hash(name))
+    }
+
+    fn dummy_application_id() -> ApplicationId {
+        ApplicationId::new(CryptoHash::test_hash("dummy application"))
+    }
+
+    #[test]
+    fn test_close_chain_without_permission_reports_error() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new().with_can_close_chain(false);
+
+        assert_matches!(runtime.close_chain(), Err(CloseChainError::NotPermitted));
+        assert!(!runtime.is_chain_closed());
+    }
+
+    #[test]
+    fn test_close_chain_twice_reports_already_closed() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new().with_can_close_chain(true);
+
+        assert!(runtime.close_chain().is_ok());
+        assert!(runtime.is_chain_closed());
+        assert_matches!(runtime.close_chain(), Err(CloseChainError::AlreadyClosed));
+    }
+
+    #[test]
+    fn test_change_application_permissions_without_permission_reports_error() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new()
+            .with_can_change_application_permissions(false)
+            .with_application_permissions(ApplicationPermissions::default());
+
+        let new_permissions = ApplicationPermissions::new_single(dummy_application_id());
+        assert_matches!(
+            runtime.change_application_permissions(new_permissions),
+            Err(ChangeApplicationPermissionsError::NotPermitted)
+        );
+        assert_eq!(
+            runtime.application_permissions(),
+            ApplicationPermissions::default()
+        );
+    }
+
+    #[test]
+    fn test_change_application_permissions_updates_authorizations() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new()
+            .with_application_id(dummy_application_id().with_abi())
+            .with_can_change_application_permissions(true)
+            .with_can_close_chain(false);
+
+        let new_permissions = ApplicationPermissions {
+            close_chain: vec![dummy_application_id()],
+            ..ApplicationPermissions::default()
+        };
+        assert!(runtime
+            .change_application_permissions(new_permissions.clone())
+            .is_ok());
+        assert_eq!(runtime.application_permissions(), new_permissions);
+        assert!(runtime.close_chain().is_ok());
+        assert_matches!(
+            runtime.change_application_permissions(ApplicationPermissions::default()),
+            Err(ChangeApplicationPermissionsError::NotPermitted)
+        );
+    }
+
+    #[test]
+    fn test_prepared_message_has_no_flags_by_default() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+
+        runtime.send_message(chain_id("destination"), Ping(1));
+
+        assert_eq!(
+            *runtime.created_send_message_requests(),
+            vec![SendMessageRequest {
+                destination: chain_id("destination"),
+                authenticated: false,
+                is_tracked: false,
+                grant: Resources::default(),
+                message: Ping(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_prepared_message_flags_are_set() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+        let grant = Resources {
+            wasm_fuel: 10,
+            ..Resources::default()
+        };
+
+        runtime
+            .prepare_message(Ping(2))
+            .with_tracking()
+            .with_authentication()
+            .with_grant(grant)
+            .send_to(chain_id("destination"));
+
+        assert_eq!(
+            *runtime.created_send_message_requests(),
+            vec![SendMessageRequest {
+                destination: chain_id("destination"),
+                authenticated: true,
+                is_tracked: true,
+                grant,
+                message: Ping(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_prepared_message_is_sent_to_many_destinations() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+        let destinations = [chain_id("first"), chain_id("second")];
+
+        runtime
+            .prepare_message(Ping(3))
+            .with_tracking()
+            .send_to_many(destinations);
+
+        let expected_requests = destinations
+            .into_iter()
+            .map(|destination| SendMessageRequest {
+                destination,
+                authenticated: false,
+                is_tracked: true,
+                grant: Resources::default(),
+                message: Ping(3),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(*runtime.created_send_message_requests(), expected_requests);
+    }
+
+    #[test]
+    fn test_refused_http_request_can_be_handled() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+        let refused = http::Request::get("http://refused.example");
+        let allowed = http::Request::get("http://allowed.example");
+        runtime.add_expected_http_request_error(refused.clone(), http::RequestError::NotAllowed);
+        runtime.add_expected_http_request(allowed.clone(), http::Response::ok(b"ok".to_vec()));
+
+        let options = http::RequestOptions::default();
+        assert_eq!(
+            runtime.http_request_with_options(refused, options),
+            Err(http::RequestError::NotAllowed)
+        );
+        assert_eq!(
+            runtime.http_request_with_options(allowed, options),
+            Ok(http::Response::ok(b"ok".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_query_service() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+        let application_id = dummy_application_id().with_abi::<DummyAbi>();
+        runtime.add_expected_service_query(application_id, "value".to_owned(), 42);
+
+        assert_eq!(
+            runtime.query_service(application_id, &"value".to_owned()),
+            42
+        );
+    }
+
+    #[test]
+    fn test_try_query_service_with_invalid_response() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+        let application_id = dummy_application_id();
+        runtime.add_expected_service_query(
+            application_id.with_abi::<TextAbi>(),
+            "value".to_owned(),
+            "not a number".to_owned(),
+        );
+
+        assert_matches!(
+            runtime.try_query_service(application_id.with_abi::<DummyAbi>(), &"value".to_owned()),
+            Err(QueryServiceError::ResponseDeserialization(_))
+        );
+    }
+
+    #[test]
+    fn test_time_window_before_deadline() {
+        let deadline = Timestamp::from(1_000);
+        let mut runtime =
+            MockContractRuntime::<DummyContract>::new().with_system_time(Timestamp::from(400));
+
+        assert!(!runtime.has_reached(deadline));
+        assert_eq!(
+            runtime.remaining_until(deadline),
+            Some(TimeDelta::from_micros(600))
+        );
+        runtime.assert_before(deadline);
+    }
+
+    #[test]
+    fn test_time_window_at_deadline() {
+        let deadline = Timestamp::from(1_000);
+        let mut runtime = MockContractRuntime::<DummyContract>::new().with_system_time(deadline);
+
+        assert!(runtime.has_reached(deadline));
+        assert_eq!(runtime.remaining_until(deadline), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_before_fails_at_deadline() {
+        let deadline = Timestamp::from(1_000);
+        let mut runtime = MockContractRuntime::<DummyContract>::new().with_system_time(deadline);
+
+        runtime.assert_before(deadline);
+    }
+
+    #[test]
+    fn test_create_data_blob() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new().with_maximum_blob_size(4);
+
+        let hash = runtime.create_data_blob(b"blob").unwrap();
+
+        assert_eq!(
+            hash,
+            DataBlobHash(Blob::new_data(b"blob".to_vec()).id().hash)
+        );
+        assert_eq!(hash, DataBlobHash::from_content(b"blob"));
+        assert_eq!(runtime.created_data_blobs()[&hash], b"blob");
+        assert_matches!(
+            runtime.create_data_blob(b"too large"),
+            Err(CreateDataBlobError::TooLarge)
+        );
+        assert_eq!(runtime.created_data_blobs().len(), 1);
+    }
+
+    #[test]
+    fn test_set_data_blob() {
+        let hash = DataBlobHash::from_content(b"content");
+        let missing = DataBlobHash::from_content(b"missing");
+        let mut runtime =
+            MockContractRuntime::<DummyContract>::new().with_data_blob(hash, b"content");
+
+        runtime.assert_data_blob_exists(hash);
+        assert!(runtime.data_blob_exists(hash));
+        assert!(!runtime.data_blob_exists(missing));
+        assert_eq!(runtime.read_data_blob(&hash), b"content");
+        assert_eq!(runtime.read_data_blob_range(hash, 3, 100), b"tent");
+        assert_eq!(runtime.data_blob_size(hash), 7);
+
+        runtime.add_expected_read_data_blob_requests(hash, b"expected".to_vec());
+        assert_eq!(runtime.read_data_blob(&hash), b"expected");
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been mocked")]
+    fn test_read_data_blob_panics_for_missing_blob() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+
+        runtime.read_data_blob(&DataBlobHash::from_content(b"missing"));
+    }
+
+    #[test]
+    fn test_recorded_events() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new();
+
+        assert_eq!(runtime.emit(StreamName::from("b"), &1u8), 0);
+        assert_eq!(runtime.emit(StreamName::from("a"), &2u8), 0);
+        assert_eq!(runtime.emit(StreamName::from("b"), &3u8), 1);
+
+        assert_eq!(
+            runtime.recorded_events(),
+            vec![
+                (StreamName::from("a"), 0, vec![2]),
+                (StreamName::from("b"), 0, vec![1]),
+                (StreamName::from("b"), 1, vec![3]),
+            ]
+        );
+    }
+
+    /// Returns a runtime on a test chain with some tokens in the chain balance, and an account on
+    /// another chain.
+    fn runtime_with_balance() -> (MockContractRuntime<DummyContract>, Account) {
+        let runtime = MockContractRuntime::<DummyContract>::new()
+            .with_chain_id(chain_id("chain"))
+            .with_chain_balance(Amount::from_tokens(10));
+        let recipient = Account {
+            chain_id: chain_id("other chain"),
+            owner: AccountOwner::from(CryptoHash::test_hash("recipient")),
+        };
+        (runtime, recipient)
+    }
+
+    #[test]
+    fn test_recorded_interactions() {
+        let (mut runtime, recipient) = runtime_with_balance();
+        let application_id = dummy_application_id();
+        runtime.set_call_application_handler(|_, _, argument| argument);
+
+        runtime.transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(1));
+        runtime.send_message(recipient.chain_id, Ping(7));
+        runtime.call_application_raw(true, application_id, b"call");
+        runtime.try_call_application_raw(false, application_id, b"try");
+
+        assert_eq!(
+            runtime.transfers(),
+            [Transfer {
+                source: AccountOwner::CHAIN,
+                destination: recipient,
+                amount: Amount::from_tokens(1),
+            }]
+        );
+        assert_eq!(
+            runtime.sent_messages(),
+            vec![SendMessageRequest {
+                destination: recipient.chain_id,
+                authenticated: false,
+                is_tracked: false,
+                grant: Resources::default(),
+                message: bcs::to_bytes(&Ping(7)).unwrap(),
+            }]
+        );
+        assert_eq!(
+            runtime.application_calls(),
+            [
+                ApplicationCall {
+                    authenticated: true,
+                    application_id,
+                    bytes: b"call".to_vec(),
+                },
+                ApplicationCall {
+                    authenticated: false,
+                    application_id,
+                    bytes: b"try".to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expectations_are_met() {
+        let (mut runtime, recipient) = runtime_with_balance();
+        runtime
+            .expect_transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(2))
+            .expect_message(recipient.chain_id, &Ping(1))
+            .fail_on_unexpected_calls();
+
+        runtime.send_message(recipient.chain_id, Ping(1));
+        runtime.transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(2));
+
+        runtime.verify_expectations();
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing transfers")]
+    fn test_unmet_transfer_expectation_fails_on_drop() {
+        let (mut runtime, recipient) = runtime_with_balance();
+        runtime.expect_transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(2));
+
+        runtime.transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected messages")]
+    fn test_unexpected_message_fails_in_strict_mode() {
+        let (mut runtime, recipient) = runtime_with_balance();
+        runtime.fail_on_unexpected_calls();
+
+        runtime.send_message(recipient.chain_id, Ping(1));
+
+        runtime.verify_expectations();
+    }
+
+    #[test]
+    fn test_unexpected_calls_are_allowed_by_default() {
+        let (mut runtime, recipient) = runtime_with_balance();
+        runtime.expect_transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(1));
+
+        runtime.transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(1));
+        runtime.transfer(AccountOwner::CHAIN, recipient, Amount::from_tokens(1));
+        runtime.send_message(recipient.chain_id, Ping(1));
+
+        runtime.verify_expectations();
+    }
+
+    #[test]
+    fn test_require_authenticated_signer() {
+        let owner = AccountOwner::from(CryptoHash::test_hash("owner"));
+        let mut runtime =
+            MockContractRuntime::<DummyContract>::new().with_authenticated_signer(owner);
+
+        assert_eq!(runtime.require_authenticated_signer(), Ok(owner));
+
+        runtime.set_authenticated_signer(None);
+        assert_eq!(runtime.require_authenticated_signer(), Err(Unauthorized));
+    }
+
+    #[test]
+    fn test_is_called_by() {
+        let caller_id = dummy_application_id();
+        let other_id = ApplicationId::new(CryptoHash::test_hash("other application"));
+        let mut runtime =
+            MockContractRuntime::<DummyContract>::new().with_authenticated_caller_id(caller_id);
+
+        assert!(runtime.is_called_by(caller_id));
+        assert!(!runtime.is_called_by(other_id));
+
+        runtime.set_authenticated_caller_id(None);
+        assert!(!runtime.is_called_by(caller_id));
+    }
+
+    #[test]
+    fn test_try_call_application_falls_back_to_call_application_handler() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new()
+            .with_call_application_handler(|_, _, argument| argument);
+
+        assert_matches!(
+            runtime.try_call_application_raw(false, dummy_application_id(), b"call"),
+            Ok(response) if response == b"call"
+        );
+    }
+
+    #[test]
+    fn test_try_call_application_reports_trapped_callee() {
+        let mut runtime = MockContractRuntime::<DummyContract>::new()
+            .with_try_call_application_handler(|_, _, _| Err(CalleeError::Trapped));
+
+        assert_matches!(
+            runtime.try_call_application(false, dummy_application_id().with_abi::<DummyAbi>(), &()),
+            Err(CalleeError::Trapped)
+        );
+    }
+
+    #[test]
+    fn test_round_and_block_proposer() {
+        let proposer = AccountOwner::from(CryptoHash::test_hash("proposer"));
+        let mut runtime = MockContractRuntime::<DummyContract>::new()
+            .with_consensus_round(Round::MultiLeader(1))
+            .with_block_proposer(proposer);
+
+        assert_eq!(runtime.round(), Some(Round::MultiLeader(1)));
+        assert_eq!(runtime.block_proposer(), Some(proposer));
+
+        runtime.set_consensus_round(None).set_block_proposer(None);
+        assert_eq!(runtime.round(), None);
+        assert_eq!(runtime.block_proposer(), None);
+    }
+
+    #[test]
+    fn test_message_context_helpers() {
+        let owner = AccountOwner::from(CryptoHash::test_hash("owner"));
+        let message_id = MessageId {
+            chain_id: chain_id("origin"),
+            height: BlockHeight(3),
+            index: 1,
+        };
+        let mut runtime = MockContractRuntime::<DummyContract>::new()
+            .with_chain_id(chain_id("current"))
+            .with_authenticated_signer(owner)
+            .with_message_id(message_id)
+            .with_message_is_bouncing(false);
+
+        assert_eq!(
+            runtime.message_origin(),
+            Some((chain_id("origin"), BlockHeight(3), 1))
+        );
+        assert!(!runtime.is_bouncing());
+        assert_eq!(
+            runtime.refund_recipient(),
+            Some(Account {
+                chain_id: chain_id("origin"),
+                owner,
+            })
+        );
+
+        runtime.set_message_is_bouncing(true);
+        assert!(runtime.is_bouncing());
+        assert_eq!(
+            runtime.refund_recipient(),
+            Some(Account {
+                chain_id: chain_id("current"),
+                owner,
+            })
+        );
+
+        runtime.set_authenticated_signer(None);
+        assert_eq!(runtime.refund_recipient(), None);
+
+        runtime
+            .set_message_id(None)
+            .set_message_is_bouncing(None)
+            .set_authenticated_signer(owner);
+        assert_eq!(runtime.message_origin(), None);
+        assert!(!runtime.is_bouncing());
+        assert_eq!(runtime.refund_recipient(), None);
+    }
+}
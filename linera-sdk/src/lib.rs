@@ -38,6 +38,8 @@ mod extensions;
 pub mod graphql;
 pub mod linera_base_types;
 mod log;
+#[cfg(all(feature = "schema", not(target_arch = "wasm32")))]
+pub mod schema;
 pub mod service;
 #[cfg(with_testing)]
 pub mod test;
@@ -54,7 +56,7 @@ pub use linera_base::{
 use linera_base::{
     abi::{ContractAbi, ServiceAbi, WithContractAbi, WithServiceAbi},
     crypto::CryptoHash,
-    data_types::StreamUpdate,
+    data_types::{BlobContent, StreamUpdate},
     doc_scalar,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -67,13 +69,21 @@ pub use self::{
     extensions::{FromBcsBytes, ToBcsBytes},
     log::{ContractLogger, ServiceLogger},
     service::ServiceRuntime,
-    views::{KeyValueStore, ViewStorageContext},
+    views::{KeyValueStore, ViewStorageContext, ViewStorageExtra},
 };
 
 /// Hash of a data blob.
 #[derive(Eq, Hash, PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct DataBlobHash(pub CryptoHash);
 
+impl DataBlobHash {
+    /// Returns the hash of a data blob with the given `content`, as computed by the
+    /// validators when the blob is published.
+    pub fn from_content(content: &[u8]) -> Self {
+        DataBlobHash(BlobContent::hash_data(content))
+    }
+}
+
 doc_scalar!(DataBlobHash, "Hash of a Data Blob");
 
 /// The contract interface of a Linera application.
@@ -0,0 +1,42 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Registries of the types that applications exchange in BCS, to generate their
+//! (de)serialization code for clients in other languages.
+//!
+//! Use [`Abi::type_registry`](crate::abi::Abi::type_registry) to obtain the registry of an
+//! application, and [`assert_registry_file`] in a test to keep a copy of it up to date in the
+//! application's repository.
+
+use std::{env, path::Path};
+
+pub use linera_base::schema::*;
+
+/// The environment variable that makes [`assert_registry_file`] overwrite outdated files.
+pub const UPDATE_SCHEMA_VARIABLE: &str = "LINERA_UPDATE_SCHEMA";
+
+/// Asserts that the file at `path` contains the `registry`, written in JSON if the file has a
+/// `.json` extension, and in YAML otherwise.
+///
+/// The file is written instead if it doesn't exist yet, or if the
+/// [`LINERA_UPDATE_SCHEMA`](UPDATE_SCHEMA_VARIABLE) environment variable is set.
+pub fn assert_registry_file(registry: &Registry, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let format = match path.extension() {
+        Some(extension) if extension == "json" => RegistryFormat::Json,
+        _ => RegistryFormat::Yaml,
+    };
+    let mut contents = Vec::new();
+    write_registry(registry, format, &mut contents).expect("Failed to serialize the registry");
+    if env::var_os(UPDATE_SCHEMA_VARIABLE).is_some() || !path.exists() {
+        std::fs::write(path, contents).expect("Failed to write the registry");
+        return;
+    }
+    let existing = std::fs::read(path).expect("Failed to read the registry file");
+    assert!(
+        existing == contents,
+        "The type registry in {} is outdated. Run the tests with {UPDATE_SCHEMA_VARIABLE}=1 to \
+        update it, and regenerate the client code from it.",
+        path.display()
+    );
+}
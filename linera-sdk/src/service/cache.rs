@@ -0,0 +1,55 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caching of the values that a service fetches from the host.
+
+use std::sync::Mutex;
+
+/// Loads a value from the `slot` cache or fetches it and stores it in the cache.
+pub(crate) fn fetch_value_through_cache<T>(slot: &Mutex<Option<T>>, fetch: impl FnOnce() -> T) -> T
+where
+    T: Clone,
+{
+    let mut value = slot
+        .lock()
+        .expect("Mutex should never be poisoned because service runs in a single thread");
+
+    if value.is_none() {
+        *value = Some(fetch());
+    }
+
+    value.clone().expect("Value should be populated above")
+}
+
+/// Clears the `slot` cache, so that its value is fetched again when it's next needed.
+pub(crate) fn clear_cache<T>(slot: &Mutex<Option<T>>) {
+    slot.lock()
+        .expect("Mutex should never be poisoned because service runs in a single thread")
+        .take();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, sync::Mutex};
+
+    use super::{clear_cache, fetch_value_through_cache};
+
+    #[test]
+    fn test_cached_value_is_fetched_again_after_clearing() {
+        let slot = Mutex::new(None);
+        let fetches = Cell::new(0);
+        let fetch = || {
+            fetches.set(fetches.get() + 1);
+            fetches.get()
+        };
+
+        assert_eq!(fetch_value_through_cache(&slot, fetch), 1);
+        assert_eq!(fetch_value_through_cache(&slot, fetch), 1);
+        assert_eq!(fetches.get(), 1);
+
+        clear_cache(&slot);
+        assert_eq!(fetch_value_through_cache(&slot, fetch), 2);
+        assert_eq!(fetch_value_through_cache(&slot, fetch), 2);
+        assert_eq!(fetches.get(), 2);
+    }
+}
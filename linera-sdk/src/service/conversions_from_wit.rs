@@ -0,0 +1,127 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversions from types generated by [`wit-bindgen`] to types declared in [`linera-sdk`].
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{
+        ApplicationDescriptionSummary, ApplicationQueryError, BlockHeaderSummary, BlockHeight,
+        Epoch, Timestamp,
+    },
+    http,
+    identifiers::{AccountOwner, ApplicationId, ChainId},
+};
+
+use super::wit::service_runtime_api as wit_service_api;
+
+impl From<wit_service_api::ApplicationQueryError> for ApplicationQueryError {
+    fn from(guest: wit_service_api::ApplicationQueryError) -> Self {
+        match guest {
+            wit_service_api::ApplicationQueryError::Trapped => ApplicationQueryError::Trapped,
+            wit_service_api::ApplicationQueryError::NotFound => ApplicationQueryError::NotFound,
+        }
+    }
+}
+
+impl From<wit_service_api::HttpRequestError> for http::RequestError {
+    fn from(error: wit_service_api::HttpRequestError) -> Self {
+        match error {
+            wit_service_api::HttpRequestError::Timeout => http::RequestError::Timeout,
+            wit_service_api::HttpRequestError::ResponseTooLarge => {
+                http::RequestError::ResponseTooLarge
+            }
+            wit_service_api::HttpRequestError::NotAllowed => http::RequestError::NotAllowed,
+        }
+    }
+}
+
+impl From<wit_service_api::HttpResponse> for http::Response {
+    fn from(response: wit_service_api::HttpResponse) -> http::Response {
+        http::Response {
+            status: response.status,
+            headers: response
+                .headers
+                .into_iter()
+                .map(http::Header::from)
+                .collect(),
+            body: response.body,
+        }
+    }
+}
+
+impl From<wit_service_api::HttpHeader> for http::Header {
+    fn from(header: wit_service_api::HttpHeader) -> http::Header {
+        http::Header::new(header.name, header.value)
+    }
+}
+
+impl From<wit_service_api::CryptoHash> for CryptoHash {
+    fn from(hash_value: wit_service_api::CryptoHash) -> Self {
+        CryptoHash::from([
+            hash_value.part1,
+            hash_value.part2,
+            hash_value.part3,
+            hash_value.part4,
+        ])
+    }
+}
+
+impl From<wit_service_api::Array20> for [u8; 20] {
+    fn from(ethereum_address: wit_service_api::Array20) -> Self {
+        let mut bytes = [0u8; 20];
+        bytes[0..8].copy_from_slice(&ethereum_address.part1.to_le_bytes());
+        bytes[8..16].copy_from_slice(&ethereum_address.part2.to_le_bytes());
+        bytes[16..20].copy_from_slice(&ethereum_address.part3.to_le_bytes()[0..4]);
+        bytes
+    }
+}
+
+impl From<wit_service_api::AccountOwner> for AccountOwner {
+    fn from(account_owner: wit_service_api::AccountOwner) -> Self {
+        match account_owner {
+            wit_service_api::AccountOwner::Reserved(value) => AccountOwner::Reserved(value),
+            wit_service_api::AccountOwner::Address32(value) => {
+                AccountOwner::Address32(value.into())
+            }
+            wit_service_api::AccountOwner::Address20(value) => {
+                AccountOwner::Address20(value.into())
+            }
+        }
+    }
+}
+
+impl From<wit_service_api::BlockHeaderSummary> for BlockHeaderSummary {
+    fn from(header: wit_service_api::BlockHeaderSummary) -> Self {
+        BlockHeaderSummary {
+            height: BlockHeight(header.height.inner0),
+            timestamp: Timestamp::from(header.timestamp.inner0),
+            epoch: Epoch(header.epoch.inner0),
+            authenticated_signer: header.authenticated_signer.map(AccountOwner::from),
+        }
+    }
+}
+
+impl From<wit_service_api::ApplicationId> for ApplicationId {
+    fn from(application_id: wit_service_api::ApplicationId) -> Self {
+        ApplicationId::new(application_id.application_description_hash.into())
+    }
+}
+
+impl From<wit_service_api::ChainId> for ChainId {
+    fn from(chain_id: wit_service_api::ChainId) -> Self {
+        ChainId(chain_id.inner0.into())
+    }
+}
+
+impl From<wit_service_api::ApplicationDescriptionSummary> for ApplicationDescriptionSummary {
+    fn from(summary: wit_service_api::ApplicationDescriptionSummary) -> Self {
+        ApplicationDescriptionSummary {
+            application_id: summary.application_id.into(),
+            contract_blob_hash: summary.contract_blob_hash.into(),
+            service_blob_hash: summary.service_blob_hash.into(),
+            creator_chain_id: summary.creator_chain_id.into(),
+            parameters: summary.parameters,
+        }
+    }
+}
@@ -3,7 +3,12 @@
 
 //! Conversions from types declared in [`linera-sdk`] to types generated by [`wit-bindgen`].
 
-use linera_base::{crypto::CryptoHash, identifiers::ApplicationId};
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::BlockHeight,
+    http,
+    identifiers::{ApplicationId, ChainId, StreamName},
+};
 
 use super::wit::service_runtime_api as wit_service_api;
 
@@ -27,3 +32,76 @@ impl From<ApplicationId> for wit_service_api::ApplicationId {
         }
     }
 }
+
+impl From<ChainId> for wit_service_api::ChainId {
+    fn from(chain_id: ChainId) -> Self {
+        wit_service_api::ChainId {
+            inner0: chain_id.0.into(),
+        }
+    }
+}
+
+impl From<BlockHeight> for wit_service_api::BlockHeight {
+    fn from(block_height: BlockHeight) -> Self {
+        wit_service_api::BlockHeight {
+            inner0: block_height.0,
+        }
+    }
+}
+
+impl From<StreamName> for wit_service_api::StreamName {
+    fn from(name: StreamName) -> Self {
+        wit_service_api::StreamName {
+            inner0: name.into_bytes(),
+        }
+    }
+}
+
+impl From<http::Request> for wit_service_api::HttpRequest {
+    fn from(request: http::Request) -> Self {
+        wit_service_api::HttpRequest {
+            method: request.method.into(),
+            url: request.url,
+            headers: request
+                .headers
+                .into_iter()
+                .map(http::Header::into)
+                .collect(),
+            body: request.body,
+        }
+    }
+}
+
+impl From<http::Method> for wit_service_api::HttpMethod {
+    fn from(method: http::Method) -> Self {
+        match method {
+            http::Method::Get => wit_service_api::HttpMethod::Get,
+            http::Method::Post => wit_service_api::HttpMethod::Post,
+            http::Method::Put => wit_service_api::HttpMethod::Put,
+            http::Method::Delete => wit_service_api::HttpMethod::Delete,
+            http::Method::Head => wit_service_api::HttpMethod::Head,
+            http::Method::Options => wit_service_api::HttpMethod::Options,
+            http::Method::Connect => wit_service_api::HttpMethod::Connect,
+            http::Method::Patch => wit_service_api::HttpMethod::Patch,
+            http::Method::Trace => wit_service_api::HttpMethod::Trace,
+        }
+    }
+}
+
+impl From<http::Header> for wit_service_api::HttpHeader {
+    fn from(header: http::Header) -> Self {
+        wit_service_api::HttpHeader {
+            name: header.name,
+            value: header.value,
+        }
+    }
+}
+
+impl From<http::RequestOptions> for wit_service_api::HttpRequestOptions {
+    fn from(options: http::RequestOptions) -> Self {
+        wit_service_api::HttpRequestOptions {
+            timeout_ms: options.timeout_ms,
+            max_response_bytes: options.max_response_bytes,
+        }
+    }
+}
@@ -3,6 +3,8 @@
 
 //! Types and macros useful for writing an application service.
 
+mod cache;
+mod conversions_from_wit;
 mod conversions_to_wit;
 #[cfg(not(with_testing))]
 mod runtime;
@@ -11,10 +13,16 @@ mod test_runtime;
 #[doc(hidden)]
 pub mod wit;
 
+use linera_base::data_types::ApplicationQueryError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
 #[cfg(not(with_testing))]
 pub use self::runtime::ServiceRuntime;
 #[cfg(with_testing)]
 pub use self::test_runtime::MockServiceRuntime;
+#[cfg(with_testing)]
+pub(crate) use self::test_runtime::ScheduledOperations;
 #[doc(hidden)]
 pub use self::wit::export_service;
 use crate::util::BlockingWait as _;
@@ -23,6 +31,41 @@ use crate::util::BlockingWait as _;
 #[cfg(with_testing)]
 pub type ServiceRuntime<Application> = MockServiceRuntime<Application>;
 
+/// Errors that can happen when a service queries another application.
+#[derive(Debug, Error)]
+pub enum QueryApplicationError {
+    /// The queried application trapped while handling the query.
+    #[error("Queried application trapped while handling the query")]
+    ApplicationTrapped,
+    /// The queried application is not registered on the current chain.
+    #[error("Queried application was not found on the current chain")]
+    ApplicationNotFound,
+    /// The response of the queried application could not be deserialized.
+    #[error("Failed to deserialize query response from application: {0}")]
+    ResponseDeserialization(#[from] serde_json::Error),
+}
+
+/// The position of a scheduled operation among the operations scheduled by the current query.
+///
+/// Indices are assigned by the host in scheduling order, starting at zero, and are shared by all
+/// the applications taking part in the query: if this service queries another application that
+/// also schedules operations, those operations take up indices too. The index is therefore a
+/// handle to correlate a scheduled operation with the query's response, not the operation's
+/// final position in a block. Once the query finishes, its scheduled operations are appended to
+/// the block being built after any operations already in it, including those scheduled by other
+/// queries, so their offset in the block may differ.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct OperationIndex(pub u32);
+
+impl From<ApplicationQueryError> for QueryApplicationError {
+    fn from(error: ApplicationQueryError) -> Self {
+        match error {
+            ApplicationQueryError::Trapped => QueryApplicationError::ApplicationTrapped,
+            ApplicationQueryError::NotFound => QueryApplicationError::ApplicationNotFound,
+        }
+    }
+}
+
 /// Declares an implementation of the [`Service`][`crate::Service`] trait, exporting it from the
 /// Wasm module.
 ///
@@ -3,32 +3,65 @@
 
 //! Runtime types to interface with the host executing the service.
 
-use std::sync::Mutex;
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{Mutex, MutexGuard},
+};
 
 use linera_base::{
     abi::ServiceAbi,
-    data_types::{Amount, BlockHeight, Timestamp},
+    crypto::CryptoHash,
+    data_types::{
+        Amount, ApplicationDescriptionSummary, ApplicationPermissions, ApplicationQueryError,
+        BlockHeaderSummary, BlockHeight, CommitteeSummary, Epoch, Round, Timestamp,
+    },
     http,
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, StreamName},
+    ownership::ChainOwnership,
 };
-use serde::Serialize;
+use linera_views::store::ReadableKeyValueStore as _;
+use serde::{de::DeserializeOwned, Serialize};
 
-use super::wit::{base_runtime_api as base_wit, service_runtime_api as service_wit};
-use crate::{DataBlobHash, KeyValueStore, Service, ViewStorageContext};
+use super::{
+    cache::{clear_cache, fetch_value_through_cache},
+    wit::{base_runtime_api as base_wit, service_runtime_api as service_wit},
+    OperationIndex, QueryApplicationError,
+};
+use crate::{
+    util::BlockingWait as _, views::CacheStatistics, DataBlobHash, KeyValueStore, Service,
+    ViewStorageContext, ViewStorageExtra,
+};
 
 /// The runtime available during execution of a query.
+///
+/// Values fetched from the host are cached for the lifetime of the runtime. The application
+/// parameters, the application ID, its creator chain ID and the chain ID never change, but the
+/// other cached values describe the chain state and may become stale if the runtime is kept
+/// across queries. Use [`ServiceRuntime::refresh`] or one of the per-value `refresh_*` methods
+/// to fetch them again.
 pub struct ServiceRuntime<Application>
 where
     Application: Service,
 {
+    // Immutable values.
     application_parameters: Mutex<Option<Application::Parameters>>,
     application_id: Mutex<Option<ApplicationId<Application::Abi>>>,
+    application_creator_chain_id: Mutex<Option<ChainId>>,
     chain_id: Mutex<Option<ChainId>>,
+    // Values that may change between queries.
     next_block_height: Mutex<Option<BlockHeight>>,
     timestamp: Mutex<Option<Timestamp>>,
+    epoch: Mutex<Option<Epoch>>,
+    current_committee: Mutex<Option<CommitteeSummary>>,
+    chain_ownership: Mutex<Option<ChainOwnership>>,
+    application_permissions: Mutex<Option<ApplicationPermissions>>,
     chain_balance: Mutex<Option<Amount>>,
     owner_balances: Mutex<Option<Vec<(AccountOwner, Amount)>>>,
+    balances_by_owner: Mutex<HashMap<AccountOwner, Amount>>,
     balance_owners: Mutex<Option<Vec<AccountOwner>>>,
+    // Storage values kept across queries.
+    cached_key_value_store: Mutex<Option<KeyValueStore>>,
 }
 
 impl<Application> ServiceRuntime<Application>
@@ -40,12 +73,19 @@ where
         ServiceRuntime {
             application_parameters: Mutex::new(None),
             application_id: Mutex::new(None),
+            application_creator_chain_id: Mutex::new(None),
             chain_id: Mutex::new(None),
             next_block_height: Mutex::new(None),
             timestamp: Mutex::new(None),
+            epoch: Mutex::new(None),
+            current_committee: Mutex::new(None),
+            chain_ownership: Mutex::new(None),
+            application_permissions: Mutex::new(None),
             chain_balance: Mutex::new(None),
             owner_balances: Mutex::new(None),
+            balances_by_owner: Mutex::new(HashMap::new()),
             balance_owners: Mutex::new(None),
+            cached_key_value_store: Mutex::new(None),
         }
     }
 
@@ -56,7 +96,91 @@ where
 
     /// Returns a storage context suitable for a root view.
     pub fn root_view_storage_context(&self) -> ViewStorageContext {
-        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), ())
+        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), self.storage_extra())
+    }
+
+    /// Returns a storage context suitable for a root view, which keeps the last
+    /// `max_entries` values read from storage across queries.
+    ///
+    /// The cached values are discarded when a new block is added to the chain. This also
+    /// refreshes the cached [`ServiceRuntime::next_block_height`].
+    pub fn root_view_storage_context_cached(&self, max_entries: usize) -> ViewStorageContext {
+        self.refresh_next_block_height();
+        let next_block_height = self.next_block_height();
+        let mut store = self
+            .cached_key_value_store
+            .lock()
+            .expect("Mutex should never be poisoned because service runs in a single thread");
+        let store = store.get_or_insert_with(|| KeyValueStore::for_services_cached(max_entries));
+        store.set_cache_block_height(next_block_height, max_entries);
+        ViewStorageContext::new_unsafe(store.clone(), Vec::new(), self.storage_extra())
+    }
+
+    /// Returns the extra data of the storage contexts.
+    fn storage_extra(&self) -> ViewStorageExtra {
+        ViewStorageExtra {
+            block_height: self.next_block_height(),
+            timestamp: self.system_time(),
+        }
+    }
+
+    /// Returns the hit and miss counts of the cache used by
+    /// [`ServiceRuntime::root_view_storage_context_cached`], to help choosing its size.
+    pub fn storage_cache_statistics(&self) -> Option<CacheStatistics> {
+        self.cached_key_value_store
+            .lock()
+            .expect("Mutex should never be poisoned because service runs in a single thread")
+            .as_ref()?
+            .cache_statistics()
+    }
+
+    /// Reads the value stored under `key` in this application's storage, if any.
+    ///
+    /// # Panics
+    ///
+    /// If the `key` is longer than the maximum key size.
+    pub fn read_value_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.key_value_store()
+            .read_value_bytes(key)
+            .blocking_wait()
+            .expect("Failed to read from the key-value store")
+    }
+
+    /// Reads the value stored under `key` in this application's storage, if any, and
+    /// deserializes it using BCS.
+    ///
+    /// # Panics
+    ///
+    /// If the `key` is longer than the maximum key size, or if the value can't be deserialized.
+    pub fn read_value<T: DeserializeOwned>(&self, key: &[u8]) -> Option<T> {
+        self.read_value_bytes(key).map(|bytes| {
+            bcs::from_bytes(&bytes).expect("Failed to deserialize value from the key-value store")
+        })
+    }
+
+    /// Returns `true` if a value is stored under `key` in this application's storage.
+    ///
+    /// # Panics
+    ///
+    /// If the `key` is longer than the maximum key size.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.key_value_store()
+            .contains_key(key)
+            .blocking_wait()
+            .expect("Failed to read from the key-value store")
+    }
+
+    /// Returns the keys in this application's storage that start with `prefix`, with the prefix
+    /// removed.
+    ///
+    /// # Panics
+    ///
+    /// If the `prefix` is longer than the maximum key size.
+    pub fn find_keys_by_prefix(&self, prefix: &[u8]) -> Vec<Vec<u8>> {
+        self.key_value_store()
+            .find_keys_by_prefix(prefix)
+            .blocking_wait()
+            .expect("Failed to read from the key-value store")
     }
 }
 
@@ -65,64 +189,213 @@ where
     Application: Service,
 {
     /// Returns the application parameters provided when the application was created.
+    ///
+    /// Panics if the parameters can't be deserialized, see
+    /// [`ServiceRuntime::try_application_parameters`] for a non-panicking alternative.
     pub fn application_parameters(&self) -> Application::Parameters {
-        Self::fetch_value_through_cache(&self.application_parameters, || {
-            let bytes = base_wit::application_parameters();
-            serde_json::from_slice(&bytes).expect("Application parameters must be deserializable")
+        self.try_application_parameters().unwrap_or_else(|error| {
+            panic!(
+                "Failed to deserialize application parameters {:?}: {error}",
+                String::from_utf8_lossy(&self.application_parameters_bytes()),
+            )
         })
     }
 
+    /// Returns the application parameters provided when the application was created, or the
+    /// error encountered while deserializing them.
+    ///
+    /// The parameters are cached only if they are deserialized successfully.
+    pub fn try_application_parameters(&self) -> Result<Application::Parameters, serde_json::Error> {
+        let mut parameters = self
+            .application_parameters
+            .lock()
+            .expect("Mutex should never be poisoned because service runs in a single thread");
+
+        if let Some(parameters) = &*parameters {
+            return Ok(parameters.clone());
+        }
+
+        let deserialized: Application::Parameters =
+            serde_json::from_slice(&self.application_parameters_bytes())?;
+        *parameters = Some(deserialized.clone());
+        Ok(deserialized)
+    }
+
+    /// Returns the raw JSON bytes of the application parameters provided when the application
+    /// was created.
+    ///
+    /// Useful for applications that need their own fallback parsing, for instance to migrate
+    /// parameters serialized by an older version of the application.
+    pub fn application_parameters_bytes(&self) -> Vec<u8> {
+        base_wit::application_parameters()
+    }
+
     /// Returns the ID of the current application.
     pub fn application_id(&self) -> ApplicationId<Application::Abi> {
-        Self::fetch_value_through_cache(&self.application_id, || {
+        fetch_value_through_cache(&self.application_id, || {
             ApplicationId::from(base_wit::get_application_id()).with_abi()
         })
     }
 
+    /// Returns the chain ID of the current application creator.
+    pub fn application_creator_chain_id(&self) -> ChainId {
+        fetch_value_through_cache(&self.application_creator_chain_id, || {
+            base_wit::get_application_creator_chain_id().into()
+        })
+    }
+
+    /// Returns `true` if the current chain is the one that created the current application.
+    pub fn is_creator_chain(&self) -> bool {
+        self.chain_id() == self.application_creator_chain_id()
+    }
+
     /// Returns the ID of the current chain.
     pub fn chain_id(&self) -> ChainId {
-        Self::fetch_value_through_cache(&self.chain_id, || base_wit::get_chain_id().into())
+        fetch_value_through_cache(&self.chain_id, || base_wit::get_chain_id().into())
     }
 
     /// Returns the height of the next block that can be added to the current chain.
     pub fn next_block_height(&self) -> BlockHeight {
-        Self::fetch_value_through_cache(&self.next_block_height, || {
+        fetch_value_through_cache(&self.next_block_height, || {
             base_wit::get_block_height().into()
         })
     }
 
     /// Retrieves the current system time, i.e. the timestamp of the block in which this is called.
     pub fn system_time(&self) -> Timestamp {
-        Self::fetch_value_through_cache(&self.timestamp, || {
-            base_wit::read_system_timestamp().into()
+        fetch_value_through_cache(&self.timestamp, || base_wit::read_system_timestamp().into())
+    }
+
+    /// Returns the consensus round of the block being executed.
+    ///
+    /// Queries aren't executed as part of a block, so this always returns `None`. It exists so
+    /// that code shared with the contract can be used in services.
+    pub fn round(&self) -> Option<Round> {
+        base_wit::get_round().map(Round::from)
+    }
+
+    /// Returns the owner who proposed the block being executed.
+    ///
+    /// Queries aren't executed as part of a block, so this always returns `None`. It exists so
+    /// that code shared with the contract can be used in services.
+    pub fn block_proposer(&self) -> Option<AccountOwner> {
+        base_wit::get_block_proposer().map(AccountOwner::from)
+    }
+
+    /// Returns the current epoch of the chain.
+    pub fn epoch(&self) -> Epoch {
+        fetch_value_through_cache(&self.epoch, || base_wit::get_epoch().into())
+    }
+
+    /// Returns a summary of the committee of validators in charge of the current epoch.
+    pub fn current_committee(&self) -> CommitteeSummary {
+        fetch_value_through_cache(&self.current_committee, || {
+            base_wit::get_current_committee().into()
+        })
+    }
+
+    /// Returns the owner configuration for the current chain.
+    pub fn chain_ownership(&self) -> ChainOwnership {
+        fetch_value_through_cache(&self.chain_ownership, || {
+            base_wit::get_chain_ownership().into()
+        })
+    }
+
+    /// Returns the application permissions for the current chain.
+    pub fn application_permissions(&self) -> ApplicationPermissions {
+        fetch_value_through_cache(&self.application_permissions, || {
+            base_wit::get_application_permissions().into()
         })
     }
 
     /// Returns the current chain balance.
     pub fn chain_balance(&self) -> Amount {
-        Self::fetch_value_through_cache(&self.chain_balance, || {
+        fetch_value_through_cache(&self.chain_balance, || {
             base_wit::read_chain_balance().into()
         })
     }
 
     /// Returns the balance of one of the accounts on this chain.
     pub fn owner_balance(&self, owner: AccountOwner) -> Amount {
-        base_wit::read_owner_balance(owner.into()).into()
+        if let Some(balance) = self.cached_owner_balance(&owner) {
+            return balance;
+        }
+
+        let balance = base_wit::read_owner_balance(owner.into()).into();
+        self.lock_balances_by_owner().insert(owner, balance);
+        balance
+    }
+
+    /// Returns the balances of the given accounts on this chain, in the same order.
+    ///
+    /// Balances that aren't cached yet are fetched from the host in a single call.
+    pub fn owner_balances_for(&self, owners: &[AccountOwner]) -> Vec<(AccountOwner, Amount)> {
+        let mut missing_owners = owners
+            .iter()
+            .filter(|owner| self.cached_owner_balance(owner).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+        missing_owners.sort_unstable();
+        missing_owners.dedup();
+
+        if !missing_owners.is_empty() {
+            let wit_owners = missing_owners
+                .iter()
+                .map(|owner| (*owner).into())
+                .collect::<Vec<base_wit::AccountOwner>>();
+            let balances = base_wit::read_owner_balances_for(&wit_owners);
+            self.lock_balances_by_owner().extend(
+                missing_owners
+                    .into_iter()
+                    .zip(balances.into_iter().map(Amount::from)),
+            );
+        }
+
+        owners
+            .iter()
+            .map(|owner| (*owner, self.owner_balance(*owner)))
+            .collect()
     }
 
     /// Returns the balances of all accounts on the chain.
     pub fn owner_balances(&self) -> Vec<(AccountOwner, Amount)> {
-        Self::fetch_value_through_cache(&self.owner_balances, || {
-            base_wit::read_owner_balances()
+        fetch_value_through_cache(&self.owner_balances, || {
+            let owner_balances = base_wit::read_owner_balances()
                 .into_iter()
                 .map(|(owner, amount)| (owner.into(), amount.into()))
-                .collect()
+                .collect::<Vec<_>>();
+            self.lock_balances_by_owner()
+                .extend(owner_balances.iter().copied());
+            owner_balances
         })
     }
 
+    /// Returns the balance of `owner` if it is already known without asking the host.
+    ///
+    /// Once all balances have been fetched, accounts missing from the cache have a zero balance.
+    fn cached_owner_balance(&self, owner: &AccountOwner) -> Option<Amount> {
+        if let Some(balance) = self.lock_balances_by_owner().get(owner) {
+            return Some(*balance);
+        }
+
+        let all_balances_are_cached = self
+            .owner_balances
+            .lock()
+            .expect("Mutex should never be poisoned because service runs in a single thread")
+            .is_some();
+        all_balances_are_cached.then_some(Amount::ZERO)
+    }
+
+    /// Locks the cache of balances fetched for individual accounts.
+    fn lock_balances_by_owner(&self) -> MutexGuard<'_, HashMap<AccountOwner, Amount>> {
+        self.balances_by_owner
+            .lock()
+            .expect("Mutex should never be poisoned because service runs in a single thread")
+    }
+
     /// Returns the owners of accounts on this chain.
     pub fn balance_owners(&self) -> Vec<AccountOwner> {
-        Self::fetch_value_through_cache(&self.balance_owners, || {
+        fetch_value_through_cache(&self.balance_owners, || {
             base_wit::read_balance_owners()
                 .into_iter()
                 .map(AccountOwner::from)
@@ -141,15 +414,102 @@ where
         base_wit::perform_http_request(&request.into()).into()
     }
 
+    /// Makes several HTTP `requests` as oracles and returns the HTTP responses in the same
+    /// order.
+    ///
+    /// All requests are sent in a single call to the host, which performs them concurrently up to
+    /// the limit set by the validators' resource control policy.
+    ///
+    /// Should only be used with queries where it is very likely that all validators will receive
+    /// the same responses, otherwise most block proposals will fail.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn http_requests(&self, requests: Vec<http::Request>) -> Vec<http::Response> {
+        let requests = requests
+            .into_iter()
+            .map(base_wit::HttpRequest::from)
+            .collect::<Vec<_>>();
+        base_wit::perform_http_requests(&requests)
+            .into_iter()
+            .map(http::Response::from)
+            .collect()
+    }
+
+    /// Makes an HTTP request to the given URL as an oracle with the limits from `options`, and
+    /// returns the answer.
+    ///
+    /// The limits are capped by the validators' resource control policy. Returns an error if
+    /// the request times out or if the response exceeds the maximum size.
+    ///
+    /// Should only be used with queries where it is very likely that all validators will receive
+    /// the same response, otherwise most block proposals will fail.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn http_request_with_options(
+        &self,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<http::Response, http::RequestError> {
+        service_wit::try_perform_http_request(&request.into(), options.into())
+            .map(http::Response::from)
+            .map_err(http::RequestError::from)
+    }
+
     /// Reads a data blob with the given hash from storage.
     pub fn read_data_blob(&self, hash: DataBlobHash) -> Vec<u8> {
         base_wit::read_data_blob(hash.0.into())
     }
 
+    /// Reads up to `length` bytes starting at `offset` from the data blob with the given hash.
+    ///
+    /// The range is truncated at the end of the blob, so large blobs can be read in chunks
+    /// without copying all of their contents at once. The fee is the same as for reading
+    /// the whole blob.
+    pub fn read_data_blob_range(&self, hash: DataBlobHash, offset: u64, length: u64) -> Vec<u8> {
+        base_wit::read_data_blob_range(hash.0.into(), offset, length)
+    }
+
+    /// Returns the size in bytes of the data blob with the given hash.
+    ///
+    /// The fee is the same as for reading the whole blob.
+    pub fn data_blob_size(&self, hash: DataBlobHash) -> u64 {
+        base_wit::data_blob_size(hash.0.into())
+    }
+
+    /// Reads the data blobs with the given hashes from storage, returned in the same order.
+    ///
+    /// All blobs are requested from the host at once. The query fails if any of them is missing.
+    pub fn read_data_blobs(&self, hashes: &[DataBlobHash]) -> Vec<Vec<u8>> {
+        let hashes = hashes
+            .iter()
+            .map(|hash| hash.0.into())
+            .collect::<Vec<base_wit::CryptoHash>>();
+        base_wit::read_data_blobs(&hashes)
+    }
+
+    /// Reads the data blobs with the given hashes from storage, returned in the same order,
+    /// with `None` in place of the blobs that don't exist.
+    pub fn try_read_data_blobs(&self, hashes: &[DataBlobHash]) -> Vec<Option<Vec<u8>>> {
+        let hashes = hashes
+            .iter()
+            .map(|hash| hash.0.into())
+            .collect::<Vec<service_wit::CryptoHash>>();
+        service_wit::try_read_data_blobs(&hashes)
+    }
+
     /// Asserts that a data blob with the given hash exists in storage.
     pub fn assert_data_blob_exists(&self, hash: DataBlobHash) {
         base_wit::assert_data_blob_exists(hash.0.into())
     }
+
+    /// Returns whether a data blob with the given hash exists in storage.
+    ///
+    /// Unlike [`Self::assert_data_blob_exists`], the query doesn't fail if the blob is missing.
+    pub fn data_blob_exists(&self, hash: DataBlobHash) -> bool {
+        base_wit::data_blob_exists(hash.0.into())
+    }
 }
 
 impl<Application> ServiceRuntime<Application>
@@ -158,34 +518,189 @@ where
 {
     /// Schedules an operation to be included in the block being built.
     ///
-    /// The operation is specified as an opaque blob of bytes.
-    pub fn schedule_raw_operation(&self, operation: Vec<u8>) {
-        service_wit::schedule_operation(&operation);
+    /// The operation is specified as an opaque blob of bytes. Returns the [`OperationIndex`]
+    /// assigned to it by the host.
+    pub fn schedule_raw_operation(&self, operation: Vec<u8>) -> OperationIndex {
+        OperationIndex(service_wit::schedule_operation(&operation))
     }
 
     /// Schedules an operation to be included in the block being built.
     ///
-    /// The operation is serialized using BCS.
-    pub fn schedule_operation(&self, operation: &impl Serialize) {
+    /// The operation is serialized using BCS. Returns the [`OperationIndex`] assigned to it by
+    /// the host.
+    pub fn schedule_operation(&self, operation: &impl Serialize) -> OperationIndex {
+        let bytes = bcs::to_bytes(operation).expect("Failed to serialize application operation");
+
+        self.schedule_raw_operation(bytes)
+    }
+
+    /// Returns the operations this application has scheduled so far during this query, as
+    /// opaque blobs of bytes in the order they were scheduled.
+    ///
+    /// The operations remain scheduled.
+    pub fn raw_pending_operations(&self) -> Vec<Vec<u8>> {
+        service_wit::scheduled_operations()
+    }
+
+    /// Returns the operations this application has scheduled so far during this query, in the
+    /// order they were scheduled.
+    ///
+    /// All operations are deserialized using BCS into the `Operation` generic type, and remain
+    /// scheduled.
+    pub fn pending_operations<Operation>(&self) -> Vec<Operation>
+    where
+        Operation: DeserializeOwned,
+    {
+        self.raw_pending_operations()
+            .iter()
+            .map(|bytes| bcs::from_bytes(bytes).expect("Failed to deserialize scheduled operation"))
+            .collect()
+    }
+
+    /// Schedules an operation to be included in the block being built, unless an operation with
+    /// the same serialized bytes has already been scheduled during this query.
+    ///
+    /// The operation is serialized using BCS. Returns `true` if the operation was scheduled.
+    pub fn schedule_operation_if_absent(&self, operation: &impl Serialize) -> bool {
         let bytes = bcs::to_bytes(operation).expect("Failed to serialize application operation");
 
+        if self.raw_pending_operations().contains(&bytes) {
+            return false;
+        }
+
         service_wit::schedule_operation(&bytes);
+        true
     }
 
     /// Queries another application.
+    ///
+    /// # Panics
+    ///
+    /// If the queried application traps, isn't registered on the chain, or returns a response
+    /// that can't be deserialized. Use [`Self::try_query_application`] to handle these failures.
     pub fn query_application<A: ServiceAbi>(
         &self,
         application: ApplicationId<A>,
         query: &A::Query,
     ) -> A::QueryResponse {
+        self.try_query_application(application, query)
+            .unwrap_or_else(|error| panic!("Failed to query application: {error}"))
+    }
+
+    /// Queries another application, returning an error instead of panicking if the query fails.
+    pub fn try_query_application<A: ServiceAbi>(
+        &self,
+        application: ApplicationId<A>,
+        query: &A::Query,
+    ) -> Result<A::QueryResponse, QueryApplicationError> {
         let query_bytes =
             serde_json::to_vec(&query).expect("Failed to serialize query to another application");
 
         let response_bytes =
-            service_wit::try_query_application(application.forget_abi().into(), &query_bytes);
+            service_wit::try_query_application(application.forget_abi().into(), &query_bytes)
+                .map_err(ApplicationQueryError::from)?;
+
+        Ok(serde_json::from_slice(&response_bytes)?)
+    }
+
+    /// Queries another application with an opaque blob of bytes, returning the raw response.
+    ///
+    /// Unlike [`Self::query_application`], the query and response aren't serialized, so this
+    /// can be used to forward queries to applications whose ABI isn't known.
+    ///
+    /// # Panics
+    ///
+    /// If the queried application traps or isn't registered on the chain.
+    pub fn query_application_raw(&self, application: ApplicationId, query_bytes: &[u8]) -> Vec<u8> {
+        service_wit::try_query_application(application.into(), query_bytes)
+            .map_err(ApplicationQueryError::from)
+            .unwrap_or_else(|error| panic!("Failed to query application: {error}"))
+    }
+
+    /// Reads the raw value of an event from one of `application_id`'s streams on the given
+    /// chain.
+    ///
+    /// The query fails if the event doesn't exist.
+    pub fn read_event(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        index: u32,
+    ) -> Vec<u8> {
+        service_wit::read_event(chain_id.into(), application_id.into(), &name.into(), index)
+    }
+
+    /// Reads the raw values of the events with indices from `start` (inclusive) to `end`
+    /// (exclusive) from one of `application_id`'s streams on the given chain.
+    ///
+    /// The query fails if any of the events doesn't exist.
+    pub fn read_events_in_range(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        start: u32,
+        end: u32,
+    ) -> Vec<Vec<u8>> {
+        service_wit::read_events_in_range(
+            chain_id.into(),
+            application_id.into(),
+            &name.into(),
+            start,
+            end,
+        )
+    }
 
-        serde_json::from_slice(&response_bytes)
-            .expect("Failed to deserialize query response from application")
+    /// Reads an event from one of `application_id`'s streams on the given chain, and
+    /// deserializes its value using BCS.
+    pub fn read_event_value<T: DeserializeOwned>(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        index: u32,
+    ) -> T {
+        let event = self.read_event(chain_id, application_id, name, index);
+        bcs::from_bytes(&event).expect("Failed to deserialize event")
+    }
+
+    /// Returns the hash of this chain's confirmed block at `height`, if the node has it.
+    pub fn block_hash(&self, height: BlockHeight) -> Option<CryptoHash> {
+        let end = height.try_add_one().ok()?;
+        self.block_hashes(height..end).into_iter().next()
+    }
+
+    /// Returns the hashes of this chain's confirmed blocks with heights in `range`, in order.
+    ///
+    /// The list stops at the last block confirmed on this node, and contains at most 1000
+    /// hashes, so it may be shorter than the range.
+    pub fn block_hashes(&self, range: Range<BlockHeight>) -> Vec<CryptoHash> {
+        service_wit::block_hashes(range.start.into(), range.end.into())
+            .into_iter()
+            .map(CryptoHash::from)
+            .collect()
+    }
+
+    /// Returns a summary of the header of this chain's confirmed block at `height`, if the node
+    /// has it.
+    pub fn block_header(&self, height: BlockHeight) -> Option<BlockHeaderSummary> {
+        service_wit::block_header(height.into()).map(BlockHeaderSummary::from)
+    }
+
+    /// Returns a page of summaries of the applications registered on this chain, sorted by
+    /// application ID.
+    ///
+    /// The page starts after `start_after`, if provided. To list all applications, call this
+    /// again with the ID of the last application returned, until an empty page is returned.
+    pub fn registered_applications(
+        &self,
+        start_after: Option<ApplicationId>,
+    ) -> Vec<ApplicationDescriptionSummary> {
+        service_wit::registered_applications(start_after.map(Into::into))
+            .into_iter()
+            .map(ApplicationDescriptionSummary::from)
+            .collect()
     }
 }
 
@@ -193,19 +708,66 @@ impl<Application> ServiceRuntime<Application>
 where
     Application: Service,
 {
-    /// Loads a value from the `slot` cache or fetches it and stores it in the cache.
-    fn fetch_value_through_cache<T>(slot: &Mutex<Option<T>>, fetch: impl FnOnce() -> T) -> T
-    where
-        T: Clone,
-    {
-        let mut value = slot
-            .lock()
-            .expect("Mutex should never be poisoned because service runs in a single thread");
+    /// Clears all cached values that may change between queries, so that they are fetched again
+    /// from the host the next time they are accessed.
+    ///
+    /// The application parameters, the application ID, its creator chain ID and the chain ID are
+    /// kept, because they never change.
+    pub fn refresh(&self) {
+        self.refresh_next_block_height();
+        self.refresh_system_time();
+        self.refresh_epoch();
+        self.refresh_current_committee();
+        self.refresh_chain_ownership();
+        self.refresh_application_permissions();
+        self.refresh_chain_balance();
+        self.refresh_owner_balances();
+        self.refresh_balance_owners();
+    }
 
-        if value.is_none() {
-            *value = Some(fetch());
-        }
+    /// Clears the cached next block height.
+    pub fn refresh_next_block_height(&self) {
+        clear_cache(&self.next_block_height);
+    }
+
+    /// Clears the cached system time.
+    pub fn refresh_system_time(&self) {
+        clear_cache(&self.timestamp);
+    }
+
+    /// Clears the cached epoch.
+    pub fn refresh_epoch(&self) {
+        clear_cache(&self.epoch);
+    }
+
+    /// Clears the cached summary of the current committee.
+    pub fn refresh_current_committee(&self) {
+        clear_cache(&self.current_committee);
+    }
+
+    /// Clears the cached owner configuration of the chain.
+    pub fn refresh_chain_ownership(&self) {
+        clear_cache(&self.chain_ownership);
+    }
+
+    /// Clears the cached application permissions of the chain.
+    pub fn refresh_application_permissions(&self) {
+        clear_cache(&self.application_permissions);
+    }
+
+    /// Clears the cached chain balance.
+    pub fn refresh_chain_balance(&self) {
+        clear_cache(&self.chain_balance);
+    }
+
+    /// Clears the cached balances of all accounts on the chain.
+    pub fn refresh_owner_balances(&self) {
+        clear_cache(&self.owner_balances);
+        self.lock_balances_by_owner().clear();
+    }
 
-        value.clone().expect("Value should be populated above")
+    /// Clears the cached owners of accounts on the chain.
+    pub fn refresh_balance_owners(&self) {
+        clear_cache(&self.balance_owners);
     }
 }
@@ -4,38 +4,82 @@
 //! Runtime types to simulate interfacing with the host executing the service.
 
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
     mem,
-    sync::Mutex,
+    ops::{Bound, Range},
+    sync::{Arc, Mutex},
 };
 
 use linera_base::{
     abi::ServiceAbi,
-    data_types::{Amount, BlockHeight, Timestamp},
+    crypto::CryptoHash,
+    data_types::{
+        Amount, ApplicationDescriptionSummary, ApplicationPermissions, ApplicationQueryError,
+        BlockHeaderSummary, BlockHeight, CommitteeSummary, Epoch, Round, Timestamp,
+    },
     hex, http,
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, StreamName},
+    ownership::ChainOwnership,
 };
+use linera_views::store::ReadableKeyValueStore as _;
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{DataBlobHash, KeyValueStore, Service, ViewStorageContext};
+use super::{
+    cache::{clear_cache, fetch_value_through_cache},
+    OperationIndex, QueryApplicationError,
+};
+use crate::{
+    util::BlockingWait as _, views::CacheStatistics, DataBlobHash, KeyValueStore, Service,
+    ViewStorageContext, ViewStorageExtra,
+};
 
 /// The runtime available during execution of a query.
+///
+/// As on the real runtime, the values that may change between queries are cached once the
+/// service reads them. A value configured afterwards with one of the `set_*` methods is only
+/// seen after a call to [`MockServiceRuntime::refresh`] or the corresponding `refresh_*` method.
 pub struct MockServiceRuntime<Application>
 where
     Application: Service,
 {
     application_parameters: Mutex<Option<Application::Parameters>>,
+    application_parameters_bytes: Mutex<Option<Vec<u8>>>,
     application_id: Mutex<Option<ApplicationId<Application::Abi>>>,
+    application_creator_chain_id: Mutex<Option<ChainId>>,
     chain_id: Mutex<Option<ChainId>>,
     next_block_height: Mutex<Option<BlockHeight>>,
     timestamp: Mutex<Option<Timestamp>>,
+    epoch: Mutex<Option<Epoch>>,
+    current_committee: Mutex<Option<CommitteeSummary>>,
+    chain_ownership: Mutex<Option<ChainOwnership>>,
+    application_permissions: Mutex<Option<ApplicationPermissions>>,
     chain_balance: Mutex<Option<Amount>>,
     owner_balances: Mutex<Option<HashMap<AccountOwner, Amount>>>,
     query_application_handler: Mutex<Option<QueryApplicationHandler>>,
     expected_http_requests: Mutex<VecDeque<(http::Request, http::Response)>>,
+    http_request_handler: Mutex<Option<HttpRequestHandler>>,
     blobs: Mutex<Option<HashMap<DataBlobHash, Vec<u8>>>>,
-    scheduled_operations: Mutex<Vec<Vec<u8>>>,
+    events: Mutex<BTreeMap<(ChainId, ApplicationId, StreamName, u32), Vec<u8>>>,
+    blocks: Mutex<BTreeMap<BlockHeight, (CryptoHash, BlockHeaderSummary)>>,
+    registered_applications: Mutex<BTreeMap<ApplicationId, ApplicationDescriptionSummary>>,
+    scheduled_operations: ScheduledOperations,
     key_value_store: KeyValueStore,
+    cached_key_value_store: Mutex<Option<KeyValueStore>>,
+    cached: CachedValues,
+}
+
+/// The mocked values that the service has read, which are kept until they are refreshed.
+#[derive(Default)]
+struct CachedValues {
+    next_block_height: Mutex<Option<BlockHeight>>,
+    timestamp: Mutex<Option<Timestamp>>,
+    epoch: Mutex<Option<Epoch>>,
+    current_committee: Mutex<Option<CommitteeSummary>>,
+    chain_ownership: Mutex<Option<ChainOwnership>>,
+    application_permissions: Mutex<Option<ApplicationPermissions>>,
+    chain_balance: Mutex<Option<Amount>>,
+    owner_balances: Mutex<Option<HashMap<AccountOwner, Amount>>>,
+    balance_owners: Mutex<Option<Vec<AccountOwner>>>,
 }
 
 impl<Application> Default for MockServiceRuntime<Application>
@@ -55,17 +99,29 @@ where
     pub fn new() -> Self {
         MockServiceRuntime {
             application_parameters: Mutex::new(None),
+            application_parameters_bytes: Mutex::new(None),
             application_id: Mutex::new(None),
+            application_creator_chain_id: Mutex::new(None),
             chain_id: Mutex::new(None),
             next_block_height: Mutex::new(None),
             timestamp: Mutex::new(None),
+            epoch: Mutex::new(None),
+            current_committee: Mutex::new(None),
+            chain_ownership: Mutex::new(None),
+            application_permissions: Mutex::new(None),
             chain_balance: Mutex::new(None),
             owner_balances: Mutex::new(None),
             query_application_handler: Mutex::new(None),
             expected_http_requests: Mutex::new(VecDeque::new()),
+            http_request_handler: Mutex::new(None),
             blobs: Mutex::new(None),
-            scheduled_operations: Mutex::new(vec![]),
+            events: Mutex::new(BTreeMap::new()),
+            blocks: Mutex::new(BTreeMap::new()),
+            registered_applications: Mutex::new(BTreeMap::new()),
+            scheduled_operations: ScheduledOperations::default(),
             key_value_store: KeyValueStore::mock(),
+            cached_key_value_store: Mutex::new(None),
+            cached: CachedValues::default(),
         }
     }
 
@@ -75,8 +131,92 @@ where
     }
 
     /// Returns a storage context suitable for a root view.
+    ///
+    /// The block height and the timestamp of the context are the ones configured with
+    /// [`MockServiceRuntime::set_next_block_height`] and
+    /// [`MockServiceRuntime::set_system_time`], or zero.
     pub fn root_view_storage_context(&self) -> ViewStorageContext {
-        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), ())
+        ViewStorageContext::new_unsafe(self.key_value_store(), Vec::new(), self.storage_extra())
+    }
+
+    /// Returns a storage context suitable for a root view, which keeps the last
+    /// `max_entries` values read from storage across queries.
+    ///
+    /// The cached values are discarded when the height configured with
+    /// [`MockServiceRuntime::set_next_block_height`] changes.
+    pub fn root_view_storage_context_cached(&self, max_entries: usize) -> ViewStorageContext {
+        let block_height = self.next_block_height.lock().unwrap().unwrap_or_default();
+        let mut store = self.cached_key_value_store.lock().unwrap();
+        let store = store.get_or_insert_with(|| self.key_value_store.with_cache(max_entries));
+        store.set_cache_block_height(block_height, max_entries);
+        ViewStorageContext::new_unsafe(store.clone(), Vec::new(), self.storage_extra())
+    }
+
+    /// Returns the extra data of the storage contexts.
+    fn storage_extra(&self) -> ViewStorageExtra {
+        ViewStorageExtra {
+            block_height: self.next_block_height.lock().unwrap().unwrap_or_default(),
+            timestamp: self.timestamp.lock().unwrap().unwrap_or_default(),
+        }
+    }
+
+    /// Returns the hit and miss counts of the cache used by
+    /// [`MockServiceRuntime::root_view_storage_context_cached`].
+    pub fn storage_cache_statistics(&self) -> Option<CacheStatistics> {
+        self.cached_key_value_store
+            .lock()
+            .unwrap()
+            .as_ref()?
+            .cache_statistics()
+    }
+
+    /// Reads the value stored under `key` in this application's storage, if any.
+    ///
+    /// # Panics
+    ///
+    /// If the `key` is longer than the maximum key size.
+    pub fn read_value_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.key_value_store()
+            .read_value_bytes(key)
+            .blocking_wait()
+            .expect("Failed to read from the key-value store")
+    }
+
+    /// Reads the value stored under `key` in this application's storage, if any, and
+    /// deserializes it using BCS.
+    ///
+    /// # Panics
+    ///
+    /// If the `key` is longer than the maximum key size, or if the value can't be deserialized.
+    pub fn read_value<T: DeserializeOwned>(&self, key: &[u8]) -> Option<T> {
+        self.read_value_bytes(key).map(|bytes| {
+            bcs::from_bytes(&bytes).expect("Failed to deserialize value from the key-value store")
+        })
+    }
+
+    /// Returns `true` if a value is stored under `key` in this application's storage.
+    ///
+    /// # Panics
+    ///
+    /// If the `key` is longer than the maximum key size.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.key_value_store()
+            .contains_key(key)
+            .blocking_wait()
+            .expect("Failed to read from the key-value store")
+    }
+
+    /// Returns the keys in this application's storage that start with `prefix`, with the prefix
+    /// removed.
+    ///
+    /// # Panics
+    ///
+    /// If the `prefix` is longer than the maximum key size.
+    pub fn find_keys_by_prefix(&self, prefix: &[u8]) -> Vec<Vec<u8>> {
+        self.key_value_store()
+            .find_keys_by_prefix(prefix)
+            .blocking_wait()
+            .expect("Failed to read from the key-value store")
     }
 
     /// Configures the application parameters to return during the test.
@@ -97,13 +237,62 @@ where
         self
     }
 
+    /// Configures the raw JSON bytes of the application parameters to return during the test.
+    ///
+    /// Takes precedence over parameters configured with
+    /// [`MockServiceRuntime::set_application_parameters`], so that tests can exercise the
+    /// handling of parameters that fail to deserialize.
+    pub fn with_application_parameters_bytes(self, bytes: Vec<u8>) -> Self {
+        *self.application_parameters_bytes.lock().unwrap() = Some(bytes);
+        self
+    }
+
+    /// Configures the raw JSON bytes of the application parameters to return during the test.
+    ///
+    /// Takes precedence over parameters configured with
+    /// [`MockServiceRuntime::set_application_parameters`], so that tests can exercise the
+    /// handling of parameters that fail to deserialize.
+    pub fn set_application_parameters_bytes(&self, bytes: Vec<u8>) -> &Self {
+        *self.application_parameters_bytes.lock().unwrap() = Some(bytes);
+        self
+    }
+
     /// Returns the application parameters provided when the application was created.
     pub fn application_parameters(&self) -> Application::Parameters {
-        Self::fetch_mocked_value(
+        self.try_application_parameters().unwrap_or_else(|error| {
+            panic!(
+                "Failed to deserialize application parameters {:?}: {error}",
+                String::from_utf8_lossy(&self.application_parameters_bytes()),
+            )
+        })
+    }
+
+    /// Returns the application parameters provided when the application was created, or the
+    /// error encountered while deserializing the mocked raw bytes.
+    pub fn try_application_parameters(&self) -> Result<Application::Parameters, serde_json::Error> {
+        match &*self.application_parameters_bytes.lock().unwrap() {
+            Some(bytes) => serde_json::from_slice(bytes),
+            None => Ok(Self::fetch_mocked_value(
+                &self.application_parameters,
+                "Application parameters have not been mocked, \
+                please call `MockServiceRuntime::set_application_parameters` first",
+            )),
+        }
+    }
+
+    /// Returns the raw JSON bytes of the application parameters provided when the application
+    /// was created.
+    pub fn application_parameters_bytes(&self) -> Vec<u8> {
+        if let Some(bytes) = &*self.application_parameters_bytes.lock().unwrap() {
+            return bytes.clone();
+        }
+
+        let parameters = Self::fetch_mocked_value(
             &self.application_parameters,
             "Application parameters have not been mocked, \
             please call `MockServiceRuntime::set_application_parameters` first",
-        )
+        );
+        serde_json::to_vec(&parameters).expect("Failed to serialize application parameters")
     }
 
     /// Configures the application ID to return during the test.
@@ -127,6 +316,32 @@ where
         )
     }
 
+    /// Configures the application creator chain ID to return during the test.
+    pub fn with_application_creator_chain_id(self, chain_id: ChainId) -> Self {
+        *self.application_creator_chain_id.lock().unwrap() = Some(chain_id);
+        self
+    }
+
+    /// Configures the application creator chain ID to return during the test.
+    pub fn set_application_creator_chain_id(&self, chain_id: ChainId) -> &Self {
+        *self.application_creator_chain_id.lock().unwrap() = Some(chain_id);
+        self
+    }
+
+    /// Returns the chain ID of the current application creator.
+    pub fn application_creator_chain_id(&self) -> ChainId {
+        Self::fetch_mocked_value(
+            &self.application_creator_chain_id,
+            "Application creator chain ID has not been mocked, \
+            please call `MockServiceRuntime::set_application_creator_chain_id` first",
+        )
+    }
+
+    /// Returns `true` if the current chain is the one that created the current application.
+    pub fn is_creator_chain(&self) -> bool {
+        self.chain_id() == self.application_creator_chain_id()
+    }
+
     /// Configures the chain ID to return during the test.
     pub fn with_chain_id(self, chain_id: ChainId) -> Self {
         *self.chain_id.lock().unwrap() = Some(chain_id);
@@ -162,7 +377,8 @@ where
 
     /// Returns the height of the next block that can be added to the current chain.
     pub fn next_block_height(&self) -> BlockHeight {
-        Self::fetch_mocked_value(
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.next_block_height,
             &self.next_block_height,
             "Next block height has not been mocked, \
             please call `MockServiceRuntime::set_next_block_height` first",
@@ -183,13 +399,120 @@ where
 
     /// Retrieves the current system time, i.e. the timestamp of the block in which this is called.
     pub fn system_time(&self) -> Timestamp {
-        Self::fetch_mocked_value(
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.timestamp,
             &self.timestamp,
             "System time has not been mocked, \
             please call `MockServiceRuntime::set_system_time` first",
         )
     }
 
+    /// Returns the consensus round of the block being executed, which is always `None` for
+    /// services.
+    pub fn round(&self) -> Option<Round> {
+        None
+    }
+
+    /// Returns the owner who proposed the block being executed, which is always `None` for
+    /// services.
+    pub fn block_proposer(&self) -> Option<AccountOwner> {
+        None
+    }
+
+    /// Configures the epoch to return during the test.
+    pub fn with_epoch(self, epoch: Epoch) -> Self {
+        *self.epoch.lock().unwrap() = Some(epoch);
+        self
+    }
+
+    /// Configures the epoch to return during the test.
+    pub fn set_epoch(&self, epoch: Epoch) -> &Self {
+        *self.epoch.lock().unwrap() = Some(epoch);
+        self
+    }
+
+    /// Returns the current epoch of the chain.
+    pub fn epoch(&self) -> Epoch {
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.epoch,
+            &self.epoch,
+            "Epoch has not been mocked, \
+            please call `MockServiceRuntime::set_epoch` first",
+        )
+    }
+
+    /// Configures the summary of the current committee to return during the test.
+    pub fn with_current_committee(self, committee: CommitteeSummary) -> Self {
+        *self.current_committee.lock().unwrap() = Some(committee);
+        self
+    }
+
+    /// Configures the summary of the current committee to return during the test.
+    pub fn set_current_committee(&self, committee: CommitteeSummary) -> &Self {
+        *self.current_committee.lock().unwrap() = Some(committee);
+        self
+    }
+
+    /// Returns a summary of the committee of validators in charge of the current epoch.
+    pub fn current_committee(&self) -> CommitteeSummary {
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.current_committee,
+            &self.current_committee,
+            "Current committee has not been mocked, \
+            please call `MockServiceRuntime::set_current_committee` first",
+        )
+    }
+
+    /// Configures the chain ownership configuration to return during the test.
+    pub fn with_chain_ownership(self, chain_ownership: ChainOwnership) -> Self {
+        *self.chain_ownership.lock().unwrap() = Some(chain_ownership);
+        self
+    }
+
+    /// Configures the chain ownership configuration to return during the test.
+    pub fn set_chain_ownership(&self, chain_ownership: ChainOwnership) -> &Self {
+        *self.chain_ownership.lock().unwrap() = Some(chain_ownership);
+        self
+    }
+
+    /// Returns the owner configuration for the current chain.
+    pub fn chain_ownership(&self) -> ChainOwnership {
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.chain_ownership,
+            &self.chain_ownership,
+            "Chain ownership has not been mocked, \
+            please call `MockServiceRuntime::set_chain_ownership` first",
+        )
+    }
+
+    /// Configures the application permissions to return during the test.
+    pub fn with_application_permissions(
+        self,
+        application_permissions: ApplicationPermissions,
+    ) -> Self {
+        *self.application_permissions.lock().unwrap() = Some(application_permissions);
+        self
+    }
+
+    /// Configures the application permissions to return during the test.
+    pub fn set_application_permissions(
+        &self,
+        application_permissions: ApplicationPermissions,
+    ) -> &Self {
+        *self.application_permissions.lock().unwrap() = Some(application_permissions);
+        self
+    }
+
+    /// Returns the application permissions for the current chain.
+    pub fn application_permissions(&self) -> ApplicationPermissions {
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.application_permissions,
+            &self.application_permissions,
+            "Application permissions have not been mocked, \
+            please call `MockServiceRuntime::set_application_permissions` first",
+        )
+    }
+
     /// Configures the chain balance to return during the test.
     pub fn with_chain_balance(self, chain_balance: Amount) -> Self {
         *self.chain_balance.lock().unwrap() = Some(chain_balance);
@@ -204,7 +527,8 @@ where
 
     /// Returns the current chain balance.
     pub fn chain_balance(&self) -> Amount {
-        Self::fetch_mocked_value(
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.chain_balance,
             &self.chain_balance,
             "Chain balance has not been mocked, \
             please call `MockServiceRuntime::set_chain_balance` first",
@@ -247,64 +571,119 @@ where
 
     /// Returns the balance of one of the accounts on this chain.
     pub fn owner_balance(&self, owner: AccountOwner) -> Amount {
-        self.owner_balances
-            .lock()
-            .unwrap()
-            .as_mut()
-            .and_then(|owner_balances| owner_balances.get(&owner).copied())
-            .unwrap_or_else(|| {
-                panic!(
-                    "Balance for owner {owner} was not mocked, \
-                    please include a balance for them with a call to \
-                    `MockServiceRuntime::set_owner_balance`"
-                )
-            })
+        fetch_value_through_cache(&self.cached.owner_balances, || {
+            self.owner_balances
+                .lock()
+                .unwrap()
+                .clone()
+                .unwrap_or_default()
+        })
+        .get(&owner)
+        .copied()
+        .unwrap_or_else(|| {
+            panic!(
+                "Balance for owner {owner} was not mocked, \
+                please include a balance for them with a call to \
+                `MockServiceRuntime::set_owner_balance`"
+            )
+        })
     }
 
-    /// Returns the balances of all accounts on the chain.
-    pub fn owner_balances(&self) -> Vec<(AccountOwner, Amount)> {
-        self.owner_balances
-            .lock()
-            .unwrap()
-            .as_ref()
-            .expect(
-                "Owner balances have not been mocked, \
-                please call `MockServiceRuntime::set_owner_balances` first",
-            )
+    /// Returns the balances of the given accounts on this chain, in the same order.
+    pub fn owner_balances_for(&self, owners: &[AccountOwner]) -> Vec<(AccountOwner, Amount)> {
+        owners
             .iter()
-            .map(|(owner, amount)| (*owner, *amount))
+            .map(|owner| (*owner, self.owner_balance(*owner)))
             .collect()
     }
 
+    /// Returns the balances of all accounts on the chain.
+    pub fn owner_balances(&self) -> Vec<(AccountOwner, Amount)> {
+        Self::fetch_mocked_value_through_cache(
+            &self.cached.owner_balances,
+            &self.owner_balances,
+            "Owner balances have not been mocked, \
+            please call `MockServiceRuntime::set_owner_balances` first",
+        )
+        .into_iter()
+        .collect()
+    }
+
     /// Returns the owners of accounts on this chain.
     pub fn balance_owners(&self) -> Vec<AccountOwner> {
-        self.owner_balances
-            .lock()
-            .unwrap()
-            .as_ref()
-            .expect(
-                "Owner balances have not been mocked, \
-                please call `MockServiceRuntime::set_owner_balances` first",
-            )
-            .keys()
-            .cloned()
-            .collect()
+        fetch_value_through_cache(&self.cached.balance_owners, || {
+            self.owner_balances
+                .lock()
+                .unwrap()
+                .as_ref()
+                .expect(
+                    "Owner balances have not been mocked, \
+                    please call `MockServiceRuntime::set_owner_balances` first",
+                )
+                .keys()
+                .cloned()
+                .collect()
+        })
     }
 
     /// Schedules an operation to be included in the block being built.
     ///
-    /// The operation is specified as an opaque blob of bytes.
-    pub fn schedule_raw_operation(&self, operation: Vec<u8>) {
-        self.scheduled_operations.lock().unwrap().push(operation);
+    /// The operation is specified as an opaque blob of bytes. Returns its [`OperationIndex`],
+    /// which is its position in the list of pending scheduled operations.
+    pub fn schedule_raw_operation(&self, operation: Vec<u8>) -> OperationIndex {
+        let mut scheduled_operations = self.scheduled_operations.lock();
+        let index =
+            u32::try_from(scheduled_operations.len()).expect("Too many scheduled operations");
+
+        scheduled_operations.push(operation);
+        OperationIndex(index)
     }
 
     /// Schedules an operation to be included in the block being built.
     ///
-    /// The operation is serialized using BCS.
-    pub fn schedule_operation(&self, operation: &impl Serialize) {
+    /// The operation is serialized using BCS. Returns its [`OperationIndex`], which is its
+    /// position in the list of pending scheduled operations.
+    pub fn schedule_operation(&self, operation: &impl Serialize) -> OperationIndex {
         let bytes = bcs::to_bytes(operation).expect("Failed to serialize application operation");
 
-        self.schedule_raw_operation(bytes);
+        self.schedule_raw_operation(bytes)
+    }
+
+    /// Schedules an operation to be included in the block being built, unless an operation with
+    /// the same serialized bytes is still pending in the list of scheduled operations.
+    ///
+    /// The operation is serialized using BCS. Returns `true` if the operation was scheduled.
+    pub fn schedule_operation_if_absent(&self, operation: &impl Serialize) -> bool {
+        let bytes = bcs::to_bytes(operation).expect("Failed to serialize application operation");
+        let mut scheduled_operations = self.scheduled_operations.lock();
+
+        if scheduled_operations.contains(&bytes) {
+            return false;
+        }
+
+        scheduled_operations.push(bytes);
+        true
+    }
+
+    /// Returns the operations scheduled so far, as opaque blobs of bytes in the order they were
+    /// scheduled.
+    ///
+    /// Unlike [`Self::raw_scheduled_operations`], the operations remain in the list of pending
+    /// scheduled operations, like they do with the real runtime.
+    pub fn raw_pending_operations(&self) -> Vec<Vec<u8>> {
+        self.scheduled_operations.lock().clone()
+    }
+
+    /// Returns the operations scheduled so far, in the order they were scheduled.
+    ///
+    /// All operations are deserialized using BCS into the `Operation` generic type. Unlike
+    /// [`Self::scheduled_operations`], the operations remain in the list of pending scheduled
+    /// operations, like they do with the real runtime.
+    pub fn pending_operations<Operation>(&self) -> Vec<Operation>
+    where
+        Operation: DeserializeOwned,
+    {
+        ScheduledOperations::deserialize(self.raw_pending_operations())
     }
 
     /// Returns the list of operations scheduled since the most recent of:
@@ -313,7 +692,7 @@ where
     /// - the last call to [`Self::scheduled_operations`];
     /// - or since the mock runtime was created.
     pub fn raw_scheduled_operations(&self) -> Vec<Vec<u8>> {
-        mem::take(&mut self.scheduled_operations.lock().unwrap())
+        self.scheduled_operations.take_raw()
     }
 
     /// Returns the list of operations scheduled since the most recent of:
@@ -327,18 +706,13 @@ where
     where
         Operation: DeserializeOwned,
     {
-        self.raw_scheduled_operations()
-            .into_iter()
-            .enumerate()
-            .map(|(index, bytes)| {
-                bcs::from_bytes(&bytes).unwrap_or_else(|error| {
-                    panic!(
-                        "Failed to deserialize scheduled operation #{index} (0x{}): {error}",
-                        hex::encode(bytes)
-                    )
-                })
-            })
-            .collect()
+        self.scheduled_operations.take()
+    }
+
+    /// Returns a handle to the list of operations scheduled with this runtime, which remains
+    /// usable after the runtime is moved into a service.
+    pub(crate) fn scheduled_operations_handle(&self) -> ScheduledOperations {
+        self.scheduled_operations.clone()
     }
 
     /// Configures the handler for application queries made during the test.
@@ -346,25 +720,65 @@ where
         self,
         handler: impl FnMut(ApplicationId, Vec<u8>) -> Vec<u8> + Send + 'static,
     ) -> Self {
-        *self.query_application_handler.lock().unwrap() = Some(Box::new(handler));
+        self.set_query_application_handler(handler);
         self
     }
 
     /// Configures the handler for application queries made during the test.
     pub fn set_query_application_handler(
         &self,
-        handler: impl FnMut(ApplicationId, Vec<u8>) -> Vec<u8> + Send + 'static,
+        mut handler: impl FnMut(ApplicationId, Vec<u8>) -> Vec<u8> + Send + 'static,
+    ) -> &Self {
+        self.set_try_query_application_handler(move |application_id, query| {
+            Ok(handler(application_id, query))
+        })
+    }
+
+    /// Configures the handler for application queries made during the test, allowing it to
+    /// simulate failed queries.
+    pub fn with_try_query_application_handler(
+        self,
+        handler: impl FnMut(ApplicationId, Vec<u8>) -> Result<Vec<u8>, ApplicationQueryError>
+            + Send
+            + 'static,
+    ) -> Self {
+        self.set_try_query_application_handler(handler);
+        self
+    }
+
+    /// Configures the handler for application queries made during the test, allowing it to
+    /// simulate failed queries.
+    pub fn set_try_query_application_handler(
+        &self,
+        handler: impl FnMut(ApplicationId, Vec<u8>) -> Result<Vec<u8>, ApplicationQueryError>
+            + Send
+            + 'static,
     ) -> &Self {
         *self.query_application_handler.lock().unwrap() = Some(Box::new(handler));
         self
     }
 
     /// Queries another application.
+    ///
+    /// # Panics
+    ///
+    /// If the queried application traps, isn't registered on the chain, or returns a response
+    /// that can't be deserialized. Use [`Self::try_query_application`] to handle these failures.
     pub fn query_application<A: ServiceAbi>(
         &self,
         application: ApplicationId<A>,
         query: &A::Query,
     ) -> A::QueryResponse {
+        self.try_query_application(application, query)
+            .unwrap_or_else(|error| panic!("Failed to query application: {error}"))
+    }
+
+    /// Queries another application, returning an error instead of panicking if the query fails.
+    pub fn try_query_application<A: ServiceAbi>(
+        &self,
+        application: ApplicationId<A>,
+        query: &A::Query,
+    ) -> Result<A::QueryResponse, QueryApplicationError> {
         let query_bytes =
             serde_json::to_vec(&query).expect("Failed to serialize query to another application");
 
@@ -374,10 +788,25 @@ where
             please call `MockServiceRuntime::set_query_application_handler` first",
         );
 
-        let response_bytes = handler(application.forget_abi(), query_bytes);
+        let response_bytes = handler(application.forget_abi(), query_bytes)?;
+
+        Ok(serde_json::from_slice(&response_bytes)?)
+    }
+
+    /// Queries another application with an opaque blob of bytes, returning the raw response.
+    ///
+    /// # Panics
+    ///
+    /// If the mocked handler reports a failure.
+    pub fn query_application_raw(&self, application: ApplicationId, query_bytes: &[u8]) -> Vec<u8> {
+        let mut handler_guard = self.query_application_handler.lock().unwrap();
+        let handler = handler_guard.as_mut().expect(
+            "Handler for `query_application` has not been mocked, \
+            please call `MockServiceRuntime::set_query_application_handler` first",
+        );
 
-        serde_json::from_slice(&response_bytes)
-            .expect("Failed to deserialize query response from application")
+        handler(application, query_bytes.to_vec())
+            .unwrap_or_else(|error| panic!("Failed to query application: {error}"))
     }
 
     /// Adds an expected `http_request` call, and the response it should return in the test.
@@ -388,6 +817,26 @@ where
             .push_back((request, response));
     }
 
+    /// Configures the handler answering the HTTP requests made during the test once all the
+    /// expected requests have been made.
+    pub fn with_http_request_handler(
+        self,
+        handler: impl FnMut(http::Request) -> http::Response + Send + 'static,
+    ) -> Self {
+        self.set_http_request_handler(handler);
+        self
+    }
+
+    /// Configures the handler answering the HTTP requests made during the test once all the
+    /// expected requests have been made.
+    pub fn set_http_request_handler(
+        &self,
+        handler: impl FnMut(http::Request) -> http::Response + Send + 'static,
+    ) -> &Self {
+        *self.http_request_handler.lock().unwrap() = Some(Box::new(handler));
+        self
+    }
+
     /// Makes an HTTP `request` as an oracle and returns the HTTP response.
     ///
     /// Should only be used with queries where it is very likely that all validators will receive
@@ -397,9 +846,47 @@ where
     /// owner, not a super owner.
     pub fn http_request(&self, request: http::Request) -> http::Response {
         let maybe_request = self.expected_http_requests.lock().unwrap().pop_front();
-        let (expected_request, response) = maybe_request.expect("Unexpected HTTP request");
-        assert_eq!(request, expected_request);
-        response
+        if let Some((expected_request, response)) = maybe_request {
+            assert_eq!(request, expected_request);
+            return response;
+        }
+        let mut handler = self.http_request_handler.lock().unwrap();
+        let Some(handler) = handler.as_mut() else {
+            panic!("Unexpected HTTP request to {}", request.url);
+        };
+        handler(request)
+    }
+
+    /// Makes several HTTP `requests` as oracles and returns the HTTP responses in the same
+    /// order.
+    ///
+    /// Each request must have been added with [`Self::add_expected_http_request`], or be
+    /// answered by the handler configured with [`Self::set_http_request_handler`].
+    pub fn http_requests(&self, requests: Vec<http::Request>) -> Vec<http::Response> {
+        requests
+            .into_iter()
+            .map(|request| self.http_request(request))
+            .collect()
+    }
+
+    /// Makes an HTTP `request` as an oracle with the limits from `options`, and returns the HTTP
+    /// response.
+    ///
+    /// The mocked response is checked against the maximum response size, the same way validators
+    /// check a recorded response. Timeouts are never reported.
+    pub fn http_request_with_options(
+        &self,
+        request: http::Request,
+        options: http::RequestOptions,
+    ) -> Result<http::Response, http::RequestError> {
+        let response = self.http_request(request);
+        if options
+            .max_response_bytes
+            .is_some_and(|limit| response.size() > limit)
+        {
+            return Err(http::RequestError::ResponseTooLarge);
+        }
+        Ok(response)
     }
 
     /// Configures the `blobs` returned when fetching from hashes during the test.
@@ -430,6 +917,21 @@ where
         self
     }
 
+    /// Configures a data blob that can be read during the test.
+    ///
+    /// The `hash` is usually computed with [`DataBlobHash::from_content`].
+    pub fn with_data_blob(self, hash: DataBlobHash, bytes: impl Into<Vec<u8>>) -> Self {
+        self.set_blob(hash, bytes.into());
+        self
+    }
+
+    /// Configures a data blob that can be read during the test.
+    ///
+    /// The `hash` is usually computed with [`DataBlobHash::from_content`].
+    pub fn set_data_blob(&self, hash: DataBlobHash, bytes: impl Into<Vec<u8>>) -> &Self {
+        self.set_blob(hash, bytes.into())
+    }
+
     /// Fetches a blob from a given hash.
     pub fn read_data_blob(&self, hash: DataBlobHash) -> Vec<u8> {
         self.blobs
@@ -440,24 +942,263 @@ where
             .unwrap_or_else(|| {
                 panic!(
                     "Blob for hash {hash:?} has not been mocked, \
-                    please call `MockServiceRuntime::set_blob` first"
+                    please call `MockServiceRuntime::set_data_blob` first"
                 )
             })
     }
 
+    /// Fetches up to `length` bytes starting at `offset` from the blob with the given hash.
+    pub fn read_data_blob_range(&self, hash: DataBlobHash, offset: u64, length: u64) -> Vec<u8> {
+        let blob = self.read_data_blob(hash);
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(blob.len());
+        let end = start
+            .saturating_add(usize::try_from(length).unwrap_or(usize::MAX))
+            .min(blob.len());
+        blob[start..end].to_vec()
+    }
+
+    /// Returns the size of the blob with the given hash.
+    pub fn data_blob_size(&self, hash: DataBlobHash) -> u64 {
+        self.read_data_blob(hash).len() as u64
+    }
+
+    /// Asserts that a blob with the given hash exists in storage.
+    pub fn assert_data_blob_exists(&self, hash: DataBlobHash) {
+        if !self.data_blob_exists(hash) {
+            panic!(
+                "Blob for hash {hash:?} has not been mocked, \
+                please call `MockServiceRuntime::set_data_blob` first"
+            );
+        }
+    }
+
     /// Asserts that a blob with the given hash exists in storage.
+    ///
+    /// This is the same as [`MockServiceRuntime::assert_data_blob_exists`].
     pub fn assert_blob_exists(&self, hash: DataBlobHash) {
+        self.assert_data_blob_exists(hash);
+    }
+
+    /// Returns whether a blob with the given hash has been mocked.
+    pub fn data_blob_exists(&self, hash: DataBlobHash) -> bool {
         self.blobs
             .lock()
             .unwrap()
             .as_ref()
-            .map(|blobs| blobs.contains_key(&hash))
-            .unwrap_or_else(|| {
-                panic!(
-                    "Blob for hash {hash:?} has not been mocked, \
-                    please call `MockServiceRuntime::set_blob` first"
-                )
-            });
+            .is_some_and(|blobs| blobs.contains_key(&hash))
+    }
+
+    /// Fetches the blobs with the given hashes, in the same order.
+    pub fn read_data_blobs(&self, hashes: &[DataBlobHash]) -> Vec<Vec<u8>> {
+        hashes
+            .iter()
+            .map(|hash| self.read_data_blob(*hash))
+            .collect()
+    }
+
+    /// Fetches the blobs with the given hashes, in the same order, with `None` in place of the
+    /// blobs that haven't been mocked.
+    pub fn try_read_data_blobs(&self, hashes: &[DataBlobHash]) -> Vec<Option<Vec<u8>>> {
+        let blobs = self.blobs.lock().unwrap();
+        hashes
+            .iter()
+            .map(|hash| blobs.as_ref().and_then(|blobs| blobs.get(hash).cloned()))
+            .collect()
+    }
+
+    /// Adds an event to a stream of `application_id`, so that it can be read using
+    /// `read_event`.
+    pub fn add_event(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        index: u32,
+        value: &[u8],
+    ) -> &Self {
+        self.events
+            .lock()
+            .unwrap()
+            .insert((chain_id, application_id, name, index), value.to_vec());
+        self
+    }
+
+    /// Reads the raw value of an event from a stream of `application_id`.
+    ///
+    /// Panics if the event doesn't exist.
+    pub fn read_event(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        index: u32,
+    ) -> Vec<u8> {
+        self.events
+            .lock()
+            .unwrap()
+            .get(&(chain_id, application_id, name, index))
+            .cloned()
+            .expect("Event not found")
+    }
+
+    /// Reads the raw values of the events with indices from `start` (inclusive) to `end`
+    /// (exclusive) from a stream of `application_id`.
+    ///
+    /// Panics if any of the events doesn't exist.
+    pub fn read_events_in_range(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        start: u32,
+        end: u32,
+    ) -> Vec<Vec<u8>> {
+        (start..end)
+            .map(|index| self.read_event(chain_id, application_id, name.clone(), index))
+            .collect()
+    }
+
+    /// Reads an event from a stream of `application_id` and deserializes its value using BCS.
+    ///
+    /// Panics if the event doesn't exist.
+    pub fn read_event_value<T: DeserializeOwned>(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        index: u32,
+    ) -> T {
+        let event = self.read_event(chain_id, application_id, name, index);
+        bcs::from_bytes(&event).expect("Failed to deserialize event value")
+    }
+
+    /// Adds a confirmed block to the chain's history, so that it can be read using
+    /// `block_hash`, `block_hashes` and `block_header`.
+    pub fn add_block(&self, hash: CryptoHash, header: BlockHeaderSummary) -> &Self {
+        self.blocks
+            .lock()
+            .unwrap()
+            .insert(header.height, (hash, header));
+        self
+    }
+
+    /// Returns the hash of the chain's confirmed block at `height`, if it was added.
+    pub fn block_hash(&self, height: BlockHeight) -> Option<CryptoHash> {
+        self.blocks
+            .lock()
+            .unwrap()
+            .get(&height)
+            .map(|(hash, _)| *hash)
+    }
+
+    /// Returns the hashes of the chain's confirmed blocks with heights in `range`, in order.
+    ///
+    /// The list stops at the first height without a block.
+    pub fn block_hashes(&self, range: Range<BlockHeight>) -> Vec<CryptoHash> {
+        let blocks = self.blocks.lock().unwrap();
+        (range.start.0..range.end.0)
+            .map_while(|height| blocks.get(&BlockHeight(height)).map(|(hash, _)| *hash))
+            .collect()
+    }
+
+    /// Returns a summary of the header of the chain's confirmed block at `height`, if it was
+    /// added.
+    pub fn block_header(&self, height: BlockHeight) -> Option<BlockHeaderSummary> {
+        self.blocks
+            .lock()
+            .unwrap()
+            .get(&height)
+            .map(|(_, header)| *header)
+    }
+
+    /// Registers an application on the chain, so that it is listed by
+    /// `registered_applications`.
+    pub fn add_registered_application(&self, application: ApplicationDescriptionSummary) -> &Self {
+        self.registered_applications
+            .lock()
+            .unwrap()
+            .insert(application.application_id, application);
+        self
+    }
+
+    /// Returns a page of summaries of the applications registered on this chain, sorted by
+    /// application ID and starting after `start_after`, if provided.
+    ///
+    /// Unlike the host, the mock returns all the remaining applications in a single page.
+    pub fn registered_applications(
+        &self,
+        start_after: Option<ApplicationId>,
+    ) -> Vec<ApplicationDescriptionSummary> {
+        let registered_applications = self.registered_applications.lock().unwrap();
+        let start = match start_after {
+            Some(application_id) => Bound::Excluded(application_id),
+            None => Bound::Unbounded,
+        };
+        registered_applications
+            .range((start, Bound::Unbounded))
+            .map(|(_, application)| application.clone())
+            .collect()
+    }
+
+    /// Clears the cached values that may change between queries, so that the values configured
+    /// with the `set_*` methods are seen the next time they are accessed.
+    pub fn refresh(&self) {
+        self.refresh_next_block_height();
+        self.refresh_system_time();
+        self.refresh_epoch();
+        self.refresh_current_committee();
+        self.refresh_chain_ownership();
+        self.refresh_application_permissions();
+        self.refresh_chain_balance();
+        self.refresh_owner_balances();
+        self.refresh_balance_owners();
+    }
+
+    /// Clears the cached next block height.
+    pub fn refresh_next_block_height(&self) {
+        clear_cache(&self.cached.next_block_height);
+    }
+
+    /// Clears the cached system time.
+    pub fn refresh_system_time(&self) {
+        clear_cache(&self.cached.timestamp);
+    }
+
+    /// Clears the cached epoch.
+    pub fn refresh_epoch(&self) {
+        clear_cache(&self.cached.epoch);
+    }
+
+    /// Clears the cached summary of the current committee.
+    pub fn refresh_current_committee(&self) {
+        clear_cache(&self.cached.current_committee);
+    }
+
+    /// Clears the cached owner configuration of the chain.
+    pub fn refresh_chain_ownership(&self) {
+        clear_cache(&self.cached.chain_ownership);
+    }
+
+    /// Clears the cached application permissions of the chain.
+    pub fn refresh_application_permissions(&self) {
+        clear_cache(&self.cached.application_permissions);
+    }
+
+    /// Clears the cached chain balance.
+    pub fn refresh_chain_balance(&self) {
+        clear_cache(&self.cached.chain_balance);
+    }
+
+    /// Clears the cached balances of all accounts on the chain.
+    pub fn refresh_owner_balances(&self) {
+        clear_cache(&self.cached.owner_balances);
+    }
+
+    /// Clears the cached owners of accounts on the chain.
+    pub fn refresh_balance_owners(&self) {
+        clear_cache(&self.cached.balance_owners);
     }
 
     /// Loads a mocked value from the `slot` cache or panics with a provided `message`.
@@ -467,7 +1208,555 @@ where
     {
         slot.lock().unwrap().clone().expect(message)
     }
+
+    /// Loads a value from the `cache`, or else loads the mocked value from the `slot` and caches
+    /// it, or panics with a provided `message`.
+    fn fetch_mocked_value_through_cache<T>(
+        cache: &Mutex<Option<T>>,
+        slot: &Mutex<Option<T>>,
+        message: &str,
+    ) -> T
+    where
+        T: Clone,
+    {
+        fetch_value_through_cache(cache, || Self::fetch_mocked_value(slot, message))
+    }
 }
 
+/// A type alias for the handler for HTTP requests.
+pub type HttpRequestHandler = Box<dyn FnMut(http::Request) -> http::Response + Send>;
+
 /// A type alias for the handler for application queries.
-pub type QueryApplicationHandler = Box<dyn FnMut(ApplicationId, Vec<u8>) -> Vec<u8> + Send>;
+pub type QueryApplicationHandler =
+    Box<dyn FnMut(ApplicationId, Vec<u8>) -> Result<Vec<u8>, ApplicationQueryError> + Send>;
+
+/// The operations scheduled by a service, shared with the [`GraphQLTester`] running it.
+///
+/// [`GraphQLTester`]: crate::test::GraphQLTester
+#[derive(Clone, Default)]
+pub(crate) struct ScheduledOperations(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl ScheduledOperations {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        self.0.lock().unwrap()
+    }
+
+    /// Removes and returns the operations scheduled so far.
+    pub(crate) fn take_raw(&self) -> Vec<Vec<u8>> {
+        mem::take(&mut self.lock())
+    }
+
+    /// Removes the operations scheduled so far, and returns them deserialized using BCS.
+    pub(crate) fn take<Operation: DeserializeOwned>(&self) -> Vec<Operation> {
+        Self::deserialize(self.take_raw())
+    }
+
+    /// Deserializes the given operations using BCS.
+    fn deserialize<Operation: DeserializeOwned>(operations: Vec<Vec<u8>>) -> Vec<Operation> {
+        operations
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| {
+                bcs::from_bytes(&bytes).unwrap_or_else(|error| {
+                    panic!(
+                        "Failed to deserialize scheduled operation #{index} (0x{}): {error}",
+                        hex::encode(bytes)
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use assert_matches::assert_matches;
+    use linera_base::abi::WithServiceAbi;
+    use linera_views::{batch::Batch, store::WritableKeyValueStore as _};
+
+    use super::*;
+
+    /// A dummy service used to instantiate the [`MockServiceRuntime`].
+    struct DummyService;
+
+    /// The ABI of the queried application in the tests.
+    struct DummyAbi;
+
+    impl ServiceAbi for DummyAbi {
+        type Query = u64;
+        type QueryResponse = u64;
+    }
+
+    impl WithServiceAbi for DummyService {
+        type Abi = DummyAbi;
+    }
+
+    impl Service for DummyService {
+        type Parameters = ();
+
+        async fn new(_runtime: MockServiceRuntime<Self>) -> Self {
+            DummyService
+        }
+
+        async fn handle_query(&self, query: u64) -> u64 {
+            query
+        }
+    }
+
+    fn dummy_application_id() -> ApplicationId<DummyAbi> {
+        ApplicationId::new(CryptoHash::test_hash("queried application")).with_abi()
+    }
+
+    #[test]
+    fn test_try_query_application_returns_response() {
+        let runtime = MockServiceRuntime::<DummyService>::new().with_query_application_handler(
+            |_application_id, query| {
+                let value: u64 = serde_json::from_slice(&query).unwrap();
+                serde_json::to_vec(&(value + 1)).unwrap()
+            },
+        );
+
+        let response = runtime.try_query_application(dummy_application_id(), &41);
+
+        assert_eq!(response.unwrap(), 42);
+        assert_eq!(runtime.query_application(dummy_application_id(), &9), 10);
+    }
+
+    #[test]
+    fn test_try_query_application_reports_host_failures() {
+        let runtime = MockServiceRuntime::<DummyService>::new().with_try_query_application_handler(
+            |_application_id, query| match serde_json::from_slice::<u64>(&query).unwrap() {
+                0 => Err(ApplicationQueryError::Trapped),
+                _ => Err(ApplicationQueryError::NotFound),
+            },
+        );
+
+        assert_matches!(
+            runtime.try_query_application(dummy_application_id(), &0),
+            Err(QueryApplicationError::ApplicationTrapped)
+        );
+        assert_matches!(
+            runtime.try_query_application(dummy_application_id(), &1),
+            Err(QueryApplicationError::ApplicationNotFound)
+        );
+    }
+
+    #[test]
+    fn test_try_query_application_reports_invalid_responses() {
+        let runtime = MockServiceRuntime::<DummyService>::new()
+            .with_query_application_handler(|_application_id, _query| b"not a number".to_vec());
+
+        assert_matches!(
+            runtime.try_query_application(dummy_application_id(), &0),
+            Err(QueryApplicationError::ResponseDeserialization(_))
+        );
+    }
+
+    #[test]
+    fn test_query_application_raw_forwards_bytes() {
+        let runtime = MockServiceRuntime::<DummyService>::new().with_query_application_handler(
+            |application_id, query| {
+                assert_eq!(application_id, dummy_application_id().forget_abi());
+                query.into_iter().rev().collect()
+            },
+        );
+
+        assert_eq!(
+            runtime.query_application_raw(dummy_application_id().forget_abi(), b"\x00\xffquery"),
+            b"yreuq\xff\x00".to_vec()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to query application")]
+    fn test_query_application_panics_on_failure() {
+        let runtime = MockServiceRuntime::<DummyService>::new().with_try_query_application_handler(
+            |_application_id, _query| Err(ApplicationQueryError::Trapped),
+        );
+
+        runtime.query_application(dummy_application_id(), &0);
+    }
+
+    #[test]
+    fn test_application_parameters_bytes_fallback() {
+        let runtime = MockServiceRuntime::<DummyService>::new().with_application_parameters(());
+
+        assert_eq!(runtime.application_parameters_bytes(), b"null");
+        assert!(runtime.try_application_parameters().is_ok());
+
+        runtime.set_application_parameters_bytes(br#"{"ticker":"LIN"}"#.to_vec());
+
+        assert_eq!(
+            runtime.application_parameters_bytes(),
+            br#"{"ticker":"LIN"}"#.to_vec()
+        );
+        assert!(runtime.try_application_parameters().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = r#"{\"ticker\":\"LIN\"}"#)]
+    fn test_application_parameters_panic_includes_raw_json() {
+        let runtime = MockServiceRuntime::<DummyService>::new()
+            .with_application_parameters_bytes(br#"{"ticker":"LIN"}"#.to_vec());
+
+        runtime.application_parameters();
+    }
+
+    #[test]
+    fn test_schedule_operation_returns_pending_index() {
+        let runtime = MockServiceRuntime::<DummyService>::new();
+
+        assert_eq!(runtime.schedule_operation(&1_u64), OperationIndex(0));
+        assert_eq!(runtime.schedule_raw_operation(vec![2]), OperationIndex(1));
+        assert_eq!(runtime.raw_scheduled_operations().len(), 2);
+        assert_eq!(runtime.schedule_operation(&3_u64), OperationIndex(0));
+    }
+
+    #[test]
+    fn test_pending_operations_remain_scheduled() {
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        runtime.schedule_operation(&1_u64);
+        runtime.schedule_operation(&2_u64);
+
+        assert_eq!(runtime.pending_operations::<u64>(), vec![1, 2]);
+        assert_eq!(runtime.raw_pending_operations().len(), 2);
+        assert_eq!(runtime.scheduled_operations::<u64>(), vec![1, 2]);
+        assert!(runtime.pending_operations::<u64>().is_empty());
+    }
+
+    #[test]
+    fn test_schedule_operation_if_absent_compares_serialized_bytes() {
+        #[derive(Serialize)]
+        struct Transfer {
+            amount: u64,
+            memo: String,
+        }
+
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        let first = Transfer {
+            amount: 10,
+            memo: "rent".to_owned(),
+        };
+        let identical = Transfer {
+            amount: 10,
+            memo: String::from("rent"),
+        };
+        let different = Transfer {
+            amount: 11,
+            memo: "rent".to_owned(),
+        };
+
+        assert!(runtime.schedule_operation_if_absent(&first));
+        assert!(!runtime.schedule_operation_if_absent(&identical));
+        assert!(runtime.schedule_operation_if_absent(&different));
+        assert_eq!(
+            runtime.raw_scheduled_operations(),
+            vec![
+                bcs::to_bytes(&first).unwrap(),
+                bcs::to_bytes(&different).unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_data_blobs_in_order() {
+        let first = DataBlobHash(CryptoHash::test_hash("first blob"));
+        let second = DataBlobHash(CryptoHash::test_hash("second blob"));
+        let missing = DataBlobHash(CryptoHash::test_hash("missing blob"));
+        let runtime = MockServiceRuntime::<DummyService>::new()
+            .with_blob(first, b"first".to_vec())
+            .with_blob(second, b"second".to_vec());
+
+        assert_eq!(
+            runtime.read_data_blobs(&[second, first]),
+            vec![b"second".to_vec(), b"first".to_vec()]
+        );
+        assert_eq!(
+            runtime.try_read_data_blobs(&[first, missing, second]),
+            vec![Some(b"first".to_vec()), None, Some(b"second".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_data_blob_exists() {
+        let hash = DataBlobHash(CryptoHash::test_hash("blob"));
+        let missing = DataBlobHash(CryptoHash::test_hash("missing blob"));
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        assert!(!runtime.data_blob_exists(hash));
+
+        runtime.set_blob(hash, b"metadata".to_vec());
+        assert!(runtime.data_blob_exists(hash));
+        assert!(!runtime.data_blob_exists(missing));
+    }
+
+    #[test]
+    fn test_set_data_blob() {
+        let hash = DataBlobHash::from_content(b"content");
+        let runtime = MockServiceRuntime::<DummyService>::new().with_data_blob(hash, b"content");
+
+        runtime.assert_data_blob_exists(hash);
+        assert_eq!(runtime.read_data_blob(hash), b"content");
+        assert_eq!(runtime.data_blob_size(hash), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been mocked")]
+    fn test_assert_data_blob_exists_panics_for_missing_blob() {
+        let runtime = MockServiceRuntime::<DummyService>::new()
+            .with_data_blob(DataBlobHash::from_content(b"content"), b"content");
+
+        runtime.assert_data_blob_exists(DataBlobHash::from_content(b"missing"));
+    }
+
+    #[test]
+    fn test_read_data_blob_range() {
+        let hash = DataBlobHash(CryptoHash::test_hash("blob"));
+        let runtime =
+            MockServiceRuntime::<DummyService>::new().with_blob(hash, b"0123456789".to_vec());
+
+        assert_eq!(runtime.data_blob_size(hash), 10);
+        assert_eq!(runtime.read_data_blob_range(hash, 2, 3), b"234".to_vec());
+        assert_eq!(runtime.read_data_blob_range(hash, 8, 5), b"89".to_vec());
+        assert_eq!(runtime.read_data_blob_range(hash, 12, 5), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_registered_applications_start_after() {
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        let summary = |name: &str| ApplicationDescriptionSummary {
+            application_id: ApplicationId::new(CryptoHash::test_hash(name)),
+            contract_blob_hash: CryptoHash::test_hash(format!("{name} contract")),
+            service_blob_hash: CryptoHash::test_hash(format!("{name} service")),
+            creator_chain_id: ChainId(CryptoHash::test_hash("creator")),
+            parameters: name.as_bytes().to_vec(),
+        };
+        let mut applications = vec![summary("first"), summary("second"), summary("third")];
+        applications.sort_by_key(|application| application.application_id);
+
+        for application in &applications {
+            runtime.add_registered_application(application.clone());
+        }
+
+        assert_eq!(runtime.registered_applications(None), applications);
+        assert_eq!(
+            runtime.registered_applications(Some(applications[0].application_id)),
+            applications[1..]
+        );
+        assert!(runtime
+            .registered_applications(Some(applications[2].application_id))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_block_hashes_stop_at_missing_height() {
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        let header = |height| BlockHeaderSummary {
+            height: BlockHeight(height),
+            timestamp: Timestamp::from(height * 1_000),
+            epoch: Epoch::ZERO,
+            authenticated_signer: None,
+        };
+        let first = CryptoHash::test_hash("block 0");
+        let second = CryptoHash::test_hash("block 1");
+
+        runtime
+            .add_block(first, header(0))
+            .add_block(second, header(1))
+            .add_block(CryptoHash::test_hash("block 3"), header(3));
+
+        assert_eq!(
+            runtime.block_hashes(BlockHeight(0)..BlockHeight(5)),
+            vec![first, second]
+        );
+        assert_eq!(runtime.block_hash(BlockHeight(1)), Some(second));
+        assert_eq!(runtime.block_hash(BlockHeight(2)), None);
+        assert_eq!(runtime.block_header(BlockHeight(1)), Some(header(1)));
+    }
+
+    #[test]
+    fn test_read_events_in_range() {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let application_id = ApplicationId::new(CryptoHash::test_hash("stream owner"));
+        let name = StreamName(b"stream".to_vec());
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        for index in 0..3 {
+            runtime.add_event(
+                chain_id,
+                application_id,
+                name.clone(),
+                index,
+                &bcs::to_bytes(&u64::from(index)).unwrap(),
+            );
+        }
+
+        assert_eq!(
+            runtime.read_events_in_range(chain_id, application_id, name.clone(), 1, 3),
+            vec![
+                bcs::to_bytes(&1_u64).unwrap(),
+                bcs::to_bytes(&2_u64).unwrap()
+            ]
+        );
+        assert_eq!(
+            runtime.read_event_value::<u64>(chain_id, application_id, name.clone(), 0),
+            0
+        );
+        assert!(runtime
+            .read_events_in_range(chain_id, application_id, name, 2, 2)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_http_request_with_options_limits_response_size() {
+        let request = http::Request::get("http://localhost");
+        let response = http::Response::ok(b"0123456789".to_vec());
+        let mut runtime = MockServiceRuntime::<DummyService>::new();
+        runtime.add_expected_http_request(request.clone(), response.clone());
+        runtime.add_expected_http_request(request.clone(), response.clone());
+
+        let options = http::RequestOptions::default().with_max_response_bytes(9);
+        assert_eq!(
+            runtime.http_request_with_options(request.clone(), options),
+            Err(http::RequestError::ResponseTooLarge)
+        );
+        let options = http::RequestOptions::default().with_max_response_bytes(10);
+        assert_eq!(
+            runtime.http_request_with_options(request, options),
+            Ok(response)
+        );
+    }
+
+    #[test]
+    fn test_http_request_handler_answers_after_expected_requests() {
+        let expected_request = http::Request::get("http://localhost/expected");
+        let expected_response = http::Response::ok(b"expected".to_vec());
+        let mut runtime = MockServiceRuntime::<DummyService>::new()
+            .with_http_request_handler(|request| http::Response::ok(request.url.into_bytes()));
+        runtime.add_expected_http_request(expected_request.clone(), expected_response.clone());
+
+        assert_eq!(runtime.http_request(expected_request), expected_response);
+        assert_eq!(
+            runtime.http_request(http::Request::get("http://localhost/handled")),
+            http::Response::ok(b"http://localhost/handled".to_vec())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected HTTP request to http://localhost/unmatched")]
+    fn test_unexpected_http_request_reports_url() {
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        runtime.http_request(http::Request::get("http://localhost/unmatched"));
+    }
+
+    #[test]
+    fn test_http_requests_in_order() {
+        let first_request = http::Request::get("http://localhost/first");
+        let second_request = http::Request::get("http://localhost/second");
+        let first_response = http::Response::ok(b"first".to_vec());
+        let second_response = http::Response::unauthorized();
+        let mut runtime = MockServiceRuntime::<DummyService>::new();
+        runtime.add_expected_http_request(first_request.clone(), first_response.clone());
+        runtime.add_expected_http_request(second_request.clone(), second_response.clone());
+
+        assert_eq!(
+            runtime.http_requests(vec![first_request, second_request]),
+            vec![first_response, second_response]
+        );
+    }
+
+    #[test]
+    fn test_owner_balances_for_in_order() {
+        let first = AccountOwner::from(CryptoHash::test_hash("first owner"));
+        let second = AccountOwner::from(CryptoHash::test_hash("second owner"));
+        let runtime = MockServiceRuntime::<DummyService>::new()
+            .with_owner_balance(first, Amount::from_tokens(1))
+            .with_owner_balance(second, Amount::from_tokens(2));
+
+        assert_eq!(
+            runtime.owner_balances_for(&[second, first, second]),
+            vec![
+                (second, Amount::from_tokens(2)),
+                (first, Amount::from_tokens(1)),
+                (second, Amount::from_tokens(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_creator_chain() {
+        let creator_chain_id = ChainId(CryptoHash::test_hash("creator chain"));
+        let other_chain_id = ChainId(CryptoHash::test_hash("other chain"));
+        let runtime = MockServiceRuntime::<DummyService>::new()
+            .with_application_creator_chain_id(creator_chain_id)
+            .with_chain_id(creator_chain_id);
+        assert!(runtime.is_creator_chain());
+
+        runtime.set_chain_id(other_chain_id);
+        assert!(!runtime.is_creator_chain());
+    }
+
+    #[test]
+    fn test_direct_storage_reads() {
+        let runtime = MockServiceRuntime::<DummyService>::new();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"counter".to_vec(), b"raw".to_vec());
+        batch
+            .put_key_value(b"user/alice".to_vec(), &10_u64)
+            .unwrap();
+        batch.put_key_value(b"user/bob".to_vec(), &20_u64).unwrap();
+        runtime
+            .key_value_store()
+            .to_mut()
+            .write_batch(batch)
+            .blocking_wait()
+            .unwrap();
+
+        assert_eq!(runtime.read_value_bytes(b"counter"), Some(b"raw".to_vec()));
+        assert_eq!(runtime.read_value_bytes(b"missing"), None);
+        assert_eq!(runtime.read_value::<u64>(b"user/bob"), Some(20));
+        assert!(runtime.contains_key(b"user/alice"));
+        assert!(!runtime.contains_key(b"user/carol"));
+        assert_eq!(
+            runtime.find_keys_by_prefix(b"user/"),
+            vec![b"alice".to_vec(), b"bob".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_refresh_after_balance_update() {
+        let runtime = MockServiceRuntime::<DummyService>::new().with_chain_balance(Amount::ONE);
+        assert_eq!(runtime.chain_balance(), Amount::ONE);
+
+        // The balance that was read is cached until it is refreshed.
+        runtime.set_chain_balance(Amount::from_tokens(2));
+        assert_eq!(runtime.chain_balance(), Amount::ONE);
+        runtime.refresh_chain_balance();
+        assert_eq!(runtime.chain_balance(), Amount::from_tokens(2));
+
+        runtime.set_chain_balance(Amount::from_tokens(3));
+        runtime.refresh_system_time();
+        assert_eq!(runtime.chain_balance(), Amount::from_tokens(2));
+        runtime.refresh();
+        assert_eq!(runtime.chain_balance(), Amount::from_tokens(3));
+    }
+
+    #[test]
+    fn test_refresh_owner_balances() {
+        let owner = AccountOwner::from(CryptoHash::test_hash("owner"));
+        let runtime =
+            MockServiceRuntime::<DummyService>::new().with_owner_balance(owner, Amount::ONE);
+        assert_eq!(runtime.owner_balance(owner), Amount::ONE);
+        assert_eq!(runtime.balance_owners(), vec![owner]);
+
+        let other_owner = AccountOwner::from(CryptoHash::test_hash("other owner"));
+        runtime.set_owner_balance(owner, Amount::from_tokens(2));
+        runtime.set_owner_balance(other_owner, Amount::ONE);
+        assert_eq!(runtime.owner_balances(), vec![(owner, Amount::ONE)]);
+        assert_eq!(runtime.balance_owners(), vec![owner]);
+
+        runtime.refresh_owner_balances();
+        assert_eq!(runtime.owner_balance(owner), Amount::from_tokens(2));
+        assert_eq!(runtime.balance_owners(), vec![owner]);
+        runtime.refresh_balance_owners();
+        assert_eq!(runtime.balance_owners().len(), 2);
+    }
+}
@@ -7,21 +7,27 @@
 
 use linera_base::{
     abi::ContractAbi,
+    crypto::{AccountSecretKey, CryptoHash},
     data_types::{Amount, ApplicationPermissions, Blob, Epoch, Round, Timestamp},
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    ensure,
+    identifiers::{AccountOwner, ApplicationId, ChainId, GenericApplicationId, StreamName},
     ownership::TimeoutConfig,
 };
 use linera_chain::{
     data_types::{
-        IncomingBundle, LiteValue, LiteVote, MessageAction, ProposedBlock, SignatureAggregator,
+        BlockProposal, IncomingBundle, LiteValue, LiteVote, MessageAction, ProposalContent,
+        ProposedBlock, SignatureAggregator,
     },
     types::{ConfirmedBlock, ConfirmedBlockCertificate},
+    ChainError,
 };
 use linera_core::worker::WorkerError;
 use linera_execution::{
     system::{Recipient, SystemOperation},
-    Operation,
+    Operation, ResourceTracker,
 };
+use linera_storage::Storage as _;
+use serde::de::DeserializeOwned;
 
 use super::TestValidator;
 
@@ -29,6 +35,11 @@ use super::TestValidator;
 /// [`ConfirmedBlockCertificate`]s using a [`TestValidator`].
 pub struct BlockBuilder {
     block: ProposedBlock,
+    previous_timestamp: Timestamp,
+    /// The key pair of the owner proposing the block.
+    proposer: AccountSecretKey,
+    /// The round in which the block is proposed, if the consensus rules apply to it.
+    round: Option<Round>,
     validator: TestValidator,
 }
 
@@ -36,8 +47,10 @@ impl BlockBuilder {
     /// Creates a new [`BlockBuilder`], initializing the block so that it belongs to a microchain.
     ///
     /// Initializes the block so that it belongs to the microchain identified by `chain_id` and
-    /// owned by `owner`. It becomes the block after the specified `previous_block`, or the genesis
-    /// block if [`None`] is specified.
+    /// is proposed by the owner of the `proposer` key pair. It becomes the block after the
+    /// specified `previous_block`, or the genesis block if [`None`] is specified. Its timestamp is
+    /// the current time of the validator's clock, or the timestamp of the previous block if the
+    /// clock is behind it.
     ///
     /// # Notes
     ///
@@ -46,7 +59,7 @@ impl BlockBuilder {
     /// block.
     pub(crate) fn new(
         chain_id: ChainId,
-        owner: AccountOwner,
+        proposer: AccountSecretKey,
         epoch: Epoch,
         previous_block: Option<&ConfirmedBlockCertificate>,
         validator: TestValidator,
@@ -61,6 +74,10 @@ impl BlockBuilder {
                     .expect("Block height limit reached")
             })
             .unwrap_or_default();
+        let previous_timestamp = previous_block
+            .map(|certificate| certificate.inner().block().header.timestamp)
+            .unwrap_or_default();
+        let timestamp = validator.clock().current_time().max(previous_timestamp);
 
         BlockBuilder {
             block: ProposedBlock {
@@ -70,19 +87,45 @@ impl BlockBuilder {
                 operations: vec![],
                 previous_block_hash,
                 height,
-                authenticated_signer: Some(owner),
-                timestamp: Timestamp::from(0),
+                authenticated_signer: Some(proposer.public().into()),
+                timestamp,
             },
+            previous_timestamp,
+            proposer,
+            round: None,
             validator,
         }
     }
 
     /// Configures the timestamp of this block.
+    ///
+    /// The timestamp must not be earlier than the one of the previous block of the chain,
+    /// otherwise adding the block fails with [`ChainError::InvalidBlockTimestamp`].
     pub fn with_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
         self.block.timestamp = timestamp;
         self
     }
 
+    /// Makes `owner` the proposer and the authenticated signer of this block.
+    ///
+    /// The owner must have been created with [`TestValidator::new_owner`].
+    pub fn with_owner(&mut self, owner: AccountOwner) -> &mut Self {
+        self.proposer = self.validator.owner_key_pair(&owner);
+        self.block.authenticated_signer = Some(owner);
+        self
+    }
+
+    /// Proposes this block in the consensus `round`.
+    ///
+    /// The block is then rejected if the chain's ownership doesn't allow its proposer to
+    /// propose in that round, or if the round is already over. Blocks proposed in the
+    /// [`Round::Fast`] must not use oracles. Without a round, the block is accepted from any
+    /// proposer.
+    pub fn with_round(&mut self, round: Round) -> &mut Self {
+        self.round = Some(round);
+        self
+    }
+
     /// Adds a native token transfer to this block.
     pub fn with_native_token_transfer(
         &mut self,
@@ -196,10 +239,20 @@ impl BlockBuilder {
 
     /// Tries to sign the prepared block with the [`TestValidator`]'s keys and return the
     /// resulting [`Certificate`]. Returns an error if block execution fails.
+    ///
+    /// If `measure_resource_usage` is set, the block is executed a second time to also return
+    /// the resources it uses.
     pub(crate) async fn try_sign(
         self,
         blobs: &[Blob],
-    ) -> Result<ConfirmedBlockCertificate, WorkerError> {
+        measure_resource_usage: bool,
+    ) -> Result<(ConfirmedBlockCertificate, Option<ResourceUsage>), WorkerError> {
+        if self.block.timestamp < self.previous_timestamp {
+            return Err(ChainError::InvalidBlockTimestamp.into());
+        }
+        if let Some(round) = self.round {
+            self.check_proposal(round).await?;
+        }
         let published_blobs = self
             .block
             .published_blob_ids()
@@ -211,26 +264,181 @@ impl BlockBuilder {
                     .expect("missing published blob")
                     .clone()
             })
-            .collect();
+            .collect::<Vec<_>>();
         let (block, _) = self
             .validator
             .worker()
-            .stage_block_execution(self.block, None, published_blobs)
+            .stage_block_execution(self.block.clone(), self.round, published_blobs.clone())
             .await?;
+        ensure!(
+            self.round != Some(Round::Fast) || !block.has_oracle_responses(),
+            WorkerError::FastBlockUsingOracles
+        );
+        let resource_usage = if measure_resource_usage {
+            // The oracle responses are replayed, so that the execution is the same.
+            let mut chain = self
+                .validator
+                .storage()
+                .load_chain(self.block.chain_id)
+                .await?;
+            let (_, tracker) = chain
+                .execute_block_with_resource_tracker(
+                    &self.block,
+                    self.validator.clock().current_time(),
+                    self.round,
+                    &published_blobs,
+                    Some(block.body.oracle_responses.clone()),
+                )
+                .await?;
+            Some(ResourceUsage::from(tracker))
+        } else {
+            None
+        };
 
+        let round = self.round.unwrap_or(Round::Fast);
         let value = ConfirmedBlock::new(block);
-        let vote = LiteVote::new(
-            LiteValue::new(&value),
-            Round::Fast,
-            self.validator.key_pair(),
-        );
+        let vote = LiteVote::new(LiteValue::new(&value), round, self.validator.key_pair());
         let committee = self.validator.committee().await;
-        let mut builder = SignatureAggregator::new(value, Round::Fast, &committee);
+        let mut builder = SignatureAggregator::new(value, round, &committee);
         let certificate = builder
             .append(vote.public_key, vote.signature)
             .expect("Failed to sign block")
             .expect("Committee has more than one test validator");
 
-        Ok(certificate)
+        Ok((certificate, resource_usage))
+    }
+
+    /// Signs the block as a proposal in `round`, and checks that the chain would accept it
+    /// as a new proposal.
+    async fn check_proposal(&self, round: Round) -> Result<(), WorkerError> {
+        let content = ProposalContent {
+            block: self.block.clone(),
+            round,
+            outcome: None,
+        };
+        let signature = self.proposer.sign_prehash(CryptoHash::new(&content));
+        let proposal = BlockProposal {
+            content,
+            public_key: self.proposer.public(),
+            signature,
+            validated_block_certificate: None,
+        };
+        proposal.check_signature()?;
+        let owner = AccountOwner::from(proposal.public_key);
+        if let Some(signer) = self.block.authenticated_signer {
+            ensure!(signer == owner, WorkerError::InvalidSigner(signer));
+        }
+
+        let chain = self
+            .validator
+            .worker()
+            .chain_state_view(self.block.chain_id)
+            .await?;
+        ensure!(
+            chain.manager.verify_owner(&proposal),
+            WorkerError::InvalidOwner
+        );
+        chain.manager.check_proposed_block(&proposal)?;
+        Ok(())
+    }
+}
+
+/// The resources used to execute a block, as measured by the resource tracker of its
+/// execution.
+///
+/// It is returned by
+/// [`ActiveChain::add_block_with_resource_usage`][super::ActiveChain::add_block_with_resource_usage].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ResourceUsage {
+    /// The fuel consumed by the Wasm and EVM applications.
+    pub fuel: u64,
+    /// The number of storage read operations of the applications.
+    pub read_operations: u32,
+    /// The number of storage write operations of the applications.
+    pub write_operations: u32,
+    /// The number of bytes read from storage by the applications.
+    pub bytes_read: u64,
+    /// The number of bytes written to storage by the applications.
+    pub bytes_written: u64,
+    /// The number of operations in the block.
+    pub operations: u32,
+    /// The number of outgoing messages created by the block.
+    pub messages: u32,
+}
+
+impl From<ResourceTracker> for ResourceUsage {
+    fn from(tracker: ResourceTracker) -> Self {
+        ResourceUsage {
+            fuel: tracker.wasm_fuel.saturating_add(tracker.evm_fuel),
+            read_operations: tracker.read_operations,
+            write_operations: tracker.write_operations,
+            bytes_read: tracker.bytes_read,
+            bytes_written: tracker.bytes_written,
+            operations: tracker.operations,
+            messages: tracker.messages,
+        }
+    }
+}
+
+/// Helpers to inspect the outcome of a block added to a chain, as returned by
+/// [`ActiveChain::add_block`][super::ActiveChain::add_block].
+pub trait BlockOutcome {
+    /// Returns the events emitted by user applications in the block, in the order of the
+    /// transactions, as tuples of the emitting application, the stream name, the index in the
+    /// stream and the serialized value.
+    fn events(&self) -> Vec<(ApplicationId, StreamName, u32, Vec<u8>)>;
+
+    /// Returns the value of the event emitted in the block into `stream` at `index`,
+    /// deserialized using BCS.
+    ///
+    /// Panics if the block did not emit such an event, or if its value can't be deserialized.
+    fn expect_event<T: DeserializeOwned>(&self, stream: impl Into<StreamName>, index: u32) -> T {
+        let stream = stream.into();
+        let (_, _, _, value) = self
+            .events()
+            .into_iter()
+            .find(|(_, event_stream, event_index, _)| {
+                *event_stream == stream && *event_index == index
+            })
+            .unwrap_or_else(|| {
+                panic!("The block did not emit an event at index {index} of stream {stream:?}")
+            });
+        bcs::from_bytes(&value).expect("Failed to deserialize event value")
+    }
+
+    /// Asserts that the block did not emit any events into `stream`.
+    fn assert_no_events(&self, stream: impl Into<StreamName>) {
+        let stream = stream.into();
+        let indices = self
+            .events()
+            .into_iter()
+            .filter(|(_, event_stream, _, _)| *event_stream == stream)
+            .map(|(_, _, index, _)| index)
+            .collect::<Vec<_>>();
+        assert!(
+            indices.is_empty(),
+            "The block emitted events at indices {indices:?} of stream {stream:?}"
+        );
+    }
+}
+
+impl BlockOutcome for ConfirmedBlockCertificate {
+    fn events(&self) -> Vec<(ApplicationId, StreamName, u32, Vec<u8>)> {
+        self.inner()
+            .block()
+            .body
+            .events
+            .iter()
+            .flatten()
+            .filter_map(|event| match event.stream_id.application_id {
+                GenericApplicationId::User(application_id) => Some((
+                    application_id,
+                    event.stream_id.stream_name.clone(),
+                    event.index,
+                    event.value.clone(),
+                )),
+                GenericApplicationId::System => None,
+            })
+            .collect()
     }
 }
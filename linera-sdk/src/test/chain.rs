@@ -18,22 +18,26 @@ use linera_base::{
     crypto::{AccountPublicKey, AccountSecretKey},
     data_types::{
         Amount, ApplicationDescription, Blob, BlockHeight, Bytecode, ChainDescription,
-        CompressedBytecode, Epoch,
+        CompressedBytecode, Epoch, Round,
     },
-    identifiers::{AccountOwner, ApplicationId, ChainId, ModuleId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, MessageId, ModuleId},
     vm::VmRuntime,
 };
-use linera_chain::{types::ConfirmedBlockCertificate, ChainExecutionContext};
+use linera_chain::{
+    data_types::{IncomingBundle, MessageAction, PostedMessage},
+    types::ConfirmedBlockCertificate,
+    ChainExecutionContext,
+};
 use linera_core::{data_types::ChainInfoQuery, worker::WorkerError};
 use linera_execution::{
     system::{SystemOperation, SystemQuery, SystemResponse},
-    ExecutionError, Operation, Query, QueryOutcome, QueryResponse,
+    ExecutionError, Message, Operation, Query, QueryOutcome, QueryResponse,
 };
 use linera_storage::Storage as _;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use tokio::{fs, sync::Mutex};
 
-use super::{BlockBuilder, TestValidator};
+use super::{BlockBuilder, ResourceUsage, TestValidator};
 use crate::{ContractAbi, ServiceAbi};
 
 /// A reference to a single microchain inside a [`TestValidator`].
@@ -108,6 +112,18 @@ impl ActiveChain {
             .expect("Active chains should be in an epoch")
     }
 
+    /// Returns the current consensus round of this microchain, in which the next block can be
+    /// proposed with [`BlockBuilder::with_round`].
+    pub async fn current_round(&self) -> Round {
+        self.validator
+            .worker()
+            .chain_state_view(self.id())
+            .await
+            .expect("Failed to load chain")
+            .manager
+            .current_round()
+    }
+
     /// Reads the current shared balance available to all of the owners of this microchain.
     pub async fn chain_balance(&self) -> Amount {
         let query = Query::System(SystemQuery);
@@ -243,6 +259,48 @@ impl ActiveChain {
         self.try_add_block_with_blobs(block_builder, vec![]).await
     }
 
+    /// Adds a block to this microchain, and returns the resources used to execute it.
+    ///
+    /// The `block_builder` parameter is a closure that should use the [`BlockBuilder`] parameter
+    /// to provide the block's contents. The block is executed twice, to measure its resource
+    /// usage.
+    ///
+    /// This allows writing tests that fail if the cost of an application increases:
+    ///
+    /// ```ignore
+    /// let (_certificate, usage) = chain
+    ///     .add_block_with_resource_usage(|block| {
+    ///         block.with_operation(application_id, operation);
+    ///     })
+    ///     .await;
+    /// assert!(usage.fuel < 2_000_000);
+    /// ```
+    pub async fn add_block_with_resource_usage(
+        &self,
+        block_builder: impl FnOnce(&mut BlockBuilder),
+    ) -> (ConfirmedBlockCertificate, ResourceUsage) {
+        self.try_add_block_with_resource_usage(block_builder)
+            .await
+            .expect("Failed to execute block.")
+    }
+
+    /// Tries to add a block to this microchain, and returns the resources used to execute it.
+    ///
+    /// The `block_builder` parameter is a closure that should use the [`BlockBuilder`] parameter
+    /// to provide the block's contents.
+    pub async fn try_add_block_with_resource_usage(
+        &self,
+        block_builder: impl FnOnce(&mut BlockBuilder),
+    ) -> Result<(ConfirmedBlockCertificate, ResourceUsage), WorkerError> {
+        let (certificate, resource_usage) = self
+            .try_add_block_with_options(block_builder, vec![], true)
+            .await?;
+        Ok((
+            certificate,
+            resource_usage.expect("Resource usage should be measured"),
+        ))
+    }
+
     /// Tries to add a block to this microchain, writing some `blobs` to storage if needed.
     ///
     /// The `block_builder` parameter is a closure that should use the [`BlockBuilder`] parameter
@@ -255,10 +313,24 @@ impl ActiveChain {
         block_builder: impl FnOnce(&mut BlockBuilder),
         blobs: Vec<Blob>,
     ) -> Result<ConfirmedBlockCertificate, WorkerError> {
+        let (certificate, _) = self
+            .try_add_block_with_options(block_builder, blobs, false)
+            .await?;
+        Ok(certificate)
+    }
+
+    /// Tries to add a block to this microchain, writing some `blobs` to storage if needed, and
+    /// measuring its resource usage if `measure_resource_usage` is set.
+    async fn try_add_block_with_options(
+        &self,
+        block_builder: impl FnOnce(&mut BlockBuilder),
+        blobs: Vec<Blob>,
+        measure_resource_usage: bool,
+    ) -> Result<(ConfirmedBlockCertificate, Option<ResourceUsage>), WorkerError> {
         let mut tip = self.tip.lock().await;
         let mut block = BlockBuilder::new(
             self.description.id(),
-            self.key_pair.public().into(),
+            self.key_pair.copy(),
             self.epoch().await,
             tip.as_ref(),
             self.validator.clone(),
@@ -267,18 +339,14 @@ impl ActiveChain {
         block_builder(&mut block);
 
         // TODO(#2066): Remove boxing once call-stack is shallower
-        let certificate = Box::pin(block.try_sign(&blobs)).await?;
+        let (certificate, resource_usage) =
+            Box::pin(block.try_sign(&blobs, measure_resource_usage)).await?;
 
-        let result = self
-            .validator
-            .worker()
-            .fully_handle_certificate_with_notifications(certificate.clone(), &())
-            .await;
+        let result = self.validator.handle_certificate(certificate.clone()).await;
         if let Err(WorkerError::BlobsNotFound(_)) = &result {
             self.validator.storage().maybe_write_blobs(&blobs).await?;
             self.validator
-                .worker()
-                .fully_handle_certificate_with_notifications(certificate.clone(), &())
+                .handle_certificate(certificate.clone())
                 .await
                 .expect("Rejected certificate");
         } else {
@@ -287,14 +355,118 @@ impl ActiveChain {
 
         *tip = Some(certificate.clone());
 
-        Ok(certificate)
+        Ok((certificate, resource_usage))
     }
 
     /// Receives all queued messages in all inboxes of this microchain.
     ///
     /// Adds a block to this microchain that receives all queued messages in the microchains
     /// inboxes.
+    ///
+    /// The messages held by the validator because message delivery is paused are not
+    /// received.
     pub async fn handle_received_messages(&self) {
+        let messages = self.inbox_bundles().await;
+
+        self.add_block(|block| {
+            block.with_incoming_bundles(messages);
+        })
+        .await;
+    }
+
+    /// Returns the messages sent to this microchain that have not been received in a block
+    /// yet.
+    ///
+    /// These are the messages in the inboxes of this microchain, followed by the messages
+    /// held by the validator since [`TestValidator::pause_message_delivery`] was called, in
+    /// the order they were sent.
+    pub async fn pending_messages(&self) -> Vec<PendingMessage> {
+        let mut messages = self
+            .inbox_bundles()
+            .await
+            .iter()
+            .flat_map(|bundle| {
+                bundle
+                    .messages_and_ids()
+                    .map(|(id, message)| PendingMessage {
+                        id,
+                        held: false,
+                        message: message.clone(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        messages.extend(self.validator.held_messages(self.id()));
+        messages
+    }
+
+    /// Adds a block to this microchain that receives the pending message `message_id`.
+    ///
+    /// Messages are received in bundles, so the block also receives the other messages
+    /// sent to this microchain by the same transaction. The pending messages sent earlier by
+    /// the same chain are received first, and the messages held by the validator are
+    /// delivered to the inbox beforehand.
+    pub async fn receive_message(&self, message_id: MessageId) -> ConfirmedBlockCertificate {
+        self.try_receive_message(message_id)
+            .await
+            .expect("Failed to execute block.")
+    }
+
+    /// Tries to add a block to this microchain that receives the pending message
+    /// `message_id`, as [`ActiveChain::receive_message`] does.
+    pub async fn try_receive_message(
+        &self,
+        message_id: MessageId,
+    ) -> Result<ConfirmedBlockCertificate, WorkerError> {
+        self.try_receive_message_by_action(message_id, MessageAction::Accept)
+            .await
+    }
+
+    /// Adds a block to this microchain that rejects the pending message `message_id`, along
+    /// with the other messages of its bundle.
+    ///
+    /// The rejected messages that are tracked bounce back to their sender. The pending
+    /// messages sent earlier by the same chain are accepted, as with
+    /// [`ActiveChain::receive_message`].
+    pub async fn reject_message(&self, message_id: MessageId) -> ConfirmedBlockCertificate {
+        self.try_receive_message_by_action(message_id, MessageAction::Reject)
+            .await
+            .expect("Failed to execute block.")
+    }
+
+    /// Tries to add a block to this microchain that receives the bundle with the pending
+    /// message `message_id` with the given `action`.
+    async fn try_receive_message_by_action(
+        &self,
+        message_id: MessageId,
+        action: MessageAction,
+    ) -> Result<ConfirmedBlockCertificate, WorkerError> {
+        let chain_id = self.id();
+        self.validator
+            .deliver_held_message(chain_id, message_id)
+            .await?;
+        let mut bundles = self
+            .inbox_bundles()
+            .await
+            .into_iter()
+            .filter(|bundle| bundle.origin == message_id.chain_id)
+            .collect::<Vec<_>>();
+        let position = bundles
+            .iter()
+            .position(|bundle| bundle.messages_and_ids().any(|(id, _)| id == message_id))
+            .unwrap_or_else(|| panic!("Message {message_id:?} is not pending on chain {chain_id}"));
+        bundles.truncate(position + 1);
+        if bundles[position].action == MessageAction::Accept {
+            bundles[position].action = action;
+        }
+
+        self.try_add_block(|block| {
+            block.with_incoming_bundles(bundles);
+        })
+        .await
+    }
+
+    /// Returns the message bundles in the inboxes of this microchain.
+    async fn inbox_bundles(&self) -> Vec<IncomingBundle> {
         let chain_id = self.id();
         let (information, _) = self
             .validator
@@ -302,12 +474,9 @@ impl ActiveChain {
             .handle_chain_info_query(ChainInfoQuery::new(chain_id).with_pending_message_bundles())
             .await
             .expect("Failed to query chain's pending messages");
-        let messages = information.info.requested_pending_message_bundles;
-
-        self.add_block(|block| {
-            block.with_incoming_bundles(messages);
-        })
-        .await;
+        let mut bundles = information.info.requested_pending_message_bundles;
+        self.validator.reject_dropped_messages(&mut bundles);
+        bundles
     }
 
     /// Processes all new events from streams this chain subscribes to.
@@ -501,6 +670,16 @@ impl ActiveChain {
         Ok(output_path)
     }
 
+    /// Returns the certificate of the last block added to this microchain, if any.
+    pub(crate) async fn tip(&self) -> Option<ConfirmedBlockCertificate> {
+        self.tip.lock().await.clone()
+    }
+
+    /// Replaces the certificate of the last block added to this microchain.
+    pub(crate) async fn set_tip(&self, tip: Option<ConfirmedBlockCertificate>) {
+        *self.tip.lock().await = tip;
+    }
+
     /// Returns the height of the tip of this microchain.
     pub async fn get_tip_height(&self) -> BlockHeight {
         self.tip
@@ -748,6 +927,44 @@ pub enum TryQueryError {
     Execution(#[from] WorkerError),
 }
 
+/// A message sent to a chain that has not been received in a block yet.
+#[derive(Clone, Debug)]
+pub struct PendingMessage {
+    /// The ID of the message.
+    pub id: MessageId,
+    /// Whether the message is held by the validator because message delivery is paused,
+    /// instead of being in the inbox of the chain.
+    pub held: bool,
+    /// The message, with its kind and authentication.
+    pub message: PostedMessage,
+}
+
+impl PendingMessage {
+    /// Returns the ID of the application that sent the message, or `None` for system
+    /// messages.
+    pub fn application_id(&self) -> Option<ApplicationId> {
+        match &self.message.message {
+            Message::User { application_id, .. } => Some(*application_id),
+            Message::System(_) => None,
+        }
+    }
+
+    /// Deserializes the message of an application, or returns `None` for system messages.
+    ///
+    /// # Panics
+    ///
+    /// If the message is not of type `M`, for instance because it was sent by another
+    /// application.
+    pub fn user_message<M: DeserializeOwned>(&self) -> Option<M> {
+        match &self.message.message {
+            Message::User { bytes, .. } => {
+                Some(bcs::from_bytes(bytes).expect("Failed to deserialize application message"))
+            }
+            Message::System(_) => None,
+        }
+    }
+}
+
 /// Failure to perform a GraphQL query on an application on a chain.
 #[derive(Debug, thiserror::Error)]
 pub enum TryGraphQLQueryError {
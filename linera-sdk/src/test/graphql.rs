@@ -0,0 +1,237 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to run GraphQL requests on a service in unit tests, using a
+//! [`MockServiceRuntime`].
+
+use async_graphql::{Request, Response, Variables};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    service::{MockServiceRuntime, ScheduledOperations},
+    util::BlockingWait as _,
+    Service,
+};
+
+/// Creates an instance of the service `S` using `runtime`, executes the GraphQL `query`, and
+/// returns the data of the response.
+///
+/// Panics if the response has errors. Use a [`GraphQLTester`] to run several requests on the
+/// same service instance, to check errors, or to retrieve scheduled operations.
+pub fn serve_graphql<S>(runtime: MockServiceRuntime<S>, query: &str) -> serde_json::Value
+where
+    S: Service<Query = Request, QueryResponse = Response>,
+{
+    GraphQLTester::new(runtime).query(query)
+}
+
+/// An instance of a service whose queries are GraphQL requests, to run them in unit tests.
+pub struct GraphQLTester<S> {
+    service: S,
+    scheduled_operations: ScheduledOperations,
+}
+
+impl<S> GraphQLTester<S>
+where
+    S: Service<Query = Request, QueryResponse = Response>,
+{
+    /// Creates an instance of the service `S` using `runtime`.
+    pub fn new(runtime: MockServiceRuntime<S>) -> Self {
+        let scheduled_operations = runtime.scheduled_operations_handle();
+        GraphQLTester {
+            service: S::new(runtime).blocking_wait(),
+            scheduled_operations,
+        }
+    }
+
+    /// Returns the service instance.
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+
+    /// Executes a GraphQL `request` and returns the response, including its errors.
+    pub fn execute(&self, request: impl Into<Request>) -> Response {
+        self.service.handle_query(request.into()).blocking_wait()
+    }
+
+    /// Executes the GraphQL `query` and returns the data of the response.
+    ///
+    /// Panics if the response has errors.
+    pub fn query(&self, query: &str) -> serde_json::Value {
+        self.query_with_variables(query, serde_json::Value::Null)
+    }
+
+    /// Executes the GraphQL `query` with the given `variables`, specified as a JSON object,
+    /// and returns the data of the response.
+    ///
+    /// Panics if the response has errors.
+    pub fn query_with_variables(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> serde_json::Value {
+        let request = Request::new(query).variables(Variables::from_json(variables));
+        let response = self.execute(request);
+        assert!(
+            response.errors.is_empty(),
+            "Unexpected errors in the response to GraphQL query {query:?}: {:?}",
+            response.errors
+        );
+        response
+            .data
+            .into_json()
+            .expect("GraphQL response data should be valid JSON")
+    }
+
+    /// Executes the GraphQL `query` and returns the messages of the errors in the response.
+    pub fn query_errors(&self, query: &str) -> Vec<String> {
+        self.execute(query)
+            .errors
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    /// Executes the GraphQL `query` and asserts that the response has an error whose message
+    /// contains `expected_message`.
+    pub fn assert_query_error(&self, query: &str, expected_message: &str) {
+        let errors = self.query_errors(query);
+        assert!(
+            errors
+                .iter()
+                .any(|message| message.contains(expected_message)),
+            "Expected an error containing {expected_message:?} in the response to GraphQL \
+            query {query:?}, got {errors:?}"
+        );
+    }
+
+    /// Returns the operations scheduled by the service since the last call to this method or
+    /// to [`GraphQLTester::scheduled_operations`], as serialized bytes.
+    pub fn raw_scheduled_operations(&self) -> Vec<Vec<u8>> {
+        self.scheduled_operations.take_raw()
+    }
+
+    /// Returns the operations scheduled by the service since the last call to this method or
+    /// to [`GraphQLTester::raw_scheduled_operations`], deserialized using BCS.
+    pub fn scheduled_operations<Operation: DeserializeOwned>(&self) -> Vec<Operation> {
+        self.scheduled_operations.take()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::sync::Arc;
+
+    use async_graphql::{EmptySubscription, Object, Schema};
+    use linera_base::abi::{ServiceAbi, WithServiceAbi};
+    use serde_json::json;
+
+    use super::*;
+
+    /// A service with a GraphQL schema, used to test the [`GraphQLTester`].
+    struct CounterService {
+        runtime: Arc<MockServiceRuntime<Self>>,
+    }
+
+    struct CounterAbi;
+
+    impl ServiceAbi for CounterAbi {
+        type Query = Request;
+        type QueryResponse = Response;
+    }
+
+    impl WithServiceAbi for CounterService {
+        type Abi = CounterAbi;
+    }
+
+    impl Service for CounterService {
+        type Parameters = ();
+
+        async fn new(runtime: MockServiceRuntime<Self>) -> Self {
+            CounterService {
+                runtime: Arc::new(runtime),
+            }
+        }
+
+        async fn handle_query(&self, request: Request) -> Response {
+            let schema = Schema::build(
+                QueryRoot,
+                MutationRoot {
+                    runtime: self.runtime.clone(),
+                },
+                EmptySubscription,
+            )
+            .finish();
+            schema.execute(request).await
+        }
+    }
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn double(&self, value: u64) -> async_graphql::Result<u64> {
+            if value == 0 {
+                return Err(async_graphql::Error::new("Value must not be zero"));
+            }
+            Ok(2 * value)
+        }
+    }
+
+    struct MutationRoot {
+        runtime: Arc<MockServiceRuntime<CounterService>>,
+    }
+
+    #[Object]
+    impl MutationRoot {
+        async fn increment(&self, value: u64) -> [u8; 0] {
+            self.runtime.schedule_operation(&value);
+            []
+        }
+    }
+
+    #[test]
+    fn test_serve_graphql() {
+        let response = serve_graphql(
+            MockServiceRuntime::<CounterService>::new(),
+            "query { double(value: 21) }",
+        );
+
+        assert_eq!(response, json!({ "double": 42 }));
+    }
+
+    #[test]
+    fn test_query_with_variables() {
+        let tester = GraphQLTester::new(MockServiceRuntime::<CounterService>::new());
+
+        let response = tester.query_with_variables(
+            "query Double($value: Int!) { double(value: $value) }",
+            json!({ "value": 5 }),
+        );
+
+        assert_eq!(response, json!({ "double": 10 }));
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let tester = GraphQLTester::new(MockServiceRuntime::<CounterService>::new());
+
+        tester.assert_query_error("query { double(value: 0) }", "must not be zero");
+        assert!(tester.query_errors("query { double(value: 1) }").is_empty());
+        assert!(!tester.query_errors("query { triple(value: 1) }").is_empty());
+    }
+
+    #[test]
+    fn test_mutation_schedules_operations() {
+        let tester = GraphQLTester::new(MockServiceRuntime::<CounterService>::new());
+
+        tester.query("mutation { increment(value: 3) }");
+        tester.query_with_variables(
+            "mutation Increment($value: Int!) { increment(value: $value) }",
+            json!({ "value": 4 }),
+        );
+
+        assert_eq!(tester.scheduled_operations::<u64>(), vec![3, 4]);
+        assert!(tester.raw_scheduled_operations().is_empty());
+    }
+}
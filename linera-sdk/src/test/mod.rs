@@ -14,6 +14,8 @@
 mod block;
 #[cfg(with_integration_testing)]
 mod chain;
+#[cfg(with_testing)]
+mod graphql;
 mod mock_stubs;
 #[cfg(with_integration_testing)]
 mod validator;
@@ -21,19 +23,29 @@ mod validator;
 #[cfg(with_integration_testing)]
 pub use {
     linera_chain::{
-        data_types::MessageAction, test::HttpServer, ChainError, ChainExecutionContext,
+        data_types::{MessageAction, PostedMessage},
+        test::HttpServer,
+        ChainError, ChainExecutionContext,
     },
     linera_core::worker::WorkerError,
-    linera_execution::{system::Recipient, ExecutionError, QueryOutcome, WasmExecutionError},
+    linera_execution::{
+        system::Recipient, ExecutionError, QueryOutcome, ResourceControlPolicy, WasmExecutionError,
+    },
+    linera_views::storage_stats::StorageStats,
 };
 
-#[cfg(with_testing)]
-pub use self::mock_stubs::*;
 #[cfg(with_integration_testing)]
 pub use self::{
-    block::BlockBuilder,
-    chain::{ActiveChain, TryGraphQLMutationError, TryGraphQLQueryError, TryQueryError},
-    validator::TestValidator,
+    block::{BlockBuilder, BlockOutcome, ResourceUsage},
+    chain::{
+        ActiveChain, PendingMessage, TryGraphQLMutationError, TryGraphQLQueryError, TryQueryError,
+    },
+    validator::{InterceptAction, OutgoingMessageInfo, TestValidator, ValidatorSnapshot},
+};
+#[cfg(with_testing)]
+pub use self::{
+    graphql::{serve_graphql, GraphQLTester},
+    mock_stubs::*,
 };
 use crate::{Contract, ContractRuntime, Service, ServiceRuntime};
 
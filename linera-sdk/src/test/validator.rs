@@ -6,7 +6,11 @@
 //! The [`TestValidator`] is a minimal validator with a single shard. Micro-chains can be added to
 //! it, and blocks can be added to each microchain individually.
 
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    num::NonZeroUsize,
+    sync::{self, Arc},
+};
 
 use dashmap::DashMap;
 use futures::{
@@ -14,26 +18,37 @@ use futures::{
     FutureExt as _,
 };
 use linera_base::{
-    crypto::{AccountSecretKey, ValidatorKeypair, ValidatorSecretKey},
+    crypto::{AccountSecretKey, CryptoRng, ValidatorKeypair, ValidatorSecretKey},
     data_types::{
-        Amount, ApplicationPermissions, Blob, BlobContent, ChainDescription, ChainOrigin, Epoch,
-        InitialChainConfig, Timestamp,
+        Amount, ApplicationPermissions, Blob, BlobContent, BlockHeight, ChainDescription,
+        ChainOrigin, Epoch, InitialChainConfig, TimeDelta, Timestamp,
     },
-    identifiers::{AccountOwner, ApplicationId, ChainId, ModuleId},
+    http,
+    identifiers::{AccountOwner, ApplicationId, ChainId, MessageId, ModuleId},
     ownership::ChainOwnership,
 };
-use linera_core::worker::WorkerState;
+use linera_chain::{
+    data_types::{IncomingBundle, MessageAction, MessageBundle, PostedMessage},
+    types::ConfirmedBlockCertificate,
+};
+use linera_core::{
+    data_types::CrossChainRequest,
+    worker::{WorkerError, WorkerState},
+};
 use linera_execution::{
     committee::Committee,
     system::{AdminOperation, OpenChainConfig, SystemOperation},
-    ResourceControlPolicy, WasmRuntime,
+    ExecutionError, ExecutionRuntimeConfig, Message, ResourceControlPolicy, WasmRuntime,
 };
 use linera_storage::{DbStorage, Storage, TestClock};
-use linera_views::memory::MemoryStore;
+use linera_views::{
+    memory::{MemoryStore, MemoryStoreSnapshot},
+    storage_stats::{StorageStats, StorageStatsCollector, StorageStatsStore},
+};
 use serde::Serialize;
 
-use super::ActiveChain;
-use crate::ContractAbi;
+use super::{ActiveChain, PendingMessage};
+use crate::{ContractAbi, DataBlobHash};
 
 /// A minimal validator implementation suited for tests.
 ///
@@ -52,13 +67,22 @@ pub struct TestValidator {
     validator_secret: ValidatorSecretKey,
     account_secret: AccountSecretKey,
     committee: Arc<Mutex<(Epoch, Committee)>>,
-    storage: DbStorage<MemoryStore, TestClock>,
-    worker: WorkerState<DbStorage<MemoryStore, TestClock>>,
+    storage: DbStorage<StorageStatsStore<MemoryStore>, TestClock>,
+    worker: WorkerState<DbStorage<StorageStatsStore<MemoryStore>, TestClock>>,
     clock: TestClock,
     admin_chain_id: ChainId,
     chains: Arc<DashMap<ChainId, ActiveChain>>,
+    http_mocks: Arc<sync::Mutex<HttpMocks>>,
+    held_messages: Arc<sync::Mutex<HeldMessages>>,
+    message_interceptor: Arc<sync::Mutex<Option<MessageInterceptor>>>,
+    intercepted_messages: Arc<sync::Mutex<InterceptedMessages>>,
+    owner_keys: Arc<DashMap<AccountOwner, AccountSecretKey>>,
+    rng: Arc<sync::Mutex<Box<dyn CryptoRng>>>,
 }
 
+/// A function deciding what happens to each cross-chain message.
+type MessageInterceptor = Box<dyn Fn(OutgoingMessageInfo) -> InterceptAction + Send + Sync>;
+
 impl Clone for TestValidator {
     fn clone(&self) -> Self {
         TestValidator {
@@ -70,6 +94,12 @@ impl Clone for TestValidator {
             worker: self.worker.clone(),
             clock: self.clock.clone(),
             chains: self.chains.clone(),
+            http_mocks: self.http_mocks.clone(),
+            held_messages: self.held_messages.clone(),
+            message_interceptor: self.message_interceptor.clone(),
+            intercepted_messages: self.intercepted_messages.clone(),
+            owner_keys: self.owner_keys.clone(),
+            rng: self.rng.clone(),
         }
     }
 }
@@ -77,17 +107,43 @@ impl Clone for TestValidator {
 impl TestValidator {
     /// Creates a new [`TestValidator`].
     pub async fn new() -> Self {
-        let validator_keypair = ValidatorKeypair::generate();
-        let account_secret = AccountSecretKey::generate();
+        Self::with_rng(None.into()).await
+    }
+
+    /// Creates a new [`TestValidator`] that generates all its keys from a random number
+    /// generator seeded with `seed`.
+    ///
+    /// The keys of the validator, of the admin chain's owner, and of the owners created by
+    /// [`TestValidator::new_chain`] and [`TestValidator::new_owner`] are derived from the seed.
+    /// So are the [`ChainId`]s of the chains, and the [`ApplicationId`]s of the applications
+    /// created on them, provided that the test creates the same chains and adds the same
+    /// blocks in the same order. The keys passed to [`TestValidator::new_chain_with_keypair`]
+    /// are not covered.
+    pub async fn with_seed(seed: u64) -> Self {
+        Self::with_rng(Some(seed).into()).await
+    }
+
+    /// Creates a new [`TestValidator`] that generates its keys using `rng`.
+    async fn with_rng(mut rng: Box<dyn CryptoRng>) -> Self {
+        let validator_keypair = ValidatorKeypair::generate_from(&mut rng);
+        let account_secret = AccountSecretKey::generate_from(&mut rng);
         let epoch = Epoch::ZERO;
         let committee = Committee::make_simple(vec![(
             validator_keypair.public_key,
             account_secret.public(),
         )]);
         let wasm_runtime = Some(WasmRuntime::default());
-        let storage = DbStorage::<MemoryStore, _>::make_test_storage(wasm_runtime)
-            .now_or_never()
-            .expect("execution of DbStorage::new should not await anything");
+        let mut storage =
+            DbStorage::<StorageStatsStore<MemoryStore>, _>::make_test_storage(wasm_runtime)
+                .now_or_never()
+                .expect("execution of DbStorage::new should not await anything");
+        let http_mocks = Arc::new(sync::Mutex::new(HttpMocks::default()));
+        let handler_mocks = http_mocks.clone();
+        storage.set_execution_runtime_config(ExecutionRuntimeConfig {
+            http_request_handler: Some(Arc::new(move |request: &http::Request| {
+                handler_mocks.lock().unwrap().respond(request)
+            })),
+        });
         let clock = storage.clock().clone();
         let worker = WorkerState::new(
             "Single validator node".to_string(),
@@ -97,7 +153,7 @@ impl TestValidator {
         );
 
         // Create an admin chain.
-        let key_pair = AccountSecretKey::generate();
+        let key_pair = AccountSecretKey::generate_from(&mut rng);
 
         let new_chain_config = InitialChainConfig {
             ownership: ChainOwnership::single(key_pair.public().into()),
@@ -132,8 +188,17 @@ impl TestValidator {
             clock,
             admin_chain_id,
             chains: Arc::default(),
+            http_mocks,
+            held_messages: Arc::default(),
+            message_interceptor: Arc::default(),
+            intercepted_messages: Arc::default(),
+            owner_keys: Arc::default(),
+            rng: Arc::new(sync::Mutex::new(rng)),
         };
 
+        validator
+            .owner_keys
+            .insert(key_pair.public().into(), key_pair.copy());
         let chain = ActiveChain::new(key_pair, description.clone(), validator.clone());
 
         validator.chains.insert(description.id(), chain);
@@ -187,12 +252,14 @@ impl TestValidator {
     }
 
     /// Returns this validator's storage.
-    pub(crate) fn storage(&self) -> &DbStorage<MemoryStore, TestClock> {
+    pub(crate) fn storage(&self) -> &DbStorage<StorageStatsStore<MemoryStore>, TestClock> {
         &self.storage
     }
 
     /// Returns the locked [`WorkerState`] of this validator.
-    pub(crate) fn worker(&self) -> WorkerState<DbStorage<MemoryStore, TestClock>> {
+    pub(crate) fn worker(
+        &self,
+    ) -> WorkerState<DbStorage<StorageStatsStore<MemoryStore>, TestClock>> {
         self.worker.clone()
     }
 
@@ -201,11 +268,236 @@ impl TestValidator {
         &self.clock
     }
 
+    /// Sets the current time of this validator, which is the default timestamp of the blocks
+    /// created from now on.
+    ///
+    /// Blocks never get an earlier timestamp than the previous block of their chain, even if
+    /// the time is set backwards.
+    pub fn set_system_time(&self, timestamp: Timestamp) {
+        self.clock.set(timestamp);
+    }
+
+    /// Advances the current time of this validator by `delta`, which affects the default
+    /// timestamp of the blocks created from now on.
+    pub fn advance_time(&self, delta: TimeDelta) {
+        self.clock.add(delta);
+    }
+
+    /// Returns the statistics of the storage accesses of all the chains since this validator
+    /// was created, or since the last call to [`TestValidator::reset_storage_stats`].
+    pub fn storage_stats(&self) -> StorageStats {
+        self.storage_stats_collector().total()
+    }
+
+    /// Returns the statistics of the storage accesses of each view, indexed by the base key
+    /// of the view in the state of its chain.
+    ///
+    /// The accesses of the views of the applications are attributed to the view
+    /// holding the storage of each application.
+    pub fn storage_stats_by_view(&self) -> BTreeMap<Vec<u8>, StorageStats> {
+        self.storage_stats_collector().stats()
+    }
+
+    /// Resets the statistics of the storage accesses.
+    pub fn reset_storage_stats(&self) {
+        self.storage_stats_collector().reset();
+    }
+
+    fn storage_stats_collector(&self) -> &StorageStatsCollector {
+        self.storage
+            .storage_stats()
+            .expect("the storage of the test validator collects statistics")
+    }
+
     /// Returns the keys this test validator uses for signing certificates.
     pub fn key_pair(&self) -> &ValidatorSecretKey {
         &self.validator_secret
     }
 
+    /// Answers the HTTP requests of the applications with `handler`, unless they match one
+    /// of the responses added with [`TestValidator::expect_http_request`].
+    ///
+    /// Once a handler or an expected request is configured, no request reaches the network.
+    /// The hosts must still be allowed by the [`ResourceControlPolicy`].
+    pub fn set_http_handler(
+        &self,
+        handler: impl Fn(http::Request) -> http::Response + Send + Sync + 'static,
+    ) {
+        self.http_mocks.lock().unwrap().handler = Some(Box::new(handler));
+    }
+
+    /// Answers the HTTP requests of the applications whose URL satisfies `url_matcher` with
+    /// `response`.
+    ///
+    /// The expected requests are checked in the order they were added, before the handler
+    /// configured with [`TestValidator::set_http_handler`]. Once any of them is configured,
+    /// the requests that are neither expected nor handled fail with
+    /// [`ExecutionError::UnexpectedHttpRequest`], which reports their URL.
+    pub fn expect_http_request(
+        &self,
+        url_matcher: impl Fn(&str) -> bool + Send + Sync + 'static,
+        response: http::Response,
+    ) {
+        self.http_mocks
+            .lock()
+            .unwrap()
+            .expected_requests
+            .push((Box::new(url_matcher), response));
+    }
+
+    /// Holds the cross-chain messages sent from now on, instead of delivering them to the
+    /// inboxes of their recipients.
+    ///
+    /// The held messages are listed by [`ActiveChain::pending_messages`]. They can be
+    /// received in any order with [`ActiveChain::receive_message`] and
+    /// [`ActiveChain::reject_message`], except that the messages from the same chain are
+    /// always received in the order they were sent.
+    pub fn pause_message_delivery(&self) {
+        self.held_messages.lock().unwrap().paused = true;
+    }
+
+    /// Delivers all the held messages to the inboxes of their recipients, and stops holding
+    /// the messages sent from now on.
+    pub async fn resume_message_delivery(&self) {
+        let requests = {
+            let mut held_messages = self.held_messages.lock().unwrap();
+            held_messages.paused = false;
+            held_messages.release_all()
+        };
+        for request in requests {
+            self.deliver(request)
+                .await
+                .expect("Failed to deliver held messages");
+        }
+    }
+
+    /// Calls `interceptor` for every cross-chain message sent from now on, before it reaches
+    /// the inbox of its recipient, to decide whether it is delivered, dropped, duplicated or
+    /// modified.
+    ///
+    /// A dropped message that is tracked is not removed: instead, its bundle is rejected when
+    /// the recipient receives it, so that it bounces back to its sender. The other messages
+    /// of that bundle are rejected too.
+    ///
+    /// Every message goes through the interceptor only once, even if message delivery is
+    /// paused.
+    pub fn set_message_interceptor(
+        &self,
+        interceptor: impl Fn(OutgoingMessageInfo) -> InterceptAction + Send + Sync + 'static,
+    ) {
+        *self.message_interceptor.lock().unwrap() = Some(Box::new(interceptor));
+    }
+
+    /// Removes the interceptor configured with [`TestValidator::set_message_interceptor`], so
+    /// that the messages sent from now on are delivered unchanged.
+    pub fn clear_message_interceptor(&self) {
+        *self.message_interceptor.lock().unwrap() = None;
+    }
+
+    /// Marks the `bundles` with messages dropped by the message interceptor to be rejected.
+    pub(crate) fn reject_dropped_messages(&self, bundles: &mut [IncomingBundle]) {
+        let intercepted_messages = self.intercepted_messages.lock().unwrap();
+        for bundle in bundles {
+            if bundle
+                .messages_and_ids()
+                .any(|(id, _)| intercepted_messages.dropped.contains(&id))
+            {
+                bundle.action = MessageAction::Reject;
+            }
+        }
+    }
+
+    /// Returns the messages to `recipient` that are held because message delivery is paused,
+    /// in the order they were sent.
+    pub(crate) fn held_messages(&self, recipient: ChainId) -> Vec<PendingMessage> {
+        self.held_messages.lock().unwrap().pending(recipient)
+    }
+
+    /// Delivers the message `message_id` to the inbox of `recipient` if it is held, along
+    /// with the other messages from the same block.
+    pub(crate) async fn deliver_held_message(
+        &self,
+        recipient: ChainId,
+        message_id: MessageId,
+    ) -> Result<(), WorkerError> {
+        let request = self
+            .held_messages
+            .lock()
+            .unwrap()
+            .release(recipient, message_id);
+        match request {
+            Some(request) => self.deliver(request).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Processes a confirmed `certificate`, and delivers the messages it sends unless
+    /// message delivery is paused.
+    pub(crate) async fn handle_certificate(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+    ) -> Result<(), WorkerError> {
+        let (_, actions) =
+            Box::pin(self.worker.handle_confirmed_certificate(certificate, None)).await?;
+        self.handle_cross_chain_requests(actions.cross_chain_requests)
+            .await
+    }
+
+    /// Handles a cross-chain `request` that delivers messages, even if message delivery is
+    /// paused.
+    async fn deliver(&self, request: CrossChainRequest) -> Result<(), WorkerError> {
+        let actions = Box::pin(self.worker.handle_cross_chain_request(request)).await?;
+        self.handle_cross_chain_requests(actions.cross_chain_requests)
+            .await
+    }
+
+    /// Handles the cross-chain `requests` and the ones they cause, holding the messages if
+    /// message delivery is paused.
+    async fn handle_cross_chain_requests(
+        &self,
+        requests: Vec<CrossChainRequest>,
+    ) -> Result<(), WorkerError> {
+        let mut requests = VecDeque::from(requests);
+        while let Some(request) = requests.pop_front() {
+            let request = self.intercept(request);
+            let Some(request) = request else {
+                continue;
+            };
+            let request = self.held_messages.lock().unwrap().hold(request);
+            let Some(request) = request else {
+                continue;
+            };
+            let actions = Box::pin(self.worker.handle_cross_chain_request(request)).await?;
+            requests.extend(actions.cross_chain_requests);
+        }
+        Ok(())
+    }
+
+    /// Applies the message interceptor to the messages of a cross-chain `request`, returning
+    /// `None` if no message is left to deliver.
+    fn intercept(&self, request: CrossChainRequest) -> Option<CrossChainRequest> {
+        let CrossChainRequest::UpdateRecipient {
+            sender,
+            recipient,
+            bundles,
+        } = request
+        else {
+            return Some(request);
+        };
+        let interceptor = self.message_interceptor.lock().unwrap();
+        let bundles = self.intercepted_messages.lock().unwrap().intercept(
+            sender,
+            recipient,
+            bundles,
+            interceptor.as_ref(),
+        );
+        (!bundles.is_empty()).then_some(CrossChainRequest::UpdateRecipient {
+            sender,
+            recipient,
+            bundles,
+        })
+    }
+
     /// Returns the ID of the admin chain.
     pub fn admin_chain_id(&self) -> ChainId {
         self.admin_chain_id
@@ -268,12 +560,91 @@ impl TestValidator {
         }
     }
 
+    /// Updates the admin chain, creating a new epoch with the given [`ResourceControlPolicy`].
+    ///
+    /// The blocks proposed from then on pay the fees of the `policy`, and are rejected if
+    /// they exceed its limits.
+    pub async fn set_resource_policy(&mut self, policy: ResourceControlPolicy) {
+        self.change_resource_control_policy(|current_policy| *current_policy = policy)
+            .await;
+    }
+
+    /// Publishes a data blob with the given `bytes`, and returns its hash.
+    ///
+    /// The blob is written directly to storage, so applications on any chain can read it in
+    /// subsequent blocks and queries.
+    pub async fn publish_data_blob(&self, bytes: impl Into<Vec<u8>>) -> DataBlobHash {
+        let blob = Blob::new_data(bytes.into());
+        self.storage
+            .write_blob(&blob)
+            .await
+            .expect("Should write data blob");
+        DataBlobHash(blob.id().hash)
+    }
+
     /// Creates a new microchain and returns the [`ActiveChain`] that can be used to add blocks to
     /// it with the given key pair.
     pub async fn new_chain_with_keypair(&self, key_pair: AccountSecretKey) -> ActiveChain {
-        let description = self
-            .request_new_chain_from_admin_chain(key_pair.public().into())
-            .await;
+        let owner = AccountOwner::from(key_pair.public());
+        self.owner_keys.insert(owner, key_pair.copy());
+        self.create_chain(key_pair, ChainOwnership::single(owner))
+            .await
+    }
+
+    /// Generates the key pair of a new owner, and returns the owner.
+    ///
+    /// The validator keeps the key pair, so that the owner can propose blocks on the chains
+    /// created with [`TestValidator::new_chain_with_ownership`].
+    pub fn new_owner(&self) -> AccountOwner {
+        let key_pair = self.generate_key_pair();
+        let owner = AccountOwner::from(key_pair.public());
+        self.owner_keys.insert(owner, key_pair);
+        owner
+    }
+
+    /// Creates a new microchain with the given `ownership`, and returns the [`ActiveChain`]
+    /// that can be used to add blocks to it.
+    ///
+    /// The `ownership` can be assembled and validated with [`ChainOwnership::builder`].
+    ///
+    /// All the owners must have been created with [`TestValidator::new_owner`]. The blocks
+    /// are proposed by the first super owner, or else by the first regular owner, unless
+    /// another owner is chosen with [`BlockBuilder::with_owner`][super::BlockBuilder::with_owner].
+    pub async fn new_chain_with_ownership(&self, ownership: ChainOwnership) -> ActiveChain {
+        let mut key_pairs = ownership
+            .all_owners()
+            .map(|owner| self.owner_key_pair(owner))
+            .collect::<Vec<_>>();
+        assert!(!key_pairs.is_empty(), "The chain should have an owner");
+        self.create_chain(key_pairs.swap_remove(0), ownership).await
+    }
+
+    /// Generates a new key pair using this validator's random number generator.
+    fn generate_key_pair(&self) -> AccountSecretKey {
+        AccountSecretKey::generate_from(&mut *self.rng.lock().unwrap())
+    }
+
+    /// Returns the key pair of an `owner` created by this validator.
+    pub(crate) fn owner_key_pair(&self, owner: &AccountOwner) -> AccountSecretKey {
+        self.owner_keys
+            .get(owner)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Owner {owner} is unknown to the validator, \
+                    please create it with `TestValidator::new_owner`"
+                )
+            })
+            .copy()
+    }
+
+    /// Creates a new microchain with the given `ownership`, whose blocks are proposed by
+    /// `key_pair` by default.
+    async fn create_chain(
+        &self,
+        key_pair: AccountSecretKey,
+        ownership: ChainOwnership,
+    ) -> ActiveChain {
+        let description = self.request_new_chain_from_admin_chain(ownership).await;
         let chain = ActiveChain::new(key_pair, description.clone(), self.clone());
 
         chain.handle_received_messages().await;
@@ -286,7 +657,7 @@ impl TestValidator {
     /// Creates a new microchain and returns the [`ActiveChain`] that can be used to add blocks to
     /// it.
     pub async fn new_chain(&self) -> ActiveChain {
-        let key_pair = AccountSecretKey::generate();
+        let key_pair = self.generate_key_pair();
         self.new_chain_with_keypair(key_pair).await
     }
 
@@ -298,7 +669,10 @@ impl TestValidator {
     /// Adds a block to the admin chain to create a new chain.
     ///
     /// Returns the [`ChainDescription`] of the new chain.
-    async fn request_new_chain_from_admin_chain(&self, owner: AccountOwner) -> ChainDescription {
+    async fn request_new_chain_from_admin_chain(
+        &self,
+        ownership: ChainOwnership,
+    ) -> ChainDescription {
         let admin_id = self.admin_chain_id;
         let admin_chain = self
             .chains
@@ -308,7 +682,7 @@ impl TestValidator {
         let (epoch, committee) = self.committee.lock().await.clone();
 
         let open_chain_config = OpenChainConfig {
-            ownership: ChainOwnership::single(owner),
+            ownership,
             balance: Amount::ZERO,
             application_permissions: ApplicationPermissions::default(),
         };
@@ -343,4 +717,354 @@ impl TestValidator {
     pub fn get_chain(&self, chain_id: &ChainId) -> ActiveChain {
         self.chains.get(chain_id).expect("Chain not found").clone()
     }
+
+    /// Captures the current state of this validator: the storage with all the chain states
+    /// and published blobs, the tips of the chains, the committee, the current time and the
+    /// held messages.
+    ///
+    /// The validator can be returned to this state any number of times with
+    /// [`TestValidator::restore`], to run several scenarios from the same setup. The snapshot
+    /// copies the data of the memory store.
+    pub async fn snapshot(&self) -> ValidatorSnapshot {
+        let chains = self
+            .chains
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect::<Vec<_>>();
+        let mut chain_tips = Vec::with_capacity(chains.len());
+        for chain in chains {
+            let tip = chain.tip().await;
+            chain_tips.push((chain, tip));
+        }
+        ValidatorSnapshot {
+            store: self.storage.store().inner().snapshot(),
+            committee: self.committee.lock().await.clone(),
+            time: self.clock.current_time(),
+            chains: chain_tips,
+            held_messages: self.held_messages.lock().unwrap().clone(),
+            intercepted_messages: self.intercepted_messages.lock().unwrap().clone(),
+        }
+    }
+
+    /// Returns this validator to the state captured in `snapshot`.
+    ///
+    /// The [`ActiveChain`]s obtained before the snapshot can still be used, and their next
+    /// blocks are added on top of the tips they had then. The chains created after the
+    /// snapshot no longer exist. The mocked HTTP responses and the storage statistics are
+    /// not affected.
+    pub async fn restore(&self, snapshot: &ValidatorSnapshot) {
+        self.storage.store().inner().restore(&snapshot.store);
+        self.worker.clear_chain_workers();
+        *self.committee.lock().await = snapshot.committee.clone();
+        self.clock.set(snapshot.time);
+        *self.held_messages.lock().unwrap() = snapshot.held_messages.clone();
+        *self.intercepted_messages.lock().unwrap() = snapshot.intercepted_messages.clone();
+        self.chains.clear();
+        for (chain, tip) in &snapshot.chains {
+            chain.set_tip(tip.clone()).await;
+            self.chains.insert(chain.id(), chain.clone());
+        }
+    }
+}
+
+/// The state of a [`TestValidator`], captured by [`TestValidator::snapshot`].
+pub struct ValidatorSnapshot {
+    /// The data of the storage.
+    store: MemoryStoreSnapshot,
+    /// The current epoch and committee.
+    committee: (Epoch, Committee),
+    /// The current time.
+    time: Timestamp,
+    /// The chains, with the certificates of their last blocks.
+    chains: Vec<(ActiveChain, Option<ConfirmedBlockCertificate>)>,
+    /// The held cross-chain messages.
+    held_messages: HeldMessages,
+    /// The record of the intercepted cross-chain messages.
+    intercepted_messages: InterceptedMessages,
+}
+
+/// The mocked HTTP responses of a [`TestValidator`].
+#[derive(Default)]
+struct HttpMocks {
+    /// The responses to the requests whose URL satisfies each matcher.
+    expected_requests: Vec<(Box<dyn Fn(&str) -> bool + Send + Sync>, http::Response)>,
+    /// The handler for the requests that were not expected.
+    handler: Option<Box<dyn Fn(http::Request) -> http::Response + Send + Sync>>,
+}
+
+impl HttpMocks {
+    /// Returns the mocked response to `request`, or `None` if nothing is mocked and the
+    /// request should be sent over the network.
+    fn respond(&self, request: &http::Request) -> Option<Result<http::Response, ExecutionError>> {
+        if self.expected_requests.is_empty() && self.handler.is_none() {
+            return None;
+        }
+        let expected_response = self
+            .expected_requests
+            .iter()
+            .find(|(url_matcher, _)| url_matcher(&request.url));
+        let response = match (expected_response, &self.handler) {
+            (Some((_, response)), _) => Ok(response.clone()),
+            (None, Some(handler)) => Ok(handler(request.clone())),
+            (None, None) => Err(ExecutionError::UnexpectedHttpRequest(request.url.clone())),
+        };
+        Some(response)
+    }
+}
+
+/// A cross-chain message about to be delivered, as passed to the interceptor configured with
+/// [`TestValidator::set_message_interceptor`].
+#[derive(Clone, Debug)]
+pub struct OutgoingMessageInfo {
+    /// The ID of the message.
+    pub id: MessageId,
+    /// The chain the message is sent to.
+    pub recipient: ChainId,
+    /// The message, with its kind and authentication.
+    pub message: PostedMessage,
+}
+
+impl OutgoingMessageInfo {
+    /// Returns the ID of the application that sent the message, or `None` for system
+    /// messages.
+    pub fn application_id(&self) -> Option<ApplicationId> {
+        match &self.message.message {
+            Message::User { application_id, .. } => Some(*application_id),
+            Message::System(_) => None,
+        }
+    }
+
+    /// Returns the serialized message of an application, or `None` for system messages.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match &self.message.message {
+            Message::User { bytes, .. } => Some(bytes),
+            Message::System(_) => None,
+        }
+    }
+}
+
+/// What happens to a cross-chain message, as decided by the interceptor configured with
+/// [`TestValidator::set_message_interceptor`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InterceptAction {
+    /// The message is delivered unchanged.
+    Deliver,
+    /// The message is not delivered. If it is tracked, it is rejected by its recipient instead
+    /// and bounces back to its sender.
+    Drop,
+    /// The message is delivered twice.
+    Duplicate,
+    /// The message of an application is delivered with these serialized bytes instead.
+    DeliverModified(Vec<u8>),
+}
+
+/// The record of the cross-chain messages that went through the message interceptor of a
+/// [`TestValidator`].
+#[derive(Clone, Default)]
+struct InterceptedMessages {
+    /// The sender, recipient, height and transaction index of every bundle that went through
+    /// the interceptor, so that the bundles sent again by the sender's outbox are ignored.
+    seen: HashSet<(ChainId, ChainId, BlockHeight, u32)>,
+    /// The dropped tracked messages, which must be rejected by their recipients.
+    dropped: HashSet<MessageId>,
+}
+
+impl InterceptedMessages {
+    /// Applies the `interceptor` to the messages of the `bundles` sent by `sender` to
+    /// `recipient`, and returns the bundles left to deliver.
+    fn intercept(
+        &mut self,
+        sender: ChainId,
+        recipient: ChainId,
+        bundles: Vec<(Epoch, MessageBundle)>,
+        interceptor: Option<&MessageInterceptor>,
+    ) -> Vec<(Epoch, MessageBundle)> {
+        let mut intercepted_bundles = Vec::with_capacity(bundles.len());
+        for (epoch, mut bundle) in bundles {
+            let key = (sender, recipient, bundle.height, bundle.transaction_index);
+            if self.seen.contains(&key) {
+                continue;
+            }
+            let Some(interceptor) = interceptor else {
+                intercepted_bundles.push((epoch, bundle));
+                continue;
+            };
+            self.seen.insert(key);
+            let mut messages = Vec::with_capacity(bundle.messages.len());
+            for mut message in bundle.messages {
+                let id = MessageId {
+                    chain_id: sender,
+                    height: bundle.height,
+                    index: message.index,
+                };
+                let info = OutgoingMessageInfo {
+                    id,
+                    recipient,
+                    message: message.clone(),
+                };
+                match interceptor(info) {
+                    InterceptAction::Deliver => messages.push(message),
+                    InterceptAction::Drop if message.is_tracked() => {
+                        self.dropped.insert(id);
+                        messages.push(message);
+                    }
+                    InterceptAction::Drop => {}
+                    InterceptAction::Duplicate => {
+                        messages.push(message.clone());
+                        messages.push(message);
+                    }
+                    InterceptAction::DeliverModified(new_bytes) => {
+                        let Message::User { bytes, .. } = &mut message.message else {
+                            panic!("Only the messages of applications can be modified, not {id:?}");
+                        };
+                        *bytes = new_bytes;
+                        messages.push(message);
+                    }
+                }
+            }
+            if !messages.is_empty() {
+                bundle.messages = messages;
+                intercepted_bundles.push((epoch, bundle));
+            }
+        }
+        intercepted_bundles
+    }
+}
+
+/// The cross-chain messages held by a [`TestValidator`] while message delivery is paused.
+#[derive(Clone, Default)]
+struct HeldMessages {
+    /// Whether the messages are held instead of being delivered.
+    paused: bool,
+    /// The held bundles, in the order they were sent.
+    bundles: Vec<HeldBundle>,
+    /// The sender, recipient, height and transaction index of every bundle that was ever
+    /// held, so that the bundles sent again by the sender's outbox are ignored.
+    seen: HashSet<(ChainId, ChainId, BlockHeight, u32)>,
+}
+
+/// A message bundle held by a [`TestValidator`].
+#[derive(Clone)]
+struct HeldBundle {
+    sender: ChainId,
+    recipient: ChainId,
+    epoch: Epoch,
+    bundle: MessageBundle,
+}
+
+impl HeldMessages {
+    /// Holds the bundles of `request` if message delivery is paused, or returns the request
+    /// so that it is handled.
+    fn hold(&mut self, request: CrossChainRequest) -> Option<CrossChainRequest> {
+        match request {
+            CrossChainRequest::UpdateRecipient {
+                sender,
+                recipient,
+                bundles,
+            } if self.paused => {
+                for (epoch, bundle) in bundles {
+                    let key = (sender, recipient, bundle.height, bundle.transaction_index);
+                    if self.seen.insert(key) {
+                        self.bundles.push(HeldBundle {
+                            sender,
+                            recipient,
+                            epoch,
+                            bundle,
+                        });
+                    }
+                }
+                None
+            }
+            request => Some(request),
+        }
+    }
+
+    /// Returns the held messages to `recipient`.
+    fn pending(&self, recipient: ChainId) -> Vec<PendingMessage> {
+        self.bundles
+            .iter()
+            .filter(|held| held.recipient == recipient)
+            .flat_map(|held| {
+                held.bundle.messages.iter().map(|message| PendingMessage {
+                    id: MessageId {
+                        chain_id: held.sender,
+                        height: held.bundle.height,
+                        index: message.index,
+                    },
+                    held: true,
+                    message: message.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Removes the held bundles sent to `recipient` by the block that sent `message_id`,
+    /// and returns the request delivering them, or `None` if the message is not held.
+    ///
+    /// Panics if messages from an earlier block of the same sender are still held.
+    fn release(&mut self, recipient: ChainId, message_id: MessageId) -> Option<CrossChainRequest> {
+        let sender = message_id.chain_id;
+        let is_from_sender =
+            |held: &HeldBundle| held.sender == sender && held.recipient == recipient;
+        let is_held = self.bundles.iter().any(|held| {
+            is_from_sender(held)
+                && held.bundle.height == message_id.height
+                && held
+                    .bundle
+                    .messages
+                    .iter()
+                    .any(|message| message.index == message_id.index)
+        });
+        if !is_held {
+            return None;
+        }
+        let first_height = self
+            .bundles
+            .iter()
+            .find(|held| is_from_sender(held))
+            .map(|held| held.bundle.height)
+            .expect("the message is held");
+        assert_eq!(
+            first_height, message_id.height,
+            "Messages from chain {sender} must be received in the order they were sent, \
+            but messages from height {first_height} are still held",
+        );
+        let (released, kept) = self.bundles.drain(..).partition::<Vec<_>, _>(|held| {
+            is_from_sender(held) && held.bundle.height == message_id.height
+        });
+        self.bundles = kept;
+        Some(CrossChainRequest::UpdateRecipient {
+            sender,
+            recipient,
+            bundles: released
+                .into_iter()
+                .map(|held| (held.epoch, held.bundle))
+                .collect(),
+        })
+    }
+
+    /// Removes all the held bundles, and returns the requests delivering them.
+    fn release_all(&mut self) -> Vec<CrossChainRequest> {
+        let mut bundles_by_route = Vec::<((ChainId, ChainId), Vec<(Epoch, MessageBundle)>)>::new();
+        for held in self.bundles.drain(..) {
+            let route = (held.sender, held.recipient);
+            match bundles_by_route
+                .iter_mut()
+                .find(|(other, _)| *other == route)
+            {
+                Some((_, bundles)) => bundles.push((held.epoch, held.bundle)),
+                None => bundles_by_route.push((route, vec![(held.epoch, held.bundle)])),
+            }
+        }
+        bundles_by_route
+            .into_iter()
+            .map(
+                |((sender, recipient), bundles)| CrossChainRequest::UpdateRecipient {
+                    sender,
+                    recipient,
+                    bundles,
+                },
+            )
+            .collect()
+    }
 }
@@ -56,6 +56,15 @@ pub type ReadGuardedView<'a, W> = linera_views::collection_view::ReadGuardedView
 /// WebAssembly-specific [`ViewStorageContext`].
 pub type RegisterView<T> = linera_views::register_view::RegisterView<ViewStorageContext, T>;
 
+/// An alias to [`linera_views::historical_register_view::HistoricalRegisterView`] that uses
+/// the WebAssembly-specific [`ViewStorageContext`].
+pub type HistoricalRegisterView<T> =
+    linera_views::historical_register_view::HistoricalRegisterView<ViewStorageContext, T>;
+
 /// An alias to [`linera_views::set_view::SetView`] that uses the WebAssembly-specific
 /// [`ViewStorageContext`].
 pub type SetView<W> = linera_views::set_view::SetView<ViewStorageContext, W>;
+
+/// An alias to [`linera_views::ttl_map_view::TtlMapView`] that uses the
+/// WebAssembly-specific [`ViewStorageContext`].
+pub type TtlMapView<K, V> = linera_views::ttl_map_view::TtlMapView<ViewStorageContext, K, V>;
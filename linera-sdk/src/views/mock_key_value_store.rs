@@ -12,33 +12,48 @@ use std::{
 };
 
 use futures::FutureExt as _;
+use linera_base::time::{timer, Duration};
 use linera_views::{
     batch::Batch,
-    memory::{create_test_memory_store, MemoryStore},
+    common::paginate,
     store::{ReadableKeyValueStore, WritableKeyValueStore},
+    test_utils::faulty_store::{FaultyMemoryStore, FaultyStoreError, StoreOperation},
 };
 
-/// A mock [`KeyValueStore`] implementation using a [`MemoryStore`].
+/// A mock [`KeyValueStore`] implementation using a [`FaultyMemoryStore`].
 pub(super) struct MockKeyValueStore {
-    store: MemoryStore,
+    store: FaultyMemoryStore,
     contains_key_promises: PromiseRegistry<bool>,
     contains_keys_promises: PromiseRegistry<Vec<bool>>,
     read_multi_promises: PromiseRegistry<Vec<Option<Vec<u8>>>>,
-    read_single_promises: PromiseRegistry<Option<Vec<u8>>>,
+    read_single_promises: PromiseRegistry<Result<Option<Vec<u8>>, FaultyStoreError>>,
     find_keys_promises: PromiseRegistry<Vec<Vec<u8>>>,
     find_key_values_promises: PromiseRegistry<Vec<(Vec<u8>, Vec<u8>)>>,
+    #[expect(clippy::type_complexity)]
+    find_keys_paginated_promises: PromiseRegistry<(Vec<Vec<u8>>, Option<Vec<u8>>)>,
+    #[expect(clippy::type_complexity)]
+    find_key_values_paginated_promises: PromiseRegistry<(Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>)>,
 }
 
 impl Default for MockKeyValueStore {
     fn default() -> Self {
+        MockKeyValueStore::new(FaultyMemoryStore::new_for_testing())
+    }
+}
+
+impl MockKeyValueStore {
+    /// Creates a mock using `store`, whose programmed faults and latency apply to the mock.
+    pub(super) fn new(store: FaultyMemoryStore) -> Self {
         MockKeyValueStore {
-            store: create_test_memory_store(),
+            store,
             contains_key_promises: PromiseRegistry::default(),
             contains_keys_promises: PromiseRegistry::default(),
             read_multi_promises: PromiseRegistry::default(),
             read_single_promises: PromiseRegistry::default(),
             find_keys_promises: PromiseRegistry::default(),
             find_key_values_promises: PromiseRegistry::default(),
+            find_keys_paginated_promises: PromiseRegistry::default(),
+            find_key_values_paginated_promises: PromiseRegistry::default(),
         }
     }
 }
@@ -77,6 +92,7 @@ impl MockKeyValueStore {
     pub(crate) fn contains_key_new(&self, key: &[u8]) -> u32 {
         self.contains_key_promises.register(
             self.store
+                .inner()
                 .contains_key(key)
                 .now_or_never()
                 .expect("Memory store should never wait for anything")
@@ -94,6 +110,7 @@ impl MockKeyValueStore {
     pub(crate) fn contains_keys_new(&self, keys: &[Vec<u8>]) -> u32 {
         self.contains_keys_promises.register(
             self.store
+                .inner()
                 .contains_keys(keys.to_vec())
                 .now_or_never()
                 .expect("Memory store should never wait for anything")
@@ -111,6 +128,7 @@ impl MockKeyValueStore {
     pub(crate) fn read_multi_values_bytes_new(&self, keys: &[Vec<u8>]) -> u32 {
         self.read_multi_promises.register(
             self.store
+                .inner()
                 .read_multi_values_bytes(keys.to_vec())
                 .now_or_never()
                 .expect("Memory store should never wait for anything")
@@ -127,17 +145,26 @@ impl MockKeyValueStore {
     /// Reads a value addressed by `key` from the storage, returning a promise to retrieve the
     /// final value.
     pub(crate) fn read_value_bytes_new(&self, key: &[u8]) -> u32 {
-        self.read_single_promises.register(
-            self.store
-                .read_value_bytes(key)
-                .now_or_never()
-                .expect("Memory store should never wait for anything")
-                .expect("Memory store should never fail"),
-        )
+        let value = self
+            .store
+            .check_fault(StoreOperation::ReadValueBytes)
+            .map(|()| {
+                self.store
+                    .inner()
+                    .read_value_bytes(key)
+                    .now_or_never()
+                    .expect("Memory store should never wait for anything")
+                    .expect("Memory store should never fail")
+            });
+        self.read_single_promises.register(value)
     }
 
-    /// Returns the value read from storage by the respective [`read_value_bytes_new`] call.
-    pub(crate) fn read_value_bytes_wait(&self, promise: u32) -> Option<Vec<u8>> {
+    /// Returns the value read from storage by the respective [`read_value_bytes_new`] call,
+    /// or the fault programmed for it.
+    pub(crate) fn read_value_bytes_wait(
+        &self,
+        promise: u32,
+    ) -> Result<Option<Vec<u8>>, FaultyStoreError> {
         self.read_single_promises.take(promise)
     }
 
@@ -146,6 +173,7 @@ impl MockKeyValueStore {
     pub(crate) fn find_keys_new(&self, key_prefix: &[u8]) -> u32 {
         self.find_keys_promises.register(
             self.store
+                .inner()
                 .find_keys_by_prefix(key_prefix)
                 .now_or_never()
                 .expect("Memory store should never wait for anything")
@@ -163,6 +191,7 @@ impl MockKeyValueStore {
     pub(crate) fn find_key_values_new(&self, key_prefix: &[u8]) -> u32 {
         self.find_key_values_promises.register(
             self.store
+                .inner()
                 .find_key_values_by_prefix(key_prefix)
                 .now_or_never()
                 .expect("Memory store should never wait for anything")
@@ -176,12 +205,86 @@ impl MockKeyValueStore {
         self.find_key_values_promises.take(promise)
     }
 
-    /// Writes a `batch` of operations to storage.
-    pub(crate) fn write_batch(&self, batch: Batch) {
+    /// Finds at most `limit` keys in the storage that start with `key_prefix` and come after
+    /// `start_after`, returning a promise to retrieve the final value.
+    pub(crate) fn find_keys_paginated_new(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u32,
+    ) -> u32 {
+        let keys = self
+            .store
+            .inner()
+            .find_keys_by_prefix(key_prefix)
+            .now_or_never()
+            .expect("Memory store should never wait for anything")
+            .expect("Memory store should never fail");
+        self.find_keys_paginated_promises.register(paginate(
+            keys,
+            Vec::as_slice,
+            start_after,
+            limit,
+        ))
+    }
+
+    /// Returns the page of keys found in storage by the respective
+    /// [`find_keys_paginated_new`] call.
+    pub(crate) fn find_keys_paginated_wait(&self, promise: u32) -> (Vec<Vec<u8>>, Option<Vec<u8>>) {
+        self.find_keys_paginated_promises.take(promise)
+    }
+
+    /// Finds at most `limit` key-value pairs in the storage in which the key starts with
+    /// `key_prefix` and comes after `start_after`, returning a promise to retrieve the final
+    /// value.
+    pub(crate) fn find_key_values_paginated_new(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u32,
+    ) -> u32 {
+        let key_values = self
+            .store
+            .inner()
+            .find_key_values_by_prefix(key_prefix)
+            .now_or_never()
+            .expect("Memory store should never wait for anything")
+            .expect("Memory store should never fail");
+        self.find_key_values_paginated_promises.register(paginate(
+            key_values,
+            |(key, _)| key.as_slice(),
+            start_after,
+            limit,
+        ))
+    }
+
+    /// Returns the page of key-value pairs found in storage by the respective
+    /// [`find_key_values_paginated_new`] call.
+    #[expect(clippy::type_complexity)]
+    pub(crate) fn find_key_values_paginated_wait(
+        &self,
+        promise: u32,
+    ) -> (Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>) {
+        self.find_key_values_paginated_promises.take(promise)
+    }
+
+    /// Writes a `batch` of operations to storage, unless a fault was programmed for it.
+    pub(crate) fn write_batch(&self, batch: Batch) -> Result<(), FaultyStoreError> {
+        self.store.check_fault(StoreOperation::WriteBatch)?;
         self.store
+            .inner()
             .write_batch(batch)
             .now_or_never()
             .expect("Memory store should never wait for anything")
             .expect("Memory store should never fail");
+        Ok(())
+    }
+
+    /// Waits for the latency programmed in the store.
+    pub(crate) async fn delay(&self) {
+        let latency = self.store.latency();
+        if latency > Duration::ZERO {
+            timer::sleep(latency).await;
+        }
     }
 }
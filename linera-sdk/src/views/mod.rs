@@ -7,7 +7,10 @@ mod aliases;
 #[cfg(with_testing)]
 mod mock_key_value_store;
 mod system_api;
+mod value_cache;
 
+#[cfg(with_testing)]
+pub use linera_views::test_utils::faulty_store::{FaultyMemoryStore, StoreOperation};
 pub use linera_views::{
     self,
     common::CustomSerialize,
@@ -17,8 +20,9 @@ pub use linera_views::{
 pub use self::{
     aliases::{
         ByteCollectionView, ByteMapView, ByteSetView, CollectionView, CustomCollectionView,
-        CustomMapView, CustomSetView, LogView, MapView, QueueView, ReadGuardedView, RegisterView,
-        SetView,
+        CustomMapView, CustomSetView, HistoricalRegisterView, LogView, MapView, QueueView,
+        ReadGuardedView, RegisterView, SetView, TtlMapView,
     },
-    system_api::{KeyValueStore, ViewStorageContext},
+    system_api::{KeyValueStore, ViewStorageContext, ViewStorageExtra},
+    value_cache::CacheStatistics,
 };
@@ -3,18 +3,25 @@
 
 //! Functions and types to interface with the system API available to application views.
 
-#[cfg(with_testing)]
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-use linera_base::ensure;
+use linera_base::{
+    data_types::{BlockHeight, Timestamp},
+    ensure,
+};
+#[cfg(with_testing)]
+use linera_views::test_utils::faulty_store::{FaultyMemoryStore, FaultyStoreError};
 use linera_views::{
     batch::Batch,
+    historical_register_view::BlockHeightProvider,
     store::{ReadableKeyValueStore, WithError, WritableKeyValueStore},
+    ttl_map_view::TimestampProvider,
 };
 use thiserror::Error;
 
 #[cfg(with_testing)]
 use super::mock_key_value_store::MockKeyValueStore;
+use super::value_cache::{CacheStatistics, ValueCache};
 use crate::{
     contract::wit::{
         base_runtime_api::{self as contract_wit},
@@ -36,6 +43,8 @@ const MAX_KEY_SIZE: usize = 900;
 #[derive(Clone)]
 pub struct KeyValueStore {
     wit_api: WitInterface,
+    /// The values read so far, if caching is enabled.
+    cache: Option<Arc<Mutex<ValueCache>>>,
 }
 
 #[cfg_attr(with_testing, allow(dead_code))]
@@ -44,6 +53,7 @@ impl KeyValueStore {
     pub(crate) fn for_contracts() -> Self {
         KeyValueStore {
             wit_api: WitInterface::Contract,
+            cache: None,
         }
     }
 
@@ -51,6 +61,26 @@ impl KeyValueStore {
     pub(crate) fn for_services() -> Self {
         KeyValueStore {
             wit_api: WitInterface::Service,
+            cache: None,
+        }
+    }
+
+    /// Returns a [`KeyValueStore`] that uses the service WIT interface and keeps the last
+    /// `max_entries` values read in memory.
+    pub(crate) fn for_services_cached(max_entries: usize) -> Self {
+        Self::for_services().with_cache(max_entries)
+    }
+
+    /// Returns a [`KeyValueStore`] that shares the storage with this instance but keeps
+    /// the last `max_entries` values read in memory.
+    ///
+    /// The cache is not updated by write operations from other instances, so it has to be
+    /// cleared with [`KeyValueStore::set_cache_block_height`] when the storage may have
+    /// changed.
+    pub(crate) fn with_cache(&self, max_entries: usize) -> Self {
+        KeyValueStore {
+            wit_api: self.wit_api.clone(),
+            cache: Some(Arc::new(Mutex::new(ValueCache::new(max_entries)))),
         }
     }
 
@@ -62,6 +92,20 @@ impl KeyValueStore {
                 store: Arc::new(MockKeyValueStore::default()),
                 read_only: true,
             },
+            cache: None,
+        }
+    }
+
+    /// Returns a new [`KeyValueStore`] that keeps the storage contents in `store`, so that the
+    /// faults and latency programmed in `store` apply to it.
+    #[cfg(with_testing)]
+    pub fn faulty_mock(store: FaultyMemoryStore) -> Self {
+        KeyValueStore {
+            wit_api: WitInterface::Mock {
+                store: Arc::new(MockKeyValueStore::new(store)),
+                read_only: true,
+            },
+            cache: None,
         }
     }
 
@@ -78,8 +122,116 @@ impl KeyValueStore {
                 store: store.clone(),
                 read_only: false,
             },
+            cache: None,
+        }
+    }
+}
+
+impl KeyValueStore {
+    /// Clears the cache if the values in it were read at a different block height, and
+    /// sets up the maximum number of values it keeps.
+    pub(crate) fn set_cache_block_height(&self, block_height: BlockHeight, max_entries: usize) {
+        if let Some(mut cache) = self.lock_cache() {
+            cache.set_block_height(block_height);
+            cache.set_max_entries(max_entries);
         }
     }
+
+    /// Returns the hit and miss counts of the cache, if caching is enabled.
+    pub fn cache_statistics(&self) -> Option<CacheStatistics> {
+        Some(self.lock_cache()?.statistics())
+    }
+
+    fn lock_cache(&self) -> Option<std::sync::MutexGuard<'_, ValueCache>> {
+        let cache = self.cache.as_ref()?;
+        Some(
+            cache.lock().expect(
+                "Mutex should never be poisoned because applications run in a single thread",
+            ),
+        )
+    }
+
+    /// Looks up the `keys` in the cache, returning the cached values and the indices of
+    /// the keys that were not found.
+    #[expect(clippy::type_complexity)]
+    fn cached_values(&self, keys: &[Vec<u8>]) -> (Vec<Option<Option<Vec<u8>>>>, Vec<usize>) {
+        let Some(mut cache) = self.lock_cache() else {
+            return (vec![None; keys.len()], (0..keys.len()).collect());
+        };
+        let values = keys.iter().map(|key| cache.get(key)).collect::<Vec<_>>();
+        let missing = values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| value.is_none().then_some(index))
+            .collect();
+        (values, missing)
+    }
+
+    /// Stores the values read from storage in the cache.
+    fn cache_values(&self, key_values: impl IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>) {
+        if let Some(mut cache) = self.lock_cache() {
+            for (key, value) in key_values {
+                cache.insert(key, value);
+            }
+        }
+    }
+}
+
+impl KeyValueStore {
+    /// Finds at most `limit` keys starting with `key_prefix`, in lexicographic order.
+    ///
+    /// As with [`ReadableKeyValueStore::find_keys_by_prefix`], the returned keys have the
+    /// `key_prefix` stripped. Only keys strictly greater than `start_after` are returned, and
+    /// if more keys remain after the page, the last returned key is provided so it can be
+    /// passed as `start_after` to fetch the next page.
+    ///
+    /// Every page is charged for all the keys starting with `key_prefix`, since they are all
+    /// scanned to produce it.
+    #[expect(clippy::type_complexity)]
+    pub async fn find_keys_by_prefix_paginated(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<(Vec<Vec<u8>>, Option<Vec<u8>>), KeyValueStoreError> {
+        ensure!(
+            key_prefix.len() <= MAX_KEY_SIZE,
+            KeyValueStoreError::KeyTooLong
+        );
+        ensure!(limit > 0, KeyValueStoreError::ZeroPageLimit);
+        let limit = u32::try_from(limit).unwrap_or(u32::MAX);
+        let promise =
+            self.wit_api
+                .find_keys_paginated_new(key_prefix, start_after.as_deref(), limit);
+        self.wit_api.wait().await;
+        Ok(self.wit_api.find_keys_paginated_wait(promise))
+    }
+
+    /// Finds at most `limit` key-value pairs whose keys start with `key_prefix`, in
+    /// lexicographic order of the keys.
+    ///
+    /// Pagination and the charged keys work the same way as in
+    /// [`KeyValueStore::find_keys_by_prefix_paginated`], and only the values in the page are
+    /// charged for.
+    #[expect(clippy::type_complexity)]
+    pub async fn find_key_values_by_prefix_paginated(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<Vec<u8>>,
+        limit: usize,
+    ) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>), KeyValueStoreError> {
+        ensure!(
+            key_prefix.len() <= MAX_KEY_SIZE,
+            KeyValueStoreError::KeyTooLong
+        );
+        ensure!(limit > 0, KeyValueStoreError::ZeroPageLimit);
+        let limit = u32::try_from(limit).unwrap_or(u32::MAX);
+        let promise =
+            self.wit_api
+                .find_key_values_paginated_new(key_prefix, start_after.as_deref(), limit);
+        self.wit_api.wait().await;
+        Ok(self.wit_api.find_key_values_paginated_wait(promise))
+    }
 }
 
 impl WithError for KeyValueStore {
@@ -93,9 +245,18 @@ pub enum KeyValueStoreError {
     #[error("Key too long")]
     KeyTooLong,
 
+    /// Page limit of zero
+    #[error("Paginated queries must request at least one entry")]
+    ZeroPageLimit,
+
     /// BCS serialization error.
     #[error(transparent)]
     BcsError(#[from] bcs::Error),
+
+    /// A fault of the mocked storage.
+    #[cfg(with_testing)]
+    #[error(transparent)]
+    FaultyStoreError(#[from] FaultyStoreError),
 }
 
 impl linera_views::store::KeyValueStoreError for KeyValueStoreError {
@@ -118,8 +279,11 @@ impl ReadableKeyValueStore for KeyValueStore {
             key.len() <= Self::MAX_KEY_SIZE,
             KeyValueStoreError::KeyTooLong
         );
+        if let Some(value) = self.lock_cache().and_then(|mut cache| cache.get(key)) {
+            return Ok(value.is_some());
+        }
         let promise = self.wit_api.contains_key_new(key);
-        yield_once().await;
+        self.wit_api.wait().await;
         Ok(self.wit_api.contains_key_wait(promise))
     }
 
@@ -130,9 +294,25 @@ impl ReadableKeyValueStore for KeyValueStore {
                 KeyValueStoreError::KeyTooLong
             );
         }
-        let promise = self.wit_api.contains_keys_new(&keys);
-        yield_once().await;
-        Ok(self.wit_api.contains_keys_wait(promise))
+        let (cached, missing) = self.cached_values(&keys);
+        let mut results = cached
+            .into_iter()
+            .map(|value| matches!(value, Some(Some(_))))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(results);
+        }
+        let missing_keys = missing
+            .iter()
+            .map(|index| keys[*index].clone())
+            .collect::<Vec<_>>();
+        let promise = self.wit_api.contains_keys_new(&missing_keys);
+        self.wit_api.wait().await;
+        let found = self.wit_api.contains_keys_wait(promise);
+        for (index, is_present) in missing.into_iter().zip(found) {
+            results[index] = is_present;
+        }
+        Ok(results)
     }
 
     async fn read_multi_values_bytes(
@@ -145,9 +325,23 @@ impl ReadableKeyValueStore for KeyValueStore {
                 KeyValueStoreError::KeyTooLong
             );
         }
-        let promise = self.wit_api.read_multi_values_bytes_new(&keys);
-        yield_once().await;
-        Ok(self.wit_api.read_multi_values_bytes_wait(promise))
+        let (cached, missing) = self.cached_values(&keys);
+        let mut values = cached.into_iter().map(Option::flatten).collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(values);
+        }
+        let missing_keys = missing
+            .iter()
+            .map(|index| keys[*index].clone())
+            .collect::<Vec<_>>();
+        let promise = self.wit_api.read_multi_values_bytes_new(&missing_keys);
+        self.wit_api.wait().await;
+        let read = self.wit_api.read_multi_values_bytes_wait(promise);
+        self.cache_values(missing_keys.into_iter().zip(read.iter().cloned()));
+        for (index, value) in missing.into_iter().zip(read) {
+            values[index] = value;
+        }
+        Ok(values)
     }
 
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, KeyValueStoreError> {
@@ -155,9 +349,14 @@ impl ReadableKeyValueStore for KeyValueStore {
             key.len() <= Self::MAX_KEY_SIZE,
             KeyValueStoreError::KeyTooLong
         );
+        if let Some(value) = self.lock_cache().and_then(|mut cache| cache.get(key)) {
+            return Ok(value);
+        }
         let promise = self.wit_api.read_value_bytes_new(key);
-        yield_once().await;
-        Ok(self.wit_api.read_value_bytes_wait(promise))
+        self.wit_api.wait().await;
+        let value = self.wit_api.read_value_bytes_wait(promise)?;
+        self.cache_values([(key.to_vec(), value.clone())]);
+        Ok(value)
     }
 
     async fn find_keys_by_prefix(
@@ -169,7 +368,7 @@ impl ReadableKeyValueStore for KeyValueStore {
             KeyValueStoreError::KeyTooLong
         );
         let promise = self.wit_api.find_keys_new(key_prefix);
-        yield_once().await;
+        self.wit_api.wait().await;
         Ok(self.wit_api.find_keys_wait(promise))
     }
 
@@ -182,7 +381,7 @@ impl ReadableKeyValueStore for KeyValueStore {
             KeyValueStoreError::KeyTooLong
         );
         let promise = self.wit_api.find_key_values_new(key_prefix);
-        yield_once().await;
+        self.wit_api.wait().await;
         Ok(self.wit_api.find_key_values_wait(promise))
     }
 }
@@ -191,8 +390,10 @@ impl WritableKeyValueStore for KeyValueStore {
     const MAX_VALUE_SIZE: usize = usize::MAX;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), KeyValueStoreError> {
-        self.wit_api.write_batch(batch);
-        Ok(())
+        if let Some(mut cache) = self.lock_cache() {
+            cache.clear();
+        }
+        self.wit_api.write_batch(batch)
     }
 
     async fn clear_journal(&self) -> Result<(), KeyValueStoreError> {
@@ -217,6 +418,16 @@ enum WitInterface {
 }
 
 impl WitInterface {
+    /// Lets the host resolve the promises created so far, waiting for the latency of the
+    /// mocked storage, if any.
+    async fn wait(&self) {
+        yield_once().await;
+        #[cfg(with_testing)]
+        if let WitInterface::Mock { store, .. } = self {
+            store.delay().await;
+        }
+    }
+
     /// Creates a promise for testing if a key exist in the key-value store
     fn contains_key_new(&self, key: &[u8]) -> u32 {
         match self {
@@ -288,12 +499,12 @@ impl WitInterface {
     }
 
     /// Resolves a promise for reading a key in the key-value store
-    fn read_value_bytes_wait(&self, promise: u32) -> Option<Vec<u8>> {
+    fn read_value_bytes_wait(&self, promise: u32) -> Result<Option<Vec<u8>>, KeyValueStoreError> {
         match self {
-            WitInterface::Contract => contract_wit::read_value_bytes_wait(promise),
-            WitInterface::Service => service_wit::read_value_bytes_wait(promise),
+            WitInterface::Contract => Ok(contract_wit::read_value_bytes_wait(promise)),
+            WitInterface::Service => Ok(service_wit::read_value_bytes_wait(promise)),
             #[cfg(with_testing)]
-            WitInterface::Mock { store, .. } => store.read_value_bytes_wait(promise),
+            WitInterface::Mock { store, .. } => Ok(store.read_value_bytes_wait(promise)?),
         }
     }
 
@@ -337,8 +548,78 @@ impl WitInterface {
         }
     }
 
+    /// Creates a promise for finding at most `limit` keys having a specified prefix in the
+    /// key-value store, starting strictly after `start_after`
+    fn find_keys_paginated_new(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u32,
+    ) -> u32 {
+        match self {
+            WitInterface::Contract => {
+                contract_wit::find_keys_paginated_new(key_prefix, start_after, limit)
+            }
+            WitInterface::Service => {
+                service_wit::find_keys_paginated_new(key_prefix, start_after, limit)
+            }
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => {
+                store.find_keys_paginated_new(key_prefix, start_after, limit)
+            }
+        }
+    }
+
+    /// Resolves a promise for finding a page of keys having a specified prefix in the
+    /// key-value store
+    fn find_keys_paginated_wait(&self, promise: u32) -> (Vec<Vec<u8>>, Option<Vec<u8>>) {
+        match self {
+            WitInterface::Contract => contract_wit::find_keys_paginated_wait(promise),
+            WitInterface::Service => service_wit::find_keys_paginated_wait(promise),
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => store.find_keys_paginated_wait(promise),
+        }
+    }
+
+    /// Creates a promise for finding at most `limit` key/values having a specified prefix in
+    /// the key-value store, starting strictly after `start_after`
+    fn find_key_values_paginated_new(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u32,
+    ) -> u32 {
+        match self {
+            WitInterface::Contract => {
+                contract_wit::find_key_values_paginated_new(key_prefix, start_after, limit)
+            }
+            WitInterface::Service => {
+                service_wit::find_key_values_paginated_new(key_prefix, start_after, limit)
+            }
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => {
+                store.find_key_values_paginated_new(key_prefix, start_after, limit)
+            }
+        }
+    }
+
+    /// Resolves a promise for finding a page of key/values having a specified prefix in the
+    /// key-value store
+    #[expect(clippy::type_complexity)]
+    fn find_key_values_paginated_wait(
+        &self,
+        promise: u32,
+    ) -> (Vec<(Vec<u8>, Vec<u8>)>, Option<Vec<u8>>) {
+        match self {
+            WitInterface::Contract => contract_wit::find_key_values_paginated_wait(promise),
+            WitInterface::Service => service_wit::find_key_values_paginated_wait(promise),
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => store.find_key_values_paginated_wait(promise),
+        }
+    }
+
     /// Calls the `write_batch` WIT function.
-    fn write_batch(&self, batch: Batch) {
+    fn write_batch(&self, batch: Batch) -> Result<(), KeyValueStoreError> {
         match self {
             WitInterface::Contract => {
                 let batch_operations = batch
@@ -348,15 +629,14 @@ impl WitInterface {
                     .collect::<Vec<_>>();
 
                 contract_runtime_api::write_batch(&batch_operations);
+                Ok(())
             }
             WitInterface::Service => panic!("Attempt to modify storage from a service"),
             #[cfg(with_testing)]
             WitInterface::Mock {
                 store,
                 read_only: false,
-            } => {
-                store.write_batch(batch);
-            }
+            } => Ok(store.write_batch(batch)?),
             #[cfg(with_testing)]
             WitInterface::Mock {
                 read_only: true, ..
@@ -367,14 +647,65 @@ impl WitInterface {
     }
 }
 
+/// The extra data of a [`ViewStorageContext`], describing the block being executed, or
+/// the next block in services.
+///
+/// This replaces the [`BlockHeight`] that used to be the extra data of the context, so
+/// that views can also depend on the timestamp of the block. This is a breaking change:
+/// code calling `extra()` on a view or a context now has to read the `block_height` field,
+/// or use the [`BlockHeightProvider`] trait.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ViewStorageExtra {
+    /// The height of the block.
+    pub block_height: BlockHeight,
+    /// The timestamp of the block.
+    pub timestamp: Timestamp,
+}
+
+impl BlockHeightProvider for ViewStorageExtra {
+    fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+}
+
+impl TimestampProvider for ViewStorageExtra {
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
 /// Implementation of [`linera_views::context::Context`] to be used for data storage
 /// by Linera applications.
-pub type ViewStorageContext = linera_views::context::ViewContext<(), KeyValueStore>;
+pub type ViewStorageContext = linera_views::context::ViewContext<ViewStorageExtra, KeyValueStore>;
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
+    use assert_matches::assert_matches;
+    use linera_views::test_utils::faulty_store::StoreOperation;
+
     use super::*;
 
+    #[tokio::test]
+    async fn test_faulty_key_value_store_mock() -> anyhow::Result<()> {
+        let faulty_store = FaultyMemoryStore::new_for_testing();
+        let store = KeyValueStore::faulty_mock(faulty_store.clone()).to_mut();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"foo".to_vec(), b"bar".to_vec());
+
+        faulty_store.fail_nth(StoreOperation::WriteBatch, 1, "disk full");
+        assert_matches!(
+            store.write_batch(batch.clone()).await,
+            Err(KeyValueStoreError::FaultyStoreError(_))
+        );
+        assert_eq!(store.read_value_bytes(b"foo").await?, None);
+        store.write_batch(batch).await?;
+
+        faulty_store.fail_nth(StoreOperation::ReadValueBytes, 1, "timeout");
+        assert!(store.read_value_bytes(b"foo").await.is_err());
+        assert_eq!(store.read_value_bytes(b"foo").await?, Some(b"bar".to_vec()));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_key_value_store_mock() -> anyhow::Result<()> {
         // Create a mock key-value store for testing
@@ -409,4 +740,162 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_read_multi_values_bytes() -> anyhow::Result<()> {
+        let store = KeyValueStore::mock();
+
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"foo".to_vec(), b"1".to_vec());
+        batch.put_key_value_bytes(b"bar".to_vec(), b"2".to_vec());
+        store.to_mut().write_batch(batch).await?;
+
+        // Values are returned in the order of the requested keys, with `None` for missing keys.
+        let values = store
+            .read_multi_values_bytes(vec![
+                b"bar".to_vec(),
+                b"missing".to_vec(),
+                b"foo".to_vec(),
+                b"bar".to_vec(),
+            ])
+            .await?;
+        assert_eq!(
+            values,
+            vec![
+                Some(b"2".to_vec()),
+                None,
+                Some(b"1".to_vec()),
+                Some(b"2".to_vec())
+            ]
+        );
+
+        assert!(store.read_multi_values_bytes(vec![]).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cached_reads() -> anyhow::Result<()> {
+        let store = KeyValueStore::mock();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"foo".to_vec(), b"1".to_vec());
+        batch.put_key_value_bytes(b"bar".to_vec(), b"2".to_vec());
+        store.to_mut().write_batch(batch).await?;
+
+        let cached = store.with_cache(2);
+        cached.set_cache_block_height(BlockHeight(1), 2);
+        assert_eq!(cached.read_value_bytes(b"foo").await?, Some(b"1".to_vec()));
+        let values = cached
+            .read_multi_values_bytes(vec![b"missing".to_vec(), b"foo".to_vec()])
+            .await?;
+        assert_eq!(values, vec![None, Some(b"1".to_vec())]);
+        assert!(!cached.contains_key(b"missing").await?);
+        assert_eq!(
+            cached.cache_statistics(),
+            Some(CacheStatistics {
+                hits: 2,
+                misses: 2,
+                entries: 2,
+            })
+        );
+
+        // Changes made by blocks are only seen once the block height changes.
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"foo".to_vec(), b"3".to_vec());
+        store.to_mut().write_batch(batch).await?;
+        cached.set_cache_block_height(BlockHeight(1), 2);
+        assert_eq!(cached.read_value_bytes(b"foo").await?, Some(b"1".to_vec()));
+        cached.set_cache_block_height(BlockHeight(2), 2);
+        assert_eq!(cached.read_value_bytes(b"foo").await?, Some(b"3".to_vec()));
+        let present = cached
+            .contains_keys(vec![b"foo".to_vec(), b"bar".to_vec()])
+            .await?;
+        assert_eq!(present, vec![true, true]);
+        assert_eq!(store.cache_statistics(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_keys_by_prefix_paginated() -> anyhow::Result<()> {
+        let store = KeyValueStore::mock().to_mut();
+
+        let mut batch = Batch::new();
+        for key in [b"a1", b"a2", b"a3", b"b1"] {
+            batch.put_key_value_bytes(key.to_vec(), key.to_vec());
+        }
+        store.write_batch(batch).await?;
+
+        // A limit below the number of matching keys returns a continuation.
+        let (keys, next) = store.find_keys_by_prefix_paginated(b"a", None, 2).await?;
+        assert_eq!(keys, vec![b"1".to_vec(), b"2".to_vec()]);
+        assert_eq!(next, Some(b"2".to_vec()));
+
+        // The last page has no continuation.
+        let (keys, next) = store.find_keys_by_prefix_paginated(b"a", next, 2).await?;
+        assert_eq!(keys, vec![b"3".to_vec()]);
+        assert_eq!(next, None);
+
+        // A limit exactly matching the number of keys has no continuation either.
+        let (keys, next) = store.find_keys_by_prefix_paginated(b"a", None, 3).await?;
+        assert_eq!(keys.len(), 3);
+        assert_eq!(next, None);
+
+        // The empty prefix scans the whole store.
+        let (keys, next) = store.find_keys_by_prefix_paginated(&[], None, 3).await?;
+        assert_eq!(keys, vec![b"a1".to_vec(), b"a2".to_vec(), b"a3".to_vec()]);
+        assert_eq!(next, Some(b"a3".to_vec()));
+        let (keys, next) = store.find_keys_by_prefix_paginated(&[], next, 3).await?;
+        assert_eq!(keys, vec![b"b1".to_vec()]);
+        assert_eq!(next, None);
+
+        // Prefixes without any matches return an empty page.
+        let (keys, next) = store.find_keys_by_prefix_paginated(b"c", None, 1).await?;
+        assert!(keys.is_empty());
+        assert_eq!(next, None);
+
+        assert_matches!(
+            store.find_keys_by_prefix_paginated(b"a", None, 0).await,
+            Err(KeyValueStoreError::ZeroPageLimit)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_key_values_by_prefix_paginated() -> anyhow::Result<()> {
+        let store = KeyValueStore::mock().to_mut();
+
+        let mut batch = Batch::new();
+        for key in [b"a1", b"a2", b"b1"] {
+            batch.put_key_value_bytes(key.to_vec(), key.to_vec());
+        }
+        store.write_batch(batch).await?;
+
+        let (key_values, next) = store
+            .find_key_values_by_prefix_paginated(&[], None, 1)
+            .await?;
+        assert_eq!(key_values, vec![(b"a1".to_vec(), b"a1".to_vec())]);
+        assert_eq!(next, Some(b"a1".to_vec()));
+
+        let (key_values, next) = store
+            .find_key_values_by_prefix_paginated(&[], next, 2)
+            .await?;
+        assert_eq!(
+            key_values,
+            vec![
+                (b"a2".to_vec(), b"a2".to_vec()),
+                (b"b1".to_vec(), b"b1".to_vec())
+            ]
+        );
+        assert_eq!(next, None);
+
+        let (key_values, next) = store
+            .find_key_values_by_prefix_paginated(b"b", None, 1)
+            .await?;
+        assert_eq!(key_values, vec![(b"1".to_vec(), b"b1".to_vec())]);
+        assert_eq!(next, None);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,186 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A least-recently-used cache of the values read from the key-value store.
+
+use std::collections::{BTreeMap, HashMap};
+
+use linera_base::data_types::BlockHeight;
+
+/// The hit and miss counts of the cache of a [`KeyValueStore`][super::KeyValueStore].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStatistics {
+    /// The number of keys whose value or presence was found in the cache.
+    pub hits: u64,
+    /// The number of keys that had to be read from storage.
+    pub misses: u64,
+    /// The number of entries currently in the cache.
+    pub entries: usize,
+}
+
+impl CacheStatistics {
+    /// Returns the fraction of the lookups that were served by the cache, or zero if there
+    /// were none.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A cache keeping the last `max_entries` values read, including the absent ones.
+///
+/// The cache does not see the changes made to storage by blocks, so it has to be cleared
+/// whenever the block height changes.
+#[derive(Debug)]
+pub(crate) struct ValueCache {
+    max_entries: usize,
+    /// The height of the next block when the cached values were read.
+    block_height: Option<BlockHeight>,
+    /// The cached values, with the time of their last use.
+    entries: HashMap<Vec<u8>, (u64, Option<Vec<u8>>)>,
+    /// The keys of the cached values, by time of last use.
+    recency: BTreeMap<u64, Vec<u8>>,
+    /// The time of the next cache access.
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl ValueCache {
+    /// Creates an empty cache holding at most `max_entries` values.
+    pub(crate) fn new(max_entries: usize) -> Self {
+        ValueCache {
+            max_entries,
+            block_height: None,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Clears the cache if the values were read at a different block height.
+    pub(crate) fn set_block_height(&mut self, block_height: BlockHeight) {
+        if self.block_height != Some(block_height) {
+            self.clear();
+            self.block_height = Some(block_height);
+        }
+    }
+
+    /// Changes the maximum number of values kept, evicting the least recently used ones if
+    /// needed.
+    pub(crate) fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+        self.evict();
+    }
+
+    /// Removes all the cached values.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// Returns the cached value of `key`, as `Some(None)` if the key is known to be absent,
+    /// or `None` if the key is not in the cache.
+    pub(crate) fn get(&mut self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        let clock = self.tick();
+        match self.entries.get_mut(key) {
+            Some((last_use, value)) => {
+                self.hits += 1;
+                let key = self
+                    .recency
+                    .remove(last_use)
+                    .expect("cached keys are in the recency index");
+                *last_use = clock;
+                self.recency.insert(clock, key);
+                Some(value.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Caches the `value` read for `key`.
+    pub(crate) fn insert(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let clock = self.tick();
+        if let Some((last_use, _)) = self.entries.insert(key.clone(), (clock, value)) {
+            self.recency.remove(&last_use);
+        }
+        self.recency.insert(clock, key);
+        self.evict();
+    }
+
+    /// Returns the hit and miss counts since the cache was created.
+    pub(crate) fn statistics(&self) -> CacheStatistics {
+        CacheStatistics {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.max_entries {
+            let (_, key) = self
+                .recency
+                .pop_first()
+                .expect("the recency index has as many entries as the cache");
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_cache_evicts_least_recently_used() {
+        let mut cache = ValueCache::new(2);
+        cache.insert(b"a".to_vec(), Some(b"1".to_vec()));
+        cache.insert(b"b".to_vec(), None);
+        assert_eq!(cache.get(b"a"), Some(Some(b"1".to_vec())));
+        cache.insert(b"c".to_vec(), Some(b"3".to_vec()));
+        assert_eq!(cache.get(b"b"), None);
+        assert_eq!(cache.get(b"a"), Some(Some(b"1".to_vec())));
+        assert_eq!(cache.get(b"c"), Some(Some(b"3".to_vec())));
+
+        cache.set_max_entries(1);
+        assert_eq!(cache.get(b"a"), None);
+        assert_eq!(
+            cache.statistics(),
+            CacheStatistics {
+                hits: 3,
+                misses: 2,
+                entries: 1,
+            }
+        );
+        assert_eq!(cache.statistics().hit_rate(), 0.6);
+    }
+
+    #[test]
+    fn test_value_cache_cleared_on_new_block() {
+        let mut cache = ValueCache::new(4);
+        cache.set_block_height(BlockHeight(1));
+        cache.insert(b"a".to_vec(), Some(b"1".to_vec()));
+        cache.set_block_height(BlockHeight(1));
+        assert_eq!(cache.get(b"a"), Some(Some(b"1".to_vec())));
+        cache.set_block_height(BlockHeight(2));
+        assert_eq!(cache.get(b"a"), None);
+    }
+}
@@ -120,6 +120,14 @@ pub struct Block;
 )]
 pub struct Notifications;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/service_schema.graphql",
+    query_path = "gql/service_requests.graphql",
+    response_derives = "Debug, Serialize, Clone, PartialEq"
+)]
+pub struct NewBlocks;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "gql/service_schema.graphql",
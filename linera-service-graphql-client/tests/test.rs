@@ -12,7 +12,7 @@ use std::{collections::BTreeMap, str::FromStr, sync::LazyLock, time::Duration};
 use fungible::{FungibleTokenAbi, InitialState};
 use linera_base::{
     data_types::Amount,
-    identifiers::{Account, AccountOwner, ChainId},
+    identifiers::{Account, ChainId},
     vm::VmRuntime,
 };
 use linera_service::cli_wrappers::{
@@ -37,11 +37,10 @@ fn reqwest_client() -> reqwest::Client {
 
 async fn transfer(client: &reqwest::Client, url: &str, from: ChainId, to: Account, amount: &str) {
     let variables = transfer::Variables {
-        chain_id: from,
-        owner: AccountOwner::CHAIN,
-        recipient_chain: to.chain_id,
-        recipient_account: to.owner,
+        source: Account::chain(from),
+        destination: to,
         amount: Amount::from_str(amount).unwrap(),
+        memo: None,
     };
     request::<Transfer, _>(client, url, variables)
         .await
@@ -36,6 +36,8 @@ use tonic_health::pb::{
 };
 use tracing::{error, info, warn};
 
+#[cfg(all(any(feature = "dynamodb", feature = "scylladb"), with_testing))]
+use crate::storage::StorageCacheOptions;
 use crate::{
     cli_wrappers::{
         ClientWrapper, LineraNet, LineraNetConfig, Network, NetworkConfig, OnClientDrop,
@@ -80,7 +82,11 @@ async fn make_testing_config(database: Database) -> Result<StorageConfig> {
             #[cfg(feature = "dynamodb")]
             {
                 let use_dynamodb_local = true;
-                Ok(StorageConfig::DynamoDb { use_dynamodb_local })
+                Ok(StorageConfig::DynamoDb {
+                    use_dynamodb_local,
+                    compression: None,
+                    cache: StorageCacheOptions::default(),
+                })
             }
             #[cfg(not(feature = "dynamodb"))]
             panic!("Database::DynamoDb is selected without the feature dynamodb");
@@ -90,7 +96,10 @@ async fn make_testing_config(database: Database) -> Result<StorageConfig> {
             {
                 let config = ScyllaDbStore::new_test_config().await?;
                 Ok(StorageConfig::ScyllaDb {
-                    uri: config.inner_config.uri,
+                    uri: config.inner_config.inner_config.uri,
+                    compression: config.inner_config.compression,
+                    cache: StorageCacheOptions::default(),
+                    replicas: Vec::new(),
                 })
             }
             #[cfg(not(feature = "scylladb"))]
@@ -105,7 +114,7 @@ async fn make_testing_config(database: Database) -> Result<StorageConfig> {
                 Ok(StorageConfig::DualRocksDbScyllaDb {
                     path_with_guard: rocksdb_config.inner_config.path_with_guard,
                     spawn_mode,
-                    uri: scylla_config.inner_config.uri,
+                    uri: scylla_config.inner_config.inner_config.uri,
                 })
             }
             #[cfg(not(all(feature = "rocksdb", feature = "scylladb")))]
@@ -13,10 +13,12 @@ use std::{
     time::Duration,
 };
 
-use anyhow::{bail, ensure, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use async_graphql::InputType;
-use async_tungstenite::tungstenite::{client::IntoClientRequest as _, http::HeaderValue};
-use futures::{SinkExt as _, Stream, StreamExt as _, TryStreamExt as _};
+use async_tungstenite::tungstenite::{
+    self, client::IntoClientRequest as _, http::HeaderValue, Message,
+};
+use futures::{stream, SinkExt as _, Stream, StreamExt as _, TryStreamExt as _};
 use heck::ToKebabCase;
 use linera_base::{
     abi::ContractAbi,
@@ -42,6 +44,7 @@ use crate::{
         local_net::{PathProvider, ProcessInbox},
         Network,
     },
+    node_service::{ChainNotification, NotificationCursor},
     util::{self, ChildExt},
 };
 
@@ -443,6 +446,31 @@ impl ClientWrapper {
         &self,
         port: impl Into<Option<u16>>,
         process_inbox: ProcessInbox,
+    ) -> Result<NodeService> {
+        self.run_node_service_with_metrics_port(port, None, process_inbox)
+            .await
+    }
+
+    /// Runs `linera service`, serving the metrics on `metrics_port` if given.
+    pub async fn run_node_service_with_metrics_port(
+        &self,
+        port: impl Into<Option<u16>>,
+        metrics_port: Option<u16>,
+        process_inbox: ProcessInbox,
+    ) -> Result<NodeService> {
+        let args = metrics_port
+            .map(|metrics_port| vec!["--metrics-port".to_string(), metrics_port.to_string()])
+            .unwrap_or_default();
+        self.run_node_service_with_args(port, process_inbox, args)
+            .await
+    }
+
+    /// Runs `linera service` with the additional command-line `args`.
+    pub async fn run_node_service_with_args(
+        &self,
+        port: impl Into<Option<u16>>,
+        process_inbox: ProcessInbox,
+        args: impl IntoIterator<Item = String>,
     ) -> Result<NodeService> {
         let port = port.into().unwrap_or(8080);
         let mut command = self.command().await?;
@@ -450,6 +478,7 @@ impl ClientWrapper {
         if let ProcessInbox::Skip = process_inbox {
             command.arg("--listener-skip-process-inbox");
         }
+        command.args(args);
         if let Ok(var) = env::var(CLIENT_SERVICE_ENV) {
             command.args(var.split_whitespace());
         }
@@ -520,6 +549,17 @@ impl ClientWrapper {
         port: impl Into<Option<u16>>,
         chain_id: ChainId,
         amount: Amount,
+    ) -> Result<FaucetService> {
+        self.run_faucet_with_args(port, chain_id, amount, []).await
+    }
+
+    /// Runs `linera faucet` with the additional command-line `args`.
+    pub async fn run_faucet_with_args(
+        &self,
+        port: impl Into<Option<u16>>,
+        chain_id: ChainId,
+        amount: Amount,
+        args: impl IntoIterator<Item = String>,
     ) -> Result<FaucetService> {
         let port = port.into().unwrap_or(8080);
         let mut command = self.command().await?;
@@ -528,6 +568,7 @@ impl ClientWrapper {
             .arg(chain_id.to_string())
             .args(["--port".to_string(), port.to_string()])
             .args(["--amount".to_string(), amount.to_string()])
+            .args(args)
             .spawn_into()?;
         let client = reqwest_client();
         for i in 0..10 {
@@ -1246,6 +1287,24 @@ impl NodeService {
         );
     }
 
+    /// Runs a GraphQL query once, and returns the whole response, including its `errors`.
+    pub async fn try_query_node(&self, query: impl AsRef<str>) -> Result<Value> {
+        let query = query.as_ref();
+        let url = format!("http://localhost:{}/", self.port);
+        let response = reqwest_client()
+            .post(url)
+            .json(&json!({ "query": query }))
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "try_query_node: failed to post query={}",
+                    truncate_query_output(query)
+                )
+            })?;
+        response.json().await.context("invalid JSON")
+    }
+
     pub async fn create_application<
         Abi: ContractAbi,
         Parameters: Serialize,
@@ -1312,7 +1371,36 @@ impl NodeService {
         &self,
         chain_id: ChainId,
     ) -> Result<impl Stream<Item = Result<Notification>>> {
-        let query = format!("subscription {{ notifications(chainId: \"{chain_id}\") }}",);
+        Ok(self
+            .notifications_with_cursors(chain_id, None)
+            .await?
+            .map_ok(|notification| notification.notification))
+    }
+
+    /// Subscribes to the node service and returns a stream of notifications about a chain,
+    /// together with their cursors. If a `cursor` is given, the stream resumes after the
+    /// notification with that cursor.
+    pub async fn notifications_with_cursors(
+        &self,
+        chain_id: ChainId,
+        cursor: Option<NotificationCursor>,
+    ) -> Result<impl Stream<Item = Result<ChainNotification>>> {
+        let cursor = cursor.map_or_else(|| "null".to_string(), |cursor| format!("\"{cursor}\""));
+        let query =
+            format!("subscription {{ notifications(chainId: \"{chain_id}\", cursor: {cursor}) }}");
+        Ok(self.subscribe(query).await?.and_then(|data| async move {
+            serde_json::from_value(data["notifications"].clone())
+                .context("Failed to deserialize notification")
+        }))
+    }
+
+    /// Runs a GraphQL subscription on the node service and returns the stream of the data
+    /// of its responses.
+    pub async fn subscribe(
+        &self,
+        query: impl AsRef<str>,
+    ) -> Result<impl Stream<Item = Result<Value>>> {
+        let query = query.as_ref();
         let url = format!("ws://localhost:{}/ws", self.port);
         let mut request = url.into_client_request()?;
         request.headers_mut().insert(
@@ -1344,17 +1432,37 @@ impl NodeService {
           }
         });
         websocket.send(query_json.to_string().into()).await?;
-        Ok(websocket
-            .map_err(anyhow::Error::from)
-            .and_then(|message| async {
-                let text = message.into_text()?;
-                let value: Value = serde_json::from_str(&text).context("invalid JSON")?;
-                if let Some(errors) = value["payload"].get("errors") {
-                    bail!("Notification subscription failed: {errors:?}");
+        Ok(stream::unfold(websocket, |mut websocket| async move {
+            loop {
+                let value = match Self::read_subscription_message(websocket.next().await?) {
+                    Ok(value) => value,
+                    Err(error) => return Some((Err(error), websocket)),
+                };
+                match value["type"].as_str() {
+                    // The node service disconnects clients that don't answer its keepalive pings.
+                    Some("ping") => {
+                        let pong = json!({ "type": "pong" }).to_string();
+                        if let Err(error) = websocket.send(pong.into()).await {
+                            return Some((Err(error.into()), websocket));
+                        }
+                    }
+                    Some("pong") | Some("ka") => {}
+                    _ => {
+                        let result = match value["payload"].get("errors") {
+                            Some(errors) => Err(anyhow!("Subscription failed: {errors:?}")),
+                            None => Ok(value["payload"]["data"].clone()),
+                        };
+                        return Some((result, websocket));
+                    }
                 }
-                serde_json::from_value(value["payload"]["data"]["notifications"].clone())
-                    .context("Failed to deserialize notification")
-            }))
+            }
+        }))
+    }
+
+    /// Parses a message received from the node service through a websocket.
+    fn read_subscription_message(message: Result<Message, tungstenite::Error>) -> Result<Value> {
+        let text = message?.into_text()?;
+        serde_json::from_str(&text).context("invalid JSON")
     }
 }
 
@@ -1441,6 +1549,26 @@ impl<A> ApplicationWrapper<A> {
         unreachable!()
     }
 
+    /// Runs a query once with the given HTTP `headers`, and returns the whole response,
+    /// including its `errors`.
+    pub async fn try_run_json_query<T: Serialize>(
+        &self,
+        query: T,
+        headers: &[(&str, String)],
+    ) -> Result<Value> {
+        let mut request = reqwest_client().post(&self.uri).json(&query);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        let response = request.send().await.with_context(|| {
+            format!(
+                "try_run_json_query: failed to post query={}",
+                truncate_query_output_serialize(&query)
+            )
+        })?;
+        response.json().await.context("invalid JSON")
+    }
+
     pub async fn query(&self, query: impl AsRef<str>) -> Result<Value> {
         let query = query.as_ref();
         self.run_graphql_query(&format!("query {{ {query} }}"))
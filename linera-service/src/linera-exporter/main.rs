@@ -9,7 +9,10 @@ use futures::FutureExt;
 use linera_client::config::{BlockExporterConfig, GenesisConfig};
 use linera_sdk::views::ViewError;
 use linera_service::{storage::StorageConfigNamespace, util};
-use linera_views::{lru_caching::StorageCacheConfig, store::CommonStoreConfig};
+use linera_views::{
+    lru_caching::{CachePolicyKind, StorageCacheConfig},
+    store::CommonStoreConfig,
+};
 
 #[allow(dead_code)]
 mod exporter_service;
@@ -69,6 +72,10 @@ struct ExporterOptions {
     #[arg(long, default_value = "1000")]
     max_cache_entries: usize,
 
+    /// The eviction policy of the storage cache: lru, lfu or segmented.
+    #[arg(long, default_value = "lru")]
+    cache_policy: CachePolicyKind,
+
     /// Path to the file describing the initial user chains (aka genesis state)
     #[arg(long = "genesis")]
     genesis_config_path: PathBuf,
@@ -91,6 +98,7 @@ impl ExporterOptions {
             max_cache_size: self.max_cache_size,
             max_entry_size: self.max_entry_size,
             max_cache_entries: self.max_cache_entries,
+            cache_policy: self.cache_policy,
         };
         let common_config = CommonStoreConfig {
             max_concurrent_queries: self.max_concurrent_queries,
@@ -1,12 +1,17 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{borrow::Cow, num::NonZeroU16, path::PathBuf};
+use std::{
+    borrow::Cow,
+    net::IpAddr,
+    num::{NonZeroU16, NonZeroU32},
+    path::PathBuf,
+};
 
 use chrono::{DateTime, Utc};
 use linera_base::{
     crypto::{AccountPublicKey, CryptoHash, ValidatorPublicKey},
-    data_types::Amount,
+    data_types::{Amount, BlockHeight},
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, ModuleId},
     time::Duration,
     vm::VmRuntime,
@@ -16,13 +21,15 @@ use linera_client::{
     client_options::{
         ApplicationPermissionsConfig, ChainOwnershipConfig, ResourceControlPolicyConfig,
     },
-    util,
+    util::{self, IdPrefix},
 };
 use linera_rpc::config::CrossChainConfig;
 #[cfg(feature = "kubernetes")]
 use linera_service::cli_wrappers::local_kubernetes_net::BuildMode;
-use linera_service::util::{
-    DEFAULT_PAUSE_AFTER_GQL_MUTATIONS_SECS, DEFAULT_PAUSE_AFTER_LINERA_SERVICE_SECS,
+use linera_service::{
+    node_service::SubscriptionConfig,
+    storage::StorageConfigNamespace,
+    util::{DEFAULT_PAUSE_AFTER_GQL_MUTATIONS_SECS, DEFAULT_PAUSE_AFTER_LINERA_SERVICE_SECS},
 };
 
 #[derive(Clone, clap::Subcommand)]
@@ -43,9 +50,9 @@ pub enum ClientCommand {
 
     /// Open (i.e. activate) a new chain deriving the UID from an existing one.
     OpenChain {
-        /// Chain ID (must be one of our chains).
+        /// Chain ID (must be one of our chains), or an unambiguous prefix of it.
         #[arg(long = "from")]
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         /// The new owner (otherwise create a key pair and remember it)
         #[arg(long = "owner")]
@@ -59,9 +66,9 @@ pub enum ClientCommand {
 
     /// Open (i.e. activate) a new multi-owner chain deriving the UID from an existing one.
     OpenMultiOwnerChain {
-        /// Chain ID (must be one of our chains).
+        /// Chain ID (must be one of our chains), or an unambiguous prefix of it.
         #[arg(long = "from")]
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         #[clap(flatten)]
         ownership_config: ChainOwnershipConfig,
@@ -82,7 +89,7 @@ pub enum ClientCommand {
     ChangeOwnership {
         /// The ID of the chain whose owners will be changed.
         #[clap(long)]
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         #[clap(flatten)]
         ownership_config: ChainOwnershipConfig,
@@ -92,7 +99,7 @@ pub enum ClientCommand {
     SetPreferredOwner {
         /// The ID of the chain whose preferred owner will be changed.
         #[clap(long)]
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         /// The new preferred owner.
         #[arg(long)]
@@ -103,7 +110,7 @@ pub enum ClientCommand {
     ChangeApplicationPermissions {
         /// The ID of the chain to which the new permissions will be applied.
         #[arg(long)]
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         #[clap(flatten)]
         application_permissions_config: ApplicationPermissionsConfig,
@@ -114,8 +121,8 @@ pub enum ClientCommand {
     /// A closed chain cannot execute operations or accept messages anymore.
     /// It can still reject incoming messages, so they bounce back to the sender.
     CloseChain {
-        /// Chain ID (must be one of our chains)
-        chain_id: ChainId,
+        /// Chain ID (must be one of our chains), or an unambiguous prefix of it
+        chain_id: IdPrefix,
     },
 
     /// Read the current native-token balance of the given account directly from the local
@@ -159,7 +166,7 @@ pub enum ClientCommand {
     Sync {
         /// The chain to synchronize with validators. If omitted, synchronizes the
         /// default chain of the wallet.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
     },
 
     /// Process all pending incoming messages from the inbox of the given chain by creating as many
@@ -167,7 +174,7 @@ pub enum ClientCommand {
     /// marked as rejected and may bounce to their sender depending on their configuration.
     ProcessInbox {
         /// The chain to process. If omitted, uses the default chain of the wallet.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
     },
 
     /// Show the version and genesis config hash of a new validator, and print a warning if it is
@@ -176,7 +183,7 @@ pub enum ClientCommand {
         /// The new validator's address.
         address: String,
         /// The chain to query. If omitted, query the default chain of the wallet.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
         /// The public key of the validator. If given, the signature of the chain query
         /// info will be checked.
         #[arg(long)]
@@ -187,7 +194,7 @@ pub enum ClientCommand {
     /// the given chain while we are at it.
     QueryValidators {
         /// The chain to query. If omitted, query the default chain of the wallet.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
     },
 
     /// Synchronizes a validator with the local state of chains.
@@ -197,7 +204,7 @@ pub enum ClientCommand {
 
         /// The chains to synchronize, or the default chain if empty.
         #[arg(long, num_args = 0..)]
-        chains: Vec<ChainId>,
+        chains: Vec<IdPrefix>,
     },
 
     /// Add or modify a validator (admin only)
@@ -279,6 +286,10 @@ pub enum ClientCommand {
         #[arg(long)]
         blob_byte_published: Option<Amount>,
 
+        /// Set the additional price to publish bytecode, per byte of the decompressed bytecode.
+        #[arg(long)]
+        bytecode_byte_published: Option<Amount>,
+
         /// Set the price per byte stored.
         #[arg(long)]
         byte_stored: Option<Amount>,
@@ -360,9 +371,19 @@ pub enum ClientCommand {
         #[arg(long)]
         http_request_timeout_ms: Option<u64>,
 
+        /// Set the maximum number of HTTP requests from a single batch that are performed
+        /// concurrently.
+        #[arg(long)]
+        maximum_concurrent_http_requests: Option<u64>,
+
         /// Set the list of hosts that contracts and services can send HTTP requests to.
         #[arg(long)]
         http_request_allow_list: Option<Vec<String>>,
+
+        /// Set the additional hosts that the contracts and services of specific applications
+        /// can send HTTP requests to, as `APPLICATION_ID=HOST` pairs.
+        #[arg(long, value_parser = util::parse_application_http_host)]
+        http_request_allow_list_per_application: Option<Vec<(ApplicationId, String)>>,
     },
 
     /// Send one transfer per chain in bulk mode
@@ -500,6 +521,11 @@ pub enum ClientCommand {
         #[arg(long)]
         blob_byte_published_price: Option<Amount>,
 
+        /// Set the additional price to publish bytecode, per byte of the decompressed bytecode.
+        /// (This will overwrite value from `--policy-config`)
+        #[arg(long)]
+        bytecode_byte_published_price: Option<Amount>,
+
         /// Set the price per byte stored.
         /// (This will overwrite value from `--policy-config`)
         #[arg(long)]
@@ -596,10 +622,20 @@ pub enum ClientCommand {
         #[arg(long)]
         http_request_timeout_ms: Option<u64>,
 
+        /// Set the maximum number of HTTP requests from a single batch that are performed
+        /// concurrently.
+        #[arg(long)]
+        maximum_concurrent_http_requests: Option<u64>,
+
         /// Set the list of hosts that contracts and services can send HTTP requests to.
         #[arg(long)]
         http_request_allow_list: Option<Vec<String>>,
 
+        /// Set the additional hosts that the contracts and services of specific applications
+        /// can send HTTP requests to, as `APPLICATION_ID=HOST` pairs.
+        #[arg(long, value_parser = util::parse_application_http_host)]
+        http_request_allow_list_per_application: Option<Vec<(ApplicationId, String)>>,
+
         /// Force this wallet to generate keys using a PRNG and a given seed. USE FOR
         /// TESTING ONLY.
         #[arg(long)]
@@ -613,7 +649,7 @@ pub enum ClientCommand {
     /// Watch the network for notifications.
     Watch {
         /// The chain ID to watch.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         /// Show all notifications from all validators.
         #[arg(long)]
@@ -625,16 +661,33 @@ pub enum ClientCommand {
         #[command(flatten)]
         config: ChainListenerConfig,
 
+        #[command(flatten)]
+        subscription_config: SubscriptionConfig,
+
         /// The port on which to run the server
         #[arg(long)]
         port: NonZeroU16,
+
+        /// The maximum amount of time that an application query may take. A lower limit can be
+        /// set with the `x-linera-query-timeout-ms` header of the request
+        #[arg(long)]
+        service_query_timeout_ms: Option<u64>,
+
+        /// The maximum amount of fuel that an application query may consume. A lower limit can
+        /// be set with the `x-linera-query-fuel-limit` header of the request
+        #[arg(long)]
+        service_query_fuel_limit: Option<u64>,
+
+        /// The port on which to serve the metrics, if the `metrics` feature is enabled
+        #[arg(long)]
+        metrics_port: Option<NonZeroU16>,
     },
 
     /// Run a GraphQL service that exposes a faucet where users can claim tokens.
     /// This gives away the chain's tokens, and is mainly intended for testing.
     Faucet {
         /// The chain that gives away its tokens.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
 
         /// The port on which to run the server
         #[arg(long, default_value = "8080")]
@@ -649,6 +702,29 @@ pub enum ClientCommand {
         #[arg(long)]
         limit_rate_until: Option<DateTime<Utc>>,
 
+        /// The maximum number of claims from the same IP address per hour.
+        #[arg(long)]
+        max_claims_per_ip_per_hour: Option<NonZeroU32>,
+
+        /// The maximum number of tokens given to the same owner per day.
+        #[arg(long)]
+        max_amount_per_day: Option<Amount>,
+
+        /// If set, each claim must come with a proof of work with this number of leading zero
+        /// bits, at most 256.
+        #[arg(long)]
+        proof_of_work_difficulty: Option<u32>,
+
+        /// The IP address of a reverse proxy in front of the faucet. The claims forwarded by it
+        /// are attributed to the client address in their `X-Forwarded-For` header. Can be
+        /// repeated.
+        #[arg(long = "trusted-proxy")]
+        trusted_proxies: Vec<IpAddr>,
+
+        /// The port on which to serve the metrics, if the `metrics` feature is enabled.
+        #[arg(long)]
+        metrics_port: Option<NonZeroU16>,
+
         /// Configuration for the faucet chain listener.
         #[command(flatten)]
         config: ChainListenerConfig,
@@ -668,7 +744,7 @@ pub enum ClientCommand {
 
         /// An optional chain ID to publish the module. The default chain of the wallet
         /// is used otherwise.
-        publisher: Option<ChainId>,
+        publisher: Option<IdPrefix>,
     },
 
     /// Publish a data blob of binary data.
@@ -677,7 +753,7 @@ pub enum ClientCommand {
         blob_path: PathBuf,
         /// An optional chain ID to publish the blob. The default chain of the wallet
         /// is used otherwise.
-        publisher: Option<ChainId>,
+        publisher: Option<IdPrefix>,
     },
 
     // TODO(#2490): Consider removing or renaming this.
@@ -687,7 +763,7 @@ pub enum ClientCommand {
         hash: CryptoHash,
         /// An optional chain ID to verify the blob. The default chain of the wallet
         /// is used otherwise.
-        reader: Option<ChainId>,
+        reader: Option<IdPrefix>,
     },
 
     /// Create an application.
@@ -697,7 +773,7 @@ pub enum ClientCommand {
 
         /// An optional chain ID to host the application. The default chain of the wallet
         /// is used otherwise.
-        creator: Option<ChainId>,
+        creator: Option<IdPrefix>,
 
         /// The shared parameters as JSON string.
         #[arg(long)]
@@ -717,7 +793,7 @@ pub enum ClientCommand {
 
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
-        required_application_ids: Option<Vec<ApplicationId>>,
+        required_application_ids: Option<Vec<IdPrefix>>,
     },
 
     /// Create an application, and publish the required module.
@@ -734,7 +810,7 @@ pub enum ClientCommand {
 
         /// An optional chain ID to publish the module. The default chain of the wallet
         /// is used otherwise.
-        publisher: Option<ChainId>,
+        publisher: Option<IdPrefix>,
 
         /// The shared parameters as JSON string.
         #[arg(long)]
@@ -754,7 +830,7 @@ pub enum ClientCommand {
 
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
-        required_application_ids: Option<Vec<ApplicationId>>,
+        required_application_ids: Option<Vec<IdPrefix>>,
     },
 
     /// Create an unassigned key pair.
@@ -779,7 +855,7 @@ pub enum ClientCommand {
     /// multiple blocks at the same height.
     RetryPendingBlock {
         /// The chain with the pending block. If not specified, the wallet's default chain is used.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
     },
 
     /// Show the contents of the wallet.
@@ -869,6 +945,38 @@ impl ClientCommand {
 
 #[derive(Clone, clap::Parser)]
 pub enum DatabaseToolCommand {
+    #[command(flatten)]
+    Store(StoreCommand),
+
+    /// Copy chains, with their certificates and blobs, from one database to another
+    Copy {
+        /// Storage configuration of the database to copy from.
+        #[arg(long)]
+        from: StorageConfigNamespace,
+
+        /// Storage configuration of the database to copy to.
+        #[arg(long)]
+        to: StorageConfigNamespace,
+
+        /// The chain to copy. If not specified, all the chains of the source database are
+        /// copied.
+        #[arg(long = "chain")]
+        chain_id: Option<ChainId>,
+
+        /// Only copy the blocks up to and including this height. The chain state is only
+        /// copied if this includes the latest block.
+        #[arg(long)]
+        up_to: Option<BlockHeight>,
+
+        /// Report the progress after this number of chains.
+        #[arg(long, default_value = "100")]
+        progress_interval: usize,
+    },
+}
+
+/// The operations on the database given by `--storage`.
+#[derive(Clone, clap::Subcommand)]
+pub enum StoreCommand {
     /// Delete all the namespaces in the database
     DeleteAll,
 
@@ -1003,7 +1111,7 @@ pub enum WalletCommand {
     /// Show the contents of the wallet.
     Show {
         /// The chain to show the metadata.
-        chain_id: Option<ChainId>,
+        chain_id: Option<IdPrefix>,
         /// Only print a non-formatted list of the wallet's chain IDs.
         #[arg(long)]
         short: bool,
@@ -1013,7 +1121,7 @@ pub enum WalletCommand {
     },
 
     /// Change the wallet default chain.
-    SetDefault { chain_id: ChainId },
+    SetDefault { chain_id: IdPrefix },
 
     /// Initialize a wallet from the genesis configuration.
     Init {
@@ -1059,10 +1167,10 @@ pub enum WalletCommand {
 
     /// Forgets the specified chain's keys. The chain will still be followed by the
     /// wallet.
-    ForgetKeys { chain_id: ChainId },
+    ForgetKeys { chain_id: IdPrefix },
 
     /// Forgets the specified chain, including the associated key pair.
-    ForgetChain { chain_id: ChainId },
+    ForgetChain { chain_id: IdPrefix },
 }
 
 #[derive(Clone, clap::Parser)]
@@ -1098,7 +1206,7 @@ pub enum ProjectCommand {
 
         /// An optional chain ID to publish the module. The default chain of the wallet
         /// is used otherwise.
-        publisher: Option<ChainId>,
+        publisher: Option<IdPrefix>,
 
         /// The virtual machine runtime to use.
         #[arg(long, default_value = "wasm")]
@@ -1122,6 +1230,6 @@ pub enum ProjectCommand {
 
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
-        required_application_ids: Option<Vec<ApplicationId>>,
+        required_application_ids: Option<Vec<IdPrefix>>,
     },
 }
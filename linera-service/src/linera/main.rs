@@ -8,26 +8,30 @@
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     env,
+    num::NonZeroU16,
     ops::Deref,
     path::PathBuf,
     process,
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail, ensure, Context, Error};
 use async_trait::async_trait;
 use chrono::Utc;
 use colored::Colorize;
-use command::{ClientCommand, DatabaseToolCommand, NetCommand, ProjectCommand, WalletCommand};
+use command::{
+    ClientCommand, DatabaseToolCommand, NetCommand, ProjectCommand, StoreCommand, WalletCommand,
+};
 use futures::{lock::Mutex, FutureExt as _, StreamExt};
 use linera_base::{
     bcs,
     crypto::{CryptoHash, InMemorySigner, Signer},
     data_types::{
-        ApplicationPermissions, ChainDescription, ChainOrigin, Epoch, InitialChainConfig, Timestamp,
+        ApplicationPermissions, BlockHeight, ChainDescription, ChainOrigin, Epoch,
+        InitialChainConfig, Timestamp,
     },
-    identifiers::{AccountOwner, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId},
     listen_for_shutdown_signals,
     ownership::ChainOwnership,
 };
@@ -43,19 +47,20 @@ use linera_core::{
 };
 use linera_execution::{
     committee::{Committee, ValidatorState},
-    WasmRuntime, WithWasmDefault as _,
+    QueryLimits, WasmRuntime, WithWasmDefault as _,
 };
-use linera_faucet_server::FaucetService;
+use linera_faucet::ProofOfWork;
+use linera_faucet_server::{FaucetService, RateLimitConfig};
 use linera_service::{
     cli_wrappers,
     node_service::NodeService,
     project::{self, Project},
-    storage::{Runnable, RunnableWithStore, StorageConfigNamespace},
+    storage::{Runnable, RunnableWithStore, StorageConfigNamespace, StoreConfig},
     util, wallet,
 };
 use linera_storage::{DbStorage, Storage};
 use linera_views::{
-    lru_caching::StorageCacheConfig,
+    lru_caching::{CachePolicyKind, StorageCacheConfig},
     store::{CommonStoreConfig, KeyValueStore},
 };
 use serde_json::Value;
@@ -83,6 +88,33 @@ fn read_json(string: Option<String>, path: Option<PathBuf>) -> anyhow::Result<Ve
     Ok(serde_json::to_vec(&value)?)
 }
 
+/// Groups the `APPLICATION_ID=HOST` pairs given on the command line by application.
+fn http_hosts_per_application(
+    entries: Vec<(ApplicationId, String)>,
+) -> BTreeMap<ApplicationId, BTreeSet<String>> {
+    let mut hosts = BTreeMap::<_, BTreeSet<_>>::new();
+    for (application_id, host) in entries {
+        hosts.entry(application_id).or_default().insert(host);
+    }
+    hosts
+}
+
+/// Serves the metrics on the given port until the `shutdown_signal` is cancelled.
+fn start_metrics(port: NonZeroU16, shutdown_signal: CancellationToken) {
+    #[cfg(with_metrics)]
+    linera_service::prometheus_server::start_metrics(
+        std::net::SocketAddr::from(([0, 0, 0, 0], port.get())),
+        shutdown_signal,
+    );
+    #[cfg(not(with_metrics))]
+    {
+        drop(shutdown_signal);
+        warn!(
+            "Not serving metrics on port {port}: `linera` was built without the `metrics` feature"
+        );
+    }
+}
+
 #[async_trait]
 impl Runnable for Job {
     type Output = anyhow::Result<()>;
@@ -145,7 +177,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Opening a new chain from existing chain {}", chain_id);
                 let time_start = Instant::now();
@@ -188,7 +220,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!(
                     "Opening a new multi-owner chain from existing chain {}",
@@ -237,6 +269,9 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
+                let chain_id = chain_id
+                    .map(|chain_id| context.resolve_chain_id(&chain_id))
+                    .transpose()?;
                 context.change_ownership(chain_id, ownership_config).await?
             }
 
@@ -247,6 +282,9 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
+                let chain_id = chain_id
+                    .map(|chain_id| context.resolve_chain_id(&chain_id))
+                    .transpose()?;
                 context.set_preferred_owner(chain_id, owner).await?
             }
 
@@ -260,7 +298,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Changing application permissions for chain {}", chain_id);
                 let time_start = Instant::now();
@@ -293,6 +331,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
+                let chain_id = context.resolve_chain_id(&chain_id)?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Closing chain {}", chain_id);
                 let time_start = Instant::now();
@@ -386,7 +425,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Synchronizing chain information");
                 let time_start = Instant::now();
@@ -406,7 +445,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Processing the inbox of chain {}", chain_id);
                 let time_start = Instant::now();
@@ -448,7 +487,7 @@ impl Runnable for Job {
                         has_errors = true;
                     }
                 }
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 if let Err(e) = context
                     .check_validator_chain_info_response(
                         public_key.as_ref(),
@@ -473,7 +512,7 @@ impl Runnable for Job {
                     wallet,
                     Box::new(signer.into_value()),
                 );
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Querying validators about chain {}", chain_id);
                 let result = chain_client.local_committee().await;
@@ -527,10 +566,7 @@ impl Runnable for Job {
                 );
             }
 
-            SyncValidator {
-                address,
-                mut chains,
-            } => {
+            SyncValidator { address, chains } => {
                 let context = ClientContext::new(
                     storage.clone(),
                     options.inner.clone(),
@@ -538,6 +574,10 @@ impl Runnable for Job {
                     Box::new(signer.into_value()),
                 );
 
+                let mut chains = chains
+                    .iter()
+                    .map(|chain_id| context.resolve_chain_id(chain_id))
+                    .collect::<Result<Vec<_>, _>>()?;
                 if chains.is_empty() {
                     chains.push(context.default_chain());
                 }
@@ -636,6 +676,7 @@ impl Runnable for Job {
                                     blob_published,
                                     blob_byte_read,
                                     blob_byte_published,
+                                    bytecode_byte_published,
                                     byte_stored,
                                     operation,
                                     operation_byte,
@@ -656,7 +697,9 @@ impl Runnable for Job {
                                     maximum_oracle_response_bytes,
                                     maximum_http_response_bytes,
                                     http_request_timeout_ms,
+                                    maximum_concurrent_http_requests,
                                     http_request_allow_list,
+                                    http_request_allow_list_per_application,
                                 } => {
                                     let existing_policy = policy.clone();
                                     policy = linera_execution::ResourceControlPolicy {
@@ -679,6 +722,8 @@ impl Runnable for Job {
                                             .unwrap_or(existing_policy.blob_byte_read),
                                         blob_byte_published: blob_byte_published
                                             .unwrap_or(existing_policy.blob_byte_published),
+                                        bytecode_byte_published: bytecode_byte_published
+                                            .unwrap_or(existing_policy.bytecode_byte_published),
                                         byte_stored: byte_stored
                                             .unwrap_or(existing_policy.byte_stored),
                                         operation: operation.unwrap_or(existing_policy.operation),
@@ -725,9 +770,20 @@ impl Runnable for Job {
                                             .unwrap_or(existing_policy.maximum_http_response_bytes),
                                         http_request_timeout_ms: http_request_timeout_ms
                                             .unwrap_or(existing_policy.http_request_timeout_ms),
+                                        maximum_concurrent_http_requests:
+                                            maximum_concurrent_http_requests.unwrap_or(
+                                                existing_policy.maximum_concurrent_http_requests,
+                                            ),
                                         http_request_allow_list: http_request_allow_list
                                             .map(BTreeSet::from_iter)
                                             .unwrap_or(existing_policy.http_request_allow_list),
+                                        http_request_allow_list_per_application:
+                                            http_request_allow_list_per_application
+                                                .map(http_hosts_per_application)
+                                                .unwrap_or(
+                                                    existing_policy
+                                                        .http_request_allow_list_per_application,
+                                                ),
                                     };
                                     info!("{policy}");
                                     if committee.policy() == &policy {
@@ -875,7 +931,7 @@ impl Runnable for Job {
                 );
 
                 let mut join_set = JoinSet::new();
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Watching for notifications for chain {:?}", chain_id);
                 let (listener, _listen_handle, mut notifications) = chain_client.listen().await?;
@@ -891,7 +947,14 @@ impl Runnable for Job {
                 info!("Notification stream ended.");
             }
 
-            Service { config, port } => {
+            Service {
+                config,
+                subscription_config,
+                port,
+                service_query_timeout_ms,
+                service_query_fuel_limit,
+                metrics_port,
+            } => {
                 let context = ClientContext::new(
                     storage.clone(),
                     options.inner.clone(),
@@ -900,9 +963,19 @@ impl Runnable for Job {
                 );
 
                 let default_chain = context.wallet().default_chain();
-                let service = NodeService::new(config, port, default_chain, context).await;
+                let query_limits = QueryLimits {
+                    timeout: service_query_timeout_ms.map(Duration::from_millis),
+                    fuel: service_query_fuel_limit,
+                };
+                let service = NodeService::new(config, port, default_chain, context)
+                    .await
+                    .with_query_limits(query_limits)
+                    .with_subscription_config(subscription_config);
                 let cancellation_token = CancellationToken::new();
                 let child_token = cancellation_token.child_token();
+                if let Some(metrics_port) = metrics_port {
+                    start_metrics(metrics_port, child_token.clone());
+                }
                 tokio::spawn(listen_for_shutdown_signals(cancellation_token));
                 service.run(child_token).await?;
             }
@@ -912,8 +985,20 @@ impl Runnable for Job {
                 port,
                 amount,
                 limit_rate_until,
+                max_claims_per_ip_per_hour,
+                max_amount_per_day,
+                proof_of_work_difficulty,
+                trusted_proxies,
+                metrics_port,
                 config,
             } => {
+                if let Some(difficulty) = proof_of_work_difficulty {
+                    ensure!(
+                        difficulty <= ProofOfWork::MAX_DIFFICULTY,
+                        "The proof of work difficulty must be at most {}",
+                        ProofOfWork::MAX_DIFFICULTY
+                    );
+                }
                 let context = ClientContext::new(
                     storage.clone(),
                     options.inner.clone(),
@@ -921,7 +1006,7 @@ impl Runnable for Job {
                     Box::new(signer.into_value()),
                 );
 
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 info!("Starting faucet service using chain {}", chain_id);
                 let end_timestamp = limit_rate_until
                     .map(|et| {
@@ -941,9 +1026,18 @@ impl Runnable for Job {
                     config,
                     storage,
                 )
-                .await?;
+                .await?
+                .with_rate_limits(RateLimitConfig {
+                    max_claims_per_ip_per_hour,
+                    max_amount_per_day,
+                })?
+                .with_proof_of_work_difficulty(proof_of_work_difficulty)
+                .with_trusted_proxies(trusted_proxies);
                 let cancellation_token = CancellationToken::new();
                 let child_token = cancellation_token.child_token();
+                if let Some(metrics_port) = metrics_port {
+                    start_metrics(metrics_port, child_token.clone());
+                }
                 tokio::spawn(listen_for_shutdown_signals(cancellation_token));
                 faucet.run(child_token).await?;
             }
@@ -962,7 +1056,7 @@ impl Runnable for Job {
                 );
 
                 let start_time = Instant::now();
-                let publisher = publisher.unwrap_or_else(|| context.default_chain());
+                let publisher = context.resolve_chain_id_or_default(publisher.as_ref())?;
                 info!("Publishing module on chain {}", publisher);
                 let chain_client = context.make_chain_client(publisher).await?;
                 let module_id = context
@@ -987,7 +1081,7 @@ impl Runnable for Job {
                 );
 
                 let start_time = Instant::now();
-                let publisher = publisher.unwrap_or_else(|| context.default_chain());
+                let publisher = context.resolve_chain_id_or_default(publisher.as_ref())?;
                 info!("Publishing data blob on chain {}", publisher);
                 let chain_client = context.make_chain_client(publisher).await?;
                 let hash = context.publish_data_blob(&chain_client, blob_path).await?;
@@ -1008,7 +1102,7 @@ impl Runnable for Job {
                 );
 
                 let start_time = Instant::now();
-                let reader = reader.unwrap_or_else(|| context.default_chain());
+                let reader = context.resolve_chain_id_or_default(reader.as_ref())?;
                 info!("Verifying data blob on chain {}", reader);
                 let chain_client = context.make_chain_client(reader).await?;
                 context.read_data_blob(&chain_client, hash).await?;
@@ -1032,9 +1126,12 @@ impl Runnable for Job {
                 );
 
                 let start_time = Instant::now();
-                let creator = creator.unwrap_or_else(|| context.default_chain());
+                let creator = context.resolve_chain_id_or_default(creator.as_ref())?;
                 info!("Creating application on chain {}", creator);
                 let chain_client = context.make_chain_client(creator).await?;
+                let required_application_ids = context
+                    .resolve_application_ids(&required_application_ids.unwrap_or_default())
+                    .await?;
                 let parameters = read_json(json_parameters, json_parameters_path)?;
                 let argument = read_json(json_argument, json_argument_path)?;
 
@@ -1054,7 +1151,7 @@ impl Runnable for Job {
                                     module_id,
                                     parameters,
                                     argument,
-                                    required_application_ids.unwrap_or_default(),
+                                    required_application_ids,
                                 )
                                 .await
                         }
@@ -1088,9 +1185,12 @@ impl Runnable for Job {
                 );
 
                 let start_time = Instant::now();
-                let publisher = publisher.unwrap_or_else(|| context.default_chain());
+                let publisher = context.resolve_chain_id_or_default(publisher.as_ref())?;
                 info!("Publishing and creating application on chain {}", publisher);
                 let chain_client = context.make_chain_client(publisher).await?;
+                let required_application_ids = context
+                    .resolve_application_ids(&required_application_ids.unwrap_or_default())
+                    .await?;
                 let parameters = read_json(json_parameters, json_parameters_path)?;
                 let argument = read_json(json_argument, json_argument_path)?;
                 let module_id = context
@@ -1109,7 +1209,7 @@ impl Runnable for Job {
                                     module_id,
                                     parameters,
                                     argument,
-                                    required_application_ids.unwrap_or_default(),
+                                    required_application_ids,
                                 )
                                 .await
                         }
@@ -1163,10 +1263,13 @@ impl Runnable for Job {
                         Box::new(signer.into_value()),
                     );
                     let start_time = Instant::now();
-                    let publisher = publisher.unwrap_or_else(|| context.default_chain());
+                    let publisher = context.resolve_chain_id_or_default(publisher.as_ref())?;
                     info!("Creating application on chain {}", publisher);
                     let chain_client = context.make_chain_client(publisher).await?;
 
+                    let required_application_ids = context
+                        .resolve_application_ids(&required_application_ids.unwrap_or_default())
+                        .await?;
                     let parameters = read_json(json_parameters, json_parameters_path)?;
                     let argument = read_json(json_argument, json_argument_path)?;
                     let project_path = path.unwrap_or_else(|| env::current_dir().unwrap());
@@ -1190,7 +1293,7 @@ impl Runnable for Job {
                                         module_id,
                                         parameters,
                                         argument,
-                                        required_application_ids.unwrap_or_default(),
+                                        required_application_ids,
                                     )
                                     .await
                             }
@@ -1215,7 +1318,7 @@ impl Runnable for Job {
                     Box::new(signer.into_value()),
                 );
                 let start_time = Instant::now();
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_id = context.resolve_chain_id_or_default(chain_id.as_ref())?;
                 info!("Committing pending block for chain {}", chain_id);
                 let chain_client = context.make_chain_client(chain_id).await?;
                 match chain_client.process_pending_block().await? {
@@ -1424,6 +1527,10 @@ struct ClientOptions {
     #[arg(long, default_value = "1000")]
     max_cache_entries: usize,
 
+    /// The eviction policy of the storage cache: lru, lfu or segmented.
+    #[arg(long, default_value = "lru")]
+    cache_policy: CachePolicyKind,
+
     /// The WebAssembly runtime to use.
     #[arg(long)]
     wasm_runtime: Option<WasmRuntime>,
@@ -1451,6 +1558,7 @@ impl ClientOptions {
             max_cache_size: self.max_cache_size,
             max_entry_size: self.max_entry_size,
             max_cache_entries: self.max_cache_entries,
+            cache_policy: self.cache_policy,
         };
         CommonStoreConfig {
             max_concurrent_queries: self.max_concurrent_queries,
@@ -1543,6 +1651,8 @@ impl ClientOptions {
                 let storage_config = linera_service::storage::StorageConfig::RocksDb {
                     path: self.config_path()?.join("wallet.db"),
                     spawn_mode,
+                    tuning: linera_views::rocks_db::RocksDbTuning::default(),
+                    cache: Default::default(),
                 };
                 let namespace = "default".to_string();
                 Ok(StorageConfigNamespace {
@@ -1607,7 +1717,7 @@ impl ClientOptions {
     }
 }
 
-struct DatabaseToolJob<'a>(&'a DatabaseToolCommand);
+struct DatabaseToolJob<'a>(&'a StoreCommand);
 
 #[async_trait]
 impl RunnableWithStore for DatabaseToolJob<'_> {
@@ -1624,21 +1734,21 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
     {
         let start_time = Instant::now();
         match self.0 {
-            DatabaseToolCommand::DeleteAll => {
+            StoreCommand::DeleteAll => {
                 S::delete_all(&config).await?;
                 info!(
                     "All namespaces deleted in {} ms",
                     start_time.elapsed().as_millis()
                 );
             }
-            DatabaseToolCommand::DeleteNamespace => {
+            StoreCommand::DeleteNamespace => {
                 S::delete(&config, &namespace).await?;
                 info!(
                     "Namespace {namespace} deleted in {} ms",
                     start_time.elapsed().as_millis()
                 );
             }
-            DatabaseToolCommand::CheckExistence => {
+            StoreCommand::CheckExistence => {
                 let test = S::exists(&config, &namespace).await?;
                 info!(
                     "Existence of a namespace {namespace} checked in {} ms",
@@ -1652,7 +1762,7 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
                     return Ok(1);
                 }
             }
-            DatabaseToolCommand::Initialize {
+            StoreCommand::Initialize {
                 genesis_config_path,
             } => {
                 let genesis_config: GenesisConfig = util::read_json(genesis_config_path)?;
@@ -1664,7 +1774,7 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
                     start_time.elapsed().as_millis()
                 );
             }
-            DatabaseToolCommand::ListNamespaces => {
+            StoreCommand::ListNamespaces => {
                 let namespaces = S::list_all(&config).await?;
                 info!(
                     "Namespaces listed in {} ms",
@@ -1675,7 +1785,7 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
                     println!("{}", namespace);
                 }
             }
-            DatabaseToolCommand::ListBlobIds => {
+            StoreCommand::ListBlobIds => {
                 let blob_ids = DbStorage::<S, _>::list_blob_ids(&config, &namespace).await?;
                 info!("Blob IDs listed in {} ms", start_time.elapsed().as_millis());
                 info!("The list of blob IDs is:");
@@ -1683,7 +1793,7 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
                     println!("{}", id);
                 }
             }
-            DatabaseToolCommand::ListChainIds => {
+            StoreCommand::ListChainIds => {
                 let chain_ids = DbStorage::<S, _>::list_chain_ids(&config, &namespace).await?;
                 info!(
                     "Chain IDs listed in {} ms",
@@ -1699,6 +1809,101 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
     }
 }
 
+/// Connects to the database to copy chains from, then copies them using a [`CopyTargetJob`].
+struct CopySourceJob {
+    target: StoreConfig,
+    chain_id: Option<ChainId>,
+    up_to: Option<BlockHeight>,
+    progress_interval: usize,
+}
+
+#[async_trait]
+impl RunnableWithStore for CopySourceJob {
+    type Output = i32;
+
+    async fn run<S>(
+        self,
+        config: S::Config,
+        namespace: String,
+    ) -> Result<Self::Output, anyhow::Error>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        let chain_ids = match self.chain_id {
+            Some(chain_id) => vec![chain_id],
+            None => DbStorage::<S, _>::list_chain_ids(&config, &namespace).await?,
+        };
+        let source = DbStorage::<S, _>::connect(&config, &namespace, None).await?;
+        let job = CopyTargetJob {
+            source,
+            chain_ids,
+            up_to: self.up_to,
+            progress_interval: self.progress_interval,
+        };
+        Box::pin(self.target.run_with_store(job)).await
+    }
+}
+
+/// Copies chains from the `source` storage to the database it is run with.
+struct CopyTargetJob<Source> {
+    source: Source,
+    chain_ids: Vec<ChainId>,
+    up_to: Option<BlockHeight>,
+    progress_interval: usize,
+}
+
+#[async_trait]
+impl<Source> RunnableWithStore for CopyTargetJob<Source>
+where
+    Source: Storage + Send + Sync,
+{
+    type Output = i32;
+
+    async fn run<S>(
+        self,
+        config: S::Config,
+        namespace: String,
+    ) -> Result<Self::Output, anyhow::Error>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        let start_time = Instant::now();
+        let target = DbStorage::<S, _>::maybe_create_and_connect(&config, &namespace, None).await?;
+        if let Some(description) = self.source.read_network_description().await? {
+            match target.read_network_description().await? {
+                None => target.write_network_description(&description).await?,
+                Some(existing) => ensure!(
+                    existing == description,
+                    "The target database belongs to a different network: {existing:?}"
+                ),
+            }
+        }
+        let num_chains = self.chain_ids.len();
+        let mut num_certificates = 0;
+        for (index, chain_id) in self.chain_ids.into_iter().enumerate() {
+            let summary = linera_storage::copy_chain(&self.source, &target, chain_id, self.up_to)
+                .await
+                .with_context(|| format!("Failed to copy chain {chain_id}"))?;
+            debug!("Copied chain {chain_id}: {summary:?}");
+            num_certificates += summary.certificates_copied;
+            if (index + 1) % self.progress_interval.max(1) == 0 {
+                info!(
+                    "Copied {} of {num_chains} chains and {num_certificates} certificates in {} ms",
+                    index + 1,
+                    start_time.elapsed().as_millis()
+                );
+            }
+        }
+        info!(
+            "Copied {num_chains} chains and {num_certificates} certificates in {} ms",
+            start_time.elapsed().as_millis()
+        );
+        Ok(0)
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let options = ClientOptions::init();
 
@@ -1786,6 +1991,7 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
             blob_published_price,
             blob_byte_read_price,
             blob_byte_published_price,
+            bytecode_byte_published_price,
             operation_price,
             operation_byte_price,
             message_price,
@@ -1805,7 +2011,9 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
             maximum_oracle_response_bytes,
             maximum_http_response_bytes,
             http_request_timeout_ms,
+            maximum_concurrent_http_requests,
             http_request_allow_list,
+            http_request_allow_list_per_application,
             testing_prng_seed,
             network_name,
         } => {
@@ -1826,6 +2034,8 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                 blob_byte_read: blob_byte_read_price.unwrap_or(existing_policy.blob_byte_read),
                 blob_byte_published: blob_byte_published_price
                     .unwrap_or(existing_policy.blob_byte_published),
+                bytecode_byte_published: bytecode_byte_published_price
+                    .unwrap_or(existing_policy.bytecode_byte_published),
                 byte_stored: byte_stored_price.unwrap_or(existing_policy.byte_stored),
                 operation: operation_price.unwrap_or(existing_policy.operation),
                 operation_byte: operation_byte_price.unwrap_or(existing_policy.operation_byte),
@@ -1859,10 +2069,15 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                     .unwrap_or(existing_policy.maximum_http_response_bytes),
                 http_request_timeout_ms: http_request_timeout_ms
                     .unwrap_or(existing_policy.http_request_timeout_ms),
+                maximum_concurrent_http_requests: maximum_concurrent_http_requests
+                    .unwrap_or(existing_policy.maximum_concurrent_http_requests),
                 http_request_allow_list: http_request_allow_list
                     .as_ref()
                     .map(|list| list.iter().cloned().collect())
                     .unwrap_or(existing_policy.http_request_allow_list),
+                http_request_allow_list_per_application: http_request_allow_list_per_application
+                    .map(http_hosts_per_application)
+                    .unwrap_or(existing_policy.http_request_allow_list_per_application),
             };
             let timestamp = start_timestamp
                 .map(|st| {
@@ -2074,7 +2289,25 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
             }
         },
 
-        ClientCommand::Storage(command) => {
+        ClientCommand::Storage(DatabaseToolCommand::Copy {
+            from,
+            to,
+            chain_id,
+            up_to,
+            progress_interval,
+        }) => {
+            let source = from.add_common_config(options.common_config()).await?;
+            let target = to.add_common_config(options.common_config()).await?;
+            let job = CopySourceJob {
+                target,
+                chain_id: *chain_id,
+                up_to: *up_to,
+                progress_interval: *progress_interval,
+            };
+            Ok(Box::pin(source.run_with_store(job)).await?)
+        }
+
+        ClientCommand::Storage(DatabaseToolCommand::Store(command)) => {
             Ok(options.run_with_store(DatabaseToolJob(command)).await?)
         }
 
@@ -2087,7 +2320,7 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                 let start_time = Instant::now();
                 let chain_ids = if let Some(chain_id) = chain_id {
                     ensure!(!owned, "Cannot specify both --owned and a chain ID");
-                    vec![*chain_id]
+                    vec![chain_id.resolve("chain", options.wallet().await?.chain_ids())?]
                 } else if *owned {
                     options.wallet().await?.owned_chain_ids()
                 } else {
@@ -2111,11 +2344,9 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
 
             WalletCommand::SetDefault { chain_id } => {
                 let start_time = Instant::now();
-                options
-                    .wallet()
-                    .await?
-                    .mutate(|w| w.set_default_chain(*chain_id))
-                    .await??;
+                let mut wallet = options.wallet().await?;
+                let chain_id = chain_id.resolve("chain", wallet.chain_ids())?;
+                wallet.mutate(|w| w.set_default_chain(chain_id)).await??;
                 info!(
                     "Default chain set in {} ms",
                     start_time.elapsed().as_millis()
@@ -2125,11 +2356,9 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
 
             WalletCommand::ForgetKeys { chain_id } => {
                 let start_time = Instant::now();
-                let owner = options
-                    .wallet()
-                    .await?
-                    .mutate(|w| w.forget_keys(chain_id))
-                    .await??;
+                let mut wallet = options.wallet().await?;
+                let chain_id = chain_id.resolve("chain", wallet.chain_ids())?;
+                let owner = wallet.mutate(|w| w.forget_keys(&chain_id)).await??;
                 if !options
                     .signer()
                     .await?
@@ -2164,11 +2393,9 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
 
             WalletCommand::ForgetChain { chain_id } => {
                 let start_time = Instant::now();
-                options
-                    .wallet()
-                    .await?
-                    .mutate(|w| w.forget_chain(chain_id))
-                    .await??;
+                let mut wallet = options.wallet().await?;
+                let chain_id = chain_id.resolve("chain", wallet.chain_ids())?;
+                wallet.mutate(|w| w.forget_chain(&chain_id)).await??;
                 info!("Chain forgotten in {} ms", start_time.elapsed().as_millis());
                 Ok(0)
             }
@@ -1,41 +1,59 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{borrow::Cow, future::IntoFuture, iter, net::SocketAddr, num::NonZeroU16, sync::Arc};
+use std::{
+    borrow::Cow, future::IntoFuture, iter, net::SocketAddr, num::NonZeroU16, sync::Arc,
+    time::Duration,
+};
 
 use async_graphql::{
-    futures_util::Stream, resolver_utils::ContainerType, Error, MergedObject, OutputType,
-    ScalarType, Schema, SimpleObject, Subscription,
+    futures_util::Stream, http::ALL_WEBSOCKET_PROTOCOLS, resolver_utils::ContainerType, Error,
+    ErrorExtensions as _, MergedObject, OutputType, ScalarType, Schema, SimpleObject, Subscription,
+};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
+use axum::{
+    extract::{Path, WebSocketUpgrade},
+    http::{HeaderMap, StatusCode},
+    response,
+    response::IntoResponse,
+    Extension, Router,
 };
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{extract::Path, http::StatusCode, response, response::IntoResponse, Extension, Router};
-use futures::{lock::Mutex, Future, FutureExt as _};
+use base64::engine::{general_purpose::STANDARD, Engine as _};
+use futures::{lock::Mutex, stream, Future, FutureExt as _, StreamExt as _};
 use linera_base::{
     crypto::{CryptoError, CryptoHash},
     data_types::{
-        Amount, ApplicationDescription, ApplicationPermissions, Bytecode, Epoch, TimeDelta,
+        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode, Epoch,
+        TimeDelta, Timestamp,
+    },
+    identifiers::{
+        Account, AccountOwner, ApplicationId, ChainId, EventId, MessageId, ModuleId, StreamId,
+        StreamName,
     },
-    identifiers::{AccountOwner, ApplicationId, ChainId, ModuleId},
     ownership::{ChainOwnership, TimeoutConfig},
     vm::VmRuntime,
     BcsHexParseError,
 };
 use linera_chain::{
+    block::Block,
     types::{ConfirmedBlock, GenericCertificate},
-    ChainStateView,
+    ChainError, ChainStateView,
 };
 use linera_client::chain_listener::{ChainListener, ChainListenerConfig, ClientContext};
 use linera_core::{
     client::{ChainClient, ChainClientError},
     data_types::ClientOutcome,
-    worker::Notification,
+    local_node::LocalNodeError,
+    worker::{Reason, WorkerError},
 };
 use linera_execution::{
     committee::Committee,
     system::{AdminOperation, Recipient},
-    Operation, Query, QueryOutcome, QueryResponse, SystemOperation,
+    ExecutionError, Operation, Query, QueryLimits, QueryOutcome, QueryResponse, SystemOperation,
+    MAX_TRANSFER_MEMO_LEN,
 };
 use linera_sdk::linera_base_types::BlobContent;
+use linera_storage::Storage as _;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error as ThisError;
@@ -44,8 +62,180 @@ use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
 use tracing::{debug, error, info, instrument, trace};
 
+use self::notifications::NotificationLog;
+pub use self::notifications::{ChainNotification, NotificationCursor};
 use crate::util;
 
+mod notifications;
+
+#[cfg(with_metrics)]
+mod metrics {
+    use std::sync::LazyLock;
+
+    use linera_base::prometheus_util::{
+        exponential_bucket_latencies, register_histogram_vec, register_int_counter_vec,
+        register_int_gauge_vec,
+    };
+    use prometheus::{HistogramVec, IntCounterVec, IntGaugeVec};
+
+    pub static REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "node_service_requests",
+            "Number of GraphQL requests to the node service",
+            &["operation", "status"],
+        )
+    });
+
+    pub static REQUEST_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec(
+            "node_service_request_latency",
+            "Node service GraphQL request latency",
+            &["operation"],
+            exponential_bucket_latencies(10_000.0),
+        )
+    });
+
+    pub static ACTIVE_SUBSCRIPTIONS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+        register_int_gauge_vec(
+            "node_service_active_subscriptions",
+            "Number of active GraphQL subscriptions to the node service",
+            &["subscription"],
+        )
+    });
+
+    /// Counts an active subscription until it is dropped.
+    pub struct ActiveSubscription(&'static str);
+
+    impl ActiveSubscription {
+        pub fn new(subscription: &'static str) -> Self {
+            ACTIVE_SUBSCRIPTIONS
+                .with_label_values(&[subscription])
+                .inc();
+            Self(subscription)
+        }
+    }
+
+    impl Drop for ActiveSubscription {
+        fn drop(&mut self) {
+            ACTIVE_SUBSCRIPTIONS.with_label_values(&[self.0]).dec();
+        }
+    }
+}
+
+/// Counts the `stream` as an active subscription with the given name until it is dropped.
+fn track_subscription<S: Stream>(
+    subscription: &'static str,
+    stream: S,
+) -> impl Stream<Item = S::Item> {
+    #[cfg(with_metrics)]
+    let stream = {
+        let active = metrics::ActiveSubscription::new(subscription);
+        stream.inspect(move |_| {
+            let _active = &active;
+        })
+    };
+    #[cfg(not(with_metrics))]
+    let _ = subscription;
+    stream
+}
+
+/// The root fields of the node service's schema, which are used as the `operation` label of
+/// the request metrics.
+#[cfg(with_metrics)]
+const OPERATION_LABELS: &[&str] = &[
+    "applications",
+    "block",
+    "blockContaining",
+    "blockSummaries",
+    "blocks",
+    "chain",
+    "chains",
+    "changeApplicationPermissions",
+    "changeMultipleOwners",
+    "changeOwner",
+    "claim",
+    "closeChain",
+    "createApplication",
+    "createCommittee",
+    "events",
+    "newEvents",
+    "notifications",
+    "openChain",
+    "openMultiOwnerChain",
+    "processInbox",
+    "publishDataBlob",
+    "publishModule",
+    "readDataBlob",
+    "removeCommittee",
+    "retryPendingBlock",
+    "transfer",
+    "version",
+];
+
+/// The `operation` label of the requests to applications.
+#[cfg(with_metrics)]
+const APPLICATION_OPERATION_LABEL: &str = "application";
+
+/// The `operation` label of the requests that don't start with one of the root fields.
+#[cfg(with_metrics)]
+const OTHER_OPERATION_LABEL: &str = "other";
+
+/// Returns the `operation` label of a GraphQL request, i.e. the first root field that the
+/// executed operation selects.
+///
+/// The labels are limited to [`OPERATION_LABELS`], so that clients can't create arbitrarily
+/// many metrics: any other request, e.g. an invalid one, is counted as `"other"`.
+#[cfg(with_metrics)]
+fn operation_label(request: &async_graphql::Request) -> &'static str {
+    use async_graphql::parser::types::{DocumentOperations, Selection};
+
+    let Ok(document) = async_graphql::parser::parse_query(&request.query) else {
+        return OTHER_OPERATION_LABEL;
+    };
+    let operation = match document.operations {
+        DocumentOperations::Single(operation) => Some(operation),
+        DocumentOperations::Multiple(mut operations) => match &request.operation_name {
+            Some(name) => operations.remove(name.as_str()),
+            // Without an explicit `operationName`, a document with a single named operation
+            // executes that one.
+            None if operations.len() == 1 => operations.into_values().next(),
+            None => None,
+        },
+    };
+    let field_name = operation.and_then(|operation| {
+        operation
+            .node
+            .selection_set
+            .node
+            .items
+            .into_iter()
+            .find_map(|selection| match selection.node {
+                Selection::Field(field) => Some(field.node.name.node),
+                Selection::FragmentSpread(_) | Selection::InlineFragment(_) => None,
+            })
+    });
+    field_name
+        .and_then(|name| {
+            OPERATION_LABELS
+                .iter()
+                .find(|label| **label == name.as_str())
+        })
+        .copied()
+        .unwrap_or(OTHER_OPERATION_LABEL)
+}
+
+/// Records a GraphQL request with the given `operation` label and how long it took.
+#[cfg(with_metrics)]
+fn record_request(operation: &str, is_ok: bool, start: linera_base::time::Instant) {
+    let status = if is_ok { "ok" } else { "error" };
+    metrics::REQUESTS
+        .with_label_values(&[operation, status])
+        .inc();
+    metrics::REQUEST_LATENCY
+        .with_label_values(&[operation])
+        .observe(start.elapsed().as_secs_f64() * 1000.0);
+}
+
 #[derive(SimpleObject, Serialize, Deserialize, Clone)]
 pub struct Chains {
     pub list: Vec<ChainId>,
@@ -59,9 +249,178 @@ pub struct QueryRoot<C> {
     default_chain: Option<ChainId>,
 }
 
+/// A new block of a chain, as delivered by the `blocks` subscription.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct BlockNotification {
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The hash of the block.
+    pub hash: CryptoHash,
+    /// The timestamp of the block.
+    pub timestamp: Timestamp,
+    /// The applications whose state the block may have changed, so that their queries
+    /// need to be refetched.
+    pub changed_applications: Vec<ApplicationId>,
+}
+
+impl From<&ConfirmedBlock> for BlockNotification {
+    fn from(block: &ConfirmedBlock) -> Self {
+        BlockNotification {
+            height: block.block().header.height,
+            hash: block.inner().hash(),
+            timestamp: block.block().header.timestamp,
+            changed_applications: block
+                .block()
+                .changed_application_ids()
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// The maximum number of blocks that a `blockSummaries` query can return.
+pub const MAX_BLOCK_RANGE: u64 = 1_000;
+
+/// The maximum number of events that an `events` query can return.
+pub const MAX_EVENT_RANGE: u32 = 1_000;
+
+/// The number of certificates read from storage at once when going through the confirmed log
+/// of a chain.
+const CERTIFICATE_CHUNK_SIZE: usize = 50;
+
+/// A summary of a confirmed block, without its body.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct BlockSummary {
+    /// The hash of the block.
+    pub hash: CryptoHash,
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The timestamp of the block.
+    pub timestamp: Timestamp,
+    /// The number of operations in the block.
+    pub operation_count: u32,
+    /// The number of incoming bundles in the block.
+    pub incoming_bundle_count: u32,
+}
+
+impl BlockSummary {
+    fn new(hash: CryptoHash, block: &Block) -> Self {
+        BlockSummary {
+            hash,
+            height: block.header.height,
+            timestamp: block.header.timestamp,
+            operation_count: block.body.operations.len() as u32,
+            incoming_bundle_count: block.body.incoming_bundles.len() as u32,
+        }
+    }
+}
+
+/// The block in which a transfer was included.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct TransferOutcome {
+    /// The hash of the block's certificate.
+    pub hash: CryptoHash,
+    /// The height of the block in the source chain.
+    pub height: BlockHeight,
+}
+
+/// Converts the error of a transfer into a GraphQL error, with a machine-readable `code`
+/// extension if the transfer itself was invalid.
+fn transfer_error(error: ChainClientError) -> Error {
+    let code = transfer_error_code(&error);
+    let error = Error::from(error);
+    match code {
+        Some(code) => error.extend_with(|_, extensions| extensions.set("code", code)),
+        None => error,
+    }
+}
+
+/// Returns the code of the error of an invalid transfer, if it is one.
+fn transfer_error_code(error: &ChainClientError) -> Option<&'static str> {
+    match execution_error(error)? {
+        ExecutionError::TransferMemoTooLong => Some("MEMO_TOO_LONG"),
+        ExecutionError::IncorrectTransferAmount => Some("INVALID_AMOUNT"),
+        ExecutionError::UnauthenticatedTransferOwner => Some("UNAUTHENTICATED_OWNER"),
+        ExecutionError::InsufficientFunding { .. }
+        | ExecutionError::InsufficientFundingForFees { .. } => Some("INSUFFICIENT_BALANCE"),
+        _ => None,
+    }
+}
+
+/// Returns the error to report if a query was stopped because it exceeded its limits.
+fn query_limit_error(error: &ChainClientError) -> Option<NodeServiceError> {
+    let error = execution_error(error)?;
+    let code = match error {
+        ExecutionError::QueryTimedOut => "QUERY_TIMED_OUT",
+        ExecutionError::QueryOutOfFuel => "QUERY_OUT_OF_FUEL",
+        _ => return None,
+    };
+    Some(NodeServiceError::QueryLimitExceeded {
+        code,
+        message: error.to_string(),
+    })
+}
+
+/// Returns the execution error that caused a client error, if any.
+fn execution_error(error: &ChainClientError) -> Option<&ExecutionError> {
+    let ChainClientError::LocalNodeError(LocalNodeError::WorkerError(WorkerError::ChainError(
+        error,
+    ))) = error
+    else {
+        return None;
+    };
+    let ChainError::ExecutionError(error, _) = &**error else {
+        return None;
+    };
+    Some(&**error)
+}
+
+/// The header lowering the timeout of an application query, in milliseconds.
+pub const QUERY_TIMEOUT_HEADER: &str = "x-linera-query-timeout-ms";
+
+/// The header lowering the fuel limit of an application query.
+pub const QUERY_FUEL_LIMIT_HEADER: &str = "x-linera-query-fuel-limit";
+
+/// How the payloads of events are encoded in GraphQL responses.
+#[derive(async_graphql::Enum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EventEncoding {
+    /// Hexadecimal encoding.
+    #[default]
+    Hex,
+    /// Base64 encoding, with padding.
+    Base64,
+}
+
+impl EventEncoding {
+    fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            EventEncoding::Hex => hex::encode(bytes),
+            EventEncoding::Base64 => STANDARD.encode(bytes),
+        }
+    }
+}
+
+/// An event of an application's stream.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct StreamEvent {
+    /// The index of the event in its stream.
+    pub index: u32,
+    /// The payload of the event, in the requested encoding.
+    pub value: String,
+}
+
+/// Returns the ID of the stream of a user application with the given name.
+fn user_stream_id(application_id: ApplicationId, stream_name: String) -> StreamId {
+    StreamId {
+        application_id: application_id.into(),
+        stream_name: StreamName::from(stream_name),
+    }
+}
+
 /// Our root GraphQL subscription type.
 pub struct SubscriptionRoot<C> {
     context: Arc<Mutex<C>>,
+    notification_log: Arc<NotificationLog>,
 }
 
 /// Our root GraphQL mutation type.
@@ -81,6 +440,10 @@ enum NodeServiceError {
     UnknownChainId { chain_id: String },
     #[error("malformed chain ID: {0}")]
     InvalidChainId(CryptoError),
+    #[error("malformed header {name}: {value:?}")]
+    InvalidHeader { name: &'static str, value: String },
+    #[error("{message}")]
+    QueryLimitExceeded { code: &'static str, message: String },
 }
 
 impl IntoResponse for NodeServiceError {
@@ -101,6 +464,17 @@ impl IntoResponse for NodeServiceError {
                 StatusCode::BAD_REQUEST,
                 vec!["invalid chain ID".to_string()],
             ),
+            NodeServiceError::InvalidHeader { name, value } => (
+                StatusCode::BAD_REQUEST,
+                vec![format!("malformed header {name}: {value:?}")],
+            ),
+            NodeServiceError::QueryLimitExceeded { code, message } => {
+                // The query itself was valid, so this is reported like any other GraphQL error.
+                let errors = json!([{ "message": message, "extensions": { "code": code } }]);
+                return json!({ "data": null, "errors": errors })
+                    .to_string()
+                    .into_response();
+            }
         };
         let tuple = (tuple.0, json!({"error": tuple.1}).to_string());
         tuple.into_response()
@@ -113,17 +487,156 @@ where
     C: ClientContext,
 {
     /// Subscribes to notifications from the specified chain.
+    ///
+    /// Each notification has a `cursor`. If the `cursor` of the last notification seen is
+    /// given, the notifications after it are returned first, so that a reconnecting client
+    /// does not miss any, as long as they are still retained. A subscriber that falls behind
+    /// or misses notifications that aren't retained anymore is sent a `LAGGED` error. The
+    /// cursors from another epoch, e.g. from before the node service restarted, are rejected.
     async fn notifications(
         &self,
         chain_id: ChainId,
-    ) -> Result<impl Stream<Item = Notification>, Error> {
+        cursor: Option<NotificationCursor>,
+    ) -> Result<impl Stream<Item = Result<ChainNotification, Error>>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let notifications = client.subscribe().await?;
+        let notifications = self
+            .notification_log
+            .subscribe(chain_id, cursor, notifications)?;
+        Ok(track_subscription("notifications", notifications))
+    }
+
+    /// Subscribes to the new blocks of the specified chain. If `since` is given, the blocks
+    /// from that height on are returned first, so that a reconnecting client does not miss
+    /// the blocks produced while it was disconnected. At most `MAX_BLOCK_RANGE` past blocks
+    /// can be requested at once.
+    async fn blocks(
+        &self,
+        chain_id: ChainId,
+        since: Option<BlockHeight>,
+    ) -> Result<impl Stream<Item = Result<BlockNotification, Error>>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        // Subscribing before reading the past blocks makes sure that none is missed in
+        // between. The notifications of the past blocks are skipped.
+        let notifications = client.subscribe().await?;
+        let mut past_hashes = Vec::new();
+        let mut next_height = BlockHeight::ZERO;
+        if let Some(since) = since {
+            let view = client.chain_state_view().await?;
+            next_height = view.tip_state.get().next_block_height;
+            let count = next_height.0.saturating_sub(since.0);
+            if count > MAX_BLOCK_RANGE {
+                return Err(Error::new(format!(
+                    "There are {count} blocks since height {since}, but at most \
+                     {MAX_BLOCK_RANGE} can be returned at once: use a later height."
+                )));
+            }
+            if since < next_height {
+                past_hashes = view
+                    .confirmed_log
+                    .read(usize::try_from(since.0)?..usize::try_from(next_height.0)?)
+                    .await?;
+            }
+        }
+        // The past blocks are read in chunks, as the subscriber consumes them.
+        let storage = client.storage_client().clone();
+        let past_blocks = stream::iter(past_hashes)
+            .chunks(CERTIFICATE_CHUNK_SIZE)
+            .then(move |hashes| {
+                let storage = storage.clone();
+                async move {
+                    let blocks = match storage.read_certificates(hashes).await {
+                        Ok(certificates) => certificates
+                            .iter()
+                            .map(|certificate| Ok(BlockNotification::from(certificate.value())))
+                            .collect(),
+                        Err(error) => vec![Err(Error::from(error))],
+                    };
+                    stream::iter(blocks)
+                }
+            })
+            .flatten();
+        let new_blocks = notifications.filter_map(move |notification| {
+            let client = client.clone();
+            async move {
+                let Reason::NewBlock { height, hash } = notification.reason else {
+                    return None;
+                };
+                if height < next_height {
+                    return None;
+                }
+                let block = client.read_confirmed_block(hash).await;
+                Some(
+                    block
+                        .map(|block| BlockNotification::from(&block))
+                        .map_err(Error::from),
+                )
+            }
+        });
+        let blocks = past_blocks.chain(new_blocks);
+        Ok(track_subscription("blocks", blocks))
+    }
+
+    /// Subscribes to the events of a stream of an application on the specified chain, as
+    /// the blocks emitting them are processed.
+    async fn new_events(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: String,
+        encoding: Option<EventEncoding>,
+    ) -> Result<impl Stream<Item = Result<StreamEvent, Error>>, Error> {
         let client = self
             .context
             .lock()
             .await
             .make_chain_client(chain_id)
             .await?;
-        Ok(client.subscribe().await?)
+        let notifications = client.subscribe().await?;
+        let stream_id = user_stream_id(application_id, stream_name);
+        let encoding = encoding.unwrap_or_default();
+        let events = notifications.filter_map(move |notification| {
+            let client = client.clone();
+            let stream_id = stream_id.clone();
+            async move {
+                let Reason::NewBlock { hash, .. } = notification.reason else {
+                    return None;
+                };
+                let block = match client.read_confirmed_block(hash).await {
+                    Ok(block) => block,
+                    Err(error) => return Some(vec![Err(error.into())]),
+                };
+                let events = block
+                    .block()
+                    .body
+                    .events
+                    .iter()
+                    .flatten()
+                    .filter(|event| event.stream_id == stream_id)
+                    .map(|event| {
+                        Ok(StreamEvent {
+                            index: event.index,
+                            value: encoding.encode(&event.value),
+                        })
+                    })
+                    .collect();
+                Some(events)
+            }
+        });
+        Ok(track_subscription(
+            "new_events",
+            events.flat_map(stream::iter),
+        ))
     }
 }
 
@@ -234,22 +747,47 @@ where
         }
     }
 
-    /// Transfers `amount` units of value from the given owner's account to the recipient.
-    /// If no owner is given, try to take the units out of the chain account.
+    /// Transfers `amount` units of value from the `source` account to the `destination`
+    /// account, possibly on another chain. If the source owner is `AccountOwner::CHAIN`, the
+    /// units are taken out of the chain account. The `memo`, if any, is stored in the
+    /// operation and can be at most `MAX_TRANSFER_MEMO_LEN` bytes long.
+    ///
+    /// Invalid transfers fail with an error whose `code` extension is one of `MEMO_TOO_LONG`,
+    /// `INVALID_AMOUNT`, `UNAUTHENTICATED_OWNER` and `INSUFFICIENT_BALANCE`.
     async fn transfer(
         &self,
-        chain_id: ChainId,
-        owner: AccountOwner,
-        recipient: Recipient,
+        source: Account,
+        destination: Account,
         amount: Amount,
-    ) -> Result<CryptoHash, Error> {
-        self.apply_client_command(&chain_id, move |client| async move {
-            let result = client
-                .transfer(owner, amount, recipient)
-                .await
-                .map_err(Error::from)
-                .map(|outcome| outcome.map(|certificate| certificate.hash()));
-            (result, client)
+        memo: Option<String>,
+    ) -> Result<TransferOutcome, Error> {
+        if memo
+            .as_ref()
+            .is_some_and(|memo| memo.len() > MAX_TRANSFER_MEMO_LEN)
+        {
+            return Err(Error::new(ExecutionError::TransferMemoTooLong.to_string())
+                .extend_with(|_, extensions| extensions.set("code", "MEMO_TOO_LONG")));
+        }
+        self.apply_client_command(&source.chain_id, move |client| {
+            let memo = memo.clone();
+            async move {
+                let recipient = Recipient::Account(destination);
+                let result = match memo {
+                    Some(memo) => {
+                        client
+                            .transfer_with_memo(source.owner, amount, recipient, memo)
+                            .await
+                    }
+                    None => client.transfer(source.owner, amount, recipient).await,
+                };
+                let result = result.map_err(transfer_error).map(|outcome| {
+                    outcome.map(|certificate| TransferOutcome {
+                        hash: certificate.hash(),
+                        height: certificate.block().header.height,
+                    })
+                });
+                (result, client)
+            }
         })
         .await
     }
@@ -554,7 +1092,7 @@ where
             }
         })
         .await
-        .map(|_| CryptoHash::new(&BlobContent::new_data(bytes)))
+        .map(|_| BlobContent::hash_data(&bytes))
     }
 
     /// Creates a new application.
@@ -693,6 +1231,211 @@ where
         }
     }
 
+    /// Returns the summaries of the blocks of a chain with the heights from `from` (by default
+    /// 0) to `to` (included, by default the latest block), but at most `limit` of them. At
+    /// most `MAX_BLOCK_RANGE` blocks can be requested at once.
+    async fn block_summaries(
+        &self,
+        chain_id: ChainId,
+        from: Option<BlockHeight>,
+        to: Option<BlockHeight>,
+        limit: Option<u32>,
+    ) -> Result<Vec<BlockSummary>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let view = client.chain_state_view().await?;
+        let next_height = view.tip_state.get().next_block_height;
+        let start = from.unwrap_or(BlockHeight::ZERO);
+        let mut end = to.map_or(next_height, |to| to.saturating_add_blocks(1));
+        if let Some(limit) = limit {
+            end = end.min(start.saturating_add_blocks(u64::from(limit)));
+        }
+        // The requested range is checked before it is capped by the chain's length, so that
+        // the result of a valid query does not depend on how long the chain is.
+        let count = end.0.saturating_sub(start.0);
+        if count > MAX_BLOCK_RANGE {
+            return Err(Error::new(format!(
+                "The requested range contains {count} blocks, but at most {MAX_BLOCK_RANGE} \
+                 can be returned at once: use a smaller range or a limit."
+            )));
+        }
+        let end = end.min(next_height);
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let hashes = view
+            .confirmed_log
+            .read(usize::try_from(start.0)?..usize::try_from(end.0)?)
+            .await?;
+        drop(view);
+        // The certificates of a query may be read from stale read replicas.
+        let storage = client.storage_client().with_bounded_staleness(true);
+        let mut summaries = Vec::with_capacity(hashes.len());
+        for chunk in hashes.chunks(CERTIFICATE_CHUNK_SIZE) {
+            let certificates = storage.read_certificates(chunk.to_vec()).await?;
+            summaries.extend(
+                certificates
+                    .iter()
+                    .map(|certificate| BlockSummary::new(certificate.hash(), certificate.block())),
+            );
+        }
+        Ok(summaries)
+    }
+
+    /// Returns the block of a chain that contains the given certificate or message: Exactly
+    /// one of `certificate_hash` and `message_id` must be given.
+    ///
+    /// A certificate hash matches the block with that hash, or the block that received
+    /// messages from that certificate. A message ID matches the block that sent the message,
+    /// if it is on this chain, or else the block that received it.
+    async fn block_containing(
+        &self,
+        chain_id: ChainId,
+        certificate_hash: Option<CryptoHash>,
+        message_id: Option<MessageId>,
+    ) -> Result<Option<ConfirmedBlock>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let view = client.chain_state_view().await?;
+        let matches: Box<dyn Fn(&GenericCertificate<ConfirmedBlock>) -> bool + Send + Sync> =
+            match (certificate_hash, message_id) {
+                (Some(hash), None) => Box::new(move |certificate| {
+                    certificate.hash() == hash
+                        || certificate
+                            .block()
+                            .body
+                            .incoming_bundles
+                            .iter()
+                            .any(|bundle| bundle.bundle.certificate_hash == hash)
+                }),
+                (None, Some(message_id)) if message_id.chain_id == chain_id => {
+                    let Some(hash) = view
+                        .confirmed_log
+                        .get(usize::try_from(message_id.height.0)?)
+                        .await?
+                    else {
+                        return Ok(None);
+                    };
+                    drop(view);
+                    let block = client.read_confirmed_block(hash).await?;
+                    let count = block.block().messages().iter().map(Vec::len).sum::<usize>();
+                    return Ok((usize::try_from(message_id.index)? < count).then_some(block));
+                }
+                (None, Some(message_id)) => Box::new(move |certificate| {
+                    certificate
+                        .block()
+                        .body
+                        .incoming_bundles
+                        .iter()
+                        .filter(|bundle| {
+                            bundle.origin == message_id.chain_id
+                                && bundle.bundle.height == message_id.height
+                        })
+                        .flat_map(|bundle| bundle.messages_and_ids())
+                        .any(|(id, _)| id == message_id)
+                }),
+                _ => {
+                    return Err(Error::new(
+                        "Exactly one of `certificateHash` and `messageId` must be given.",
+                    ))
+                }
+            };
+        // Go through the confirmed log from the latest block down, a chunk at a time, so that
+        // only a few certificates are in memory at once, and without locking the chain state
+        // in the meantime.
+        let mut end = view.confirmed_log.count();
+        drop(view);
+        let storage = client.storage_client().with_bounded_staleness(true);
+        while end > 0 {
+            let start = end.saturating_sub(CERTIFICATE_CHUNK_SIZE);
+            let hashes = client
+                .chain_state_view()
+                .await?
+                .confirmed_log
+                .read(start..end)
+                .await?;
+            let certificates = storage.read_certificates(hashes).await?;
+            if let Some(certificate) = certificates
+                .into_iter()
+                .rev()
+                .find(|certificate| matches(certificate))
+            {
+                return Ok(Some(certificate.into_value()));
+            }
+            end = start;
+        }
+        Ok(None)
+    }
+
+    /// Returns the events of a stream of an application on a chain, with the indices from
+    /// `start` (by default 0) to `end` (excluded, by default the number of events in the
+    /// stream). At most `MAX_EVENT_RANGE` events can be requested at once.
+    async fn events(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        stream_name: String,
+        start: Option<u32>,
+        end: Option<u32>,
+        encoding: Option<EventEncoding>,
+    ) -> Result<Vec<StreamEvent>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let stream_id = user_stream_id(application_id, stream_name);
+        let count = client
+            .chain_state_view()
+            .await?
+            .execution_state
+            .stream_event_counts
+            .get(&stream_id)
+            .await?
+            .unwrap_or(0);
+        let start = start.unwrap_or(0);
+        let end = end.unwrap_or(count);
+        // As for blocks, the range is checked before it is capped by the stream's length.
+        let requested = end.saturating_sub(start);
+        if requested > MAX_EVENT_RANGE {
+            return Err(Error::new(format!(
+                "The requested range contains {requested} events, but at most \
+                 {MAX_EVENT_RANGE} can be returned at once: use a smaller range."
+            )));
+        }
+        let indices = start..end.min(count);
+        let event_ids = indices
+            .clone()
+            .map(|index| EventId {
+                chain_id,
+                stream_id: stream_id.clone(),
+                index,
+            })
+            .collect::<Vec<_>>();
+        let encoding = encoding.unwrap_or_default();
+        let values = client
+            .storage_client()
+            .with_bounded_staleness(true)
+            .read_events(&event_ids)
+            .await?;
+        Ok(indices
+            .zip(values)
+            .map(|(index, value)| StreamEvent {
+                index,
+                value: encoding.encode(&value),
+            })
+            .collect())
+    }
+
     /// Returns the version information on this node service.
     async fn version(&self) -> linera_version::VersionInfo {
         linera_version::VersionInfo::default()
@@ -805,9 +1548,51 @@ where
     config: ChainListenerConfig,
     port: NonZeroU16,
     default_chain: Option<ChainId>,
+    query_limits: QueryLimits,
+    subscription_config: SubscriptionConfig,
+    notification_log: Arc<NotificationLog>,
     context: Arc<Mutex<C>>,
 }
 
+/// How the node service serves GraphQL subscriptions.
+#[derive(Clone, Debug, clap::Args)]
+pub struct SubscriptionConfig {
+    /// The interval between the keepalive pings sent to websocket clients, which are
+    /// disconnected if they don't answer in time. Zero disables keepalive pings.
+    #[arg(long = "websocket-keepalive-ms", default_value = "30000")]
+    pub keepalive_ms: u64,
+
+    /// The number of notifications buffered for each subscriber. If a subscriber falls
+    /// further behind, its oldest notifications are dropped and it is sent a `LAGGED` error.
+    #[arg(long = "subscriber-buffer-size", default_value = "256")]
+    pub buffer_size: usize,
+
+    /// The number of past notifications retained for each chain, from which a reconnecting
+    /// subscriber can resume.
+    #[arg(long = "notification-retention", default_value = "1000")]
+    pub retention: usize,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_ms: 30_000,
+            buffer_size: 256,
+            retention: 1_000,
+        }
+    }
+}
+
+impl SubscriptionConfig {
+    fn keepalive_interval(&self) -> Option<Duration> {
+        (self.keepalive_ms > 0).then(|| Duration::from_millis(self.keepalive_ms))
+    }
+
+    fn notification_log(&self) -> NotificationLog {
+        NotificationLog::new(self.buffer_size, self.retention)
+    }
+}
+
 impl<C> Clone for NodeService<C>
 where
     C: ClientContext,
@@ -817,6 +1602,9 @@ where
             config: self.config.clone(),
             port: self.port,
             default_chain: self.default_chain,
+            query_limits: self.query_limits,
+            subscription_config: self.subscription_config.clone(),
+            notification_log: Arc::clone(&self.notification_log),
             context: Arc::clone(&self.context),
         }
     }
@@ -837,10 +1625,27 @@ where
             config,
             port,
             default_chain,
+            query_limits: QueryLimits::default(),
+            subscription_config: SubscriptionConfig::default(),
+            notification_log: Arc::new(SubscriptionConfig::default().notification_log()),
             context: Arc::new(Mutex::new(context)),
         }
     }
 
+    /// Sets how GraphQL subscriptions are served.
+    pub fn with_subscription_config(mut self, subscription_config: SubscriptionConfig) -> Self {
+        self.notification_log = Arc::new(subscription_config.notification_log());
+        self.subscription_config = subscription_config;
+        self
+    }
+
+    /// Sets the maximum limits of application queries. They can be lowered for each request
+    /// using the [`QUERY_TIMEOUT_HEADER`] and [`QUERY_FUEL_LIMIT_HEADER`] headers.
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.query_limits = query_limits;
+        self
+    }
+
     pub fn schema(&self) -> Schema<QueryRoot<C>, MutationRoot<C>, SubscriptionRoot<C>> {
         Schema::build(
             QueryRoot {
@@ -853,6 +1658,7 @@ where
             },
             SubscriptionRoot {
                 context: Arc::clone(&self.context),
+                notification_log: Arc::clone(&self.notification_log),
             },
         )
         .finish()
@@ -873,7 +1679,7 @@ where
                 application_handler,
             )
             .route("/ready", axum::routing::get(|| async { "ready!" }))
-            .route_service("/ws", GraphQLSubscription::new(self.schema()))
+            .route("/ws", axum::routing::get(Self::websocket_handler))
             .layer(Extension(self.clone()))
             // TODO(#551): Provide application authentication.
             .layer(CorsLayer::permissive());
@@ -902,12 +1708,13 @@ where
         application_id: ApplicationId,
         request: Vec<u8>,
         chain_id: ChainId,
+        limits: QueryLimits,
     ) -> Result<Vec<u8>, NodeServiceError> {
         let QueryOutcome {
             response,
             operations,
         } = self
-            .query_user_application(application_id, request, chain_id)
+            .query_user_application(application_id, request, chain_id, limits)
             .await?;
         if operations.is_empty() {
             return Ok(response);
@@ -946,6 +1753,7 @@ where
         application_id: ApplicationId,
         bytes: Vec<u8>,
         chain_id: ChainId,
+        limits: QueryLimits,
     ) -> Result<QueryOutcome<Vec<u8>>, NodeServiceError> {
         let query = Query::User {
             application_id,
@@ -963,7 +1771,10 @@ where
         let QueryOutcome {
             response,
             operations,
-        } = client.query_application(query).await?;
+        } = client
+            .query_application_with_limits(query, limits)
+            .await
+            .map_err(|error| query_limit_error(&error).unwrap_or_else(|| error.into()))?;
         match response {
             QueryResponse::System(_) => {
                 unreachable!("cannot get a system response for a user query")
@@ -975,14 +1786,31 @@ where
         }
     }
 
+    /// Serves GraphQL subscriptions over a websocket, sending keepalive pings to the client.
+    async fn websocket_handler(
+        service: Extension<Self>,
+        protocol: GraphQLProtocol,
+        websocket: WebSocketUpgrade,
+    ) -> response::Response {
+        let keepalive = service.0.subscription_config.keepalive_interval();
+        websocket
+            .protocols(ALL_WEBSOCKET_PROTOCOLS)
+            .on_upgrade(move |stream| {
+                GraphQLWebSocket::new(stream, service.0.schema(), protocol)
+                    .keepalive_timeout(keepalive)
+                    .serve()
+            })
+    }
+
     /// Executes a GraphQL query and generates a response for our `Schema`.
     async fn index_handler(service: Extension<Self>, request: GraphQLRequest) -> GraphQLResponse {
-        service
-            .0
-            .schema()
-            .execute(request.into_inner())
-            .await
-            .into()
+        let request = request.into_inner();
+        #[cfg(with_metrics)]
+        let (operation, start) = (operation_label(&request), linera_base::time::Instant::now());
+        let response = service.0.schema().execute(request).await;
+        #[cfg(with_metrics)]
+        record_request(operation, response.is_ok(), start);
+        response.into()
     }
 
     /// Executes a GraphQL query against an application.
@@ -991,20 +1819,112 @@ where
     async fn application_handler(
         Path((chain_id, application_id)): Path<(String, String)>,
         service: Extension<Self>,
+        headers: HeaderMap,
         request: String,
     ) -> Result<Vec<u8>, NodeServiceError> {
         let chain_id: ChainId = chain_id.parse().map_err(NodeServiceError::InvalidChainId)?;
         let application_id: ApplicationId = application_id.parse()?;
+        // The headers can only lower the configured limits.
+        let mut limits = service.0.query_limits;
+        if let Some(timeout_ms) = Self::parse_header(&headers, QUERY_TIMEOUT_HEADER)? {
+            let timeout = Duration::from_millis(timeout_ms);
+            limits.timeout = Some(limits.timeout.map_or(timeout, |limit| limit.min(timeout)));
+        }
+        if let Some(fuel) = Self::parse_header(&headers, QUERY_FUEL_LIMIT_HEADER)? {
+            limits.fuel = Some(limits.fuel.map_or(fuel, |limit| limit.min(fuel)));
+        }
 
         debug!(
             "Processing request for application {application_id} on chain {chain_id}:\n{:?}",
             &request
         );
+        #[cfg(with_metrics)]
+        let start = linera_base::time::Instant::now();
         let response = service
             .0
-            .handle_service_request(application_id, request.into_bytes(), chain_id)
-            .await?;
+            .handle_service_request(application_id, request.into_bytes(), chain_id, limits)
+            .await;
+        #[cfg(with_metrics)]
+        record_request(APPLICATION_OPERATION_LABEL, response.is_ok(), start);
+
+        response
+    }
 
-        Ok(response)
+    /// Parses the numeric value of the header with the given name, if present.
+    fn parse_header(
+        headers: &HeaderMap,
+        name: &'static str,
+    ) -> Result<Option<u64>, NodeServiceError> {
+        let Some(value) = headers.get(name) else {
+            return Ok(None);
+        };
+        let invalid = || NodeServiceError::InvalidHeader {
+            name,
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        };
+        let value = value.to_str().map_err(|_| invalid())?;
+        value.parse().map(Some).map_err(|_| invalid())
+    }
+}
+
+#[cfg(all(test, with_metrics))]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use async_graphql::parser::types::{TypeKind, TypeSystemDefinition};
+
+    use super::{operation_label, OPERATION_LABELS};
+
+    #[test]
+    fn test_operation_labels_are_the_root_fields() {
+        let schema = async_graphql::parser::parse_schema(include_str!(
+            "../../linera-service-graphql-client/gql/service_schema.graphql"
+        ))
+        .unwrap();
+        let mut root_fields = BTreeSet::new();
+        for definition in schema.definitions {
+            let TypeSystemDefinition::Type(definition) = definition else {
+                continue;
+            };
+            let definition = definition.node;
+            let TypeKind::Object(object) = definition.kind else {
+                continue;
+            };
+            if ["QueryRoot", "MutationRoot", "SubscriptionRoot"]
+                .contains(&definition.name.node.as_str())
+            {
+                root_fields.extend(
+                    object
+                        .fields
+                        .into_iter()
+                        .map(|field| field.node.name.node.to_string()),
+                );
+            }
+        }
+        let labels = OPERATION_LABELS
+            .iter()
+            .map(|label| label.to_string())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(labels, root_fields);
+    }
+
+    #[test]
+    fn test_operation_label() {
+        let label = |query: &str| operation_label(&async_graphql::Request::new(query));
+        assert_eq!(label("{ chains { list } }"), "chains");
+        assert_eq!(label("query Chains { version chains { list } }"), "version");
+        assert_eq!(
+            label("mutation { processInbox(chainId: \"\") }"),
+            "processInbox"
+        );
+        // The names chosen by the clients are not used as labels.
+        assert_eq!(label("query { unknownField }"), "other");
+        assert_eq!(label("query { ... on QueryRoot { version } }"), "other");
+        assert_eq!(label("not a query"), "other");
+
+        let query = "query A { version } query B { chains { list } }";
+        assert_eq!(label(query), "other");
+        let request = async_graphql::Request::new(query).operation_name("B");
+        assert_eq!(operation_label(&request), "chains");
     }
 }
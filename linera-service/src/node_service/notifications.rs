@@ -0,0 +1,538 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The notifications of the chains that the node service's clients subscribe to.
+//!
+//! Every notification of a chain is given a _cursor_: the epoch of the log of that chain,
+//! and the position of the notification in that log. The most recent notifications of each
+//! chain are retained, so that a client that reconnects can resume from the cursor of the
+//! last notification it has seen, without missing any in between.
+//!
+//! The log of a chain is created with a random epoch when the chain is first subscribed to,
+//! and evicted some time after its last subscriber left. The cursors of another epoch, e.g.
+//! from before the node service was restarted, are rejected.
+//!
+//! Each subscriber has a buffer of bounded size. If a subscriber is too slow to keep up, the
+//! oldest notifications of its buffer are dropped, and it is sent a `LAGGED` error with the
+//! number of notifications it missed. It then keeps receiving the newer notifications.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_graphql::{Error, ErrorExtensions as _};
+use futures::{
+    future::{AbortHandle, Abortable},
+    stream, Stream, StreamExt as _,
+};
+use linera_base::identifiers::ChainId;
+use linera_core::{node::NotificationStream, worker::Notification};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// How long the log of a chain is kept after its last subscriber left, so that a client can
+/// still resume after reconnecting.
+const EVICTION_DELAY: Duration = Duration::from_secs(60);
+
+/// The position of a notification in the log of its chain.
+///
+/// It is written as the epoch of the log and the index of the notification, separated by a
+/// `-`, e.g. `12345-7`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NotificationCursor {
+    /// The epoch of the log of the chain.
+    pub epoch: u64,
+    /// The index of the notification in the log.
+    pub index: u64,
+}
+
+impl fmt::Display for NotificationCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.epoch, self.index)
+    }
+}
+
+impl FromStr for NotificationCursor {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cursor {input:?}, expected epoch-index");
+        let (epoch, index) = input.split_once('-').ok_or_else(invalid)?;
+        Ok(NotificationCursor {
+            epoch: epoch.parse().map_err(|_| invalid())?,
+            index: index.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl Serialize for NotificationCursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NotificationCursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+async_graphql::scalar!(
+    NotificationCursor,
+    "NotificationCursor",
+    "The position of a notification in the log of its chain"
+);
+
+/// A notification about a chain, together with its cursor.
+///
+/// It is serialized as the [`Notification`] itself, with an additional `cursor` field.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChainNotification {
+    #[serde(flatten)]
+    pub notification: Notification,
+    /// The position of the notification in the log of its chain.
+    pub cursor: NotificationCursor,
+}
+
+async_graphql::scalar!(
+    ChainNotification,
+    "Notification",
+    "Notify that a chain has a new certified block or a new message"
+);
+
+/// The logs of the chains, by chain ID.
+type ChainLogs = Arc<Mutex<HashMap<ChainId, Arc<Mutex<ChainLog>>>>>;
+
+/// The recent notifications of all the chains that are subscribed to.
+pub struct NotificationLog {
+    /// The number of notifications buffered for each subscriber.
+    buffer_size: usize,
+    /// The number of past notifications retained for each chain.
+    retention: usize,
+    chains: ChainLogs,
+}
+
+/// The recent notifications of a single chain.
+struct ChainLog {
+    /// The epoch of the log, which is part of the cursors of its notifications.
+    epoch: u64,
+    /// The index of the next notification.
+    next_index: u64,
+    /// The retained notifications, oldest first.
+    recent: VecDeque<ChainNotification>,
+    /// The sender of the new notifications, until the notifications of the chain end.
+    sender: Option<broadcast::Sender<ChainNotification>>,
+    /// The number of subscribers of the chain.
+    subscribers: usize,
+    /// The number of times the last subscriber left, so that the log is only evicted after
+    /// the latest time.
+    times_unused: u64,
+    /// Stops the task recording the notifications of the chain.
+    recorder: AbortHandle,
+}
+
+impl NotificationLog {
+    /// Creates a new log, buffering `buffer_size` notifications for each subscriber and
+    /// retaining the last `retention` notifications of each chain.
+    pub fn new(buffer_size: usize, retention: usize) -> Self {
+        Self {
+            buffer_size: buffer_size.max(1),
+            retention,
+            chains: ChainLogs::default(),
+        }
+    }
+
+    /// Subscribes to the notifications of a chain.
+    ///
+    /// If the chain has no log yet, one is created, recording `notifications`; otherwise
+    /// they are dropped, since the log already records the same notifications. If a
+    /// `cursor` is given, the retained notifications after it are returned first. If some of
+    /// them aren't retained anymore, a `LAGGED` error is returned before them.
+    pub fn subscribe(
+        &self,
+        chain_id: ChainId,
+        cursor: Option<NotificationCursor>,
+        notifications: NotificationStream,
+    ) -> Result<impl Stream<Item = Result<ChainNotification, Error>>, Error> {
+        let subscriber = self.subscriber(chain_id, notifications);
+        let log = subscriber.chain_log.lock().unwrap();
+        let mut past_notifications = Vec::new();
+        if let Some(cursor) = cursor {
+            if cursor.epoch != log.epoch {
+                return Err(Error::new(format!(
+                    "The cursor {cursor} is from another log of the chain, e.g. from before \
+                    the node service was restarted; subscribe without a cursor instead"
+                )));
+            }
+            if cursor.index >= log.next_index {
+                return Err(Error::new(format!(
+                    "Unknown cursor {cursor}: subscribe without a cursor instead"
+                )));
+            }
+            let first_retained = log.next_index - log.recent.len() as u64;
+            if cursor.index + 1 < first_retained {
+                past_notifications.push(Err(lagged_error(first_retained - cursor.index - 1)));
+            }
+            past_notifications.extend(
+                log.recent
+                    .iter()
+                    .filter(|notification| notification.cursor.index > cursor.index)
+                    .cloned()
+                    .map(Ok),
+            );
+        }
+        // Subscribing while holding the lock makes sure that the new notifications follow the
+        // retained ones exactly.
+        let receiver = log.sender.as_ref().map(broadcast::Sender::subscribe);
+        drop(log);
+        let new_notifications = stream::unfold(
+            (receiver, subscriber),
+            |(mut receiver, subscriber)| async move {
+                let item = match receiver.as_mut()?.recv().await {
+                    Ok(notification) => Ok(notification),
+                    Err(RecvError::Lagged(missed)) => Err(lagged_error(missed)),
+                    Err(RecvError::Closed) => return None,
+                };
+                Some((item, (receiver, subscriber)))
+            },
+        );
+        Ok(stream::iter(past_notifications).chain(new_notifications))
+    }
+
+    /// Registers a new subscriber of the log of the given chain, creating the log and
+    /// recording its `notifications` if it doesn't exist.
+    fn subscriber(&self, chain_id: ChainId, notifications: NotificationStream) -> Subscriber {
+        let mut chains = self.chains.lock().unwrap();
+        let chain_log = match chains.get(&chain_id) {
+            Some(chain_log) => chain_log.clone(),
+            None => {
+                let chain_log = self.record(chain_id, notifications);
+                chains.insert(chain_id, chain_log.clone());
+                chain_log
+            }
+        };
+        // The subscriber is counted before the lock on the chains is released, so that the
+        // log isn't evicted in the meantime.
+        chain_log.lock().unwrap().subscribers += 1;
+        Subscriber {
+            chains: self.chains.clone(),
+            chain_id,
+            chain_log,
+        }
+    }
+
+    /// Creates the log of a chain, and starts recording its `notifications`.
+    fn record(
+        &self,
+        chain_id: ChainId,
+        mut notifications: NotificationStream,
+    ) -> Arc<Mutex<ChainLog>> {
+        let (sender, _) = broadcast::channel(self.buffer_size);
+        let (recorder, registration) = AbortHandle::new_pair();
+        let chain_log = Arc::new(Mutex::new(ChainLog {
+            epoch: rand::random(),
+            next_index: 0,
+            recent: VecDeque::new(),
+            sender: Some(sender),
+            subscribers: 0,
+            times_unused: 0,
+            recorder,
+        }));
+        let retention = self.retention;
+        let chains = self.chains.clone();
+        let recorded_log = chain_log.clone();
+        let recording = async move {
+            while let Some(notification) = notifications.next().await {
+                recorded_log.lock().unwrap().push(notification, retention);
+            }
+            // The next subscription creates a new log.
+            evict(&chains, chain_id, &recorded_log, None);
+        };
+        tokio::spawn(Abortable::new(recording, registration));
+        chain_log
+    }
+}
+
+impl ChainLog {
+    /// Records a new notification and sends it to the subscribers.
+    fn push(&mut self, notification: Notification, retention: usize) {
+        let notification = ChainNotification {
+            notification,
+            cursor: NotificationCursor {
+                epoch: self.epoch,
+                index: self.next_index,
+            },
+        };
+        self.next_index += 1;
+        if retention > 0 {
+            if self.recent.len() == retention {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(notification.clone());
+        }
+        if let Some(sender) = &self.sender {
+            // Sending only fails if there are no subscribers at the moment.
+            let _ = sender.send(notification);
+        }
+    }
+}
+
+/// Removes the log of a chain if it is still `chain_log` and, if `times_unused` is given, it
+/// had no subscribers since its last subscriber left for that number of times. Its
+/// notifications stop being recorded, and its remaining subscribers are told that there are
+/// no more notifications.
+fn evict(
+    chains: &Mutex<HashMap<ChainId, Arc<Mutex<ChainLog>>>>,
+    chain_id: ChainId,
+    chain_log: &Arc<Mutex<ChainLog>>,
+    times_unused: Option<u64>,
+) {
+    let mut chains = chains.lock().unwrap();
+    if !chains
+        .get(&chain_id)
+        .is_some_and(|current| Arc::ptr_eq(current, chain_log))
+    {
+        return;
+    }
+    let mut log = chain_log.lock().unwrap();
+    if times_unused.is_some_and(|times| log.subscribers > 0 || log.times_unused != times) {
+        return;
+    }
+    log.recorder.abort();
+    log.sender = None;
+    drop(log);
+    chains.remove(&chain_id);
+}
+
+/// A subscriber of the log of a chain, which is evicted some time after its last subscriber
+/// is dropped.
+struct Subscriber {
+    chains: ChainLogs,
+    chain_id: ChainId,
+    chain_log: Arc<Mutex<ChainLog>>,
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        let mut log = self.chain_log.lock().unwrap();
+        log.subscribers -= 1;
+        if log.subscribers > 0 {
+            return;
+        }
+        log.times_unused += 1;
+        let times_unused = Some(log.times_unused);
+        drop(log);
+        let chains = self.chains.clone();
+        let chain_id = self.chain_id;
+        let chain_log = self.chain_log.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move {
+                    tokio::time::sleep(EVICTION_DELAY).await;
+                    evict(&chains, chain_id, &chain_log, times_unused);
+                });
+            }
+            Err(_) => evict(&chains, chain_id, &chain_log, times_unused),
+        }
+    }
+}
+
+/// Returns the error sent to a subscriber that missed `missed` notifications.
+fn lagged_error(missed: u64) -> Error {
+    Error::new(format!(
+        "The subscriber fell behind and missed {missed} notifications"
+    ))
+    .extend_with(|_, extensions| {
+        extensions.set("code", "LAGGED");
+        extensions.set("missed", missed);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt as _;
+    use linera_base::{crypto::CryptoHash, data_types::BlockHeight, identifiers::ChainId};
+    use linera_core::worker::{Notification, Reason};
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    use super::{NotificationCursor, NotificationLog, EVICTION_DELAY};
+
+    fn new_block(chain_id: ChainId, height: u64) -> Notification {
+        Notification {
+            chain_id,
+            reason: Reason::NewBlock {
+                height: BlockHeight(height),
+                hash: CryptoHash::test_hash(format!("block {height}")),
+            },
+        }
+    }
+
+    /// Subscribes to the chain with a stream of notifications that never yields.
+    fn resume(
+        log: &NotificationLog,
+        chain_id: ChainId,
+        cursor: NotificationCursor,
+    ) -> Result<
+        impl futures::Stream<Item = Result<super::ChainNotification, async_graphql::Error>>,
+        async_graphql::Error,
+    > {
+        let (_, unused) = mpsc::unbounded_channel();
+        log.subscribe(
+            chain_id,
+            Some(cursor),
+            Box::pin(UnboundedReceiverStream::new(unused)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_cursor() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let log = NotificationLog::new(10, 3);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut first = Box::pin(log.subscribe(
+            chain_id,
+            None,
+            Box::pin(UnboundedReceiverStream::new(receiver)),
+        )?);
+        for height in 0..5 {
+            sender.send(new_block(chain_id, height))?;
+        }
+        let mut epoch = None;
+        for index in 0..5 {
+            let notification = first.next().await.unwrap()?;
+            assert_eq!(notification.cursor.index, index);
+            assert_eq!(
+                *epoch.get_or_insert(notification.cursor.epoch),
+                notification.cursor.epoch
+            );
+            assert_eq!(notification.notification, new_block(chain_id, index));
+        }
+        let cursor = |index| NotificationCursor {
+            epoch: epoch.unwrap(),
+            index,
+        };
+
+        // The notifications after cursor 2 are still retained.
+        let mut resumed = Box::pin(resume(&log, chain_id, cursor(2))?);
+        assert_eq!(resumed.next().await.unwrap()?.cursor, cursor(3));
+        assert_eq!(resumed.next().await.unwrap()?.cursor, cursor(4));
+
+        // The notification after cursor 0 isn't retained anymore.
+        let mut lagged = Box::pin(resume(&log, chain_id, cursor(0))?);
+        let error = lagged.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            error.message,
+            "The subscriber fell behind and missed 1 notifications"
+        );
+        assert_eq!(lagged.next().await.unwrap()?.cursor, cursor(2));
+
+        assert!(resume(&log, chain_id, cursor(5)).is_err());
+        // The cursors of another epoch, e.g. from before a restart, are rejected.
+        let other_epoch = NotificationCursor {
+            epoch: epoch.unwrap().wrapping_add(1),
+            index: 0,
+        };
+        assert!(resume(&log, chain_id, other_epoch).is_err());
+        assert_eq!(
+            cursor(7).to_string().parse::<NotificationCursor>(),
+            Ok(cursor(7))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_lags() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let log = NotificationLog::new(2, 0);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut subscriber = Box::pin(log.subscribe(
+            chain_id,
+            None,
+            Box::pin(UnboundedReceiverStream::new(receiver)),
+        )?);
+        for height in 0..5 {
+            sender.send(new_block(chain_id, height))?;
+        }
+        // Wait until all the notifications are recorded.
+        while log.chains.lock().unwrap()[&chain_id]
+            .lock()
+            .unwrap()
+            .next_index
+            < 5
+        {
+            tokio::task::yield_now().await;
+        }
+
+        // The oldest notifications were dropped from the subscriber's buffer.
+        let error = subscriber.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            error.message,
+            "The subscriber fell behind and missed 3 notifications"
+        );
+        assert_eq!(subscriber.next().await.unwrap()?.cursor.index, 3);
+        assert_eq!(subscriber.next().await.unwrap()?.cursor.index, 4);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_logs_without_subscribers_are_evicted() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let log = NotificationLog::new(10, 10);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut subscriber = Box::pin(log.subscribe(
+            chain_id,
+            None,
+            Box::pin(UnboundedReceiverStream::new(receiver)),
+        )?);
+        sender.send(new_block(chain_id, 0))?;
+        let cursor = subscriber.next().await.unwrap()?.cursor;
+        drop(subscriber);
+
+        // A client that reconnects soon enough can resume.
+        tokio::time::sleep(EVICTION_DELAY / 2).await;
+        sender.send(new_block(chain_id, 1))?;
+        let mut resumed = Box::pin(resume(&log, chain_id, cursor)?);
+        assert_eq!(resumed.next().await.unwrap()?.cursor.index, 1);
+        drop(resumed);
+
+        tokio::time::sleep(EVICTION_DELAY * 2).await;
+        assert!(log.chains.lock().unwrap().is_empty());
+        // The recording of the notifications stopped.
+        assert!(sender.is_closed());
+        assert!(resume(&log, chain_id, cursor).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_end_with_the_notifications() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let log = NotificationLog::new(10, 10);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut subscriber = Box::pin(log.subscribe(
+            chain_id,
+            None,
+            Box::pin(UnboundedReceiverStream::new(receiver)),
+        )?);
+        drop(sender);
+        assert!(subscriber.next().await.is_none());
+        assert!(log.chains.lock().unwrap().is_empty());
+
+        // The next subscription records the new notifications.
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut subscriber = Box::pin(log.subscribe(
+            chain_id,
+            None,
+            Box::pin(UnboundedReceiverStream::new(receiver)),
+        )?);
+        sender.send(new_block(chain_id, 0))?;
+        assert_eq!(subscriber.next().await.unwrap()?.cursor.index, 0);
+        Ok(())
+    }
+}
@@ -67,6 +67,9 @@ impl Project {
         debug!("Writing single_chain.rs");
         Self::create_test_file(&test_directory, name)?;
 
+        debug!("Writing schema.rs");
+        Self::create_schema_test_file(&test_directory, name)?;
+
         Ok(Self { root })
     }
 
@@ -225,6 +228,17 @@ impl Project {
         Self::write_string_to_file(&test_path, &test_contents)
     }
 
+    fn create_schema_test_file(test_directory: &Path, name: &str) -> Result<()> {
+        let project_name = name.to_case(Case::Pascal);
+        let test_path = test_directory.join("schema.rs");
+        let test_contents = format!(
+            include_str!("../template/tests/schema.rs.template"),
+            project_name = name.replace('-', "_"),
+            project_abi = project_name,
+        );
+        Self::write_string_to_file(&test_path, &test_contents)
+    }
+
     fn write_string_to_file(path: &Path, content: &str) -> Result<()> {
         let mut file = File::create(path)?;
         file.write_all(content.as_bytes())?;
@@ -249,7 +263,7 @@ impl Project {
             linera_sdk_path.display()
         );
         let linera_sdk_dev_dep = format!(
-            "linera-sdk = {{ path = \"{}\", features = [\"test\", \"wasmer\"] }}",
+            "linera-sdk = {{ path = \"{}\", features = [\"schema\", \"test\", \"wasmer\"] }}",
             linera_sdk_path.display()
         );
         (linera_sdk_dep, linera_sdk_dev_dep)
@@ -260,7 +274,7 @@ impl Project {
         let version = env!("CARGO_PKG_VERSION");
         let linera_sdk_dep = format!("linera-sdk = \"{}\"", version);
         let linera_sdk_dev_dep = format!(
-            "linera-sdk = {{ version = \"{}\", features = [\"test\", \"wasmer\"] }}",
+            "linera-sdk = {{ version = \"{}\", features = [\"schema\", \"test\", \"wasmer\"] }}",
             version
         );
         (linera_sdk_dep, linera_sdk_dev_dep)
@@ -62,6 +62,7 @@ use {
 
 #[cfg(with_metrics)]
 use crate::prometheus_server;
+use crate::read_with_fallback;
 
 #[cfg(with_metrics)]
 static PROXY_REQUEST_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
@@ -153,6 +154,8 @@ struct GrpcProxyInner<S> {
     notifier: ChannelNotifier<Result<Notification, Status>>,
     tls: TlsConfig,
     storage: S,
+    /// The storage for the downloads, which may be served by stale read replicas.
+    download_storage: S,
 }
 
 impl<S> GrpcProxy<S>
@@ -175,6 +178,7 @@ where
                 .with_timeout(timeout),
             notifier: ChannelNotifier::default(),
             tls,
+            download_storage: storage.with_bounded_staleness(true),
             storage,
         }))
     }
@@ -334,9 +338,10 @@ where
     /// Returns the appropriate gRPC status for the given [`ViewError`].
     fn error_to_status(err: ViewError) -> Status {
         let mut status = match &err {
-            ViewError::TooLargeValue | ViewError::BcsError(_) => {
-                Status::invalid_argument(err.to_string())
-            }
+            ViewError::TooLargeValue
+            | ViewError::BcsError(_)
+            | ViewError::DuplicateCollectionEntry(_)
+            | ViewError::OverlappingBaseKeys(..) => Status::invalid_argument(err.to_string()),
             ViewError::StoreError { .. }
             | ViewError::TokioJoinError(_)
             | ViewError::TryLockError(_)
@@ -501,12 +506,12 @@ where
         request: Request<BlobId>,
     ) -> Result<Response<BlobContent>, Status> {
         let blob_id = request.into_inner().try_into()?;
-        let blob = self
-            .0
-            .storage
-            .read_blob(blob_id)
-            .await
-            .map_err(Self::error_to_status)?;
+        let inner = &self.0;
+        let blob = read_with_fallback(&inner.download_storage, &inner.storage, |storage| {
+            storage.read_blob(blob_id)
+        })
+        .await
+        .map_err(Self::error_to_status)?;
         Ok(Response::new(blob.into_content().try_into()?))
     }
 
@@ -566,10 +571,11 @@ where
         request: Request<CryptoHash>,
     ) -> Result<Response<Certificate>, Status> {
         let hash = request.into_inner().try_into()?;
-        let certificate: linera_chain::types::Certificate = self
-            .0
-            .storage
-            .read_certificate(hash)
+        let inner = &self.0;
+        let certificate: linera_chain::types::Certificate =
+            read_with_fallback(&inner.download_storage, &inner.storage, |storage| {
+                storage.read_certificate(hash)
+            })
             .await
             .map_err(Self::error_to_status)?
             .into();
@@ -595,11 +601,12 @@ where
 
         let mut certificates = vec![];
 
+        let inner = &self.0;
         'outer: for batch in hashes.chunks(100) {
-            for certificate in self
-                .0
-                .storage
-                .read_certificates(batch.to_vec())
+            for certificate in
+                read_with_fallback(&inner.download_storage, &inner.storage, |storage| {
+                    storage.read_certificates(batch.to_vec())
+                })
                 .await
                 .map_err(Self::error_to_status)?
             {
@@ -622,12 +629,12 @@ where
         request: Request<BlobId>,
     ) -> Result<Response<CryptoHash>, Status> {
         let blob_id = request.into_inner().try_into()?;
-        let blob_state = self
-            .0
-            .storage
-            .read_blob_state(blob_id)
-            .await
-            .map_err(Self::error_to_status)?;
+        let inner = &self.0;
+        let blob_state = read_with_fallback(&inner.download_storage, &inner.storage, |storage| {
+            storage.read_blob_state(blob_id)
+        })
+        .await
+        .map_err(Self::error_to_status)?;
         Ok(Response::new(blob_state.last_used_by.into()))
     }
 
@@ -3,7 +3,7 @@
 
 #![deny(clippy::large_futures)]
 
-use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use std::{future::Future, net::SocketAddr, path::PathBuf, time::Duration};
 
 use anyhow::{anyhow, bail, ensure, Result};
 use async_trait::async_trait;
@@ -19,7 +19,7 @@ use linera_rpc::{
     simple::{MessageHandler, TransportProtocol},
     RpcMessage,
 };
-use linera_sdk::linera_base_types::Blob;
+use linera_sdk::{linera_base_types::Blob, views::ViewError};
 #[cfg(with_metrics)]
 use linera_service::prometheus_server;
 use linera_service::{
@@ -27,7 +27,10 @@ use linera_service::{
     util,
 };
 use linera_storage::Storage;
-use linera_views::{lru_caching::StorageCacheConfig, store::CommonStoreConfig};
+use linera_views::{
+    lru_caching::{CachePolicyKind, StorageCacheConfig},
+    store::CommonStoreConfig,
+};
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, instrument};
@@ -92,6 +95,15 @@ pub struct ProxyOptions {
     #[arg(long, default_value = "1000")]
     pub max_cache_entries: usize,
 
+    /// The eviction policy of the storage cache: lru, lfu or segmented.
+    #[arg(long, default_value = "lru")]
+    pub cache_policy: CachePolicyKind,
+
+    /// The buckets of the storage latency histograms, in milliseconds, separated by commas.
+    #[cfg(with_metrics)]
+    #[arg(long, value_delimiter = ',')]
+    storage_latency_buckets: Vec<f64>,
+
     /// Path to the file describing the initial user chains (aka genesis state)
     #[arg(long = "genesis")]
     genesis_config_path: PathBuf,
@@ -177,6 +189,7 @@ where
                     .clone_with_protocol(public_transport),
                 send_timeout: context.send_timeout,
                 recv_timeout: context.recv_timeout,
+                download_storage: storage.with_bounded_staleness(true),
                 storage,
             })),
             _ => {
@@ -192,6 +205,22 @@ where
     }
 }
 
+/// Reads from the `download_storage`, and retries from the `storage` if the value was not
+/// found: the read replicas of the `download_storage` may not have received it yet.
+async fn read_with_fallback<'a, S, T, F>(
+    download_storage: &'a S,
+    storage: &'a S,
+    read: impl Fn(&'a S) -> F,
+) -> Result<T, ViewError>
+where
+    F: Future<Output = Result<T, ViewError>>,
+{
+    match read(download_storage).await {
+        Err(ViewError::NotFound(_) | ViewError::BlobsNotFound(_)) => read(storage).await,
+        result => result,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SimpleProxy<S>
 where
@@ -202,6 +231,8 @@ where
     send_timeout: Duration,
     recv_timeout: Duration,
     storage: S,
+    /// The storage for the downloads, which may be served by stale read replicas.
+    download_storage: S,
 }
 
 #[async_trait]
@@ -330,18 +361,36 @@ where
                 Ok(Some(RpcMessage::UploadBlobResponse(Box::new(id))))
             }
             DownloadBlob(blob_id) => {
-                let content = self.storage.read_blob(*blob_id).await?.into_content();
+                let content =
+                    read_with_fallback(&self.download_storage, &self.storage, |storage| {
+                        storage.read_blob(*blob_id)
+                    })
+                    .await?
+                    .into_content();
                 Ok(Some(RpcMessage::DownloadBlobResponse(Box::new(content))))
             }
-            DownloadConfirmedBlock(hash) => Ok(Some(RpcMessage::DownloadConfirmedBlockResponse(
-                Box::new(self.storage.read_confirmed_block(*hash).await?),
-            ))),
+            DownloadConfirmedBlock(hash) => {
+                Ok(Some(RpcMessage::DownloadConfirmedBlockResponse(Box::new(
+                    read_with_fallback(&self.download_storage, &self.storage, |storage| {
+                        storage.read_confirmed_block(*hash)
+                    })
+                    .await?,
+                ))))
+            }
             DownloadCertificates(hashes) => {
-                let certificates = self.storage.read_certificates(hashes).await?;
+                let certificates =
+                    read_with_fallback(&self.download_storage, &self.storage, |storage| {
+                        storage.read_certificates(hashes.clone())
+                    })
+                    .await?;
                 Ok(Some(RpcMessage::DownloadCertificatesResponse(certificates)))
             }
             BlobLastUsedBy(blob_id) => Ok(Some(RpcMessage::BlobLastUsedByResponse(Box::new(
-                self.storage.read_blob_state(*blob_id).await?.last_used_by,
+                read_with_fallback(&self.download_storage, &self.storage, |storage| {
+                    storage.read_blob_state(*blob_id)
+                })
+                .await?
+                .last_used_by,
             )))),
             MissingBlobIds(blob_ids) => Ok(Some(RpcMessage::MissingBlobIdsResponse(
                 self.storage.missing_blobs(&blob_ids).await?,
@@ -400,10 +449,16 @@ fn main() -> Result<()> {
 
 impl ProxyOptions {
     async fn run(&self) -> Result<()> {
+        #[cfg(with_metrics)]
+        if !self.storage_latency_buckets.is_empty() {
+            linera_views::metering::set_latency_buckets(self.storage_latency_buckets.clone())
+                .map_err(|_| anyhow!("The storage latency buckets are already set"))?;
+        }
         let storage_cache_config = StorageCacheConfig {
             max_cache_size: self.max_cache_size,
             max_entry_size: self.max_entry_size,
             max_cache_entries: self.max_cache_entries,
+            cache_policy: self.cache_policy,
         };
         let common_config = CommonStoreConfig {
             max_concurrent_queries: self.max_concurrent_queries,
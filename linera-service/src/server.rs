@@ -39,7 +39,10 @@ use linera_service::{
     util,
 };
 use linera_storage::Storage;
-use linera_views::{lru_caching::StorageCacheConfig, store::CommonStoreConfig};
+use linera_views::{
+    lru_caching::{CachePolicyKind, StorageCacheConfig},
+    store::CommonStoreConfig,
+};
 use serde::Deserialize;
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
@@ -387,6 +390,15 @@ enum ServerCommand {
         /// The maximal number of entries in the storage cache.
         #[arg(long, default_value = "1000")]
         max_cache_entries: usize,
+
+        /// The eviction policy of the storage cache: lru, lfu or segmented.
+        #[arg(long, default_value = "lru")]
+        cache_policy: CachePolicyKind,
+
+        /// The buckets of the storage latency histograms, in milliseconds, separated by commas.
+        #[cfg(with_metrics)]
+        #[arg(long, value_delimiter = ',')]
+        storage_latency_buckets: Vec<f64>,
     },
 
     /// Act as a trusted third-party and generate all server configurations
@@ -436,6 +448,10 @@ enum ServerCommand {
         /// The maximal number of entries in the storage cache.
         #[arg(long, default_value = "1000")]
         max_cache_entries: usize,
+
+        /// The eviction policy of the storage cache: lru, lfu or segmented.
+        #[arg(long, default_value = "lru")]
+        cache_policy: CachePolicyKind,
     },
 
     /// Replaces the configurations of the shards by following the given template.
@@ -538,9 +554,18 @@ async fn run(options: ServerOptions) {
             max_cache_size,
             max_entry_size,
             max_cache_entries,
+            cache_policy,
+            #[cfg(with_metrics)]
+            storage_latency_buckets,
         } => {
             linera_version::VERSION_INFO.log();
 
+            #[cfg(with_metrics)]
+            if !storage_latency_buckets.is_empty() {
+                linera_views::metering::set_latency_buckets(storage_latency_buckets)
+                    .expect("The storage latency buckets should only be set once");
+            }
+
             let genesis_config: GenesisConfig =
                 util::read_json(&genesis_config_path).expect("Failed to read initial chain config");
             let server_config: ValidatorServerConfig =
@@ -559,6 +584,7 @@ async fn run(options: ServerOptions) {
                 max_cache_size,
                 max_entry_size,
                 max_cache_entries,
+                cache_policy,
             };
             let common_config = CommonStoreConfig {
                 max_concurrent_queries,
@@ -626,6 +652,7 @@ async fn run(options: ServerOptions) {
             max_cache_size,
             max_entry_size,
             max_cache_entries,
+            cache_policy,
         } => {
             let genesis_config: GenesisConfig =
                 util::read_json(&genesis_config_path).expect("Failed to read initial chain config");
@@ -633,6 +660,7 @@ async fn run(options: ServerOptions) {
                 max_cache_size,
                 max_entry_size,
                 max_cache_entries,
+                cache_policy,
             };
             let common_config = CommonStoreConfig {
                 max_concurrent_queries,
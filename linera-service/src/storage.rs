@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt, str::FromStr};
+use std::{fmt, path::PathBuf, str::FromStr};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -13,14 +13,18 @@ use linera_storage_service::{
     client::ServiceStoreClient,
     common::{ServiceStoreConfig, ServiceStoreInternalConfig},
 };
+#[cfg(any(feature = "dynamodb", feature = "scylladb"))]
+use linera_views::compression::{ValueCompression, DEFAULT_COMPRESSION_THRESHOLD};
 #[cfg(feature = "dynamodb")]
 use linera_views::dynamo_db::{DynamoDbStore, DynamoDbStoreConfig};
+#[cfg(any(feature = "rocksdb", feature = "dynamodb", feature = "scylladb"))]
+use linera_views::lru_caching::{CachePolicyKind, StorageCacheConfig};
 use linera_views::{
     memory::{MemoryStore, MemoryStoreConfig},
     store::{CommonStoreConfig, KeyValueStore},
 };
 use serde::{Deserialize, Serialize};
-use tracing::error;
+use tracing::{error, info};
 #[allow(unused_imports)]
 use {anyhow::bail, linera_views::store::AdminKeyValueStore as _};
 #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
@@ -31,12 +35,18 @@ use {
 };
 #[cfg(feature = "rocksdb")]
 use {
-    linera_views::rocks_db::{PathWithGuard, RocksDbSpawnMode, RocksDbStore, RocksDbStoreConfig},
-    std::path::PathBuf,
+    linera_storage::StorageSection,
+    linera_views::rocks_db::{
+        PathWithGuard, RocksDbColumnFamily, RocksDbCompression, RocksDbSpawnMode, RocksDbStore,
+        RocksDbStoreConfig, RocksDbTuning,
+    },
 };
 #[cfg(feature = "scylladb")]
 use {
-    linera_views::scylla_db::{ScyllaDbStore, ScyllaDbStoreConfig},
+    linera_views::{
+        replicated::{ReplicatedStore, ReplicatedStoreConfig},
+        scylla_db::{ScyllaDbStore, ScyllaDbStoreConfig},
+    },
     std::num::NonZeroU16,
     tracing::debug,
 };
@@ -54,6 +64,9 @@ pub enum StoreConfig {
     Memory {
         config: MemoryStoreConfig,
         namespace: String,
+        /// The snapshot file loaded at startup and written on shutdown, if any.
+        #[serde(default)]
+        persist: Option<PathBuf>,
     },
     /// The RocksDB key value store
     #[cfg(feature = "rocksdb")]
@@ -73,6 +86,12 @@ pub enum StoreConfig {
         config: ScyllaDbStoreConfig,
         namespace: String,
     },
+    /// The ScyllaDB key value store with read replicas
+    #[cfg(feature = "scylladb")]
+    ReplicatedScyllaDb {
+        config: ReplicatedStoreConfig<ScyllaDbStoreConfig>,
+        namespace: String,
+    },
     #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
     DualRocksDbScyllaDb {
         config: DualStoreConfig<RocksDbStoreConfig, ScyllaDbStoreConfig>,
@@ -91,7 +110,10 @@ pub enum StorageConfig {
         endpoint: String,
     },
     /// The memory description.
-    Memory,
+    Memory {
+        /// The snapshot file loaded at startup and written on shutdown, if any.
+        persist: Option<PathBuf>,
+    },
     /// The RocksDB description.
     #[cfg(feature = "rocksdb")]
     RocksDb {
@@ -99,18 +121,32 @@ pub enum StorageConfig {
         path: PathBuf,
         /// Whether to use `block_in_place` or `spawn_blocking`.
         spawn_mode: RocksDbSpawnMode,
+        /// The tuning of the database.
+        tuning: RocksDbTuning,
+        /// The options of the storage cache.
+        cache: StorageCacheOptions,
     },
     /// The DynamoDB description.
     #[cfg(feature = "dynamodb")]
     DynamoDb {
         /// Whether to use the DynamoDB Local system
         use_dynamodb_local: bool,
+        /// The compression of the values, if any.
+        compression: Option<ValueCompression>,
+        /// The options of the storage cache.
+        cache: StorageCacheOptions,
     },
     /// The ScyllaDB description.
     #[cfg(feature = "scylladb")]
     ScyllaDb {
         /// The URI for accessing the database.
         uri: String,
+        /// The compression of the values, if any.
+        compression: Option<ValueCompression>,
+        /// The options of the storage cache.
+        cache: StorageCacheOptions,
+        /// The URIs of the read replicas of the database, if any.
+        replicas: Vec<String>,
     },
     #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
     DualRocksDbScyllaDb {
@@ -123,6 +159,31 @@ pub enum StorageConfig {
     },
 }
 
+/// The options of the storage cache given in the storage configuration. They replace the
+/// ones given on the command line.
+#[cfg(any(feature = "rocksdb", feature = "dynamodb", feature = "scylladb"))]
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(any(test), derive(Eq, PartialEq))]
+pub struct StorageCacheOptions {
+    /// The maximum size of the cache of the namespace, in bytes.
+    pub size: Option<usize>,
+    /// The eviction policy of the cache.
+    pub policy: Option<CachePolicyKind>,
+}
+
+#[cfg(any(feature = "rocksdb", feature = "dynamodb", feature = "scylladb"))]
+impl StorageCacheOptions {
+    /// Applies the options to the configuration of the cache.
+    fn apply(&self, storage_cache_config: &mut StorageCacheConfig) {
+        if let Some(size) = self.size {
+            storage_cache_config.max_cache_size = size;
+        }
+        if let Some(policy) = self.policy {
+            storage_cache_config.cache_policy = policy;
+        }
+    }
+}
+
 impl StorageConfig {
     pub fn maybe_append_shard_path(&mut self, _shard: usize) -> std::io::Result<()> {
         match self {
@@ -168,17 +229,22 @@ impl FromStr for StorageConfigNamespace {
     type Err = anyhow::Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        if input == MEMORY {
-            let namespace = DEFAULT_NAMESPACE.to_string();
-            let storage_config = StorageConfig::Memory;
-            return Ok(StorageConfigNamespace {
-                storage_config,
-                namespace,
-            });
-        }
-        if let Some(s) = input.strip_prefix(MEMORY_EXT) {
-            let namespace = s.to_string();
-            let storage_config = StorageConfig::Memory;
+        let (memory_input, memory_options) = match input.split_once('?') {
+            Some((input, options)) => (input, Some(options)),
+            None => (input, None),
+        };
+        let memory_namespace = if memory_input == MEMORY {
+            Some(DEFAULT_NAMESPACE)
+        } else {
+            memory_input.strip_prefix(MEMORY_EXT)
+        };
+        if let Some(namespace) = memory_namespace {
+            let namespace = namespace.to_string();
+            let persist = match memory_options {
+                Some(options) => parse_memory_options(options)?,
+                None => None,
+            };
+            let storage_config = StorageConfig::Memory { persist };
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
@@ -217,14 +283,24 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         if let Some(s) = input.strip_prefix(ROCKS_DB) {
             if s.is_empty() {
                 bail!(
-                    "For RocksDB, the formatting has to be rocksdb:directory or rocksdb:directory:spawn_mode:namespace");
+                    "For RocksDB, the formatting has to be rocksdb:directory or rocksdb:directory:spawn_mode:namespace, \
+optionally followed by tuning options, e.g. rocksdb:directory?profile=write-heavy&cache=2GiB");
             }
+            let (s, (tuning, cache)) = match s.split_once('?') {
+                Some((s, options)) => (s, parse_rocks_db_options(options)?),
+                None => (s, Default::default()),
+            };
             let parts = s.split(':').collect::<Vec<_>>();
             if parts.len() == 1 {
                 let path = parts[0].to_string().into();
                 let namespace = DEFAULT_NAMESPACE.to_string();
                 let spawn_mode = RocksDbSpawnMode::SpawnBlocking;
-                let storage_config = StorageConfig::RocksDb { path, spawn_mode };
+                let storage_config = StorageConfig::RocksDb {
+                    path,
+                    spawn_mode,
+                    tuning,
+                    cache,
+                };
                 return Ok(StorageConfigNamespace {
                     storage_config,
                     namespace,
@@ -243,7 +319,12 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
                 } else {
                     parts[2].to_string()
                 };
-                let storage_config = StorageConfig::RocksDb { path, spawn_mode };
+                let storage_config = StorageConfig::RocksDb {
+                    path,
+                    spawn_mode,
+                    tuning,
+                    cache,
+                };
                 return Ok(StorageConfigNamespace {
                     storage_config,
                     namespace,
@@ -253,6 +334,15 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         }
         #[cfg(feature = "dynamodb")]
         if let Some(s) = input.strip_prefix(DYNAMO_DB) {
+            let (s, (compression, cache, replicas)) = match s.split_once('?') {
+                Some((s, options)) => (s, parse_database_options(options)?),
+                None => (s, Default::default()),
+            };
+            if !replicas.is_empty() {
+                // The replicas of DynamoDB global tables are in other regions, which the
+                // configuration cannot select yet.
+                bail!("Read replicas are only supported by ScyllaDB");
+            }
             let mut parts = s.splitn(2, ':');
             let namespace = parts
                 .next()
@@ -264,11 +354,16 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
                 Some(unknown) => {
                     bail!(
                         "Invalid DynamoDB endpoint {unknown:?}. \
-                        Expected {DYNAMO_DB}TABLE:[env|dynamodb_local]"
+                        Expected {DYNAMO_DB}TABLE:[env|dynamodb_local], optionally followed by \
+                        ?compression=zstd:LEVEL&cache=SIZE&cache-policy=POLICY"
                     );
                 }
             };
-            let storage_config = StorageConfig::DynamoDb { use_dynamodb_local };
+            let storage_config = StorageConfig::DynamoDb {
+                use_dynamodb_local,
+                compression,
+                cache,
+            };
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
@@ -276,6 +371,10 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         }
         #[cfg(feature = "scylladb")]
         if let Some(s) = input.strip_prefix(SCYLLA_DB) {
+            let (s, (compression, cache, replicas)) = match s.split_once('?') {
+                Some((s, options)) => (s, parse_database_options(options)?),
+                None => (s, Default::default()),
+            };
             let mut uri: Option<String> = None;
             let mut namespace: Option<String> = None;
             let parse_error: &'static str = "Correct format is tcp:db_hostname:port.";
@@ -314,7 +413,12 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
             }
             let uri = uri.unwrap_or("localhost:9042".to_string());
             let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE.to_string());
-            let storage_config = StorageConfig::ScyllaDb { uri };
+            let storage_config = StorageConfig::ScyllaDb {
+                uri,
+                compression,
+                cache,
+                replicas,
+            };
             debug!("ScyllaDB connection info: {:?}", storage_config);
             return Ok(StorageConfigNamespace {
                 storage_config,
@@ -398,28 +502,73 @@ impl StorageConfigNamespace {
                 };
                 Ok(StoreConfig::Service { config, namespace })
             }
-            StorageConfig::Memory => {
+            StorageConfig::Memory { persist } => {
                 let config = MemoryStoreConfig {
                     common_config: common_config.reduced(),
                 };
-                Ok(StoreConfig::Memory { config, namespace })
+                let persist = persist.clone();
+                Ok(StoreConfig::Memory {
+                    config,
+                    namespace,
+                    persist,
+                })
             }
             #[cfg(feature = "rocksdb")]
-            StorageConfig::RocksDb { path, spawn_mode } => {
+            StorageConfig::RocksDb {
+                path,
+                spawn_mode,
+                tuning,
+                cache,
+            } => {
+                let mut common_config = common_config;
+                cache.apply(&mut common_config.storage_cache_config);
                 let path_buf = path.to_path_buf();
                 let path_with_guard = PathWithGuard::new(path_buf);
-                let config = RocksDbStoreConfig::new(*spawn_mode, path_with_guard, common_config);
+                let config = RocksDbStoreConfig::new(*spawn_mode, path_with_guard, common_config)
+                    .with_tuning(tuning.clone());
                 Ok(StoreConfig::RocksDb { config, namespace })
             }
             #[cfg(feature = "dynamodb")]
-            StorageConfig::DynamoDb { use_dynamodb_local } => {
-                let config = DynamoDbStoreConfig::new(*use_dynamodb_local, common_config);
+            StorageConfig::DynamoDb {
+                use_dynamodb_local,
+                compression,
+                cache,
+            } => {
+                let mut common_config = common_config;
+                cache.apply(&mut common_config.storage_cache_config);
+                let config = DynamoDbStoreConfig::new(*use_dynamodb_local, common_config)
+                    .with_compression(*compression);
                 Ok(StoreConfig::DynamoDb { config, namespace })
             }
             #[cfg(feature = "scylladb")]
-            StorageConfig::ScyllaDb { uri } => {
-                let config = ScyllaDbStoreConfig::new(uri.to_string(), common_config);
-                Ok(StoreConfig::ScyllaDb { config, namespace })
+            StorageConfig::ScyllaDb {
+                uri,
+                compression,
+                cache,
+                replicas,
+            } => {
+                let mut common_config = common_config;
+                cache.apply(&mut common_config.storage_cache_config);
+                let config = ScyllaDbStoreConfig::new(uri.to_string(), common_config.clone())
+                    .with_compression(*compression);
+                if replicas.is_empty() {
+                    return Ok(StoreConfig::ScyllaDb { config, namespace });
+                }
+                // The caches of the replicas would not be invalidated by the writes to the
+                // primary database, so the replicas are not cached.
+                common_config.storage_cache_config.max_cache_entries = 0;
+                let replica_configs = replicas
+                    .iter()
+                    .map(|replica| {
+                        ScyllaDbStoreConfig::new(replica.to_string(), common_config.clone())
+                            .with_compression(*compression)
+                    })
+                    .collect();
+                let config = ReplicatedStoreConfig {
+                    primary_config: config,
+                    replica_configs,
+                };
+                Ok(StoreConfig::ReplicatedScyllaDb { config, namespace })
             }
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             StorageConfig::DualRocksDbScyllaDb {
@@ -451,22 +600,45 @@ impl fmt::Display for StorageConfigNamespace {
             StorageConfig::Service { endpoint } => {
                 write!(f, "service:tcp:{}:{}", endpoint, namespace)
             }
-            StorageConfig::Memory => {
-                write!(f, "memory:{}", namespace)
+            StorageConfig::Memory { persist } => {
+                write!(f, "memory:{}", namespace)?;
+                if let Some(path) = persist {
+                    write!(f, "?persist={}", path.display())?;
+                }
+                Ok(())
             }
             #[cfg(feature = "rocksdb")]
-            StorageConfig::RocksDb { path, spawn_mode } => {
+            StorageConfig::RocksDb {
+                path,
+                spawn_mode,
+                tuning,
+                cache,
+            } => {
                 let spawn_mode = spawn_mode.to_string();
-                write!(f, "rocksdb:{}:{}:{}", path.display(), spawn_mode, namespace)
+                write!(f, "rocksdb:{}:{}:{}", path.display(), spawn_mode, namespace)?;
+                write_rocks_db_options(f, tuning, cache)
             }
             #[cfg(feature = "dynamodb")]
-            StorageConfig::DynamoDb { use_dynamodb_local } => match use_dynamodb_local {
-                true => write!(f, "dynamodb:{}:dynamodb_local", namespace),
-                false => write!(f, "dynamodb:{}:env", namespace),
-            },
+            StorageConfig::DynamoDb {
+                use_dynamodb_local,
+                compression,
+                cache,
+            } => {
+                match use_dynamodb_local {
+                    true => write!(f, "dynamodb:{}:dynamodb_local", namespace)?,
+                    false => write!(f, "dynamodb:{}:env", namespace)?,
+                }
+                write_database_options(f, compression, cache, &[])
+            }
             #[cfg(feature = "scylladb")]
-            StorageConfig::ScyllaDb { uri } => {
-                write!(f, "scylladb:tcp:{}:{}", uri, namespace)
+            StorageConfig::ScyllaDb {
+                uri,
+                compression,
+                cache,
+                replicas,
+            } => {
+                write!(f, "scylladb:tcp:{}:{}", uri, namespace)?;
+                write_database_options(f, compression, cache, replicas)
             }
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             StorageConfig::DualRocksDbScyllaDb {
@@ -487,6 +659,208 @@ impl fmt::Display for StorageConfigNamespace {
     }
 }
 
+/// Parses the options of the memory storage, given as `key=value` pairs separated by `&`, and
+/// returns the path of its snapshot file, if any.
+fn parse_memory_options(options: &str) -> Result<Option<PathBuf>, anyhow::Error> {
+    let mut persist = None;
+    for option in options.split('&').filter(|option| !option.is_empty()) {
+        let (key, value) = option
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Memory options have to be key=value, not {option:?}"))?;
+        match key {
+            "persist" if !value.is_empty() => persist = Some(PathBuf::from(value)),
+            "persist" => bail!("The memory option persist needs the path of a snapshot file"),
+            _ => bail!("Unknown memory option {key:?}, expected persist"),
+        }
+    }
+    Ok(persist)
+}
+
+/// Parses the options of the databases, given as `key=value` pairs separated by `&`, e.g.
+/// `compression=zstd:3&compression_threshold=1024&cache=512MiB&cache-policy=segmented`, and
+/// returns the compression, the cache options and the URIs of the read replicas.
+#[cfg(any(feature = "dynamodb", feature = "scylladb"))]
+fn parse_database_options(
+    options: &str,
+) -> Result<(Option<ValueCompression>, StorageCacheOptions, Vec<String>), anyhow::Error> {
+    let mut compression = None;
+    let mut threshold = None;
+    let mut cache = StorageCacheOptions::default();
+    let mut replicas = Vec::new();
+    for option in options.split('&').filter(|option| !option.is_empty()) {
+        let (key, value) = option
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Database options have to be key=value, not {option:?}"))?;
+        match key {
+            "compression" => compression = Some(value.parse::<ValueCompression>()?),
+            "compression_threshold" => threshold = Some(parse_size(value)? as usize),
+            "cache" => cache.size = Some(parse_size(value)?.try_into()?),
+            "cache-policy" => cache.policy = Some(value.parse::<CachePolicyKind>()?),
+            "replicas" => {
+                for replica in value.split(',') {
+                    let (address, port) = replica.rsplit_once(':').ok_or_else(|| {
+                        anyhow!("The read replica {replica:?} has to be hostname:port")
+                    })?;
+                    let port = port.parse::<std::num::NonZeroU16>().map_err(|_| {
+                        anyhow!("Failed to parse the port of the read replica {replica:?}")
+                    })?;
+                    replicas.push(format!("{address}:{port}"));
+                }
+            }
+            _ => bail!(
+                "Unknown database option {key:?}, expected compression, compression_threshold, \
+                cache, cache-policy or replicas"
+            ),
+        }
+    }
+    let compression = match (compression, threshold) {
+        (Some(compression), Some(threshold)) => Some(compression.with_threshold(threshold)),
+        (compression, None) => compression,
+        (None, Some(_)) => bail!("The option compression_threshold needs a compression"),
+    };
+    Ok((compression, cache, replicas))
+}
+
+/// Writes the options of the databases, in the format read by [`parse_database_options`].
+#[cfg(any(feature = "dynamodb", feature = "scylladb"))]
+fn write_database_options(
+    f: &mut fmt::Formatter,
+    compression: &Option<ValueCompression>,
+    cache: &StorageCacheOptions,
+    replicas: &[String],
+) -> fmt::Result {
+    let mut options = Vec::new();
+    if let Some(compression) = compression {
+        options.push(format!("compression={compression}"));
+        if compression.threshold != DEFAULT_COMPRESSION_THRESHOLD {
+            options.push(format!("compression_threshold={}", compression.threshold));
+        }
+    }
+    if let Some(size) = cache.size {
+        options.push(format!("cache={size}"));
+    }
+    if let Some(policy) = cache.policy {
+        options.push(format!("cache-policy={policy}"));
+    }
+    if !replicas.is_empty() {
+        options.push(format!("replicas={}", replicas.join(",")));
+    }
+    if !options.is_empty() {
+        write!(f, "?{}", options.join("&"))?;
+    }
+    Ok(())
+}
+
+/// Parses the options of RocksDB, given as `key=value` pairs separated by `&`, and returns
+/// its tuning and the options of the storage cache. Since `cache` is the size of the block
+/// cache of RocksDB, the size of the storage cache is given by `cache-size`.
+#[cfg(feature = "rocksdb")]
+fn parse_rocks_db_options(
+    options: &str,
+) -> Result<(RocksDbTuning, StorageCacheOptions), anyhow::Error> {
+    let mut tuning = RocksDbTuning::default();
+    let mut cache = StorageCacheOptions::default();
+    for option in options.split('&').filter(|option| !option.is_empty()) {
+        let (key, value) = option
+            .split_once('=')
+            .ok_or_else(|| anyhow!("RocksDB options have to be key=value, not {option:?}"))?;
+        match key {
+            "profile" => tuning.profile = value.parse()?,
+            "cache" => tuning.block_cache_size = Some(parse_size(value)?),
+            "cache-size" => cache.size = Some(parse_size(value)?.try_into()?),
+            "cache-policy" => cache.policy = Some(value.parse::<CachePolicyKind>()?),
+            "bloom" => tuning.bloom_filter_bits_per_key = Some(value.parse()?),
+            "compression" => {
+                let compression_per_level = value
+                    .split(',')
+                    .map(RocksDbCompression::from_str)
+                    .collect::<Result<Vec<_>, _>>()?;
+                tuning.compression_per_level = Some(compression_per_level);
+            }
+            "column_families" => {
+                tuning.column_families = if value.parse()? {
+                    StorageSection::ALL
+                        .into_iter()
+                        .map(|section| {
+                            RocksDbColumnFamily::new(section.name(), section.key_prefix())
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+            }
+            _ => bail!(
+                "Unknown RocksDB option {key:?}, expected profile, cache, cache-size, \
+                cache-policy, bloom, compression or column_families"
+            ),
+        }
+    }
+    Ok((tuning, cache))
+}
+
+/// Parses a number of bytes, optionally followed by a unit, e.g. `512MiB` or `2GB`.
+#[cfg(any(feature = "rocksdb", feature = "dynamodb", feature = "scylladb"))]
+fn parse_size(input: &str) -> Result<u64, anyhow::Error> {
+    let digits = input
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(digits);
+    let multiplier: u64 = match unit {
+        "" | "B" => 1,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        _ => bail!("Unknown size unit {unit:?} in {input:?}"),
+    };
+    number
+        .parse::<u64>()?
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow!("The size {input:?} is too large"))
+}
+
+/// Writes the options of RocksDB that differ from the defaults, in the format read by
+/// [`parse_rocks_db_options`].
+#[cfg(feature = "rocksdb")]
+fn write_rocks_db_options(
+    f: &mut fmt::Formatter,
+    tuning: &RocksDbTuning,
+    cache: &StorageCacheOptions,
+) -> fmt::Result {
+    let mut options = Vec::new();
+    if tuning.profile != Default::default() {
+        options.push(format!("profile={}", tuning.profile));
+    }
+    if let Some(size) = tuning.block_cache_size {
+        options.push(format!("cache={size}"));
+    }
+    if let Some(bits) = tuning.bloom_filter_bits_per_key {
+        options.push(format!("bloom={bits}"));
+    }
+    if let Some(compression_per_level) = &tuning.compression_per_level {
+        let levels = compression_per_level
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        options.push(format!("compression={}", levels.join(",")));
+    }
+    if !tuning.column_families.is_empty() {
+        options.push("column_families=true".to_string());
+    }
+    if let Some(size) = cache.size {
+        options.push(format!("cache-size={size}"));
+    }
+    if let Some(policy) = cache.policy {
+        options.push(format!("cache-policy={policy}"));
+    }
+    if !options.is_empty() {
+        write!(f, "?{}", options.join("&"))?;
+    }
+    Ok(())
+}
+
 #[async_trait]
 pub trait Runnable {
     type Output;
@@ -522,7 +896,19 @@ impl StoreConfig {
         Job: Runnable,
     {
         match self {
-            StoreConfig::Memory { config, namespace } => {
+            StoreConfig::Memory {
+                config,
+                namespace,
+                persist,
+            } => {
+                let loaded = match &persist {
+                    Some(path) if path.exists() => {
+                        info!("Loading the memory storage from {}", path.display());
+                        MemoryStore::load_from_file(path)?;
+                        true
+                    }
+                    _ => false,
+                };
                 let store_config = MemoryStoreConfig::new(config.common_config.max_stream_queries);
                 let mut storage = DbStorage::<MemoryStore, _>::maybe_create_and_connect(
                     &store_config,
@@ -530,9 +916,22 @@ impl StoreConfig {
                     wasm_runtime,
                 )
                 .await?;
-                // Memory storage must be initialized every time.
-                genesis_config.initialize_storage(&mut storage).await?;
-                Ok(job.run(storage).await)
+                // Memory storage must be initialized every time, unless it was loaded from a
+                // snapshot of an initialized storage.
+                if !loaded || storage.read_network_description().await?.is_none() {
+                    genesis_config.initialize_storage(&mut storage).await?;
+                }
+                let Some(path) = persist else {
+                    return Ok(job.run(storage).await);
+                };
+                #[cfg(unix)]
+                let dump_on_signal = tokio::spawn(dump_memory_store_on_signal(path.clone()));
+                let output = job.run(storage).await;
+                #[cfg(unix)]
+                dump_on_signal.abort();
+                info!("Writing the memory storage to {}", path.display());
+                MemoryStore::dump_to_file(&path)?;
+                Ok(output)
             }
             #[cfg(feature = "storage-service")]
             StoreConfig::Service { config, namespace } => {
@@ -562,6 +961,16 @@ impl StoreConfig {
                         .await?;
                 Ok(job.run(storage).await)
             }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ReplicatedScyllaDb { config, namespace } => {
+                let storage = DbStorage::<ReplicatedStore<ScyllaDbStore>, _>::connect(
+                    &config,
+                    &namespace,
+                    wasm_runtime,
+                )
+                .await?;
+                Ok(job.run(storage).await)
+            }
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             StoreConfig::DualRocksDbScyllaDb { config, namespace } => {
                 let storage = DbStorage::<
@@ -599,6 +1008,10 @@ impl StoreConfig {
             StoreConfig::ScyllaDb { config, namespace } => {
                 Ok(job.run::<ScyllaDbStore>(config, namespace).await?)
             }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ReplicatedScyllaDb { config, namespace } => Ok(job
+                .run::<ReplicatedStore<ScyllaDbStore>>(config, namespace)
+                .await?),
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             StoreConfig::DualRocksDbScyllaDb { config, namespace } => Ok(job
                 .run::<DualStore<RocksDbStore, ScyllaDbStore, ChainStatesFirstAssignment>>(
@@ -613,6 +1026,27 @@ impl StoreConfig {
     }
 }
 
+/// Writes the memory storage to the snapshot file at `path` every time the process receives
+/// `SIGUSR1`.
+#[cfg(unix)]
+async fn dump_memory_store_on_signal(path: PathBuf) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut signals = match signal(SignalKind::user_defined1()) {
+        Ok(signals) => signals,
+        Err(error) => {
+            error!("Failed to listen to SIGUSR1: {error}");
+            return;
+        }
+    };
+    while signals.recv().await.is_some() {
+        info!("Writing the memory storage to {}", path.display());
+        if let Err(error) = MemoryStore::dump_to_file(&path) {
+            error!("Failed to write the memory storage: {error}");
+        }
+    }
+}
+
 struct InitializeStorageJob<'a>(&'a GenesisConfig);
 
 #[async_trait]
@@ -640,26 +1074,55 @@ fn test_memory_storage_config_from_str() {
     assert_eq!(
         StorageConfigNamespace::from_str("memory:").unwrap(),
         StorageConfigNamespace {
-            storage_config: StorageConfig::Memory,
+            storage_config: StorageConfig::Memory { persist: None },
             namespace: "".into()
         }
     );
     assert_eq!(
         StorageConfigNamespace::from_str("memory").unwrap(),
         StorageConfigNamespace {
-            storage_config: StorageConfig::Memory,
+            storage_config: StorageConfig::Memory { persist: None },
             namespace: DEFAULT_NAMESPACE.into()
         }
     );
     assert_eq!(
         StorageConfigNamespace::from_str("memory:table_linera").unwrap(),
         StorageConfigNamespace {
-            storage_config: StorageConfig::Memory,
+            storage_config: StorageConfig::Memory { persist: None },
             namespace: DEFAULT_NAMESPACE.into()
         }
     );
 }
 
+#[test]
+fn test_memory_storage_config_with_persist_from_str() {
+    let storage_config = StorageConfig::Memory {
+        persist: Some("/tmp/linera.snapshot".into()),
+    };
+    assert_eq!(
+        StorageConfigNamespace::from_str("memory:?persist=/tmp/linera.snapshot").unwrap(),
+        StorageConfigNamespace {
+            storage_config: storage_config.clone(),
+            namespace: "".into()
+        }
+    );
+    let config_namespace =
+        StorageConfigNamespace::from_str("memory?persist=/tmp/linera.snapshot").unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config,
+            namespace: DEFAULT_NAMESPACE.into()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+    assert!(StorageConfigNamespace::from_str("memory:?persist=").is_err());
+    assert!(StorageConfigNamespace::from_str("memory:?path=/tmp/linera.snapshot").is_err());
+}
+
 #[cfg(feature = "storage-service")]
 #[test]
 fn test_shared_store_config_from_str() {
@@ -686,6 +1149,8 @@ fn test_rocks_db_storage_config_from_str() {
             storage_config: StorageConfig::RocksDb {
                 path: "foo.db".into(),
                 spawn_mode: RocksDbSpawnMode::SpawnBlocking,
+                tuning: RocksDbTuning::default(),
+                cache: StorageCacheOptions::default(),
             },
             namespace: DEFAULT_NAMESPACE.to_string()
         }
@@ -696,6 +1161,8 @@ fn test_rocks_db_storage_config_from_str() {
             storage_config: StorageConfig::RocksDb {
                 path: "foo.db".into(),
                 spawn_mode: RocksDbSpawnMode::BlockInPlace,
+                tuning: RocksDbTuning::default(),
+                cache: StorageCacheOptions::default(),
             },
             namespace: DEFAULT_NAMESPACE.to_string()
         }
@@ -706,12 +1173,104 @@ fn test_rocks_db_storage_config_from_str() {
             storage_config: StorageConfig::RocksDb {
                 path: "foo.db".into(),
                 spawn_mode: RocksDbSpawnMode::BlockInPlace,
+                tuning: RocksDbTuning::default(),
+                cache: StorageCacheOptions::default(),
             },
             namespace: "chosen_namespace".into()
         }
     );
 }
 
+#[cfg(feature = "rocksdb")]
+#[test]
+fn test_rocks_db_tuning_from_str() {
+    use linera_views::rocks_db::RocksDbProfile;
+
+    let config =
+        StorageConfigNamespace::from_str("rocksdb:foo.db?profile=write-heavy&cache=2GiB").unwrap();
+    assert_eq!(
+        config,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::RocksDb {
+                path: "foo.db".into(),
+                spawn_mode: RocksDbSpawnMode::SpawnBlocking,
+                tuning: RocksDbTuning {
+                    block_cache_size: Some(2 << 30),
+                    ..RocksDbTuning::new(RocksDbProfile::WriteHeavy)
+                },
+                cache: StorageCacheOptions::default(),
+            },
+            namespace: DEFAULT_NAMESPACE.to_string()
+        }
+    );
+    assert_eq!(
+        config.to_string(),
+        format!("rocksdb:foo.db:spawn_blocking:{DEFAULT_NAMESPACE}?profile=write-heavy&cache=2147483648")
+    );
+
+    let input = "rocksdb:foo.db:block_in_place:chosen_namespace\
+        ?profile=point-lookup&bloom=12&compression=none,lz4,zstd&column_families=true";
+    let config = StorageConfigNamespace::from_str(input).unwrap();
+    let StorageConfig::RocksDb { tuning, .. } = &config.storage_config else {
+        panic!("unexpected storage config {config:?}");
+    };
+    assert_eq!(tuning.profile, RocksDbProfile::PointLookup);
+    assert_eq!(tuning.bloom_filter_bits_per_key, Some(12));
+    assert_eq!(
+        tuning.compression_per_level,
+        Some(vec![
+            RocksDbCompression::None,
+            RocksDbCompression::Lz4,
+            RocksDbCompression::Zstd
+        ])
+    );
+    assert_eq!(tuning.column_families.len(), StorageSection::ALL.len());
+    assert_eq!(config.namespace, "chosen_namespace");
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config.to_string()).unwrap(),
+        config
+    );
+
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?profile=fast").is_err());
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?cache=2TiB").is_err());
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?compression=brotli").is_err());
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?unknown=1").is_err());
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?profile").is_err());
+}
+
+#[cfg(feature = "rocksdb")]
+#[test]
+fn test_rocks_db_storage_config_with_cache_options_from_str() {
+    let config_namespace = StorageConfigNamespace::from_str(
+        "rocksdb:foo.db?cache=2GiB&cache-size=512MiB&cache-policy=segmented",
+    )
+    .unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::RocksDb {
+                path: "foo.db".into(),
+                spawn_mode: RocksDbSpawnMode::SpawnBlocking,
+                tuning: RocksDbTuning {
+                    block_cache_size: Some(2 << 30),
+                    ..RocksDbTuning::default()
+                },
+                cache: StorageCacheOptions {
+                    size: Some(512 << 20),
+                    policy: Some(CachePolicyKind::Segmented),
+                },
+            },
+            namespace: DEFAULT_NAMESPACE.to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?cache-policy=fifo").is_err());
+    assert!(StorageConfigNamespace::from_str("rocksdb:foo.db?cache-size=lots").is_err());
+}
+
 #[cfg(feature = "dynamodb")]
 #[test]
 fn test_aws_storage_config_from_str() {
@@ -719,7 +1278,9 @@ fn test_aws_storage_config_from_str() {
         StorageConfigNamespace::from_str("dynamodb:table").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::DynamoDb {
-                use_dynamodb_local: false
+                use_dynamodb_local: false,
+                compression: None,
+                cache: StorageCacheOptions::default(),
             },
             namespace: "table".to_string()
         }
@@ -728,7 +1289,9 @@ fn test_aws_storage_config_from_str() {
         StorageConfigNamespace::from_str("dynamodb:table:env").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::DynamoDb {
-                use_dynamodb_local: false
+                use_dynamodb_local: false,
+                compression: None,
+                cache: StorageCacheOptions::default(),
             },
             namespace: "table".to_string()
         }
@@ -737,7 +1300,9 @@ fn test_aws_storage_config_from_str() {
         StorageConfigNamespace::from_str("dynamodb:table:dynamodb_local").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::DynamoDb {
-                use_dynamodb_local: true
+                use_dynamodb_local: true,
+                compression: None,
+                cache: StorageCacheOptions::default(),
             },
             namespace: "table".to_string()
         }
@@ -748,6 +1313,29 @@ fn test_aws_storage_config_from_str() {
     assert!(StorageConfigNamespace::from_str("dynamodb:wrong:endpoint").is_err());
 }
 
+#[cfg(feature = "dynamodb")]
+#[test]
+fn test_aws_storage_config_with_compression_from_str() {
+    let config_namespace =
+        StorageConfigNamespace::from_str("dynamodb:table:env?compression=zstd:3").unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::DynamoDb {
+                use_dynamodb_local: false,
+                compression: Some(ValueCompression::zstd(3)),
+                cache: StorageCacheOptions::default(),
+            },
+            namespace: "table".to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+    assert!(StorageConfigNamespace::from_str("dynamodb:table?compression=lz4").is_err());
+}
+
 #[cfg(feature = "scylladb")]
 #[test]
 fn test_scylla_db_storage_config_from_str() {
@@ -755,7 +1343,10 @@ fn test_scylla_db_storage_config_from_str() {
         StorageConfigNamespace::from_str("scylladb:").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::ScyllaDb {
-                uri: "localhost:9042".to_string()
+                uri: "localhost:9042".to_string(),
+                compression: None,
+                cache: StorageCacheOptions::default(),
+                replicas: Vec::new(),
             },
             namespace: DEFAULT_NAMESPACE.to_string()
         }
@@ -765,7 +1356,10 @@ fn test_scylla_db_storage_config_from_str() {
             .unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::ScyllaDb {
-                uri: "db_hostname:230".to_string()
+                uri: "db_hostname:230".to_string(),
+                compression: None,
+                cache: StorageCacheOptions::default(),
+                replicas: Vec::new(),
             },
             namespace: "table_other_storage".to_string()
         }
@@ -774,7 +1368,10 @@ fn test_scylla_db_storage_config_from_str() {
         StorageConfigNamespace::from_str("scylladb:tcp:db_hostname:230").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::ScyllaDb {
-                uri: "db_hostname:230".to_string()
+                uri: "db_hostname:230".to_string(),
+                compression: None,
+                cache: StorageCacheOptions::default(),
+                replicas: Vec::new(),
             },
             namespace: DEFAULT_NAMESPACE.to_string()
         }
@@ -786,3 +1383,138 @@ fn test_scylla_db_storage_config_from_str() {
     assert!(StorageConfigNamespace::from_str("scylladb:tcp:address1:tcp:/address2").is_err());
     assert!(StorageConfigNamespace::from_str("scylladb:wrong").is_err());
 }
+
+#[cfg(feature = "scylladb")]
+#[test]
+fn test_scylla_db_storage_config_with_compression_from_str() {
+    assert_eq!(
+        StorageConfigNamespace::from_str("scylladb:tcp:db_hostname:230?compression=zstd:3")
+            .unwrap(),
+        StorageConfigNamespace {
+            storage_config: StorageConfig::ScyllaDb {
+                uri: "db_hostname:230".to_string(),
+                compression: Some(ValueCompression::zstd(3)),
+                cache: StorageCacheOptions::default(),
+                replicas: Vec::new(),
+            },
+            namespace: DEFAULT_NAMESPACE.to_string()
+        }
+    );
+    let config_namespace = StorageConfigNamespace::from_str(
+        "scylladb:tcp:db_hostname:230:table_other_storage\
+        ?compression=zstd:19&compression_threshold=1KiB",
+    )
+    .unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::ScyllaDb {
+                uri: "db_hostname:230".to_string(),
+                compression: Some(ValueCompression::zstd(19).with_threshold(1024)),
+                cache: StorageCacheOptions::default(),
+                replicas: Vec::new(),
+            },
+            namespace: "table_other_storage".to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+    assert!(StorageConfigNamespace::from_str("scylladb:?compression=zstd:100").is_err());
+    assert!(StorageConfigNamespace::from_str("scylladb:?compression_threshold=10").is_err());
+    assert!(StorageConfigNamespace::from_str("scylladb:?level=3").is_err());
+}
+
+#[cfg(feature = "scylladb")]
+#[test]
+fn test_scylla_db_storage_config_with_cache_options_from_str() {
+    let config_namespace = StorageConfigNamespace::from_str(
+        "scylladb:tcp:db_hostname:230?compression=zstd:3&cache=512MiB&cache-policy=segmented",
+    )
+    .unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::ScyllaDb {
+                uri: "db_hostname:230".to_string(),
+                compression: Some(ValueCompression::zstd(3)),
+                cache: StorageCacheOptions {
+                    size: Some(512 << 20),
+                    policy: Some(CachePolicyKind::Segmented),
+                },
+                replicas: Vec::new(),
+            },
+            namespace: DEFAULT_NAMESPACE.to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+
+    let mut storage_cache_config = linera_views::lru_caching::DEFAULT_STORAGE_CACHE_CONFIG;
+    StorageCacheOptions {
+        size: None,
+        policy: Some(CachePolicyKind::Lfu),
+    }
+    .apply(&mut storage_cache_config);
+    assert_eq!(storage_cache_config.cache_policy, CachePolicyKind::Lfu);
+    assert_eq!(storage_cache_config.max_cache_size, 10000000);
+    assert!(StorageConfigNamespace::from_str("scylladb:?cache-policy=fifo").is_err());
+    assert!(StorageConfigNamespace::from_str("scylladb:?cache=lots").is_err());
+}
+
+#[cfg(feature = "scylladb")]
+#[test]
+fn test_scylla_db_storage_config_with_replicas_from_str() {
+    let config_namespace = StorageConfigNamespace::from_str(
+        "scylladb:tcp:db_hostname:230?replicas=replica1:9042,replica2:9043",
+    )
+    .unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::ScyllaDb {
+                uri: "db_hostname:230".to_string(),
+                compression: None,
+                cache: StorageCacheOptions::default(),
+                replicas: vec!["replica1:9042".to_string(), "replica2:9043".to_string()],
+            },
+            namespace: DEFAULT_NAMESPACE.to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+    assert!(StorageConfigNamespace::from_str("scylladb:?replicas=replica1").is_err());
+    assert!(StorageConfigNamespace::from_str("scylladb:?replicas=replica1:0").is_err());
+    #[cfg(feature = "dynamodb")]
+    assert!(StorageConfigNamespace::from_str("dynamodb:table?replicas=replica1:9042").is_err());
+}
+
+#[cfg(feature = "dynamodb")]
+#[test]
+fn test_aws_storage_config_with_cache_options_from_str() {
+    let config_namespace =
+        StorageConfigNamespace::from_str("dynamodb:table:env?cache-policy=lfu").unwrap();
+    assert_eq!(
+        config_namespace,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::DynamoDb {
+                use_dynamodb_local: false,
+                compression: None,
+                cache: StorageCacheOptions {
+                    size: None,
+                    policy: Some(CachePolicyKind::Lfu),
+                },
+            },
+            namespace: "table".to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config_namespace.to_string()).unwrap(),
+        config_namespace
+    );
+}
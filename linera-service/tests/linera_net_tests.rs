@@ -946,6 +946,81 @@ async fn test_wasm_end_to_end_counter_no_graphql(config: impl LineraNetConfig) -
     Ok(())
 }
 
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_test_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_wasm_end_to_end_query_limits(config: impl LineraNetConfig) -> Result<()> {
+    use linera_service::node_service::{QUERY_FUEL_LIMIT_HEADER, QUERY_TIMEOUT_HEADER};
+    use looping_service::{Abi, Query};
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let chain = client.load_wallet()?.default_chain().unwrap();
+    let (contract, service) = client.build_example("looping-service").await?;
+    let application_id = client
+        .publish_and_create::<Abi, (), ()>(contract, service, VmRuntime::Wasm, &(), &(), &[], None)
+        .await?;
+
+    let port = get_node_port().await;
+    let args = [
+        "--service-query-timeout-ms".to_string(),
+        "60000".to_string(),
+        "--service-query-fuel-limit".to_string(),
+        "100000000".to_string(),
+    ];
+    let mut node_service = client
+        .run_node_service_with_args(port, ProcessInbox::Skip, args)
+        .await?;
+    let application = node_service
+        .make_application(&chain, &application_id)
+        .await?;
+    let error_code = |response: &Value| response["errors"][0]["extensions"]["code"].clone();
+
+    // Queries within the limits succeed.
+    let response = application
+        .try_run_json_query(Query::SumUpTo(1_000), &[])
+        .await?;
+    assert_eq!(response, 500_500);
+
+    // The instrumented service is stopped once it runs out of fuel.
+    let response = application.try_run_json_query(Query::Loop, &[]).await?;
+    assert_eq!(error_code(&response), "QUERY_OUT_OF_FUEL");
+
+    // A request can lower the fuel limit...
+    let headers = [(QUERY_FUEL_LIMIT_HEADER, "1000".to_string())];
+    let response = application
+        .try_run_json_query(Query::SumUpTo(1_000_000), &headers)
+        .await?;
+    assert_eq!(error_code(&response), "QUERY_OUT_OF_FUEL");
+
+    // ... and the timeout.
+    let headers = [(QUERY_TIMEOUT_HEADER, "1".to_string())];
+    let response = application
+        .try_run_json_query(Query::Loop, &headers)
+        .await?;
+    assert_eq!(error_code(&response), "QUERY_TIMED_OUT");
+
+    // But not raise them: the service still runs out of fuel before the requested timeout.
+    let headers = [
+        (QUERY_FUEL_LIMIT_HEADER, u64::MAX.to_string()),
+        (QUERY_TIMEOUT_HEADER, u64::MAX.to_string()),
+    ];
+    let response = application
+        .try_run_json_query(Query::Loop, &headers)
+        .await?;
+    assert_eq!(error_code(&response), "QUERY_OUT_OF_FUEL");
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
 #[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_test_service_grpc"))]
 #[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
 #[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
@@ -1009,6 +1084,7 @@ async fn test_wasm_end_to_end_counter_publish_create(config: impl LineraNetConfi
 #[cfg_attr(feature = "remote-net", test_case(RemoteNetTestingConfig::new(None) ; "remote_net_grpc"))]
 #[test_log::test(tokio::test)]
 async fn test_wasm_end_to_end_social_event_streams(config: impl LineraNetConfig) -> Result<()> {
+    use base64::engine::{general_purpose::STANDARD, Engine as _};
     use linera_base::time::Instant;
     use social::SocialAbi;
     let _guard = INTEGRATION_TEST_GUARD.lock().await;
@@ -1047,6 +1123,17 @@ async fn test_wasm_end_to_end_social_event_streams(config: impl LineraNetConfig)
 
     let mut notifications = Box::pin(node_service2.notifications(chain2).await?);
 
+    let application_id1 = application_id.forget_abi();
+    let mut new_events = Box::pin(
+        node_service1
+            .subscribe(format!(
+                "subscription {{ newEvents(chainId: \"{chain1}\", \
+                 applicationId: \"{application_id1}\", streamName: \"posts\") \
+                 {{ index value }} }}"
+            ))
+            .await?,
+    );
+
     let app1 = node_service1
         .make_application(&chain1, &application_id)
         .await?;
@@ -1075,6 +1162,37 @@ async fn test_wasm_end_to_end_social_event_streams(config: impl LineraNetConfig)
         tracing::warn!("Waiting to confirm post: {}", response);
     }
 
+    // The event is pushed to the subscribers of the stream, and can also be queried.
+    let event = linera_base::time::timer::timeout(Duration::from_secs(20), new_events.next())
+        .await?
+        .expect("the event stream should not end")?;
+    assert_eq!(event["newEvents"]["index"], 0);
+    let hex_value = event["newEvents"]["value"].as_str().unwrap();
+    let query = format!(
+        "query {{ events(chainId: \"{chain1}\", applicationId: \"{application_id1}\", \
+         streamName: \"posts\", encoding: BASE64) {{ index value }} }}"
+    );
+    let response = node_service1.query_node(query).await?;
+    let events = response["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["index"], 0);
+    let base64_value = events[0]["value"].as_str().unwrap();
+    let bytes = hex::decode(hex_value)?;
+    assert_eq!(STANDARD.decode(base64_value)?, bytes);
+    let social::Event::Post { post, index } = bcs::from_bytes(&bytes)? else {
+        panic!("Unexpected event in the posts stream");
+    };
+    assert_eq!(index, 0);
+    assert_eq!(post.text, "Linera Social is the new Mastodon!");
+
+    // Queries for too many events are rejected, even if the stream is shorter.
+    let query = format!(
+        "query {{ events(chainId: \"{chain1}\", applicationId: \"{application_id1}\", \
+         streamName: \"posts\", end: {}) {{ index }} }}",
+        linera_service::node_service::MAX_EVENT_RANGE + 1
+    );
+    assert!(node_service1.query_node(query).await.is_err());
+
     node_service1.ensure_is_running()?;
     node_service2.ensure_is_running()?;
 
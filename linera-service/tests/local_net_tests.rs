@@ -14,15 +14,17 @@ mod guard;
 use std::{env, path::PathBuf, time::Duration};
 
 use anyhow::Result;
+use assert_matches::assert_matches;
+use futures::StreamExt as _;
 use guard::INTEGRATION_TEST_GUARD;
 #[cfg(any(feature = "benchmark", feature = "ethereum"))]
 use linera_base::vm::VmRuntime;
 use linera_base::{
     crypto::Secp256k1SecretKey,
     data_types::{Amount, BlockHeight, Epoch},
-    identifiers::{Account, AccountOwner},
+    identifiers::{Account, AccountOwner, MessageId},
 };
-use linera_core::{data_types::ChainInfoQuery, node::ValidatorNode};
+use linera_core::{data_types::ChainInfoQuery, node::ValidatorNode, worker::Reason};
 use linera_faucet::ClaimOutcome;
 use linera_sdk::linera_base_types::AccountSecretKey;
 use linera_service::{
@@ -30,6 +32,7 @@ use linera_service::{
         local_net::{get_node_port, Database, LocalNet, LocalNetConfig, ProcessInbox},
         ClientWrapper, FaucetOption, LineraNet, LineraNetConfig, Network,
     },
+    node_service::NotificationCursor,
     test_name,
 };
 use test_case::test_case;
@@ -506,6 +509,602 @@ async fn test_end_to_end_retry_notification_stream(config: LocalNetConfig) -> Re
     Ok(())
 }
 
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_block_connections(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let (chain1, chain2) = {
+        let wallet = client.load_wallet()?;
+        let chains = wallet.chain_ids();
+        (chains[0], chains[1])
+    };
+
+    // Each transfer sends a bundle, and all of them are received in the same block.
+    for _ in 0..3 {
+        client.transfer(Amount::ONE, chain1, chain2).await?;
+    }
+    client.process_inbox(chain2).await?;
+
+    let port = get_node_port().await;
+    let mut node_service = client.run_node_service(port, ProcessInbox::Skip).await?;
+    let query_page = |field: &str, arguments: &str| {
+        format!(
+            "query {{ block(chainId: \"{chain2}\") {{ block {{ body {{ \
+                {field}({arguments}) {{ \
+                    edges {{ cursor }} \
+                    pageInfo {{ hasPreviousPage hasNextPage endCursor }} \
+                }} \
+            }} }} }} }}"
+        )
+    };
+
+    let response = node_service
+        .query_node(query_page("incomingBundlesConnection", "first: 2"))
+        .await?;
+    let page = &response["block"]["block"]["body"]["incomingBundlesConnection"];
+    let cursors = page["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["cursor"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(cursors, ["0", "1"]);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"].as_bool(), Some(false));
+    assert_eq!(page["pageInfo"]["hasNextPage"].as_bool(), Some(true));
+    assert_eq!(page["pageInfo"]["endCursor"].as_str(), Some("1"));
+
+    // The last page ends exactly at the last bundle.
+    let response = node_service
+        .query_node(query_page(
+            "incomingBundlesConnection",
+            "first: 1, after: \"1\"",
+        ))
+        .await?;
+    let page = &response["block"]["block"]["body"]["incomingBundlesConnection"];
+    assert_eq!(page["edges"][0]["cursor"].as_str(), Some("2"));
+    assert_eq!(page["pageInfo"]["hasPreviousPage"].as_bool(), Some(true));
+    assert_eq!(page["pageInfo"]["hasNextPage"].as_bool(), Some(false));
+
+    let response = node_service
+        .query_node(query_page(
+            "incomingBundlesConnection",
+            "first: 2, after: \"2\"",
+        ))
+        .await?;
+    let page = &response["block"]["block"]["body"]["incomingBundlesConnection"];
+    assert_eq!(page["edges"].as_array().map(Vec::len), Some(0));
+    assert_eq!(page["pageInfo"]["hasNextPage"].as_bool(), Some(false));
+
+    // The block has no operations, so the cursors of the bundles are not valid ones.
+    let response = node_service
+        .query_node(query_page("operationsConnection", "first: 10"))
+        .await?;
+    let page = &response["block"]["block"]["body"]["operationsConnection"];
+    assert_eq!(page["edges"].as_array().map(Vec::len), Some(0));
+    assert!(node_service
+        .query_node(query_page("operationsConnection", "after: \"0\""))
+        .await
+        .is_err());
+    assert!(node_service
+        .query_node(query_page("messagesConnection", "after: \"not a cursor\""))
+        .await
+        .is_err());
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_block_range_and_search(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let (chain1, chain2) = {
+        let wallet = client.load_wallet()?;
+        let chains = wallet.chain_ids();
+        (chains[0], chains[1])
+    };
+
+    // Each transfer is a block of chain 1, and all of them are received in one block of chain 2.
+    for _ in 0..3 {
+        client.transfer(Amount::ONE, chain1, chain2).await?;
+    }
+    client.process_inbox(chain2).await?;
+
+    let port = get_node_port().await;
+    let mut node_service = client.run_node_service(port, ProcessInbox::Skip).await?;
+
+    let query = format!(
+        "query {{ blockSummaries(chainId: \"{chain1}\") {{ \
+            hash height operationCount incomingBundleCount \
+        }} }}"
+    );
+    let response = node_service.query_node(query).await?;
+    let summaries = response["blockSummaries"].as_array().unwrap();
+    assert!(summaries.len() >= 3);
+    for (height, summary) in summaries.iter().enumerate() {
+        assert_eq!(summary["height"], height);
+    }
+    let transfers = &summaries[summaries.len() - 3..];
+    for summary in transfers {
+        assert_eq!(summary["operationCount"], 1);
+        assert_eq!(summary["incomingBundleCount"], 0);
+    }
+
+    // The range is capped by the limit and by the length of the chain.
+    let from = summaries.len() - 2;
+    let query = format!(
+        "query {{ blockSummaries(chainId: \"{chain1}\", from: {from}, to: 1000, limit: 5) {{ \
+            hash height \
+        }} }}"
+    );
+    let response = node_service.query_node(query).await?;
+    let hashes = response["blockSummaries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|summary| &summary["hash"])
+        .collect::<Vec<_>>();
+    let expected_hashes = summaries[from..]
+        .iter()
+        .map(|summary| &summary["hash"])
+        .collect::<Vec<_>>();
+    assert_eq!(hashes, expected_hashes);
+
+    let query = format!(
+        "query {{ blockSummaries(chainId: \"{chain2}\", from: 0, to: 0) {{ \
+            hash operationCount incomingBundleCount \
+        }} }}"
+    );
+    let response = node_service.query_node(query).await?;
+    let receiving_block = &response["blockSummaries"][0];
+    assert_eq!(receiving_block["operationCount"], 0);
+    assert_eq!(receiving_block["incomingBundleCount"], 3);
+
+    // Too large ranges are rejected, even on a short chain.
+    for arguments in ["from: 0, to: 1000", "limit: 1001"] {
+        let query =
+            format!("query {{ blockSummaries(chainId: \"{chain1}\", {arguments}) {{ hash }} }}");
+        assert!(node_service.query_node(query).await.is_err());
+    }
+
+    // The certificate of a transfer is found in both chains.
+    let transfer_hash = transfers[1]["hash"].as_str().unwrap();
+    let query_containing = |chain_id, argument: String| {
+        format!("query {{ blockContaining(chainId: \"{chain_id}\", {argument}) {{ hash }} }}")
+    };
+    let response = node_service
+        .query_node(query_containing(
+            chain1,
+            format!("certificateHash: \"{transfer_hash}\""),
+        ))
+        .await?;
+    assert_eq!(response["blockContaining"]["hash"], transfer_hash);
+    let response = node_service
+        .query_node(query_containing(
+            chain2,
+            format!("certificateHash: \"{transfer_hash}\""),
+        ))
+        .await?;
+    assert_eq!(response["blockContaining"]["hash"], receiving_block["hash"]);
+
+    // So is the message it sent, but not a message with a higher index.
+    let height = BlockHeight(transfers[1]["height"].as_u64().unwrap());
+    let message_id = |index| {
+        let message_id = MessageId {
+            chain_id: chain1,
+            height,
+            index,
+        };
+        format!(
+            "messageId: \"{}\"",
+            hex::encode(bcs::to_bytes(&message_id).unwrap())
+        )
+    };
+    let response = node_service
+        .query_node(query_containing(chain1, message_id(0)))
+        .await?;
+    assert_eq!(response["blockContaining"]["hash"], transfer_hash);
+    let response = node_service
+        .query_node(query_containing(chain2, message_id(0)))
+        .await?;
+    assert_eq!(response["blockContaining"]["hash"], receiving_block["hash"]);
+    for chain_id in [chain1, chain2] {
+        let response = node_service
+            .query_node(query_containing(chain_id, message_id(1)))
+            .await?;
+        assert!(response["blockContaining"].is_null());
+    }
+
+    // Exactly one of the arguments must be given.
+    assert!(node_service
+        .query_node(format!(
+            "query {{ blockContaining(chainId: \"{chain1}\") {{ hash }} }}"
+        ))
+        .await
+        .is_err());
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_transfer_with_memo(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let (chain1, chain2) = {
+        let wallet = client.load_wallet()?;
+        let chains = wallet.chain_ids();
+        (chains[0], chains[1])
+    };
+    let owner2 = AccountOwner::from(AccountSecretKey::generate().public());
+
+    let port = get_node_port().await;
+    let mut node_service = client.run_node_service(port, ProcessInbox::Skip).await?;
+    let transfer = |amount: &str, memo: &str| {
+        format!(
+            "mutation {{ transfer(\
+                source: {{ chain_id: \"{chain1}\", owner: \"{}\" }}, \
+                destination: {{ chain_id: \"{chain2}\", owner: \"{owner2}\" }}, \
+                amount: \"{amount}\", memo: \"{memo}\" \
+            ) {{ hash height }} }}",
+            AccountOwner::CHAIN
+        )
+    };
+
+    let response = node_service
+        .query_node(transfer("1.5", "invoice 42"))
+        .await?;
+    let hash = response["transfer"]["hash"].as_str().unwrap();
+    let height = response["transfer"]["height"].as_u64().unwrap();
+
+    // The memo is stored in the operation of the block.
+    let query = format!(
+        "query {{ block(chainId: \"{chain1}\", hash: \"{hash}\") {{ \
+            block {{ header {{ height }} body {{ operations }} }} \
+        }} }}"
+    );
+    let response = node_service.query_node(query).await?;
+    let block = &response["block"]["block"];
+    assert_eq!(block["header"]["height"].as_u64(), Some(height));
+    let transfer_operation = &block["body"]["operations"][0]["System"]["TransferWithMemo"];
+    assert_eq!(transfer_operation["memo"], "invoice 42");
+
+    // Overly long memos and transfers exceeding the balance are rejected.
+    let long_memo = "a".repeat(linera_execution::MAX_TRANSFER_MEMO_LEN + 1);
+    let response = node_service
+        .try_query_node(transfer("1", &long_memo))
+        .await?;
+    assert_eq!(response["errors"][0]["extensions"]["code"], "MEMO_TOO_LONG");
+    let response = node_service
+        .try_query_node(transfer("1000000000", "too much"))
+        .await?;
+    assert_eq!(
+        response["errors"][0]["extensions"]["code"],
+        "INSUFFICIENT_BALANCE"
+    );
+
+    node_service.ensure_is_running()?;
+
+    client.process_inbox(chain2).await?;
+    let balance = client.query_balance(Account::new(chain2, owner2)).await?;
+    assert_eq!(balance, Amount::from_millis(1500));
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_resume_notifications(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let (chain1, chain2) = {
+        let wallet = client.load_wallet()?;
+        let chains = wallet.chain_ids();
+        (chains[0], chains[1])
+    };
+
+    let port = get_node_port().await;
+    let mut node_service = client.run_node_service(port, ProcessInbox::Skip).await?;
+    let mut notifications = Box::pin(
+        node_service
+            .notifications_with_cursors(chain1, None)
+            .await?,
+    );
+
+    let transfer = format!(
+        "mutation {{ transfer(\
+            source: {{ chain_id: \"{chain1}\", owner: \"{owner}\" }}, \
+            destination: {{ chain_id: \"{chain2}\", owner: \"{owner}\" }}, \
+            amount: \"1\" \
+        ) {{ hash height }} }}",
+        owner = AccountOwner::CHAIN
+    );
+    node_service.query_node(&transfer).await?;
+    node_service.query_node(&transfer).await?;
+
+    // Receive the notifications up to the second new block.
+    let mut received = Vec::new();
+    let mut new_blocks = 0;
+    while new_blocks < 2 {
+        let notification = tokio::time::timeout(Duration::from_secs(10), notifications.next())
+            .await?
+            .expect("the node service should keep sending notifications")?;
+        if matches!(notification.notification.reason, Reason::NewBlock { .. }) {
+            new_blocks += 1;
+        }
+        received.push(notification);
+    }
+    drop(notifications);
+    let first = received[0].cursor;
+    let cursors = received
+        .iter()
+        .map(|notification| notification.cursor)
+        .collect::<Vec<_>>();
+    let expected_cursors = (first.index..first.index + received.len() as u64)
+        .map(|index| NotificationCursor { index, ..first })
+        .collect::<Vec<_>>();
+    assert_eq!(cursors, expected_cursors);
+
+    // A reconnecting client resumes right after the last notification it has seen.
+    let mut resumed = Box::pin(
+        node_service
+            .notifications_with_cursors(chain1, Some(first))
+            .await?,
+    );
+    for expected in &received[1..] {
+        let notification = tokio::time::timeout(Duration::from_secs(10), resumed.next())
+            .await?
+            .expect("the retained notifications should be sent")?;
+        assert_eq!(&notification, expected);
+    }
+    drop(resumed);
+
+    // Cursors from another epoch, e.g. from before a restart, are rejected.
+    let other_epoch = NotificationCursor {
+        epoch: first.epoch.wrapping_add(1),
+        index: 0,
+    };
+    let mut unknown = Box::pin(
+        node_service
+            .notifications_with_cursors(chain1, Some(other_epoch))
+            .await?,
+    );
+    let result = tokio::time::timeout(Duration::from_secs(10), unknown.next()).await?;
+    assert_matches!(result, Some(Err(_)));
+    drop(unknown);
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_new_blocks_subscription(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let (chain1, chain2) = {
+        let wallet = client.load_wallet()?;
+        let chains = wallet.chain_ids();
+        (chains[0], chains[1])
+    };
+
+    let port = get_node_port().await;
+    let mut node_service = client.run_node_service(port, ProcessInbox::Skip).await?;
+
+    let transfer = format!(
+        "mutation {{ transfer(\
+            source: {{ chain_id: \"{chain1}\", owner: \"{owner}\" }}, \
+            destination: {{ chain_id: \"{chain2}\", owner: \"{owner}\" }}, \
+            amount: \"1\" \
+        ) {{ hash height }} }}",
+        owner = AccountOwner::CHAIN
+    );
+    let mut heights = Vec::new();
+    for _ in 0..2 {
+        let response = node_service.query_node(&transfer).await?;
+        heights.push(serde_json::from_value::<BlockHeight>(
+            response["transfer"]["height"].clone(),
+        )?);
+    }
+
+    // The blocks since the given height are sent first, then the new ones.
+    let since = heights[0];
+    let mut blocks = Box::pin(
+        node_service
+            .subscribe(format!(
+                "subscription {{ blocks(chainId: \"{chain1}\", since: {since}) {{ \
+                    height changedApplications \
+                }} }}"
+            ))
+            .await?,
+    );
+    let response = node_service.query_node(&transfer).await?;
+    heights.push(serde_json::from_value(
+        response["transfer"]["height"].clone(),
+    )?);
+
+    for expected_height in &heights {
+        let block = tokio::time::timeout(Duration::from_secs(10), blocks.next())
+            .await?
+            .expect("the node service should keep sending blocks")?;
+        let height = serde_json::from_value::<BlockHeight>(block["blocks"]["height"].clone())?;
+        assert_eq!(&height, expected_height);
+        // Transfers only change the system application.
+        assert_eq!(
+            block["blocks"]["changedApplications"],
+            serde_json::json!([])
+        );
+    }
+    drop(blocks);
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
+#[cfg(with_metrics)]
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_faucet_metrics(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let faucet_client = net.make_client().await;
+    faucet_client.wallet_init(&[], FaucetOption::None).await?;
+    let faucet_chain = client
+        .open_and_assign(&faucet_client, Amount::from_tokens(1_000u128))
+        .await?;
+
+    let metrics_port = get_node_port().await;
+    let args = [
+        "--metrics-port".to_string(),
+        metrics_port.to_string(),
+        "--max-claims-per-ip-per-hour".to_string(),
+        "1".to_string(),
+    ];
+    let mut faucet_service = faucet_client
+        .run_faucet_with_args(None, faucet_chain, Amount::from_tokens(2), args)
+        .await?;
+    let faucet = faucet_service.instance();
+
+    let owner = client.keygen().await?;
+    faucet.claim(&owner).await?;
+    // The second claim from the same IP address is rejected.
+    let other_owner = client.keygen().await?;
+    assert!(faucet.claim(&other_owner).await.is_err());
+
+    let expected = [
+        "linera_faucet_claims_served 1",
+        "linera_faucet_claims_rejected 1",
+        "linera_faucet_claim_latency_count 1",
+    ];
+    let url = format!("http://localhost:{metrics_port}/metrics");
+    let metrics = reqwest::get(&url).await?.text().await?;
+    for line in expected {
+        assert!(
+            metrics.contains(line),
+            "{line:?} is missing from:\n{metrics}"
+        );
+    }
+
+    faucet_service.ensure_is_running()?;
+    faucet_service.terminate().await?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
+#[cfg(with_metrics)]
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_node_service_metrics(config: LocalNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client) = config.instantiate().await?;
+    let chain = client.load_wallet()?.default_chain().unwrap();
+
+    let port = get_node_port().await;
+    let metrics_port = get_node_port().await;
+    let mut node_service = client
+        .run_node_service_with_metrics_port(port, Some(metrics_port), ProcessInbox::Skip)
+        .await?;
+    node_service
+        .query_node("query Chains { chains { list } }")
+        .await?;
+    // The names of invalid fields are not used as labels.
+    let response = node_service
+        .try_query_node("query { unknownField }")
+        .await?;
+    assert!(response["errors"].is_array());
+    let notifications = Box::pin(node_service.notifications(chain).await?);
+
+    let expected = [
+        "linera_node_service_requests{operation=\"chains\",status=\"ok\"} 1",
+        "linera_node_service_requests{operation=\"other\",status=\"error\"} 1",
+        "linera_node_service_request_latency_count{operation=\"chains\"} 1",
+        "linera_node_service_active_subscriptions{subscription=\"notifications\"} 1",
+        "linera_chain_worker_queue_depth",
+    ];
+    let url = format!("http://localhost:{metrics_port}/metrics");
+    let mut metrics = String::new();
+    for _ in 0..10 {
+        metrics = reqwest::get(&url).await?.text().await?;
+        if expected.iter().all(|line| metrics.contains(line)) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    for line in expected {
+        assert!(
+            metrics.contains(line),
+            "{line:?} is missing from:\n{metrics}"
+        );
+    }
+    drop(notifications);
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
 #[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_service_grpc"))]
 #[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
 #[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
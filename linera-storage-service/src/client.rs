@@ -455,6 +455,10 @@ impl AdminKeyValueStore for ServiceStoreClientInternal {
         })
     }
 
+    fn clone_with_bounded_staleness(&self, _bounded_staleness: bool) -> Self {
+        self.clone()
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, ServiceStoreError> {
         let endpoint = config.http_address();
         let endpoint = Endpoint::from_shared(endpoint)?;
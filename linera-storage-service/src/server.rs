@@ -13,7 +13,7 @@ use linera_views::{
 #[cfg(with_rocksdb)]
 use linera_views::{
     rocks_db::{PathWithGuard, RocksDbSpawnMode, RocksDbStore, RocksDbStoreConfig},
-    store::AdminKeyValueStore as _,
+    store::{AdminKeyValueStore as _, KeyIterable as _},
 };
 use serde::Serialize;
 use tonic::{transport::Server, Request, Response, Status};
@@ -131,9 +131,16 @@ impl ServiceStoreServer {
             }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => {
-                store.find_keys_by_prefix(key_prefix).await.map_err(|e| {
-                    Status::unknown(format!("RocksDB error {:?} at find_keys_by_prefix", e))
-                })
+                let to_status =
+                    |e| Status::unknown(format!("RocksDB error {:?} at find_keys_by_prefix", e));
+                let keys = store
+                    .find_keys_by_prefix(key_prefix)
+                    .await
+                    .map_err(to_status)?;
+                keys.iterator()
+                    .map(|key| key.map(<[u8]>::to_vec))
+                    .collect::<Result<_, _>>()
+                    .map_err(to_status)
             }
         }
     }
@@ -0,0 +1,206 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Copying of chains from one storage to another, e.g. to migrate a validator between
+//! database backends without resynchronizing from genesis.
+
+use linera_base::{
+    data_types::{ArithmeticError, BlobType, BlockHeight},
+    identifiers::{BlobId, ChainId},
+};
+use linera_chain::ChainStateView;
+use linera_views::{
+    batch::Batch,
+    context::Context as _,
+    store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
+    views::{View as _, ViewError},
+};
+
+use crate::Storage;
+
+/// The number of certificates read from the source storage at once.
+const CERTIFICATE_CHUNK_SIZE: usize = 100;
+
+/// The number of keys of the chain state copied at once.
+const CHAIN_STATE_PAGE_SIZE: usize = 1000;
+
+/// What was done while copying a chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainCopySummary {
+    /// The number of certificates written to the target storage.
+    pub certificates_copied: usize,
+    /// The number of certificates that the target storage already contained.
+    pub certificates_skipped: usize,
+    /// The number of blobs written to the target storage.
+    pub blobs_copied: usize,
+    /// Whether the chain state was copied. This is only the case if all the blocks of the
+    /// chain were copied.
+    pub chain_state_copied: bool,
+}
+
+/// Copies a chain from the `source` storage to the `target` storage: its description, its
+/// confirmed certificates together with the blobs, blob states and events they refer to, and
+/// its chain state.
+///
+/// If `up_to` is given, only the certificates up to and including that height are copied,
+/// and the chain state is only copied if that is the latest block of the chain.
+///
+/// Copying is idempotent: certificates that the target already contains are skipped, and the
+/// chain state in the target is replaced. Since the chain state is written last, in batches
+/// of [`CHAIN_STATE_PAGE_SIZE`] keys, an interrupted copy can be resumed by running it again.
+pub async fn copy_chain<S, T>(
+    source: &S,
+    target: &T,
+    chain_id: ChainId,
+    up_to: Option<BlockHeight>,
+) -> Result<ChainCopySummary, ViewError>
+where
+    S: Storage + Sync,
+    T: Storage + Sync,
+{
+    let mut summary = ChainCopySummary::default();
+    let description_id = BlobId::new(chain_id.0, BlobType::ChainDescription);
+    let description = source.read_blob(description_id).await?;
+    if !target.contains_blob(description_id).await? {
+        target.write_blob(&description).await?;
+        summary.blobs_copied += 1;
+    }
+
+    let chain = source.load_chain(chain_id).await?;
+    let next_height = chain.tip_state.get().next_block_height;
+    let end = match up_to {
+        Some(height) => next_height.min(height.try_add_one()?),
+        None => next_height,
+    };
+    let end_index = usize::try_from(end.0).map_err(|_| ArithmeticError::Overflow)?;
+
+    for start in (0..end_index).step_by(CERTIFICATE_CHUNK_SIZE) {
+        let chunk = chain
+            .confirmed_log
+            .read(start..end_index.min(start + CERTIFICATE_CHUNK_SIZE))
+            .await?;
+        let contained = target.contains_certificates(&chunk).await?;
+        let mut missing = Vec::new();
+        for (hash, contained) in chunk.iter().zip(contained) {
+            if contained {
+                summary.certificates_skipped += 1;
+            } else {
+                missing.push(*hash);
+            }
+        }
+        for certificate in source.read_certificates(missing).await? {
+            let block = certificate.block();
+            let blob_ids = block.required_blob_ids().into_iter().collect::<Vec<_>>();
+            let blobs = source.read_blobs(&blob_ids).await?;
+            let missing_blob_ids = blob_ids
+                .iter()
+                .zip(&blobs)
+                .filter(|(_, blob)| blob.is_none())
+                .map(|(blob_id, _)| *blob_id)
+                .collect::<Vec<_>>();
+            if !missing_blob_ids.is_empty() {
+                return Err(ViewError::BlobsNotFound(missing_blob_ids));
+            }
+            let blobs = blobs.into_iter().flatten().collect::<Vec<_>>();
+            let events = block
+                .body
+                .events
+                .iter()
+                .flatten()
+                .map(|event| (event.id(chain_id), event.value.clone()));
+            target.write_events(events).await?;
+            target
+                .maybe_write_blob_states(&blob_ids, certificate.value().to_blob_state(), true)
+                .await?;
+            // The certificate is written last, so that it is only skipped when re-running if
+            // everything it refers to has been copied.
+            target
+                .write_blobs_and_certificate(&blobs, &certificate)
+                .await?;
+            summary.certificates_copied += 1;
+            summary.blobs_copied += blobs.len();
+        }
+    }
+
+    if end == next_height {
+        copy_chain_state::<S, T>(&chain, target, chain_id).await?;
+        summary.chain_state_copied = true;
+    }
+    Ok(summary)
+}
+
+/// Replaces the chain state of `chain_id` in the `target` storage by `source_chain`, key by
+/// key, in batches of [`CHAIN_STATE_PAGE_SIZE`] keys.
+async fn copy_chain_state<S, T>(
+    source_chain: &ChainStateView<S::Context>,
+    target: &T,
+    chain_id: ChainId,
+) -> Result<(), ViewError>
+where
+    S: Storage,
+    T: Storage + Sync,
+{
+    let target_chain = target.load_chain(chain_id).await?;
+    let source_context = source_chain.context();
+    let target_context = target_chain.context();
+    let source_store = source_context.store();
+    let target_store = target_context.store();
+    let source_prefix = &source_context.base_key().bytes;
+    let target_prefix = &target_context.base_key().bytes;
+
+    // Delete the keys of the target that are not in the source.
+    let mut start_after = None;
+    loop {
+        let keys = target_store
+            .find_keys_by_prefix_after(target_prefix, start_after.as_deref(), CHAIN_STATE_PAGE_SIZE)
+            .await?;
+        let source_keys = keys
+            .iter()
+            .map(|key| [source_prefix.as_slice(), key.as_slice()].concat())
+            .collect();
+        let contained = source_store.contains_keys(source_keys).await?;
+        let mut batch = Batch::new();
+        for (key, contained) in keys.iter().zip(contained) {
+            if !contained {
+                batch.delete_key([target_prefix.as_slice(), key.as_slice()].concat());
+            }
+        }
+        if !batch.is_empty() {
+            target_store.write_batch(batch).await?;
+        }
+        if keys.len() < CHAIN_STATE_PAGE_SIZE {
+            break;
+        }
+        start_after = keys.into_iter().last();
+    }
+
+    // Copy the values of the source.
+    let mut start_after = None;
+    loop {
+        let keys = source_store
+            .find_keys_by_prefix_after(source_prefix, start_after.as_deref(), CHAIN_STATE_PAGE_SIZE)
+            .await?;
+        let source_keys = keys
+            .iter()
+            .map(|key| [source_prefix.as_slice(), key.as_slice()].concat())
+            .collect();
+        let values = source_store.read_multi_values_bytes(source_keys).await?;
+        let mut batch = Batch::new();
+        for (key, value) in keys.iter().zip(values) {
+            if let Some(value) = value {
+                batch.put_key_value_bytes(
+                    [target_prefix.as_slice(), key.as_slice()].concat(),
+                    value,
+                );
+            }
+        }
+        if !batch.is_empty() {
+            target_store.write_batch(batch).await?;
+        }
+        if keys.len() < CHAIN_STATE_PAGE_SIZE {
+            break;
+        }
+        start_after = keys.into_iter().last();
+    }
+    Ok(())
+}
@@ -39,6 +39,7 @@ use {
         exponential_bucket_latencies, register_histogram_vec, register_int_counter_vec,
         MeasureLatency,
     },
+    linera_views::{storage_stats::StorageStatsCollector, store::ReadableKeyValueStore as _},
     prometheus::{HistogramVec, IntCounterVec},
 };
 
@@ -324,13 +325,62 @@ enum BaseKey {
     Event(EventId),
     BlockExporterState(u32),
     NetworkDescription,
+    Named(String),
 }
 
 const INDEX_CHAIN_ID: u8 = 0;
+const INDEX_CERTIFICATE: u8 = 1;
+const INDEX_CONFIRMED_BLOCK: u8 = 2;
 const INDEX_BLOB_ID: u8 = 3;
 const CHAIN_ID_LENGTH: usize = std::mem::size_of::<ChainId>();
 const BLOB_ID_LENGTH: usize = std::mem::size_of::<BlobId>();
 
+/// The main kinds of data stored by a [`DbStorage`], which a key-value store may want to keep
+/// apart, e.g. in separate RocksDB column families.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StorageSection {
+    /// The states of the chains.
+    ChainStates,
+    /// The certificates.
+    Certificates,
+    /// The confirmed blocks.
+    ConfirmedBlocks,
+    /// The blobs.
+    Blobs,
+}
+
+impl StorageSection {
+    /// All the sections.
+    pub const ALL: [StorageSection; 4] = [
+        StorageSection::ChainStates,
+        StorageSection::Certificates,
+        StorageSection::ConfirmedBlocks,
+        StorageSection::Blobs,
+    ];
+
+    /// Returns a short name for the section.
+    pub fn name(self) -> &'static str {
+        match self {
+            StorageSection::ChainStates => "chain_states",
+            StorageSection::Certificates => "certificates",
+            StorageSection::ConfirmedBlocks => "confirmed_blocks",
+            StorageSection::Blobs => "blobs",
+        }
+    }
+
+    /// Returns the prefix of the root key followed by the key, for all the data of the
+    /// section.
+    pub fn key_prefix(self) -> Vec<u8> {
+        let index = match self {
+            StorageSection::ChainStates => INDEX_CHAIN_ID,
+            StorageSection::Certificates => INDEX_CERTIFICATE,
+            StorageSection::ConfirmedBlocks => INDEX_CONFIRMED_BLOCK,
+            StorageSection::Blobs => INDEX_BLOB_ID,
+        };
+        vec![index]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use linera_base::{
@@ -339,7 +389,7 @@ mod tests {
     };
 
     use crate::db_storage::{
-        BaseKey, BLOB_ID_LENGTH, CHAIN_ID_LENGTH, INDEX_BLOB_ID, INDEX_CHAIN_ID,
+        BaseKey, StorageSection, BLOB_ID_LENGTH, CHAIN_ID_LENGTH, INDEX_BLOB_ID, INDEX_CHAIN_ID,
     };
 
     #[test]
@@ -362,6 +412,27 @@ mod tests {
         assert_eq!(key[0], INDEX_CHAIN_ID);
         assert_eq!(key.len(), 1 + CHAIN_ID_LENGTH);
     }
+
+    #[test]
+    fn test_storage_section_key_prefixes() {
+        let hash = CryptoHash::default();
+        let blob_id = BlobId::new(hash, BlobType::default());
+        for (section, base_key) in [
+            (
+                StorageSection::ChainStates,
+                BaseKey::ChainState(ChainId(hash)),
+            ),
+            (StorageSection::Certificates, BaseKey::Certificate(hash)),
+            (
+                StorageSection::ConfirmedBlocks,
+                BaseKey::ConfirmedBlock(hash),
+            ),
+            (StorageSection::Blobs, BaseKey::Blob(blob_id)),
+        ] {
+            let key = bcs::to_bytes(&base_key).expect("a key");
+            assert!(key.starts_with(&section.key_prefix()), "{section:?}");
+        }
+    }
 }
 
 /// An implementation of [`DualStoreRootKeyAssignment`] that stores the
@@ -505,11 +576,20 @@ where
     type Context = ViewContext<ChainRuntimeContext<Self>, Store>;
     type Clock = C;
     type BlockExporterContext = ViewContext<u32, Store>;
+    type NamedContext = ViewContext<(), Store>;
 
     fn clock(&self) -> &C {
         &self.clock
     }
 
+    fn with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        Self {
+            store: Arc::new(store),
+            ..self.clone()
+        }
+    }
+
     async fn load_chain(
         &self,
         chain_id: ChainId,
@@ -519,7 +599,7 @@ where
         let runtime_context = ChainRuntimeContext {
             storage: self.clone(),
             chain_id,
-            execution_runtime_config: self.execution_runtime_config,
+            execution_runtime_config: self.execution_runtime_config.clone(),
             user_contracts: self.user_contracts.clone(),
             user_services: self.user_services.clone(),
         };
@@ -787,6 +867,19 @@ where
         Ok(results[0] && results[1])
     }
 
+    async fn contains_certificates(&self, hashes: &[CryptoHash]) -> Result<Vec<bool>, ViewError> {
+        let keys = Self::get_keys_for_certificates(hashes)?;
+        let results = self.store.contains_keys(keys).await?;
+        #[cfg(with_metrics)]
+        CONTAINS_CERTIFICATE_COUNTER
+            .with_label_values(&[])
+            .inc_by(hashes.len() as u64);
+        Ok(results
+            .chunks_exact(2)
+            .map(|pair| pair[0] && pair[1])
+            .collect())
+    }
+
     async fn read_certificate(
         &self,
         hash: CryptoHash,
@@ -832,6 +925,33 @@ where
         maybe_value.ok_or_else(|| ViewError::EventsNotFound(vec![event_id]))
     }
 
+    async fn read_events(&self, event_ids: &[EventId]) -> Result<Vec<Vec<u8>>, ViewError> {
+        if event_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let event_keys = event_ids
+            .iter()
+            .map(|event_id| bcs::to_bytes(&BaseKey::Event(event_id.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let maybe_values = self.store.read_multi_values_bytes(event_keys).await?;
+        #[cfg(with_metrics)]
+        READ_EVENT_COUNTER
+            .with_label_values(&[])
+            .inc_by(event_ids.len() as u64);
+        let mut values = Vec::new();
+        let mut missing_events = Vec::new();
+        for (event_id, maybe_value) in event_ids.iter().zip(maybe_values) {
+            match maybe_value {
+                Some(value) => values.push(value),
+                None => missing_events.push(event_id.clone()),
+            }
+        }
+        if !missing_events.is_empty() {
+            return Err(ViewError::EventsNotFound(missing_events));
+        }
+        Ok(values)
+    }
+
     async fn contains_event(&self, event_id: EventId) -> Result<bool, ViewError> {
         let event_key = bcs::to_bytes(&BaseKey::Event(event_id))?;
         let exists = self.store.contains_key(&event_key).await?;
@@ -881,6 +1001,12 @@ where
         let store = self.store.clone_with_root_key(&root_key)?;
         Ok(ViewContext::create_root_context(store, block_exporter_id).await?)
     }
+
+    async fn named_context(&self, name: &str) -> Result<Self::NamedContext, ViewError> {
+        let root_key = bcs::to_bytes(&BaseKey::Named(name.to_owned()))?;
+        let store = self.store.clone_with_root_key(&root_key)?;
+        Ok(ViewContext::create_root_context(store, ()).await?)
+    }
 }
 
 impl<Store, C> DbStorage<Store, C>
@@ -924,6 +1050,27 @@ where
         Ok(())
     }
 
+    /// Returns the underlying key-value store.
+    #[cfg(with_testing)]
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Returns the statistics of the storage accesses of the views, if the store collects
+    /// them.
+    #[cfg(with_metrics)]
+    pub fn storage_stats(&self) -> Option<&StorageStatsCollector> {
+        self.store.storage_stats()
+    }
+
+    /// Sets the configuration of the execution runtime of the chains loaded from now on.
+    pub fn set_execution_runtime_config(
+        &mut self,
+        execution_runtime_config: ExecutionRuntimeConfig,
+    ) {
+        self.execution_runtime_config = execution_runtime_config;
+    }
+
     fn new(store: Store, wasm_runtime: Option<WasmRuntime>, clock: C) -> Self {
         Self {
             store: Arc::new(store),
@@ -5,17 +5,18 @@
 
 #![deny(clippy::large_futures)]
 
+mod copy;
 mod db_storage;
 
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use async_trait::async_trait;
 use dashmap::{mapref::entry::Entry, DashMap};
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        ApplicationDescription, Blob, ChainDescription, CompressedBytecode, Epoch, TimeDelta,
-        Timestamp,
+        ApplicationDescription, Blob, BlockHeaderSummary, BlockHeight, ChainDescription,
+        CompressedBytecode, Epoch, TimeDelta, Timestamp,
     },
     identifiers::{ApplicationId, BlobId, ChainId, EventId},
     vm::VmRuntime,
@@ -43,11 +44,14 @@ use serde::{Deserialize, Serialize};
 
 #[cfg(with_testing)]
 pub use crate::db_storage::TestClock;
-pub use crate::db_storage::{ChainStatesFirstAssignment, DbStorage, WallClock};
 #[cfg(with_metrics)]
 pub use crate::db_storage::{
     READ_CERTIFICATE_COUNTER, READ_CONFIRMED_BLOCK_COUNTER, WRITE_CERTIFICATE_COUNTER,
 };
+pub use crate::{
+    copy::{copy_chain, ChainCopySummary},
+    db_storage::{ChainStatesFirstAssignment, DbStorage, StorageSection, WallClock},
+};
 
 /// The default namespace to be used when none is specified
 pub const DEFAULT_NAMESPACE: &str = "table_linera";
@@ -65,9 +69,18 @@ pub trait Storage: Sized {
     /// The low-level storage implementation in use by the block exporter.
     type BlockExporterContext: Context<Extra = u32> + Clone + Send + Sync + 'static;
 
+    /// The low-level storage implementation in use by other services, e.g. the faucet.
+    type NamedContext: Context<Extra = ()> + Clone + Send + Sync + 'static;
+
     /// Returns the current wall clock time.
     fn clock(&self) -> &Self::Clock;
 
+    /// Returns a handle on the same storage whose reads may be served by read replicas
+    /// lagging behind the primary database, if `bounded_staleness` is true. By default, the
+    /// reads go to the primary database, and only the queries and the downloads of the
+    /// clients may accept bounded staleness.
+    fn with_bounded_staleness(&self, bounded_staleness: bool) -> Self;
+
     /// Loads the view of a chain state.
     ///
     /// # Notes
@@ -150,6 +163,9 @@ pub trait Storage: Sized {
     /// Tests existence of the certificate with the given hash.
     async fn contains_certificate(&self, hash: CryptoHash) -> Result<bool, ViewError>;
 
+    /// Tests existence of the certificates with the given hashes.
+    async fn contains_certificates(&self, hashes: &[CryptoHash]) -> Result<Vec<bool>, ViewError>;
+
     /// Reads the certificate with the given hash.
     async fn read_certificate(
         &self,
@@ -165,6 +181,9 @@ pub trait Storage: Sized {
     /// Reads the event with the given ID.
     async fn read_event(&self, id: EventId) -> Result<Vec<u8>, ViewError>;
 
+    /// Reads the events with the given IDs.
+    async fn read_events(&self, event_ids: &[EventId]) -> Result<Vec<Vec<u8>>, ViewError>;
+
     /// Tests existence of the event with the given ID.
     async fn contains_event(&self, id: EventId) -> Result<bool, ViewError>;
 
@@ -326,6 +345,10 @@ pub trait Storage: Sized {
         &self,
         block_exporter_id: u32,
     ) -> Result<Self::BlockExporterContext, ViewError>;
+
+    /// Returns the context in which the service with the given `name` keeps its state, e.g.
+    /// the faucet's rate limits. Each name has its own root key.
+    async fn named_context(&self, name: &str) -> Result<Self::NamedContext, ViewError>;
 }
 
 /// A description of the current Linera network to be stored in every node's database.
@@ -357,7 +380,7 @@ where
     }
 
     fn execution_runtime_config(&self) -> linera_execution::ExecutionRuntimeConfig {
-        self.execution_runtime_config
+        self.execution_runtime_config.clone()
     }
 
     fn user_contracts(&self) -> &Arc<DashMap<ApplicationId, UserContractCode>> {
@@ -412,6 +435,21 @@ where
         self.storage.contains_event(event_id).await
     }
 
+    async fn get_block_hashes(
+        &self,
+        range: Range<BlockHeight>,
+    ) -> Result<Vec<CryptoHash>, ViewError> {
+        let chain = self.storage.load_chain(self.chain_id).await?;
+        let start = usize::try_from(range.start).unwrap_or(usize::MAX);
+        let end = usize::try_from(range.end).unwrap_or(usize::MAX);
+        chain.confirmed_log.read(start..end).await
+    }
+
+    async fn get_block_header(&self, hash: CryptoHash) -> Result<BlockHeaderSummary, ViewError> {
+        let block = self.storage.read_confirmed_block(hash).await?;
+        Ok(block.block().header.summary())
+    }
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
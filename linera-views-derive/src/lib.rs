@@ -5,7 +5,7 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, ToTokens as _};
 use syn::{parse_macro_input, parse_quote, ItemStruct, Type, TypePath};
 
 #[derive(Debug, deluxe::ParseAttributes)]
@@ -24,9 +24,13 @@ struct ContextAndConstraints<'a> {
 
 impl<'a> ContextAndConstraints<'a> {
     fn get(item: &'a syn::ItemStruct) -> Self {
+        Self::from_item(item, &item.generics)
+    }
+
+    fn from_item<T: deluxe::HasAttributes>(item: &T, generics: &'a syn::Generics) -> Self {
         let attrs: StructAttrs = deluxe::parse_attributes(item).unwrap();
 
-        let (impl_generics, type_generics, maybe_where_clause) = item.generics.split_for_impl();
+        let (impl_generics, type_generics, maybe_where_clause) = generics.split_for_impl();
         let input_constraints = maybe_where_clause
             .map(|w| w.predicates.iter())
             .into_iter()
@@ -36,8 +40,7 @@ impl<'a> ContextAndConstraints<'a> {
         let (context, context_constraints) = if let Some(context) = attrs.context {
             (context, vec![])
         } else {
-            let first_type_param = item
-                .generics
+            let first_type_param = generics
                 .type_params()
                 .map(|param| &param.ident)
                 .next()
@@ -64,14 +67,76 @@ impl<'a> ContextAndConstraints<'a> {
     }
 }
 
-fn get_extended_entry(e: Type) -> TokenStream2 {
-    let syn::Type::Path(typepath) = e else {
-        panic!("The type should be a path");
+/// Returns the path to use for calling the associated items of the view type `e`.
+fn get_extended_entry(e: Type, context: &Type) -> TokenStream2 {
+    match e {
+        syn::Type::Path(typepath)
+            if typepath.qself.is_none() && typepath.path.segments.len() == 1 =>
+        {
+            let path_segment = typepath.path.segments.into_iter().next().unwrap();
+            let ident = path_segment.ident;
+            let arguments = path_segment.arguments;
+            quote! { #ident :: #arguments }
+        }
+        // The first segment of other types does not name the view type.
+        e => quote! { <#e as linera_views::views::View<#context>> },
+    }
+}
+
+/// Returns an error if `input` is not a struct that the derive macros support.
+fn check_struct(input: &ItemStruct) -> Result<(), syn::Error> {
+    let syn::Fields::Named(fields) = &input.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "views can only be derived for structs with named fields",
+        ));
     };
-    let path_segment = typepath.path.segments.into_iter().next().unwrap();
-    let ident = path_segment.ident;
-    let arguments = path_segment.arguments;
-    quote! { #ident :: #arguments }
+    if fields.named.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "views can only be derived for structs with at least one field",
+        ));
+    }
+    check_context(&input.attrs, &input.generics, &input.ident)
+}
+
+/// Returns an error if the context of the view can be neither read from a
+/// `#[view(context = ...)]` attribute nor taken as the first type parameter.
+fn check_context(
+    attrs: &[syn::Attribute],
+    generics: &syn::Generics,
+    ident: &syn::Ident,
+) -> Result<(), syn::Error> {
+    let has_context_attribute = attrs.iter().any(|attr| attr.path().is_ident("view"));
+    if !has_context_attribute && generics.type_params().next().is_none() {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "the context of the view must be given as its first type parameter \
+            or with `#[view(context = ...)]`",
+        ));
+    }
+    Ok(())
+}
+
+/// Parses the input of a derive macro that only supports structs.
+fn parse_struct(input: TokenStream, derive: &str) -> Result<ItemStruct, syn::Error> {
+    match syn::parse::<syn::Item>(input)? {
+        syn::Item::Struct(input) => {
+            check_struct(&input)?;
+            Ok(input)
+        }
+        syn::Item::Enum(input) => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "`{derive}` cannot be derived for enums: derive `View` for the enum \
+                and use it in an `EnumView` field of a struct"
+            ),
+        )),
+        item => Err(syn::Error::new_spanned(
+            item,
+            format!("`{derive}` can only be derived for structs"),
+        )),
+    }
 }
 
 fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
@@ -90,6 +155,7 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
     let mut test_flush_quotes = Vec::new();
     let mut clear_quotes = Vec::new();
     let mut has_pending_changes_quotes = Vec::new();
+    let mut pre_save_quotes = Vec::new();
     let mut num_init_keys_quotes = Vec::new();
     let mut pre_load_keys_quotes = Vec::new();
     let mut post_load_keys_quotes = Vec::new();
@@ -99,7 +165,7 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
         let ty = &e.ty;
         let test_flush_ident = format_ident!("deleted{}", idx);
         let idx_lit = syn::LitInt::new(&idx.to_string(), Span::call_site());
-        let g = get_extended_entry(e.ty.clone());
+        let g = get_extended_entry(e.ty.clone(), &context);
         name_quotes.push(quote! { #name });
         field_constraints.push(quote! { #ty: linera_views::views::View<#context> });
         rollback_quotes.push(quote! { self.#name.rollback(); });
@@ -111,6 +177,7 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
                 return true;
             }
         });
+        pre_save_quotes.push(quote! { self.#name.pre_save().await?; });
         num_init_keys_quotes.push(quote! { #g :: NUM_INIT_KEYS });
         pre_load_keys_quotes.push(quote! {
             let index = #idx_lit;
@@ -197,6 +264,12 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
                 false
             }
 
+            async fn pre_save(&mut self) -> Result<(), linera_views::views::ViewError> {
+                use linera_views::views::View as _;
+                #(#pre_save_quotes)*
+                Ok(())
+            }
+
             fn flush(&mut self, batch: &mut linera_views::batch::Batch) -> Result<bool, linera_views::views::ViewError> {
                 use linera_views::views::View;
                 #(#flush_quotes)*
@@ -210,6 +283,336 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
     }
 }
 
+fn generate_view_variants_code(input: syn::ItemEnum) -> Result<TokenStream2, syn::Error> {
+    check_context(&input.attrs, &input.generics, &input.ident)?;
+    let ContextAndConstraints {
+        context,
+        context_constraints,
+        input_constraints,
+        impl_generics,
+        type_generics,
+    } = ContextAndConstraints::from_item(&input, &input.generics);
+    let enum_name = &input.ident;
+    let vis = &input.vis;
+    let discriminant_name = format_ident!("{}Discriminant", enum_name);
+
+    let mut discriminant_quotes = Vec::new();
+    let mut num_init_keys_quotes = Vec::new();
+    let mut discriminant_arms = Vec::new();
+    let mut pre_load_arms = Vec::new();
+    let mut post_load_arms = Vec::new();
+    let mut rollback_arms = Vec::new();
+    let mut has_pending_changes_arms = Vec::new();
+    let mut pre_save_arms = Vec::new();
+    let mut clear_arms = Vec::new();
+    let mut flush_arms = Vec::new();
+    let mut field_constraints = Vec::new();
+    for (variant_idx, variant) in input.variants.iter().enumerate() {
+        let variant_name = &variant.ident;
+        let fields = variant_fields(variant)?;
+        let variant_lit = syn::LitInt::new(&format!("{variant_idx}u32"), Span::call_site());
+
+        let mut names = Vec::new();
+        let mut bindings = Vec::new();
+        let mut num_init_keys = Vec::new();
+        let mut pre_loads = Vec::new();
+        let mut post_loads = Vec::new();
+        let mut flushes = Vec::new();
+        let mut deleted = Vec::new();
+        for (idx, field) in fields.into_iter().enumerate() {
+            let name = field.ident.clone().unwrap();
+            let ty = &field.ty;
+            let binding = format_ident!("field{}", idx);
+            let deleted_ident = format_ident!("deleted{}", idx);
+            let idx_lit = syn::LitInt::new(&format!("{idx}u32"), Span::call_site());
+            let g = get_extended_entry(ty.clone(), &context);
+            field_constraints.push(quote! { #ty: linera_views::views::View<#context> });
+            num_init_keys.push(quote! { #g :: NUM_INIT_KEYS });
+            pre_loads.push(quote! {
+                let index = (#variant_lit, #idx_lit);
+                let base_key = context.base_key().derive_tag_key(linera_views::views::MIN_VIEW_TAG, &index)?;
+                keys.extend(#g :: pre_load(&context.clone_with_base_key(base_key))?);
+            });
+            post_loads.push(quote! {
+                let index = (#variant_lit, #idx_lit);
+                let pos_next = pos + #g :: NUM_INIT_KEYS;
+                let base_key = context.base_key().derive_tag_key(linera_views::views::MIN_VIEW_TAG, &index)?;
+                let #binding = #g :: post_load(context.clone_with_base_key(base_key), &values[pos..pos_next])?;
+                pos = pos_next;
+            });
+            flushes.push(quote! { let #deleted_ident = #binding.flush(batch)?; });
+            deleted.push(quote! { #deleted_ident });
+            names.push(name);
+            bindings.push(binding);
+        }
+        if bindings.is_empty() {
+            num_init_keys.push(quote! { 0 });
+            deleted.push(quote! { true });
+        } else {
+            post_loads.insert(0, quote! { let mut pos = 0; });
+        }
+
+        let pattern = quote! { Self::#variant_name { #(#names: #bindings),* } };
+        discriminant_quotes.push(quote! { #discriminant_name::#variant_name });
+        num_init_keys_quotes.push(quote! { #(#num_init_keys)+* });
+        discriminant_arms
+            .push(quote! { Self::#variant_name { .. } => #discriminant_name::#variant_name, });
+        pre_load_arms.push(quote! { #discriminant_name::#variant_name => { #(#pre_loads)* } });
+        post_load_arms.push(quote! {
+            #discriminant_name::#variant_name => {
+                #(#post_loads)*
+                Ok(#pattern)
+            }
+        });
+        rollback_arms.push(quote! { #pattern => { #(#bindings.rollback();)* } });
+        has_pending_changes_arms.push(quote! {
+            #pattern => {
+                #(
+                    if #bindings.has_pending_changes().await {
+                        return true;
+                    }
+                )*
+                false
+            }
+        });
+        pre_save_arms.push(quote! { #pattern => { #(#bindings.pre_save().await?;)* } });
+        clear_arms.push(quote! { #pattern => { #(#bindings.clear();)* } });
+        flush_arms.push(quote! {
+            #pattern => {
+                #(#flushes)*
+                Ok( #(#deleted)&&* )
+            }
+        });
+    }
+
+    if field_constraints.is_empty() {
+        return Err(syn::Error::new_spanned(
+            enum_name,
+            "a view enum must have at least one variant with views",
+        ));
+    }
+
+    Ok(quote! {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #discriminant_name {
+            #(#discriminant_quotes,)*
+        }
+
+        impl #impl_generics linera_views::enum_view::ViewVariants<#context> for #enum_name #type_generics
+        where
+            #(#input_constraints,)*
+            #(#context_constraints,)*
+            #(#field_constraints,)*
+            Self: Send + Sync,
+        {
+            type Discriminant = #discriminant_name;
+
+            const DISCRIMINANTS: &'static [Self::Discriminant] = &[#(#discriminant_quotes),*];
+
+            const NUM_INIT_KEYS: &'static [usize] = &[#(#num_init_keys_quotes),*];
+
+            fn discriminant(&self) -> Self::Discriminant {
+                match self {
+                    #(#discriminant_arms)*
+                }
+            }
+
+            fn pre_load(context: &#context, discriminant: Self::Discriminant) -> Result<Vec<Vec<u8>>, linera_views::views::ViewError> {
+                use linera_views::context::Context as _;
+                let mut keys = Vec::new();
+                match discriminant {
+                    #(#pre_load_arms)*
+                }
+                Ok(keys)
+            }
+
+            fn post_load(context: #context, discriminant: Self::Discriminant, values: &[Option<Vec<u8>>]) -> Result<Self, linera_views::views::ViewError> {
+                use linera_views::context::Context as _;
+                match discriminant {
+                    #(#post_load_arms)*
+                }
+            }
+
+            fn rollback(&mut self) {
+                use linera_views::views::View as _;
+                match self {
+                    #(#rollback_arms)*
+                }
+            }
+
+            async fn has_pending_changes(&self) -> bool {
+                use linera_views::views::View as _;
+                match self {
+                    #(#has_pending_changes_arms)*
+                }
+            }
+
+            async fn pre_save(&mut self) -> Result<(), linera_views::views::ViewError> {
+                use linera_views::views::View as _;
+                match self {
+                    #(#pre_save_arms)*
+                }
+                Ok(())
+            }
+
+            fn clear(&mut self) {
+                use linera_views::views::View as _;
+                match self {
+                    #(#clear_arms)*
+                }
+            }
+
+            fn flush(&mut self, batch: &mut linera_views::batch::Batch) -> Result<bool, linera_views::views::ViewError> {
+                use linera_views::views::View as _;
+                match self {
+                    #(#flush_arms)*
+                }
+            }
+        }
+    })
+}
+
+/// Returns the fields of a variant of a view enum.
+fn variant_fields(variant: &syn::Variant) -> Result<Vec<&syn::Field>, syn::Error> {
+    match &variant.fields {
+        syn::Fields::Named(fields) => Ok(fields.named.iter().collect()),
+        syn::Fields::Unit => Ok(Vec::new()),
+        syn::Fields::Unnamed(_) => Err(syn::Error::new_spanned(
+            variant,
+            "the variants of a view enum must have named fields or none",
+        )),
+    }
+}
+
+/// Returns the pattern matching all the fields of a variant of a view enum, with the
+/// bindings of the fields, and the types of the fields.
+fn variant_pattern(
+    variant: &syn::Variant,
+) -> Result<(TokenStream2, Vec<syn::Ident>, Vec<Type>), syn::Error> {
+    let variant_name = &variant.ident;
+    let fields = variant_fields(variant)?;
+    let names = fields
+        .iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<_>>();
+    let bindings = (0..fields.len())
+        .map(|idx| format_ident!("field{}", idx))
+        .collect::<Vec<_>>();
+    let types = fields.iter().map(|field| field.ty.clone()).collect();
+    let pattern = quote! { Self::#variant_name { #(#names: #bindings),* } };
+    Ok((pattern, bindings, types))
+}
+
+fn generate_hashable_variants_code(input: &syn::ItemEnum) -> Result<TokenStream2, syn::Error> {
+    let ContextAndConstraints {
+        context,
+        context_constraints,
+        input_constraints,
+        impl_generics,
+        type_generics,
+    } = ContextAndConstraints::from_item(input, &input.generics);
+    let enum_name = &input.ident;
+
+    let mut hash_mut_arms = Vec::new();
+    let mut hash_arms = Vec::new();
+    let mut hash_constraints = Vec::new();
+    for variant in &input.variants {
+        let (pattern, bindings, types) = variant_pattern(variant)?;
+        hash_constraints.extend(
+            types
+                .iter()
+                .map(|ty| quote! { #ty: linera_views::views::HashableView<#context> }),
+        );
+        hash_mut_arms.push(quote! {
+            #pattern => {
+                #(hasher.write_all(#bindings.hash_mut().await?.as_ref())?;)*
+            }
+        });
+        hash_arms.push(quote! {
+            #pattern => {
+                #(hasher.write_all(#bindings.hash().await?.as_ref())?;)*
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics linera_views::enum_view::HashableViewVariants<#context> for #enum_name #type_generics
+        where
+            #(#input_constraints,)*
+            #(#context_constraints,)*
+            #(#hash_constraints,)*
+            Self: Send + Sync,
+        {
+            async fn hash_mut(&mut self) -> Result<linera_views::common::HasherOutput, linera_views::views::ViewError> {
+                use linera_views::views::{Hasher, HashableView};
+                use std::io::Write;
+                let mut hasher = linera_views::sha3::Sha3_256::default();
+                match self {
+                    #(#hash_mut_arms)*
+                }
+                Ok(hasher.finalize())
+            }
+
+            async fn hash(&self) -> Result<linera_views::common::HasherOutput, linera_views::views::ViewError> {
+                use linera_views::views::{Hasher, HashableView};
+                use std::io::Write;
+                let mut hasher = linera_views::sha3::Sha3_256::default();
+                match self {
+                    #(#hash_arms)*
+                }
+                Ok(hasher.finalize())
+            }
+        }
+    })
+}
+
+fn generate_clonable_variants_code(input: &syn::ItemEnum) -> Result<TokenStream2, syn::Error> {
+    check_context(&input.attrs, &input.generics, &input.ident)?;
+    let ContextAndConstraints {
+        context,
+        context_constraints,
+        input_constraints,
+        impl_generics,
+        type_generics,
+    } = ContextAndConstraints::from_item(input, &input.generics);
+    let enum_name = &input.ident;
+
+    let mut clone_arms = Vec::new();
+    let mut clone_constraints = Vec::new();
+    for variant in &input.variants {
+        let variant_name = &variant.ident;
+        let (pattern, bindings, types) = variant_pattern(variant)?;
+        let names = variant_fields(variant)?
+            .into_iter()
+            .map(|field| field.ident.clone().unwrap());
+        clone_constraints.extend(
+            types
+                .iter()
+                .map(|ty| quote! { #ty: linera_views::views::ClonableView<#context> }),
+        );
+        clone_arms.push(quote! {
+            #pattern => Ok(Self::#variant_name { #(#names: #bindings.clone_unchecked()?),* }),
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics linera_views::enum_view::ClonableViewVariants<#context> for #enum_name #type_generics
+        where
+            #(#input_constraints,)*
+            #(#context_constraints,)*
+            #(#clone_constraints,)*
+            Self: Send + Sync,
+        {
+            fn clone_unchecked(&mut self) -> Result<Self, linera_views::views::ViewError> {
+                use linera_views::views::ClonableView as _;
+                match self {
+                    #(#clone_arms)*
+                }
+            }
+        }
+    })
+}
+
 fn generate_root_view_code(input: ItemStruct) -> TokenStream2 {
     let ContextAndConstraints {
         context,
@@ -219,6 +622,7 @@ fn generate_root_view_code(input: ItemStruct) -> TokenStream2 {
         type_generics,
     } = ContextAndConstraints::get(&input);
     let struct_name = &input.ident;
+    let field_constraints = generic_field_constraints(&input, &context);
     let mut flushes = Vec::new();
     let mut deletes = Vec::new();
     for e in &input.fields {
@@ -245,11 +649,13 @@ fn generate_root_view_code(input: ItemStruct) -> TokenStream2 {
         where
             #(#input_constraints,)*
             #(#context_constraints,)*
+            #(#field_constraints,)*
             Self: Send + Sync,
         {
             async fn save(&mut self) -> Result<(), linera_views::views::ViewError> {
                 use linera_views::{context::Context, batch::Batch, store::WritableKeyValueStore as _, views::View};
                 #increment_counter
+                self.pre_save().await?;
                 let mut batch = Batch::new();
                 #(#flushes)*
                 if !batch.is_empty() {
@@ -261,6 +667,34 @@ fn generate_root_view_code(input: ItemStruct) -> TokenStream2 {
     }
 }
 
+/// Returns the constraints for the fields whose type depends on type parameters other than
+/// the context to be views.
+fn generic_field_constraints(input: &ItemStruct, context: &Type) -> Vec<syn::WherePredicate> {
+    let type_params = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .filter(|ident| *context != parse_quote! { #ident })
+        .collect::<Vec<_>>();
+    input
+        .fields
+        .iter()
+        .filter(|field| mentions_any(field.ty.to_token_stream(), &type_params))
+        .map(|field| {
+            let ty = &field.ty;
+            parse_quote! { #ty: linera_views::views::View<#context> }
+        })
+        .collect()
+}
+
+fn mentions_any(tokens: TokenStream2, idents: &[&syn::Ident]) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        proc_macro2::TokenTree::Ident(ident) => idents.contains(&&ident),
+        proc_macro2::TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
 fn hash_view_constraints(input: &ItemStruct, context: &syn::Type) -> Vec<syn::WherePredicate> {
     input
         .fields
@@ -414,21 +848,45 @@ fn generate_clonable_view_code(input: ItemStruct) -> TokenStream2 {
 
 #[proc_macro_derive(View, attributes(view))]
 pub fn derive_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
-    generate_view_code(input, false).into()
+    let result = match parse_macro_input!(input as syn::Item) {
+        syn::Item::Struct(input) => check_struct(&input).map(|()| generate_view_code(input, false)),
+        syn::Item::Enum(input) => generate_view_variants_code(input),
+        item => Err(syn::Error::new_spanned(
+            item,
+            "`View` can only be derived for structs and enums",
+        )),
+    };
+    result.unwrap_or_else(syn::Error::into_compile_error).into()
 }
 
 #[proc_macro_derive(HashableView, attributes(view))]
 pub fn derive_hash_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
-    let mut stream = generate_view_code(input.clone(), false);
-    stream.extend(generate_hash_view_code(input));
-    stream.into()
+    let result = match parse_macro_input!(input as syn::Item) {
+        syn::Item::Struct(input) => check_struct(&input).map(|()| {
+            let mut stream = generate_view_code(input.clone(), false);
+            stream.extend(generate_hash_view_code(input));
+            stream
+        }),
+        syn::Item::Enum(input) => {
+            generate_view_variants_code(input.clone()).and_then(|mut stream| {
+                stream.extend(generate_hashable_variants_code(&input)?);
+                Ok(stream)
+            })
+        }
+        item => Err(syn::Error::new_spanned(
+            item,
+            "`HashableView` can only be derived for structs and enums",
+        )),
+    };
+    result.unwrap_or_else(syn::Error::into_compile_error).into()
 }
 
 #[proc_macro_derive(RootView, attributes(view))]
 pub fn derive_root_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
+    let input = match parse_struct(input, "RootView") {
+        Ok(input) => input,
+        Err(error) => return error.into_compile_error().into(),
+    };
     let mut stream = generate_view_code(input.clone(), true);
     stream.extend(generate_root_view_code(input));
     stream.into()
@@ -436,7 +894,10 @@ pub fn derive_root_view(input: TokenStream) -> TokenStream {
 
 #[proc_macro_derive(CryptoHashView, attributes(view))]
 pub fn derive_crypto_hash_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
+    let input = match parse_struct(input, "CryptoHashView") {
+        Ok(input) => input,
+        Err(error) => return error.into_compile_error().into(),
+    };
     let mut stream = generate_view_code(input.clone(), false);
     stream.extend(generate_hash_view_code(input.clone()));
     stream.extend(generate_crypto_hash_code(input));
@@ -445,7 +906,10 @@ pub fn derive_crypto_hash_view(input: TokenStream) -> TokenStream {
 
 #[proc_macro_derive(CryptoHashRootView, attributes(view))]
 pub fn derive_crypto_hash_root_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
+    let input = match parse_struct(input, "CryptoHashRootView") {
+        Ok(input) => input,
+        Err(error) => return error.into_compile_error().into(),
+    };
     let mut stream = generate_view_code(input.clone(), true);
     stream.extend(generate_root_view_code(input.clone()));
     stream.extend(generate_hash_view_code(input.clone()));
@@ -456,7 +920,10 @@ pub fn derive_crypto_hash_root_view(input: TokenStream) -> TokenStream {
 #[proc_macro_derive(HashableRootView, attributes(view))]
 #[cfg(test)]
 pub fn derive_hashable_root_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
+    let input = match parse_struct(input, "HashableRootView") {
+        Ok(input) => input,
+        Err(error) => return error.into_compile_error().into(),
+    };
     let mut stream = generate_view_code(input.clone(), true);
     stream.extend(generate_root_view_code(input.clone()));
     stream.extend(generate_hash_view_code(input));
@@ -465,8 +932,17 @@ pub fn derive_hashable_root_view(input: TokenStream) -> TokenStream {
 
 #[proc_macro_derive(ClonableView, attributes(view))]
 pub fn derive_clonable_view(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as ItemStruct);
-    generate_clonable_view_code(input).into()
+    let result = match parse_macro_input!(input as syn::Item) {
+        syn::Item::Struct(input) => {
+            check_struct(&input).map(|()| generate_clonable_view_code(input))
+        }
+        syn::Item::Enum(input) => generate_clonable_variants_code(&input),
+        item => Err(syn::Error::new_spanned(
+            item,
+            "`ClonableView` can only be derived for structs and enums",
+        )),
+    };
+    result.unwrap_or_else(syn::Error::into_compile_error).into()
 }
 
 #[cfg(test)]
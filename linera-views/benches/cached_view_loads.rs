@@ -0,0 +1,136 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+#[cfg(with_dynamodb)]
+use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
+#[cfg(with_scylladb)]
+use linera_views::scylla_db::ScyllaDbStore;
+use linera_views::{
+    context::ViewContext,
+    lru_caching::{
+        CachePolicyKind, LruCachingConfig, LruCachingMemoryStore, StorageCacheConfig,
+        DEFAULT_STORAGE_CACHE_CONFIG,
+    },
+    map_view::MapView,
+    random::generate_test_namespace,
+    store::{AdminKeyValueStore as _, TestKeyValueStore},
+    views::{CryptoHashRootView, RootView, View},
+};
+use tokio::runtime::Runtime;
+
+/// The number of entries stored in the map.
+const N_ENTRIES: u64 = 200;
+
+/// The number of entries read after each load of the view.
+const N_READS: u64 = 50;
+
+/// The root key of the view, as the one of a chain state.
+const ROOT_KEY: &[u8] = &[0, 1, 2, 3];
+
+#[derive(CryptoHashRootView)]
+pub struct MapStateView<C> {
+    pub map: MapView<C, u64, u64>,
+}
+
+/// Loads the view repeatedly from new clones of the store, as `DbStorage::load_chain` does,
+/// and reads its indices and some of its entries.
+async fn performance_view_loads<S, C>(
+    storage_cache_config: StorageCacheConfig,
+    iterations: u64,
+) -> Duration
+where
+    S: TestKeyValueStore<Config = LruCachingConfig<C>> + Clone + Sync + 'static,
+    S::Error: Send + Sync,
+{
+    let mut config = S::new_test_config().await.unwrap();
+    config.storage_cache_config = storage_cache_config;
+    let namespace = generate_test_namespace();
+    let root_store = S::recreate_and_connect(&config, &namespace).await.unwrap();
+    let store = root_store.clone_with_root_key(ROOT_KEY).unwrap();
+    let context = ViewContext::<(), S>::create_root_context(store, ())
+        .await
+        .unwrap();
+    let mut view = MapStateView::load(context).await.unwrap();
+    for index in 0..N_ENTRIES {
+        view.map.insert(&index, index).unwrap();
+    }
+    view.save().await.unwrap();
+
+    let measurement = Instant::now();
+    for _ in 0..iterations {
+        let store = root_store.clone_with_root_key(ROOT_KEY).unwrap();
+        let context = ViewContext::<(), S>::create_root_context(store, ())
+            .await
+            .unwrap();
+        let view = MapStateView::load(context).await.unwrap();
+        black_box(view.map.indices().await.unwrap());
+        for index in 0..N_READS {
+            black_box(view.map.get(&index).await.unwrap());
+        }
+    }
+    measurement.elapsed()
+}
+
+fn bench_cached_view_loads(criterion: &mut Criterion) {
+    let uncached = StorageCacheConfig {
+        max_cache_entries: 0,
+        ..DEFAULT_STORAGE_CACHE_CONFIG
+    };
+    let mut configs = vec![("uncached".to_string(), uncached)];
+    for policy in [
+        CachePolicyKind::Lru,
+        CachePolicyKind::Lfu,
+        CachePolicyKind::Segmented,
+    ] {
+        let config = StorageCacheConfig {
+            cache_policy: policy,
+            ..DEFAULT_STORAGE_CACHE_CONFIG
+        };
+        configs.push((policy.to_string(), config));
+    }
+
+    for (name, config) in configs {
+        criterion.bench_function(format!("memory_view_loads_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    performance_view_loads::<LruCachingMemoryStore, _>(config.clone(), iterations)
+                })
+        });
+
+        #[cfg(with_rocksdb)]
+        criterion.bench_function(format!("rocksdb_view_loads_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    performance_view_loads::<RocksDbStore, _>(config.clone(), iterations)
+                })
+        });
+
+        #[cfg(with_dynamodb)]
+        criterion.bench_function(format!("dynamodb_view_loads_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    performance_view_loads::<DynamoDbStore, _>(config.clone(), iterations)
+                })
+        });
+
+        #[cfg(with_scylladb)]
+        criterion.bench_function(format!("scylladb_view_loads_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    performance_view_loads::<ScyllaDbStore, _>(config.clone(), iterations)
+                })
+        });
+    }
+}
+
+criterion_group!(benches, bench_cached_view_loads);
+criterion_main!(benches);
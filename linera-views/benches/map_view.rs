@@ -0,0 +1,124 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+#[cfg(with_dynamodb)]
+use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
+#[cfg(with_scylladb)]
+use linera_views::scylla_db::ScyllaDbStore;
+use linera_views::{
+    context::ViewContext,
+    map_view::MapView,
+    memory::MemoryStore,
+    random::make_deterministic_rng,
+    store::TestKeyValueStore,
+    views::{CryptoHashRootView, RootView, View},
+};
+use rand::Rng;
+use tokio::runtime::Runtime;
+
+/// The number of entries stored in the map.
+const N_ENTRIES: u64 = 1000;
+
+/// The number of entries read by each query, as a GraphQL query for specific entries would.
+const N_READS: usize = 50;
+
+#[derive(CryptoHashRootView)]
+pub struct MapStateView<C> {
+    pub map: MapView<C, u64, u64>,
+}
+
+/// Whether the entries are read one storage query at a time or all at once.
+#[derive(Clone, Copy)]
+enum ReadMode {
+    Get,
+    MultiGet,
+}
+
+async fn performance_map_view_reads<S: TestKeyValueStore + Clone + Sync + 'static>(
+    mode: ReadMode,
+    iterations: u64,
+) -> Duration
+where
+    S::Error: Send + Sync,
+{
+    let store = S::new_test_store().await.unwrap();
+    let context = ViewContext::<(), S>::create_root_context(store, ())
+        .await
+        .unwrap();
+    let mut view = MapStateView::load(context.clone()).await.unwrap();
+    for index in 0..N_ENTRIES {
+        view.map.insert(&index, index).unwrap();
+    }
+    view.save().await.unwrap();
+
+    let mut total_time = Duration::ZERO;
+    let mut rng = make_deterministic_rng();
+    for _ in 0..iterations {
+        // Reading from a freshly loaded view makes every read go to storage.
+        let view = MapStateView::load(context.clone()).await.unwrap();
+        let indices = (0..N_READS)
+            .map(|_| rng.gen_range(0..2 * N_ENTRIES))
+            .collect::<Vec<_>>();
+        let measurement = Instant::now();
+        match mode {
+            ReadMode::Get => {
+                for index in &indices {
+                    black_box(view.map.get(index).await.unwrap());
+                }
+            }
+            ReadMode::MultiGet => {
+                black_box(view.map.multi_get(&indices).await.unwrap());
+            }
+        }
+        total_time += measurement.elapsed();
+    }
+
+    total_time
+}
+
+fn bench_map_view_reads(criterion: &mut Criterion) {
+    for (name, mode) in [("get", ReadMode::Get), ("multi_get", ReadMode::MultiGet)] {
+        criterion.bench_function(format!("memory_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    performance_map_view_reads::<MemoryStore>(mode, iterations).await
+                })
+        });
+
+        #[cfg(with_rocksdb)]
+        criterion.bench_function(format!("rocksdb_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    performance_map_view_reads::<RocksDbStore>(mode, iterations).await
+                })
+        });
+
+        #[cfg(with_dynamodb)]
+        criterion.bench_function(format!("dynamodb_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    performance_map_view_reads::<DynamoDbStore>(mode, iterations).await
+                })
+        });
+
+        #[cfg(with_scylladb)]
+        criterion.bench_function(format!("scylladb_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    performance_map_view_reads::<ScyllaDbStore>(mode, iterations).await
+                })
+        });
+    }
+}
+
+criterion_group!(benches, bench_map_view_reads);
+criterion_main!(benches);
@@ -3,12 +3,15 @@
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use linera_base::time::{Duration, Instant};
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
 use linera_views::{
     batch::Batch,
-    context::{Context, MemoryContext},
+    context::{Context, MemoryContext, ViewContext},
+    memory::MemoryStore,
     reentrant_collection_view::ReentrantCollectionView,
     register_view::RegisterView,
-    store::WritableKeyValueStore as _,
+    store::{TestKeyValueStore, WritableKeyValueStore as _},
     views::View,
 };
 use serde::{Deserialize, Serialize};
@@ -137,6 +140,114 @@ fn bench_load_all_entries_from_storage(criterion: &mut Criterion) {
     });
 }
 
+/// The number of entries loaded for writing, as for a contract touching the sub-views of
+/// many users in one block.
+const NUM_ENTRIES_TO_LOAD: u64 = 200;
+
+/// Benchmarks loading entries for writing one by one against
+/// [`ReentrantCollectionView::try_load_entries_mut`], which reads them from storage in a
+/// single query.
+fn bench_load_entries_mut(criterion: &mut Criterion) {
+    criterion.bench_function("load_entries_mut_memory_sequentially", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                load_entries_mut::<MemoryStore>(iterations, false).await
+            })
+    });
+
+    criterion.bench_function("load_entries_mut_memory_batched", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                load_entries_mut::<MemoryStore>(iterations, true).await
+            })
+    });
+
+    #[cfg(with_rocksdb)]
+    criterion.bench_function("load_entries_mut_rocksdb_sequentially", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                load_entries_mut::<RocksDbStore>(iterations, false).await
+            })
+    });
+
+    #[cfg(with_rocksdb)]
+    criterion.bench_function("load_entries_mut_rocksdb_batched", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                load_entries_mut::<RocksDbStore>(iterations, true).await
+            })
+    });
+}
+
+/// Measures the time to load [`NUM_ENTRIES_TO_LOAD`] stored entries for writing, either
+/// with a single call to [`ReentrantCollectionView::try_load_entries_mut`] or with one call
+/// to [`ReentrantCollectionView::try_load_entry_mut`] per entry.
+async fn load_entries_mut<S>(iterations: u64, batched: bool) -> Duration
+where
+    S: TestKeyValueStore + Clone + Send + Sync + 'static,
+    S::Error: Send + Sync,
+{
+    let store = S::new_test_store()
+        .await
+        .expect("Failed to create test store");
+    let context = ViewContext::create_root_context(store, ())
+        .await
+        .expect("Failed to create root context");
+    let indices = (0..NUM_ENTRIES_TO_LOAD).collect::<Vec<_>>();
+    let mut view: ReentrantCollectionView<_, u64, RegisterView<_, u64>> =
+        ReentrantCollectionView::load(context.clone())
+            .await
+            .expect("Failed to create `ReentrantCollectionView`");
+    for index in &indices {
+        view.try_load_entry_mut(index)
+            .await
+            .expect("Failed to create entry in `ReentrantCollectionView`")
+            .set(*index);
+    }
+    let mut batch = Batch::new();
+    view.flush(&mut batch)
+        .expect("Failed to flush populated `ReentrantCollectionView`'s contents");
+    context
+        .store()
+        .write_batch(batch)
+        .await
+        .expect("Failed to store populated `ReentrantCollectionView`'s contents");
+
+    let mut total_time = Duration::ZERO;
+    for _ in 0..iterations {
+        let mut view: ReentrantCollectionView<_, u64, RegisterView<_, u64>> =
+            ReentrantCollectionView::load(context.clone())
+                .await
+                .expect("Failed to load `ReentrantCollectionView`");
+
+        let measurement = Instant::now();
+        if batched {
+            let entries = view
+                .try_load_entries_mut(&indices)
+                .await
+                .expect("Failed to load entries from `ReentrantCollectionView`");
+            black_box(entries);
+        } else {
+            let mut entries = Vec::with_capacity(indices.len());
+            for index in &indices {
+                entries.push(
+                    view.try_load_entry_mut(index)
+                        .await
+                        .expect("Failed to load entry from `ReentrantCollectionView`"),
+                );
+            }
+            black_box(entries);
+        }
+        total_time += measurement.elapsed();
+    }
+
+    total_time
+}
+
 /// A helper type that simulates an index type that has a non-trivial cost to
 /// serialize/deserialize.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -219,6 +330,7 @@ async fn create_and_store_populated_reentrant_collection_view(
 criterion_group!(
     benches,
     bench_load_all_entries_already_in_memory,
-    bench_load_all_entries_from_storage
+    bench_load_all_entries_from_storage,
+    bench_load_entries_mut
 );
 criterion_main!(benches);
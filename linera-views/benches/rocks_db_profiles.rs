@@ -0,0 +1,151 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares the tuning profiles of RocksDB on the store benchmarks.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use linera_views::{
+    random::generate_test_namespace,
+    rocks_db::{RocksDbColumnFamily, RocksDbProfile, RocksDbStore, RocksDbTuning},
+    store::{AdminKeyValueStore as _, TestKeyValueStore as _},
+    test_utils::performance,
+};
+use tokio::runtime::Runtime;
+
+/// The tunings to compare, with their names.
+fn tunings() -> Vec<(&'static str, RocksDbTuning)> {
+    vec![
+        ("mixed", RocksDbTuning::new(RocksDbProfile::Mixed)),
+        (
+            "point_lookup",
+            RocksDbTuning::new(RocksDbProfile::PointLookup),
+        ),
+        (
+            "write_heavy",
+            RocksDbTuning::new(RocksDbProfile::WriteHeavy),
+        ),
+        (
+            "write_heavy_column_families",
+            RocksDbTuning {
+                // The benchmarks use keys starting with 0, followed by bytes below 4.
+                column_families: (0..4)
+                    .map(|byte| RocksDbColumnFamily::new(format!("family_{byte}"), vec![0, byte]))
+                    .collect(),
+                ..RocksDbTuning::new(RocksDbProfile::WriteHeavy)
+            },
+        ),
+    ]
+}
+
+async fn new_store(tuning: RocksDbTuning) -> RocksDbStore {
+    let config = RocksDbStore::new_test_config()
+        .await
+        .expect("Failed to create the RocksDB config")
+        .with_tuning(tuning);
+    RocksDbStore::recreate_and_connect(&config, &generate_test_namespace())
+        .await
+        .expect("Failed to create the RocksDB store")
+}
+
+fn bench_contains_key(criterion: &mut Criterion) {
+    for (name, tuning) in tunings() {
+        criterion.bench_function(&format!("rocksdb_{name}_contains_key"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    let tuning = tuning.clone();
+                    async move {
+                        let store = new_store(tuning).await;
+                        performance::contains_key_with_store(&store, iterations, black_box).await
+                    }
+                })
+        });
+    }
+}
+
+fn bench_find_key_values_by_prefix(criterion: &mut Criterion) {
+    for (name, tuning) in tunings() {
+        criterion.bench_function(
+            &format!("rocksdb_{name}_find_key_values_by_prefix"),
+            |bencher| {
+                bencher
+                    .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                    .iter_custom(|iterations| {
+                        let tuning = tuning.clone();
+                        async move {
+                            let store = new_store(tuning).await;
+                            performance::find_key_values_by_prefix_with_store(
+                                &store, iterations, black_box,
+                            )
+                            .await
+                        }
+                    })
+            },
+        );
+    }
+}
+
+fn bench_read_value_bytes(criterion: &mut Criterion) {
+    for (name, tuning) in tunings() {
+        criterion.bench_function(&format!("rocksdb_{name}_read_value_bytes"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    let tuning = tuning.clone();
+                    async move {
+                        let store = new_store(tuning).await;
+                        performance::read_value_bytes_with_store(&store, iterations, black_box)
+                            .await
+                    }
+                })
+        });
+    }
+}
+
+fn bench_read_multi_values_bytes(criterion: &mut Criterion) {
+    for (name, tuning) in tunings() {
+        criterion.bench_function(
+            &format!("rocksdb_{name}_read_multi_values_bytes"),
+            |bencher| {
+                bencher
+                    .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                    .iter_custom(|iterations| {
+                        let tuning = tuning.clone();
+                        async move {
+                            let store = new_store(tuning).await;
+                            performance::read_multi_values_bytes_with_store(
+                                &store, iterations, black_box,
+                            )
+                            .await
+                        }
+                    })
+            },
+        );
+    }
+}
+
+fn bench_write_batch(criterion: &mut Criterion) {
+    for (name, tuning) in tunings() {
+        criterion.bench_function(&format!("rocksdb_{name}_write_batch"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| {
+                    let tuning = tuning.clone();
+                    async move {
+                        let store = new_store(tuning).await;
+                        performance::write_batch_with_store(&store, iterations).await
+                    }
+                })
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_contains_key,
+    bench_find_key_values_by_prefix,
+    bench_read_value_bytes,
+    bench_read_multi_values_bytes,
+    bench_write_batch
+);
+criterion_main!(benches);
@@ -8,7 +8,7 @@ use linera_views::dynamo_db::DynamoDbStore;
 use linera_views::rocks_db::RocksDbStore;
 #[cfg(with_scylladb)]
 use linera_views::scylla_db::ScyllaDbStore;
-use linera_views::{memory::MemoryStore, test_utils::performance};
+use linera_views::{compression::CompressedStore, memory::MemoryStore, test_utils::performance};
 use tokio::runtime::Runtime;
 
 fn bench_contains_key(criterion: &mut Criterion) {
@@ -20,6 +20,15 @@ fn bench_contains_key(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function("store_memory_compressed_contains_key", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                performance::contains_key::<CompressedStore<MemoryStore>, _>(iterations, black_box)
+                    .await
+            })
+    });
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_contains_key", |bencher| {
         bencher
@@ -57,6 +66,15 @@ fn bench_contains_keys(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function("store_memory_compressed_contains_keys", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                performance::contains_keys::<CompressedStore<MemoryStore>, _>(iterations, black_box)
+                    .await
+            })
+    });
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_contains_keys", |bencher| {
         bencher
@@ -94,6 +112,17 @@ fn bench_find_keys_by_prefix(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function("store_memory_compressed_find_keys_by_prefix", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                performance::find_keys_by_prefix::<CompressedStore<MemoryStore>, _>(
+                    iterations, black_box,
+                )
+                .await
+            })
+    });
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_find_keys_by_prefix", |bencher| {
         bencher
@@ -132,6 +161,20 @@ fn bench_find_key_values_by_prefix(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function(
+        "store_memory_compressed_find_key_values_by_prefix",
+        |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    performance::find_key_values_by_prefix::<CompressedStore<MemoryStore>, _>(
+                        iterations, black_box,
+                    )
+                    .await
+                })
+        },
+    );
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_find_key_values_by_prefix", |bencher| {
         bencher
@@ -172,6 +215,17 @@ fn bench_read_value_bytes(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function("store_memory_compressed_read_value_bytes", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                performance::read_value_bytes::<CompressedStore<MemoryStore>, _>(
+                    iterations, black_box,
+                )
+                .await
+            })
+    });
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_read_value_bytes", |bencher| {
         bencher
@@ -209,6 +263,20 @@ fn bench_read_multi_values_bytes(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function(
+        "store_memory_compressed_read_multi_values_bytes",
+        |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    performance::read_multi_values_bytes::<CompressedStore<MemoryStore>, _>(
+                        iterations, black_box,
+                    )
+                    .await
+                })
+        },
+    );
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_read_multi_values_bytes", |bencher| {
         bencher
@@ -248,6 +316,14 @@ fn bench_write_batch(criterion: &mut Criterion) {
             })
     });
 
+    criterion.bench_function("store_memory_compressed_write_batch", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                performance::write_batch::<CompressedStore<MemoryStore>>(iterations).await
+            })
+    });
+
     #[cfg(with_rocksdb)]
     criterion.bench_function("store_rocksdb_write_batch", |bencher| {
         bencher
@@ -0,0 +1,606 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adds transparent compression of the values to a given store.
+//!
+//! Compressed values start with a header made of a magic number and a format byte. Other
+//! values are stored as they are, unless they start with the magic number, in which case
+//! they get a header telling that they are not compressed. Values below the size threshold,
+//! and values that do not get smaller, are not compressed.
+//!
+//! Values without the magic number are read as they are, so the data written before
+//! compression was enabled remains readable, and compression can be enabled or disabled,
+//! and its threshold and level changed, at any time.
+
+use std::{fmt, str::FromStr};
+
+use linera_base::time::Duration;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueStore;
+use crate::{
+    batch::{Batch, WriteOperation},
+    store::{
+        AdminKeyValueStore, KeyIterable, KeyValueIterable, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
+};
+
+/// The size from which values are compressed by default, in bytes.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 256;
+
+/// The zstd level used when none is specified.
+const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// The magic number starting the header of the encoded values. It was chosen at random,
+/// so that the values written without compression are unlikely to start with it.
+const MAGIC: [u8; 6] = [0xd4, 0x1e, 0x7a, 0x53, 0x8b, 0xc2];
+
+/// The length of the header of the encoded values: the magic number and the format byte.
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+/// The format of the values that start with the magic number but are not compressed.
+const UNCOMPRESSED_FORMAT: u8 = 0;
+
+/// The format of the values compressed with zstd.
+const ZSTD_FORMAT: u8 = 1;
+
+/// The compression of the values of a [`CompressedStore`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ValueCompression {
+    /// The zstd compression level.
+    pub level: i32,
+    /// The size from which values are compressed, in bytes.
+    pub threshold: usize,
+}
+
+impl ValueCompression {
+    /// Compresses with zstd at the given `level` the values of at least
+    /// [`DEFAULT_COMPRESSION_THRESHOLD`] bytes.
+    pub fn zstd(level: i32) -> Self {
+        ValueCompression {
+            level,
+            threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+
+    /// Sets the size from which values are compressed, in bytes.
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+/// The error returned when parsing a [`ValueCompression`] fails.
+#[derive(Error, Debug)]
+#[error("invalid compression {0:?}, expected zstd or zstd:LEVEL")]
+pub struct InvalidCompression(String);
+
+impl FromStr for ValueCompression {
+    type Err = InvalidCompression;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let level = match input.split_once(':') {
+            None if input == "zstd" => DEFAULT_ZSTD_LEVEL,
+            Some(("zstd", level)) => level
+                .parse()
+                .map_err(|_| InvalidCompression(input.to_string()))?,
+            _ => return Err(InvalidCompression(input.to_string())),
+        };
+        if !zstd::compression_level_range().contains(&level) {
+            return Err(InvalidCompression(input.to_string()));
+        }
+        Ok(ValueCompression::zstd(level))
+    }
+}
+
+impl fmt::Display for ValueCompression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "zstd:{}", self.level)
+    }
+}
+
+/// The composed error type built from the inner error type.
+#[derive(Error, Debug)]
+pub enum CompressionError<E> {
+    /// inner store error
+    #[error(transparent)]
+    InnerStoreError(#[from] E),
+
+    /// The value has a magic number but no format
+    #[error("the compression header of the value is truncated")]
+    TruncatedHeader,
+
+    /// The compression format of the value is unknown
+    #[error("unknown compression format {0}")]
+    UnknownFormat(u8),
+
+    /// The value could not be compressed or decompressed
+    #[error("failed to compress or decompress a value: {0}")]
+    Zstd(std::io::Error),
+}
+
+impl<E: KeyValueStoreError> From<bcs::Error> for CompressionError<E> {
+    fn from(error: bcs::Error) -> Self {
+        let error = E::from(error);
+        CompressionError::InnerStoreError(error)
+    }
+}
+
+impl<E: KeyValueStoreError + 'static> KeyValueStoreError for CompressionError<E> {
+    const BACKEND: &'static str = "compression";
+}
+
+/// A key-value store compressing its values.
+///
+/// It wraps a key-value store, and compresses the values written with
+/// [`WritableKeyValueStore::write_batch`] if a [`ValueCompression`] is set. The values are
+/// decompressed when read whether or not it is set.
+#[derive(Clone)]
+pub struct CompressedStore<K> {
+    /// The underlying store of the transformed store.
+    store: K,
+    /// The compression of the values, if any.
+    compression: Option<ValueCompression>,
+}
+
+impl<K> WithError for CompressedStore<K>
+where
+    K: WithError,
+    K::Error: 'static,
+{
+    type Error = CompressionError<K::Error>;
+}
+
+impl<K> ReadableKeyValueStore for CompressedStore<K>
+where
+    K: ReadableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    const MAX_KEY_SIZE: usize = K::MAX_KEY_SIZE;
+    type Keys = Vec<Vec<u8>>;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        self.store.prefetch(keys)
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let value = self.store.read_value_bytes(key).await?;
+        value.map(|value| self.decode_value(value)).transpose()
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.store.contains_key(key).await?)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        Ok(self.store.contains_keys(keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let values = self.store.read_multi_values_bytes(keys).await?;
+        values
+            .into_iter()
+            .map(|value| value.map(|value| self.decode_value(value)).transpose())
+            .collect()
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        let keys = self.store.find_keys_by_prefix(key_prefix).await?;
+        let mut owned_keys = Vec::new();
+        for key in keys.iterator() {
+            owned_keys.push(key?.to_vec());
+        }
+        Ok(owned_keys)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        let key_values = self.store.find_key_values_by_prefix(key_prefix).await?;
+        let mut decoded_key_values = Vec::new();
+        for key_value in key_values.into_iterator_owned() {
+            let (key, value) = key_value?;
+            decoded_key_values.push((key, self.decode_value(value)?));
+        }
+        Ok(decoded_key_values)
+    }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self
+            .store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await?)
+    }
+}
+
+impl<K> WritableKeyValueStore for CompressedStore<K>
+where
+    K: WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE - HEADER_LEN;
+    const SUPPORTS_TTL: bool = K::SUPPORTS_TTL;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
+        let mut batch_new = Batch::new();
+        for operation in batch.operations {
+            match operation {
+                WriteOperation::Put { key, value } => {
+                    let value = Self::encode_value(self.compression.as_ref(), value)?;
+                    batch_new.put_key_value_bytes(key, value);
+                }
+                operation => batch_new.operations.push(operation),
+            }
+        }
+        Ok(self.store.write_batch_with_ttl(batch_new, ttl).await?)
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        Ok(self.store.clear_journal().await?)
+    }
+}
+
+/// The configuration type for the `CompressedStore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressedStoreConfig<C> {
+    /// The inner configuration of the `CompressedStore`.
+    pub inner_config: C,
+    /// The compression of the values, if any.
+    #[serde(default)]
+    pub compression: Option<ValueCompression>,
+}
+
+impl<K> AdminKeyValueStore for CompressedStore<K>
+where
+    K: AdminKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    type Config = CompressedStoreConfig<K::Config>;
+
+    fn get_name() -> String {
+        format!("compressed {}", K::get_name())
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let store = K::connect(&config.inner_config, namespace).await?;
+        Ok(Self::new(store, config.compression))
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let store = self.store.clone_with_root_key(root_key)?;
+        Ok(Self::new(store, self.compression))
+    }
+
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        Self::new(store, self.compression)
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        Ok(K::list_all(&config.inner_config).await?)
+    }
+
+    async fn list_root_keys(
+        config: &Self::Config,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(K::list_root_keys(&config.inner_config, namespace).await?)
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        Ok(K::delete_all(&config.inner_config).await?)
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(K::exists(&config.inner_config, namespace).await?)
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(K::create(&config.inner_config, namespace).await?)
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(K::delete(&config.inner_config, namespace).await?)
+    }
+}
+
+#[cfg(with_testing)]
+impl<K> TestKeyValueStore for CompressedStore<K>
+where
+    K: TestKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    async fn new_test_config() -> Result<Self::Config, Self::Error> {
+        let inner_config = K::new_test_config().await?;
+        Ok(CompressedStoreConfig {
+            inner_config,
+            compression: Some(ValueCompression::zstd(DEFAULT_ZSTD_LEVEL)),
+        })
+    }
+}
+
+impl<K> CompressedStore<K>
+where
+    K: WithError,
+{
+    /// Creates a new store compressing the values of `store` with `compression`, if any.
+    pub fn new(store: K, compression: Option<ValueCompression>) -> Self {
+        CompressedStore { store, compression }
+    }
+
+    fn encode_value(
+        compression: Option<&ValueCompression>,
+        value: Vec<u8>,
+    ) -> Result<Vec<u8>, CompressionError<K::Error>> {
+        if let Some(compression) = compression.filter(|c| value.len() >= c.threshold) {
+            let compressed =
+                zstd::bulk::compress(&value, compression.level).map_err(CompressionError::Zstd)?;
+            if compressed.len() + HEADER_LEN < value.len() {
+                return Ok(Self::with_header(ZSTD_FORMAT, &compressed));
+            }
+        }
+        if value.starts_with(&MAGIC) {
+            return Ok(Self::with_header(UNCOMPRESSED_FORMAT, &value));
+        }
+        Ok(value)
+    }
+
+    fn with_header(format: u8, content: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(HEADER_LEN + content.len());
+        encoded.extend(MAGIC);
+        encoded.push(format);
+        encoded.extend(content);
+        encoded
+    }
+
+    fn decode_value(&self, mut value: Vec<u8>) -> Result<Vec<u8>, CompressionError<K::Error>> {
+        if !value.starts_with(&MAGIC) {
+            return Ok(value);
+        }
+        match value.get(MAGIC.len()) {
+            None => Err(CompressionError::TruncatedHeader),
+            Some(&UNCOMPRESSED_FORMAT) => {
+                value.drain(..HEADER_LEN);
+                Ok(value)
+            }
+            Some(&ZSTD_FORMAT) => {
+                zstd::stream::decode_all(&value[HEADER_LEN..]).map_err(CompressionError::Zstd)
+            }
+            Some(&format) => Err(CompressionError::UnknownFormat(format)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::memory::{create_test_memory_store, MemoryStore};
+
+    fn create_compressed_store(threshold: usize) -> (MemoryStore, CompressedStore<MemoryStore>) {
+        let store = create_test_memory_store();
+        let compression = ValueCompression::zstd(DEFAULT_ZSTD_LEVEL).with_threshold(threshold);
+        let compressed_store = CompressedStore::new(store.clone(), Some(compression));
+        (store, compressed_store)
+    }
+
+    #[tokio::test]
+    async fn test_compression_threshold() {
+        let threshold = 100;
+        let (store, compressed_store) = create_compressed_store(threshold);
+        let short_value = vec![7; threshold - 1];
+        let long_value = vec![7; threshold];
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"short".to_vec(), short_value.clone());
+        batch.put_key_value_bytes(b"long".to_vec(), long_value.clone());
+        batch.put_key_value_bytes(b"empty".to_vec(), Vec::new());
+        compressed_store.write_batch(batch).await.unwrap();
+
+        let stored_short = store.read_value_bytes(b"short").await.unwrap().unwrap();
+        assert_eq!(stored_short, short_value);
+        let stored_long = store.read_value_bytes(b"long").await.unwrap().unwrap();
+        assert_eq!(
+            stored_long[..HEADER_LEN],
+            [&MAGIC[..], &[ZSTD_FORMAT]].concat()
+        );
+        assert!(stored_long.len() < threshold);
+
+        assert_eq!(
+            compressed_store.read_value_bytes(b"short").await.unwrap(),
+            Some(short_value.clone())
+        );
+        assert_eq!(
+            compressed_store
+                .read_multi_values_bytes(vec![
+                    b"long".to_vec(),
+                    b"missing".to_vec(),
+                    b"empty".to_vec()
+                ])
+                .await
+                .unwrap(),
+            vec![Some(long_value.clone()), None, Some(Vec::new())]
+        );
+        assert_eq!(
+            compressed_store
+                .find_key_values_by_prefix(b"")
+                .await
+                .unwrap(),
+            vec![
+                (b"empty".to_vec(), Vec::new()),
+                (b"long".to_vec(), long_value),
+                (b"short".to_vec(), short_value),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incompressible_values_are_stored_uncompressed() {
+        let (store, compressed_store) = create_compressed_store(0);
+        let value = (0..=255).collect::<Vec<u8>>();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"key".to_vec(), value.clone());
+        compressed_store.write_batch(batch).await.unwrap();
+
+        let stored = store.read_value_bytes(b"key").await.unwrap().unwrap();
+        assert_eq!(stored, value);
+        assert_eq!(
+            compressed_store.read_value_bytes(b"key").await.unwrap(),
+            Some(value)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_values_written_before_compression_are_unchanged() {
+        let store = create_test_memory_store();
+        let values = [
+            vec![],
+            vec![0],
+            vec![1, 2, 3],
+            vec![7; 1000],
+            MAGIC[..3].to_vec(),
+        ];
+        let mut batch = Batch::new();
+        for (index, value) in values.iter().enumerate() {
+            batch.put_key_value_bytes(vec![index as u8], value.clone());
+        }
+        store.write_batch(batch).await.unwrap();
+
+        let compression = ValueCompression::zstd(DEFAULT_ZSTD_LEVEL).with_threshold(0);
+        let compressed_store = CompressedStore::new(store.clone(), Some(compression));
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(
+                compressed_store
+                    .read_value_bytes(&[index as u8])
+                    .await
+                    .unwrap(),
+                Some(value.clone())
+            );
+        }
+        let key_values = compressed_store
+            .find_key_values_by_prefix(&[])
+            .await
+            .unwrap();
+        let read_values = key_values
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect::<Vec<_>>();
+        assert_eq!(read_values, values);
+    }
+
+    #[tokio::test]
+    async fn test_values_starting_with_the_magic_number() {
+        let store = create_test_memory_store();
+        let value = [&MAGIC[..], &[ZSTD_FORMAT, 1, 2]].concat();
+        for compression in [None, Some(ValueCompression::zstd(DEFAULT_ZSTD_LEVEL))] {
+            let compressed_store = CompressedStore::new(store.clone(), compression);
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(b"key".to_vec(), value.clone());
+            compressed_store.write_batch(batch).await.unwrap();
+            let stored = store.read_value_bytes(b"key").await.unwrap().unwrap();
+            assert_eq!(
+                stored[..HEADER_LEN],
+                [&MAGIC[..], &[UNCOMPRESSED_FORMAT]].concat()
+            );
+            assert_eq!(
+                compressed_store.read_value_bytes(b"key").await.unwrap(),
+                Some(value.clone())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changing_the_threshold_keeps_values_readable() {
+        let (store, compressed_store) = create_compressed_store(0);
+        let value = vec![1; 1000];
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"key".to_vec(), value.clone());
+        compressed_store.write_batch(batch).await.unwrap();
+
+        let compression = ValueCompression::zstd(19).with_threshold(usize::MAX);
+        let other_store = CompressedStore::new(store.clone(), Some(compression));
+        assert_eq!(
+            other_store.read_value_bytes(b"key").await.unwrap(),
+            Some(value.clone())
+        );
+
+        // The compressed values can also be read once compression is disabled.
+        let uncompressed_store = CompressedStore::new(store.clone(), None);
+        assert_eq!(
+            uncompressed_store.read_value_bytes(b"key").await.unwrap(),
+            Some(value)
+        );
+
+        let mut batch = Batch::new();
+        let invalid = [&MAGIC[..], &[2, 3]].concat();
+        batch.put_key_value_bytes(b"invalid".to_vec(), invalid);
+        store.write_batch(batch).await.unwrap();
+        assert_matches!(
+            compressed_store.read_value_bytes(b"invalid").await,
+            Err(CompressionError::UnknownFormat(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_without_compression_values_are_unchanged() {
+        let store = create_test_memory_store();
+        let compressed_store = CompressedStore::new(store.clone(), None);
+        let value = vec![0; 1000];
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"key".to_vec(), value.clone());
+        compressed_store.write_batch(batch).await.unwrap();
+        assert_eq!(
+            store.read_value_bytes(b"key").await.unwrap(),
+            Some(value.clone())
+        );
+        assert_eq!(
+            compressed_store.read_value_bytes(b"key").await.unwrap(),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn test_value_compression_from_str() {
+        assert_eq!(
+            "zstd:3".parse::<ValueCompression>().unwrap(),
+            ValueCompression::zstd(3)
+        );
+        assert_eq!(
+            "zstd".parse::<ValueCompression>().unwrap(),
+            ValueCompression::zstd(DEFAULT_ZSTD_LEVEL)
+        );
+        let compression = ValueCompression::zstd(19);
+        assert_eq!(
+            compression.to_string().parse::<ValueCompression>().unwrap(),
+            compression
+        );
+        for invalid in ["lz4", "zstd:", "zstd:high", "zstd:100", "zstd:3:4"] {
+            assert!(invalid.parse::<ValueCompression>().is_err(), "{invalid}");
+        }
+    }
+}
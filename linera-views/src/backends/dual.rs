@@ -3,6 +3,7 @@
 
 //! Implements [`crate::store::KeyValueStore`] by combining two existing stores.
 
+use linera_base::time::Duration;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -84,6 +85,13 @@ where
         }
     }
 
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        match self.store_in_use {
+            StoreInUse::First => self.first_store.prefetch(keys),
+            StoreInUse::Second => self.second_store.prefetch(keys),
+        }
+    }
+
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
         let result = match self.store_in_use {
             StoreInUse::First => self
@@ -189,6 +197,26 @@ where
         };
         Ok(result)
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_keys_by_prefix_after(key_prefix, start_after, limit)
+                .await
+                .map_err(DualStoreError::First),
+            StoreInUse::Second => self
+                .second_store
+                .find_keys_by_prefix_after(key_prefix, start_after, limit)
+                .await
+                .map_err(DualStoreError::Second),
+        }
+    }
 }
 
 impl<S1, S2, A> WritableKeyValueStore for DualStore<S1, S2, A>
@@ -198,6 +226,7 @@ where
     A: Send + Sync,
 {
     const MAX_VALUE_SIZE: usize = usize::MAX;
+    const SUPPORTS_TTL: bool = S1::SUPPORTS_TTL && S2::SUPPORTS_TTL;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
         match self.store_in_use {
@@ -215,6 +244,26 @@ where
         Ok(())
     }
 
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
+        match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .write_batch_with_ttl(batch, ttl)
+                .await
+                .map_err(DualStoreError::First)?,
+            StoreInUse::Second => self
+                .second_store
+                .write_batch_with_ttl(batch, ttl)
+                .await
+                .map_err(DualStoreError::Second)?,
+        }
+        Ok(())
+    }
+
     async fn clear_journal(&self) -> Result<(), Self::Error> {
         match self.store_in_use {
             StoreInUse::First => self
@@ -278,6 +327,21 @@ where
         })
     }
 
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let first_store = self
+            .first_store
+            .clone_with_bounded_staleness(bounded_staleness);
+        let second_store = self
+            .second_store
+            .clone_with_bounded_staleness(bounded_staleness);
+        Self {
+            first_store,
+            second_store,
+            store_in_use: self.store_in_use,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
         let namespaces1 = S1::list_all(&config.first_config)
             .await
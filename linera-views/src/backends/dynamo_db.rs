@@ -2,6 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueStore`] for the DynamoDB database.
+//!
+//! Batches are written atomically with `TransactWriteItems` whenever they fit in a
+//! transaction, which is always the case for the batches written by the journaling
+//! layer. Larger batches are split into `BatchWriteItem` calls, whose unprocessed items
+//! are retried with an exponential backoff.
+//!
+//! Values that do not fit in an item are spilled: their content is written as segments in
+//! a separate partition, and the item of the key only holds a manifest of the segments.
+//! The segments of a spilled value are deleted once its item is overwritten or deleted.
+//!
+//! Values written with a TTL have an expiration time, which is the TTL attribute of the
+//! tables, enabled when a table is created or opened. DynamoDB deletes the expired items in
+//! the background, typically within a few days, so the reads skip them until then.
 
 use std::{
     collections::HashMap,
@@ -20,22 +33,31 @@ use aws_sdk_dynamodb::{
         create_table::CreateTableError,
         delete_table::DeleteTableError,
         describe_table::DescribeTableError,
+        describe_time_to_live::DescribeTimeToLiveError,
         get_item::GetItemError,
         list_tables::ListTablesError,
         query::{QueryError, QueryOutput},
         transact_write_items::TransactWriteItemsError,
+        update_time_to_live::UpdateTimeToLiveError,
     },
     primitives::Blob,
     types::{
-        AttributeDefinition, AttributeValue, Delete, KeySchemaElement, KeyType,
-        ProvisionedThroughput, Put, ScalarAttributeType, TransactWriteItem,
+        AttributeDefinition, AttributeValue, Delete, DeleteRequest, KeySchemaElement, KeyType,
+        ProvisionedThroughput, Put, PutRequest, ReturnValuesOnConditionCheckFailure,
+        ScalarAttributeType, TimeToLiveSpecification, TimeToLiveStatus, TransactWriteItem,
+        WriteRequest,
     },
     Client,
 };
 use aws_smithy_types::error::operation::BuildError;
 use futures::future::{join_all, FutureExt as _};
-use linera_base::ensure;
+use linera_base::{
+    data_types::{TimeDelta, Timestamp},
+    ensure,
+    time::{timer, Duration},
+};
 use serde::{Deserialize, Serialize};
+use sha3::Digest as _;
 use thiserror::Error;
 
 #[cfg(with_metrics)]
@@ -45,6 +67,7 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::SimpleUnorderedBatch,
     common::get_uleb128_size,
+    compression::{CompressedStore, CompressedStoreConfig, CompressionError, ValueCompression},
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
@@ -103,8 +126,22 @@ const KEY_ATTRIBUTE: &str = "item_key";
 /// The attribute name of the table value blob.
 const VALUE_ATTRIBUTE: &str = "item_value";
 
-/// The attribute for obtaining the primary key (used as a sort key) with the stored value.
-const KEY_VALUE_ATTRIBUTE: &str = "item_key, item_value";
+/// The attribute name of the number of segments of a spilled value. It is only present
+/// in the manifest items of spilled values.
+const SEGMENT_COUNT_ATTRIBUTE: &str = "item_segment_count";
+
+/// The attribute name of the expiration time of the items written with a TTL, in seconds
+/// since the Unix epoch. It is the TTL attribute of the tables.
+const EXPIRATION_ATTRIBUTE: &str = "item_expiration";
+
+/// The attribute for obtaining the primary key (used as a sort key) with the stored value,
+/// or with the manifest of the spilled value.
+const KEY_VALUE_ATTRIBUTE: &str = "item_key, item_value, item_segment_count";
+
+/// The segments of the spilled values of a root key are stored in a partition starting
+/// with `[2]`, which intersects neither `PARTITION_KEY_ROOT_KEY` nor the partitions of
+/// the root keys.
+const SPILL_PARTITION_PREFIX: &[u8] = &[2];
 
 /// TODO(#1084): The scheme below with the `MAX_VALUE_SIZE` has to be checked
 /// This is the maximum size of a raw value in DynamoDB.
@@ -153,6 +190,30 @@ const TEST_DYNAMO_DB_MAX_STREAM_QUERIES: usize = 10;
 /// See <https://docs.aws.amazon.com/amazondynamodb/latest/APIReference/API_TransactWriteItems.html>
 const MAX_TRANSACT_WRITE_ITEM_SIZE: usize = 100;
 
+/// Fundamental constants in DynamoDB: A `BatchWriteItem` has at most 25 items.
+/// See <https://docs.aws.amazon.com/amazondynamodb/latest/APIReference/API_BatchWriteItem.html>
+const MAX_BATCH_WRITE_ITEM_SIZE: usize = 25;
+
+/// Fundamental constants in DynamoDB: The maximum size of a `BatchWriteItem` is 16 MB.
+/// As for transactions, we're taking a conservative value.
+const MAX_BATCH_WRITE_ITEM_TOTAL_SIZE: usize = 16000000;
+
+/// The number of attempts at writing the items of a `BatchWriteItem` before giving up on
+/// the unprocessed ones.
+const MAX_BATCH_WRITE_ATTEMPTS: u32 = 8;
+
+/// The delay before retrying the unprocessed items of a `BatchWriteItem` for the first
+/// time. It is doubled at every new attempt.
+const BATCH_WRITE_BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// The number of attempts at writing the items of the keys of a batch in a transaction,
+/// whose conditions on the spilled values being replaced may fail if they are changed
+/// concurrently.
+const MAX_TRANSACT_WRITE_ATTEMPTS: u32 = 4;
+
+/// The size of the segments of a spilled value.
+const SEGMENT_SIZE: usize = VISIBLE_MAX_VALUE_SIZE;
+
 /// Keys of length 0 are not allowed, so we extend by having a prefix on start
 fn extend_root_key(root_key: &[u8]) -> Vec<u8> {
     let mut start_key = EMPTY_ROOT_KEY.to_vec();
@@ -160,6 +221,33 @@ fn extend_root_key(root_key: &[u8]) -> Vec<u8> {
     start_key
 }
 
+/// Returns the partition holding the segments of the spilled values of the partition
+/// `start_key`.
+fn get_spill_partition(start_key: &[u8]) -> Vec<u8> {
+    let mut partition = SPILL_PARTITION_PREFIX.to_vec();
+    partition.extend(&start_key[EMPTY_ROOT_KEY.len()..]);
+    partition
+}
+
+/// Returns the digest identifying the segments of the spilled `value` of `key`.
+///
+/// The segments of a new value thus never overwrite those of the current value, which
+/// remain valid until the manifest is replaced.
+fn get_segments_digest(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut hasher = sha3::Sha3_256::default();
+    hasher.update((key.len() as u64).to_le_bytes());
+    hasher.update(key);
+    hasher.update(value);
+    hasher.finalize().to_vec()
+}
+
+/// Returns the key of the segment of index `index` of a spilled value.
+fn get_segment_key(digest: &[u8], index: u32) -> Vec<u8> {
+    let mut key = digest.to_vec();
+    key.extend(index.to_be_bytes());
+    key
+}
+
 /// Builds the key attributes for a table item.
 ///
 /// The key is composed of two attributes that are both binary blobs. The first attribute is a
@@ -251,6 +339,33 @@ fn extract_value_owned(
     }
 }
 
+/// The location of the segments of a spilled value, as recorded in its manifest item.
+#[derive(Clone)]
+struct SpillManifest {
+    digest: Vec<u8>,
+    segment_count: u32,
+}
+
+/// Extracts the manifest from an item, if its value is spilled.
+fn extract_spill_manifest(
+    attributes: &HashMap<String, AttributeValue>,
+) -> Result<Option<SpillManifest>, DynamoDbStoreInternalError> {
+    let Some(segment_count) = attributes.get(SEGMENT_COUNT_ATTRIBUTE) else {
+        return Ok(None);
+    };
+    let AttributeValue::N(segment_count) = segment_count else {
+        return Err(DynamoDbStoreInternalError::InvalidSpillManifest);
+    };
+    let segment_count = segment_count
+        .parse()
+        .map_err(|_| DynamoDbStoreInternalError::InvalidSpillManifest)?;
+    let digest = extract_value(attributes)?.to_vec();
+    Ok(Some(SpillManifest {
+        digest,
+        segment_count,
+    }))
+}
+
 /// Extracts the key and value attributes from an item.
 fn extract_key_value(
     prefix_len: usize,
@@ -271,39 +386,329 @@ fn extract_key_value_owned(
     Ok((key, value))
 }
 
-struct TransactionBuilder {
+/// Returns the current time, in seconds since the Unix epoch, which the expiration times of
+/// the items are compared to.
+fn current_expiration_time() -> u64 {
+    Timestamp::now().micros() / 1_000_000
+}
+
+/// Whether the item was written with a TTL that has elapsed at time `now`. DynamoDB
+/// removes expired items only eventually, so they have to be skipped when reading.
+fn is_expired(attributes: &HashMap<String, AttributeValue>, now: u64) -> bool {
+    attributes
+        .get(EXPIRATION_ATTRIBUTE)
+        .and_then(|expiration| expiration.as_n().ok())
+        .and_then(|expiration| expiration.parse::<u64>().ok())
+        .is_some_and(|expiration| expiration <= now)
+}
+
+/// Extracts the manifests of the spilled values replaced by the writes of a transaction
+/// that was canceled because some of their conditions failed, by position in the
+/// transaction. Returns `None` if the transaction failed for another reason.
+fn extract_replaced_manifests(
+    error: &DynamoDbStoreInternalError,
+) -> Result<Option<Vec<(usize, Option<SpillManifest>)>>, DynamoDbStoreInternalError> {
+    let DynamoDbStoreInternalError::TransactWriteItem(error) = error else {
+        return Ok(None);
+    };
+    let Some(TransactWriteItemsError::TransactionCanceledException(canceled)) =
+        error.as_service_error()
+    else {
+        return Ok(None);
+    };
+    let mut replaced = Vec::new();
+    for (index, reason) in canceled.cancellation_reasons().iter().enumerate() {
+        if reason.code() == Some("ConditionalCheckFailed") {
+            let manifest = reason
+                .item()
+                .map(extract_spill_manifest)
+                .transpose()?
+                .flatten();
+            replaced.push((index, manifest));
+        }
+    }
+    Ok((!replaced.is_empty()).then_some(replaced))
+}
+
+/// A condition on the previous item of a write in a transaction.
+struct WriteCondition {
+    expression: String,
+    values: Option<HashMap<String, AttributeValue>>,
+}
+
+impl WriteCondition {
+    /// The condition that the previous item holds the manifest `replaced`, or no manifest
+    /// if it is `None`, so that the segments it refers to are known.
+    fn replaces(replaced: Option<&SpillManifest>) -> Self {
+        let Some(manifest) = replaced else {
+            return WriteCondition {
+                expression: format!("attribute_not_exists({SEGMENT_COUNT_ATTRIBUTE})"),
+                values: None,
+            };
+        };
+        let values = [
+            (
+                ":digest".to_owned(),
+                AttributeValue::B(Blob::new(manifest.digest.clone())),
+            ),
+            (
+                ":count".to_owned(),
+                AttributeValue::N(manifest.segment_count.to_string()),
+            ),
+        ];
+        WriteCondition {
+            expression: format!(
+                "{VALUE_ATTRIBUTE} = :digest AND {SEGMENT_COUNT_ATTRIBUTE} = :count"
+            ),
+            values: Some(values.into()),
+        }
+    }
+}
+
+/// A write of a table item.
+#[derive(Clone)]
+enum ItemWrite {
+    /// Deletes the item with the given key attributes.
+    Delete(HashMap<String, AttributeValue>),
+    /// Puts the given item.
+    Put(HashMap<String, AttributeValue>),
+}
+
+impl ItemWrite {
+    /// Returns the key attributes of the written item.
+    fn key(&self) -> HashMap<String, AttributeValue> {
+        let (ItemWrite::Delete(attributes) | ItemWrite::Put(attributes)) = self;
+        attributes
+            .iter()
+            .filter(|(name, _)| *name == PARTITION_ATTRIBUTE || *name == KEY_ATTRIBUTE)
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    fn into_transact_write_item(
+        self,
+        table_name: &str,
+        condition: Option<WriteCondition>,
+    ) -> Result<TransactWriteItem, DynamoDbStoreInternalError> {
+        // The previous item is returned if the condition fails.
+        let on_failure = condition
+            .as_ref()
+            .map(|_| ReturnValuesOnConditionCheckFailure::AllOld);
+        let (expression, values) = condition
+            .map(|condition| (condition.expression, condition.values))
+            .unzip();
+        Ok(match self {
+            ItemWrite::Delete(key) => {
+                let request = Delete::builder()
+                    .table_name(table_name)
+                    .set_key(Some(key))
+                    .set_condition_expression(expression)
+                    .set_expression_attribute_values(values.flatten())
+                    .set_return_values_on_condition_check_failure(on_failure)
+                    .build()?;
+                TransactWriteItem::builder().delete(request).build()
+            }
+            ItemWrite::Put(item) => {
+                let request = Put::builder()
+                    .table_name(table_name)
+                    .set_item(Some(item))
+                    .set_condition_expression(expression)
+                    .set_expression_attribute_values(values.flatten())
+                    .set_return_values_on_condition_check_failure(on_failure)
+                    .build()?;
+                TransactWriteItem::builder().put(request).build()
+            }
+        })
+    }
+
+    fn into_write_request(self) -> Result<WriteRequest, DynamoDbStoreInternalError> {
+        Ok(match self {
+            ItemWrite::Delete(key) => {
+                let request = DeleteRequest::builder().set_key(Some(key)).build()?;
+                WriteRequest::builder().delete_request(request).build()
+            }
+            ItemWrite::Put(item) => {
+                let request = PutRequest::builder().set_item(Some(item)).build()?;
+                WriteRequest::builder().put_request(request).build()
+            }
+        })
+    }
+}
+
+/// Enables the TTL of the table, unless it is already enabled or the table does not exist.
+/// Tables created before the TTL was supported have it disabled.
+async fn enable_time_to_live(
+    client: &Client,
+    namespace: &str,
+) -> Result<(), DynamoDbStoreInternalError> {
+    let response = client
+        .describe_time_to_live()
+        .table_name(namespace)
+        .send()
+        .boxed()
+        .await;
+    let status = match response {
+        Ok(response) => response
+            .time_to_live_description
+            .and_then(|description| description.time_to_live_status),
+        Err(SdkError::ServiceError(error))
+            if matches!(
+                error.err(),
+                DescribeTimeToLiveError::ResourceNotFoundException(_)
+            ) =>
+        {
+            return Ok(());
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if matches!(
+        status,
+        Some(TimeToLiveStatus::Enabled | TimeToLiveStatus::Enabling)
+    ) {
+        return Ok(());
+    }
+    client
+        .update_time_to_live()
+        .table_name(namespace)
+        .time_to_live_specification(
+            TimeToLiveSpecification::builder()
+                .attribute_name(EXPIRATION_ATTRIBUTE)
+                .enabled(true)
+                .build()?,
+        )
+        .send()
+        .boxed()
+        .await?;
+    Ok(())
+}
+
+/// Builds the item writes of a batch, spilling the values that do not fit in an item.
+struct WriteBuilder {
     start_key: Vec<u8>,
-    transactions: Vec<TransactWriteItem>,
+    /// The expiration time of the items that are put, in seconds since the Unix epoch.
+    expiration: Option<u64>,
+    /// The writes of the keys of the batch, with their sizes.
+    writes: Vec<(ItemWrite, usize)>,
+    /// The total size of `writes`.
+    size: usize,
+    /// The writes of the segments of the spilled values, with their sizes.
+    segments: Vec<(ItemWrite, usize)>,
+    /// The digests of the segments of the spilled values.
+    digests: Vec<Vec<u8>>,
 }
 
-impl TransactionBuilder {
+impl WriteBuilder {
     fn new(start_key: &[u8]) -> Self {
         Self {
             start_key: start_key.to_vec(),
-            transactions: Vec::new(),
+            expiration: None,
+            writes: Vec::new(),
+            size: 0,
+            segments: Vec::new(),
+            digests: Vec::new(),
         }
     }
 
-    fn insert_delete_request(
-        &mut self,
-        key: Vec<u8>,
-        store: &DynamoDbStoreInternal,
-    ) -> Result<(), DynamoDbStoreInternalError> {
-        let transaction = store.build_delete_transaction(&self.start_key, key)?;
-        self.transactions.push(transaction);
+    fn insert_delete_request(&mut self, key: Vec<u8>) -> Result<(), DynamoDbStoreInternalError> {
+        check_key_size(&key)?;
+        let size = key.len();
+        self.push_write(ItemWrite::Delete(build_key(&self.start_key, key)), size);
         Ok(())
     }
 
+    /// Makes the items that are put expire after `ttl`.
+    fn with_ttl(mut self, ttl: Duration) -> Self {
+        let expiration = Timestamp::now().saturating_add(TimeDelta::from_duration(ttl));
+        self.expiration = Some(expiration.micros().div_ceil(1_000_000));
+        self
+    }
+
+    fn build_item(
+        &self,
+        partition: &[u8],
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> HashMap<String, AttributeValue> {
+        let mut item = build_key_value(partition, key, value);
+        if let Some(expiration) = self.expiration {
+            item.insert(
+                EXPIRATION_ATTRIBUTE.to_owned(),
+                AttributeValue::N(expiration.to_string()),
+            );
+        }
+        item
+    }
+
     fn insert_put_request(
         &mut self,
         key: Vec<u8>,
         value: Vec<u8>,
-        store: &DynamoDbStoreInternal,
     ) -> Result<(), DynamoDbStoreInternalError> {
-        let transaction = store.build_put_transaction(&self.start_key, key, value)?;
-        self.transactions.push(transaction);
+        check_key_size(&key)?;
+        if value.len() <= RAW_MAX_VALUE_SIZE {
+            let size = key.len() + value.len();
+            let item = self.build_item(&self.start_key, key, value);
+            self.push_write(ItemWrite::Put(item), size);
+            return Ok(());
+        }
+        let segment_count = u32::try_from(value.len().div_ceil(SEGMENT_SIZE))
+            .map_err(|_| DynamoDbStoreInternalError::ValueLengthTooLarge)?;
+        let digest = get_segments_digest(&key, &value);
+        let partition = get_spill_partition(&self.start_key);
+        for (index, segment) in (0..segment_count).zip(value.chunks(SEGMENT_SIZE)) {
+            let segment_key = get_segment_key(&digest, index);
+            let size = segment_key.len() + segment.len();
+            let item = self.build_item(&partition, segment_key, segment.to_vec());
+            self.segments.push((ItemWrite::Put(item), size));
+        }
+        let size = key.len() + digest.len();
+        let mut item = self.build_item(&self.start_key, key, digest);
+        item.insert(
+            SEGMENT_COUNT_ATTRIBUTE.to_owned(),
+            AttributeValue::N(segment_count.to_string()),
+        );
+        self.push_write(ItemWrite::Put(item), size);
+        self.digests.push(digest);
         Ok(())
     }
+
+    fn push_write(&mut self, write: ItemWrite, size: usize) {
+        self.size += size;
+        self.writes.push((write, size));
+    }
+
+    /// Whether the writes of the keys can be done in a single transaction.
+    fn fits_in_transaction(&self) -> bool {
+        self.writes.len() <= MAX_TRANSACT_WRITE_ITEM_SIZE
+            && self.size <= MAX_TRANSACT_WRITE_ITEM_TOTAL_SIZE
+    }
+}
+
+/// Splits sized `writes` into chunks that can each be written by a `BatchWriteItem`.
+fn split_batch_writes<T>(writes: Vec<(T, usize)>) -> Vec<Vec<T>> {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_size = 0;
+    for (write, size) in writes {
+        if chunk.len() == MAX_BATCH_WRITE_ITEM_SIZE
+            || (!chunk.is_empty() && chunk_size + size > MAX_BATCH_WRITE_ITEM_TOTAL_SIZE)
+        {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_size = 0;
+        }
+        chunk.push(write);
+        chunk_size += size;
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Returns the delay before the given retry of the unprocessed items of a
+/// `BatchWriteItem`, starting from retry `1`.
+fn get_batch_write_backoff(retry: u32) -> Duration {
+    BATCH_WRITE_BASE_DELAY * 2u32.pow(retry - 1)
 }
 
 /// A DynamoDB client.
@@ -357,6 +762,7 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         let max_stream_queries = config.common_config.max_stream_queries;
         let namespace = namespace.to_string();
         let start_key = extend_root_key(&[]);
+        enable_time_to_live(&client, &namespace).await?;
         let store = Self {
             client,
             namespace,
@@ -384,6 +790,10 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         })
     }
 
+    fn clone_with_bounded_staleness(&self, _bounded_staleness: bool) -> Self {
+        self.clone()
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, DynamoDbStoreInternalError> {
         let client = config.client().await?;
         let mut namespaces = Vec::new();
@@ -514,7 +924,7 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
             .send()
             .boxed()
             .await?;
-        Ok(())
+        enable_time_to_live(&client, namespace).await
     }
 
     async fn delete(
@@ -555,35 +965,185 @@ impl DynamoDbStoreInternal {
         Ok(())
     }
 
-    fn build_delete_transaction(
+    /// Writes the `writes` atomically, in a single transaction, if their conditions hold.
+    async fn transact_write_items(
         &self,
-        start_key: &[u8],
-        key: Vec<u8>,
-    ) -> Result<TransactWriteItem, DynamoDbStoreInternalError> {
-        check_key_size(&key)?;
-        let request = Delete::builder()
-            .table_name(&self.namespace)
-            .set_key(Some(build_key(start_key, key)))
-            .build()?;
-        Ok(TransactWriteItem::builder().delete(request).build())
+        writes: Vec<(ItemWrite, Option<WriteCondition>)>,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let transact_items = writes
+            .into_iter()
+            .map(|(write, condition)| write.into_transact_write_item(&self.namespace, condition))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .boxed()
+            .await?;
+        Ok(())
     }
 
-    fn build_put_transaction(
+    /// Writes the `batch` with items built by `builder`.
+    async fn write_batch_with_builder(
         &self,
-        start_key: &[u8],
-        key: Vec<u8>,
-        value: Vec<u8>,
-    ) -> Result<TransactWriteItem, DynamoDbStoreInternalError> {
-        check_key_size(&key)?;
-        ensure!(
-            value.len() <= RAW_MAX_VALUE_SIZE,
-            DynamoDbStoreInternalError::ValueLengthTooLarge
-        );
-        let request = Put::builder()
-            .table_name(&self.namespace)
-            .set_item(Some(build_key_value(start_key, key, value)))
-            .build()?;
-        Ok(TransactWriteItem::builder().put(request).build())
+        batch: SimpleUnorderedBatch,
+        mut builder: WriteBuilder,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        if !self.root_key_written.fetch_or(true, Ordering::SeqCst) {
+            let mut builder = WriteBuilder::new(PARTITION_KEY_ROOT_KEY);
+            builder.insert_put_request(self.start_key.clone(), vec![])?;
+            let writes = builder
+                .writes
+                .into_iter()
+                .map(|(write, _)| (write, None))
+                .collect();
+            self.transact_write_items(writes).await?;
+        }
+        for key in batch.deletions {
+            builder.insert_delete_request(key)?;
+        }
+        for (key, value) in batch.insertions {
+            builder.insert_put_request(key, value)?;
+        }
+        // The segments are only read through the manifests, which are written afterwards.
+        let segments = std::mem::take(&mut builder.segments);
+        self.batch_write_items(segments).await?;
+        if builder.writes.is_empty() {
+            return Ok(());
+        }
+        let replaced = if builder.fits_in_transaction() {
+            self.transact_key_writes(builder.writes).await?
+        } else {
+            // Batches coming from the journaling layer always fit in a transaction, so
+            // this is only reached when the store is used directly. The writes cannot be
+            // conditional, so the replaced items are read first.
+            let handles = builder
+                .writes
+                .iter()
+                .map(|(write, _)| self.read_item(write.key()));
+            let mut replaced = Vec::new();
+            for item in join_all(handles).await {
+                if let Some(item) = item? {
+                    replaced.extend(extract_spill_manifest(&item)?);
+                }
+            }
+            self.batch_write_items(builder.writes).await?;
+            replaced
+        };
+        self.delete_segments(replaced, &builder.digests).await
+    }
+
+    /// Writes the items of the keys of a batch in a single transaction, and returns the
+    /// manifests of the spilled values they replaced.
+    ///
+    /// The transaction checks that the previous items are not manifests. If some are, they
+    /// are returned by DynamoDB, and the transaction is retried, checking that they are
+    /// still the ones being replaced.
+    async fn transact_key_writes(
+        &self,
+        writes: Vec<(ItemWrite, usize)>,
+    ) -> Result<Vec<SpillManifest>, DynamoDbStoreInternalError> {
+        let mut replaced = vec![None; writes.len()];
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let conditional_writes = writes
+                .iter()
+                .zip(&replaced)
+                .map(|((write, _), replaced)| {
+                    (
+                        write.clone(),
+                        Some(WriteCondition::replaces(replaced.as_ref())),
+                    )
+                })
+                .collect();
+            let result = {
+                let _guard = self.acquire().await;
+                self.transact_write_items(conditional_writes).await
+            };
+            let Err(error) = result else {
+                return Ok(replaced.into_iter().flatten().collect());
+            };
+            match extract_replaced_manifests(&error)? {
+                Some(manifests) if attempt < MAX_TRANSACT_WRITE_ATTEMPTS => {
+                    for (index, manifest) in manifests {
+                        replaced[index] = manifest;
+                    }
+                }
+                _ => return Err(error),
+            }
+        }
+    }
+
+    /// Deletes the segments of the `replaced` spilled values, except those of the values
+    /// whose digest is in `current` and that were therefore written again.
+    async fn delete_segments(
+        &self,
+        replaced: Vec<SpillManifest>,
+        current: &[Vec<u8>],
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let partition = get_spill_partition(&self.start_key);
+        let deletions = replaced
+            .into_iter()
+            .filter(|manifest| !current.contains(&manifest.digest))
+            .flat_map(|manifest| {
+                (0..manifest.segment_count)
+                    .map(move |index| get_segment_key(&manifest.digest, index))
+            })
+            .map(|segment_key| {
+                let size = segment_key.len();
+                (ItemWrite::Delete(build_key(&partition, segment_key)), size)
+            })
+            .collect();
+        self.batch_write_items(deletions).await
+    }
+
+    /// Writes the `writes` with concurrent `BatchWriteItem` calls. This is not atomic.
+    async fn batch_write_items(
+        &self,
+        writes: Vec<(ItemWrite, usize)>,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let handles = split_batch_writes(writes)
+            .into_iter()
+            .map(|chunk| self.batch_write_chunk(chunk));
+        join_all(handles).await.into_iter().collect()
+    }
+
+    /// Writes a chunk of items with a `BatchWriteItem`, retrying the unprocessed items
+    /// with an exponential backoff.
+    async fn batch_write_chunk(
+        &self,
+        chunk: Vec<ItemWrite>,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let mut requests = chunk
+            .into_iter()
+            .map(ItemWrite::into_write_request)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut retry = 0;
+        loop {
+            let response = {
+                let _guard = self.acquire().await;
+                self.client
+                    .batch_write_item()
+                    .request_items(&self.namespace, requests)
+                    .send()
+                    .boxed()
+                    .await?
+            };
+            requests = response
+                .unprocessed_items
+                .and_then(|mut items| items.remove(&self.namespace))
+                .unwrap_or_default();
+            if requests.is_empty() {
+                return Ok(());
+            }
+            retry += 1;
+            ensure!(
+                retry < MAX_BATCH_WRITE_ATTEMPTS,
+                DynamoDbStoreInternalError::UnprocessedItems(requests.len())
+            );
+            timer::sleep(get_batch_write_backoff(retry)).await;
+        }
     }
 
     /// Obtains the semaphore lock on the database if needed.
@@ -611,8 +1171,15 @@ impl DynamoDbStoreInternal {
             .key_condition_expression(format!(
                 "{PARTITION_ATTRIBUTE} = :partition and begins_with({KEY_ATTRIBUTE}, :prefix)"
             ))
+            .filter_expression(format!(
+                "attribute_not_exists({EXPIRATION_ATTRIBUTE}) or {EXPIRATION_ATTRIBUTE} > :now"
+            ))
             .expression_attribute_values(":partition", AttributeValue::B(Blob::new(start_key)))
             .expression_attribute_values(":prefix", AttributeValue::B(Blob::new(key_prefix)))
+            .expression_attribute_values(
+                ":now",
+                AttributeValue::N(current_expiration_time().to_string()),
+            )
             .set_exclusive_start_key(start_key_map)
             .send()
             .boxed()
@@ -620,10 +1187,10 @@ impl DynamoDbStoreInternal {
         Ok(response)
     }
 
-    async fn read_value_bytes_general(
+    async fn read_item(
         &self,
         key_db: HashMap<String, AttributeValue>,
-    ) -> Result<Option<Vec<u8>>, DynamoDbStoreInternalError> {
+    ) -> Result<Option<HashMap<String, AttributeValue>>, DynamoDbStoreInternalError> {
         let _guard = self.acquire().await;
         let response = self
             .client
@@ -633,14 +1200,76 @@ impl DynamoDbStoreInternal {
             .send()
             .boxed()
             .await?;
+        Ok(response.item)
+    }
+
+    async fn read_value_bytes_general(
+        &self,
+        key_db: HashMap<String, AttributeValue>,
+    ) -> Result<Option<Vec<u8>>, DynamoDbStoreInternalError> {
+        let Some(mut item) = self.read_item(key_db).await? else {
+            return Ok(None);
+        };
+        if is_expired(&item, current_expiration_time()) {
+            return Ok(None);
+        }
+        let value = match extract_spill_manifest(&item)? {
+            None => extract_value_owned(&mut item)?,
+            Some(manifest) => self.read_segments(&manifest).await?,
+        };
+        Ok(Some(value))
+    }
+
+    /// Reads the segments of a spilled value and concatenates them.
+    async fn read_segments(
+        &self,
+        manifest: &SpillManifest,
+    ) -> Result<Vec<u8>, DynamoDbStoreInternalError> {
+        let partition = get_spill_partition(&self.start_key);
+        let handles = (0..manifest.segment_count).map(|index| {
+            let segment_key = get_segment_key(&manifest.digest, index);
+            self.read_item(build_key(&partition, segment_key))
+        });
+        let mut value = Vec::new();
+        for segment in join_all(handles).await {
+            let mut segment = segment?.ok_or(DynamoDbStoreInternalError::MissingSegment)?;
+            value.extend(extract_value_owned(&mut segment)?);
+        }
+        Ok(value)
+    }
 
-        match response.item {
-            Some(mut item) => {
-                let value = extract_value_owned(&mut item)?;
-                Ok(Some(value))
+    /// Replaces the manifests in the items of `result_queries` by the spilled values.
+    async fn read_spilled_values(
+        &self,
+        result_queries: &mut QueryResponses,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let mut positions = Vec::new();
+        let mut manifests = Vec::new();
+        for (i_response, response) in result_queries.responses.iter().enumerate() {
+            for (i_item, item) in response.items().iter().enumerate() {
+                if let Some(manifest) = extract_spill_manifest(item)? {
+                    positions.push((i_response, i_item));
+                    manifests.push(manifest);
+                }
             }
-            None => Ok(None),
         }
+        let handles = manifests
+            .iter()
+            .map(|manifest| self.read_segments(manifest));
+        let values = join_all(handles).await;
+        for ((i_response, i_item), value) in positions.into_iter().zip(values) {
+            let items = result_queries.responses[i_response]
+                .items
+                .as_mut()
+                .expect("the item was found in the response");
+            let item = &mut items[i_item];
+            item.remove(SEGMENT_COUNT_ATTRIBUTE);
+            item.insert(
+                VALUE_ATTRIBUTE.to_owned(),
+                AttributeValue::B(Blob::new(value?)),
+            );
+        }
+        Ok(())
     }
 
     async fn contains_key_general(
@@ -653,12 +1282,14 @@ impl DynamoDbStoreInternal {
             .get_item()
             .table_name(&self.namespace)
             .set_key(Some(key_db))
-            .projection_expression(PARTITION_ATTRIBUTE)
+            .projection_expression(format!("{PARTITION_ATTRIBUTE}, {EXPIRATION_ATTRIBUTE}"))
             .send()
             .boxed()
             .await?;
 
-        Ok(response.item.is_some())
+        Ok(response
+            .item
+            .is_some_and(|item| !is_expired(&item, current_expiration_time())))
     }
 
     async fn get_list_responses(
@@ -933,13 +1564,46 @@ impl ReadableKeyValueStore for DynamoDbStoreInternal {
         Ok(DynamoDbKeys { result_queries })
     }
 
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, DynamoDbStoreInternalError> {
+        check_key_size(key_prefix)?;
+        // The query starts right after `start_after`, and only reads as many pages as needed.
+        let mut start_key_map = start_after
+            .map(|start_after| build_key(&self.start_key, [key_prefix, start_after].concat()));
+        let mut keys = Vec::new();
+        while keys.len() < limit {
+            let response = self
+                .get_query_output(KEY_ATTRIBUTE, &self.start_key, key_prefix, start_key_map)
+                .await?;
+            start_key_map = response.last_evaluated_key.clone();
+            let page = DynamoDbKeys {
+                result_queries: QueryResponses {
+                    prefix_len: key_prefix.len(),
+                    responses: vec![response],
+                },
+            };
+            for key in page.iterator().take(limit - keys.len()) {
+                keys.push(key?.to_vec());
+            }
+            if start_key_map.is_none() {
+                break;
+            }
+        }
+        Ok(keys)
+    }
+
     async fn find_key_values_by_prefix(
         &self,
         key_prefix: &[u8],
     ) -> Result<DynamoDbKeyValues, DynamoDbStoreInternalError> {
-        let result_queries = self
+        let mut result_queries = self
             .get_list_responses(KEY_VALUE_ATTRIBUTE, &self.start_key, key_prefix)
             .await?;
+        self.read_spilled_values(&mut result_queries).await?;
         Ok(DynamoDbKeyValues { result_queries })
     }
 }
@@ -949,37 +1613,23 @@ impl DirectWritableKeyValueStore for DynamoDbStoreInternal {
     const MAX_BATCH_TOTAL_SIZE: usize = MAX_TRANSACT_WRITE_ITEM_TOTAL_SIZE;
     const MAX_VALUE_SIZE: usize = VISIBLE_MAX_VALUE_SIZE;
 
+    const SUPPORTS_TTL: bool = true;
+
     // DynamoDB does not support the `DeletePrefix` operation.
     type Batch = SimpleUnorderedBatch;
 
     async fn write_batch(&self, batch: Self::Batch) -> Result<(), DynamoDbStoreInternalError> {
-        if !self.root_key_written.fetch_or(true, Ordering::SeqCst) {
-            let mut builder = TransactionBuilder::new(PARTITION_KEY_ROOT_KEY);
-            builder.insert_put_request(self.start_key.clone(), vec![], self)?;
-            self.client
-                .transact_write_items()
-                .set_transact_items(Some(builder.transactions))
-                .send()
-                .boxed()
-                .await?;
-        }
-        let mut builder = TransactionBuilder::new(&self.start_key);
-        for key in batch.deletions {
-            builder.insert_delete_request(key, self)?;
-        }
-        for (key, value) in batch.insertions {
-            builder.insert_put_request(key, value, self)?;
-        }
-        if !builder.transactions.is_empty() {
-            let _guard = self.acquire().await;
-            self.client
-                .transact_write_items()
-                .set_transact_items(Some(builder.transactions))
-                .send()
-                .boxed()
-                .await?;
-        }
-        Ok(())
+        let builder = WriteBuilder::new(&self.start_key);
+        self.write_batch_with_builder(batch, builder).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Self::Batch,
+        ttl: Duration,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let builder = WriteBuilder::new(&self.start_key).with_ttl(ttl);
+        self.write_batch_with_builder(batch, builder).await
     }
 }
 
@@ -1058,10 +1708,22 @@ pub enum DynamoDbStoreInternalError {
     #[error(transparent)]
     JournalConsistencyError(#[from] JournalConsistencyError),
 
-    /// The length of the value should be at most 400 KB.
-    #[error("The DynamoDB value should be less than 400 KB")]
+    /// The value is too large to be spilled.
+    #[error("The DynamoDB value is too large to be spilled")]
     ValueLengthTooLarge,
 
+    /// Some items of a `BatchWriteItem` could not be processed after all the retries.
+    #[error("{0} items of a DynamoDB batch write were still unprocessed after retrying")]
+    UnprocessedItems(usize),
+
+    /// The manifest of a spilled value is invalid.
+    #[error("The manifest of a spilled DynamoDB value is invalid")]
+    InvalidSpillManifest,
+
+    /// A segment of a spilled value is missing.
+    #[error("A segment of a spilled DynamoDB value is missing")]
+    MissingSegment,
+
     /// The stored key is missing.
     #[error("The stored key attribute is missing")]
     MissingKey,
@@ -1090,6 +1752,14 @@ pub enum DynamoDbStoreInternalError {
     #[error(transparent)]
     CreateTable(#[from] Box<SdkError<CreateTableError>>),
 
+    /// An error occurred while checking the TTL of the table.
+    #[error(transparent)]
+    DescribeTimeToLive(#[from] Box<SdkError<DescribeTimeToLiveError>>),
+
+    /// An error occurred while enabling the TTL of the table.
+    #[error(transparent)]
+    UpdateTimeToLive(#[from] Box<SdkError<UpdateTimeToLiveError>>),
+
     /// An error occurred while building an object
     #[error(transparent)]
     Build(#[from] Box<BuildError>),
@@ -1170,21 +1840,24 @@ impl TestKeyValueStore for JournalingKeyValueStore<DynamoDbStoreInternal> {
 pub type DynamoDbStore = MeteredStore<
     LruCachingStore<
         MeteredStore<
-            ValueSplittingStore<MeteredStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>,
+            CompressedStore<
+                ValueSplittingStore<MeteredStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>,
+            >,
         >,
     >,
 >;
 
 /// A shared DB client for DynamoDB implementing LRU caching
 #[cfg(not(with_metrics))]
-pub type DynamoDbStore =
-    LruCachingStore<ValueSplittingStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>;
+pub type DynamoDbStore = LruCachingStore<
+    CompressedStore<ValueSplittingStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>,
+>;
 
 /// The combined error type for [`DynamoDbStore`].
-pub type DynamoDbStoreError = ValueSplittingError<DynamoDbStoreInternalError>;
+pub type DynamoDbStoreError = CompressionError<ValueSplittingError<DynamoDbStoreInternalError>>;
 
 /// The config type for [`DynamoDbStore`]`
-pub type DynamoDbStoreConfig = LruCachingConfig<DynamoDbStoreInternalConfig>;
+pub type DynamoDbStoreConfig = LruCachingConfig<CompressedStoreConfig<DynamoDbStoreInternalConfig>>;
 
 impl DynamoDbStoreConfig {
     /// Creates a `DynamoDbStoreConfig` from the input.
@@ -1196,19 +1869,207 @@ impl DynamoDbStoreConfig {
             use_dynamodb_local,
             common_config: common_config.reduced(),
         };
+        let inner_config = CompressedStoreConfig {
+            inner_config,
+            compression: None,
+        };
         DynamoDbStoreConfig {
             inner_config,
             storage_cache_config: common_config.storage_cache_config,
         }
     }
+
+    /// Sets the compression of the values written from now on.
+    pub fn with_compression(mut self, compression: Option<ValueCompression>) -> Self {
+        self.inner_config.compression = compression;
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use bcs::serialized_size;
 
+    use super::{
+        get_batch_write_backoff, get_segment_key, get_segments_digest, get_spill_partition,
+        split_batch_writes, BATCH_WRITE_BASE_DELAY, EMPTY_ROOT_KEY, PARTITION_KEY_ROOT_KEY,
+    };
     use crate::common::get_uleb128_size;
 
+    #[test]
+    fn test_split_batch_writes() {
+        let writes = (0..60).map(|i| (i, 10)).collect::<Vec<_>>();
+        let chunks = split_batch_writes(writes);
+        let lengths = chunks.iter().map(Vec::len).collect::<Vec<_>>();
+        assert_eq!(lengths, [25, 25, 10]);
+        assert_eq!(chunks.concat(), (0..60).collect::<Vec<_>>());
+
+        let writes = (0..5).map(|i| (i, 7000000)).collect::<Vec<_>>();
+        let lengths = split_batch_writes(writes)
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, [2, 2, 1]);
+
+        assert!(split_batch_writes(Vec::<(u8, usize)>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_batch_write_backoff() {
+        assert_eq!(get_batch_write_backoff(1), BATCH_WRITE_BASE_DELAY);
+        assert_eq!(get_batch_write_backoff(2), 2 * BATCH_WRITE_BASE_DELAY);
+        assert_eq!(get_batch_write_backoff(4), 8 * BATCH_WRITE_BASE_DELAY);
+    }
+
+    #[test]
+    fn test_spilled_segments_are_separate() {
+        let start_key = [EMPTY_ROOT_KEY, &[5, 6]].concat();
+        let partition = get_spill_partition(&start_key);
+        assert_eq!(partition, [2, 5, 6]);
+        assert_ne!(partition[0], EMPTY_ROOT_KEY[0]);
+        assert_ne!(partition, PARTITION_KEY_ROOT_KEY);
+
+        let digest = get_segments_digest(b"key", b"value");
+        assert_ne!(digest, get_segments_digest(b"ke", b"yvalue"));
+        assert_ne!(digest, get_segments_digest(b"key", b"other value"));
+        assert_eq!(
+            get_segment_key(&digest, 1),
+            [&digest[..], &[0, 0, 0, 1]].concat()
+        );
+    }
+
+    /// Returns the number of segments of spilled values stored for the root key of `store`.
+    #[cfg(with_dynamodb)]
+    async fn count_segments(store: &super::DynamoDbStoreInternal) -> usize {
+        let partition = get_spill_partition(&store.start_key);
+        let responses = store
+            .get_list_responses(super::KEY_ATTRIBUTE, &partition, &[])
+            .await
+            .unwrap();
+        responses
+            .responses
+            .iter()
+            .map(|response| response.items().len())
+            .sum()
+    }
+
+    #[cfg(with_dynamodb)]
+    #[tokio::test]
+    async fn test_replaced_segments_are_deleted() {
+        use super::DynamoDbStoreInternal;
+        use crate::{
+            batch::SimpleUnorderedBatch,
+            journaling::{DirectWritableKeyValueStore as _, JournalingKeyValueStore},
+            random::generate_test_namespace,
+            store::{AdminKeyValueStore as _, ReadableKeyValueStore as _, TestKeyValueStore as _},
+        };
+
+        let config = JournalingKeyValueStore::<DynamoDbStoreInternal>::new_test_config()
+            .await
+            .unwrap();
+        let store =
+            DynamoDbStoreInternal::recreate_and_connect(&config, &generate_test_namespace())
+                .await
+                .unwrap();
+        // Larger than a DynamoDB item, which is limited to 400 KB.
+        let big_value = (0..1000000u32).map(|i| i as u8).collect::<Vec<_>>();
+        let other_big_value = (0..900000u32).map(|i| (i / 3) as u8).collect::<Vec<_>>();
+        let write = |insertions: Vec<(Vec<u8>, Vec<u8>)>, deletions: Vec<Vec<u8>>| {
+            store.write_batch(SimpleUnorderedBatch {
+                deletions,
+                insertions,
+            })
+        };
+
+        write(
+            vec![(vec![1], big_value.clone()), (vec![2], big_value.clone())],
+            vec![],
+        )
+        .await
+        .unwrap();
+        assert_eq!(count_segments(&store).await, 6);
+        // Writing the same value again keeps its segments.
+        write(vec![(vec![1], big_value.clone())], vec![])
+            .await
+            .unwrap();
+        assert_eq!(count_segments(&store).await, 6);
+        write(vec![(vec![1], other_big_value.clone())], vec![])
+            .await
+            .unwrap();
+        assert_eq!(count_segments(&store).await, 6);
+        assert_eq!(
+            store.read_value_bytes(&[1]).await.unwrap(),
+            Some(other_big_value)
+        );
+        write(vec![(vec![1], vec![7])], vec![vec![2]])
+            .await
+            .unwrap();
+        assert_eq!(count_segments(&store).await, 0);
+        assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), Some(vec![7]));
+        assert_eq!(store.read_value_bytes(&[2]).await.unwrap(), None);
+    }
+
+    #[cfg(with_dynamodb)]
+    #[tokio::test]
+    async fn test_expired_items_are_not_read() {
+        use linera_base::time::{timer, Duration};
+
+        use super::DynamoDbStoreInternal;
+        use crate::{
+            batch::Batch,
+            journaling::JournalingKeyValueStore,
+            random::generate_test_namespace,
+            store::{
+                AdminKeyValueStore as _, KeyIterable as _, ReadableKeyValueStore as _,
+                TestKeyValueStore as _, WritableKeyValueStore as _,
+            },
+        };
+
+        let config = JournalingKeyValueStore::<DynamoDbStoreInternal>::new_test_config()
+            .await
+            .unwrap();
+        let store = JournalingKeyValueStore::<DynamoDbStoreInternal>::recreate_and_connect(
+            &config,
+            &generate_test_namespace(),
+        )
+        .await
+        .unwrap()
+        .clone_with_root_key(&[])
+        .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1]);
+        batch.put_key_value_bytes(vec![2], vec![2]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::ZERO))
+            .await
+            .unwrap();
+        // The batch is too big to be written at once, so it goes through the journal.
+        let mut batch = Batch::new();
+        for index in 0..200u8 {
+            batch.put_key_value_bytes(vec![3, index], vec![index]);
+        }
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(3600)))
+            .await
+            .unwrap();
+
+        // The expiration times are in seconds.
+        timer::sleep(Duration::from_secs(2)).await;
+        assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), None);
+        assert!(!store.contains_key(&[2]).await.unwrap());
+        assert_eq!(
+            store
+                .read_multi_values_bytes(vec![vec![1], vec![3, 7]])
+                .await
+                .unwrap(),
+            vec![None, Some(vec![7])]
+        );
+        let keys = store.find_keys_by_prefix(&[]).await.unwrap();
+        let keys = keys.iterator().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(keys.len(), 200);
+        assert!(keys.iter().all(|key| key[0] == 3));
+    }
+
     #[test]
     fn test_serialization_len() {
         for n in [0, 10, 127, 128, 129, 16383, 16384, 20000] {
@@ -292,6 +292,15 @@ impl AdminKeyValueStore for IndexedDbStore {
         })
     }
 
+    fn clone_with_bounded_staleness(&self, _bounded_staleness: bool) -> Self {
+        Self {
+            database: self.database.clone(),
+            object_store_name: self.object_store_name.clone(),
+            max_stream_queries: self.max_stream_queries,
+            start_key: self.start_key.clone(),
+        }
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, IndexedDbStoreError> {
         Ok(Self::connect(config, "")
             .await?
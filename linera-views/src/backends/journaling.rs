@@ -18,7 +18,17 @@
 //! cleared. This is done by processing every block of the journal successively. Every
 //! time the data in a block are written, the journal header is updated in the same
 //! transaction to mark the block as processed.
+//!
+//! A batch written with a TTL takes the same path. The journal then also records the
+//! expiration time of the batch, and the blocks are written with the TTL that remains when
+//! they are processed.
+
+use std::future::Future;
 
+use linera_base::{
+    data_types::{TimeDelta, Timestamp},
+    time::Duration,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use static_assertions as sa;
 use thiserror::Error;
@@ -54,6 +64,9 @@ enum KeyTag {
     Journal = 1,
     /// Prefix for the block entry.
     Entry,
+    /// Prefix for the expiration time of the values of the journal, if they were written
+    /// with a TTL.
+    Expiration,
 }
 
 fn get_journaling_key(tag: u8, pos: u32) -> Result<Vec<u8>, bcs::Error> {
@@ -75,11 +88,30 @@ pub trait DirectWritableKeyValueStore: WithError {
     /// The maximal size of values that can be stored.
     const MAX_VALUE_SIZE: usize;
 
+    /// Whether the values written by [`Self::write_batch_with_ttl`] expire.
+    const SUPPORTS_TTL: bool = false;
+
     /// The batch type.
     type Batch: SimplifiedBatch + Serialize + DeserializeOwned + Default;
 
     /// Writes the batch to the database.
     async fn write_batch(&self, batch: Self::Batch) -> Result<(), Self::Error>;
+
+    /// Writes the batch to the database. The values put by the batch expire once `ttl`
+    /// has elapsed, if the store [supports TTL](Self::SUPPORTS_TTL).
+    fn write_batch_with_ttl(
+        &self,
+        batch: Self::Batch,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<(), Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let _ = ttl;
+            self.write_batch(batch).await
+        }
+    }
 }
 
 /// Low-level, asynchronous direct read/write key-value operations with simplified batch
@@ -174,6 +206,17 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
 }
 
 impl<K> AdminKeyValueStore for JournalingKeyValueStore<K>
@@ -202,6 +245,14 @@ where
         })
     }
 
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        Self {
+            store,
+            has_exclusive_access: self.has_exclusive_access,
+        }
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
         K::list_all(config).await
     }
@@ -237,6 +288,7 @@ where
 {
     /// The size constant do not change
     const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = K::SUPPORTS_TTL;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
         let batch = K::Batch::from_batch(self, batch).await?;
@@ -246,11 +298,31 @@ where
             if !self.has_exclusive_access {
                 return Err(JournalConsistencyError::JournalRequiresExclusiveAccess.into());
             }
-            let header = self.write_journal(batch).await?;
+            let header = self.write_journal(batch, None).await?;
             self.coherently_resolve_journal(header).await
         }
     }
 
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
+        let Some(ttl) = ttl else {
+            return self.write_batch(batch).await;
+        };
+        let batch = K::Batch::from_batch(self, batch).await?;
+        if Self::is_fastpath_feasible(&batch) {
+            return self.store.write_batch_with_ttl(batch, ttl).await;
+        }
+        if !self.has_exclusive_access {
+            return Err(JournalConsistencyError::JournalRequiresExclusiveAccess.into());
+        }
+        let expiration = Timestamp::now().saturating_add(TimeDelta::from_duration(ttl));
+        let header = self.write_journal(batch, Some(expiration)).await?;
+        self.coherently_resolve_journal(header).await
+    }
+
     async fn clear_journal(&self) -> Result<(), Self::Error> {
         let key = get_journaling_key(KeyTag::Journal as u8, 0)?;
         let value = self.read_value::<JournalHeader>(&key).await?;
@@ -276,18 +348,25 @@ where
     /// This function respects the constraints of the underlying key-value store `K` if
     /// the following conditions are met:
     ///
-    /// (1) each block contains at most `K::MAX_BATCH_SIZE - 2` operations;
+    /// (1) each block contains at most `K::MAX_BATCH_SIZE - 3` operations;
     ///
     /// (2) the total size of the all operations in a block doesn't exceed:
-    /// `K::MAX_BATCH_TOTAL_SIZE - sizeof(block_key) - sizeof(header_key) - sizeof(bcs_header)`
+    /// `K::MAX_BATCH_TOTAL_SIZE - sizeof(block_key) - sizeof(header_key) - sizeof(bcs_header)
+    /// - sizeof(expiration_key)`
     ///
     /// (3) every operation in a block satisfies the constraints on individual database
     /// operations represented by `K::MAX_KEY_SIZE` and `K::MAX_VALUE_SIZE`.
     ///
     /// (4) `block_key` and `header_key` don't exceed `K::MAX_KEY_SIZE` and `bcs_header`
     /// doesn't exceed `K::MAX_VALUE_SIZE`.
+    ///
+    /// If the journal has an expiration time, the blocks are written with the TTL that
+    /// remains until then. The header then expires with the values, so the blocks that
+    /// are left once it has expired are never processed.
     async fn coherently_resolve_journal(&self, mut header: JournalHeader) -> Result<(), K::Error> {
         let header_key = get_journaling_key(KeyTag::Journal as u8, 0)?;
+        let expiration_key = get_journaling_key(KeyTag::Expiration as u8, 0)?;
+        let expiration = self.store.read_value::<Timestamp>(&expiration_key).await?;
         while header.block_count > 0 {
             let block_key = get_journaling_key(KeyTag::Entry as u8, header.block_count - 1)?;
             // Read the batch of updates (aka. "block") previously saved in the journal.
@@ -304,8 +383,17 @@ where
                 batch.add_insert(header_key.clone(), value);
             } else {
                 batch.add_delete(header_key.clone());
+                if expiration.is_some() {
+                    batch.add_delete(expiration_key.clone());
+                }
+            }
+            match expiration {
+                None => self.store.write_batch(batch).await?,
+                Some(expiration) => {
+                    let ttl = expiration.delta_since(Timestamp::now()).as_duration();
+                    self.store.write_batch_with_ttl(batch, ttl).await?;
+                }
             }
-            self.store.write_batch(batch).await?;
         }
         Ok(())
     }
@@ -333,11 +421,13 @@ where
     ///
     /// (3) The size of each BCS-serialized block doesn't exceed `K::MAX_VALUE_SIZE`.
     ///
-    /// (4) When processing a journal block, we have to do two other operations.
+    /// (4) When processing a journal block, we have to do up to three other operations.
     ///   (a) removing the existing block. The cost is `key_len`.
     ///   (b) updating or removing the journal. The cost is `key_len + header_value_len`
-    ///       or `key_len`. An upper bound is thus
-    ///       `journal_len_upper_bound = key_len + header_value_len`.
+    ///       or `key_len`.
+    ///   (c) removing the expiration time, with the last block. The cost is `key_len`.
+    ///   An upper bound is thus
+    ///       `journal_len_upper_bound = 2 * key_len + header_value_len`.
     ///   Thus the following has to be taken as upper bound on the block size:
     ///   `K::MAX_BATCH_TOTAL_SIZE - key_len - journal_len_upper_bound`.
     ///
@@ -350,11 +440,17 @@ where
     /// * Similarly, a transaction must contain at least one block so it is desirable that
     ///   the maximum size of a block insertion `1 + sizeof(block_key) + K::MAX_VALUE_SIZE`
     ///   plus M bytes of overhead doesn't exceed the threshold of condition (2).
-    async fn write_journal(&self, batch: K::Batch) -> Result<JournalHeader, K::Error> {
+    ///
+    /// The `expiration` of the values of the batch, if any, is written with the header.
+    async fn write_journal(
+        &self,
+        batch: K::Batch,
+        expiration: Option<Timestamp>,
+    ) -> Result<JournalHeader, K::Error> {
         let header_key = get_journaling_key(KeyTag::Journal as u8, 0)?;
         let key_len = header_key.len();
         let header_value_len = bcs::serialized_size(&JournalHeader::default())?;
-        let journal_len_upper_bound = key_len + header_value_len;
+        let journal_len_upper_bound = 2 * key_len + header_value_len;
         // Each block in a transaction comes with a key.
         let max_transaction_size = K::MAX_BATCH_TOTAL_SIZE;
         let max_block_size = std::cmp::min(
@@ -379,7 +475,7 @@ where
                     let next_transaction_size = transaction_size + next_block_size + key_len;
                     let transaction_flush = next_transaction_size > max_transaction_size;
                     let block_flush = transaction_flush
-                        || block_batch.len() == K::MAX_BATCH_SIZE - 2
+                        || block_batch.len() == K::MAX_BATCH_SIZE - 3
                         || next_block_size > max_block_size;
                     (block_flush, transaction_flush)
                 }
@@ -406,6 +502,11 @@ where
             let value = bcs::to_bytes(&header)?;
             let mut batch = K::Batch::default();
             batch.add_insert(header_key, value);
+            let expiration_key = get_journaling_key(KeyTag::Expiration as u8, 0)?;
+            match expiration {
+                None => batch.add_delete(expiration_key),
+                Some(expiration) => batch.add_insert(expiration_key, bcs::to_bytes(&expiration)?),
+            }
             self.store.write_batch(batch).await?;
         }
         Ok(header)
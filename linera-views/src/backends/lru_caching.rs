@@ -1,24 +1,44 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-//! Add LRU (least recently used) caching to a given store.
+//! Add caching to a given store, with LRU (least recently used) eviction by default.
+//!
+//! The eviction policy is chosen by the [`CachePolicyKind`] of the configuration. The
+//! segmented policy also caches the results of `find_keys_by_prefix` for the stores with
+//! exclusive access to their root key, and these results are invalidated whenever a batch
+//! is written under the same root key.
+//!
+//! The stores obtained with `clone_with_root_key` share the cache of the store they were
+//! cloned from. The limits of the configuration apply to the whole namespace rather than to
+//! each root key, so that the memory used does not grow with the number of root keys, but
+//! the entries of the root keys that are used a lot may evict the ones of the others. The
+//! keys of the cache start with the root key of the store that accessed them, so that a
+//! store never reads the entries of another root key.
 
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
 use std::{
-    collections::{btree_map, hash_map::RandomState, BTreeMap},
+    collections::{hash_map::RandomState, BTreeMap, BTreeSet, HashMap},
+    fmt,
+    marker::PhantomData,
+    str::FromStr,
     sync::{Arc, Mutex},
 };
 
+use linera_base::time::{Duration, Instant};
 use linked_hash_map::LinkedHashMap;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 #[cfg(with_metrics)]
 use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCounterVec};
 
 use crate::{
     batch::{Batch, WriteOperation},
     common::get_interval,
-    store::{AdminKeyValueStore, ReadableKeyValueStore, WithError, WritableKeyValueStore},
+    store::{
+        AdminKeyValueStore, KeyIterable, ReadableKeyValueStore, SimpleKeyIterator, WithError,
+        WritableKeyValueStore,
+    },
 };
 #[cfg(with_testing)]
 use crate::{memory::MemoryStore, store::TestKeyValueStore};
@@ -63,6 +83,46 @@ static CONTAINS_KEY_CACHE_HIT_COUNT: LazyLock<IntCounterVec> = LazyLock::new(||
     )
 });
 
+#[cfg(with_metrics)]
+/// The total number of find keys cache misses
+static FIND_KEYS_CACHE_MISS_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "num_find_keys_cache_miss",
+        "Number of find keys cache misses",
+        &[],
+    )
+});
+
+#[cfg(with_metrics)]
+/// The total number of find keys cache hits
+static FIND_KEYS_CACHE_HIT_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "num_find_keys_cache_hit",
+        "Number of find keys cache hits",
+        &[],
+    )
+});
+
+#[cfg(with_metrics)]
+/// The total number of keys read in the background by prefetches
+static PREFETCHED_KEY_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "num_prefetched_keys",
+        "Number of keys read in the background by prefetches",
+        &[],
+    )
+});
+
+#[cfg(with_metrics)]
+/// The total number of entries evicted from the cache
+static CACHE_EVICTION_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "num_cache_evictions",
+        "Number of entries evicted from the cache",
+        &[],
+    )
+});
+
 /// The parametrization of the cache
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StorageCacheConfig {
@@ -72,6 +132,9 @@ pub struct StorageCacheConfig {
     pub max_entry_size: usize,
     /// The maximum number of entries in the cache.
     pub max_cache_entries: usize,
+    /// The policy deciding which entries are evicted when the cache is full.
+    #[serde(default)]
+    pub cache_policy: CachePolicyKind,
 }
 
 /// The maximum number of entries in the cache.
@@ -81,8 +144,225 @@ pub const DEFAULT_STORAGE_CACHE_CONFIG: StorageCacheConfig = StorageCacheConfig
     max_cache_size: 10000000,
     max_entry_size: 1000000,
     max_cache_entries: 1000,
+    cache_policy: CachePolicyKind::Lru,
 };
 
+/// The eviction policies of the cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CachePolicyKind {
+    /// Evicts the least recently used entries first.
+    #[default]
+    Lru,
+    /// Evicts the least frequently used entries first. The frequencies are small counters
+    /// that are halved regularly, so that entries which are no longer used are evicted
+    /// eventually.
+    Lfu,
+    /// Evicts the least recently used entries first, and also caches the results of
+    /// `find_keys_by_prefix` in a separate segment, so that large results do not evict the
+    /// values.
+    Segmented,
+}
+
+/// The error returned when parsing a [`CachePolicyKind`] fails.
+#[derive(Error, Debug)]
+#[error("invalid cache policy {0:?}, expected lru, lfu or segmented")]
+pub struct InvalidCachePolicy(String);
+
+impl FromStr for CachePolicyKind {
+    type Err = InvalidCachePolicy;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "lru" => Ok(CachePolicyKind::Lru),
+            "lfu" => Ok(CachePolicyKind::Lfu),
+            "segmented" => Ok(CachePolicyKind::Segmented),
+            _ => Err(InvalidCachePolicy(input.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for CachePolicyKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CachePolicyKind::Lru => write!(f, "lru"),
+            CachePolicyKind::Lfu => write!(f, "lfu"),
+            CachePolicyKind::Segmented => write!(f, "segmented"),
+        }
+    }
+}
+
+/// The part of `max_cache_size` given to the results of `find_keys_by_prefix` by the
+/// segmented policy is `1 / FIND_KEYS_SEGMENT_DIVISOR`.
+const FIND_KEYS_SEGMENT_DIVISOR: usize = 4;
+
+/// The maximal frequency counted by the LFU policy.
+const LFU_MAX_FREQUENCY: u8 = 15;
+
+/// The LFU policy halves the frequencies after this many accesses per entry.
+const LFU_AGING_PERIOD: usize = 8;
+
+/// How long after its TTL a key is still not cached. The stores count the TTL from the time
+/// they apply the write, may round the expiration time up, and have their own clocks.
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(10);
+
+/// Decides which entries of a cache segment are evicted first.
+trait CachePolicy: Send {
+    /// Records an entry that was inserted or replaced, and returns its previous size.
+    fn insert(&mut self, key: Vec<u8>, size: usize) -> Option<usize>;
+
+    /// Records an access to an entry.
+    fn touch(&mut self, key: &[u8]);
+
+    /// Changes the size of an entry without counting it as an access, and returns its
+    /// previous size.
+    fn resize(&mut self, key: &[u8], size: usize) -> Option<usize>;
+
+    /// Forgets about an entry and returns its size.
+    fn remove(&mut self, key: &[u8]) -> Option<usize>;
+
+    /// Removes the entry to evict first, and returns it with its size.
+    fn pop_victim(&mut self) -> Option<(Vec<u8>, usize)>;
+
+    /// The number of entries.
+    fn num_entries(&self) -> usize;
+}
+
+/// Evicts the least recently used entries first.
+#[derive(Default)]
+struct LruPolicy {
+    queue: LinkedHashMap<Vec<u8>, usize, RandomState>,
+}
+
+impl CachePolicy for LruPolicy {
+    fn insert(&mut self, key: Vec<u8>, size: usize) -> Option<usize> {
+        // Removing the key first puts it at the last position of the queue.
+        let old_size = self.queue.remove(&key);
+        self.queue.insert(key, size);
+        old_size
+    }
+
+    fn touch(&mut self, key: &[u8]) {
+        self.queue.get_refresh(key);
+    }
+
+    fn resize(&mut self, key: &[u8], size: usize) -> Option<usize> {
+        let old_size = self.queue.get_mut(key)?;
+        Some(std::mem::replace(old_size, size))
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Option<usize> {
+        self.queue.remove(key)
+    }
+
+    fn pop_victim(&mut self) -> Option<(Vec<u8>, usize)> {
+        self.queue.pop_front()
+    }
+
+    fn num_entries(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// An entry of the [`LfuPolicy`].
+struct LfuEntry {
+    size: usize,
+    frequency: u8,
+    /// The time of the last access to the entry, used to break frequency ties.
+    tick: u64,
+}
+
+/// Evicts the least frequently used entries first, and the least recently used ones
+/// among those with the same frequency.
+#[derive(Default)]
+struct LfuPolicy {
+    entries: HashMap<Vec<u8>, LfuEntry>,
+    /// The keys, ordered by frequency and then by time of last access.
+    order: BTreeMap<(u8, u64), Vec<u8>>,
+    next_tick: u64,
+    accesses_since_aging: usize,
+}
+
+impl LfuPolicy {
+    /// Records an access to the key, with its new size and frequency.
+    fn record(&mut self, key: Vec<u8>, size: usize, frequency: u8) {
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        self.order.insert((frequency, tick), key.clone());
+        self.entries.insert(
+            key,
+            LfuEntry {
+                size,
+                frequency,
+                tick,
+            },
+        );
+        self.accesses_since_aging += 1;
+        if self.accesses_since_aging >= LFU_AGING_PERIOD * self.entries.len() {
+            self.age();
+        }
+    }
+
+    /// Halves the frequencies, keeping the order of the last accesses.
+    fn age(&mut self) {
+        self.accesses_since_aging = 0;
+        self.order.clear();
+        for (key, entry) in &mut self.entries {
+            entry.frequency = entry.frequency.div_ceil(2);
+            self.order
+                .insert((entry.frequency, entry.tick), key.clone());
+        }
+    }
+
+    /// Removes the entry of the key from the order, and returns its frequency and size.
+    fn take(&mut self, key: &[u8]) -> Option<(u8, usize)> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&(entry.frequency, entry.tick));
+        Some((entry.frequency, entry.size))
+    }
+}
+
+impl CachePolicy for LfuPolicy {
+    fn insert(&mut self, key: Vec<u8>, size: usize) -> Option<usize> {
+        match self.take(&key) {
+            Some((frequency, old_size)) => {
+                let frequency = (frequency + 1).min(LFU_MAX_FREQUENCY);
+                self.record(key, size, frequency);
+                Some(old_size)
+            }
+            None => {
+                self.record(key, size, 1);
+                None
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &[u8]) {
+        if let Some((frequency, size)) = self.take(key) {
+            let frequency = (frequency + 1).min(LFU_MAX_FREQUENCY);
+            self.record(key.to_vec(), size, frequency);
+        }
+    }
+
+    fn resize(&mut self, key: &[u8], size: usize) -> Option<usize> {
+        let entry = self.entries.get_mut(key)?;
+        Some(std::mem::replace(&mut entry.size, size))
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Option<usize> {
+        self.take(key).map(|(_, size)| size)
+    }
+
+    fn pop_victim(&mut self) -> Option<(Vec<u8>, usize)> {
+        let (_, key) = self.order.pop_first()?;
+        let entry = self.entries.remove(&key)?;
+        Some((key, entry.size))
+    }
+
+    fn num_entries(&self) -> usize {
+        self.entries.len()
+    }
+}
+
 enum CacheEntry {
     DoesNotExist,
     Exists,
@@ -98,161 +378,457 @@ impl CacheEntry {
     }
 }
 
-/// Stores the data for simple `read_values` queries.
-///
-/// This data structure is inspired by the crate `lru-cache` but was modified to support
-/// range deletions.
-struct LruPrefixCache {
-    map: BTreeMap<Vec<u8>, CacheEntry>,
-    queue: LinkedHashMap<Vec<u8>, usize, RandomState>,
-    storage_cache_config: StorageCacheConfig,
+/// A part of the cache, with its own size limits and eviction policy.
+struct CacheSegment<V> {
+    map: BTreeMap<Vec<u8>, V>,
+    policy: Box<dyn CachePolicy>,
     total_size: usize,
-    /// Whether we have exclusive R/W access to the keys under the root key of the store.
-    has_exclusive_access: bool,
+    max_size: usize,
+    max_entries: usize,
 }
 
-impl LruPrefixCache {
-    /// Creates an `LruPrefixCache`.
-    pub fn new(storage_cache_config: StorageCacheConfig) -> Self {
+impl<V> CacheSegment<V> {
+    fn new(policy: Box<dyn CachePolicy>, max_size: usize, max_entries: usize) -> Self {
         Self {
             map: BTreeMap::new(),
-            queue: LinkedHashMap::new(),
-            storage_cache_config,
+            policy,
             total_size: 0,
-            has_exclusive_access: false,
+            max_size,
+            max_entries,
         }
     }
 
-    /// Trim the cache so that it fits within the constraints.
-    fn trim_cache(&mut self) {
-        while self.total_size > self.storage_cache_config.max_cache_size
-            || self.queue.len() > self.storage_cache_config.max_cache_entries
-        {
-            let Some((key, key_value_size)) = self.queue.pop_front() else {
+    /// Inserts or replaces an entry of the given size, then trims the segment so that it
+    /// fits within its limits.
+    fn insert(&mut self, key: Vec<u8>, value: V, size: usize) {
+        if let Some(old_size) = self.policy.insert(key.clone(), size) {
+            self.total_size -= old_size;
+        }
+        self.total_size += size;
+        self.map.insert(key, value);
+        self.trim();
+    }
+
+    /// Returns the entry, if any, and counts it as an access.
+    fn get(&mut self, key: &[u8]) -> Option<&V> {
+        let value = self.map.get(key)?;
+        self.policy.touch(key);
+        Some(value)
+    }
+
+    /// Forgets about an entry.
+    fn remove(&mut self, key: &[u8]) {
+        if self.map.remove(key).is_some() {
+            let size = self.policy.remove(key).expect("cached entries have a size");
+            self.total_size -= size;
+        }
+    }
+
+    /// Forgets about the entries that match the prefix.
+    fn remove_prefix(&mut self, key_prefix: &[u8]) {
+        let keys = self
+            .map
+            .range(get_interval(key_prefix.to_vec()))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
+    /// Evicts entries until the segment fits within its limits.
+    fn trim(&mut self) {
+        while self.total_size > self.max_size || self.policy.num_entries() > self.max_entries {
+            let Some((key, size)) = self.policy.pop_victim() else {
                 break;
             };
             self.map.remove(&key);
-            self.total_size -= key_value_size;
+            self.total_size -= size;
+            #[cfg(with_metrics)]
+            CACHE_EVICTION_COUNT.with_label_values(&[]).inc();
+        }
+    }
+}
+
+impl CacheSegment<CacheEntry> {
+    /// Marks the entries that match the prefix as deleted. Importantly, this does not
+    /// create new entries.
+    fn mark_prefix_deleted(&mut self, key_prefix: &[u8]) {
+        for (key, value) in self.map.range_mut(get_interval(key_prefix.to_vec())) {
+            let old_size = self
+                .policy
+                .resize(key, key.len())
+                .expect("cached entries have a size");
+            self.total_size -= old_size;
+            self.total_size += key.len();
+            *value = CacheEntry::DoesNotExist;
+        }
+    }
+}
+
+/// Stores the data for simple `read_values` queries, and for `find_keys_by_prefix` queries
+/// if the policy is segmented.
+///
+/// The cache is shared by the stores of a namespace. Its keys start with the prefix of the
+/// root key of the store that accessed them, see [`cache_prefix`].
+struct StorageCache {
+    storage_cache_config: StorageCacheConfig,
+    values: CacheSegment<CacheEntry>,
+    /// The results of `find_keys_by_prefix`, indexed by the prefix.
+    find_keys: Option<CacheSegment<Vec<Vec<u8>>>>,
+    /// The keys last written with a TTL, with the time after which they have expired. They
+    /// are not cached until then, since their values may expire at any time.
+    expiring_keys: BTreeMap<Vec<u8>, Instant>,
+    /// The keys of `expiring_keys`, ordered by expiration time.
+    expirations: BTreeSet<(Instant, Vec<u8>)>,
+    /// The keys being prefetched, with the number of the prefetch reading them. Writing a
+    /// key removes it, so that the value read by the prefetch, which may be older than the
+    /// write, is not cached.
+    prefetching: BTreeMap<Vec<u8>, u64>,
+    /// The number of the next prefetch.
+    next_prefetch: u64,
+    /// A buffer for the keys that are only looked up, to avoid allocating them.
+    lookup_key: Vec<u8>,
+}
+
+impl StorageCache {
+    /// Creates a `StorageCache`.
+    fn new(storage_cache_config: StorageCacheConfig) -> Self {
+        let max_size = storage_cache_config.max_cache_size;
+        let max_entries = storage_cache_config.max_cache_entries;
+        let (values, find_keys) = match storage_cache_config.cache_policy {
+            CachePolicyKind::Lru => {
+                let values = CacheSegment::new(Box::<LruPolicy>::default(), max_size, max_entries);
+                (values, None)
+            }
+            CachePolicyKind::Lfu => {
+                let values = CacheSegment::new(Box::<LfuPolicy>::default(), max_size, max_entries);
+                (values, None)
+            }
+            CachePolicyKind::Segmented => {
+                let find_keys_size = max_size / FIND_KEYS_SEGMENT_DIVISOR;
+                let values = CacheSegment::new(
+                    Box::<LruPolicy>::default(),
+                    max_size - find_keys_size,
+                    max_entries,
+                );
+                let find_keys =
+                    CacheSegment::new(Box::<LruPolicy>::default(), find_keys_size, max_entries);
+                (values, Some(find_keys))
+            }
+        };
+        Self {
+            storage_cache_config,
+            values,
+            find_keys,
+            expiring_keys: BTreeMap::new(),
+            expirations: BTreeSet::new(),
+            prefetching: BTreeMap::new(),
+            next_prefetch: 0,
+            lookup_key: Vec::new(),
         }
     }
 
+    /// Calls `f` with the key of the cache made of `cache_prefix` and `key`, without
+    /// allocating it.
+    fn with_lookup_key<R>(
+        &mut self,
+        cache_prefix: &[u8],
+        key: &[u8],
+        f: impl FnOnce(&mut Self, &[u8]) -> R,
+    ) -> R {
+        let mut lookup_key = std::mem::take(&mut self.lookup_key);
+        lookup_key.clear();
+        lookup_key.extend_from_slice(cache_prefix);
+        lookup_key.extend_from_slice(key);
+        let result = f(self, &lookup_key);
+        self.lookup_key = lookup_key;
+        result
+    }
+
     /// Inserts an entry into the cache.
-    pub fn insert(&mut self, key: Vec<u8>, cache_entry: CacheEntry) {
+    fn insert(&mut self, key: Vec<u8>, cache_entry: CacheEntry, has_exclusive_access: bool) {
+        self.prune_expiring();
         let key_value_size = key.len() + cache_entry.size();
-        if (matches!(cache_entry, CacheEntry::DoesNotExist) && !self.has_exclusive_access)
+        if (matches!(cache_entry, CacheEntry::DoesNotExist) && !has_exclusive_access)
             || key_value_size > self.storage_cache_config.max_entry_size
+            || self.expiring_keys.contains_key(&key)
         {
             // Just forget about the entry.
-            if let Some(old_key_value_size) = self.queue.remove(&key) {
-                self.total_size -= old_key_value_size;
-                self.map.remove(&key);
-            };
+            self.values.remove(&key);
             return;
         }
-        match self.map.entry(key.clone()) {
-            btree_map::Entry::Occupied(mut entry) => {
-                entry.insert(cache_entry);
-                // Put it on first position for LRU
-                let old_key_value_size = self.queue.remove(&key).expect("old_key_value_size");
-                self.total_size -= old_key_value_size;
-                self.queue.insert(key, key_value_size);
-                self.total_size += key_value_size;
-            }
-            btree_map::Entry::Vacant(entry) => {
-                entry.insert(cache_entry);
-                self.queue.insert(key, key_value_size);
-                self.total_size += key_value_size;
-            }
-        }
-        self.trim_cache();
+        self.values.insert(key, cache_entry, key_value_size);
     }
 
     /// Inserts a read_value entry into the cache.
-    pub fn insert_read_value(&mut self, key: Vec<u8>, value: &Option<Vec<u8>>) {
+    fn insert_read_value(
+        &mut self,
+        key: Vec<u8>,
+        value: &Option<Vec<u8>>,
+        has_exclusive_access: bool,
+    ) {
         let cache_entry = match value {
             None => CacheEntry::DoesNotExist,
             Some(vec) => CacheEntry::Value(vec.to_vec()),
         };
-        self.insert(key, cache_entry)
+        self.insert(key, cache_entry, has_exclusive_access)
     }
 
     /// Inserts a read_value entry into the cache.
-    pub fn insert_contains_key(&mut self, key: Vec<u8>, result: bool) {
+    fn insert_contains_key(&mut self, key: Vec<u8>, result: bool, has_exclusive_access: bool) {
         let cache_entry = match result {
             false => CacheEntry::DoesNotExist,
             true => CacheEntry::Exists,
         };
-        self.insert(key, cache_entry)
+        self.insert(key, cache_entry, has_exclusive_access)
+    }
+
+    /// Inserts an entry written by the store into the cache.
+    fn insert_written(
+        &mut self,
+        key: Vec<u8>,
+        cache_entry: CacheEntry,
+        has_exclusive_access: bool,
+    ) {
+        self.remove_expiring(&key);
+        self.prefetching.remove(&key);
+        self.insert(key, cache_entry, has_exclusive_access)
+    }
+
+    /// Forgets about a key written with the given TTL, and stops caching it until it is
+    /// written again without a TTL, or has expired.
+    fn insert_expiring(&mut self, key: Vec<u8>, ttl: Duration) {
+        self.prune_expiring();
+        self.values.remove(&key);
+        self.prefetching.remove(&key);
+        self.remove_expiring(&key);
+        let expiration = Instant::now() + ttl + EXPIRATION_MARGIN;
+        self.expirations.insert((expiration, key.clone()));
+        self.expiring_keys.insert(key, expiration);
+    }
+
+    /// Stops treating the key as written with a TTL.
+    fn remove_expiring(&mut self, key: &[u8]) {
+        if let Some(expiration) = self.expiring_keys.remove(key) {
+            self.expirations.remove(&(expiration, key.to_vec()));
+        }
+    }
+
+    /// Forgets about the keys written with a TTL that have expired, so that they can be
+    /// cached again.
+    fn prune_expiring(&mut self) {
+        if self.expirations.is_empty() {
+            return;
+        }
+        let pending = self.expirations.split_off(&(Instant::now(), Vec::new()));
+        let expired = std::mem::replace(&mut self.expirations, pending);
+        for (_, key) in expired {
+            self.expiring_keys.remove(&key);
+        }
     }
 
     /// Marks cached keys that match the prefix as deleted. Importantly, this does not
     /// create new entries in the cache.
-    pub fn delete_prefix(&mut self, key_prefix: &[u8]) {
-        if self.has_exclusive_access {
-            for (key, value) in self.map.range_mut(get_interval(key_prefix.to_vec())) {
-                *self.queue.get_mut(key).unwrap() = key.len();
-                self.total_size -= value.size();
-                *value = CacheEntry::DoesNotExist;
-            }
+    fn delete_prefix(&mut self, key_prefix: &[u8], has_exclusive_access: bool) {
+        let expiring_keys = self
+            .expiring_keys
+            .range(get_interval(key_prefix.to_vec()))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in expiring_keys {
+            self.remove_expiring(&key);
+        }
+        let prefetching = self
+            .prefetching
+            .range(get_interval(key_prefix.to_vec()))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in prefetching {
+            self.prefetching.remove(&key);
+        }
+        if has_exclusive_access {
+            self.values.mark_prefix_deleted(key_prefix);
         } else {
             // Just forget about the entries.
-            let mut keys = Vec::new();
-            for (key, _) in self.map.range(get_interval(key_prefix.to_vec())) {
-                keys.push(key.to_vec());
-            }
-            for key in keys {
-                self.map.remove(&key);
-                let Some(key_value_size) = self.queue.remove(&key) else {
-                    unreachable!("The key should be in the queue");
-                };
-                self.total_size -= key_value_size;
+            self.values.remove_prefix(key_prefix);
+        }
+    }
+
+    /// Registers a prefetch of the given keys, skipping the ones that are already cached,
+    /// and returns its number together with the keys to read.
+    fn start_prefetch(&mut self, keys: Vec<Vec<u8>>) -> (u64, Vec<Vec<u8>>) {
+        let number = self.next_prefetch;
+        self.next_prefetch += 1;
+        let keys = keys
+            .into_iter()
+            .filter(|key| !self.values.map.contains_key(key))
+            .collect::<Vec<_>>();
+        for key in &keys {
+            self.prefetching.insert(key.clone(), number);
+        }
+        (number, keys)
+    }
+
+    /// Inserts a value read by the prefetch with the given number, unless the key was
+    /// written or prefetched again in the meantime, or was read by another query.
+    fn finish_prefetch(
+        &mut self,
+        number: u64,
+        key: Vec<u8>,
+        value: &Option<Vec<u8>>,
+        has_exclusive_access: bool,
+    ) {
+        if self.prefetching.get(&key) != Some(&number) {
+            return;
+        }
+        self.prefetching.remove(&key);
+        if !self.values.map.contains_key(&key) {
+            self.insert_read_value(key, value, has_exclusive_access);
+        }
+    }
+
+    /// Forgets about a prefetch that failed.
+    fn abort_prefetch(&mut self, number: u64, keys: &[Vec<u8>]) {
+        for key in keys {
+            if self.prefetching.get(key) == Some(&number) {
+                self.prefetching.remove(key);
             }
         }
     }
 
+    /// Forgets about the results of `find_keys_by_prefix` for the prefixes that start with
+    /// `key_prefix`.
+    fn invalidate_find_keys(&mut self, key_prefix: &[u8]) {
+        if let Some(find_keys) = &mut self.find_keys {
+            find_keys.remove_prefix(key_prefix);
+        }
+    }
+
     /// Returns the cached value, or `Some(None)` if the entry does not exist in the
     /// database. If `None` is returned, the entry might exist in the database but is
     /// not in the cache.
-    pub fn query_read_value(&mut self, key: &[u8]) -> Option<Option<Vec<u8>>> {
-        let result = match self.map.get(key) {
-            None => None,
-            Some(entry) => match entry {
-                CacheEntry::DoesNotExist => Some(None),
-                CacheEntry::Exists => None,
-                CacheEntry::Value(vec) => Some(Some(vec.clone())),
-            },
-        };
-        if result.is_some() {
-            // Put back the key on top
-            let key_value_size = self.queue.remove(key).expect("key_value_size");
-            self.queue.insert(key.to_vec(), key_value_size);
+    fn query_read_value(&mut self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        match self.values.get(key)? {
+            CacheEntry::DoesNotExist => Some(None),
+            CacheEntry::Exists => None,
+            CacheEntry::Value(vec) => Some(Some(vec.clone())),
         }
-        result
     }
 
     /// Returns `Some(true)` or `Some(false)` if we know that the entry does or does not
     /// exist in the database. Returns `None` if that information is not in the cache.
-    pub fn query_contains_key(&mut self, key: &[u8]) -> Option<bool> {
-        let result = self
-            .map
+    fn query_contains_key(&mut self, key: &[u8]) -> Option<bool> {
+        self.values
             .get(key)
-            .map(|entry| !matches!(entry, CacheEntry::DoesNotExist));
-        if result.is_some() {
-            // Put back the key on top
-            let key_value_size = self.queue.remove(key).expect("key_value_size");
-            self.queue.insert(key.to_vec(), key_value_size);
+            .map(|entry| !matches!(entry, CacheEntry::DoesNotExist))
+    }
+
+    /// Whether the results of `find_keys_by_prefix` are cached.
+    fn caches_find_keys(&self) -> bool {
+        self.find_keys.is_some()
+    }
+
+    /// Returns the cached result of `find_keys_by_prefix`, if any.
+    fn query_find_keys(&mut self, key_prefix: &[u8]) -> Option<Vec<Vec<u8>>> {
+        self.find_keys.as_mut()?.get(key_prefix).cloned()
+    }
+
+    /// Inserts a result of `find_keys_by_prefix` into the cache. The results containing
+    /// keys written with a TTL are not cached, since the keys may expire at any time.
+    fn insert_find_keys(&mut self, key_prefix: Vec<u8>, keys: &[Vec<u8>]) {
+        self.prune_expiring();
+        let Some(find_keys) = &mut self.find_keys else {
+            return;
+        };
+        let size = key_prefix.len() + keys.iter().map(Vec::len).sum::<usize>();
+        if size > self.storage_cache_config.max_entry_size
+            || self
+                .expiring_keys
+                .range(get_interval(key_prefix.clone()))
+                .next()
+                .is_some()
+        {
+            find_keys.remove(&key_prefix);
+            return;
+        }
+        find_keys.insert(key_prefix, keys.to_vec(), size);
+    }
+}
+
+/// Returns the prefix of the keys of a store with the given root key in the cache. The
+/// length of the root key comes first, so that no prefix is the start of another one.
+fn cache_prefix(root_key: &[u8]) -> Vec<u8> {
+    let length = u32::try_from(root_key.len()).expect("root keys are small");
+    let mut prefix = length.to_be_bytes().to_vec();
+    prefix.extend_from_slice(root_key);
+    prefix
+}
+
+/// A set of keys returned by [`LruCachingStore::find_keys_by_prefix`].
+pub enum LruCachingKeys<K, E> {
+    /// The keys returned by the inner store, when the results are not cached.
+    Store(K),
+    /// The keys read from the cache, or inserted into it.
+    Cached(Vec<Vec<u8>>, PhantomData<E>),
+}
+
+/// An iterator over the keys in [`LruCachingKeys`].
+pub enum LruCachingKeyIterator<'a, I, E> {
+    /// Iterating over the keys returned by the inner store.
+    Store(I),
+    /// Iterating over the cached keys.
+    Cached(SimpleKeyIterator<'a, E>),
+}
+
+impl<E, K> KeyIterable<E> for LruCachingKeys<K, E>
+where
+    K: KeyIterable<E>,
+{
+    type Iterator<'a>
+        = LruCachingKeyIterator<'a, K::Iterator<'a>, E>
+    where
+        K: 'a,
+        E: 'a;
+
+    fn iterator(&self) -> Self::Iterator<'_> {
+        match self {
+            Self::Store(keys) => LruCachingKeyIterator::Store(keys.iterator()),
+            Self::Cached(keys, _) => {
+                LruCachingKeyIterator::Cached(KeyIterable::<E>::iterator(keys))
+            }
+        }
+    }
+}
+
+impl<'a, I, E> Iterator for LruCachingKeyIterator<'a, I, E>
+where
+    I: Iterator<Item = Result<&'a [u8], E>>,
+{
+    type Item = Result<&'a [u8], E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Store(iter) => iter.next(),
+            Self::Cached(iter) => iter.next(),
         }
-        result
     }
 }
 
-/// We take a store, a maximum size and build a LRU-based system.
+/// We take a store, a maximum size and build a caching system, by default LRU-based.
+///
+/// The stores obtained with `clone_with_root_key` share the cache of the store they were
+/// cloned from, so the size limits of the cache apply to the whole namespace.
 #[derive(Clone)]
 pub struct LruCachingStore<K> {
     /// The inner store that is called by the LRU cache one
     store: K,
-    /// The LRU cache of values.
-    cache: Option<Arc<Mutex<LruPrefixCache>>>,
+    /// The cache of values, shared with the clones of the store.
+    cache: Option<Arc<Mutex<StorageCache>>>,
+    /// The prefix of the keys of this store in the cache.
+    cache_prefix: Vec<u8>,
+    /// Whether we have exclusive R/W access to the keys under the root key of the store.
+    has_exclusive_access: bool,
 }
 
 impl<K> WithError for LruCachingStore<K>
@@ -264,17 +840,61 @@ where
 
 impl<K> ReadableKeyValueStore for LruCachingStore<K>
 where
-    K: ReadableKeyValueStore + Send + Sync,
+    K: ReadableKeyValueStore + Clone + Send + Sync + 'static,
 {
     // The LRU cache does not change the underlying store's size limits.
     const MAX_KEY_SIZE: usize = K::MAX_KEY_SIZE;
-    type Keys = K::Keys;
+    type Keys = LruCachingKeys<K::Keys, K::Error>;
     type KeyValues = K::KeyValues;
 
     fn max_stream_queries(&self) -> usize {
         self.store.max_stream_queries()
     }
 
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        #[cfg(not(web))]
+        if tokio::runtime::Handle::try_current().is_err() {
+            // Without a runtime, the values are simply read when they are needed.
+            return;
+        }
+        let cache_keys = keys.iter().map(|key| self.cache_key(key)).collect();
+        let (number, cache_keys) = cache.lock().unwrap().start_prefetch(cache_keys);
+        if cache_keys.is_empty() {
+            return;
+        }
+        let prefix_len = self.cache_prefix.len();
+        let keys = cache_keys
+            .iter()
+            .map(|cache_key| cache_key[prefix_len..].to_vec())
+            .collect::<Vec<_>>();
+        #[cfg(with_metrics)]
+        PREFETCHED_KEY_COUNT
+            .with_label_values(&[])
+            .inc_by(keys.len() as u64);
+        let store = self.store.clone();
+        let cache = cache.clone();
+        let has_exclusive_access = self.has_exclusive_access;
+        // The values written while the prefetch is running are not overwritten, see
+        // `StorageCache::finish_prefetch`.
+        linera_base::task::spawn(async move {
+            match store.read_multi_values_bytes(keys).await {
+                Ok(values) => {
+                    let mut cache = cache.lock().unwrap();
+                    for (cache_key, value) in cache_keys.into_iter().zip(values) {
+                        cache.finish_prefetch(number, cache_key, &value, has_exclusive_access);
+                    }
+                }
+                Err(error) => {
+                    tracing::debug!("Prefetching values failed: {error}");
+                    cache.lock().unwrap().abort_prefetch(number, &cache_keys);
+                }
+            }
+        });
+    }
+
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
         let Some(cache) = &self.cache else {
             return self.store.read_value_bytes(key).await;
@@ -282,7 +902,9 @@ where
         // First inquiring in the read_value_bytes LRU
         {
             let mut cache = cache.lock().unwrap();
-            if let Some(value) = cache.query_read_value(key) {
+            if let Some(value) = cache.with_lookup_key(&self.cache_prefix, key, |cache, key| {
+                cache.query_read_value(key)
+            }) {
                 #[cfg(with_metrics)]
                 READ_VALUE_CACHE_HIT_COUNT.with_label_values(&[]).inc();
                 return Ok(value);
@@ -292,7 +914,7 @@ where
         READ_VALUE_CACHE_MISS_COUNT.with_label_values(&[]).inc();
         let value = self.store.read_value_bytes(key).await?;
         let mut cache = cache.lock().unwrap();
-        cache.insert_read_value(key.to_vec(), &value);
+        cache.insert_read_value(self.cache_key(key), &value, self.has_exclusive_access);
         Ok(value)
     }
 
@@ -302,7 +924,9 @@ where
         };
         {
             let mut cache = cache.lock().unwrap();
-            if let Some(value) = cache.query_contains_key(key) {
+            if let Some(value) = cache.with_lookup_key(&self.cache_prefix, key, |cache, key| {
+                cache.query_contains_key(key)
+            }) {
                 #[cfg(with_metrics)]
                 CONTAINS_KEY_CACHE_HIT_COUNT.with_label_values(&[]).inc();
                 return Ok(value);
@@ -312,7 +936,7 @@ where
         CONTAINS_KEY_CACHE_MISS_COUNT.with_label_values(&[]).inc();
         let result = self.store.contains_key(key).await?;
         let mut cache = cache.lock().unwrap();
-        cache.insert_contains_key(key.to_vec(), result);
+        cache.insert_contains_key(self.cache_key(key), result, self.has_exclusive_access);
         Ok(result)
     }
 
@@ -327,7 +951,11 @@ where
         {
             let mut cache = cache.lock().unwrap();
             for i in 0..size {
-                if let Some(value) = cache.query_contains_key(&keys[i]) {
+                if let Some(value) =
+                    cache.with_lookup_key(&self.cache_prefix, &keys[i], |cache, key| {
+                        cache.query_contains_key(key)
+                    })
+                {
                     #[cfg(with_metrics)]
                     CONTAINS_KEY_CACHE_HIT_COUNT.with_label_values(&[]).inc();
                     results[i] = value;
@@ -344,7 +972,7 @@ where
             let mut cache = cache.lock().unwrap();
             for ((index, result), key) in indices.into_iter().zip(key_results).zip(key_requests) {
                 results[index] = result;
-                cache.insert_contains_key(key, result);
+                cache.insert_contains_key(self.cache_key(&key), result, self.has_exclusive_access);
             }
         }
         Ok(results)
@@ -364,7 +992,11 @@ where
         {
             let mut cache = cache.lock().unwrap();
             for (i, key) in keys.into_iter().enumerate() {
-                if let Some(value) = cache.query_read_value(&key) {
+                if let Some(value) =
+                    cache.with_lookup_key(&self.cache_prefix, &key, |cache, key| {
+                        cache.query_read_value(key)
+                    })
+                {
                     #[cfg(with_metrics)]
                     READ_VALUE_CACHE_HIT_COUNT.with_label_values(&[]).inc();
                     result.push(value);
@@ -387,7 +1019,7 @@ where
                 .into_iter()
                 .zip(miss_keys.into_iter().zip(values))
             {
-                cache.insert_read_value(key, &value);
+                cache.insert_read_value(self.cache_key(&key), &value, self.has_exclusive_access);
                 result[i] = value;
             }
         }
@@ -395,7 +1027,34 @@ where
     }
 
     async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
-        self.store.find_keys_by_prefix(key_prefix).await
+        // Without exclusive access, keys may be added by other clients at any time.
+        let caches_find_keys = self.has_exclusive_access
+            && self
+                .cache
+                .as_ref()
+                .is_some_and(|cache| cache.lock().unwrap().caches_find_keys());
+        let Some(cache) = self.cache.as_ref().filter(|_| caches_find_keys) else {
+            let keys = self.store.find_keys_by_prefix(key_prefix).await?;
+            return Ok(LruCachingKeys::Store(keys));
+        };
+        {
+            let mut cache = cache.lock().unwrap();
+            if let Some(keys) =
+                cache.with_lookup_key(&self.cache_prefix, key_prefix, |cache, key| {
+                    cache.query_find_keys(key)
+                })
+            {
+                #[cfg(with_metrics)]
+                FIND_KEYS_CACHE_HIT_COUNT.with_label_values(&[]).inc();
+                return Ok(LruCachingKeys::Cached(keys, PhantomData));
+            }
+        }
+        #[cfg(with_metrics)]
+        FIND_KEYS_CACHE_MISS_COUNT.with_label_values(&[]).inc();
+        let keys = self.read_keys_by_prefix(key_prefix).await?;
+        let mut cache = cache.lock().unwrap();
+        cache.insert_find_keys(self.cache_key(key_prefix), &keys);
+        Ok(LruCachingKeys::Cached(keys, PhantomData))
     }
 
     async fn find_key_values_by_prefix(
@@ -404,6 +1063,19 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        // Pages are always read from the store: the cached searches contain all the keys
+        // matching a prefix.
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
 }
 
 impl<K> WritableKeyValueStore for LruCachingStore<K>
@@ -412,31 +1084,59 @@ where
 {
     // The LRU cache does not change the underlying store's size limits.
     const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = K::SUPPORTS_TTL;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
         let Some(cache) = &self.cache else {
-            return self.store.write_batch(batch).await;
+            return self.store.write_batch_with_ttl(batch, ttl).await;
         };
 
         {
             let mut cache = cache.lock().unwrap();
+            let expiring_ttl = ttl.filter(|_| K::SUPPORTS_TTL);
             for operation in &batch.operations {
-                match operation {
-                    WriteOperation::Put { key, value } => {
+                match (operation, expiring_ttl) {
+                    (WriteOperation::Put { key, .. }, Some(ttl)) => {
+                        cache.insert_expiring(self.cache_key(key), ttl);
+                    }
+                    (WriteOperation::Put { key, value }, None) => {
                         let cache_entry = CacheEntry::Value(value.to_vec());
-                        cache.insert(key.to_vec(), cache_entry);
+                        cache.insert_written(
+                            self.cache_key(key),
+                            cache_entry,
+                            self.has_exclusive_access,
+                        );
                     }
-                    WriteOperation::Delete { key } => {
+                    (WriteOperation::Delete { key }, _) => {
                         let cache_entry = CacheEntry::DoesNotExist;
-                        cache.insert(key.to_vec(), cache_entry);
+                        cache.insert_written(
+                            self.cache_key(key),
+                            cache_entry,
+                            self.has_exclusive_access,
+                        );
                     }
-                    WriteOperation::DeletePrefix { key_prefix } => {
-                        cache.delete_prefix(key_prefix);
+                    (WriteOperation::DeletePrefix { key_prefix }, _) => {
+                        cache.with_lookup_key(&self.cache_prefix, key_prefix, |cache, key| {
+                            cache.delete_prefix(key, self.has_exclusive_access)
+                        });
                     }
                 }
             }
+            // The results of `find_keys_by_prefix` are not updated by the writes, so the
+            // ones of the root key are all invalidated.
+            if !batch.operations.is_empty() {
+                cache.invalidate_find_keys(&self.cache_prefix);
+            }
         }
-        self.store.write_batch(batch).await
+        self.store.write_batch_with_ttl(batch, ttl).await
     }
 
     async fn clear_journal(&self) -> Result<(), Self::Error> {
@@ -473,9 +1173,28 @@ where
 
     fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
         let store = self.store.clone_with_root_key(root_key)?;
-        let store = LruCachingStore::new(store, self.storage_cache_config());
-        store.enable_exclusive_access();
-        Ok(store)
+        Ok(LruCachingStore {
+            store,
+            cache: self.cache.clone(),
+            cache_prefix: cache_prefix(root_key),
+            has_exclusive_access: true,
+        })
+    }
+
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        // The values read from stale replicas must not be served to the other clones.
+        let cache = if bounded_staleness {
+            None
+        } else {
+            self.cache.clone()
+        };
+        LruCachingStore {
+            store,
+            cache,
+            cache_prefix: self.cache_prefix.clone(),
+            has_exclusive_access: self.has_exclusive_access,
+        }
     }
 
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
@@ -509,7 +1228,7 @@ where
 #[cfg(with_testing)]
 impl<K> TestKeyValueStore for LruCachingStore<K>
 where
-    K: TestKeyValueStore + Send + Sync,
+    K: TestKeyValueStore + Clone + Send + Sync + 'static,
 {
     async fn new_test_config() -> Result<LruCachingConfig<K::Config>, K::Error> {
         let inner_config = K::new_test_config().await?;
@@ -523,17 +1242,32 @@ where
 
 impl<K> LruCachingStore<K> {
     /// Creates a new key-value store that provides LRU caching at top of the given store.
+    /// The store is expected to have the empty root key, as the stores returned by
+    /// `connect` do.
     pub fn new(store: K, storage_cache_config: StorageCacheConfig) -> Self {
         let cache = {
             if storage_cache_config.max_cache_entries == 0 {
                 None
             } else {
-                Some(Arc::new(Mutex::new(LruPrefixCache::new(
+                Some(Arc::new(Mutex::new(StorageCache::new(
                     storage_cache_config,
                 ))))
             }
         };
-        Self { store, cache }
+        Self {
+            store,
+            cache,
+            cache_prefix: cache_prefix(&[]),
+            has_exclusive_access: false,
+        }
+    }
+
+    /// Returns the key of the cache for the given key of the store, to insert it. The keys
+    /// that are only looked up use [`StorageCache::with_lookup_key`] instead.
+    fn cache_key(&self, key: &[u8]) -> Vec<u8> {
+        let mut cache_key = self.cache_prefix.clone();
+        cache_key.extend_from_slice(key);
+        cache_key
     }
 
     /// Gets the `cache_size`.
@@ -543,6 +1277,7 @@ impl<K> LruCachingStore<K> {
                 max_cache_size: 0,
                 max_entry_size: 0,
                 max_cache_entries: 0,
+                cache_policy: CachePolicyKind::default(),
             },
             Some(cache) => {
                 let cache = cache.lock().unwrap();
@@ -551,15 +1286,193 @@ impl<K> LruCachingStore<K> {
         }
     }
 
-    /// Sets the value `has_exclusive_access` to `true`, if applicable.
-    pub fn enable_exclusive_access(&self) {
-        if let Some(cache) = &self.cache {
-            let mut cache = cache.lock().unwrap();
-            cache.has_exclusive_access = true;
-        }
+    /// Sets the value `has_exclusive_access` to `true`.
+    pub fn enable_exclusive_access(&mut self) {
+        self.has_exclusive_access = true;
+    }
+}
+
+impl<K> LruCachingStore<K>
+where
+    K: ReadableKeyValueStore,
+{
+    /// Reads the keys that match the prefix from the inner store.
+    async fn read_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Vec<Vec<u8>>, K::Error> {
+        let keys = self.store.find_keys_by_prefix(key_prefix).await?;
+        keys.iterator().map(|key| key.map(<[u8]>::to_vec)).collect()
     }
 }
 
 /// A memory store with caching.
 #[cfg(with_testing)]
 pub type LruCachingMemoryStore = LruCachingStore<MemoryStore>;
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::memory::{create_test_memory_store, MemoryStore};
+
+    fn create_caching_store(
+        cache_policy: CachePolicyKind,
+    ) -> (MemoryStore, LruCachingStore<MemoryStore>) {
+        let store = create_test_memory_store();
+        let storage_cache_config = StorageCacheConfig {
+            cache_policy,
+            ..DEFAULT_STORAGE_CACHE_CONFIG
+        };
+        let caching_store = LruCachingStore::new(store.clone(), storage_cache_config);
+        (store, caching_store)
+    }
+
+    async fn write_value(store: &impl WritableKeyValueStore, key: &[u8], value: &[u8]) {
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(key.to_vec(), value.to_vec());
+        store.write_batch(batch).await.unwrap();
+    }
+
+    async fn find_keys(store: &LruCachingStore<MemoryStore>, key_prefix: &[u8]) -> Vec<Vec<u8>> {
+        let keys = store.find_keys_by_prefix(key_prefix).await.unwrap();
+        keys.iterator().map(|key| key.unwrap().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_lfu_policy_keeps_frequently_read_entries() {
+        let mut cache = StorageCache::new(StorageCacheConfig {
+            max_cache_entries: 2,
+            cache_policy: CachePolicyKind::Lfu,
+            ..DEFAULT_STORAGE_CACHE_CONFIG
+        });
+        cache.insert_read_value(vec![1], &Some(vec![1]), false);
+        cache.insert_read_value(vec![2], &Some(vec![2]), false);
+        for _ in 0..3 {
+            assert_eq!(cache.query_read_value(&[1]), Some(Some(vec![1])));
+        }
+        cache.insert_read_value(vec![3], &Some(vec![3]), false);
+        assert_eq!(cache.query_read_value(&[1]), Some(Some(vec![1])));
+        assert_eq!(cache.query_read_value(&[2]), None);
+
+        let mut cache = StorageCache::new(StorageCacheConfig {
+            max_cache_entries: 2,
+            ..DEFAULT_STORAGE_CACHE_CONFIG
+        });
+        cache.insert_read_value(vec![1], &Some(vec![1]), false);
+        cache.insert_read_value(vec![2], &Some(vec![2]), false);
+        for _ in 0..3 {
+            assert_eq!(cache.query_read_value(&[1]), Some(Some(vec![1])));
+        }
+        assert_eq!(cache.query_read_value(&[2]), Some(Some(vec![2])));
+        cache.insert_read_value(vec![3], &Some(vec![3]), false);
+        assert_eq!(cache.query_read_value(&[1]), None);
+    }
+
+    #[test]
+    fn test_cache_size_is_bounded_in_bytes() {
+        for cache_policy in [
+            CachePolicyKind::Lru,
+            CachePolicyKind::Lfu,
+            CachePolicyKind::Segmented,
+        ] {
+            let mut cache = StorageCache::new(StorageCacheConfig {
+                max_cache_size: 1000,
+                max_entry_size: 1000,
+                max_cache_entries: 1000,
+                cache_policy,
+            });
+            for index in 0..100u8 {
+                cache.insert_read_value(vec![index], &Some(vec![0; 99]), false);
+                cache.insert_find_keys(vec![index], &[vec![0; 99]]);
+                assert!(cache.values.total_size <= cache.values.max_size);
+                if let Some(find_keys) = &cache.find_keys {
+                    assert!(find_keys.total_size <= find_keys.max_size);
+                }
+            }
+            let find_keys_size = cache
+                .find_keys
+                .as_ref()
+                .map_or(0, |segment| segment.total_size);
+            assert!(cache.values.total_size + find_keys_size <= 1000);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_the_cache() {
+        let (store, caching_store) = create_caching_store(CachePolicyKind::Lru);
+        let first = caching_store.clone_with_root_key(&[1]).unwrap();
+        write_value(&first, &[0], &[1]).await;
+
+        // Bypassing the cache shows which values are read from it.
+        write_value(&store.clone_with_root_key(&[1]).unwrap(), &[0], &[2]).await;
+        let second = caching_store.clone_with_root_key(&[1]).unwrap();
+        assert_eq!(second.read_value_bytes(&[0]).await.unwrap(), Some(vec![1]));
+        let other = caching_store.clone_with_root_key(&[1, 0]).unwrap();
+        assert_eq!(other.read_value_bytes(&[]).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_segmented_policy_caches_find_keys_until_written() {
+        let (store, caching_store) = create_caching_store(CachePolicyKind::Segmented);
+        let caching_store = caching_store.clone_with_root_key(&[1]).unwrap();
+        let store = store.clone_with_root_key(&[1]).unwrap();
+        write_value(&caching_store, &[0, 1], &[]).await;
+        assert_eq!(find_keys(&caching_store, &[0]).await, vec![vec![1]]);
+
+        write_value(&store, &[0, 2], &[]).await;
+        assert_eq!(find_keys(&caching_store, &[0]).await, vec![vec![1]]);
+
+        write_value(&caching_store, &[5], &[]).await;
+        assert_eq!(
+            find_keys(&caching_store, &[0]).await,
+            vec![vec![1], vec![2]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_keys_are_not_copied_without_caching() {
+        let (_, caching_store) = create_caching_store(CachePolicyKind::Lru);
+        let caching_store = caching_store.clone_with_root_key(&[1]).unwrap();
+        write_value(&caching_store, &[0, 1], &[]).await;
+        let keys = caching_store.find_keys_by_prefix(&[0]).await.unwrap();
+        assert_matches!(keys, LruCachingKeys::Store(_));
+        assert_eq!(find_keys(&caching_store, &[0]).await, vec![vec![1]]);
+    }
+
+    /// Lets the spawned prefetches run. Those on a memory store complete when first polled.
+    async fn run_prefetches() {
+        tokio::task::yield_now().await;
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_fills_the_cache() {
+        let (store, caching_store) = create_caching_store(CachePolicyKind::Lru);
+        write_value(&store, &[0], &[1]).await;
+        caching_store.prefetch(vec![vec![0]]);
+        run_prefetches().await;
+
+        // Bypassing the cache shows which values are read from it.
+        write_value(&store, &[0], &[2]).await;
+        assert_eq!(
+            caching_store.read_value_bytes(&[0]).await.unwrap(),
+            Some(vec![1])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_does_not_override_writes() {
+        let (store, caching_store) = create_caching_store(CachePolicyKind::Lru);
+        write_value(&store, &[0], &[1]).await;
+        caching_store.prefetch(vec![vec![0]]);
+        let mut batch = Batch::new();
+        batch.delete_key(vec![0]);
+        caching_store.write_batch(batch).await.unwrap();
+        // The prefetch reads the value from before the deletion.
+        write_value(&store, &[0], &[1]).await;
+        run_prefetches().await;
+
+        let mut batch = Batch::new();
+        batch.delete_key(vec![0]);
+        store.write_batch(batch).await.unwrap();
+        assert_eq!(caching_store.read_value_bytes(&[0]).await.unwrap(), None);
+    }
+}
@@ -2,13 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueStore`] in memory.
+//!
+//! The values written with a TTL are not read once they have expired, and are removed by
+//! a background task sweeping each root key that has some.
 
 use std::{
     collections::BTreeMap,
+    fs,
+    path::Path,
     sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
+use linera_base::{
+    data_types::{TimeDelta, Timestamp},
+    ensure,
+    time::{timer, Duration},
+};
 use serde::{Deserialize, Serialize};
+use sha3::Digest as _;
 use thiserror::Error;
 
 #[cfg(with_testing)]
@@ -17,7 +28,7 @@ use crate::random::generate_test_namespace;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::{Batch, WriteOperation},
-    common::get_interval,
+    common::{get_interval, TtlClock},
     store::{
         AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
         WithError, WritableKeyValueStore,
@@ -49,10 +60,104 @@ pub const TEST_MEMORY_MAX_STREAM_QUERIES: usize = 10;
 /// The analog of the database is the `BTreeMap`
 type MemoryStoreMap = BTreeMap<Vec<u8>, Vec<u8>>;
 
+/// The data of the root keys of a namespace.
+type NamespaceMaps = BTreeMap<Vec<u8>, MemoryStoreMap>;
+
+/// The interval between two sweeps of the expired values of a root key.
+const TTL_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The data of a root key of a memory store.
+#[derive(Default)]
+struct MemoryStoreData {
+    /// The values of the keys.
+    map: MemoryStoreMap,
+    /// The expiration times of the values written with a TTL.
+    expirations: BTreeMap<Vec<u8>, Timestamp>,
+    /// Whether a task is sweeping the expired values.
+    has_sweeper: bool,
+}
+
+impl MemoryStoreData {
+    fn new(map: MemoryStoreMap) -> Self {
+        Self {
+            map,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the current time if some values may have expired.
+    fn expiration_time(&self, clock: &TtlClock) -> Option<Timestamp> {
+        (!self.expirations.is_empty()).then(|| clock.current_time())
+    }
+
+    fn is_expired(&self, key: &[u8], now: Option<Timestamp>) -> bool {
+        now.is_some_and(|now| {
+            self.expirations
+                .get(key)
+                .is_some_and(|expiration| *expiration <= now)
+        })
+    }
+
+    fn get(&self, key: &[u8], now: Option<Timestamp>) -> Option<&Vec<u8>> {
+        self.map.get(key).filter(|_| !self.is_expired(key, now))
+    }
+
+    fn range(
+        &self,
+        key_prefix: &[u8],
+        now: Option<Timestamp>,
+    ) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> {
+        self.map
+            .range(get_interval(key_prefix.to_vec()))
+            .filter(move |(key, _)| !self.is_expired(key, now))
+    }
+
+    fn insert(&mut self, key: Vec<u8>, value: Vec<u8>, expiration: Option<Timestamp>) {
+        match expiration {
+            None => self.expirations.remove(&key),
+            Some(expiration) => self.expirations.insert(key.clone(), expiration),
+        };
+        self.map.insert(key, value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.map.remove(key);
+        self.expirations.remove(key);
+    }
+
+    fn sweep_expired(&mut self, now: Timestamp) {
+        let expired_keys = self
+            .expirations
+            .iter()
+            .filter(|(_, expiration)| **expiration <= now)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in expired_keys {
+            self.remove(&key);
+        }
+    }
+
+    /// Returns the values that were written without a TTL.
+    fn persistent_map(&self) -> MemoryStoreMap {
+        self.map
+            .iter()
+            .filter(|(key, _)| !self.expirations.contains_key(*key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// The first bytes of the files written by [`MemoryStore::dump_to_file`].
+const SNAPSHOT_FILE_MAGIC: &[u8; 8] = b"LNRMEM01";
+
+/// The size of the checksum at the end of the files written by
+/// [`MemoryStore::dump_to_file`].
+const SNAPSHOT_FILE_CHECKSUM_SIZE: usize = 32;
+
 /// The container for the `MemoryStoreMap`s by namespace and then root key
 #[derive(Default)]
 struct MemoryStores {
-    stores: BTreeMap<String, BTreeMap<Vec<u8>, Arc<RwLock<MemoryStoreMap>>>>,
+    stores: BTreeMap<String, BTreeMap<Vec<u8>, Arc<RwLock<MemoryStoreData>>>>,
 }
 
 impl MemoryStores {
@@ -62,20 +167,19 @@ impl MemoryStores {
         namespace: &str,
         root_key: &[u8],
         kill_on_drop: bool,
+        clock: TtlClock,
     ) -> Result<MemoryStore, MemoryStoreError> {
         let max_stream_queries = config.common_config.max_stream_queries;
         let Some(stores) = self.stores.get_mut(namespace) else {
             return Err(MemoryStoreError::NamespaceNotFound);
         };
-        let store = stores.entry(root_key.to_vec()).or_insert_with(|| {
-            let map = MemoryStoreMap::new();
-            Arc::new(RwLock::new(map))
-        });
-        let map = store.clone();
+        let store = stores.entry(root_key.to_vec()).or_default();
+        let data = store.clone();
         let namespace = namespace.to_string();
         let root_key = root_key.to_vec();
         Ok(MemoryStore {
-            map,
+            data,
+            clock,
             max_stream_queries,
             namespace,
             root_key,
@@ -105,6 +209,74 @@ impl MemoryStores {
     fn sync_delete(&mut self, namespace: &str) {
         self.stores.remove(namespace);
     }
+
+    /// Returns the persistent data of the root keys of `namespace`, locking all of them
+    /// before copying any.
+    fn sync_snapshot(&self, namespace: &str) -> NamespaceMaps {
+        let locked = self
+            .stores
+            .get(namespace)
+            .into_iter()
+            .flatten()
+            .map(|(root_key, data)| {
+                let data = data
+                    .read()
+                    .expect("MemoryStore lock should not be poisoned");
+                (root_key, data)
+            })
+            .collect::<Vec<_>>();
+        locked
+            .iter()
+            .map(|(root_key, data)| ((*root_key).clone(), data.persistent_map()))
+            .collect()
+    }
+
+    /// Returns the persistent data of all the namespaces, as of a single point in time: the
+    /// data of all the root keys are locked before any of it is copied.
+    fn sync_snapshot_all(&self) -> BTreeMap<String, NamespaceMaps> {
+        let locked = self
+            .stores
+            .iter()
+            .flat_map(|(namespace, stores)| {
+                stores.iter().map(move |(root_key, data)| {
+                    let data = data
+                        .read()
+                        .expect("MemoryStore lock should not be poisoned");
+                    (namespace, root_key, data)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut namespaces = self
+            .stores
+            .keys()
+            .map(|namespace| (namespace.clone(), NamespaceMaps::new()))
+            .collect::<BTreeMap<_, _>>();
+        for (namespace, root_key, data) in &locked {
+            namespaces
+                .get_mut(*namespace)
+                .expect("all the namespaces were inserted")
+                .insert((*root_key).clone(), data.persistent_map());
+        }
+        namespaces
+    }
+
+    /// Replaces the data of all the root keys of `namespace` with `maps`, creating the
+    /// namespace if needed. The other root keys of the namespace are emptied.
+    fn sync_restore(&mut self, namespace: &str, maps: &NamespaceMaps) {
+        let stores = self.stores.entry(namespace.to_string()).or_default();
+        for (root_key, data) in stores.iter() {
+            let mut data = data
+                .write()
+                .expect("MemoryStore lock should not be poisoned");
+            data.map = maps.get(root_key).cloned().unwrap_or_default();
+            data.expirations.clear();
+        }
+        for (root_key, map) in maps {
+            stores
+                .entry(root_key.clone())
+                .or_insert_with(|| Arc::new(RwLock::new(MemoryStoreData::new(map.clone()))));
+        }
+    }
 }
 
 /// The global variables of the Namespace memory stores
@@ -114,8 +286,10 @@ static MEMORY_STORES: LazyLock<Mutex<MemoryStores>> =
 /// A virtual DB client where data are persisted in memory.
 #[derive(Clone)]
 pub struct MemoryStore {
-    /// The map used for storing the data.
-    map: Arc<RwLock<MemoryStoreMap>>,
+    /// The data of the root key.
+    data: Arc<RwLock<MemoryStoreData>>,
+    /// The clock used to expire the values written with a TTL.
+    clock: TtlClock,
     /// The maximum number of queries used for the stream.
     max_stream_queries: usize,
     /// The namespace of the store
@@ -152,29 +326,32 @@ impl ReadableKeyValueStore for MemoryStore {
     }
 
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, MemoryStoreError> {
-        let map = self
-            .map
+        let data = self
+            .data
             .read()
             .expect("MemoryStore lock should not be poisoned");
-        Ok(map.get(key).cloned())
+        let now = data.expiration_time(&self.clock);
+        Ok(data.get(key, now).cloned())
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool, MemoryStoreError> {
-        let map = self
-            .map
+        let data = self
+            .data
             .read()
             .expect("MemoryStore lock should not be poisoned");
-        Ok(map.contains_key(key))
+        let now = data.expiration_time(&self.clock);
+        Ok(data.get(key, now).is_some())
     }
 
     async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, MemoryStoreError> {
-        let map = self
-            .map
+        let data = self
+            .data
             .read()
             .expect("MemoryStore lock should not be poisoned");
+        let now = data.expiration_time(&self.clock);
         Ok(keys
             .into_iter()
-            .map(|key| map.contains_key(&key))
+            .map(|key| data.get(&key, now).is_some())
             .collect::<Vec<_>>())
     }
 
@@ -182,13 +359,14 @@ impl ReadableKeyValueStore for MemoryStore {
         &self,
         keys: Vec<Vec<u8>>,
     ) -> Result<Vec<Option<Vec<u8>>>, MemoryStoreError> {
-        let map = self
-            .map
+        let data = self
+            .data
             .read()
             .expect("MemoryStore lock should not be poisoned");
+        let now = data.expiration_time(&self.clock);
         let mut result = Vec::new();
         for key in keys {
-            result.push(map.get(&key).cloned());
+            result.push(data.get(&key, now).cloned());
         }
         Ok(result)
     }
@@ -197,13 +375,14 @@ impl ReadableKeyValueStore for MemoryStore {
         &self,
         key_prefix: &[u8],
     ) -> Result<Vec<Vec<u8>>, MemoryStoreError> {
-        let map = self
-            .map
+        let data = self
+            .data
             .read()
             .expect("MemoryStore lock should not be poisoned");
+        let now = data.expiration_time(&self.clock);
         let mut values = Vec::new();
         let len = key_prefix.len();
-        for (key, _value) in map.range(get_interval(key_prefix.to_vec())) {
+        for (key, _value) in data.range(key_prefix, now) {
             values.push(key[len..].to_vec())
         }
         Ok(values)
@@ -213,13 +392,14 @@ impl ReadableKeyValueStore for MemoryStore {
         &self,
         key_prefix: &[u8],
     ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, MemoryStoreError> {
-        let map = self
-            .map
+        let data = self
+            .data
             .read()
             .expect("MemoryStore lock should not be poisoned");
+        let now = data.expiration_time(&self.clock);
         let mut key_values = Vec::new();
         let len = key_prefix.len();
-        for (key, value) in map.range(get_interval(key_prefix.to_vec())) {
+        for (key, value) in data.range(key_prefix, now) {
             let key_value = (key[len..].to_vec(), value.to_vec());
             key_values.push(key_value);
         }
@@ -229,31 +409,49 @@ impl ReadableKeyValueStore for MemoryStore {
 
 impl WritableKeyValueStore for MemoryStore {
     const MAX_VALUE_SIZE: usize = usize::MAX;
+    const SUPPORTS_TTL: bool = true;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), MemoryStoreError> {
-        let mut map = self
-            .map
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), MemoryStoreError> {
+        let expiration = ttl.map(|ttl| {
+            self.clock
+                .current_time()
+                .saturating_add(TimeDelta::from_duration(ttl))
+        });
+        let mut data = self
+            .data
             .write()
             .expect("MemoryStore lock should not be poisoned");
         for ent in batch.operations {
             match ent {
                 WriteOperation::Put { key, value } => {
-                    map.insert(key, value);
+                    data.insert(key, value, expiration);
                 }
                 WriteOperation::Delete { key } => {
-                    map.remove(&key);
+                    data.remove(&key);
                 }
                 WriteOperation::DeletePrefix { key_prefix } => {
-                    let key_list = map
+                    let key_list = data
+                        .map
                         .range(get_interval(key_prefix))
                         .map(|x| x.0.to_vec())
                         .collect::<Vec<_>>();
                     for key in key_list {
-                        map.remove(&key);
+                        data.remove(&key);
                     }
                 }
             }
         }
+        if !data.expirations.is_empty() && !data.has_sweeper {
+            data.has_sweeper = self.spawn_sweeper();
+        }
         Ok(())
     }
 
@@ -273,7 +471,45 @@ impl MemoryStore {
         if !memory_stores.sync_exists(namespace) {
             memory_stores.sync_create(namespace);
         }
-        memory_stores.sync_connect(config, namespace, &[], kill_on_drop)
+        let clock = TtlClock::default();
+        memory_stores.sync_connect(config, namespace, &[], kill_on_drop, clock)
+    }
+
+    /// Uses `clock` to expire the values written with a TTL by this store and the stores
+    /// cloned from it.
+    #[cfg(with_testing)]
+    pub fn with_clock(mut self, clock: TtlClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Starts a task that removes the expired values of the root key of this store,
+    /// until none are left. Returns whether the task was started.
+    fn spawn_sweeper(&self) -> bool {
+        #[cfg(not(web))]
+        if tokio::runtime::Handle::try_current().is_err() {
+            // Without a runtime, the expired values are only skipped when reading.
+            return false;
+        }
+        let weak_data = Arc::downgrade(&self.data);
+        let clock = self.clock.clone();
+        linera_base::task::spawn(async move {
+            loop {
+                timer::sleep(TTL_SWEEP_INTERVAL).await;
+                let Some(data) = weak_data.upgrade() else {
+                    break;
+                };
+                let mut data = data
+                    .write()
+                    .expect("MemoryStore lock should not be poisoned");
+                data.sweep_expired(clock.current_time());
+                if data.expirations.is_empty() {
+                    data.has_sweeper = false;
+                    break;
+                }
+            }
+        });
+        true
     }
 
     /// Creates a `MemoryStore` from a number of queries and a namespace.
@@ -301,6 +537,95 @@ impl MemoryStore {
         let kill_on_drop = true;
         MemoryStore::sync_maybe_create_and_connect(&config, namespace, kill_on_drop)
     }
+
+    /// Captures the data of all the root keys of the namespace of this store. The values
+    /// written with a TTL are not captured.
+    pub fn snapshot(&self) -> MemoryStoreSnapshot {
+        let memory_stores = MEMORY_STORES
+            .lock()
+            .expect("MEMORY_STORES lock should not be poisoned");
+        let maps = memory_stores.sync_snapshot(&self.namespace);
+        MemoryStoreSnapshot { maps }
+    }
+
+    /// Replaces the data of all the root keys of the namespace of this store with the data
+    /// captured in `snapshot`. The root keys created after the snapshot are emptied.
+    ///
+    /// The change is visible to all the stores connected to the namespace.
+    pub fn restore(&self, snapshot: &MemoryStoreSnapshot) {
+        let mut memory_stores = MEMORY_STORES
+            .lock()
+            .expect("MEMORY_STORES lock should not be poisoned");
+        memory_stores.sync_restore(&self.namespace, &snapshot.maps);
+    }
+
+    /// Writes the data of all the namespaces of the memory stores to the file at `path`,
+    /// replacing it atomically. The values written with a TTL are not written.
+    ///
+    /// The file contains the length of the BCS serialization of the data, the
+    /// serialization itself, and its SHA3-256 checksum, so that partial or corrupted files
+    /// are rejected by [`MemoryStore::load_from_file`].
+    pub fn dump_to_file(path: &Path) -> Result<(), MemoryStoreError> {
+        let namespaces = MEMORY_STORES
+            .lock()
+            .expect("MEMORY_STORES lock should not be poisoned")
+            .sync_snapshot_all();
+        // The serialization and the writing happen without holding any lock.
+        let payload = bcs::to_bytes(&namespaces)?;
+        let mut bytes = SNAPSHOT_FILE_MAGIC.to_vec();
+        bytes.extend((payload.len() as u64).to_le_bytes());
+        bytes.extend(&payload);
+        bytes.extend(sha3::Sha3_256::digest(&payload));
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".tmp");
+        fs::write(&temporary_path, bytes)?;
+        fs::rename(temporary_path, path)?;
+        Ok(())
+    }
+
+    /// Loads the namespaces written by [`MemoryStore::dump_to_file`] to the file at `path`.
+    ///
+    /// Each namespace of the file replaces the existing namespace with the same name, as
+    /// with [`MemoryStore::restore`]. The other namespaces are left unchanged.
+    pub fn load_from_file(path: &Path) -> Result<(), MemoryStoreError> {
+        let bytes = fs::read(path)?;
+        let namespaces = decode_snapshot_file(&bytes)?;
+        let mut memory_stores = MEMORY_STORES
+            .lock()
+            .expect("MEMORY_STORES lock should not be poisoned");
+        for (namespace, maps) in &namespaces {
+            memory_stores.sync_restore(namespace, maps);
+        }
+        Ok(())
+    }
+}
+
+/// Checks and deserializes the content of a file written by [`MemoryStore::dump_to_file`].
+fn decode_snapshot_file(bytes: &[u8]) -> Result<BTreeMap<String, NamespaceMaps>, MemoryStoreError> {
+    let invalid = |reason: &str| MemoryStoreError::InvalidSnapshotFile(reason.to_string());
+    let bytes = bytes
+        .strip_prefix(SNAPSHOT_FILE_MAGIC.as_slice())
+        .ok_or_else(|| invalid("not a memory store snapshot"))?;
+    let (length, bytes) = bytes
+        .split_first_chunk::<8>()
+        .ok_or_else(|| invalid("the file is truncated"))?;
+    let length = u64::from_le_bytes(*length);
+    ensure!(
+        bytes.len().checked_sub(SNAPSHOT_FILE_CHECKSUM_SIZE) == Some(length as usize),
+        invalid("the file is truncated or has trailing data")
+    );
+    let (payload, checksum) = bytes.split_at(length as usize);
+    ensure!(
+        sha3::Sha3_256::digest(payload).as_slice() == checksum,
+        invalid("the checksum does not match")
+    );
+    Ok(bcs::from_bytes(payload)?)
+}
+
+/// The data of all the root keys of a namespace, captured by [`MemoryStore::snapshot`].
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStoreSnapshot {
+    maps: BTreeMap<Vec<u8>, MemoryStoreMap>,
 }
 
 impl AdminKeyValueStore for MemoryStore {
@@ -315,7 +640,8 @@ impl AdminKeyValueStore for MemoryStore {
             .lock()
             .expect("MEMORY_STORES lock should not be poisoned");
         let kill_on_drop = false;
-        memory_stores.sync_connect(config, namespace, &[], kill_on_drop)
+        let clock = TtlClock::default();
+        memory_stores.sync_connect(config, namespace, &[], kill_on_drop, clock)
     }
 
     fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, MemoryStoreError> {
@@ -330,7 +656,12 @@ impl AdminKeyValueStore for MemoryStore {
             .expect("MEMORY_STORES lock should not be poisoned");
         let kill_on_drop = self.kill_on_drop;
         let namespace = &self.namespace;
-        memory_stores.sync_connect(&config, namespace, root_key, kill_on_drop)
+        let clock = self.clock.clone();
+        memory_stores.sync_connect(&config, namespace, root_key, kill_on_drop, clock)
+    }
+
+    fn clone_with_bounded_staleness(&self, _bounded_staleness: bool) -> Self {
+        self.clone()
     }
 
     async fn list_all(_config: &Self::Config) -> Result<Vec<String>, MemoryStoreError> {
@@ -414,8 +745,161 @@ pub enum MemoryStoreError {
     /// The namespace does not exist
     #[error("The namespace does not exist")]
     NamespaceNotFound,
+
+    /// An I/O error with a snapshot file
+    #[error("I/O error with the memory store snapshot file: {0}")]
+    SnapshotFileIo(#[from] std::io::Error),
+
+    /// The snapshot file is partial or corrupted
+    #[error("Invalid memory store snapshot file: {0}")]
+    InvalidSnapshotFile(String),
 }
 
 impl KeyValueStoreError for MemoryStoreError {
     const BACKEND: &'static str = "memory";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lru_caching::{LruCachingStore, DEFAULT_STORAGE_CACHE_CONFIG};
+
+    fn create_store_with_mocked_clock() -> (MemoryStore, TtlClock) {
+        let clock = TtlClock::mocked(Timestamp::from(1_000));
+        let namespace = generate_test_namespace();
+        let store = MemoryStore::new_for_testing(TEST_MEMORY_MAX_STREAM_QUERIES, &namespace)
+            .unwrap()
+            .with_clock(clock.clone());
+        (store, clock)
+    }
+
+    #[tokio::test]
+    async fn test_memory_values_expire_after_their_ttl() {
+        let (store, clock) = create_store_with_mocked_clock();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 1], vec![1]);
+        batch.put_key_value_bytes(vec![1, 2], vec![2]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 3], vec![3]);
+        store.write_batch(batch).await.unwrap();
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(
+            store.read_value_bytes(&[1, 1]).await.unwrap(),
+            Some(vec![1])
+        );
+        assert_eq!(
+            store.find_keys_by_prefix(&[1]).await.unwrap(),
+            vec![vec![1], vec![2], vec![3]]
+        );
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(store.read_value_bytes(&[1, 1]).await.unwrap(), None);
+        assert!(!store.contains_key(&[1, 2]).await.unwrap());
+        assert_eq!(
+            store
+                .contains_keys(vec![vec![1, 2], vec![1, 3]])
+                .await
+                .unwrap(),
+            vec![false, true]
+        );
+        assert_eq!(
+            store
+                .read_multi_values_bytes(vec![vec![1, 1], vec![1, 3]])
+                .await
+                .unwrap(),
+            vec![None, Some(vec![3])]
+        );
+        assert_eq!(
+            store.find_keys_by_prefix(&[1]).await.unwrap(),
+            vec![vec![3]]
+        );
+        assert_eq!(
+            store.find_key_values_by_prefix(&[1]).await.unwrap(),
+            vec![(vec![3], vec![3])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_value_rewritten_without_ttl_does_not_expire() {
+        let (store, clock) = create_store_with_mocked_clock();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1]);
+        batch.put_key_value_bytes(vec![2], vec![2]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![3]);
+        store.write_batch_with_ttl(batch, None).await.unwrap();
+
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), Some(vec![3]));
+        assert_eq!(store.read_value_bytes(&[2]).await.unwrap(), None);
+        // Only the values written without a TTL are captured by snapshots.
+        let snapshot = store.snapshot();
+        assert_eq!(
+            snapshot.maps.get(&Vec::new()).unwrap(),
+            &MemoryStoreMap::from([(vec![1], vec![3])])
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_memory_sweeper_removes_expired_values() {
+        let (store, clock) = create_store_with_mocked_clock();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![2], vec![2]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(20)))
+            .await
+            .unwrap();
+        assert!(store.data.read().unwrap().has_sweeper);
+
+        clock.advance(Duration::from_secs(10));
+        timer::sleep(2 * TTL_SWEEP_INTERVAL).await;
+        {
+            let data = store.data.read().unwrap();
+            assert_eq!(data.map, MemoryStoreMap::from([(vec![2], vec![2])]));
+            assert!(data.has_sweeper);
+        }
+
+        clock.advance(Duration::from_secs(10));
+        timer::sleep(2 * TTL_SWEEP_INTERVAL).await;
+        let data = store.data.read().unwrap();
+        assert!(data.map.is_empty());
+        assert!(data.expirations.is_empty());
+        assert!(!data.has_sweeper);
+    }
+
+    #[tokio::test]
+    async fn test_lru_cache_does_not_keep_expired_values() {
+        let (store, clock) = create_store_with_mocked_clock();
+        let store = LruCachingStore::new(store, DEFAULT_STORAGE_CACHE_CONFIG);
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), Some(vec![1]));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), None);
+
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![2]);
+        store.write_batch(batch).await.unwrap();
+        assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), Some(vec![2]));
+    }
+}
@@ -2,17 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Adds metrics to a key-value store.
+//!
+//! Besides the metrics of each backend, the latencies of the main operations and the number of
+//! bytes read and written are recorded in metrics shared by all the stores, labeled by backend
+//! and namespace. This module is only compiled with the `metrics` feature, so that stores
+//! without metrics have no overhead.
 
 use std::{
     collections::{btree_map::Entry, BTreeMap},
-    sync::{Arc, LazyLock, Mutex},
+    sync::{Arc, LazyLock, Mutex, OnceLock},
 };
 
 use convert_case::{Case, Casing};
-use linera_base::prometheus_util::{
-    register_histogram_vec, register_int_counter_vec, MeasureLatency,
+use linera_base::{
+    prometheus_util::{
+        exponential_bucket_latencies, register_histogram_vec, register_int_counter_vec,
+        MeasureLatency,
+    },
+    time::Duration,
 };
-use prometheus::{HistogramVec, IntCounterVec};
+use prometheus::{Histogram, HistogramVec, IntCounter, IntCounterVec};
 
 #[cfg(with_testing)]
 use crate::store::TestKeyValueStore;
@@ -62,6 +71,108 @@ pub struct KeyValueStoreMetrics {
     exists_true_cases: IntCounterVec,
 }
 
+/// The buckets of the latency histograms labeled by backend and namespace, in milliseconds.
+static LATENCY_BUCKETS: OnceLock<Vec<f64>> = OnceLock::new();
+
+/// Sets the buckets, in milliseconds, of the latency histograms labeled by backend and
+/// namespace.
+///
+/// This has to be done before the first metered store is connected: the buckets are returned
+/// back if the histograms already exist.
+pub fn set_latency_buckets(buckets: Vec<f64>) -> Result<(), Vec<f64>> {
+    LATENCY_BUCKETS.set(buckets)
+}
+
+fn latency_buckets() -> Vec<f64> {
+    LATENCY_BUCKETS
+        .get_or_init(|| {
+            exponential_bucket_latencies(10_000.0).expect("The default buckets should exist")
+        })
+        .clone()
+}
+
+/// The metrics shared by all the stores, labeled by backend and namespace.
+struct NamespaceMetricVecs {
+    read_value_bytes_latency: HistogramVec,
+    read_multi_values_bytes_latency: HistogramVec,
+    find_keys_by_prefix_latency: HistogramVec,
+    write_batch_latency: HistogramVec,
+    bytes_read: IntCounterVec,
+    bytes_written: IntCounterVec,
+}
+
+static NAMESPACE_METRIC_VECS: LazyLock<NamespaceMetricVecs> = LazyLock::new(|| {
+    let labels = &["backend", "namespace"];
+    NamespaceMetricVecs {
+        read_value_bytes_latency: register_histogram_vec(
+            "key_value_store_read_value_bytes_latency",
+            "Key-value store read value bytes latency",
+            labels,
+            Some(latency_buckets()),
+        ),
+        read_multi_values_bytes_latency: register_histogram_vec(
+            "key_value_store_read_multi_values_bytes_latency",
+            "Key-value store read multi values bytes latency",
+            labels,
+            Some(latency_buckets()),
+        ),
+        find_keys_by_prefix_latency: register_histogram_vec(
+            "key_value_store_find_keys_by_prefix_latency",
+            "Key-value store find keys by prefix latency",
+            labels,
+            Some(latency_buckets()),
+        ),
+        write_batch_latency: register_histogram_vec(
+            "key_value_store_write_batch_latency",
+            "Key-value store write batch latency",
+            labels,
+            Some(latency_buckets()),
+        ),
+        bytes_read: register_int_counter_vec(
+            "key_value_store_bytes_read",
+            "Key-value store bytes read",
+            labels,
+        ),
+        bytes_written: register_int_counter_vec(
+            "key_value_store_bytes_written",
+            "Key-value store bytes written",
+            labels,
+        ),
+    }
+});
+
+/// The metrics of a store for its backend and namespace, obtained when connecting so that
+/// the operations don't have to look up their labels.
+struct NamespaceMetrics {
+    read_value_bytes_latency: Histogram,
+    read_multi_values_bytes_latency: Histogram,
+    find_keys_by_prefix_latency: Histogram,
+    write_batch_latency: Histogram,
+    bytes_read: IntCounter,
+    bytes_written: IntCounter,
+}
+
+impl NamespaceMetrics {
+    fn new(backend: &str, namespace: &str) -> Self {
+        let metric_vecs = &*NAMESPACE_METRIC_VECS;
+        let labels = &[backend, namespace];
+        NamespaceMetrics {
+            read_value_bytes_latency: metric_vecs
+                .read_value_bytes_latency
+                .with_label_values(labels),
+            read_multi_values_bytes_latency: metric_vecs
+                .read_multi_values_bytes_latency
+                .with_label_values(labels),
+            find_keys_by_prefix_latency: metric_vecs
+                .find_keys_by_prefix_latency
+                .with_label_values(labels),
+            write_batch_latency: metric_vecs.write_batch_latency.with_label_values(labels),
+            bytes_read: metric_vecs.bytes_read.with_label_values(labels),
+            bytes_written: metric_vecs.bytes_written.with_label_values(labels),
+        }
+    }
+}
+
 #[derive(Default)]
 struct StoreMetrics {
     stores: BTreeMap<String, Arc<KeyValueStoreMetrics>>,
@@ -272,6 +383,8 @@ impl KeyValueStoreMetrics {
 pub struct MeteredStore<K> {
     /// the metrics being stored
     counter: Arc<KeyValueStoreMetrics>,
+    /// The metrics labeled by the backend and the namespace of the store
+    namespace_metrics: Arc<NamespaceMetrics>,
     /// The underlying store of the metered store
     store: K,
 }
@@ -295,8 +408,16 @@ where
         self.store.max_stream_queries()
     }
 
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        self.store.prefetch(keys)
+    }
+
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
         let _latency = self.counter.read_value_bytes_latency.measure_latency();
+        let _namespace_latency = self
+            .namespace_metrics
+            .read_value_bytes_latency
+            .measure_latency();
         self.counter
             .read_value_key_size
             .with_label_values(&[])
@@ -308,11 +429,13 @@ where
                 .read_value_none_cases
                 .with_label_values(&[])
                 .inc(),
-            Some(value) => self
-                .counter
-                .read_value_value_size
-                .with_label_values(&[])
-                .observe(value.len() as f64),
+            Some(value) => {
+                self.counter
+                    .read_value_value_size
+                    .with_label_values(&[])
+                    .observe(value.len() as f64);
+                self.namespace_metrics.bytes_read.inc_by(value.len() as u64);
+            }
         }
         Ok(result)
     }
@@ -348,6 +471,10 @@ where
             .counter
             .read_multi_values_bytes_latency
             .measure_latency();
+        let _namespace_latency = self
+            .namespace_metrics
+            .read_multi_values_bytes_latency
+            .measure_latency();
         self.counter
             .read_multi_values_num_entries
             .with_label_values(&[])
@@ -357,11 +484,18 @@ where
             .read_multi_values_key_sizes
             .with_label_values(&[])
             .observe(key_sizes as f64);
-        self.store.read_multi_values_bytes(keys).await
+        let values = self.store.read_multi_values_bytes(keys).await?;
+        let values_size = values.iter().flatten().map(Vec::len).sum::<usize>();
+        self.namespace_metrics.bytes_read.inc_by(values_size as u64);
+        Ok(values)
     }
 
     async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
         let _latency = self.counter.find_keys_by_prefix_latency.measure_latency();
+        let _namespace_latency = self
+            .namespace_metrics
+            .find_keys_by_prefix_latency
+            .measure_latency();
         self.counter
             .find_keys_by_prefix_prefix_size
             .with_label_values(&[])
@@ -381,6 +515,7 @@ where
             .find_keys_by_prefix_keys_size
             .with_label_values(&[])
             .observe(keys_size as f64);
+        self.namespace_metrics.bytes_read.inc_by(keys_size as u64);
         Ok(result)
     }
 
@@ -411,8 +546,23 @@ where
             .find_key_values_by_prefix_key_values_size
             .with_label_values(&[])
             .observe(key_values_size as f64);
+        self.namespace_metrics
+            .bytes_read
+            .inc_by(key_values_size as u64);
         Ok(result)
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let _latency = self.counter.find_keys_by_prefix_latency.measure_latency();
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
 }
 
 impl<K> WritableKeyValueStore for MeteredStore<K>
@@ -420,14 +570,27 @@ where
     K: WritableKeyValueStore + Send + Sync,
 {
     const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = K::SUPPORTS_TTL;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
         let _latency = self.counter.write_batch_latency.measure_latency();
+        let _namespace_latency = self.namespace_metrics.write_batch_latency.measure_latency();
+        let size = batch.size();
         self.counter
             .write_batch_size
             .with_label_values(&[])
-            .observe(batch.size() as f64);
-        self.store.write_batch(batch).await
+            .observe(size as f64);
+        self.store.write_batch_with_ttl(batch, ttl).await?;
+        self.namespace_metrics.bytes_written.inc_by(size as u64);
+        Ok(())
     }
 
     async fn clear_journal(&self) -> Result<(), Self::Error> {
@@ -452,14 +615,35 @@ where
         let _latency = counter.connect_latency.measure_latency();
         let store = K::connect(config, namespace).await?;
         let counter = get_counter(&name);
-        Ok(Self { counter, store })
+        let namespace_metrics = Arc::new(NamespaceMetrics::new(&name.replace(' ', "_"), namespace));
+        Ok(Self {
+            counter,
+            namespace_metrics,
+            store,
+        })
     }
 
     fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
         let _latency = self.counter.clone_with_root_key_latency.measure_latency();
         let store = self.store.clone_with_root_key(root_key)?;
         let counter = self.counter.clone();
-        Ok(Self { counter, store })
+        let namespace_metrics = self.namespace_metrics.clone();
+        Ok(Self {
+            counter,
+            namespace_metrics,
+            store,
+        })
+    }
+
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        let counter = self.counter.clone();
+        let namespace_metrics = self.namespace_metrics.clone();
+        Self {
+            counter,
+            namespace_metrics,
+            store,
+        }
     }
 
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
@@ -528,3 +712,73 @@ where
         K::new_test_config().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::proto::MetricFamily;
+
+    use super::*;
+    use crate::memory::{MemoryStore, MemoryStoreConfig, TEST_MEMORY_MAX_STREAM_QUERIES};
+
+    fn find_namespace_metric<'a>(
+        families: &'a [MetricFamily],
+        name: &str,
+        namespace: &str,
+    ) -> &'a prometheus::proto::Metric {
+        let family = families
+            .iter()
+            .find(|family| family.get_name() == format!("linera_{name}"))
+            .unwrap_or_else(|| panic!("The metric family {name} is missing"));
+        family
+            .get_metric()
+            .iter()
+            .find(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .any(|label| label.get_name() == "namespace" && label.get_value() == namespace)
+                    && metric
+                        .get_label()
+                        .iter()
+                        .any(|label| label.get_name() == "backend" && label.get_value() == "memory")
+            })
+            .unwrap_or_else(|| panic!("The metric {name} has no entry for {namespace}"))
+    }
+
+    #[tokio::test]
+    async fn test_namespace_metrics_are_recorded() {
+        let namespace = "metering_test";
+        let config = MemoryStoreConfig::new(TEST_MEMORY_MAX_STREAM_QUERIES);
+        let store = MeteredStore::<MemoryStore>::connect(&config, namespace)
+            .await
+            .unwrap();
+
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"key1".to_vec(), b"value1".to_vec());
+        batch.put_key_value_bytes(b"key2".to_vec(), b"value2".to_vec());
+        store.write_batch(batch).await.unwrap();
+        store.read_value_bytes(b"key1").await.unwrap();
+        store
+            .read_multi_values_bytes(vec![b"key1".to_vec(), b"key3".to_vec()])
+            .await
+            .unwrap();
+        store.find_keys_by_prefix(b"key").await.unwrap();
+
+        let families = prometheus::gather();
+        for name in [
+            "key_value_store_read_value_bytes_latency",
+            "key_value_store_read_multi_values_bytes_latency",
+            "key_value_store_find_keys_by_prefix_latency",
+            "key_value_store_write_batch_latency",
+        ] {
+            let metric = find_namespace_metric(&families, name, namespace);
+            assert_eq!(metric.get_histogram().get_sample_count(), 1);
+        }
+        let bytes_written =
+            find_namespace_metric(&families, "key_value_store_bytes_written", namespace);
+        assert_eq!(bytes_written.get_counter().get_value(), 20.0);
+        // Two values of 6 bytes, and two keys of 1 byte after the prefix.
+        let bytes_read = find_namespace_metric(&families, "key_value_store_bytes_read", namespace);
+        assert_eq!(bytes_read.get_counter().get_value(), 14.0);
+    }
+}
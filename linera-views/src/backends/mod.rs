@@ -6,14 +6,22 @@ pub mod journaling;
 #[cfg(with_metrics)]
 pub mod metering;
 
+#[cfg(with_metrics)]
+pub mod storage_stats;
+
 pub mod value_splitting;
 
+#[cfg(with_compression)]
+pub mod compression;
+
 pub mod memory;
 
 pub mod lru_caching;
 
 pub mod dual;
 
+pub mod replicated;
+
 #[cfg(with_scylladb)]
 pub mod scylla_db;
 
@@ -0,0 +1,436 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements [`crate::store::KeyValueStore`] on top of a primary store and read replicas.
+//!
+//! All the writes go to the primary store. The reads are distributed round-robin over the
+//! replicas, and fall back to the primary store if a replica fails. Since the replicas may
+//! lag behind the primary store, the reads go to the primary store by default, and the
+//! replicas are only used by the clones of the store for which bounded staleness is
+//! acceptable, see [`AdminKeyValueStore::clone_with_bounded_staleness`].
+
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use linera_base::time::Duration;
+use serde::{Deserialize, Serialize};
+#[cfg(with_metrics)]
+use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCounterVec};
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueStore;
+use crate::{
+    batch::Batch,
+    store::{AdminKeyValueStore, ReadableKeyValueStore, WithError, WritableKeyValueStore},
+};
+
+#[cfg(with_metrics)]
+/// The total number of reads served by a replica
+static REPLICA_READ_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "num_replica_reads",
+        "Number of reads served by a read replica",
+        &[],
+    )
+});
+
+#[cfg(with_metrics)]
+/// The total number of failed replica reads that were retried on the primary store
+static REPLICA_FAILOVER_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "num_replica_failovers",
+        "Number of failed replica reads retried on the primary store",
+        &[],
+    )
+});
+
+/// The configuration of a [`ReplicatedStore`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedStoreConfig<C> {
+    /// The configuration of the primary store, which receives all the writes.
+    pub primary_config: C,
+    /// The configurations of the read replicas.
+    pub replica_configs: Vec<C>,
+}
+
+/// A store that writes to a primary store and reads from read replicas if bounded
+/// staleness is acceptable.
+#[derive(Clone)]
+pub struct ReplicatedStore<S> {
+    /// The primary store.
+    primary: S,
+    /// The read replicas.
+    replicas: Vec<S>,
+    /// The index of the next replica to read from, shared by all the clones.
+    next_replica: Arc<AtomicUsize>,
+    /// Whether reads may be served by replicas lagging behind the primary store.
+    bounded_staleness: bool,
+}
+
+impl<S> ReplicatedStore<S> {
+    /// Creates a store from a primary store and its read replicas. The reads of the new
+    /// store go to the primary store.
+    pub fn new(primary: S, replicas: Vec<S>) -> Self {
+        Self {
+            primary,
+            replicas,
+            next_replica: Arc::new(AtomicUsize::new(0)),
+            bounded_staleness: false,
+        }
+    }
+
+    /// Returns the replica to read from next, if reads may be served by a replica.
+    fn replica(&self) -> Option<&S> {
+        if !self.bounded_staleness || self.replicas.is_empty() {
+            return None;
+        }
+        let index = self.next_replica.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+        #[cfg(with_metrics)]
+        REPLICA_READ_COUNT.with_label_values(&[]).inc();
+        Some(&self.replicas[index])
+    }
+}
+
+/// Records that a read from a replica failed and is retried on the primary store.
+fn fail_over(error: &impl std::fmt::Display) {
+    #[cfg(with_metrics)]
+    REPLICA_FAILOVER_COUNT.with_label_values(&[]).inc();
+    tracing::warn!("Reading from a replica failed, using the primary store: {error}");
+}
+
+impl<S> WithError for ReplicatedStore<S>
+where
+    S: WithError,
+{
+    type Error = S::Error;
+}
+
+impl<S> ReadableKeyValueStore for ReplicatedStore<S>
+where
+    S: ReadableKeyValueStore + Send + Sync,
+{
+    const MAX_KEY_SIZE: usize = S::MAX_KEY_SIZE;
+
+    type Keys = S::Keys;
+    type KeyValues = S::KeyValues;
+
+    fn max_stream_queries(&self) -> usize {
+        self.primary.max_stream_queries()
+    }
+
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        // The replicas have no cache, so only the reads from the primary are prefetched.
+        if !self.bounded_staleness || self.replicas.is_empty() {
+            self.primary.prefetch(keys)
+        }
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica.read_value_bytes(key).await {
+                Ok(value) => return Ok(value),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary.read_value_bytes(key).await
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica.contains_key(key).await {
+                Ok(test) => return Ok(test),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary.contains_key(key).await
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica.contains_keys(keys.clone()).await {
+                Ok(tests) => return Ok(tests),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary.contains_keys(keys).await
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica.read_multi_values_bytes(keys.clone()).await {
+                Ok(values) => return Ok(values),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary.read_multi_values_bytes(keys).await
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica.find_keys_by_prefix(key_prefix).await {
+                Ok(keys) => return Ok(keys),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary.find_keys_by_prefix(key_prefix).await
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica.find_key_values_by_prefix(key_prefix).await {
+                Ok(key_values) => return Ok(key_values),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary.find_key_values_by_prefix(key_prefix).await
+    }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        if let Some(replica) = self.replica() {
+            match replica
+                .find_keys_by_prefix_after(key_prefix, start_after, limit)
+                .await
+            {
+                Ok(keys) => return Ok(keys),
+                Err(error) => fail_over(&error),
+            }
+        }
+        self.primary
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+}
+
+impl<S> WritableKeyValueStore for ReplicatedStore<S>
+where
+    S: WritableKeyValueStore + Send + Sync,
+{
+    const MAX_VALUE_SIZE: usize = S::MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = S::SUPPORTS_TTL;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        self.primary.write_batch(batch).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
+        self.primary.write_batch_with_ttl(batch, ttl).await
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        self.primary.clear_journal().await
+    }
+}
+
+impl<S> AdminKeyValueStore for ReplicatedStore<S>
+where
+    S: AdminKeyValueStore + Clone + Send + Sync,
+{
+    type Config = ReplicatedStoreConfig<S::Config>;
+
+    fn get_name() -> String {
+        format!("replicated {}", S::get_name())
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let primary = S::connect(&config.primary_config, namespace).await?;
+        let mut replicas = Vec::new();
+        for replica_config in &config.replica_configs {
+            replicas.push(S::connect(replica_config, namespace).await?);
+        }
+        Ok(Self::new(primary, replicas))
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let primary = self.primary.clone_with_root_key(root_key)?;
+        let replicas = self
+            .replicas
+            .iter()
+            .map(|replica| replica.clone_with_root_key(root_key))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            primary,
+            replicas,
+            next_replica: self.next_replica.clone(),
+            bounded_staleness: self.bounded_staleness,
+        })
+    }
+
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        Self {
+            bounded_staleness,
+            ..self.clone()
+        }
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        S::list_all(&config.primary_config).await
+    }
+
+    async fn list_root_keys(
+        config: &Self::Config,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        S::list_root_keys(&config.primary_config, namespace).await
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        S::exists(&config.primary_config, namespace).await
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        S::create(&config.primary_config, namespace).await
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        S::delete(&config.primary_config, namespace).await
+    }
+}
+
+#[cfg(with_testing)]
+impl<S> TestKeyValueStore for ReplicatedStore<S>
+where
+    S: TestKeyValueStore + Clone + Send + Sync,
+{
+    async fn new_test_config() -> Result<Self::Config, Self::Error> {
+        let primary_config = S::new_test_config().await?;
+        Ok(ReplicatedStoreConfig {
+            primary_config,
+            replica_configs: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplicatedStore;
+    use crate::{
+        batch::Batch,
+        memory::{create_test_memory_store, MemoryStore},
+        store::{AdminKeyValueStore as _, ReadableKeyValueStore as _, WritableKeyValueStore as _},
+        value_splitting::ValueSplittingStore,
+    };
+
+    /// Creates a replicated store whose replica never receives the writes.
+    fn create_stale_store() -> (ReplicatedStore<MemoryStore>, MemoryStore) {
+        let primary = create_test_memory_store();
+        let replica = create_test_memory_store();
+        let store = ReplicatedStore::new(primary, vec![replica.clone()]);
+        (store, replica)
+    }
+
+    #[tokio::test]
+    async fn test_writes_go_to_the_primary() -> anyhow::Result<()> {
+        let (store, replica) = create_stale_store();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 2], vec![3]);
+        store.write_batch(batch).await?;
+
+        assert!(!replica.contains_key(&[1, 2]).await?);
+        // The reads go to the primary store by default.
+        assert_eq!(store.read_value_bytes(&[1, 2]).await?, Some(vec![3]));
+        assert_eq!(store.find_keys_by_prefix(&[1]).await?, vec![vec![2]]);
+
+        // The stale replica is read if bounded staleness is acceptable.
+        let stale_store = store.clone_with_bounded_staleness(true);
+        assert_eq!(stale_store.read_value_bytes(&[1, 2]).await?, None);
+        assert!(stale_store.find_keys_by_prefix(&[1]).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wrappers_forward_the_bounded_staleness() -> anyhow::Result<()> {
+        let (store, _replica) = create_stale_store();
+        let store = ValueSplittingStore::new(store);
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![2]);
+        store.write_batch(batch).await?;
+
+        assert_eq!(store.read_value_bytes(&[1]).await?, Some(vec![2]));
+        let stale_store = store.clone_with_bounded_staleness(true);
+        assert_eq!(stale_store.read_value_bytes(&[1]).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_replica_reads_fall_back_to_the_primary() -> anyhow::Result<()> {
+        let primary = ValueSplittingStore::new(create_test_memory_store());
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![2]);
+        primary.write_batch(batch).await?;
+        // The replica has a value without a segment count, so reading it fails.
+        let replica_memory = create_test_memory_store();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 0, 0, 0, 0], vec![7]);
+        replica_memory.write_batch(batch).await?;
+        let replica = ValueSplittingStore::new(replica_memory);
+        assert!(replica.read_value_bytes(&[1]).await.is_err());
+
+        let store = ReplicatedStore::new(primary, vec![replica]).clone_with_bounded_staleness(true);
+        assert_eq!(store.read_value_bytes(&[1]).await?, Some(vec![2]));
+        assert_eq!(
+            store
+                .read_multi_values_bytes(vec![vec![1], vec![3]])
+                .await?,
+            vec![Some(vec![2]), None]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reads_are_distributed_over_the_replicas() -> anyhow::Result<()> {
+        let primary = create_test_memory_store();
+        let replicas = vec![create_test_memory_store(), create_test_memory_store()];
+        for (index, replica) in replicas.iter().enumerate() {
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![0], vec![index as u8]);
+            replica.write_batch(batch).await?;
+        }
+        let store = ReplicatedStore::new(primary, replicas).clone_with_bounded_staleness(true);
+
+        let mut values = Vec::new();
+        for _ in 0..4 {
+            values.push(store.read_value_bytes(&[0]).await?);
+        }
+        assert_eq!(
+            values,
+            vec![Some(vec![0]), Some(vec![1]), Some(vec![0]), Some(vec![1])]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clones_keep_the_bounded_staleness() -> anyhow::Result<()> {
+        let (store, _replica) = create_stale_store();
+        let strict_store = store
+            .clone_with_bounded_staleness(true)
+            .clone_with_bounded_staleness(false)
+            .clone_with_root_key(&[5])?;
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![2]);
+        strict_store.write_batch(batch).await?;
+
+        assert_eq!(strict_store.read_value_bytes(&[1]).await?, Some(vec![2]));
+        let stale_store = strict_store.clone_with_bounded_staleness(true);
+        assert_eq!(stale_store.read_value_bytes(&[1]).await?, None);
+        Ok(())
+    }
+}
@@ -2,19 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueStore`] for the RocksDB database.
+//!
+//! RocksDB has no TTL per value, so values written with a TTL are recorded in an index of
+//! expiration times, from which a background task periodically removes the expired values.
+//! Until then, the expired values are skipped when reading.
 
 use std::{
+    collections::{BTreeSet, HashMap},
     ffi::OsString,
     fmt::Display,
     path::PathBuf,
+    str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex, RwLock, Weak,
     },
 };
 
-use linera_base::ensure;
-use rocksdb::{BlockBasedOptions, Cache, DBCompactionStyle};
+use linera_base::{
+    data_types::{TimeDelta, Timestamp},
+    ensure,
+    time::{timer, Duration},
+};
+use rocksdb::{
+    BlockBasedOptions, BoundColumnFamily, Cache, ColumnFamilyDescriptor, DBCompactionStyle,
+    DBCompressionType, DataBlockIndexType,
+};
 use serde::{Deserialize, Serialize};
 use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
 use tempfile::TempDir;
@@ -26,7 +39,7 @@ use crate::metering::MeteredStore;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::{Batch, WriteOperation},
-    common::get_upper_bound_option,
+    common::{get_upper_bound_option, TtlClock},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
         AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
@@ -38,6 +51,15 @@ use crate::{
 /// The prefixes being used in the system
 static ROOT_KEY_DOMAIN: [u8; 1] = [0];
 static STORED_ROOT_KEYS_PREFIX: u8 = 1;
+/// The prefix of the index of the values written with a TTL, by expiration time.
+static EXPIRATION_INDEX_PREFIX: u8 = 2;
+/// The prefix of the expiration times of the values written with a TTL, by key.
+static EXPIRATION_TIMES_PREFIX: u8 = 3;
+/// The key of the column families that the database was created with.
+static COLUMN_FAMILIES_KEY: [u8; 1] = [4];
+
+/// The interval between two sweeps of the expired values of a database.
+const TTL_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
 
 /// The number of streams for the test
 #[cfg(with_testing)]
@@ -108,6 +130,310 @@ impl Display for RocksDbSpawnMode {
     }
 }
 
+/// The tuning profiles of RocksDB, each adapted to a kind of workload.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RocksDbProfile {
+    /// Balanced settings for reads and writes.
+    #[default]
+    Mixed,
+    /// Bloom filters, a hash index and a larger block cache, for workloads dominated by
+    /// reads of individual keys.
+    PointLookup,
+    /// Larger memtables, later write stalls and stronger compression of the last levels,
+    /// for workloads dominated by writes.
+    WriteHeavy,
+}
+
+impl Display for RocksDbProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RocksDbProfile::Mixed => write!(f, "mixed"),
+            RocksDbProfile::PointLookup => write!(f, "point-lookup"),
+            RocksDbProfile::WriteHeavy => write!(f, "write-heavy"),
+        }
+    }
+}
+
+impl FromStr for RocksDbProfile {
+    type Err = RocksDbStoreInternalError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "mixed" => Ok(RocksDbProfile::Mixed),
+            "point-lookup" => Ok(RocksDbProfile::PointLookup),
+            "write-heavy" => Ok(RocksDbProfile::WriteHeavy),
+            _ => Err(RocksDbStoreInternalError::InvalidTuning(format!(
+                "unknown profile {input:?}, expected mixed, point-lookup or write-heavy"
+            ))),
+        }
+    }
+}
+
+/// The compression algorithms that RocksDB can apply to the files of a level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RocksDbCompression {
+    /// No compression.
+    None,
+    /// Snappy compression.
+    Snappy,
+    /// LZ4 compression.
+    Lz4,
+    /// Zstandard compression.
+    Zstd,
+}
+
+impl RocksDbCompression {
+    fn compression_type(self) -> DBCompressionType {
+        match self {
+            RocksDbCompression::None => DBCompressionType::None,
+            RocksDbCompression::Snappy => DBCompressionType::Snappy,
+            RocksDbCompression::Lz4 => DBCompressionType::Lz4,
+            RocksDbCompression::Zstd => DBCompressionType::Zstd,
+        }
+    }
+}
+
+impl Display for RocksDbCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RocksDbCompression::None => write!(f, "none"),
+            RocksDbCompression::Snappy => write!(f, "snappy"),
+            RocksDbCompression::Lz4 => write!(f, "lz4"),
+            RocksDbCompression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl FromStr for RocksDbCompression {
+    type Err = RocksDbStoreInternalError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "none" => Ok(RocksDbCompression::None),
+            "snappy" => Ok(RocksDbCompression::Snappy),
+            "lz4" => Ok(RocksDbCompression::Lz4),
+            "zstd" => Ok(RocksDbCompression::Zstd),
+            _ => Err(RocksDbStoreInternalError::InvalidTuning(format!(
+                "unknown compression {input:?}, expected none, snappy, lz4 or zstd"
+            ))),
+        }
+    }
+}
+
+/// A column family holding the keys that start with a given prefix.
+///
+/// The prefix is matched against the root key of the store followed by the key, so that a
+/// column family can hold the data of several root keys.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RocksDbColumnFamily {
+    /// The name of the column family.
+    pub name: String,
+    /// The prefix of the keys stored in the column family.
+    pub key_prefix: Vec<u8>,
+}
+
+impl RocksDbColumnFamily {
+    /// Creates a column family for the keys starting with `key_prefix`.
+    pub fn new(name: impl Into<String>, key_prefix: Vec<u8>) -> Self {
+        RocksDbColumnFamily {
+            name: name.into(),
+            key_prefix,
+        }
+    }
+}
+
+/// The tuning of a RocksDB database: a profile, and options overriding its settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RocksDbTuning {
+    /// The profile providing the default settings.
+    pub profile: RocksDbProfile,
+    /// The size of the block cache, in bytes. The profile uses a fraction of the RAM by
+    /// default.
+    pub block_cache_size: Option<u64>,
+    /// The number of bits per key of the bloom filters, or zero to disable them.
+    pub bloom_filter_bits_per_key: Option<u32>,
+    /// The compression of each level, starting with level 0. The last one is also used for
+    /// the deeper levels.
+    pub compression_per_level: Option<Vec<RocksDbCompression>>,
+    /// The column families that keys are stored in, depending on their prefix. Other keys
+    /// are stored in the default column family.
+    ///
+    /// The column families of a database cannot be changed after it has been created: they
+    /// are recorded in the database, which then cannot be opened with other ones.
+    pub column_families: Vec<RocksDbColumnFamily>,
+}
+
+/// The settings of a [`RocksDbProfile`].
+struct ProfileSettings {
+    write_buffer_size: usize,
+    max_write_buffer_number: i32,
+    min_write_buffer_number_to_merge: i32,
+    level_zero_slowdown_writes_trigger: i32,
+    level_zero_stop_writes_trigger: i32,
+    /// The fraction of the RAM used by the block cache is `1 / block_cache_ram_divisor`.
+    block_cache_ram_divisor: usize,
+    bloom_filter_bits_per_key: u32,
+    hash_index: bool,
+    compression_per_level: &'static [RocksDbCompression],
+}
+
+impl RocksDbProfile {
+    fn settings(self) -> ProfileSettings {
+        match self {
+            RocksDbProfile::Mixed => ProfileSettings {
+                write_buffer_size: WRITE_BUFFER_SIZE,
+                max_write_buffer_number: MAX_WRITE_BUFFER_NUMBER,
+                min_write_buffer_number_to_merge: 1,
+                level_zero_slowdown_writes_trigger: 12,
+                level_zero_stop_writes_trigger: 20,
+                // 1/4 of the RAM is a reasonable balance: large enough to significantly
+                // improve read performance by caching frequently accessed blocks, and small
+                // enough to leave memory for other system components and to prevent swapping
+                // or OOM conditions.
+                block_cache_ram_divisor: 4,
+                bloom_filter_bits_per_key: 0,
+                hash_index: false,
+                compression_per_level: &[RocksDbCompression::Lz4],
+            },
+            RocksDbProfile::PointLookup => ProfileSettings {
+                block_cache_ram_divisor: 3,
+                bloom_filter_bits_per_key: 10,
+                hash_index: true,
+                ..RocksDbProfile::Mixed.settings()
+            },
+            RocksDbProfile::WriteHeavy => ProfileSettings {
+                write_buffer_size: 2 * WRITE_BUFFER_SIZE,
+                min_write_buffer_number_to_merge: 2,
+                level_zero_slowdown_writes_trigger: 20,
+                level_zero_stop_writes_trigger: 36,
+                block_cache_ram_divisor: 8,
+                bloom_filter_bits_per_key: 10,
+                // Most of the data ends up in the last levels, which are rarely rewritten.
+                compression_per_level: &[
+                    RocksDbCompression::None,
+                    RocksDbCompression::None,
+                    RocksDbCompression::Lz4,
+                    RocksDbCompression::Lz4,
+                    RocksDbCompression::Zstd,
+                ],
+                ..RocksDbProfile::Mixed.settings()
+            },
+        }
+    }
+}
+
+impl RocksDbTuning {
+    /// Creates the tuning of a profile, without overriding any of its settings.
+    pub fn new(profile: RocksDbProfile) -> Self {
+        RocksDbTuning {
+            profile,
+            ..RocksDbTuning::default()
+        }
+    }
+
+    /// Checks that the column families have distinct names, and non-empty prefixes of which
+    /// none is a prefix of another.
+    fn check_column_families(&self) -> Result<(), RocksDbStoreInternalError> {
+        for (index, family) in self.column_families.iter().enumerate() {
+            ensure!(
+                !family.name.is_empty() && family.name != rocksdb::DEFAULT_COLUMN_FAMILY_NAME,
+                RocksDbStoreInternalError::InvalidTuning(format!(
+                    "invalid column family name {:?}",
+                    family.name
+                ))
+            );
+            ensure!(
+                !family.key_prefix.is_empty(),
+                RocksDbStoreInternalError::InvalidTuning(format!(
+                    "the key prefix of column family {:?} is empty",
+                    family.name
+                ))
+            );
+            for other in &self.column_families[..index] {
+                ensure!(
+                    other.name != family.name,
+                    RocksDbStoreInternalError::InvalidTuning(format!(
+                        "duplicate column family name {:?}",
+                        family.name
+                    ))
+                );
+                ensure!(
+                    !other.key_prefix.starts_with(&family.key_prefix)
+                        && !family.key_prefix.starts_with(&other.key_prefix),
+                    RocksDbStoreInternalError::InvalidTuning(format!(
+                        "the key prefixes of column families {:?} and {:?} overlap",
+                        other.name, family.name
+                    ))
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the options of the database and of its column families.
+    fn options(&self, num_cpus: i32, total_ram: usize) -> rocksdb::Options {
+        let settings = self.profile.settings();
+        let mut options = rocksdb::Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        // Flush in-memory buffer to disk more often
+        options.set_write_buffer_size(settings.write_buffer_size);
+        options.set_max_write_buffer_number(settings.max_write_buffer_number);
+        options.set_min_write_buffer_number_to_merge(settings.min_write_buffer_number_to_merge);
+        let compression_per_level = self
+            .compression_per_level
+            .as_deref()
+            .unwrap_or(settings.compression_per_level);
+        if let [compression] = compression_per_level {
+            options.set_compression_type(compression.compression_type());
+        } else {
+            let compression_per_level = compression_per_level
+                .iter()
+                .map(|compression| compression.compression_type())
+                .collect::<Vec<_>>();
+            options.set_compression_per_level(&compression_per_level);
+        }
+        options.set_level_zero_slowdown_writes_trigger(settings.level_zero_slowdown_writes_trigger);
+        options.set_level_zero_stop_writes_trigger(settings.level_zero_stop_writes_trigger);
+        // We use half the available CPUs for RocksDB parallelism to allow concurrent operations
+        // while leaving resources for other application tasks. Using a third of CPUs for background
+        // jobs (compactions, flushes) balances background maintenance with foreground operations,
+        // preventing RocksDB from consuming too many system resources, while still keeping good
+        // performance.
+        options.increase_parallelism((num_cpus / 2).max(1));
+        options.set_max_background_jobs((num_cpus / 3).max(1));
+        options.set_level_compaction_dynamic_level_bytes(true);
+
+        options.set_compaction_style(DBCompactionStyle::Level);
+        options.set_target_file_size_base(settings.write_buffer_size as u64);
+
+        let mut block_options = BlockBasedOptions::default();
+        block_options.set_pin_l0_filter_and_index_blocks_in_cache(true);
+        block_options.set_cache_index_and_filter_blocks(true);
+        let bloom_filter_bits_per_key = self
+            .bloom_filter_bits_per_key
+            .unwrap_or(settings.bloom_filter_bits_per_key);
+        if bloom_filter_bits_per_key > 0 {
+            block_options.set_bloom_filter(f64::from(bloom_filter_bits_per_key), false);
+            block_options.set_whole_key_filtering(true);
+        }
+        if settings.hash_index {
+            block_options.set_data_block_index_type(DataBlockIndexType::BinaryAndHash);
+        }
+        let block_cache_size = self
+            .block_cache_size
+            .map_or(total_ram / settings.block_cache_ram_divisor, |size| {
+                usize::try_from(size).unwrap_or(usize::MAX)
+            });
+        block_options.set_block_cache(&Cache::new_hyper_clock_cache(
+            block_cache_size,
+            HYPER_CLOCK_CACHE_BLOCK_SIZE,
+        ));
+        options.set_block_based_table_factory(&block_options);
+        options
+    }
+}
+
 fn check_key_size(key: &[u8]) -> Result<(), RocksDbStoreInternalError> {
     ensure!(
         key.len() <= MAX_KEY_SIZE,
@@ -116,13 +442,224 @@ fn check_key_size(key: &[u8]) -> Result<(), RocksDbStoreInternalError> {
     Ok(())
 }
 
+/// The state of the emulation of TTL, shared by the stores of a database.
+struct RocksDbExpirations {
+    /// Whether the stores with the given start keys may have values written with a TTL.
+    /// Until then, their reads and writes do not need to track expiration times. A start
+    /// key is checked in the database when a store first accesses it.
+    in_use: RwLock<HashMap<Vec<u8>, bool>>,
+    /// Whether a task removes the expired values of the database.
+    sweeping: AtomicBool,
+    /// Orders the writes tracking expiration times with the sweeps.
+    lock: Mutex<()>,
+    /// The clock that the expiration times are compared to.
+    clock: TtlClock,
+}
+
+impl RocksDbExpirations {
+    fn new(clock: TtlClock) -> Self {
+        Self {
+            in_use: RwLock::default(),
+            sweeping: AtomicBool::new(false),
+            lock: Mutex::new(()),
+            clock,
+        }
+    }
+}
+
+/// Returns whether the expiration time recorded in `bytes` is not after `now`.
+fn is_expired(bytes: &[u8], now: Timestamp) -> bool {
+    bytes
+        .try_into()
+        .is_ok_and(|bytes| u64::from_be_bytes(bytes) <= now.micros())
+}
+
+fn get_expiration_times_key(full_key: &[u8]) -> Vec<u8> {
+    let mut key = vec![EXPIRATION_TIMES_PREFIX];
+    key.extend(full_key);
+    key
+}
+
 #[derive(Clone)]
 struct RocksDbStoreExecutor {
     db: Arc<DB>,
     start_key: Vec<u8>,
+    column_families: Arc<[RocksDbColumnFamily]>,
+    expirations: Arc<RocksDbExpirations>,
 }
 
 impl RocksDbStoreExecutor {
+    fn handle(&self, name: &str) -> Arc<BoundColumnFamily<'_>> {
+        self.db
+            .cf_handle(name)
+            .expect("all column families are opened with the database")
+    }
+
+    /// Returns the key without the root key domain, which the prefixes of the column
+    /// families are matched against, or `None` for the keys listing the root keys.
+    fn column_family_key(full_key: &[u8]) -> Option<&[u8]> {
+        full_key.strip_prefix(&ROOT_KEY_DOMAIN[..])
+    }
+
+    /// Returns the column family storing `full_key`.
+    fn column_family(&self, full_key: &[u8]) -> Arc<BoundColumnFamily<'_>> {
+        let name = Self::column_family_key(full_key)
+            .and_then(|key| {
+                self.column_families
+                    .iter()
+                    .find(|family| key.starts_with(&family.key_prefix))
+            })
+            .map_or(rocksdb::DEFAULT_COLUMN_FAMILY_NAME, |family| {
+                family.name.as_str()
+            });
+        self.handle(name)
+    }
+
+    /// Returns the column families storing keys that start with `full_prefix`.
+    fn column_families_for_prefix(&self, full_prefix: &[u8]) -> Vec<Arc<BoundColumnFamily<'_>>> {
+        let Some(prefix) = Self::column_family_key(full_prefix) else {
+            return vec![self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME)];
+        };
+        if let Some(family) = self
+            .column_families
+            .iter()
+            .find(|family| prefix.starts_with(&family.key_prefix))
+        {
+            return vec![self.handle(&family.name)];
+        }
+        let mut handles = vec![self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME)];
+        handles.extend(
+            self.column_families
+                .iter()
+                .filter(|family| family.key_prefix.starts_with(prefix))
+                .map(|family| self.handle(&family.name)),
+        );
+        handles
+    }
+
+    /// Returns whether the values of this store may have been written with a TTL.
+    fn expirations_in_use_internal(&self) -> Result<bool, RocksDbStoreInternalError> {
+        let in_use = &self.expirations.in_use;
+        if let Some(in_use) = in_use
+            .read()
+            .expect("RocksDB expirations lock should not be poisoned")
+            .get(&self.start_key)
+        {
+            return Ok(*in_use);
+        }
+        let mut in_use = in_use
+            .write()
+            .expect("RocksDB expirations lock should not be poisoned");
+        if let Some(in_use) = in_use.get(&self.start_key) {
+            return Ok(*in_use);
+        }
+        let has_expiration_times = self.has_expiration_times_internal(&self.start_key)?;
+        in_use.insert(self.start_key.clone(), has_expiration_times);
+        Ok(has_expiration_times)
+    }
+
+    /// Records that this store writes values with a TTL, and starts removing the expired
+    /// values of the database if needed.
+    fn start_expirations(&self) {
+        let mut in_use = self
+            .expirations
+            .in_use
+            .write()
+            .expect("RocksDB expirations lock should not be poisoned");
+        if in_use.insert(self.start_key.clone(), true) != Some(true) {
+            // The stores with shorter start keys may access the same values.
+            for (start_key, in_use) in in_use.iter_mut() {
+                if self.start_key.starts_with(start_key) {
+                    *in_use = true;
+                }
+            }
+        }
+        drop(in_use);
+        if !self.expirations.sweeping.swap(true, Ordering::SeqCst) {
+            self.spawn_sweeper();
+        }
+    }
+
+    /// Returns the current time if some values of this store may expire.
+    fn expiration_time_internal(&self) -> Result<Option<Timestamp>, RocksDbStoreInternalError> {
+        Ok(self
+            .expirations_in_use_internal()?
+            .then(|| self.expirations.clock.current_time()))
+    }
+
+    /// Returns which of the values of `full_keys` have expired.
+    fn expired_internal<'a>(
+        &self,
+        full_keys: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<Vec<bool>, RocksDbStoreInternalError> {
+        let full_keys = full_keys.into_iter();
+        let Some(now) = self.expiration_time_internal()? else {
+            return Ok(full_keys.map(|_| false).collect());
+        };
+        let default_column_family = self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
+        let times_keys = full_keys.map(get_expiration_times_key).collect::<Vec<_>>();
+        let times = self.db.multi_get_cf(
+            times_keys
+                .iter()
+                .map(|times_key| (&default_column_family, times_key)),
+        );
+        times
+            .into_iter()
+            .map(|time| Ok(time?.is_some_and(|time| is_expired(&time, now))))
+            .collect()
+    }
+
+    /// Returns the keys starting with `full_prefix` whose values have expired.
+    fn expired_keys_internal(
+        &self,
+        full_prefix: &[u8],
+    ) -> Result<BTreeSet<Vec<u8>>, RocksDbStoreInternalError> {
+        let mut expired_keys = BTreeSet::new();
+        let Some(now) = self.expiration_time_internal()? else {
+            return Ok(expired_keys);
+        };
+        let default_column_family = self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
+        let times_prefix = get_expiration_times_key(full_prefix);
+        let mut iter = self.db.raw_iterator_cf(&default_column_family);
+        iter.seek(&times_prefix);
+        while let Some(times_key) = iter.key() {
+            if !times_key.starts_with(&times_prefix) {
+                break;
+            }
+            if iter.value().is_some_and(|time| is_expired(time, now)) {
+                expired_keys.insert(times_key[1..].to_vec());
+            }
+            iter.next();
+        }
+        iter.status()?;
+        Ok(expired_keys)
+    }
+
+    fn read_value_bytes_internal(
+        &self,
+        full_key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, RocksDbStoreInternalError> {
+        let column_family = self.column_family(&full_key);
+        let Some(value) = self.db.get_cf(&column_family, &full_key)? else {
+            return Ok(None);
+        };
+        if self.expired_internal([full_key.as_slice()])?[0] {
+            return Ok(None);
+        }
+        Ok(Some(value))
+    }
+
+    fn contains_key_internal(&self, full_key: Vec<u8>) -> Result<bool, RocksDbStoreInternalError> {
+        let column_family = self.column_family(&full_key);
+        if !self.db.key_may_exist_cf(&column_family, &full_key) {
+            return Ok(false);
+        }
+        if self.db.get_cf(&column_family, &full_key)?.is_none() {
+            return Ok(false);
+        }
+        Ok(!self.expired_internal([full_key.as_slice()])?[0])
+    }
+
     pub fn contains_keys_internal(
         &self,
         keys: Vec<Vec<u8>>,
@@ -135,14 +672,20 @@ impl RocksDbStoreExecutor {
             check_key_size(&key)?;
             let mut full_key = self.start_key.to_vec();
             full_key.extend(key);
-            if self.db.key_may_exist(&full_key) {
+            let column_family = self.column_family(&full_key);
+            if self.db.key_may_exist_cf(&column_family, &full_key) {
                 indices.push(i);
-                keys_red.push(full_key);
+                keys_red.push((column_family, full_key));
             }
         }
-        let values_red = self.db.multi_get(keys_red);
-        for (index, value) in indices.into_iter().zip(values_red) {
-            results[index] = value?.is_some();
+        let values_red = self.db.multi_get_cf(
+            keys_red
+                .iter()
+                .map(|(column_family, key)| (column_family, key)),
+        );
+        let expired = self.expired_internal(keys_red.iter().map(|(_, key)| key.as_slice()))?;
+        for ((index, value), expired) in indices.into_iter().zip(values_red).zip(expired) {
+            results[index] = value?.is_some() && !expired;
         }
         Ok(results)
     }
@@ -162,8 +705,17 @@ impl RocksDbStoreExecutor {
                 full_key
             })
             .collect::<Vec<_>>();
-        let entries = self.db.multi_get(&full_keys);
-        Ok(entries.into_iter().collect::<Result<_, _>>()?)
+        let column_families = full_keys
+            .iter()
+            .map(|full_key| self.column_family(full_key))
+            .collect::<Vec<_>>();
+        let entries = self.db.multi_get_cf(column_families.iter().zip(&full_keys));
+        let expired = self.expired_internal(full_keys.iter().map(Vec::as_slice))?;
+        entries
+            .into_iter()
+            .zip(expired)
+            .map(|(entry, expired)| Ok(entry?.filter(|_| !expired)))
+            .collect()
     }
 
     fn find_keys_by_prefix_internal(
@@ -174,17 +726,67 @@ impl RocksDbStoreExecutor {
         let mut prefix = self.start_key.clone();
         prefix.extend(key_prefix);
         let len = prefix.len();
-        let mut iter = self.db.raw_iterator();
+        let column_families = self.column_families_for_prefix(&prefix);
+        let expired_keys = self.expired_keys_internal(&prefix)?;
         let mut keys = Vec::new();
-        iter.seek(&prefix);
-        let mut next_key = iter.key();
-        while let Some(key) = next_key {
-            if !key.starts_with(&prefix) {
-                break;
+        for column_family in &column_families {
+            let mut iter = self.db.raw_iterator_cf(column_family);
+            iter.seek(&prefix);
+            let mut next_key = iter.key();
+            while let Some(key) = next_key {
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                if !expired_keys.contains(key) {
+                    keys.push(key[len..].to_vec());
+                }
+                iter.next();
+                next_key = iter.key();
             }
-            keys.push(key[len..].to_vec());
-            iter.next();
-            next_key = iter.key();
+        }
+        if column_families.len() > 1 {
+            keys.sort_unstable();
+        }
+        Ok(keys)
+    }
+
+    fn find_keys_by_prefix_after_internal(
+        &self,
+        (key_prefix, start_after, limit): (Vec<u8>, Option<Vec<u8>>, usize),
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        check_key_size(&key_prefix)?;
+        let mut prefix = self.start_key.clone();
+        prefix.extend(key_prefix);
+        let len = prefix.len();
+        let mut start_key = prefix.clone();
+        start_key.extend(start_after.iter().flatten());
+        let column_families = self.column_families_for_prefix(&prefix);
+        let expired_keys = self.expired_keys_internal(&prefix)?;
+        let mut keys = Vec::new();
+        for column_family in &column_families {
+            let mut iter = self.db.raw_iterator_cf(column_family);
+            iter.seek(&start_key);
+            if start_after.is_some() && iter.key() == Some(&start_key[..]) {
+                iter.next();
+            }
+            // Each column family contributes at most `limit` keys before they are merged.
+            let mut count = 0;
+            let mut next_key = iter.key();
+            while let Some(key) = next_key {
+                if count == limit || !key.starts_with(&prefix) {
+                    break;
+                }
+                if !expired_keys.contains(key) {
+                    keys.push(key[len..].to_vec());
+                    count += 1;
+                }
+                iter.next();
+                next_key = iter.key();
+            }
+        }
+        if column_families.len() > 1 {
+            keys.sort_unstable();
+            keys.truncate(limit);
         }
         Ok(keys)
     }
@@ -198,20 +800,27 @@ impl RocksDbStoreExecutor {
         let mut prefix = self.start_key.clone();
         prefix.extend(key_prefix);
         let len = prefix.len();
-        let mut iter = self.db.raw_iterator();
+        let column_families = self.column_families_for_prefix(&prefix);
+        let expired_keys = self.expired_keys_internal(&prefix)?;
         let mut key_values = Vec::new();
-        iter.seek(&prefix);
-        let mut next_key = iter.key();
-        while let Some(key) = next_key {
-            if !key.starts_with(&prefix) {
-                break;
-            }
-            if let Some(value) = iter.value() {
-                let key_value = (key[len..].to_vec(), value.to_vec());
-                key_values.push(key_value);
+        for column_family in &column_families {
+            let mut iter = self.db.raw_iterator_cf(column_family);
+            iter.seek(&prefix);
+            let mut next_key = iter.key();
+            while let Some(key) = next_key {
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                if let Some(value) = iter.value().filter(|_| !expired_keys.contains(key)) {
+                    let key_value = (key[len..].to_vec(), value.to_vec());
+                    key_values.push(key_value);
+                }
+                iter.next();
+                next_key = iter.key();
             }
-            iter.next();
-            next_key = iter.key();
+        }
+        if column_families.len() > 1 {
+            key_values.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
         }
         Ok(key_values)
     }
@@ -220,7 +829,18 @@ impl RocksDbStoreExecutor {
         &self,
         batch: Batch,
         write_root_key: bool,
+        expiration: Option<Timestamp>,
     ) -> Result<(), RocksDbStoreInternalError> {
+        // A value written without a TTL must no longer expire.
+        let expirations_guard =
+            (expiration.is_some() || self.expirations_in_use_internal()?).then(|| {
+                self.expirations
+                    .lock
+                    .lock()
+                    .expect("RocksDB expirations lock should not be poisoned")
+            });
+        let track_expirations = expirations_guard.is_some();
+        let default_column_family = self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
         let mut inner_batch = rocksdb::WriteBatchWithTransaction::default();
         for operation in batch.operations {
             match operation {
@@ -228,13 +848,28 @@ impl RocksDbStoreExecutor {
                     check_key_size(&key)?;
                     let mut full_key = self.start_key.to_vec();
                     full_key.extend(key);
-                    inner_batch.delete(&full_key)
+                    inner_batch.delete_cf(&self.column_family(&full_key), &full_key);
+                    if track_expirations {
+                        let times_key = get_expiration_times_key(&full_key);
+                        inner_batch.delete_cf(&default_column_family, times_key);
+                    }
                 }
                 WriteOperation::Put { key, value } => {
                     check_key_size(&key)?;
                     let mut full_key = self.start_key.to_vec();
                     full_key.extend(key);
-                    inner_batch.put(&full_key, value)
+                    let times_key = get_expiration_times_key(&full_key);
+                    if let Some(expiration) = expiration {
+                        let expiration = expiration.micros().to_be_bytes();
+                        let mut index_key = vec![EXPIRATION_INDEX_PREFIX];
+                        index_key.extend(expiration);
+                        index_key.extend(&full_key);
+                        inner_batch.put_cf(&default_column_family, index_key, []);
+                        inner_batch.put_cf(&default_column_family, times_key, expiration);
+                    } else if track_expirations {
+                        inner_batch.delete_cf(&default_column_family, times_key);
+                    }
+                    inner_batch.put_cf(&self.column_family(&full_key), &full_key, value)
                 }
                 WriteOperation::DeletePrefix { key_prefix } => {
                     check_key_size(&key_prefix)?;
@@ -242,18 +877,141 @@ impl RocksDbStoreExecutor {
                     full_key1.extend(&key_prefix);
                     let full_key2 =
                         get_upper_bound_option(&full_key1).expect("the first entry cannot be 255");
-                    inner_batch.delete_range(&full_key1, &full_key2);
+                    for column_family in self.column_families_for_prefix(&full_key1) {
+                        inner_batch.delete_range_cf(&column_family, &full_key1, &full_key2);
+                    }
+                    if track_expirations {
+                        inner_batch.delete_range_cf(
+                            &default_column_family,
+                            get_expiration_times_key(&full_key1),
+                            get_expiration_times_key(&full_key2),
+                        );
+                    }
                 }
             }
         }
         if write_root_key {
             let mut full_key = self.start_key.to_vec();
             full_key[0] = STORED_ROOT_KEYS_PREFIX;
-            inner_batch.put(&full_key, vec![]);
+            inner_batch.put_cf(&self.column_family(&full_key), &full_key, vec![]);
         }
         self.db.write(inner_batch)?;
         Ok(())
     }
+
+    /// Checks that the database was created with the configured column families, and
+    /// records them if the database is new. Databases created before the column families
+    /// were recorded have none.
+    fn check_column_families_internal(
+        &self,
+        is_new: bool,
+    ) -> Result<(), RocksDbStoreInternalError> {
+        let column_family = self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
+        let stored = match self.db.get_cf(&column_family, COLUMN_FAMILIES_KEY)? {
+            Some(bytes) => bcs::from_bytes::<Vec<RocksDbColumnFamily>>(&bytes)?,
+            None if is_new => {
+                let bytes = bcs::to_bytes(&*self.column_families)?;
+                self.db.put_cf(&column_family, COLUMN_FAMILIES_KEY, bytes)?;
+                return Ok(());
+            }
+            None => Vec::new(),
+        };
+        ensure!(
+            stored.as_slice() == &*self.column_families,
+            RocksDbStoreInternalError::ColumnFamiliesMismatch {
+                stored,
+                configured: self.column_families.to_vec(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Returns whether the database has values written with a TTL that are not removed yet,
+    /// with keys starting with `full_prefix`.
+    fn has_expiration_times_internal(
+        &self,
+        full_prefix: &[u8],
+    ) -> Result<bool, RocksDbStoreInternalError> {
+        let column_family = self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
+        let times_prefix = get_expiration_times_key(full_prefix);
+        let mut iter = self.db.raw_iterator_cf(&column_family);
+        iter.seek(&times_prefix);
+        iter.status()?;
+        Ok(iter.key().is_some_and(|key| key.starts_with(&times_prefix)))
+    }
+
+    /// Removes the values that have expired at time `now`, unless they were written again
+    /// since they expired.
+    fn sweep_expired_internal(&self, now: Timestamp) -> Result<(), RocksDbStoreInternalError> {
+        let _expirations_guard = self
+            .expirations
+            .lock
+            .lock()
+            .expect("RocksDB expirations lock should not be poisoned");
+        let default_column_family = self.handle(rocksdb::DEFAULT_COLUMN_FAMILY_NAME);
+        let mut inner_batch = rocksdb::WriteBatchWithTransaction::default();
+        let mut iter = self.db.raw_iterator_cf(&default_column_family);
+        iter.seek([EXPIRATION_INDEX_PREFIX]);
+        while let Some(index_key) = iter.key() {
+            let Some((expiration, full_key)) = index_key
+                .strip_prefix(&[EXPIRATION_INDEX_PREFIX])
+                .and_then(|entry| entry.split_first_chunk::<8>())
+            else {
+                break;
+            };
+            if u64::from_be_bytes(*expiration) > now.micros() {
+                break;
+            }
+            let times_key = get_expiration_times_key(full_key);
+            let current_expiration = self.db.get_cf(&default_column_family, &times_key)?;
+            if current_expiration.as_deref() == Some(expiration.as_slice()) {
+                inner_batch.delete_cf(&self.column_family(full_key), full_key);
+                inner_batch.delete_cf(&default_column_family, times_key);
+            }
+            inner_batch.delete_cf(&default_column_family, index_key);
+            iter.next();
+        }
+        iter.status()?;
+        self.db.write(inner_batch)?;
+        Ok(())
+    }
+
+    /// Starts a task that periodically removes the expired values of the database, until
+    /// the database is closed.
+    fn spawn_sweeper(&self) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("No runtime to remove the expired values of RocksDB");
+            return;
+        };
+        let weak_db = Arc::downgrade(&self.db);
+        let column_families = self.column_families.clone();
+        let expirations = self.expirations.clone();
+        runtime.spawn(async move {
+            loop {
+                timer::sleep(TTL_SWEEP_INTERVAL).await;
+                let Some(db) = Weak::upgrade(&weak_db) else {
+                    break;
+                };
+                let executor = RocksDbStoreExecutor {
+                    db,
+                    start_key: Vec::new(),
+                    column_families: column_families.clone(),
+                    expirations: expirations.clone(),
+                };
+                let now = expirations.clock.current_time();
+                let result =
+                    match tokio::task::spawn_blocking(move || executor.sweep_expired_internal(now))
+                        .await
+                    {
+                        Ok(result) => result,
+                        Err(error) => Err(error.into()),
+                    };
+                if let Err(error) = result {
+                    tracing::warn!("Failed to remove the expired values of RocksDB: {error}");
+                }
+            }
+        });
+    }
 }
 
 /// The inner client
@@ -275,6 +1033,12 @@ pub struct RocksDbStoreInternalConfig {
     spawn_mode: RocksDbSpawnMode,
     /// The common configuration of the key value store
     common_config: CommonStoreInternalConfig,
+    /// The tuning of the database
+    #[serde(default)]
+    tuning: RocksDbTuning,
+    /// The clock used to expire the values written with a TTL
+    #[serde(skip)]
+    clock: TtlClock,
 }
 
 impl RocksDbStoreInternal {
@@ -303,6 +1067,7 @@ impl RocksDbStoreInternal {
         if !std::path::Path::exists(&path_buf) {
             std::fs::create_dir(path_buf.clone())?;
         }
+        let is_new = !path_buf.join("CURRENT").exists();
         let sys = System::new_with_specifics(
             RefreshKind::nothing()
                 .with_cpu(CpuRefreshKind::everything())
@@ -310,46 +1075,31 @@ impl RocksDbStoreInternal {
         );
         let num_cpus = sys.cpus().len() as i32;
         let total_ram = sys.total_memory() as usize;
-        let mut options = rocksdb::Options::default();
-        options.create_if_missing(true);
-        options.create_missing_column_families(true);
-        // Flush in-memory buffer to disk more often
-        options.set_write_buffer_size(WRITE_BUFFER_SIZE);
-        options.set_max_write_buffer_number(MAX_WRITE_BUFFER_NUMBER);
-        options.set_compression_type(rocksdb::DBCompressionType::Lz4);
-        options.set_level_zero_slowdown_writes_trigger(12);
-        options.set_level_zero_stop_writes_trigger(20);
-        // We use half the available CPUs for RocksDB parallelism to allow concurrent operations
-        // while leaving resources for other application tasks. Using a third of CPUs for background
-        // jobs (compactions, flushes) balances background maintenance with foreground operations,
-        // preventing RocksDB from consuming too many system resources, while still keeping good
-        // performance.
-        options.increase_parallelism((num_cpus / 2).max(1));
-        options.set_max_background_jobs((num_cpus / 3).max(1));
-        options.set_level_compaction_dynamic_level_bytes(true);
-
-        options.set_compaction_style(DBCompactionStyle::Level);
-        options.set_target_file_size_base(WRITE_BUFFER_SIZE as u64);
-
-        let mut block_options = BlockBasedOptions::default();
-        block_options.set_pin_l0_filter_and_index_blocks_in_cache(true);
-        block_options.set_cache_index_and_filter_blocks(true);
-        // Allocate 1/4 of total RAM for RocksDB block cache, which is a reasonable balance:
-        // - Large enough to significantly improve read performance by caching frequently accessed blocks
-        // - Small enough to leave memory for other system components
-        // - Follows common practice for database caching in server environments
-        // - Prevents excessive memory pressure that could lead to swapping or OOM conditions
-        block_options.set_block_cache(&Cache::new_hyper_clock_cache(
-            total_ram / 4,
-            HYPER_CLOCK_CACHE_BLOCK_SIZE,
-        ));
-        options.set_block_based_table_factory(&block_options);
+        let tuning = &config.tuning;
+        tuning.check_column_families()?;
+        let options = tuning.options(num_cpus, total_ram);
+        let descriptors = std::iter::once(rocksdb::DEFAULT_COLUMN_FAMILY_NAME)
+            .chain(
+                tuning
+                    .column_families
+                    .iter()
+                    .map(|family| family.name.as_str()),
+            )
+            .map(|name| ColumnFamilyDescriptor::new(name, options.clone()))
+            .collect::<Vec<_>>();
 
-        let db = DB::open(&options, path_buf)?;
+        let db = DB::open_cf_descriptors(&options, path_buf, descriptors)?;
         let executor = RocksDbStoreExecutor {
             db: Arc::new(db),
             start_key,
+            column_families: tuning.column_families.clone().into(),
+            expirations: Arc::new(RocksDbExpirations::new(config.clock.clone())),
         };
+        executor.check_column_families_internal(is_new)?;
+        if executor.has_expiration_times_internal(&[])? {
+            executor.expirations.sweeping.store(true, Ordering::SeqCst);
+            executor.spawn_sweeper();
+        }
         Ok(RocksDbStoreInternal {
             executor,
             _path_with_guard: path_with_guard,
@@ -378,29 +1128,21 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
         key: &[u8],
     ) -> Result<Option<Vec<u8>>, RocksDbStoreInternalError> {
         check_key_size(key)?;
-        let db = self.executor.db.clone();
+        let executor = self.executor.clone();
         let mut full_key = self.executor.start_key.to_vec();
         full_key.extend(key);
         self.spawn_mode
-            .spawn(move |x| Ok(db.get(&x)?), full_key)
+            .spawn(move |x| executor.read_value_bytes_internal(x), full_key)
             .await
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool, RocksDbStoreInternalError> {
         check_key_size(key)?;
-        let db = self.executor.db.clone();
+        let executor = self.executor.clone();
         let mut full_key = self.executor.start_key.to_vec();
         full_key.extend(key);
         self.spawn_mode
-            .spawn(
-                move |x| {
-                    if !db.key_may_exist(&x) {
-                        return Ok(false);
-                    }
-                    Ok(db.get(&x)?.is_some())
-                },
-                full_key,
-            )
+            .spawn(move |x| executor.contains_key_internal(x), full_key)
             .await
     }
 
@@ -451,17 +1193,59 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
             )
             .await
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        let executor = self.executor.clone();
+        let query = (key_prefix.to_vec(), start_after.map(<[u8]>::to_vec), limit);
+        self.spawn_mode
+            .spawn(
+                move |x| executor.find_keys_by_prefix_after_internal(x),
+                query,
+            )
+            .await
+    }
 }
 
 impl WritableKeyValueStore for RocksDbStoreInternal {
     const MAX_VALUE_SIZE: usize = MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = true;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), RocksDbStoreInternalError> {
         let write_root_key = !self.root_key_written.fetch_or(true, Ordering::SeqCst);
         let executor = self.executor.clone();
         self.spawn_mode
             .spawn(
-                move |x| executor.write_batch_internal(x, write_root_key),
+                move |x| executor.write_batch_internal(x, write_root_key, None),
+                batch,
+            )
+            .await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), RocksDbStoreInternalError> {
+        let Some(ttl) = ttl else {
+            return self.write_batch(batch).await;
+        };
+        self.executor.start_expirations();
+        let expiration = self
+            .executor
+            .expirations
+            .clock
+            .current_time()
+            .saturating_add(TimeDelta::from_duration(ttl));
+        let write_root_key = !self.root_key_written.fetch_or(true, Ordering::SeqCst);
+        let executor = self.executor.clone();
+        self.spawn_mode
+            .spawn(
+                move |x| executor.write_batch_internal(x, write_root_key, Some(expiration)),
                 batch,
             )
             .await
@@ -496,6 +1280,10 @@ impl AdminKeyValueStore for RocksDbStoreInternal {
         Ok(store)
     }
 
+    fn clone_with_bounded_staleness(&self, _bounded_staleness: bool) -> Self {
+        self.clone()
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, RocksDbStoreInternalError> {
         let entries = std::fs::read_dir(config.path_with_guard.path_buf.clone())?;
         let mut namespaces = Vec::new();
@@ -585,6 +1373,8 @@ impl TestKeyValueStore for RocksDbStoreInternal {
             path_with_guard,
             spawn_mode,
             common_config,
+            tuning: RocksDbTuning::default(),
+            clock: TtlClock::default(),
         })
     }
 }
@@ -627,6 +1417,22 @@ pub enum RocksDbStoreInternalError {
     /// BCS serialization error.
     #[error(transparent)]
     BcsError(#[from] bcs::Error),
+
+    /// The tuning of the database is invalid
+    #[error("Invalid RocksDB tuning: {0}")]
+    InvalidTuning(String),
+
+    /// The column families differ from the ones the database was created with
+    #[error(
+        "The database was created with the column families {stored:?}, \
+        not {configured:?}"
+    )]
+    ColumnFamiliesMismatch {
+        /// The column families the database was created with.
+        stored: Vec<RocksDbColumnFamily>,
+        /// The configured column families.
+        configured: Vec<RocksDbColumnFamily>,
+    },
 }
 
 /// A path and the guard for the temporary directory if needed
@@ -696,10 +1502,181 @@ impl RocksDbStoreConfig {
             path_with_guard,
             spawn_mode,
             common_config: common_config.reduced(),
+            tuning: RocksDbTuning::default(),
+            clock: TtlClock::default(),
         };
         RocksDbStoreConfig {
             inner_config,
             storage_cache_config: common_config.storage_cache_config,
         }
     }
+
+    /// Sets the tuning of the database.
+    pub fn with_tuning(mut self, tuning: RocksDbTuning) -> Self {
+        self.inner_config.tuning = tuning;
+        self
+    }
+
+    /// Returns the tuning of the database.
+    pub fn tuning(&self) -> &RocksDbTuning {
+        &self.inner_config.tuning
+    }
+
+    /// Uses `clock` to expire the values written with a TTL.
+    #[cfg(with_testing)]
+    pub fn with_clock(mut self, clock: TtlClock) -> Self {
+        self.inner_config.clock = clock;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::generate_test_namespace;
+
+    async fn create_store_with_mocked_clock() -> (RocksDbStoreInternal, TtlClock) {
+        let clock = TtlClock::mocked(Timestamp::from(1_000));
+        let mut config = RocksDbStoreInternal::new_test_config().await.unwrap();
+        config.clock = clock.clone();
+        let namespace = generate_test_namespace();
+        let store = RocksDbStoreInternal::connect(&config, &namespace)
+            .await
+            .unwrap();
+        (store, clock)
+    }
+
+    #[tokio::test]
+    async fn test_rocks_db_values_expire_after_their_ttl() {
+        let (store, clock) = create_store_with_mocked_clock().await;
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 1], vec![1]);
+        batch.put_key_value_bytes(vec![1, 2], vec![2]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 3], vec![3]);
+        store.write_batch(batch).await.unwrap();
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(
+            store.read_value_bytes(&[1, 1]).await.unwrap(),
+            Some(vec![1])
+        );
+        assert_eq!(
+            store.find_keys_by_prefix(&[1]).await.unwrap(),
+            vec![vec![1], vec![2], vec![3]]
+        );
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(store.read_value_bytes(&[1, 1]).await.unwrap(), None);
+        assert!(!store.contains_key(&[1, 2]).await.unwrap());
+        assert_eq!(
+            store
+                .contains_keys(vec![vec![1, 2], vec![1, 3]])
+                .await
+                .unwrap(),
+            vec![false, true]
+        );
+        assert_eq!(
+            store
+                .read_multi_values_bytes(vec![vec![1, 1], vec![1, 3]])
+                .await
+                .unwrap(),
+            vec![None, Some(vec![3])]
+        );
+        assert_eq!(
+            store.find_keys_by_prefix(&[1]).await.unwrap(),
+            vec![vec![3]]
+        );
+        assert_eq!(
+            store.find_key_values_by_prefix(&[1]).await.unwrap(),
+            vec![(vec![3], vec![3])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rocks_db_expirations_are_tracked_per_root_key() {
+        let (store, _clock) = create_store_with_mocked_clock().await;
+        let store1 = store.clone_with_root_key(&[1]).unwrap();
+        let store2 = store.clone_with_root_key(&[2]).unwrap();
+        assert!(!store1.executor.expirations_in_use_internal().unwrap());
+        assert!(!store2.executor.expirations_in_use_internal().unwrap());
+        assert!(!store.executor.expirations_in_use_internal().unwrap());
+
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1]);
+        store1
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert!(store1.executor.expirations_in_use_internal().unwrap());
+        assert!(!store2.executor.expirations_in_use_internal().unwrap());
+        // The store without a root key accesses the values of both.
+        assert!(store.executor.expirations_in_use_internal().unwrap());
+
+        // The start keys that are not known are checked in the database.
+        let store3 = store.clone_with_root_key(&[1]).unwrap();
+        store3.executor.expirations.in_use.write().unwrap().clear();
+        assert!(store3.executor.expirations_in_use_internal().unwrap());
+        assert!(!store2.executor.expirations_in_use_internal().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rocks_db_sweep_removes_expired_values() {
+        let (store, clock) = create_store_with_mocked_clock().await;
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1]);
+        batch.put_key_value_bytes(vec![2], vec![2]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![3], vec![3]);
+        store
+            .write_batch_with_ttl(batch, Some(Duration::from_secs(20)))
+            .await
+            .unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![2], vec![4]);
+        store.write_batch(batch).await.unwrap();
+
+        let executor = &store.executor;
+        let stored_keys = || {
+            let mut keys = Vec::new();
+            for key in [[1], [2], [3]] {
+                let mut full_key = executor.start_key.clone();
+                full_key.extend(key);
+                let column_family = executor.column_family(&full_key);
+                if executor
+                    .db
+                    .get_cf(&column_family, &full_key)
+                    .unwrap()
+                    .is_some()
+                {
+                    keys.push(key[0]);
+                }
+            }
+            keys
+        };
+
+        clock.advance(Duration::from_secs(10));
+        executor
+            .sweep_expired_internal(clock.current_time())
+            .unwrap();
+        // The value rewritten without a TTL is kept.
+        assert_eq!(stored_keys(), vec![2, 3]);
+        assert!(executor.has_expiration_times_internal(&[]).unwrap());
+
+        clock.advance(Duration::from_secs(10));
+        executor
+            .sweep_expired_internal(clock.current_time())
+            .unwrap();
+        assert_eq!(stored_keys(), vec![2]);
+        assert!(!executor.has_expiration_times_internal(&[]).unwrap());
+        assert_eq!(store.read_value_bytes(&[2]).await.unwrap(), Some(vec![4]));
+    }
 }
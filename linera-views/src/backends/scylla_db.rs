@@ -6,18 +6,33 @@
 //! The current connection is done via a Session and a corresponding primary key called
 //! "namespace". The maximum number of concurrent queries is controlled by
 //! `max_concurrent_queries`.
+//!
+//! The statements are prepared once per namespace, so that the driver routes each query to
+//! a replica of the partition of its root key. Multi-key operations are split into chunks
+//! which are queried concurrently, at most `max_concurrent_multi_key_queries` at a time.
+//!
+//! Values written with a TTL use the TTL of ScyllaDB, rounded up to a whole number of
+//! seconds.
 
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
 use std::{
     collections::{hash_map::Entry, BTreeSet, HashMap},
+    future::Future,
     ops::Deref,
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
 use async_lock::{Semaphore, SemaphoreGuard};
-use futures::{future::join_all, FutureExt as _, StreamExt};
-use linera_base::ensure;
+use futures::{stream, FutureExt as _, StreamExt, TryStreamExt as _};
+#[cfg(with_metrics)]
+use linera_base::prometheus_util::register_int_counter_vec;
+use linera_base::{ensure, time::Duration};
+#[cfg(with_metrics)]
+use prometheus::IntCounterVec;
 use scylla::{
     batch::BatchStatement,
+    frame::response::result::CqlValue,
     prepared_statement::PreparedStatement,
     statement::batch::BatchType,
     transport::errors::{DbError, QueryError},
@@ -33,6 +48,7 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::UnorderedBatch,
     common::{get_uleb128_size, get_upper_bound_option},
+    compression::{CompressedStore, CompressedStoreConfig, CompressionError, ValueCompression},
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
@@ -84,10 +100,35 @@ const VISIBLE_MAX_VALUE_SIZE: usize = RAW_MAX_VALUE_SIZE
 /// correct.
 const MAX_BATCH_SIZE: usize = 5000;
 
+/// The maximal TTL of ScyllaDB, in seconds, which is 20 years.
+const MAX_TTL_SECONDS: u64 = 630_720_000;
+
+#[cfg(with_metrics)]
+/// The total number of statements prepared for multi-key queries.
+static MULTI_KEYS_PREPARED_STATEMENTS_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "scylla_db_multi_keys_prepared_statements",
+        "Number of statements prepared for ScyllaDB multi-key queries",
+        &[],
+    )
+});
+
+#[cfg(with_metrics)]
+/// The total number of chunks queried by multi-key operations.
+static MULTI_KEYS_QUERIES_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "scylla_db_multi_keys_queries",
+        "Number of chunks queried by ScyllaDB multi-key operations",
+        &[],
+    )
+});
+
 /// The client for ScyllaDB:
 /// * The session allows to pass queries
 /// * The namespace that is being assigned to the database
 /// * The prepared queries used for implementing the features of `KeyValueStore`.
+/// * The prepared multi-key queries, indexed by their number of keys, which are prepared
+///   when first used.
 struct ScyllaDbClient {
     session: Session,
     namespace: String,
@@ -97,10 +138,15 @@ struct ScyllaDbClient {
     write_batch_delete_prefix_bounded: BatchStatement,
     write_batch_deletion: BatchStatement,
     write_batch_insertion: BatchStatement,
+    write_batch_insertion_with_ttl: BatchStatement,
     find_keys_by_prefix_unbounded: PreparedStatement,
     find_keys_by_prefix_bounded: PreparedStatement,
+    find_keys_by_prefix_after_unbounded: PreparedStatement,
+    find_keys_by_prefix_after_bounded: PreparedStatement,
     find_key_values_by_prefix_unbounded: PreparedStatement,
     find_key_values_by_prefix_bounded: PreparedStatement,
+    read_multi_values: Mutex<HashMap<usize, PreparedStatement>>,
+    contains_keys: Mutex<HashMap<usize, PreparedStatement>>,
 }
 
 impl ScyllaDbClient {
@@ -156,6 +202,11 @@ impl ScyllaDbClient {
             namespace
         );
         let write_batch_insertion = session.prepare(query).await?.into();
+        let query = format!(
+            "INSERT INTO kv.{} (root_key, k, v) VALUES (?, ?, ?) USING TTL ?",
+            namespace
+        );
+        let write_batch_insertion_with_ttl = session.prepare(query).await?.into();
 
         let query = format!(
             "SELECT k FROM kv.{} WHERE root_key = ? AND k >= ? ALLOW FILTERING",
@@ -167,6 +218,16 @@ impl ScyllaDbClient {
             namespace
         );
         let find_keys_by_prefix_bounded = session.prepare(query).await?;
+        let query = format!(
+            "SELECT k FROM kv.{} WHERE root_key = ? AND k >= ? LIMIT ? ALLOW FILTERING",
+            namespace
+        );
+        let find_keys_by_prefix_after_unbounded = session.prepare(query).await?;
+        let query = format!(
+            "SELECT k FROM kv.{} WHERE root_key = ? AND k >= ? AND k < ? LIMIT ? ALLOW FILTERING",
+            namespace
+        );
+        let find_keys_by_prefix_after_bounded = session.prepare(query).await?;
 
         let query = format!(
             "SELECT k,v FROM kv.{} WHERE root_key = ? AND k >= ? ALLOW FILTERING",
@@ -188,13 +249,73 @@ impl ScyllaDbClient {
             write_batch_delete_prefix_bounded,
             write_batch_deletion,
             write_batch_insertion,
+            write_batch_insertion_with_ttl,
             find_keys_by_prefix_unbounded,
             find_keys_by_prefix_bounded,
+            find_keys_by_prefix_after_unbounded,
+            find_keys_by_prefix_after_bounded,
             find_key_values_by_prefix_unbounded,
             find_key_values_by_prefix_bounded,
+            read_multi_values: Mutex::default(),
+            contains_keys: Mutex::default(),
         })
     }
 
+    /// Returns the statement selecting the `selection` of `num_keys` keys of a partition,
+    /// preparing it if it is not in `statements` yet.
+    async fn get_multi_keys_statement(
+        &self,
+        statements: &Mutex<HashMap<usize, PreparedStatement>>,
+        selection: &str,
+        num_keys: usize,
+    ) -> Result<PreparedStatement, ScyllaDbStoreInternalError> {
+        if let Some(statement) = statements.lock().unwrap().get(&num_keys) {
+            return Ok(statement.clone());
+        }
+        #[cfg(with_metrics)]
+        MULTI_KEYS_PREPARED_STATEMENTS_COUNT
+            .with_label_values(&[])
+            .inc();
+        let mut group_query = "?".to_string();
+        group_query.push_str(&",?".repeat(num_keys - 1));
+        let query = format!(
+            "SELECT {} FROM kv.{} WHERE root_key = ? AND k IN ({}) ALLOW FILTERING",
+            selection, self.namespace, group_query
+        );
+        let statement = self.session.prepare(query).await?;
+        statements
+            .lock()
+            .unwrap()
+            .insert(num_keys, statement.clone());
+        Ok(statement)
+    }
+
+    /// Returns the positions of each distinct key in `keys`, and the values to bind
+    /// to a multi-key query: the `root_key` followed by the distinct keys.
+    #[allow(clippy::type_complexity)]
+    fn group_keys(
+        root_key: &[u8],
+        keys: Vec<Vec<u8>>,
+    ) -> Result<(HashMap<Vec<u8>, Vec<usize>>, Vec<Vec<u8>>), ScyllaDbStoreInternalError> {
+        let mut map = HashMap::<Vec<u8>, Vec<usize>>::new();
+        let mut inputs = Vec::new();
+        inputs.push(root_key.to_vec());
+        for (i_key, key) in keys.into_iter().enumerate() {
+            Self::check_key_size(&key)?;
+            match map.entry(key.clone()) {
+                Entry::Occupied(entry) => {
+                    let entry = entry.into_mut();
+                    entry.push(i_key);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(vec![i_key]);
+                    inputs.push(key);
+                }
+            }
+        }
+        Ok((map, inputs))
+    }
+
     async fn read_value_internal(
         &self,
         root_key: &[u8],
@@ -222,33 +343,13 @@ impl ScyllaDbClient {
         keys: Vec<Vec<u8>>,
     ) -> Result<Vec<Option<Vec<u8>>>, ScyllaDbStoreInternalError> {
         let num_keys = keys.len();
-        let session = &self.session;
-        let mut map = HashMap::<Vec<u8>, Vec<usize>>::new();
-        let mut inputs = Vec::new();
-        inputs.push(root_key.to_vec());
-        for (i_key, key) in keys.into_iter().enumerate() {
-            Self::check_key_size(&key)?;
-            match map.entry(key.clone()) {
-                Entry::Occupied(entry) => {
-                    let entry = entry.into_mut();
-                    entry.push(i_key);
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert(vec![i_key]);
-                    inputs.push(key);
-                }
-            }
-        }
-        let num_unique_keys = map.len();
-        let mut group_query = "?".to_string();
-        group_query.push_str(&",?".repeat(num_unique_keys - 1));
-        let query = format!(
-            "SELECT k,v FROM kv.{} WHERE root_key = ? AND k IN ({}) ALLOW FILTERING",
-            self.namespace, group_query
-        );
-
-        let mut rows = session
-            .query_iter(&*query, &inputs)
+        let (map, inputs) = Self::group_keys(root_key, keys)?;
+        let statement = self
+            .get_multi_keys_statement(&self.read_multi_values, "k,v", map.len())
+            .await?;
+        let mut rows = self
+            .session
+            .execute_iter(statement, &inputs)
             .await?
             .rows_stream::<(Vec<u8>, Vec<u8>)>()?;
 
@@ -269,33 +370,13 @@ impl ScyllaDbClient {
         keys: Vec<Vec<u8>>,
     ) -> Result<Vec<bool>, ScyllaDbStoreInternalError> {
         let num_keys = keys.len();
-        let session = &self.session;
-        let mut map = HashMap::<Vec<u8>, Vec<usize>>::new();
-        let mut inputs = Vec::new();
-        inputs.push(root_key.to_vec());
-        for (i_key, key) in keys.into_iter().enumerate() {
-            Self::check_key_size(&key)?;
-            match map.entry(key.clone()) {
-                Entry::Occupied(entry) => {
-                    let entry = entry.into_mut();
-                    entry.push(i_key);
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert(vec![i_key]);
-                    inputs.push(key);
-                }
-            }
-        }
-        let num_unique_keys = map.len();
-        let mut group_query = "?".to_string();
-        group_query.push_str(&",?".repeat(num_unique_keys - 1));
-        let query = format!(
-            "SELECT k FROM kv.{} WHERE root_key = ? AND k IN ({}) ALLOW FILTERING",
-            self.namespace, group_query
-        );
-
-        let mut rows = session
-            .query_iter(&*query, &inputs)
+        let (map, inputs) = Self::group_keys(root_key, keys)?;
+        let statement = self
+            .get_multi_keys_statement(&self.contains_keys, "k", map.len())
+            .await?;
+        let mut rows = self
+            .session
+            .execute_iter(statement, &inputs)
             .await?
             .rows_stream::<(Vec<u8>,)>()?;
 
@@ -331,10 +412,12 @@ impl ScyllaDbClient {
         &self,
         root_key: &[u8],
         batch: UnorderedBatch,
+        ttl: Option<Duration>,
     ) -> Result<(), ScyllaDbStoreInternalError> {
         let session = &self.session;
         let mut batch_query = scylla::statement::batch::Batch::new(BatchType::Unlogged);
         let mut batch_values = Vec::new();
+        let root_key = CqlValue::Blob(root_key.to_vec());
         let query1 = &self.write_batch_delete_prefix_unbounded;
         let query2 = &self.write_batch_delete_prefix_bounded;
         Self::check_batch_len(&batch)?;
@@ -342,12 +425,16 @@ impl ScyllaDbClient {
             Self::check_key_size(&key_prefix)?;
             match get_upper_bound_option(&key_prefix) {
                 None => {
-                    let values = vec![root_key.to_vec(), key_prefix];
+                    let values = vec![root_key.clone(), CqlValue::Blob(key_prefix)];
                     batch_values.push(values);
                     batch_query.append_statement(query1.clone());
                 }
                 Some(upper_bound) => {
-                    let values = vec![root_key.to_vec(), key_prefix, upper_bound];
+                    let values = vec![
+                        root_key.clone(),
+                        CqlValue::Blob(key_prefix),
+                        CqlValue::Blob(upper_bound),
+                    ];
                     batch_values.push(values);
                     batch_query.append_statement(query2.clone());
                 }
@@ -356,17 +443,27 @@ impl ScyllaDbClient {
         let query3 = &self.write_batch_deletion;
         for key in batch.simple_unordered_batch.deletions {
             Self::check_key_size(&key)?;
-            let values = vec![root_key.to_vec(), key];
+            let values = vec![root_key.clone(), CqlValue::Blob(key)];
             batch_values.push(values);
             batch_query.append_statement(query3.clone());
         }
-        let query4 = &self.write_batch_insertion;
+        let ttl_seconds = ttl.map(|ttl| {
+            let ttl_seconds =
+                (ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0)).clamp(1, MAX_TTL_SECONDS);
+            CqlValue::Int(i32::try_from(ttl_seconds).expect("the maximal TTL fits in an i32"))
+        });
         for (key, value) in batch.simple_unordered_batch.insertions {
             Self::check_key_size(&key)?;
             Self::check_value_size(&value)?;
-            let values = vec![root_key.to_vec(), key, value];
+            let mut values = vec![root_key.clone(), CqlValue::Blob(key), CqlValue::Blob(value)];
+            match &ttl_seconds {
+                None => batch_query.append_statement(self.write_batch_insertion.clone()),
+                Some(ttl_seconds) => {
+                    values.push(ttl_seconds.clone());
+                    batch_query.append_statement(self.write_batch_insertion_with_ttl.clone());
+                }
+            }
             batch_values.push(values);
-            batch_query.append_statement(query4.clone());
         }
         session.batch(&batch_query, batch_values).await?;
         Ok(())
@@ -405,6 +502,46 @@ impl ScyllaDbClient {
         Ok(keys)
     }
 
+    async fn find_keys_by_prefix_after_internal(
+        &self,
+        root_key: &[u8],
+        key_prefix: Vec<u8>,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, ScyllaDbStoreInternalError> {
+        Self::check_key_size(&key_prefix)?;
+        let session = &self.session;
+        let len = key_prefix.len();
+        // The first key strictly after `start_after` is `start_after` followed by a zero byte.
+        let mut start_key = key_prefix.clone();
+        if let Some(start_after) = start_after {
+            start_key.extend_from_slice(start_after);
+            start_key.push(0);
+        }
+        let limit = i32::try_from(limit).unwrap_or(i32::MAX);
+        let query_unbounded = &self.find_keys_by_prefix_after_unbounded;
+        let query_bounded = &self.find_keys_by_prefix_after_bounded;
+        let rows = match get_upper_bound_option(&key_prefix) {
+            None => {
+                let values = (root_key.to_vec(), start_key, limit);
+                session
+                    .execute_iter(query_unbounded.clone(), values)
+                    .await?
+            }
+            Some(upper_bound) => {
+                let values = (root_key.to_vec(), start_key, upper_bound, limit);
+                session.execute_iter(query_bounded.clone(), values).await?
+            }
+        };
+        let mut rows = rows.rows_stream::<(Vec<u8>,)>()?;
+        let mut keys = Vec::new();
+        while let Some(row) = rows.next().await {
+            let (key,) = row?;
+            keys.push(key[len..].to_vec());
+        }
+        Ok(keys)
+    }
+
     async fn find_key_values_by_prefix_internal(
         &self,
         root_key: &[u8],
@@ -445,6 +582,7 @@ pub struct ScyllaDbStoreInternal {
     store: Arc<ScyllaDbClient>,
     semaphore: Option<Arc<Semaphore>>,
     max_stream_queries: usize,
+    max_concurrent_multi_key_queries: Option<usize>,
     root_key: Vec<u8>,
 }
 
@@ -545,14 +683,10 @@ impl ReadableKeyValueStore for ScyllaDbStoreInternal {
         }
         let store = self.store.deref();
         let _guard = self.acquire().await;
-        let handles = keys
+        let queries = keys
             .chunks(MAX_MULTI_KEYS)
             .map(|keys| store.contains_keys_internal(&self.root_key, keys.to_vec()));
-        let results: Vec<_> = join_all(handles)
-            .await
-            .into_iter()
-            .collect::<Result<_, _>>()?;
-        Ok(results.into_iter().flatten().collect())
+        self.run_multi_key_queries(queries).await
     }
 
     async fn read_multi_values_bytes(
@@ -564,14 +698,10 @@ impl ReadableKeyValueStore for ScyllaDbStoreInternal {
         }
         let store = self.store.deref();
         let _guard = self.acquire().await;
-        let handles = keys
+        let queries = keys
             .chunks(MAX_MULTI_KEYS)
             .map(|keys| store.read_multi_values_internal(&self.root_key, keys.to_vec()));
-        let results: Vec<_> = join_all(handles)
-            .await
-            .into_iter()
-            .collect::<Result<_, _>>()?;
-        Ok(results.into_iter().flatten().collect())
+        self.run_multi_key_queries(queries).await
     }
 
     async fn find_keys_by_prefix(
@@ -585,6 +715,24 @@ impl ReadableKeyValueStore for ScyllaDbStoreInternal {
             .await
     }
 
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, ScyllaDbStoreInternalError> {
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        store
+            .find_keys_by_prefix_after_internal(
+                &self.root_key,
+                key_prefix.to_vec(),
+                start_after,
+                limit,
+            )
+            .await
+    }
+
     async fn find_key_values_by_prefix(
         &self,
         key_prefix: &[u8],
@@ -601,6 +749,7 @@ impl DirectWritableKeyValueStore for ScyllaDbStoreInternal {
     const MAX_BATCH_SIZE: usize = MAX_BATCH_SIZE;
     const MAX_BATCH_TOTAL_SIZE: usize = MAX_BATCH_TOTAL_SIZE;
     const MAX_VALUE_SIZE: usize = VISIBLE_MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = true;
 
     // ScyllaDB cannot take a `crate::batch::Batch` directly. Indeed, if a delete is
     // followed by a write, then the delete takes priority. See the sentence "The first
@@ -612,7 +761,21 @@ impl DirectWritableKeyValueStore for ScyllaDbStoreInternal {
     async fn write_batch(&self, batch: Self::Batch) -> Result<(), ScyllaDbStoreInternalError> {
         let store = self.store.deref();
         let _guard = self.acquire().await;
-        store.write_batch_internal(&self.root_key, batch).await
+        store
+            .write_batch_internal(&self.root_key, batch, None)
+            .await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Self::Batch,
+        ttl: Duration,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        store
+            .write_batch_internal(&self.root_key, batch, Some(ttl))
+            .await
     }
 }
 
@@ -630,6 +793,10 @@ pub struct ScyllaDbStoreInternalConfig {
     pub uri: String,
     /// The common configuration of the key value store
     common_config: CommonStoreInternalConfig,
+    /// The maximal number of chunks that a multi-key operation queries at the same time,
+    /// if any
+    #[serde(default)]
+    max_concurrent_multi_key_queries: Option<usize>,
 }
 
 impl AdminKeyValueStore for ScyllaDbStoreInternal {
@@ -656,11 +823,13 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
             .max_concurrent_queries
             .map(|n| Arc::new(Semaphore::new(n)));
         let max_stream_queries = config.common_config.max_stream_queries;
+        let max_concurrent_multi_key_queries = config.max_concurrent_multi_key_queries;
         let root_key = get_big_root_key(&[]);
         Ok(Self {
             store,
             semaphore,
             max_stream_queries,
+            max_concurrent_multi_key_queries,
             root_key,
         })
     }
@@ -669,15 +838,21 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
         let store = self.store.clone();
         let semaphore = self.semaphore.clone();
         let max_stream_queries = self.max_stream_queries;
+        let max_concurrent_multi_key_queries = self.max_concurrent_multi_key_queries;
         let root_key = get_big_root_key(root_key);
         Ok(Self {
             store,
             semaphore,
             max_stream_queries,
+            max_concurrent_multi_key_queries,
             root_key,
         })
     }
 
+    fn clone_with_bounded_staleness(&self, _bounded_staleness: bool) -> Self {
+        self.clone()
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, ScyllaDbStoreInternalError> {
         let session = SessionBuilder::new()
             .known_node(config.uri.as_str())
@@ -745,9 +920,7 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
             .await?;
 
         let query = "DROP KEYSPACE IF EXISTS kv;";
-        let prepared = session.prepare(query).await?;
-
-        session.execute_unpaged(&prepared, &[]).await?;
+        session.query_unpaged(query, &[]).await?;
         Ok(())
     }
 
@@ -815,8 +988,7 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
         }";
 
         // Execute the query
-        let prepared = session.prepare(query).await?;
-        session.execute_unpaged(&prepared, &[]).await?;
+        session.query_unpaged(query, &[]).await?;
 
         // Create a table if it doesn't exist
         // The schema appears too complicated for non-trivial reasons.
@@ -827,8 +999,7 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
         );
 
         // Execute the query
-        let prepared = session.prepare(&*query).await?;
-        session.execute_unpaged(&prepared, &[]).await?;
+        session.query_unpaged(query, &[]).await?;
         Ok(())
     }
 
@@ -844,8 +1015,7 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
             .await?;
 
         let query = format!("DROP TABLE IF EXISTS kv.{};", namespace);
-        let prepared = session.prepare(&*query).await?;
-        let _result = session.execute_unpaged(&prepared, &[]).await?;
+        session.query_unpaged(query, &[]).await?;
         Ok(())
     }
 }
@@ -859,6 +1029,30 @@ impl ScyllaDbStoreInternal {
         }
     }
 
+    /// Runs the `queries` on the chunks of a multi-key operation, at most
+    /// `max_concurrent_multi_key_queries` at a time, and concatenates their results in order.
+    async fn run_multi_key_queries<T>(
+        &self,
+        queries: impl ExactSizeIterator<
+            Item = impl Future<Output = Result<Vec<T>, ScyllaDbStoreInternalError>>,
+        >,
+    ) -> Result<Vec<T>, ScyllaDbStoreInternalError> {
+        let num_queries = queries.len();
+        #[cfg(with_metrics)]
+        MULTI_KEYS_QUERIES_COUNT
+            .with_label_values(&[])
+            .inc_by(num_queries as u64);
+        let limit = self
+            .max_concurrent_multi_key_queries
+            .unwrap_or(num_queries)
+            .max(1);
+        let results = stream::iter(queries)
+            .buffered(limit)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(results.into_iter().flatten().collect())
+    }
+
     fn check_namespace(namespace: &str) -> Result<(), ScyllaDbStoreInternalError> {
         if !namespace.is_empty()
             && namespace.len() <= 48
@@ -888,7 +1082,11 @@ impl TestKeyValueStore for JournalingKeyValueStore<ScyllaDbStoreInternal> {
             max_concurrent_queries: Some(TEST_SCYLLA_DB_MAX_CONCURRENT_QUERIES),
             max_stream_queries: TEST_SCYLLA_DB_MAX_STREAM_QUERIES,
         };
-        Ok(ScyllaDbStoreInternalConfig { uri, common_config })
+        Ok(ScyllaDbStoreInternalConfig {
+            uri,
+            common_config,
+            max_concurrent_multi_key_queries: None,
+        })
     }
 }
 
@@ -897,18 +1095,21 @@ impl TestKeyValueStore for JournalingKeyValueStore<ScyllaDbStoreInternal> {
 pub type ScyllaDbStore = MeteredStore<
     LruCachingStore<
         MeteredStore<
-            ValueSplittingStore<MeteredStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>,
+            CompressedStore<
+                ValueSplittingStore<MeteredStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>,
+            >,
         >,
     >,
 >;
 
 /// The `ScyllaDbStore` composed type
 #[cfg(not(with_metrics))]
-pub type ScyllaDbStore =
-    LruCachingStore<ValueSplittingStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>;
+pub type ScyllaDbStore = LruCachingStore<
+    CompressedStore<ValueSplittingStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>,
+>;
 
 /// The `ScyllaDbStoreConfig` input type
-pub type ScyllaDbStoreConfig = LruCachingConfig<ScyllaDbStoreInternalConfig>;
+pub type ScyllaDbStoreConfig = LruCachingConfig<CompressedStoreConfig<ScyllaDbStoreInternalConfig>>;
 
 impl ScyllaDbStoreConfig {
     /// Creates a `ScyllaDbStoreConfig` from the inputs.
@@ -916,13 +1117,33 @@ impl ScyllaDbStoreConfig {
         let inner_config = ScyllaDbStoreInternalConfig {
             uri,
             common_config: common_config.reduced(),
+            max_concurrent_multi_key_queries: None,
+        };
+        let inner_config = CompressedStoreConfig {
+            inner_config,
+            compression: None,
         };
         ScyllaDbStoreConfig {
             inner_config,
             storage_cache_config: common_config.storage_cache_config,
         }
     }
+
+    /// Sets the maximal number of chunks that a multi-key operation queries at the same
+    /// time. Without a limit, all the chunks are queried at once.
+    pub fn with_max_concurrent_multi_key_queries(mut self, limit: Option<usize>) -> Self {
+        self.inner_config
+            .inner_config
+            .max_concurrent_multi_key_queries = limit;
+        self
+    }
+
+    /// Sets the compression of the values written from now on.
+    pub fn with_compression(mut self, compression: Option<ValueCompression>) -> Self {
+        self.inner_config.compression = compression;
+        self
+    }
 }
 
 /// The combined error type for the `ScyllaDbStore`.
-pub type ScyllaDbStoreError = ValueSplittingError<ScyllaDbStoreInternalError>;
+pub type ScyllaDbStoreError = CompressionError<ValueSplittingError<ScyllaDbStoreInternalError>>;
@@ -0,0 +1,425 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adds the accounting of the storage accesses of each view to a key-value store.
+//!
+//! A [`StorageStatsStore`] records the keys and bytes read and written into a
+//! [`StorageStatsCollector`]. The contexts of the views created on top of such a store
+//! register the base key of every view with the collector, and each access is attributed to
+//! the view with the longest base key that is a prefix of the accessed key. The statistics
+//! are also exported as Prometheus metrics, with the hexadecimal base key of the view as
+//! label.
+//!
+//! The accounting is opt-in: the stores of the storage backends don't include it, and a
+//! store has to be wrapped explicitly, as done by the test validator of the SDK.
+
+use std::{
+    collections::BTreeMap,
+    ops::AddAssign,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use linera_base::{prometheus_util::register_int_counter_vec, time::Duration};
+use prometheus::IntCounterVec;
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueStore;
+use crate::{
+    batch::{Batch, WriteOperation},
+    store::{
+        AdminKeyValueStore, KeyIterable as _, KeyValueIterable as _, ReadableKeyValueStore,
+        WithError, WritableKeyValueStore,
+    },
+};
+
+/// The maximal number of views whose accesses are accounted separately. The accesses of the
+/// views registered after that are attributed to their closest registered ancestor.
+const MAX_REGISTERED_VIEWS: usize = 10_000;
+
+/// The metric counting the keys read by each view.
+static VIEW_KEYS_READ: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "view_keys_read",
+        "The number of keys read by each view",
+        &["base_key"],
+    )
+});
+
+/// The metric counting the bytes read by each view.
+static VIEW_BYTES_READ: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "view_bytes_read",
+        "The number of bytes read by each view",
+        &["base_key"],
+    )
+});
+
+/// The metric counting the keys written by each view.
+static VIEW_KEYS_WRITTEN: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "view_keys_written",
+        "The number of keys written or deleted by each view",
+        &["base_key"],
+    )
+});
+
+/// The metric counting the bytes written by each view.
+static VIEW_BYTES_WRITTEN: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "view_bytes_written",
+        "The number of bytes written by each view",
+        &["base_key"],
+    )
+});
+
+/// The storage accesses of a view.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StorageStats {
+    /// The number of keys read, including the absent ones.
+    pub keys_read: u64,
+    /// The number of bytes of the keys and values returned by the reads.
+    pub bytes_read: u64,
+    /// The number of keys written or deleted. Deleting a prefix counts as one key.
+    pub keys_written: u64,
+    /// The number of bytes of the keys and values written.
+    pub bytes_written: u64,
+}
+
+impl AddAssign for StorageStats {
+    fn add_assign(&mut self, other: Self) {
+        self.keys_read += other.keys_read;
+        self.bytes_read += other.bytes_read;
+        self.keys_written += other.keys_written;
+        self.bytes_written += other.bytes_written;
+    }
+}
+
+/// The statistics of the storage accesses of the views sharing a store, indexed by the base
+/// keys of the views.
+#[derive(Clone, Debug, Default)]
+pub struct StorageStatsCollector {
+    views: Arc<Mutex<BTreeMap<Vec<u8>, StorageStats>>>,
+}
+
+impl StorageStatsCollector {
+    /// Accounts the accesses to the keys starting with `base_key` separately.
+    pub fn register_view(&self, base_key: &[u8]) {
+        let mut views = self.views.lock().unwrap();
+        if views.len() < MAX_REGISTERED_VIEWS && !views.contains_key(base_key) {
+            views.insert(base_key.to_vec(), StorageStats::default());
+        }
+    }
+
+    /// Returns the statistics of the views that accessed the storage. The accesses outside
+    /// of any registered view are attributed to the empty base key.
+    pub fn stats(&self) -> BTreeMap<Vec<u8>, StorageStats> {
+        let views = self.views.lock().unwrap();
+        views
+            .iter()
+            .filter(|(_, stats)| **stats != StorageStats::default())
+            .map(|(base_key, stats)| (base_key.clone(), *stats))
+            .collect()
+    }
+
+    /// Returns the statistics of all the storage accesses.
+    pub fn total(&self) -> StorageStats {
+        let views = self.views.lock().unwrap();
+        let mut total = StorageStats::default();
+        for stats in views.values() {
+            total += *stats;
+        }
+        total
+    }
+
+    /// Resets the statistics of all the views.
+    pub fn reset(&self) {
+        let mut views = self.views.lock().unwrap();
+        for stats in views.values_mut() {
+            *stats = StorageStats::default();
+        }
+    }
+
+    /// Attributes the accesses to `key` to the view with the longest base key that is a
+    /// prefix of it.
+    fn record(&self, key: &[u8], accesses: StorageStats) {
+        let mut views = self.views.lock().unwrap();
+        let length = (0..=key.len())
+            .rev()
+            .find(|length| views.contains_key(&key[..*length]))
+            .unwrap_or_default();
+        let base_key = &key[..length];
+        let label = hex::encode(base_key);
+        let labels = [label.as_str()];
+        VIEW_KEYS_READ
+            .with_label_values(&labels)
+            .inc_by(accesses.keys_read);
+        VIEW_BYTES_READ
+            .with_label_values(&labels)
+            .inc_by(accesses.bytes_read);
+        VIEW_KEYS_WRITTEN
+            .with_label_values(&labels)
+            .inc_by(accesses.keys_written);
+        VIEW_BYTES_WRITTEN
+            .with_label_values(&labels)
+            .inc_by(accesses.bytes_written);
+        *views.entry(base_key.to_vec()).or_default() += accesses;
+    }
+
+    fn record_read(&self, key: &[u8], keys_read: usize, bytes_read: usize) {
+        let accesses = StorageStats {
+            keys_read: keys_read as u64,
+            bytes_read: bytes_read as u64,
+            ..StorageStats::default()
+        };
+        self.record(key, accesses);
+    }
+
+    fn record_write(&self, key: &[u8], bytes_written: usize) {
+        let accesses = StorageStats {
+            keys_written: 1,
+            bytes_written: bytes_written as u64,
+            ..StorageStats::default()
+        };
+        self.record(key, accesses);
+    }
+}
+
+/// A wrapper of a store accounting the storage accesses of the views built on top of it.
+#[derive(Clone)]
+pub struct StorageStatsStore<K> {
+    /// The statistics of the storage accesses.
+    storage_stats: StorageStatsCollector,
+    /// The underlying store.
+    store: K,
+}
+
+impl<K> StorageStatsStore<K> {
+    /// Wraps `store` with a new collector of statistics.
+    pub fn new(store: K) -> Self {
+        Self {
+            storage_stats: StorageStatsCollector::default(),
+            store,
+        }
+    }
+
+    /// Returns the underlying store.
+    pub fn inner(&self) -> &K {
+        &self.store
+    }
+}
+
+impl<K> WithError for StorageStatsStore<K>
+where
+    K: WithError,
+{
+    type Error = K::Error;
+}
+
+impl<K> ReadableKeyValueStore for StorageStatsStore<K>
+where
+    K: ReadableKeyValueStore + Send + Sync,
+{
+    const MAX_KEY_SIZE: usize = K::MAX_KEY_SIZE;
+    type Keys = K::Keys;
+    type KeyValues = K::KeyValues;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    fn storage_stats(&self) -> Option<&StorageStatsCollector> {
+        Some(&self.storage_stats)
+    }
+
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        self.store.prefetch(keys)
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let result = self.store.read_value_bytes(key).await?;
+        let bytes_read = result.as_ref().map_or(0, Vec::len);
+        self.storage_stats.record_read(key, 1, bytes_read);
+        Ok(result)
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        let result = self.store.contains_key(key).await?;
+        self.storage_stats.record_read(key, 1, 0);
+        Ok(result)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        let result = self.store.contains_keys(keys.clone()).await?;
+        for key in &keys {
+            self.storage_stats.record_read(key, 1, 0);
+        }
+        Ok(result)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let result = self.store.read_multi_values_bytes(keys.clone()).await?;
+        for (key, value) in keys.iter().zip(&result) {
+            let bytes_read = value.as_ref().map_or(0, Vec::len);
+            self.storage_stats.record_read(key, 1, bytes_read);
+        }
+        Ok(result)
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        let result = self.store.find_keys_by_prefix(key_prefix).await?;
+        let (num_keys, keys_size) = result
+            .iterator()
+            .map(|key| key.map(|k| k.len()))
+            .collect::<Result<Vec<usize>, _>>()?
+            .into_iter()
+            .fold((0, 0), |(count, size), len| (count + 1, size + len));
+        self.storage_stats
+            .record_read(key_prefix, num_keys, keys_size);
+        Ok(result)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        let result = self.store.find_key_values_by_prefix(key_prefix).await?;
+        let (num_keys, key_values_size) = result
+            .iterator()
+            .map(|key_value| key_value.map(|(key, value)| key.len() + value.len()))
+            .collect::<Result<Vec<usize>, _>>()?
+            .into_iter()
+            .fold((0, 0), |(count, size), len| (count + 1, size + len));
+        self.storage_stats
+            .record_read(key_prefix, num_keys, key_values_size);
+        Ok(result)
+    }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let result = self
+            .store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await?;
+        let keys_size = result.iter().map(Vec::len).sum();
+        self.storage_stats
+            .record_read(key_prefix, result.len(), keys_size);
+        Ok(result)
+    }
+}
+
+impl<K> WritableKeyValueStore for StorageStatsStore<K>
+where
+    K: WritableKeyValueStore + Send + Sync,
+{
+    const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = K::SUPPORTS_TTL;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
+        for operation in &batch.operations {
+            match operation {
+                WriteOperation::Delete { key } => {
+                    self.storage_stats.record_write(key, key.len());
+                }
+                WriteOperation::DeletePrefix { key_prefix } => {
+                    self.storage_stats
+                        .record_write(key_prefix, key_prefix.len());
+                }
+                WriteOperation::Put { key, value } => {
+                    self.storage_stats
+                        .record_write(key, key.len() + value.len());
+                }
+            }
+        }
+        self.store.write_batch_with_ttl(batch, ttl).await
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        self.store.clear_journal().await
+    }
+}
+
+impl<K> AdminKeyValueStore for StorageStatsStore<K>
+where
+    K: AdminKeyValueStore + Send + Sync,
+{
+    type Config = K::Config;
+
+    fn get_name() -> String {
+        K::get_name()
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let store = K::connect(config, namespace).await?;
+        Ok(Self::new(store))
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let store = self.store.clone_with_root_key(root_key)?;
+        let storage_stats = self.storage_stats.clone();
+        Ok(Self {
+            storage_stats,
+            store,
+        })
+    }
+
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        let storage_stats = self.storage_stats.clone();
+        Self {
+            storage_stats,
+            store,
+        }
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        K::list_all(config).await
+    }
+
+    async fn list_root_keys(
+        config: &Self::Config,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        K::list_root_keys(config, namespace).await
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        K::delete_all(config).await
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        K::exists(config, namespace).await
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        K::create(config, namespace).await
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        K::delete(config, namespace).await
+    }
+}
+
+#[cfg(with_testing)]
+impl<K> TestKeyValueStore for StorageStatsStore<K>
+where
+    K: TestKeyValueStore + Send + Sync,
+{
+    async fn new_test_config() -> Result<K::Config, Self::Error> {
+        K::new_test_config().await
+    }
+}
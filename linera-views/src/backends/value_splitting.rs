@@ -3,7 +3,7 @@
 
 //! Adds support for large values to a given store by splitting them between several keys.
 
-use linera_base::ensure;
+use linera_base::{ensure, time::Duration};
 use thiserror::Error;
 
 use crate::{
@@ -199,6 +199,38 @@ where
         Ok(keys)
     }
 
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        // The other segments of a value come right after its first one, and before the keys
+        // extending its key, so the search continues after the first segment.
+        let mut big_start_after = match start_after {
+            Some(start_after) => Some(Self::get_segment_key(start_after, 0)?),
+            None => None,
+        };
+        let mut keys = Vec::new();
+        while keys.len() < limit {
+            let remaining = limit - keys.len();
+            let big_keys = self
+                .store
+                .find_keys_by_prefix_after(key_prefix, big_start_after.as_deref(), remaining)
+                .await?;
+            for big_key in &big_keys {
+                if Self::read_index_from_key(big_key)? == 0 {
+                    keys.push(big_key[..big_key.len() - 4].to_vec());
+                }
+            }
+            if big_keys.len() < remaining {
+                break;
+            }
+            big_start_after = big_keys.into_iter().last();
+        }
+        Ok(keys)
+    }
+
     async fn find_key_values_by_prefix(
         &self,
         key_prefix: &[u8],
@@ -239,8 +271,17 @@ where
     K::Error: 'static,
 {
     const MAX_VALUE_SIZE: usize = usize::MAX;
+    const SUPPORTS_TTL: bool = K::SUPPORTS_TTL;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
         let mut batch_new = Batch::new();
         for operation in batch.operations {
             match operation {
@@ -270,7 +311,7 @@ where
                 }
             }
         }
-        Ok(self.store.write_batch(batch_new).await?)
+        Ok(self.store.write_batch_with_ttl(batch_new, ttl).await?)
     }
 
     async fn clear_journal(&self) -> Result<(), Self::Error> {
@@ -299,6 +340,11 @@ where
         Ok(Self { store })
     }
 
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self {
+        let store = self.store.clone_with_bounded_staleness(bounded_staleness);
+        Self { store }
+    }
+
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
         Ok(K::list_all(config).await?)
     }
@@ -4,22 +4,73 @@
 //! This provides some common code for the linera-views.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
     },
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 
-use serde::de::DeserializeOwned;
+#[cfg(with_testing)]
+use linera_base::{data_types::TimeDelta, time::Duration};
+use linera_base::{data_types::Timestamp, ensure};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    batch::Batch,
+    context::{BaseKey, Context},
+    store::{KeyIterable as _, ReadableKeyValueStore as _},
+    views::ViewError,
+};
+
+/// The clock of a store, used to expire the values written with a TTL.
+#[derive(Clone, Debug, Default)]
+pub struct TtlClock {
+    /// The time of a mocked clock, in microseconds, or `None` for the system clock.
+    mocked_micros: Option<Arc<AtomicU64>>,
+}
+
+impl TtlClock {
+    /// Creates a mocked clock that shows `time` until it is advanced.
+    #[cfg(with_testing)]
+    pub fn mocked(time: Timestamp) -> Self {
+        let mocked_micros = Some(Arc::new(AtomicU64::new(time.micros())));
+        Self { mocked_micros }
+    }
+
+    /// Advances a mocked clock by `duration`. Panics if the clock is not mocked.
+    #[cfg(with_testing)]
+    pub fn advance(&self, duration: Duration) {
+        let mocked_micros = self
+            .mocked_micros
+            .as_ref()
+            .expect("only mocked clocks can be advanced");
+        let delta = TimeDelta::from_duration(duration).as_micros();
+        mocked_micros.fetch_add(delta, Ordering::SeqCst);
+    }
 
-use crate::views::ViewError;
+    /// Returns the current time.
+    pub fn current_time(&self) -> Timestamp {
+        match &self.mocked_micros {
+            None => Timestamp::now(),
+            Some(mocked_micros) => Timestamp::from(mocked_micros.load(Ordering::SeqCst)),
+        }
+    }
+}
 
 #[doc(hidden)]
 pub type HasherOutputSize = <sha3::Sha3_256 as sha3::digest::OutputSizeUser>::OutputSize;
 #[doc(hidden)]
 pub type HasherOutput = generic_array::GenericArray<u8, HasherOutputSize>;
 
+/// The number of keys loaded at once from storage when the keys of a view are read page
+/// by page.
+pub(crate) const KEY_PAGE_SIZE: usize = 1000;
+
 #[derive(Clone, Debug)]
 pub(crate) enum Update<T> {
     Removed,
@@ -65,6 +116,271 @@ impl DeletionSet {
     }
 }
 
+/// Keeps track of the number of entries of a map-like view, so that it can be obtained
+/// without iterating over the keys in storage.
+///
+/// The count is persisted next to the entries. Since entries are inserted and removed
+/// without reading storage, whether the modified keys were already in storage is looked
+/// up with a single query, either when the count is requested or by
+/// [`View::pre_save`][crate::views::View::pre_save] before the view is flushed.
+///
+/// Views saved before the count was introduced have no persisted count. It is then
+/// unknown, and rebuilt from the keys in storage the first time it is requested.
+#[derive(Debug)]
+pub(crate) struct EntryCounter {
+    /// The count as persisted in storage, if any.
+    stored_count: Option<usize>,
+    /// Where the count is persisted.
+    location: CountLocation,
+    /// What is known about the entries currently in storage.
+    storage: Mutex<StoredEntries>,
+}
+
+/// Where an [`EntryCounter`] persists the number of entries.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum CountLocation {
+    /// At a key of its own, next to the entries.
+    Tagged,
+    /// At the empty key of the entries, which is then not an entry. Such a view can't have
+    /// indices with an empty serialization.
+    EmptyKey,
+    /// Nowhere: the indices are zero-sized, so the only possible entry is at the empty key,
+    /// and the count is obtained by looking it up.
+    NotPersisted,
+}
+
+impl CountLocation {
+    /// Returns where a map-like view with indices of type `I` persists its count: at the
+    /// empty key, unless that key is the one of the only possible index.
+    pub(crate) fn for_index<I>() -> Self {
+        if std::mem::size_of::<I>() == 0 {
+            CountLocation::NotPersisted
+        } else {
+            CountLocation::EmptyKey
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct StoredEntries {
+    /// The number of entries in storage, if known.
+    count: Option<usize>,
+    /// Whether some of the keys are present in storage.
+    presence: BTreeMap<Vec<u8>, bool>,
+}
+
+impl EntryCounter {
+    /// Creates a counter from the persisted value. A missing value means that the count
+    /// is unknown.
+    pub(crate) fn post_load(
+        value: &Option<Vec<u8>>,
+        location: CountLocation,
+    ) -> Result<Self, ViewError> {
+        let stored_count = match location {
+            CountLocation::NotPersisted => None,
+            _ => from_bytes_option::<usize, ViewError>(value)?,
+        };
+        Ok(Self {
+            stored_count,
+            location,
+            storage: Mutex::new(StoredEntries {
+                count: stored_count,
+                presence: BTreeMap::new(),
+            }),
+        })
+    }
+
+    /// Returns the number of entries, if it can be computed without reading storage.
+    /// `cleared` indicates that all the entries in storage are going to be deleted.
+    pub(crate) fn known_count<T>(
+        &self,
+        cleared: bool,
+        updates: &BTreeMap<Vec<u8>, Update<T>>,
+    ) -> Option<usize> {
+        let storage = self.storage.lock().unwrap();
+        Self::compute_count(&storage, cleared, updates)
+    }
+
+    fn compute_count<T>(
+        storage: &StoredEntries,
+        cleared: bool,
+        updates: &BTreeMap<Vec<u8>, Update<T>>,
+    ) -> Option<usize> {
+        if cleared {
+            let count = updates
+                .values()
+                .filter(|update| matches!(update, Update::Set(_)))
+                .count();
+            return Some(count);
+        }
+        let mut count = storage.count?;
+        for (key, update) in updates {
+            match (update, *storage.presence.get(key)?) {
+                (Update::Set(_), false) => count += 1,
+                (Update::Removed, true) => count = count.saturating_sub(1),
+                _ => (),
+            }
+        }
+        Some(count)
+    }
+
+    /// Returns the number of entries. The context is the one of the entries.
+    pub(crate) async fn count<C, T>(
+        &self,
+        context: &C,
+        cleared: bool,
+        updates: &BTreeMap<Vec<u8>, Update<T>>,
+    ) -> Result<usize, ViewError>
+    where
+        C: Context,
+        ViewError: From<C::Error>,
+    {
+        if let Some(count) = self.known_count(cleared, updates) {
+            return Ok(count);
+        }
+        let count_is_known = self.storage.lock().unwrap().count.is_some();
+        if !count_is_known {
+            let base_key = &context.base_key().bytes;
+            let mut count = 0;
+            for key in context
+                .store()
+                .find_keys_by_prefix(base_key)
+                .await?
+                .iterator()
+            {
+                let key = key?;
+                if !(self.location == CountLocation::EmptyKey && key.is_empty()) {
+                    count += 1;
+                }
+            }
+            self.storage.lock().unwrap().count = Some(count);
+        }
+        self.look_up_presence(context, updates).await?;
+        let storage = self.storage.lock().unwrap();
+        Ok(Self::compute_count(&storage, cleared, updates)
+            .expect("the count and the presence of all updated keys are known"))
+    }
+
+    /// Looks up whether the updated keys whose presence is unknown are in storage.
+    async fn look_up_presence<C, T>(
+        &self,
+        context: &C,
+        updates: &BTreeMap<Vec<u8>, Update<T>>,
+    ) -> Result<(), ViewError>
+    where
+        C: Context,
+        ViewError: From<C::Error>,
+    {
+        let keys = {
+            let storage = self.storage.lock().unwrap();
+            updates
+                .keys()
+                .filter(|key| !storage.presence.contains_key(*key))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let full_keys = keys
+            .iter()
+            .map(|key| context.base_key().base_index(key))
+            .collect();
+        let presence = context.store().contains_keys(full_keys).await?;
+        let mut storage = self.storage.lock().unwrap();
+        storage.presence.extend(keys.into_iter().zip(presence));
+        Ok(())
+    }
+
+    /// Recomputes the number of entries in storage from its keys, discarding the
+    /// persisted count, and returns the number of entries.
+    pub(crate) async fn recount<C, T>(
+        &mut self,
+        context: &C,
+        cleared: bool,
+        updates: &BTreeMap<Vec<u8>, Update<T>>,
+    ) -> Result<usize, ViewError>
+    where
+        C: Context,
+        ViewError: From<C::Error>,
+    {
+        self.storage.get_mut().unwrap().count = None;
+        self.count(context, cleared, updates).await
+    }
+
+    /// Looks up what is needed for the count to be known when flushing. Unless it was
+    /// requested, an unknown count is not rebuilt.
+    pub(crate) async fn pre_save<C, T>(
+        &self,
+        context: &C,
+        cleared: bool,
+        updates: &BTreeMap<Vec<u8>, Update<T>>,
+    ) -> Result<(), ViewError>
+    where
+        C: Context,
+        ViewError: From<C::Error>,
+    {
+        if cleared || self.storage.lock().unwrap().count.is_none() {
+            return Ok(());
+        }
+        self.look_up_presence(context, updates).await
+    }
+
+    /// Persists at `key` the `count` obtained from [`EntryCounter::known_count`] before
+    /// the entries were flushed. This has to be called after the entries are flushed,
+    /// since clearing them deletes the persisted count. An unknown count is not persisted,
+    /// so that it is rebuilt when needed.
+    pub(crate) fn flush(
+        &mut self,
+        batch: &mut Batch,
+        key: Vec<u8>,
+        cleared: bool,
+        count: Option<usize>,
+    ) -> Result<(), ViewError> {
+        if cleared {
+            self.stored_count = None;
+        }
+        // Empty views leave no keys in storage.
+        let stored_count =
+            count.filter(|count| *count > 0 && self.location != CountLocation::NotPersisted);
+        if stored_count != self.stored_count {
+            match stored_count {
+                Some(count) => batch.put_key_value(key, &count)?,
+                None => batch.delete_key(key),
+            }
+            self.stored_count = stored_count;
+        }
+        let storage = self.storage.get_mut().unwrap();
+        storage.count = count;
+        storage.presence.clear();
+        Ok(())
+    }
+
+    /// Obtains the short key of an index of the entries, which must not be the key where
+    /// the count is persisted.
+    pub(crate) fn derive_short_key<I: Serialize + ?Sized>(
+        &self,
+        index: &I,
+    ) -> Result<Vec<u8>, ViewError> {
+        let short_key = BaseKey::derive_short_key(index)?;
+        ensure!(
+            self.location != CountLocation::EmptyKey || !short_key.is_empty(),
+            ViewError::EmptyIndex
+        );
+        Ok(short_key)
+    }
+}
+
+impl Clone for EntryCounter {
+    fn clone(&self) -> Self {
+        Self {
+            stored_count: self.stored_count,
+            location: self.location,
+            storage: Mutex::new(self.storage.lock().unwrap().clone()),
+        }
+    }
+}
+
 /// When wanting to find the entries in a `BTreeMap` with a specific prefix,
 /// one option is to iterate over all keys. Another is to select an interval
 /// that represents exactly the keys having that prefix. Which fortunately
@@ -115,6 +431,28 @@ where
     }
 }
 
+/// Returns at most `limit` of the sorted `entries` whose keys come strictly after
+/// `start_after`, together with the key to continue from if more entries remain.
+pub fn paginate<T>(
+    entries: Vec<T>,
+    key: impl Fn(&T) -> &[u8],
+    start_after: Option<&[u8]>,
+    limit: u32,
+) -> (Vec<T>, Option<Vec<u8>>) {
+    let mut remaining = entries
+        .into_iter()
+        .skip_while(|entry| start_after.is_some_and(|start_after| key(entry) <= start_after))
+        .peekable();
+    let page = remaining.by_ref().take(limit as usize).collect::<Vec<_>>();
+    let has_more = remaining.peek().is_some();
+    let continuation = match page.last() {
+        _ if !has_more => None,
+        Some(last) => Some(key(last).to_vec()),
+        None => start_after.map(<[u8]>::to_vec),
+    };
+    (page, continuation)
+}
+
 pub(crate) fn from_bytes_option_or_default<V: DeserializeOwned + Default, E>(
     key_opt: &Option<Vec<u8>>,
 ) -> Result<V, E>
@@ -352,6 +690,24 @@ mod tests {
     }
 }
 
+#[test]
+fn test_paginate() {
+    let keys = vec![vec![1], vec![2], vec![3]];
+    assert_eq!(
+        paginate(keys.clone(), Vec::as_slice, None, 2),
+        (vec![vec![1], vec![2]], Some(vec![2]))
+    );
+    assert_eq!(
+        paginate(keys.clone(), Vec::as_slice, Some(&[2]), 2),
+        (vec![vec![3]], None)
+    );
+    assert_eq!(
+        paginate(keys.clone(), Vec::as_slice, Some(&[1]), 0),
+        (vec![], Some(vec![1]))
+    );
+    assert_eq!(paginate(keys, Vec::as_slice, Some(&[3]), 2), (vec![], None));
+}
+
 #[test]
 fn test_upper_bound() {
     assert_eq!(get_upper_bound(&[255]), Unbounded);
@@ -113,6 +113,13 @@ pub trait Context: Clone {
         context.base_key_mut().bytes = base_key;
         context
     }
+
+    /// Hints that the values of the given keys are about to be read, so that the store
+    /// can start reading them into its cache in the background. This does not wait for
+    /// the values.
+    fn prefetch(&self, keys: Vec<Vec<u8>>) {
+        self.store().prefetch(keys)
+    }
 }
 
 /// Implementation of the [`Context`] trait on top of a DB client implementing
@@ -179,6 +186,17 @@ where
     fn base_key_mut(&mut self) -> &mut BaseKey {
         &mut self.base_key
     }
+
+    #[cfg(with_metrics)]
+    fn clone_with_base_key(&self, base_key: Vec<u8>) -> Self {
+        // The storage accesses of the subviews are accounted separately.
+        if let Some(storage_stats) = self.store.storage_stats() {
+            storage_stats.register_view(&base_key);
+        }
+        let mut context = self.clone();
+        context.base_key.bytes = base_key;
+        context
+    }
 }
 
 /// An implementation of [`crate::context::Context`] that stores all values in memory.
@@ -28,6 +28,17 @@ pub(crate) fn hash_name<T: ?Sized>() -> u32 {
     )
 }
 
+/// The number of entries of a page of a connection if `first` is not specified.
+pub(crate) const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// The maximal number of entries of a page of a connection.
+pub(crate) const MAX_PAGE_SIZE: usize = 1000;
+
+/// Returns the number of entries of a page of a connection requested with `first`.
+pub(crate) fn page_size(first: Option<usize>) -> usize {
+    first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
 /// A GraphQL-visible map item, complete with key.
 #[derive(async_graphql::SimpleObject)]
 #[graphql(name_type)]
@@ -45,11 +45,15 @@ The specific functionalities of the trait `View` are the following:
 
 The following views implement the `View` trait:
 * `RegisterView` implements the storing of a single data.
+* `HistoricalRegisterView` implements the storing of a single data along with its last values.
 * `LogView` implements a log, which is a list of entries that can be expanded.
 * `QueueView` implements a queue, which is a list of entries that can be expanded and reduced.
 * `MapView` implements a map with keys and values.
+* `SortedMapView` implements a map ordered by its keys that supports range queries.
+* `TtlMapView` implements a map whose entries expire after a deadline.
 * `SetView` implements a set with keys.
 * `CollectionView` implements a map whose values are views themselves.
+* `EnumView` implements an enum whose variants contain views.
 * `ReentrantCollectionView` implements a map for which different keys can be accessed independently.
 * `ViewContainer<C>` implements a `KeyValueStore` and is used internally.
 
@@ -83,6 +87,10 @@ pub mod backends;
 #[cfg(with_metrics)]
 pub mod metrics;
 
+/// Merkle proofs of the entries of maps.
+#[cfg(with_merkle_map)]
+pub mod merkle_proof;
+
 /// GraphQL implementations.
 #[cfg(with_graphql)]
 mod graphql;
@@ -95,6 +103,8 @@ pub mod random;
 #[cfg(with_testing)]
 pub mod test_utils;
 
+#[cfg(with_compression)]
+pub use backends::compression;
 #[cfg(with_dynamodb)]
 pub use backends::dynamo_db;
 #[cfg(with_indexeddb)]
@@ -105,10 +115,13 @@ pub use backends::metering;
 pub use backends::rocks_db;
 #[cfg(with_scylladb)]
 pub use backends::scylla_db;
-pub use backends::{journaling, lru_caching, memory, value_splitting};
+#[cfg(with_metrics)]
+pub use backends::storage_stats;
+pub use backends::{journaling, lru_caching, memory, replicated, value_splitting};
 pub use views::{
-    bucket_queue_view, collection_view, hashable_wrapper, key_value_store_view, log_view, map_view,
-    queue_view, reentrant_collection_view, register_view, set_view,
+    bucket_queue_view, collection_view, enum_view, hashable_wrapper, historical_register_view,
+    key_value_store_view, log_view, map_view, queue_view, reentrant_collection_view, register_view,
+    set_view, sorted_map_view, ttl_map_view,
 };
 /// Re-exports used by the derive macros of this library.
 #[doc(hidden)]
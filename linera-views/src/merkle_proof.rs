@@ -0,0 +1,166 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Merkle proofs that an entry belongs to a map.
+//!
+//! With the `merkle_map` feature, the hash of a [`ByteMapView`][crate::map_view::ByteMapView]
+//! is the root of a binary trie over the hashes of its keys: an entry is stored in a leaf
+//! at the shortest prefix of the bits of its key hash that no other entry shares. A
+//! [`MembershipProof`] of an entry is the list of the hashes of the siblings on the path
+//! from the root to that leaf, which is enough to recompute the root hash from the entry
+//! alone.
+
+use serde::{Deserialize, Serialize};
+use sha3::digest::Update as _;
+
+use crate::{common::HasherOutput, views::Hasher as _};
+
+/// The domain separators of the hashed nodes of the trie.
+#[repr(u8)]
+enum NodeTag {
+    /// A leaf holding exactly one entry.
+    Leaf,
+    /// An internal node with two children.
+    Internal,
+    /// An empty subtree.
+    Empty,
+}
+
+/// The proof that a key is present in a map with a given value.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MembershipProof {
+    /// The hashes of the siblings of the nodes on the path to the entry, from the root
+    /// downwards.
+    pub siblings: Vec<HasherOutput>,
+}
+
+/// Checks that `proof` shows that `key_bytes` maps to `value_bytes` in the map of hash
+/// `root_hash`.
+///
+/// The bytes are the ones that the map stores: for a [`MapView`][crate::map_view::MapView],
+/// the BCS serializations of the key and of the value.
+pub fn verify_proof(
+    root_hash: &HasherOutput,
+    key_bytes: &[u8],
+    value_bytes: &[u8],
+    proof: &MembershipProof,
+) -> bool {
+    let key_hash = key_hash(key_bytes);
+    if proof.siblings.len() > 8 * key_hash.len() {
+        return false;
+    }
+    let mut hash = leaf_hash(&key_hash, value_bytes);
+    for (depth, sibling) in proof.siblings.iter().enumerate().rev() {
+        hash = if bit(&key_hash, depth) {
+            internal_hash(sibling, &hash)
+        } else {
+            internal_hash(&hash, sibling)
+        };
+    }
+    hash == *root_hash
+}
+
+/// The entries of a map, as the hashes of their keys and of their leaves.
+#[derive(Default)]
+pub(crate) struct MerkleLeaves {
+    leaves: Vec<(HasherOutput, HasherOutput)>,
+}
+
+impl MerkleLeaves {
+    /// Adds an entry of the map.
+    pub(crate) fn push(&mut self, key_bytes: &[u8], value_bytes: &[u8]) {
+        let key_hash = key_hash(key_bytes);
+        let leaf_hash = leaf_hash(&key_hash, value_bytes);
+        self.leaves.push((key_hash, leaf_hash));
+    }
+
+    /// Computes the root hash of the trie.
+    pub(crate) fn root(mut self) -> HasherOutput {
+        self.leaves.sort_unstable();
+        subtree_hash(&self.leaves, 0)
+    }
+
+    /// Computes the proof for the entry of `key_bytes`, if it is present.
+    pub(crate) fn proof(mut self, key_bytes: &[u8]) -> Option<MembershipProof> {
+        self.leaves.sort_unstable();
+        let key_hash = key_hash(key_bytes);
+        let mut siblings = Vec::new();
+        let mut leaves = &self.leaves[..];
+        let mut depth = 0;
+        while leaves.len() > 1 {
+            let (left, right) = split(leaves, depth);
+            if bit(&key_hash, depth) {
+                siblings.push(subtree_hash(left, depth + 1));
+                leaves = right;
+            } else {
+                siblings.push(subtree_hash(right, depth + 1));
+                leaves = left;
+            }
+            depth += 1;
+        }
+        match leaves {
+            [(leaf_key_hash, _)] if *leaf_key_hash == key_hash => {
+                Some(MembershipProof { siblings })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Computes the hash of the subtree of the sorted `leaves` sharing their first `depth`
+/// bits.
+fn subtree_hash(leaves: &[(HasherOutput, HasherOutput)], depth: usize) -> HasherOutput {
+    match leaves {
+        [] => tagged_hash(NodeTag::Empty, &[]),
+        [(_, leaf_hash)] => leaf_hash.clone(),
+        _ => {
+            let (left, right) = split(leaves, depth);
+            internal_hash(
+                &subtree_hash(left, depth + 1),
+                &subtree_hash(right, depth + 1),
+            )
+        }
+    }
+}
+
+/// Splits the sorted `leaves` according to the bit of their key hash at `depth`.
+#[expect(clippy::type_complexity)]
+fn split(
+    leaves: &[(HasherOutput, HasherOutput)],
+    depth: usize,
+) -> (
+    &[(HasherOutput, HasherOutput)],
+    &[(HasherOutput, HasherOutput)],
+) {
+    let middle = leaves.partition_point(|(key_hash, _)| !bit(key_hash, depth));
+    leaves.split_at(middle)
+}
+
+fn bit(hash: &HasherOutput, depth: usize) -> bool {
+    (hash[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
+
+fn key_hash(key_bytes: &[u8]) -> HasherOutput {
+    let mut hasher = sha3::Sha3_256::default();
+    hasher.update(key_bytes);
+    hasher.finalize()
+}
+
+fn leaf_hash(key_hash: &HasherOutput, value_bytes: &[u8]) -> HasherOutput {
+    tagged_hash(NodeTag::Leaf, &[key_hash.as_slice(), value_bytes])
+}
+
+fn internal_hash(left: &HasherOutput, right: &HasherOutput) -> HasherOutput {
+    tagged_hash(NodeTag::Internal, &[left.as_slice(), right.as_slice()])
+}
+
+/// Hashes the concatenation of `parts`, all of which but the last one have a fixed
+/// length.
+fn tagged_hash(tag: NodeTag, parts: &[&[u8]]) -> HasherOutput {
+    let mut hasher = sha3::Sha3_256::default();
+    hasher.update(&[tag as u8]);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
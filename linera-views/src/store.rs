@@ -5,10 +5,13 @@
 
 use std::{fmt::Debug, future::Future};
 
+use linera_base::time::Duration;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 #[cfg(with_testing)]
 use crate::random::generate_test_namespace;
+#[cfg(with_metrics)]
+use crate::storage_stats::StorageStatsCollector;
 use crate::{
     batch::Batch,
     common::from_bytes_option,
@@ -92,6 +95,18 @@ pub trait ReadableKeyValueStore: WithError {
     /// Retrieve the number of stream queries.
     fn max_stream_queries(&self) -> usize;
 
+    /// Returns the collector of the statistics of the storage accesses of the views, if the
+    /// store has one.
+    #[cfg(with_metrics)]
+    fn storage_stats(&self) -> Option<&StorageStatsCollector> {
+        None
+    }
+
+    /// Hints that the values of the given keys are about to be read. A store with a cache
+    /// may start reading them in the background; this does not wait for the values. By
+    /// default, this does nothing.
+    fn prefetch(&self, _keys: Vec<Vec<u8>>) {}
+
     /// Retrieves a `Vec<u8>` from the database using the provided `key`.
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
 
@@ -147,6 +162,37 @@ pub trait ReadableKeyValueStore: WithError {
             Ok(values)
         }
     }
+
+    /// Finds at most `limit` keys matching the prefix that come strictly after `start_after`,
+    /// or all of them from the first one if `start_after` is `None`, in lexicographic order.
+    /// The prefix is not included in the returned keys.
+    ///
+    /// By default, all the keys matching the prefix are loaded. Stores that can seek to a key
+    /// override this so that only the requested keys are loaded.
+    fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Vec<u8>>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let keys = self.find_keys_by_prefix(key_prefix).await?;
+            let mut page = Vec::new();
+            for key in keys.iterator() {
+                if page.len() == limit {
+                    break;
+                }
+                let key = key?;
+                if start_after.is_none_or(|start_after| key > start_after) {
+                    page.push(key.to_vec());
+                }
+            }
+            Ok(page)
+        }
+    }
 }
 
 /// Low-level, asynchronous write key-value operations. Useful for storage APIs not based on views.
@@ -155,9 +201,33 @@ pub trait WritableKeyValueStore: WithError {
     /// The maximal size of values that can be stored.
     const MAX_VALUE_SIZE: usize;
 
+    /// Whether the values written by [`Self::write_batch_with_ttl`] expire.
+    const SUPPORTS_TTL: bool = false;
+
     /// Writes the `batch` in the database.
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error>;
 
+    /// Writes the `batch` in the database. If `ttl` is set, the values put by the batch
+    /// expire once `ttl` has elapsed. Depending on the store, expired values may still be
+    /// read until they are removed.
+    ///
+    /// Writing a batch with no `ttl` is the same as calling [`Self::write_batch`], and
+    /// writing a value without a TTL makes it no longer expire. Stores that do not
+    /// [support TTL](Self::SUPPORTS_TTL) ignore the `ttl` and keep the values.
+    fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> impl Future<Output = Result<(), Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let _ = ttl;
+            self.write_batch(batch).await
+        }
+    }
+
     /// Clears any journal entry that may remain.
     /// The journal is located at the `root_key`.
     async fn clear_journal(&self) -> Result<(), Self::Error>;
@@ -177,6 +247,12 @@ pub trait AdminKeyValueStore: WithError + Sized {
     /// Takes a connection and creates a new one with a different `root_key`.
     fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error>;
 
+    /// Takes a connection and creates a new one whose reads may be served by read replicas
+    /// lagging behind the primary database, if `bounded_staleness` is true. Stores without
+    /// read replicas return a plain clone, and the other stores pass it on to the stores
+    /// they are built on.
+    fn clone_with_bounded_staleness(&self, bounded_staleness: bool) -> Self;
+
     /// Obtains the list of existing namespaces.
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error>;
 
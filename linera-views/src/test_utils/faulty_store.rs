@@ -0,0 +1,345 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [`MemoryStore`] wrapper that can be programmed to fail or delay operations, to test how
+//! views and applications handle storage errors.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use linera_base::time::{timer, Duration};
+use thiserror::Error;
+
+use crate::{
+    batch::Batch,
+    context::ViewContext,
+    memory::{create_test_memory_store, MemoryStore, MemoryStoreError},
+    store::{KeyValueStoreError, ReadableKeyValueStore, WithError, WritableKeyValueStore},
+};
+
+/// The operations of a [`FaultyMemoryStore`] that can be programmed to fail.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StoreOperation {
+    /// A call to [`WritableKeyValueStore::write_batch`].
+    WriteBatch,
+    /// A call to [`ReadableKeyValueStore::read_value_bytes`].
+    ReadValueBytes,
+}
+
+/// A [`MemoryStore`] whose operations can be programmed to fail, or to take some time.
+///
+/// A failing operation has no effect on the stored data. The clones of a store share their
+/// data and their programmed faults.
+#[derive(Clone)]
+pub struct FaultyMemoryStore {
+    store: MemoryStore,
+    faults: Arc<Mutex<Faults>>,
+}
+
+/// The faults programmed for a [`FaultyMemoryStore`].
+#[derive(Default)]
+struct Faults {
+    write_batch: OperationFaults,
+    read_value_bytes: OperationFaults,
+    /// The time each operation takes before running.
+    latency: Duration,
+}
+
+/// The faults programmed for one [`StoreOperation`].
+#[derive(Default)]
+struct OperationFaults {
+    /// The number of calls so far.
+    calls: usize,
+    /// The errors to return, indexed by the number of the failing call.
+    failures: BTreeMap<usize, String>,
+}
+
+impl Faults {
+    fn operation(&mut self, operation: StoreOperation) -> &mut OperationFaults {
+        match operation {
+            StoreOperation::WriteBatch => &mut self.write_batch,
+            StoreOperation::ReadValueBytes => &mut self.read_value_bytes,
+        }
+    }
+}
+
+impl FaultyMemoryStore {
+    /// Wraps a [`MemoryStore`], initially without any faults.
+    pub fn new(store: MemoryStore) -> Self {
+        FaultyMemoryStore {
+            store,
+            faults: Arc::default(),
+        }
+    }
+
+    /// Creates a [`FaultyMemoryStore`] using a new memory store for testing.
+    pub fn new_for_testing() -> Self {
+        Self::new(create_test_memory_store())
+    }
+
+    /// Returns the wrapped [`MemoryStore`], whose operations never fail.
+    pub fn inner(&self) -> &MemoryStore {
+        &self.store
+    }
+
+    /// Makes the `n`th call to `operation` from now on fail with `error`, where `1` designates
+    /// the next call.
+    pub fn fail_nth(&self, operation: StoreOperation, n: usize, error: impl Into<String>) {
+        assert!(n > 0, "Calls are numbered starting from 1");
+        let mut faults = self.lock();
+        let faults = faults.operation(operation);
+        let call = faults.calls + n;
+        faults.failures.insert(call, error.into());
+    }
+
+    /// Makes every operation wait for `latency` before running.
+    pub fn set_latency(&self, latency: Duration) {
+        self.lock().latency = latency;
+    }
+
+    /// Returns the time every operation waits for before running.
+    pub fn latency(&self) -> Duration {
+        self.lock().latency
+    }
+
+    /// Returns the number of calls to `operation` so far.
+    pub fn calls(&self, operation: StoreOperation) -> usize {
+        self.lock().operation(operation).calls
+    }
+
+    /// Counts a call to `operation`, and returns the error programmed for it, if any.
+    ///
+    /// This is done by the store's own implementation of `operation`, and only needs to be
+    /// called by wrappers accessing the [`FaultyMemoryStore::inner`] store directly.
+    pub fn check_fault(&self, operation: StoreOperation) -> Result<(), FaultyStoreError> {
+        let mut faults = self.lock();
+        let faults = faults.operation(operation);
+        faults.calls += 1;
+        match faults.failures.remove(&faults.calls) {
+            Some(error) => Err(FaultyStoreError::InjectedFault(error)),
+            None => Ok(()),
+        }
+    }
+
+    async fn delay(&self) {
+        let latency = self.latency();
+        if !latency.is_zero() {
+            timer::sleep(latency).await;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Faults> {
+        self.faults
+            .lock()
+            .expect("FaultyMemoryStore lock should not be poisoned")
+    }
+}
+
+impl WithError for FaultyMemoryStore {
+    type Error = FaultyStoreError;
+}
+
+impl ReadableKeyValueStore for FaultyMemoryStore {
+    const MAX_KEY_SIZE: usize = MemoryStore::MAX_KEY_SIZE;
+    type Keys = <MemoryStore as ReadableKeyValueStore>::Keys;
+    type KeyValues = <MemoryStore as ReadableKeyValueStore>::KeyValues;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, FaultyStoreError> {
+        self.delay().await;
+        self.check_fault(StoreOperation::ReadValueBytes)?;
+        Ok(self.store.read_value_bytes(key).await?)
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, FaultyStoreError> {
+        self.delay().await;
+        Ok(self.store.contains_key(key).await?)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, FaultyStoreError> {
+        self.delay().await;
+        Ok(self.store.contains_keys(keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, FaultyStoreError> {
+        self.delay().await;
+        Ok(self.store.read_multi_values_bytes(keys).await?)
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, FaultyStoreError> {
+        self.delay().await;
+        Ok(self.store.find_keys_by_prefix(key_prefix).await?)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, FaultyStoreError> {
+        self.delay().await;
+        Ok(self.store.find_key_values_by_prefix(key_prefix).await?)
+    }
+}
+
+impl WritableKeyValueStore for FaultyMemoryStore {
+    const MAX_VALUE_SIZE: usize = MemoryStore::MAX_VALUE_SIZE;
+    const SUPPORTS_TTL: bool = MemoryStore::SUPPORTS_TTL;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), FaultyStoreError> {
+        self.write_batch_with_ttl(batch, None).await
+    }
+
+    async fn write_batch_with_ttl(
+        &self,
+        batch: Batch,
+        ttl: Option<Duration>,
+    ) -> Result<(), FaultyStoreError> {
+        self.delay().await;
+        self.check_fault(StoreOperation::WriteBatch)?;
+        Ok(self.store.write_batch_with_ttl(batch, ttl).await?)
+    }
+
+    async fn clear_journal(&self) -> Result<(), FaultyStoreError> {
+        Ok(self.store.clear_journal().await?)
+    }
+}
+
+/// The error type for [`FaultyMemoryStore`].
+#[derive(Error, Debug)]
+pub enum FaultyStoreError {
+    /// A fault programmed with [`FaultyMemoryStore::fail_nth`].
+    #[error("Injected fault: {0}")]
+    InjectedFault(String),
+
+    /// An error from the wrapped memory store.
+    #[error(transparent)]
+    MemoryStoreError(#[from] MemoryStoreError),
+
+    /// Serialization error with BCS.
+    #[error(transparent)]
+    BcsError(#[from] bcs::Error),
+}
+
+impl KeyValueStoreError for FaultyStoreError {
+    const BACKEND: &'static str = "faulty_memory";
+}
+
+/// An implementation of [`crate::context::Context`] storing its values in a
+/// [`FaultyMemoryStore`].
+pub type FaultyMemoryContext<E> = ViewContext<E, FaultyMemoryStore>;
+
+impl<E> FaultyMemoryContext<E> {
+    /// Creates a root context using `store`, which may be shared with other contexts to program
+    /// its faults.
+    pub fn new_for_testing(store: FaultyMemoryStore, extra: E) -> Self {
+        ViewContext::new_unsafe(store, Vec::new(), extra)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::{
+        self as linera_views,
+        map_view::MapView,
+        register_view::RegisterView,
+        views::{RootView, View, ViewError},
+    };
+
+    #[derive(RootView)]
+    struct Account<C> {
+        balance: RegisterView<C, u64>,
+        history: MapView<C, u32, u64>,
+    }
+
+    impl Account<FaultyMemoryContext<()>> {
+        /// Records a deposit in the history and updates the balance.
+        async fn deposit(&mut self, amount: u64) -> Result<(), ViewError> {
+            let index = self.history.count().await? as u32;
+            self.history.insert(&index, amount)?;
+            *self.balance.get_mut() += amount;
+            Ok(())
+        }
+
+        /// Checks that the balance is the sum of the amounts in the history.
+        async fn is_consistent(&self) -> Result<bool, ViewError> {
+            let mut total = 0;
+            self.history
+                .for_each_index_value(|_, amount| {
+                    total += *amount;
+                    Ok(())
+                })
+                .await?;
+            Ok(total == *self.balance.get())
+        }
+    }
+
+    async fn load_account(store: &FaultyMemoryStore) -> Account<FaultyMemoryContext<()>> {
+        let context = FaultyMemoryContext::new_for_testing(store.clone(), ());
+        Account::load(context).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_is_detected_and_recovered() {
+        let store = FaultyMemoryStore::new_for_testing();
+        let mut account = load_account(&store).await;
+        account.deposit(10).await.unwrap();
+        account.save().await.unwrap();
+
+        store.fail_nth(StoreOperation::WriteBatch, 1, "disk full");
+        account.deposit(5).await.unwrap();
+        let error = account.save().await.unwrap_err();
+        assert!(error.to_string().contains("disk full"));
+
+        // The storage still holds the state of the last successful save, and the deposit can
+        // be retried on a fresh view.
+        let mut account = load_account(&store).await;
+        assert_eq!(*account.balance.get(), 10);
+        assert!(account.is_consistent().await.unwrap());
+        account.deposit(5).await.unwrap();
+        account.save().await.unwrap();
+
+        let account = load_account(&store).await;
+        assert_eq!(*account.balance.get(), 15);
+        assert!(account.is_consistent().await.unwrap());
+        assert_eq!(store.calls(StoreOperation::WriteBatch), 3);
+    }
+
+    #[tokio::test]
+    async fn test_nth_read_fails() {
+        let store = FaultyMemoryStore::new_for_testing();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"key".to_vec(), b"value".to_vec());
+        store.write_batch(batch).await.unwrap();
+
+        store.fail_nth(StoreOperation::ReadValueBytes, 2, "timeout");
+        assert_eq!(
+            store.read_value_bytes(b"key").await.unwrap(),
+            Some(b"value".to_vec())
+        );
+        assert_matches!(
+            store.read_value_bytes(b"key").await,
+            Err(FaultyStoreError::InjectedFault(error)) if error == "timeout"
+        );
+        assert!(store.read_value_bytes(b"key").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_latency() {
+        let store = FaultyMemoryStore::new_for_testing();
+        store.set_latency(Duration::from_millis(20));
+
+        let start = linera_base::time::Instant::now();
+        store.read_value_bytes(b"key").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
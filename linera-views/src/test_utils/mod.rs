@@ -3,6 +3,9 @@
 
 pub mod test_views;
 
+/// A memory store that can be programmed to fail.
+pub mod faulty_store;
+
 /// Functions for computing the performance of stores.
 #[cfg(not(target_arch = "wasm32"))]
 pub mod performance;
@@ -149,8 +152,12 @@ pub fn span_random_reordering_put_delete<R: Rng>(
 /// * `read_value_bytes`
 /// * `read_multi_values_bytes`
 /// * `find_keys_by_prefix` / `find_key_values_by_prefix`
+/// * `find_keys_by_prefix_after`
 /// * The ordering of keys returned by `find_keys_by_prefix` and `find_key_values_by_prefix`
-pub async fn run_reads<S: RestrictedKeyValueStore>(store: S, key_values: Vec<(Vec<u8>, Vec<u8>)>) {
+pub async fn run_reads<S: RestrictedKeyValueStore + Sync>(
+    store: S,
+    key_values: Vec<(Vec<u8>, Vec<u8>)>,
+) {
     // We need a nontrivial key_prefix because dynamo requires a non-trivial prefix
     let mut batch = Batch::new();
     let mut keys = Vec::new();
@@ -185,6 +192,27 @@ pub async fn run_reads<S: RestrictedKeyValueStore>(store: S, key_values: Vec<(Ve
         for i in 1..keys_request.len() {
             assert!(keys_request[i - 1] < keys_request[i]);
         }
+        // Check that reading the keys page by page gives the same keys
+        let limit = 1 + keys_request.len() / 3;
+        let mut keys_by_pages = Vec::new();
+        let mut start_after = None;
+        loop {
+            let page = store
+                .find_keys_by_prefix_after(key_prefix, start_after.as_deref(), limit)
+                .await
+                .unwrap();
+            assert!(page.len() <= limit);
+            let is_last_page = page.len() < limit;
+            start_after = page.last().cloned();
+            keys_by_pages.extend(page);
+            if is_last_page {
+                break;
+            }
+        }
+        assert!(keys_by_pages
+            .iter()
+            .map(Vec::as_slice)
+            .eq(keys_request.iter().copied()));
         // Check the obtained values
         let mut set_key_value2 = HashSet::new();
         for (key, value) in &key_values {
@@ -42,6 +42,18 @@ where
     F: Fn(bool) -> bool,
 {
     let store = S::new_test_store().await.unwrap();
+    contains_key_with_store(&store, iterations, f).await
+}
+
+/// Benchmarks the `contains_key` operation on the given store.
+pub async fn contains_key_with_store<S: KeyValueStore, F>(
+    store: &S,
+    iterations: u64,
+    f: F,
+) -> Duration
+where
+    F: Fn(bool) -> bool,
+{
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -60,7 +72,7 @@ where
         }
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
@@ -72,6 +84,18 @@ where
     F: Fn(Vec<bool>) -> Vec<bool>,
 {
     let store = S::new_test_store().await.unwrap();
+    contains_keys_with_store(&store, iterations, f).await
+}
+
+/// Benchmarks the `contains_keys` operation on the given store.
+pub async fn contains_keys_with_store<S: KeyValueStore, F>(
+    store: &S,
+    iterations: u64,
+    f: F,
+) -> Duration
+where
+    F: Fn(Vec<bool>) -> Vec<bool>,
+{
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -92,7 +116,7 @@ where
         f(store.contains_keys(keys).await.unwrap());
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
@@ -104,6 +128,18 @@ where
     F: Fn(S::Keys) -> S::Keys,
 {
     let store = S::new_test_store().await.unwrap();
+    find_keys_by_prefix_with_store(&store, iterations, f).await
+}
+
+/// Benchmarks the `find_keys_by_prefix` operation on the given store.
+pub async fn find_keys_by_prefix_with_store<S: KeyValueStore, F>(
+    store: &S,
+    iterations: u64,
+    f: F,
+) -> Duration
+where
+    F: Fn(S::Keys) -> S::Keys,
+{
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -120,7 +156,7 @@ where
         f(store.find_keys_by_prefix(PREFIX_SEARCH).await.unwrap());
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
@@ -132,6 +168,18 @@ where
     F: Fn(S::KeyValues) -> S::KeyValues,
 {
     let store = S::new_test_store().await.unwrap();
+    find_key_values_by_prefix_with_store(&store, iterations, f).await
+}
+
+/// Benchmarks the `find_keys_by_prefix` operation on the given store.
+pub async fn find_key_values_by_prefix_with_store<S: KeyValueStore, F>(
+    store: &S,
+    iterations: u64,
+    f: F,
+) -> Duration
+where
+    F: Fn(S::KeyValues) -> S::KeyValues,
+{
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -151,7 +199,7 @@ where
             .unwrap());
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
@@ -163,6 +211,18 @@ where
     F: Fn(Option<Vec<u8>>) -> Option<Vec<u8>>,
 {
     let store = S::new_test_store().await.unwrap();
+    read_value_bytes_with_store(&store, iterations, f).await
+}
+
+/// Benchmarks the `read_value_bytes` operation on the given store.
+pub async fn read_value_bytes_with_store<S: KeyValueStore, F>(
+    store: &S,
+    iterations: u64,
+    f: F,
+) -> Duration
+where
+    F: Fn(Option<Vec<u8>>) -> Option<Vec<u8>>,
+{
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -181,7 +241,7 @@ where
         }
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
@@ -193,6 +253,18 @@ where
     F: Fn(Vec<Option<Vec<u8>>>) -> Vec<Option<Vec<u8>>>,
 {
     let store = S::new_test_store().await.unwrap();
+    read_multi_values_bytes_with_store(&store, iterations, f).await
+}
+
+/// Benchmarks the `read_multi_values_bytes` operation on the given store.
+pub async fn read_multi_values_bytes_with_store<S: KeyValueStore, F>(
+    store: &S,
+    iterations: u64,
+    f: F,
+) -> Duration
+where
+    F: Fn(Vec<Option<Vec<u8>>>) -> Vec<Option<Vec<u8>>>,
+{
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -213,7 +285,7 @@ where
         f(store.read_multi_values_bytes(keys).await.unwrap());
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
@@ -222,6 +294,11 @@ where
 /// Benchmarks the `write_batch` operation.
 pub async fn write_batch<S: TestKeyValueStore>(iterations: u64) -> Duration {
     let store = S::new_test_store().await.unwrap();
+    write_batch_with_store(&store, iterations).await
+}
+
+/// Benchmarks the `write_batch` operation on the given store.
+pub async fn write_batch_with_store<S: KeyValueStore>(store: &S, iterations: u64) -> Duration {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
         let key_values = add_prefix(
@@ -237,7 +314,7 @@ pub async fn write_batch<S: TestKeyValueStore>(iterations: u64) -> Duration {
         store.write_batch(batch).await.unwrap();
         total_time += measurement.elapsed();
 
-        clear_store(&store).await;
+        clear_store(store).await;
     }
 
     total_time
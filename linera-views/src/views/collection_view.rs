@@ -23,7 +23,7 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{CountLocation, CustomSerialize, EntryCounter, HasherOutput, Update},
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
     store::{KeyIterable, ReadableKeyValueStore as _},
@@ -43,11 +43,15 @@ static COLLECTION_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
 
 /// A view that supports accessing a collection of views of the same kind, indexed by a
 /// `Vec<u8>`, one subview at a time.
+///
+/// The number of entries is stored along with them, so that [`ByteCollectionView::count`]
+/// does not need to iterate over the indices.
 #[derive(Debug)]
 pub struct ByteCollectionView<C, W> {
     context: C,
     delete_storage_first: bool,
     updates: RwLock<BTreeMap<Vec<u8>, Update<W>>>,
+    counter: EntryCounter,
 }
 
 /// A read-only accessor for a particular subview in a [`CollectionView`].
@@ -80,6 +84,8 @@ enum KeyTag {
     Index = MIN_VIEW_TAG,
     /// Prefix for specifying as the prefix for the sub-view.
     Subview,
+    /// Prefix for the number of entries.
+    Count,
 }
 
 impl<C, W> View<C> for ByteCollectionView<C, W>
@@ -88,26 +94,33 @@ where
     ViewError: From<C::Error>,
     W: View<C> + Send + Sync,
 {
-    const NUM_INIT_KEYS: usize = 0;
+    const NUM_INIT_KEYS: usize = 1;
 
     fn context(&self) -> &C {
         &self.context
     }
 
-    fn pre_load(_context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
-        Ok(vec![])
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        Ok(vec![context.base_key().base_tag(KeyTag::Count as u8)])
     }
 
-    fn post_load(context: C, _values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let counter = EntryCounter::post_load(
+            values.first().ok_or(ViewError::PostLoadValuesError)?,
+            CountLocation::Tagged,
+        )?;
         Ok(Self {
             context,
             delete_storage_first: false,
             updates: RwLock::new(BTreeMap::new()),
+            counter,
         })
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -123,11 +136,29 @@ where
         !updates.is_empty()
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        let context = self.index_context();
+        let updates = self.updates.get_mut();
+        self.counter
+            .pre_save(&context, self.delete_storage_first, updates)
+            .await?;
+        for update in updates.values_mut() {
+            if let Update::Set(view) = update {
+                view.pre_save().await?;
+            }
+        }
+        Ok(())
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         let mut delete_view = false;
+        let count_key = self.context.base_key().base_tag(KeyTag::Count as u8);
         if self.delete_storage_first {
             delete_view = true;
             batch.delete_key_prefix(self.context.base_key().bytes.clone());
+            // The count is written after the deletion of the prefix that contains it.
+            let count = self.counter.known_count(true, self.updates.get_mut());
+            self.counter.flush(batch, count_key, true, count)?;
             for (index, update) in mem::take(self.updates.get_mut()) {
                 if let Update::Set(mut view) = update {
                     view.flush(batch)?;
@@ -136,6 +167,8 @@ where
                 }
             }
         } else {
+            let count = self.counter.known_count(false, self.updates.get_mut());
+            self.counter.flush(batch, count_key, false, count)?;
             for (index, update) in mem::take(self.updates.get_mut()) {
                 match update {
                     Update::Set(mut view) => {
@@ -185,6 +218,7 @@ where
             context: self.context.clone(),
             delete_storage_first: self.delete_storage_first,
             updates: RwLock::new(cloned_updates),
+            counter: self.counter.clone(),
         })
     }
 }
@@ -212,6 +246,12 @@ where
         batch.put_key_value_bytes(key, vec![]);
     }
 
+    /// The context whose keys are the indices of the entries, as used for counting them.
+    fn index_context(&self) -> C {
+        let key = self.context.base_key().base_tag(KeyTag::Index as u8);
+        self.context.clone_with_base_key(key)
+    }
+
     /// Loads a subview for the data at the given index in the collection. If an entry
     /// is absent then a default entry is added to the collection. The resulting view
     /// can be modified.
@@ -255,6 +295,83 @@ where
         Ok(self.do_load_entry_mut(short_key).await?)
     }
 
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection and `init` is applied
+    /// to it. The index and, if present, the subview are read from storage at once.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::collection_view::ByteCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: ByteCollectionView<_, RegisterView<_, String>> =
+    ///     ByteCollectionView::load(context).await.unwrap();
+    /// let init = |subview: &mut RegisterView<_, String>| {
+    ///     subview.set(String::from("Hello"));
+    ///     Ok(())
+    /// };
+    /// let subview = view.try_load_entry_or_insert(&[0, 1], init).await.unwrap();
+    /// subview.set(String::from("Bonjour"));
+    /// let subview = view.try_load_entry_or_insert(&[0, 1], init).await.unwrap();
+    /// assert_eq!(subview.get(), "Bonjour");
+    /// # })
+    /// ```
+    pub async fn try_load_entry_or_insert<F>(
+        &mut self,
+        short_key: &[u8],
+        init: F,
+    ) -> Result<&mut W, ViewError>
+    where
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        let key = self
+            .context
+            .base_key()
+            .base_tag_index(KeyTag::Subview as u8, short_key);
+        let context = self.context.clone_with_base_key(key);
+        match self.updates.get_mut().entry(short_key.to_vec()) {
+            btree_map::Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                if let Update::Removed = entry {
+                    let mut view = W::new(context)?;
+                    init(&mut view)?;
+                    *entry = Update::Set(view);
+                }
+                let Update::Set(view) = entry else {
+                    unreachable!();
+                };
+                Ok(view)
+            }
+            btree_map::Entry::Vacant(entry) => {
+                let mut stored_view = None;
+                if !self.delete_storage_first {
+                    let mut keys = vec![self
+                        .context
+                        .base_key()
+                        .base_tag_index(KeyTag::Index as u8, short_key)];
+                    keys.extend(W::pre_load(&context)?);
+                    let values = self.context.store().read_multi_values_bytes(keys).await?;
+                    if values[0].is_some() {
+                        stored_view = Some(W::post_load(context.clone(), &values[1..])?);
+                    }
+                }
+                let view = match stored_view {
+                    Some(view) => view,
+                    None => {
+                        let mut view = W::new(context)?;
+                        init(&mut view)?;
+                        view
+                    }
+                };
+                let Update::Set(view) = entry.insert(Update::Set(view)) else {
+                    unreachable!();
+                };
+                Ok(view)
+            }
+        }
+    }
+
     /// Loads a subview for the data at the given index in the collection. If an entry
     /// is absent then `None` is returned. The resulting view cannot be modified.
     /// May fail if one subview is already being visited.
@@ -608,7 +725,8 @@ where
         Ok(keys)
     }
 
-    /// Returns the number of entries in the collection.
+    /// Returns the number of entries in the collection. This uses the stored number of
+    /// entries and only looks up the indices modified since the last flush.
     /// ```rust
     /// # tokio_test::block_on(async {
     /// # use linera_views::context::MemoryContext;
@@ -624,13 +742,10 @@ where
     /// # })
     /// ```
     pub async fn count(&self) -> Result<usize, ViewError> {
-        let mut count = 0;
-        self.for_each_key(|_key| {
-            count += 1;
-            Ok(())
-        })
-        .await?;
-        Ok(count)
+        let updates = self.updates.read().await;
+        self.counter
+            .count(&self.index_context(), self.delete_storage_first, &updates)
+            .await
     }
 }
 
@@ -741,7 +856,9 @@ where
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -752,6 +869,10 @@ where
         self.collection.has_pending_changes().await
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.collection.pre_save().await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         self.collection.flush(batch)
     }
@@ -836,6 +957,42 @@ where
         self.collection.load_entry_or_insert(&short_key).await
     }
 
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection and `init` is applied
+    /// to it. The resulting view can be modified.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::collection_view::CollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: CollectionView<_, u64, RegisterView<_, String>> =
+    ///     CollectionView::load(context).await.unwrap();
+    /// let init = |subview: &mut RegisterView<_, String>| {
+    ///     subview.set(String::from("Hello"));
+    ///     Ok(())
+    /// };
+    /// let subview = view.try_load_entry_or_insert(&23, init).await.unwrap();
+    /// assert_eq!(subview.get(), "Hello");
+    /// # })
+    /// ```
+    pub async fn try_load_entry_or_insert<Q, F>(
+        &mut self,
+        index: &Q,
+        init: F,
+    ) -> Result<&mut W, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.collection
+            .try_load_entry_or_insert(&short_key, init)
+            .await
+    }
+
     /// Loads a subview for the data at the given index in the collection. If an entry
     /// is absent then `None` is returned. The resulting view cannot be modified.
     /// May fail if one subview is already being visited.
@@ -871,6 +1028,32 @@ where
         self.collection.try_load_entry(&short_key).await
     }
 
+    /// Returns `true` if the collection contains an entry at the given index. This
+    /// reads at most one key from storage.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::collection_view::CollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: CollectionView<_, u64, RegisterView<_, String>> =
+    ///     CollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&23).await.unwrap();
+    /// assert!(view.contains_key(&23).await.unwrap());
+    /// view.remove_entry(&23).unwrap();
+    /// assert!(!view.contains_key(&23).await.unwrap());
+    /// # })
+    /// ```
+    pub async fn contains_key<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.collection.contains_key(&short_key).await
+    }
+
     /// Resets an entry to the default value.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1119,7 +1302,9 @@ where
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -1130,6 +1315,10 @@ where
         self.collection.has_pending_changes().await
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.collection.pre_save().await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         self.collection.flush(batch)
     }
@@ -1214,6 +1403,42 @@ where
         self.collection.load_entry_or_insert(&short_key).await
     }
 
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection and `init` is applied
+    /// to it. The resulting view can be modified.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::collection_view::CustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: CustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     CustomCollectionView::load(context).await.unwrap();
+    /// let init = |subview: &mut RegisterView<_, String>| {
+    ///     subview.set(String::from("Hello"));
+    ///     Ok(())
+    /// };
+    /// let subview = view.try_load_entry_or_insert(&23, init).await.unwrap();
+    /// assert_eq!(subview.get(), "Hello");
+    /// # })
+    /// ```
+    pub async fn try_load_entry_or_insert<Q, F>(
+        &mut self,
+        index: &Q,
+        init: F,
+    ) -> Result<&mut W, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        let short_key = index.to_custom_bytes()?;
+        self.collection
+            .try_load_entry_or_insert(&short_key, init)
+            .await
+    }
+
     /// Loads a subview for the data at the given index in the collection. If an entry
     /// is absent then `None` is returned. The resulting view cannot be modified.
     /// May fail if one subview is already being visited.
@@ -1249,6 +1474,32 @@ where
         self.collection.try_load_entry(&short_key).await
     }
 
+    /// Returns `true` if the collection contains an entry at the given index. This
+    /// reads at most one key from storage.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::collection_view::CustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: CustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     CustomCollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&23).await.unwrap();
+    /// assert!(view.contains_key(&23).await.unwrap());
+    /// view.remove_entry(&23).unwrap();
+    /// assert!(!view.contains_key(&23).await.unwrap());
+    /// # })
+    /// ```
+    pub async fn contains_key<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+    {
+        let short_key = index.to_custom_bytes()?;
+        self.collection.contains_key(&short_key).await
+    }
+
     /// Marks the entry so that it is removed in the next flush.
     /// ```rust
     /// # tokio_test::block_on(async {
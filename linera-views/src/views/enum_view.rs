@@ -0,0 +1,478 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `EnumView` holds an enum whose variants contain views.
+//!
+//! The enum is written as a regular Rust enum with named fields, and `#[derive(View)]`
+//! implements [`ViewVariants`] for it, along with a fieldless `<Name>Discriminant` enum
+//! identifying its variants. The index of the active variant is persisted at a
+//! dedicated key, and the fields of each variant are stored under their own prefix.
+//! Only the index is read when the view is loaded as part of another view: the views of
+//! the active variant are loaded when first accessed. Switching to another variant
+//! deletes the views of the previous one when flushing.
+//!
+//! Deriving `HashableView` or `ClonableView` on the enum instead of `View` also makes
+//! the `EnumView` hashable or clonable. With GraphQL, the `EnumView` is exposed as its
+//! current variant.
+//!
+//! ```rust
+//! # tokio_test::block_on(async {
+//! # use linera_views::context::MemoryContext;
+//! # use linera_views::enum_view::EnumView;
+//! # use linera_views::map_view::MapView;
+//! # use linera_views::register_view::RegisterView;
+//! # use linera_views::views::View;
+//! # let context = MemoryContext::new_for_testing(());
+//! #[derive(View)]
+//! enum GameState<C> {
+//!     Uninitialized,
+//!     Active { players: MapView<C, String, u32> },
+//!     Finished { winner: RegisterView<C, String> },
+//! }
+//!
+//! let mut state = EnumView::<_, GameState<_>>::load(context).await.unwrap();
+//! assert_eq!(state.discriminant(), GameStateDiscriminant::Uninitialized);
+//! let GameState::Active { players } = state.switch_to(GameStateDiscriminant::Active).unwrap()
+//! else {
+//!     unreachable!()
+//! };
+//! players.insert("Alice", 3).unwrap();
+//! let GameState::Active { players } = state.get().await.unwrap() else {
+//!     unreachable!()
+//! };
+//! assert_eq!(players.get("Alice").await.unwrap(), Some(3));
+//! # })
+//! ```
+
+use std::fmt::Debug;
+
+use async_lock::OnceCell;
+
+use crate::{
+    batch::Batch,
+    common::{from_bytes_option_or_default, HasherOutput},
+    context::Context,
+    store::ReadableKeyValueStore as _,
+    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+};
+
+/// Key tags to create the sub-keys of an `EnumView` on top of the base key.
+#[repr(u8)]
+enum KeyTag {
+    /// Prefix for the index of the active variant.
+    Variant = MIN_VIEW_TAG,
+    /// Prefix for the fields of the variants.
+    Fields,
+}
+
+/// An enum whose variants contain views, as implemented by `#[derive(View)]` on enums.
+///
+/// The methods apply to the views of the variant of `self`, or of the given discriminant.
+#[cfg_attr(not(web), trait_variant::make(Send))]
+pub trait ViewVariants<C>: Sized {
+    /// The type identifying the variants without their fields.
+    type Discriminant: Copy + Debug + Eq + Send + Sync + 'static;
+
+    /// The discriminants of all variants, in declaration order. The first one is the
+    /// variant of an empty view.
+    const DISCRIMINANTS: &'static [Self::Discriminant];
+
+    /// The number of keys used for the initialization of each variant, in the order of
+    /// [`ViewVariants::DISCRIMINANTS`].
+    const NUM_INIT_KEYS: &'static [usize];
+
+    /// Returns the discriminant of the variant.
+    fn discriminant(&self) -> Self::Discriminant;
+
+    /// Creates the keys needed for loading the views of a variant.
+    fn pre_load(context: &C, discriminant: Self::Discriminant) -> Result<Vec<Vec<u8>>, ViewError>;
+
+    /// Loads the views of a variant from the values.
+    fn post_load(
+        context: C,
+        discriminant: Self::Discriminant,
+        values: &[Option<Vec<u8>>],
+    ) -> Result<Self, ViewError>;
+
+    /// Discards all pending changes of the views.
+    fn rollback(&mut self);
+
+    /// Returns [`true`] if flushing the views would result in changes to the persistent
+    /// storage.
+    async fn has_pending_changes(&self) -> bool;
+
+    /// Reads from storage what is needed to flush the views.
+    async fn pre_save(&mut self) -> Result<(), ViewError>;
+
+    /// Clears the views.
+    fn clear(&mut self);
+
+    /// Persists the changes of the views. The returned boolean indicates whether all the
+    /// views were removed.
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError>;
+
+    /// Builds a variant whose views are already deleted.
+    fn new(context: C, discriminant: Self::Discriminant) -> Result<Self, ViewError> {
+        let values = vec![None; Self::NUM_INIT_KEYS[variant_index::<C, Self>(discriminant)]];
+        let mut variant = Self::post_load(context, discriminant, &values)?;
+        variant.clear();
+        Ok(variant)
+    }
+}
+
+/// A [`ViewVariants`] enum whose views can be hashed, as implemented by
+/// `#[derive(HashableView)]` on enums.
+#[cfg_attr(not(web), trait_variant::make(Send))]
+pub trait HashableViewVariants<C>: ViewVariants<C> {
+    /// Computes the hash of the views of the variant.
+    async fn hash_mut(&mut self) -> Result<HasherOutput, ViewError>;
+
+    /// Computes the hash of the views of the variant.
+    async fn hash(&self) -> Result<HasherOutput, ViewError>;
+}
+
+/// A [`ViewVariants`] enum whose views can be cloned, as implemented by
+/// `#[derive(ClonableView)]` on enums.
+pub trait ClonableViewVariants<C>: ViewVariants<C> {
+    /// Creates a clone of the views of the variant, sharing the underlying storage
+    /// context, as in [`ClonableView::clone_unchecked`].
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError>;
+}
+
+/// Returns the position of `discriminant` in [`ViewVariants::DISCRIMINANTS`].
+fn variant_index<C, E: ViewVariants<C>>(discriminant: E::Discriminant) -> usize {
+    E::DISCRIMINANTS
+        .iter()
+        .position(|other| *other == discriminant)
+        .expect("all discriminants are listed")
+}
+
+/// A view holding one of the variants of an enum `E`, each of which contains views.
+#[derive(Debug)]
+pub struct EnumView<C, E> {
+    context: C,
+    /// The index of the variant in storage.
+    stored_index: usize,
+    /// The variant in storage, with its staged changes, once its views are loaded.
+    stored: OnceCell<E>,
+    /// The variant replacing the stored one since the last flush, if any. After a
+    /// [`View::clear`], it is the first variant, which is only created when accessed.
+    update: Option<OnceCell<E>>,
+}
+
+impl<C, E> EnumView<C, E>
+where
+    C: Context,
+{
+    fn fields_context(context: &C) -> C {
+        let base_key = context.base_key().base_tag(KeyTag::Fields as u8);
+        context.clone_with_base_key(base_key)
+    }
+}
+
+impl<C, E> EnumView<C, E>
+where
+    C: Context,
+    E: ViewVariants<C>,
+{
+    /// Reads the index of the stored variant. A missing value means the first variant.
+    fn stored_variant_index(value: &Option<Vec<u8>>) -> Result<usize, ViewError> {
+        let index = from_bytes_option_or_default::<u32, ViewError>(value)? as usize;
+        if index >= E::DISCRIMINANTS.len() {
+            return Err(ViewError::PostLoadValuesError);
+        }
+        Ok(index)
+    }
+
+    /// Returns the stored variant, loading its views if needed.
+    async fn stored(&self) -> Result<&E, ViewError>
+    where
+        ViewError: From<C::Error>,
+    {
+        self.stored
+            .get_or_try_init(|| async {
+                let fields_context = Self::fields_context(&self.context);
+                let discriminant = E::DISCRIMINANTS[self.stored_index];
+                let keys = E::pre_load(&fields_context, discriminant)?;
+                let values = self.context.store().read_multi_values_bytes(keys).await?;
+                E::post_load(fields_context, discriminant, &values)
+            })
+            .await
+    }
+
+    /// Returns the replacing variant, if any, creating it if needed.
+    async fn update(&self) -> Result<Option<&E>, ViewError> {
+        let Some(update) = &self.update else {
+            return Ok(None);
+        };
+        let update = update
+            .get_or_try_init(|| async {
+                E::new(Self::fields_context(&self.context), E::DISCRIMINANTS[0])
+            })
+            .await?;
+        Ok(Some(update))
+    }
+}
+
+impl<C, E> View<C> for EnumView<C, E>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    E: ViewVariants<C> + Send + Sync,
+{
+    // The views of the variant are only loaded when accessed, since the variant is only
+    // known once the first key is read.
+    const NUM_INIT_KEYS: usize = 1;
+
+    fn context(&self) -> &C {
+        &self.context
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        Ok(vec![context.base_key().base_tag(KeyTag::Variant as u8)])
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let stored_index =
+            Self::stored_variant_index(values.first().ok_or(ViewError::PostLoadValuesError)?)?;
+        Ok(Self {
+            context,
+            stored_index,
+            stored: OnceCell::new(),
+            update: None,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        let key = context.base_key().base_tag(KeyTag::Variant as u8);
+        let value = context.store().read_value_bytes(&key).await?;
+        let view = Self::post_load(context, &[value])?;
+        view.stored().await?;
+        Ok(view)
+    }
+
+    fn rollback(&mut self) {
+        self.update = None;
+        if let Some(stored) = self.stored.get_mut() {
+            stored.rollback();
+        }
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        if self.update.is_some() {
+            return true;
+        }
+        match self.stored.get() {
+            Some(stored) => stored.has_pending_changes().await,
+            None => false,
+        }
+    }
+
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        // The views of a replaced variant are cleared, so they need nothing from storage.
+        match (&mut self.update, self.stored.get_mut()) {
+            (Some(update), _) => match update.get_mut() {
+                Some(update) => update.pre_save().await,
+                // The first variant was not created since the view was cleared.
+                None => Ok(()),
+            },
+            (None, Some(stored)) => stored.pre_save().await,
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        if let Some(mut update) = self.update.take() {
+            let fields_context = Self::fields_context(&self.context);
+            let update = match update.take() {
+                Some(update) => update,
+                None => E::new(fields_context.clone(), E::DISCRIMINANTS[0])?,
+            };
+            // The views of the previous variant are deleted, without loading them.
+            let mut stored = match self.stored.take() {
+                Some(stored) => stored,
+                None => E::new(fields_context, E::DISCRIMINANTS[self.stored_index])?,
+            };
+            stored.clear();
+            stored.flush(batch)?;
+            self.stored_index = variant_index::<C, E>(update.discriminant());
+            self.stored = OnceCell::from(update);
+            let key = self.context.base_key().base_tag(KeyTag::Variant as u8);
+            match self.stored_index {
+                0 => batch.delete_key(key),
+                index => batch.put_key_value(key, &(index as u32))?,
+            }
+        }
+        // Views that were not loaded have no changes, but may not be empty.
+        let Some(stored) = self.stored.get_mut() else {
+            return Ok(false);
+        };
+        let deleted = stored.flush(batch)?;
+        Ok(deleted && self.stored_index == 0)
+    }
+
+    fn clear(&mut self) {
+        // Creating the views of the first variant may fail, so that is left to the next
+        // access or flush, which can return the error.
+        self.update = Some(OnceCell::new());
+    }
+}
+
+impl<C, E> ClonableView<C> for EnumView<C, E>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    E: ClonableViewVariants<C> + Send + Sync,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        let stored = match self.stored.get_mut() {
+            Some(stored) => OnceCell::from(stored.clone_unchecked()?),
+            None => OnceCell::new(),
+        };
+        let update = match &mut self.update {
+            Some(update) => Some(match update.get_mut() {
+                Some(update) => OnceCell::from(update.clone_unchecked()?),
+                None => OnceCell::new(),
+            }),
+            None => None,
+        };
+        Ok(EnumView {
+            context: self.context.clone(),
+            stored_index: self.stored_index,
+            stored,
+            update,
+        })
+    }
+}
+
+impl<C, E> HashableView<C> for EnumView<C, E>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    E: HashableViewVariants<C> + Send + Sync,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let index = variant_index::<C, E>(self.discriminant());
+        let variant_hash = self.get_mut().await?.hash_mut().await?;
+        Self::compute_hash(index, variant_hash)
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let index = variant_index::<C, E>(self.discriminant());
+        let variant_hash = self.get().await?.hash().await?;
+        Self::compute_hash(index, variant_hash)
+    }
+}
+
+impl<C, E> EnumView<C, E> {
+    /// Combines the index of the current variant with the hash of its views.
+    fn compute_hash(
+        index: usize,
+        variant_hash: HasherOutput,
+    ) -> Result<<sha3::Sha3_256 as Hasher>::Output, ViewError> {
+        let mut hasher = sha3::Sha3_256::default();
+        hasher.update_with_bcs_bytes(&(index as u32))?;
+        hasher.update_with_bytes(&variant_hash)?;
+        Ok(hasher.finalize())
+    }
+}
+
+impl<C, E> EnumView<C, E>
+where
+    C: Context,
+    E: ViewVariants<C>,
+{
+    /// Accesses the current variant, loading its views if needed.
+    pub async fn get(&self) -> Result<&E, ViewError>
+    where
+        ViewError: From<C::Error>,
+    {
+        match self.update().await? {
+            Some(update) => Ok(update),
+            None => self.stored().await,
+        }
+    }
+
+    /// Obtains a mutable reference to the current variant, loading its views if needed.
+    pub async fn get_mut(&mut self) -> Result<&mut E, ViewError>
+    where
+        ViewError: From<C::Error>,
+    {
+        if self.update().await?.is_none() {
+            self.stored().await?;
+        }
+        match &mut self.update {
+            Some(update) => Ok(update
+                .get_mut()
+                .expect("the replacing variant was created above")),
+            None => Ok(self
+                .stored
+                .get_mut()
+                .expect("the stored variant was loaded above")),
+        }
+    }
+
+    /// Returns the discriminant of the current variant.
+    pub fn discriminant(&self) -> E::Discriminant {
+        match &self.update {
+            Some(update) => update
+                .get()
+                .map_or(E::DISCRIMINANTS[0], ViewVariants::discriminant),
+            None => E::DISCRIMINANTS[self.stored_index],
+        }
+    }
+
+    /// Replaces the current variant by the variant `discriminant` with empty views, and
+    /// returns it. The views of the previous variant are deleted by the next flush.
+    pub fn switch_to(&mut self, discriminant: E::Discriminant) -> Result<&mut E, ViewError> {
+        let fields_context = Self::fields_context(&self.context);
+        let update = self
+            .update
+            .insert(OnceCell::from(E::new(fields_context, discriminant)?));
+        Ok(update.get_mut().expect("the variant was just created"))
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.context.extra()
+    }
+}
+
+#[cfg(with_graphql)]
+mod graphql {
+    use std::borrow::Cow;
+
+    use super::{EnumView, ViewVariants};
+    use crate::{context::Context, views::ViewError};
+
+    /// An `EnumView` is exposed as its current variant, which can implement
+    /// [`async_graphql::OutputType`] e.g. with `#[async_graphql::Object]`.
+    impl<C, E> async_graphql::OutputType for EnumView<C, E>
+    where
+        C: Context + Send + Sync,
+        ViewError: From<C::Error>,
+        E: ViewVariants<C> + async_graphql::OutputType + Send + Sync,
+    {
+        fn type_name() -> Cow<'static, str> {
+            E::type_name()
+        }
+
+        fn create_type_info(registry: &mut async_graphql::registry::Registry) -> String {
+            E::create_type_info(registry)
+        }
+
+        async fn resolve(
+            &self,
+            ctx: &async_graphql::ContextSelectionSet<'_>,
+            field: &async_graphql::Positioned<async_graphql::parser::types::Field>,
+        ) -> async_graphql::ServerResult<async_graphql::Value> {
+            let variant = self
+                .get()
+                .await
+                .map_err(|e| async_graphql::Error::from(e).into_server_error(ctx.item.pos))?;
+            variant.resolve(ctx, field).await
+        }
+    }
+}
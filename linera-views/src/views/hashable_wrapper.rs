@@ -92,6 +92,10 @@ where
         self.stored_hash != *hash
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.inner.pre_save().await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         let delete_view = self.inner.flush(batch)?;
         let hash = self.hash.get_mut().unwrap();
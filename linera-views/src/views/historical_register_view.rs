@@ -0,0 +1,374 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `HistoricalRegisterView` implements a register that remembers its previous values.
+//!
+//! Every time the register is written, the new value is recorded together with the block
+//! height at which it was written. Only the last `N` recorded values are kept: older ones
+//! are pruned when flushing. The block height is obtained from the extra data of the
+//! context, which has to implement [`BlockHeightProvider`].
+
+use std::collections::VecDeque;
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+
+use linera_base::data_types::BlockHeight;
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(with_metrics)]
+use {
+    linera_base::prometheus_util::{
+        exponential_bucket_latencies, register_histogram_vec, MeasureLatency,
+    },
+    prometheus::HistogramVec,
+};
+
+use crate::{
+    batch::Batch,
+    common::{from_bytes_option_or_default, HasherOutput},
+    context::Context,
+    hashable_wrapper::WrappedHashableContainerView,
+    store::ReadableKeyValueStore as _,
+    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+};
+
+#[cfg(with_metrics)]
+/// The runtime of hash computation
+static HISTORICAL_REGISTER_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec(
+        "historical_register_view_hash_runtime",
+        "HistoricalRegisterView hash runtime",
+        &[],
+        exponential_bucket_latencies(5.0),
+    )
+});
+
+/// The default number of values kept by a [`HistoricalRegisterView`].
+pub const DEFAULT_HISTORY_LIMIT: usize = 16;
+
+/// The extra data of a context providing the height of the block being executed.
+pub trait BlockHeightProvider {
+    /// Returns the height at which the changes to the views are written.
+    fn block_height(&self) -> BlockHeight;
+}
+
+impl BlockHeightProvider for BlockHeight {
+    fn block_height(&self) -> BlockHeight {
+        *self
+    }
+}
+
+/// Key tags to create the sub-keys of a `HistoricalRegisterView` on top of the base key.
+#[repr(u8)]
+enum KeyTag {
+    /// Prefix for the current value.
+    Value = MIN_VIEW_TAG,
+    /// Prefix for the recorded values.
+    History,
+}
+
+/// A view that supports modifying a single value of type `T` while keeping the last `N`
+/// values written, along with the block height at which each of them was written.
+#[derive(Debug)]
+pub struct HistoricalRegisterView<C, T, const N: usize = DEFAULT_HISTORY_LIMIT> {
+    delete_storage_first: bool,
+    context: C,
+    stored_value: Box<T>,
+    stored_history: VecDeque<(BlockHeight, T)>,
+    /// The value written since the last flush. After a `clear`, this is the default value
+    /// and is not recorded unless `set` is called.
+    update: Option<Box<T>>,
+    /// Whether `update` was written with `set`.
+    is_recorded: bool,
+}
+
+impl<C, T, const N: usize> View<C> for HistoricalRegisterView<C, T, N>
+where
+    C: Context + Send + Sync,
+    C::Extra: BlockHeightProvider,
+    ViewError: From<C::Error>,
+    T: Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+{
+    const NUM_INIT_KEYS: usize = 2;
+
+    fn context(&self) -> &C {
+        &self.context
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        let key_value = context.base_key().base_tag(KeyTag::Value as u8);
+        let key_history = context.base_key().base_tag(KeyTag::History as u8);
+        Ok(vec![key_value, key_history])
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let value =
+            from_bytes_option_or_default(values.first().ok_or(ViewError::PostLoadValuesError)?)?;
+        let history =
+            from_bytes_option_or_default(values.get(1).ok_or(ViewError::PostLoadValuesError)?)?;
+        Ok(Self {
+            delete_storage_first: false,
+            context,
+            stored_value: Box::new(value),
+            stored_history: history,
+            update: None,
+            is_recorded: false,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
+    }
+
+    fn rollback(&mut self) {
+        self.delete_storage_first = false;
+        self.update = None;
+        self.is_recorded = false;
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        if self.delete_storage_first {
+            return true;
+        }
+        self.update.is_some()
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let mut delete_view = false;
+        if self.delete_storage_first {
+            batch.delete_key_prefix(self.context.base_key().bytes.clone());
+            self.stored_value = Box::default();
+            self.stored_history.clear();
+            delete_view = true;
+        }
+        if let Some(value) = self.update.take() {
+            if self.is_recorded {
+                let height = self.context.extra().block_height();
+                record(&mut self.stored_history, N, height, &value);
+                let key = self.context.base_key().base_tag(KeyTag::Value as u8);
+                batch.put_key_value(key, &value)?;
+                let key = self.context.base_key().base_tag(KeyTag::History as u8);
+                if self.stored_history.is_empty() {
+                    batch.delete_key(key);
+                } else {
+                    batch.put_key_value(key, &self.stored_history)?;
+                }
+                self.stored_value = value;
+                delete_view = false;
+            }
+        }
+        self.delete_storage_first = false;
+        self.is_recorded = false;
+        Ok(delete_view)
+    }
+
+    fn clear(&mut self) {
+        self.delete_storage_first = true;
+        self.update = Some(Box::default());
+        self.is_recorded = false;
+    }
+}
+
+/// Appends `value` to the `history`, replacing the entry written at the same block
+/// height if any, and keeps only the last `limit` entries.
+fn record<T: Clone>(
+    history: &mut VecDeque<(BlockHeight, T)>,
+    limit: usize,
+    height: BlockHeight,
+    value: &T,
+) {
+    if matches!(history.back(), Some((last_height, _)) if *last_height == height) {
+        history.pop_back();
+    }
+    history.push_back((height, value.clone()));
+    while history.len() > limit {
+        history.pop_front();
+    }
+}
+
+impl<C, T, const N: usize> ClonableView<C> for HistoricalRegisterView<C, T, N>
+where
+    C: Context + Send + Sync,
+    C::Extra: BlockHeightProvider,
+    ViewError: From<C::Error>,
+    T: Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(HistoricalRegisterView {
+            delete_storage_first: self.delete_storage_first,
+            context: self.context.clone(),
+            stored_value: self.stored_value.clone(),
+            stored_history: self.stored_history.clone(),
+            update: self.update.clone(),
+            is_recorded: self.is_recorded,
+        })
+    }
+}
+
+impl<C, T, const N: usize> HistoricalRegisterView<C, T, N>
+where
+    C: Context,
+{
+    /// Accesses the current value in the register.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::BlockHeight;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::historical_register_view::HistoricalRegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(BlockHeight(0));
+    /// let mut register = HistoricalRegisterView::<_, u32>::load(context)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(*register.get(), 0);
+    /// # })
+    /// ```
+    pub fn get(&self) -> &T {
+        match &self.update {
+            None => &self.stored_value,
+            Some(value) => value,
+        }
+    }
+
+    /// Sets the value in the register. The value is recorded in the history at the current
+    /// block height when flushing. Setting the value several times in the same block only
+    /// records the last value.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::BlockHeight;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::historical_register_view::HistoricalRegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(BlockHeight(0));
+    /// let mut register = HistoricalRegisterView::<_, u32>::load(context)
+    ///     .await
+    ///     .unwrap();
+    /// register.set(5);
+    /// assert_eq!(*register.get(), 5);
+    /// # })
+    /// ```
+    pub fn set(&mut self, value: T) {
+        self.update = Some(Box::new(value));
+        self.is_recorded = true;
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.context.extra()
+    }
+}
+
+impl<C, T, const N: usize> HistoricalRegisterView<C, T, N>
+where
+    C: Context,
+    C::Extra: BlockHeightProvider,
+    T: Clone + Serialize,
+{
+    /// Returns the last `count` values written, with the block height at which each was
+    /// written, from the oldest to the most recent one. This includes the pending value,
+    /// and at most `N` values are available.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::BlockHeight;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::historical_register_view::HistoricalRegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(BlockHeight(3));
+    /// let mut register = HistoricalRegisterView::<_, u32>::load(context)
+    ///     .await
+    ///     .unwrap();
+    /// register.set(5);
+    /// assert_eq!(register.history(2), vec![(BlockHeight(3), 5)]);
+    /// # })
+    /// ```
+    pub fn history(&self, count: usize) -> Vec<(BlockHeight, T)> {
+        let mut history = if self.delete_storage_first {
+            VecDeque::new()
+        } else {
+            self.stored_history.clone()
+        };
+        if let Some(value) = self.update.as_ref().filter(|_| self.is_recorded) {
+            let height = self.context.extra().block_height();
+            record(&mut history, N, height, value);
+        }
+        let start = history.len().saturating_sub(count);
+        history.into_iter().skip(start).collect()
+    }
+
+    fn compute_hash(&self) -> Result<<sha3::Sha3_256 as Hasher>::Output, ViewError> {
+        #[cfg(with_metrics)]
+        let _hash_latency = HISTORICAL_REGISTER_VIEW_HASH_RUNTIME.measure_latency();
+        let mut hasher = sha3::Sha3_256::default();
+        hasher.update_with_bcs_bytes(self.get())?;
+        hasher.update_with_bcs_bytes(&self.history(N))?;
+        Ok(hasher.finalize())
+    }
+}
+
+impl<C, T, const N: usize> HashableView<C> for HistoricalRegisterView<C, T, N>
+where
+    C: Context + Send + Sync,
+    C::Extra: BlockHeightProvider,
+    ViewError: From<C::Error>,
+    T: Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.compute_hash()
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.compute_hash()
+    }
+}
+
+/// Type wrapping `HistoricalRegisterView` while memoizing the hash.
+pub type HashedHistoricalRegisterView<C, T, const N: usize = DEFAULT_HISTORY_LIMIT> =
+    WrappedHashableContainerView<C, HistoricalRegisterView<C, T, N>, HasherOutput>;
+
+#[cfg(with_graphql)]
+mod graphql {
+    use std::borrow::Cow;
+
+    use linera_base::data_types::BlockHeight;
+
+    use super::{BlockHeightProvider, HistoricalRegisterView};
+    use crate::{
+        context::Context,
+        graphql::{hash_name, mangle, Entry},
+    };
+
+    impl<C: Send + Sync, T: async_graphql::OutputType, const N: usize> async_graphql::TypeName
+        for HistoricalRegisterView<C, T, N>
+    {
+        fn type_name() -> Cow<'static, str> {
+            format!(
+                "HistoricalRegisterView_{}_{:08x}",
+                mangle(T::type_name()),
+                hash_name::<T>()
+            )
+            .into()
+        }
+    }
+
+    #[async_graphql::Object(cache_control(no_cache), name_type)]
+    impl<C: Context, T: async_graphql::OutputType, const N: usize> HistoricalRegisterView<C, T, N>
+    where
+        C: Send + Sync,
+        C::Extra: BlockHeightProvider,
+        T: serde::ser::Serialize + Clone + Send + Sync,
+    {
+        async fn value(&self) -> &T {
+            self.get()
+        }
+
+        async fn history_(&self, count: usize) -> Vec<Entry<BlockHeight, T>> {
+            self.history(count)
+                .into_iter()
+                .map(|(key, value)| Entry { key, value })
+                .collect()
+        }
+    }
+}
@@ -15,7 +15,13 @@
 
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
-use std::{collections::BTreeMap, fmt::Debug, mem, ops::Bound::Included, sync::Mutex};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    mem,
+    ops::Bound::{Excluded, Included},
+    sync::Mutex,
+};
 
 use linera_base::{data_types::ArithmeticError, ensure};
 use serde::{Deserialize, Serialize};
@@ -1075,6 +1081,101 @@ where
         Ok(keys)
     }
 
+    /// Finds at most `limit` keys matching the given prefix that come strictly after
+    /// `start_after`, or from the first one if `start_after` is `None`. The prefix is not
+    /// included in the returned keys.
+    ///
+    /// Unlike [`Self::find_keys_by_prefix`], this seeks to `start_after` in storage, so only the
+    /// keys of the page are loaded, together with the ones hidden by pending deletions.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::key_value_store_view::KeyValueStoreView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// view.insert(vec![0, 2], vec![42]).await.unwrap();
+    /// view.insert(vec![0, 3], vec![56]).await.unwrap();
+    /// let keys = view
+    ///     .find_keys_by_prefix_after(&[0], Some(&[1]), 1)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(keys, vec![vec![2]]);
+    /// # })
+    /// ```
+    pub async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        #[cfg(with_metrics)]
+        let _latency = KEY_VALUE_STORE_VIEW_FIND_KEYS_BY_PREFIX_LATENCY.measure_latency();
+        ensure!(
+            key_prefix.len() <= self.max_key_size(),
+            ViewError::KeyTooLong
+        );
+        let len = key_prefix.len();
+        let key_prefix_full = self
+            .context
+            .base_key()
+            .base_tag_index(KeyTag::Index as u8, key_prefix);
+        let lower_bound = match start_after {
+            Some(start_after) => Excluded([key_prefix, start_after].concat()),
+            None => Included(key_prefix.to_vec()),
+        };
+        let mut updates = self
+            .updates
+            .range((lower_bound, get_upper_bound(key_prefix)))
+            .peekable();
+        let mut suffix_closed_set =
+            SuffixClosedSetIterator::new(0, self.deletion_set.deleted_prefixes.iter());
+        let mut stored_keys = Vec::new().into_iter();
+        let mut stored_keys_done = self.deletion_set.delete_storage_first;
+        let mut cursor = start_after.map(<[u8]>::to_vec);
+        let mut keys = Vec::new();
+        while keys.len() < limit {
+            if stored_keys.len() == 0 && !stored_keys_done {
+                // Some stored keys may be deleted by the updates, in which case more of them
+                // are read.
+                let remaining = limit - keys.len();
+                let page = self
+                    .context
+                    .store()
+                    .find_keys_by_prefix_after(&key_prefix_full, cursor.as_deref(), remaining)
+                    .await?;
+                stored_keys_done = page.len() < remaining;
+                if let Some(last_key) = page.last() {
+                    cursor = Some(last_key.clone());
+                }
+                stored_keys = page.into_iter();
+            }
+            let next_is_update = match (stored_keys.as_slice().first(), updates.peek()) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(key), Some((update_key, _))) => update_key[len..] <= key[..],
+            };
+            if next_is_update {
+                let (update_key, update_value) = updates.next().expect("an update was peeked");
+                if stored_keys.as_slice().first().map(Vec::as_slice) == Some(&update_key[len..]) {
+                    stored_keys.next();
+                }
+                if let Update::Set(_) = update_value {
+                    keys.push(update_key[len..].to_vec());
+                }
+            } else if let Some(key) = stored_keys.next() {
+                let key_with_prefix = [key_prefix, &key].concat();
+                if !suffix_closed_set.find_key(&key_with_prefix) {
+                    keys.push(key);
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(keys)
+    }
+
     /// Iterates over all the key-value pairs, for keys matching the given prefix. The
     /// prefix is not included in the returned keys.
     /// ```rust
@@ -1289,6 +1390,18 @@ where
         let view = self.view.read().await;
         Ok(view.find_key_values_by_prefix(key_prefix).await?)
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, ViewContainerError> {
+        let view = self.view.read().await;
+        Ok(view
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await?)
+    }
 }
 
 #[cfg(with_testing)]
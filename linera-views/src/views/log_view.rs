@@ -347,6 +347,46 @@ where
             )
         }
     }
+
+    /// Reads the logged values in the given range (including staged ones). The end of the
+    /// range is capped by the size of the log.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::log_view::LogView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut log = LogView::load(context).await.unwrap();
+    /// log.push(34);
+    /// log.push(42);
+    /// log.push(56);
+    /// assert_eq!(log.read_range(1..5).await.unwrap(), vec![42, 56]);
+    /// # })
+    /// ```
+    pub async fn read_range(&self, range: Range<usize>) -> Result<Vec<T>, ViewError> {
+        self.read(range).await
+    }
+
+    /// Reads the `count` last logged values (including staged ones), or all of them if the
+    /// log is shorter.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::log_view::LogView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut log = LogView::load(context).await.unwrap();
+    /// log.push(34);
+    /// log.push(42);
+    /// log.push(56);
+    /// assert_eq!(log.read_last(2).await.unwrap(), vec![42, 56]);
+    /// assert_eq!(log.read_last(5).await.unwrap(), vec![34, 42, 56]);
+    /// # })
+    /// ```
+    pub async fn read_last(&self, count: usize) -> Result<Vec<T>, ViewError> {
+        let end = self.count();
+        self.read_range(end.saturating_sub(count)..end).await
+    }
 }
 
 impl<C, T> HashableView<C> for LogView<C, T>
@@ -407,8 +447,16 @@ mod graphql {
             end: Option<usize>,
         ) -> async_graphql::Result<Vec<T>> {
             Ok(self
-                .read(start.unwrap_or_default()..end.unwrap_or_else(|| self.count()))
+                .read_range(start.unwrap_or_default()..end.unwrap_or_else(|| self.count()))
                 .await?)
         }
+
+        async fn last_entries(&self, count: usize) -> async_graphql::Result<Vec<T>> {
+            Ok(self.read_last(count).await?)
+        }
+
+        async fn count_(&self) -> usize {
+            self.count()
+        }
     }
 }
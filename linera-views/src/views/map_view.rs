@@ -40,18 +40,22 @@ static MAP_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
 
 use std::{
     borrow::{Borrow, Cow},
-    collections::{btree_map::Entry, BTreeMap},
+    cmp::Ordering,
+    collections::{btree_map::Entry, BTreeMap, VecDeque},
     marker::PhantomData,
     mem,
+    ops::Bound,
 };
 
 use serde::{de::DeserializeOwned, Serialize};
 
+#[cfg(with_merkle_map)]
+use crate::merkle_proof::{MembershipProof, MerkleLeaves};
 use crate::{
     batch::Batch,
     common::{
-        from_bytes_option, get_interval, CustomSerialize, DeletionSet, HasherOutput,
-        SuffixClosedSetIterator, Update,
+        from_bytes_option, get_interval, CountLocation, CustomSerialize, DeletionSet, EntryCounter,
+        HasherOutput, SuffixClosedSetIterator, Update, KEY_PAGE_SIZE,
     },
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
@@ -65,6 +69,9 @@ pub struct ByteMapView<C, V> {
     context: C,
     deletion_set: DeletionSet,
     updates: BTreeMap<Vec<u8>, Update<V>>,
+    /// Whether the empty key holds the number of entries of the [`MapView`] wrapping this
+    /// view, and is therefore not an entry.
+    empty_key_is_count: bool,
 }
 
 /// Whether we have a value or its serialization.
@@ -122,6 +129,7 @@ where
             context,
             updates: BTreeMap::new(),
             deletion_set: DeletionSet::new(),
+            empty_key_is_count: false,
         })
     }
 
@@ -184,6 +192,7 @@ where
             context: self.context.clone(),
             updates: self.updates.clone(),
             deletion_set: self.deletion_set.clone(),
+            empty_key_is_count: self.empty_key_is_count,
         })
     }
 }
@@ -398,6 +407,14 @@ where
     }
 }
 
+impl<C, V> ByteMapView<C, V> {
+    /// Returns whether the key, with the first `prefix_len` bytes stripped, is the one
+    /// holding the number of entries rather than an entry.
+    fn is_count_key(&self, prefix_len: usize, short_key: &[u8]) -> bool {
+        self.empty_key_is_count && prefix_len == 0 && short_key.is_empty()
+    }
+}
+
 impl<C, V> ByteMapView<C, V>
 where
     C: Context,
@@ -453,6 +470,9 @@ where
                 .iterator()
             {
                 let index = index?;
+                if self.is_count_key(prefix_len, index) {
+                    continue;
+                }
                 loop {
                     match update {
                         Some((key, value)) if &key[prefix_len..] <= index => {
@@ -651,6 +671,9 @@ where
                 .into_iterator_owned()
             {
                 let (index, bytes) = entry?;
+                if self.is_count_key(prefix_len, &index) {
+                    continue;
+                }
                 loop {
                     match update {
                         Some((key, value)) if key[prefix_len..] <= *index => {
@@ -863,6 +886,157 @@ where
     pub async fn key_values(&self) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
         self.key_values_by_prefix(Vec::new()).await
     }
+
+    /// Returns at most `limit` keys of the map following `start_after`, in lexicographic
+    /// order. The last key returned is also returned as the `start_after` of the next page,
+    /// unless there are no more keys. Since the keys themselves serve as continuation,
+    /// pages remain consistent when entries are inserted in between. Storage is only read
+    /// from `start_after` on, so that a page costs about as much as its keys.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 2], String::from("Hallo"));
+    /// let (keys, next) = map.keys_paginated(None, 2).await.unwrap();
+    /// assert_eq!(keys, vec![vec![0, 1], vec![1, 2]]);
+    /// assert_eq!(next, Some(vec![1, 2]));
+    /// let (keys, next) = map.keys_paginated(next.as_deref(), 2).await.unwrap();
+    /// assert_eq!(keys, vec![vec![2, 2]]);
+    /// assert_eq!(next, None);
+    /// # })
+    /// ```
+    pub async fn keys_paginated(
+        &self,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<(Vec<Vec<u8>>, Option<Vec<u8>>), ViewError> {
+        let (keys, has_more) = self.keys_after(start_after, limit).await?;
+        let keys = keys.into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+        let next = if has_more { keys.last().cloned() } else { None };
+        Ok((keys, next))
+    }
+
+    /// Returns at most `limit` keys and values of the map following `start_after`, in
+    /// lexicographic order, as in [`ByteMapView::keys_paginated`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// let (key_values, next) = map.key_values_paginated(Some(&[0, 1]), 2).await.unwrap();
+    /// assert_eq!(key_values, vec![(vec![1, 2], String::from("Bonjour"))]);
+    /// assert_eq!(next, None);
+    /// # })
+    /// ```
+    #[expect(clippy::type_complexity)]
+    pub async fn key_values_paginated(
+        &self,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<(Vec<(Vec<u8>, V)>, Option<Vec<u8>>), ViewError> {
+        let (keys, has_more) = self.keys_after(start_after, limit).await?;
+        let stored_keys = keys
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| self.context.base_key().base_index(key))
+            .collect();
+        let mut stored_values = self
+            .context
+            .store()
+            .read_multi_values_bytes(stored_keys)
+            .await?
+            .into_iter();
+        let mut key_values = Vec::with_capacity(keys.len());
+        for (key, value) in keys {
+            let value = match value {
+                Some(value) => value.clone(),
+                None => {
+                    let bytes = stored_values.next().flatten();
+                    from_bytes_option::<_, ViewError>(&bytes)?.ok_or(ViewError::MissingEntries)?
+                }
+            };
+            key_values.push((key, value));
+        }
+        let next = if has_more {
+            key_values.last().map(|(key, _)| key.clone())
+        } else {
+            None
+        };
+        Ok((key_values, next))
+    }
+
+    /// Returns at most `limit` keys of the map following `start_after`, each with its
+    /// value if it is set by a pending update, and whether there are more keys. Only the
+    /// keys in storage following `start_after` are read, by pages.
+    #[expect(clippy::type_complexity)]
+    async fn keys_after(
+        &self,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<(Vec<(Vec<u8>, Option<&V>)>, bool), ViewError> {
+        let lower_bound = match start_after {
+            Some(start_after) => Bound::Excluded(start_after.to_vec()),
+            None => Bound::Unbounded,
+        };
+        let mut updates = self
+            .updates
+            .range((lower_bound, Bound::Unbounded))
+            .peekable();
+        let page_size = limit.saturating_add(1).min(KEY_PAGE_SIZE);
+        let mut stored_keys = VecDeque::new();
+        let mut last_stored_key = start_after.map(<[u8]>::to_vec);
+        let mut all_loaded = self.deletion_set.contains_prefix_of(&[]);
+        let mut keys = Vec::new();
+        while keys.len() <= limit {
+            if stored_keys.is_empty() && !all_loaded {
+                let page = self
+                    .context
+                    .store()
+                    .find_keys_by_prefix_after(
+                        &self.context.base_key().bytes,
+                        last_stored_key.as_deref(),
+                        page_size,
+                    )
+                    .await?;
+                all_loaded = page.len() < page_size;
+                last_stored_key = page.last().cloned().or(last_stored_key);
+                stored_keys.extend(page.into_iter().filter(|key| {
+                    !self.is_count_key(0, key) && !self.deletion_set.contains_prefix_of(key)
+                }));
+                continue;
+            }
+            let ordering = match (stored_keys.front(), updates.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(stored_key), Some((key, _))) => stored_key.cmp(key),
+            };
+            if ordering != Ordering::Greater {
+                let stored_key = stored_keys.pop_front();
+                if ordering == Ordering::Less {
+                    keys.extend(stored_key.map(|key| (key, None)));
+                    continue;
+                }
+            }
+            // The update overrides the stored key, if any.
+            let (key, update) = updates.next().unwrap();
+            if let Update::Set(value) = update {
+                keys.push((key.clone(), Some(value)));
+            }
+        }
+        let has_more = keys.len() > limit;
+        keys.truncate(limit);
+        Ok((keys, has_more))
+    }
 }
 
 impl<C, V> ByteMapView<C, V>
@@ -936,30 +1110,85 @@ where
     async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
         #[cfg(with_metrics)]
         let _hash_latency = MAP_VIEW_HASH_RUNTIME.measure_latency();
-        let mut hasher = sha3::Sha3_256::default();
-        let mut count = 0u32;
-        let prefix = Vec::new();
+        #[cfg(with_merkle_map)]
+        return Ok(self.merkle_leaves().await?.root());
+        #[cfg(not(with_merkle_map))]
+        {
+            let mut hasher = sha3::Sha3_256::default();
+            let mut count = 0u32;
+            let prefix = Vec::new();
+            self.for_each_key_value_or_bytes(
+                |index, value| {
+                    count += 1;
+                    hasher.update_with_bytes(index)?;
+                    let bytes = value.into_bytes()?;
+                    hasher.update_with_bytes(&bytes)?;
+                    Ok(())
+                },
+                prefix,
+            )
+            .await?;
+            hasher.update_with_bcs_bytes(&count)?;
+            Ok(hasher.finalize())
+        }
+    }
+}
+
+#[cfg(with_merkle_map)]
+impl<C, V> ByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Computes the proof that the entry of `short_key` is part of the hash of the map,
+    /// or returns `None` if the key is missing. This visits all the entries of the map.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::merkle_proof::verify_proof;
+    /// # use linera_views::views::{HashableView, View};
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![2], String::from("Bonjour"));
+    /// let root_hash = map.hash().await.unwrap();
+    /// let proof = map.hash_proof(&[0, 1]).await.unwrap().unwrap();
+    /// let value_bytes = bcs::to_bytes("Hello").unwrap();
+    /// assert!(verify_proof(&root_hash, &[0, 1], &value_bytes, &proof));
+    /// assert!(map.hash_proof(&[3]).await.unwrap().is_none());
+    /// # })
+    /// ```
+    pub async fn hash_proof(&self, short_key: &[u8]) -> Result<Option<MembershipProof>, ViewError> {
+        Ok(self.merkle_leaves().await?.proof(short_key))
+    }
+
+    async fn merkle_leaves(&self) -> Result<MerkleLeaves, ViewError> {
+        let mut leaves = MerkleLeaves::default();
         self.for_each_key_value_or_bytes(
             |index, value| {
-                count += 1;
-                hasher.update_with_bytes(index)?;
-                let bytes = value.into_bytes()?;
-                hasher.update_with_bytes(&bytes)?;
+                leaves.push(index, &value.into_bytes()?);
                 Ok(())
             },
-            prefix,
+            Vec::new(),
         )
         .await?;
-        hasher.update_with_bcs_bytes(&count)?;
-        Ok(hasher.finalize())
+        Ok(leaves)
     }
 }
 
 /// A `View` that has a type for keys. The ordering of the entries
 /// is determined by the serialization of the context.
+///
+/// The number of entries is stored along with them, at the base key itself, so that
+/// [`MapView::count`] does not need to iterate over the keys. Indices must therefore not
+/// have an empty serialization, unless they are zero-sized, e.g. `()`: there is then at
+/// most one entry, and the count is not persisted.
 #[derive(Debug)]
 pub struct MapView<C, I, V> {
     map: ByteMapView<C, V>,
+    counter: EntryCounter,
     _phantom: PhantomData<I>,
 }
 
@@ -970,26 +1199,40 @@ where
     I: Send + Sync,
     V: Send + Sync + Serialize,
 {
-    const NUM_INIT_KEYS: usize = ByteMapView::<C, V>::NUM_INIT_KEYS;
+    const NUM_INIT_KEYS: usize = 1 + ByteMapView::<C, V>::NUM_INIT_KEYS;
 
     fn context(&self) -> &C {
         self.map.context()
     }
 
     fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
-        ByteMapView::<C, V>::pre_load(context)
+        let mut v = vec![context.base_key().bytes.clone()];
+        v.extend(ByteMapView::<C, V>::pre_load(context)?);
+        Ok(v)
     }
 
     fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
-        let map = ByteMapView::post_load(context, values)?;
+        let location = CountLocation::for_index::<I>();
+        let counter = EntryCounter::post_load(
+            values.first().ok_or(ViewError::PostLoadValuesError)?,
+            location,
+        )?;
+        let mut map = ByteMapView::post_load(
+            context,
+            values.get(1..).ok_or(ViewError::PostLoadValuesError)?,
+        )?;
+        map.empty_key_is_count = location == CountLocation::EmptyKey;
         Ok(MapView {
             map,
+            counter,
             _phantom: PhantomData,
         })
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -1000,8 +1243,23 @@ where
         self.map.has_pending_changes().await
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.counter
+            .pre_save(
+                self.map.context(),
+                self.map.deletion_set.delete_storage_first,
+                &self.map.updates,
+            )
+            .await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
-        self.map.flush(batch)
+        let cleared = self.map.deletion_set.delete_storage_first;
+        let count = self.counter.known_count(cleared, &self.map.updates);
+        let delete_view = self.map.flush(batch)?;
+        let key = self.map.context().base_key().bytes.clone();
+        self.counter.flush(batch, key, cleared, count)?;
+        Ok(delete_view)
     }
 
     fn clear(&mut self) {
@@ -1019,6 +1277,7 @@ where
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
         Ok(MapView {
             map: self.map.clone_unchecked()?,
+            counter: self.counter.clone(),
             _phantom: PhantomData,
         })
     }
@@ -1050,7 +1309,7 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.map.insert(short_key, value);
         Ok(())
     }
@@ -1072,7 +1331,7 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.map.remove(short_key);
         Ok(())
     }
@@ -1100,7 +1359,7 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.map.contains_key(&short_key).await
     }
 }
@@ -1133,10 +1392,41 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.map.get(&short_key).await
     }
 
+    /// Reads the values at the given positions, if any, with a single storage query.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: MapView<_, u32, _> = MapView::load(context).await.unwrap();
+    /// map.insert(&(37 as u32), String::from("Hello"));
+    /// let indices = vec![37 as u32, 34 as u32];
+    /// assert_eq!(
+    ///     map.multi_get(&indices).await.unwrap(),
+    ///     vec![Some(String::from("Hello")), None]
+    /// );
+    /// # })
+    /// ```
+    pub async fn multi_get<'a, Q>(
+        &self,
+        indices: impl IntoIterator<Item = &'a Q>,
+    ) -> Result<Vec<Option<V>>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + 'a,
+    {
+        let short_keys = indices
+            .into_iter()
+            .map(|index| self.counter.derive_short_key(index))
+            .collect::<Result<_, _>>()?;
+        self.map.multi_get(short_keys).await
+    }
+
     /// Obtains a mutable reference to a value at a given position if available
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1160,7 +1450,7 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.map.get_mut(&short_key).await
     }
 }
@@ -1194,6 +1484,52 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices following `start_after`, in the order determined by
+    /// serialization. The last index returned is also returned as the `start_after` of the
+    /// next page, unless there are no more indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: MapView<_, u32, String> = MapView::load(context).await.unwrap();
+    /// map.insert(&(34 as u32), String::from("Hello"));
+    /// map.insert(&(37 as u32), String::from("Bonjour"));
+    /// map.insert(&(42 as u32), String::from("Hallo"));
+    /// let (indices, next) = map.keys_paginated(None, 2).await.unwrap();
+    /// assert_eq!(indices, vec![34, 37]);
+    /// let (indices, next) = map.keys_paginated(next.as_ref(), 2).await.unwrap();
+    /// assert_eq!(indices, vec![42]);
+    /// assert_eq!(next, None);
+    /// # })
+    /// ```
+    pub async fn keys_paginated(
+        &self,
+        start_after: Option<&I>,
+        limit: usize,
+    ) -> Result<(Vec<I>, Option<I>), ViewError>
+    where
+        I: Serialize,
+        V: Send,
+    {
+        let start_after = start_after
+            .map(|index| self.counter.derive_short_key(index))
+            .transpose()?;
+        let (keys, next) = self
+            .map
+            .keys_paginated(start_after.as_deref(), limit)
+            .await?;
+        let indices = keys
+            .iter()
+            .map(|key| BaseKey::deserialize_value(key))
+            .collect::<Result<_, _>>()?;
+        let next = next
+            .map(|key| BaseKey::deserialize_value(&key))
+            .transpose()?;
+        Ok((indices, next))
+    }
+
     /// Applies a function f on each index. Indices are visited in an order
     /// determined by the serialization. If the function returns false, then
     /// the loop ends prematurely.
@@ -1383,7 +1719,54 @@ where
         Ok(key_values)
     }
 
-    /// Obtains the number of entries in the map
+    /// Returns at most `limit` `(index,value)` pairs following `start_after`, as in
+    /// [`MapView::keys_paginated`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: MapView<_, String, _> = MapView::load(context).await.unwrap();
+    /// map.insert("French", String::from("Bonjour"));
+    /// map.insert("Italian", String::from("Ciao"));
+    /// let (index_values, next) = map.index_values_paginated(None, 1).await.unwrap();
+    /// assert_eq!(
+    ///     index_values,
+    ///     vec![("French".to_string(), "Bonjour".to_string())]
+    /// );
+    /// assert_eq!(next, Some("French".to_string()));
+    /// # })
+    /// ```
+    #[expect(clippy::type_complexity)]
+    pub async fn index_values_paginated(
+        &self,
+        start_after: Option<&I>,
+        limit: usize,
+    ) -> Result<(Vec<(I, V)>, Option<I>), ViewError>
+    where
+        I: Serialize,
+    {
+        let start_after = start_after
+            .map(|index| self.counter.derive_short_key(index))
+            .transpose()?;
+        let (key_values, next) = self
+            .map
+            .key_values_paginated(start_after.as_deref(), limit)
+            .await?;
+        let index_values = key_values
+            .into_iter()
+            .map(|(key, value)| Ok((BaseKey::deserialize_value(&key)?, value)))
+            .collect::<Result<_, ViewError>>()?;
+        let next = next
+            .map(|key| BaseKey::deserialize_value(&key))
+            .transpose()?;
+        Ok((index_values, next))
+    }
+
+    /// Obtains the number of entries in the map. This uses the stored number of entries
+    /// and only looks up the keys modified since the last flush. If no number is stored,
+    /// as for maps saved before it was maintained, it is rebuilt from the keys in storage.
     /// ```rust
     /// # tokio_test::block_on(async {
     /// # use linera_views::context::MemoryContext;
@@ -1397,7 +1780,36 @@ where
     /// # })
     /// ```
     pub async fn count(&self) -> Result<usize, ViewError> {
-        self.map.count().await
+        self.counter
+            .count(
+                self.map.context(),
+                self.map.deletion_set.delete_storage_first,
+                &self.map.updates,
+            )
+            .await
+    }
+
+    /// Rebuilds the stored number of entries by iterating over the keys in storage.
+    /// The result is persisted by the next flush.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: MapView<_, String, _> = MapView::load(context).await.unwrap();
+    /// map.insert("Italian", String::from("Ciao"));
+    /// assert_eq!(map.recount().await.unwrap(), 1);
+    /// # })
+    /// ```
+    pub async fn recount(&mut self) -> Result<usize, ViewError> {
+        self.counter
+            .recount(
+                self.map.context(),
+                self.map.deletion_set.delete_storage_first,
+                &self.map.updates,
+            )
+            .await
     }
 }
 
@@ -1426,7 +1838,7 @@ where
         I: Borrow<Q>,
         Q: Sync + Send + Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.map.get_mut_or_default(&short_key).await
     }
 }
@@ -1449,6 +1861,44 @@ where
     }
 }
 
+#[cfg(with_merkle_map)]
+impl<C, I, V> MapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Serialize,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Computes the proof that the entry of `index` is part of the hash of the map, or
+    /// returns `None` if the index is missing. The proof is checked against the BCS
+    /// serializations of the index and of the value. This visits all the entries of the
+    /// map.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::merkle_proof::verify_proof;
+    /// # use linera_views::views::{HashableView, View};
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: MapView<_, u32, _> = MapView::load(context).await.unwrap();
+    /// map.insert(&(37 as u32), String::from("Hello"));
+    /// let root_hash = map.hash().await.unwrap();
+    /// let proof = map.hash_proof(&(37 as u32)).await.unwrap().unwrap();
+    /// let key_bytes = bcs::to_bytes(&(37 as u32)).unwrap();
+    /// let value_bytes = bcs::to_bytes("Hello").unwrap();
+    /// assert!(verify_proof(&root_hash, &key_bytes, &value_bytes, &proof));
+    /// # })
+    /// ```
+    pub async fn hash_proof<Q>(&self, index: &Q) -> Result<Option<MembershipProof>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = self.counter.derive_short_key(index)?;
+        self.map.hash_proof(&short_key).await
+    }
+}
+
 /// A map view that uses custom serialization
 #[derive(Debug)]
 pub struct CustomMapView<C, I, V> {
@@ -1630,6 +2080,37 @@ where
         self.map.get(&short_key).await
     }
 
+    /// Reads the values at the given positions, if any, with a single storage query.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: CustomMapView<MemoryContext<()>, u128, String> =
+    ///     CustomMapView::load(context).await.unwrap();
+    /// map.insert(&(34 as u128), String::from("Hello"));
+    /// assert_eq!(
+    ///     map.multi_get([34 as u128, 37 as u128]).await.unwrap(),
+    ///     vec![Some(String::from("Hello")), None]
+    /// );
+    /// # })
+    /// ```
+    pub async fn multi_get<Q>(
+        &self,
+        indices: impl IntoIterator<Item = Q>,
+    ) -> Result<Vec<Option<V>>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+    {
+        let short_keys = indices
+            .into_iter()
+            .map(|index| index.to_custom_bytes())
+            .collect::<Result<_, _>>()?;
+        self.map.multi_get(short_keys).await
+    }
+
     /// Obtains a mutable reference to a value at a given position if available
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1961,12 +2442,51 @@ pub type HashedCustomMapView<C, I, V> =
 mod graphql {
     use std::borrow::Cow;
 
+    use async_graphql::connection::{Connection, CursorType as _, Edge, OpaqueCursor};
+    use serde::{de::DeserializeOwned, Serialize};
+
     use super::{ByteMapView, CustomMapView, MapView};
     use crate::{
-        context::Context,
-        graphql::{hash_name, mangle, Entry, MapInput},
+        common::CustomSerialize as _,
+        context::{BaseKey, Context},
+        graphql::{hash_name, mangle, page_size, Entry, MapInput},
+        views::ViewError,
     };
 
+    /// Returns the page of at most `first` entries of the `map` following the cursor
+    /// `after`. The cursors are the encoded short keys, which remain valid when entries
+    /// are inserted or removed.
+    async fn entries_connection<C, I, V>(
+        map: &ByteMapView<C, V>,
+        after: Option<String>,
+        first: Option<usize>,
+        deserialize_key: impl Fn(&[u8]) -> Result<I, ViewError>,
+    ) -> Result<Connection<String, Entry<I, Option<V>>>, async_graphql::Error>
+    where
+        C: Context,
+        ViewError: From<C::Error>,
+        I: async_graphql::OutputType,
+        V: async_graphql::OutputType + Clone + Send + Serialize + DeserializeOwned + 'static,
+    {
+        let start_after = after
+            .as_deref()
+            .map(OpaqueCursor::<Vec<u8>>::decode_cursor)
+            .transpose()?;
+        let (key_values, next) = map
+            .key_values_paginated(start_after.as_deref().map(Vec::as_slice), page_size(first))
+            .await?;
+        let mut connection = Connection::new(start_after.is_some(), next.is_some());
+        for (short_key, value) in key_values {
+            let entry = Entry {
+                key: deserialize_key(&short_key)?,
+                value: Some(value),
+            };
+            let cursor = OpaqueCursor(short_key).encode_cursor();
+            connection.edges.push(Edge::new(cursor, entry));
+        }
+        Ok(connection)
+    }
+
     impl<C: Send + Sync, V: async_graphql::OutputType> async_graphql::TypeName for ByteMapView<C, V> {
         fn type_name() -> Cow<'static, str> {
             format!(
@@ -2024,15 +2544,20 @@ mod graphql {
                 self.keys().await?
             };
 
-            let mut entries = vec![];
-            for key in keys {
-                entries.push(Entry {
-                    value: self.get(&key).await?,
-                    key,
-                })
-            }
+            let values = self.multi_get(keys.clone()).await?;
+            Ok(keys
+                .into_iter()
+                .zip(values)
+                .map(|(key, value)| Entry { value, key })
+                .collect())
+        }
 
-            Ok(entries)
+        async fn entries_connection(
+            &self,
+            after: Option<String>,
+            first: Option<usize>,
+        ) -> Result<Connection<String, Entry<Vec<u8>, Option<V>>>, async_graphql::Error> {
+            entries_connection(self, after, first, |key| Ok(key.to_vec())).await
         }
     }
 
@@ -2101,15 +2626,23 @@ mod graphql {
                 self.indices().await?
             };
 
-            let mut values = vec![];
-            for key in keys {
-                values.push(Entry {
-                    value: self.get(&key).await?,
-                    key,
-                })
-            }
+            let values = self.multi_get(&keys).await?;
+            Ok(keys
+                .into_iter()
+                .zip(values)
+                .map(|(key, value)| Entry { value, key })
+                .collect())
+        }
 
-            Ok(values)
+        async fn entries_connection(
+            &self,
+            after: Option<String>,
+            first: Option<usize>,
+        ) -> Result<Connection<String, Entry<I, Option<V>>>, async_graphql::Error> {
+            entries_connection(&self.map, after, first, |key| {
+                Ok(BaseKey::deserialize_value(key)?)
+            })
+            .await
         }
     }
 
@@ -2171,15 +2704,20 @@ mod graphql {
                 self.indices().await?
             };
 
-            let mut values = vec![];
-            for key in keys {
-                values.push(Entry {
-                    value: self.get(&key).await?,
-                    key,
-                })
-            }
+            let values = self.multi_get(keys.clone()).await?;
+            Ok(keys
+                .into_iter()
+                .zip(values)
+                .map(|(key, value)| Entry { value, key })
+                .collect())
+        }
 
-            Ok(values)
+        async fn entries_connection(
+            &self,
+            after: Option<String>,
+            first: Option<usize>,
+        ) -> Result<Connection<String, Entry<I, Option<V>>>, async_graphql::Error> {
+            entries_connection(&self.map, after, first, I::from_custom_bytes).await
         }
     }
 }
@@ -23,6 +23,9 @@ mod tests;
 /// The `RegisterView` implements a register for a single value.
 pub mod register_view;
 
+/// The `HistoricalRegisterView` implements a register that keeps its last values.
+pub mod historical_register_view;
+
 /// The `LogView` implements a log list that can be pushed.
 pub mod log_view;
 
@@ -35,9 +38,18 @@ pub mod queue_view;
 /// The `MapView` implements a map with ordered keys.
 pub mod map_view;
 
+/// The `SortedMapView` implements a map ordered by its keys that supports range queries.
+pub mod sorted_map_view;
+
+/// The `TtlMapView` implements a map whose entries expire after a deadline.
+pub mod ttl_map_view;
+
 /// The `SetView` implements a set with ordered entries.
 pub mod set_view;
 
+/// The `EnumView` holds one of the variants of an enum of views.
+pub mod enum_view;
+
 /// The `CollectionView` implements a map structure whose keys are ordered and the values are views.
 pub mod collection_view;
 
@@ -78,6 +90,14 @@ pub trait View<C>: Sized {
     /// Returns [`true`] if flushing this view would result in changes to the persistent storage.
     async fn has_pending_changes(&self) -> bool;
 
+    /// Reads from storage what `flush` needs to keep the metadata of the view exact, such
+    /// as whether the modified entries were already stored. This is called by
+    /// [`RootView::save`] before flushing. Views flushed without it may have to rebuild
+    /// some of their metadata from storage later.
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        Ok(())
+    }
+
     /// Clears the view. That can be seen as resetting to default. If the clear is followed
     /// by a flush then all the relevant data is removed on the storage.
     fn clear(&mut self);
@@ -121,6 +141,10 @@ pub enum ViewError {
     #[error("Failed to lock collection entry: {0:?}")]
     TryLockError(Vec<u8>),
 
+    /// The same entry was requested more than once for writing.
+    #[error("Collection entry requested more than once for writing: {0:?}")]
+    DuplicateCollectionEntry(Vec<u8>),
+
     /// Tokio errors can happen while joining.
     #[error("Panic in sub-task: {0}")]
     TokioJoinError(#[from] tokio::task::JoinError),
@@ -138,6 +162,11 @@ pub enum ViewError {
     #[error("The key must not be too long")]
     KeyTooLong,
 
+    /// The index of an entry has an empty serialization, and the empty key holds the
+    /// number of entries.
+    #[error("Indices with an empty serialization are not supported by this view")]
+    EmptyIndex,
+
     /// The entry does not exist in memory
     // FIXME(#148): This belongs to a future `linera_storage::StoreError`.
     #[error("Entry does not exist in memory: {0}")]
@@ -166,6 +195,10 @@ pub enum ViewError {
     /// Some events were not found.
     #[error("Events not found: {0:?}")]
     EventsNotFound(Vec<EventId>),
+
+    /// Views saved together must not share any key.
+    #[error("Views saved together have overlapping base keys {0:?} and {1:?}")]
+    OverlappingBaseKeys(Vec<u8>, Vec<u8>),
 }
 
 impl ViewError {
@@ -232,6 +265,74 @@ pub trait RootView<C>: View<C> {
     async fn save(&mut self) -> Result<(), ViewError>;
 }
 
+/// Saves several views sharing the same store with a single write, so that either all
+/// or none of their changes are persisted.
+///
+/// The views are typically root views, and their base keys must not be prefixes of each
+/// other, otherwise [`ViewError::OverlappingBaseKeys`] is returned before anything is
+/// flushed. If flushing one of the views fails, nothing is written; however, the views
+/// flushed before it already consider their changes saved, so all the views should be
+/// reloaded.
+/// ```rust
+/// # tokio_test::block_on(async {
+/// # use linera_views::context::{Context as _, MemoryContext};
+/// # use linera_views::register_view::RegisterView;
+/// # use linera_views::views::View;
+/// # let context = MemoryContext::new_for_testing(());
+/// let mut hot = RegisterView::<_, u64>::load(context.clone_with_base_key(vec![0]))
+///     .await
+///     .unwrap();
+/// let mut archive = RegisterView::<_, u64>::load(context.clone_with_base_key(vec![1]))
+///     .await
+///     .unwrap();
+/// hot.set(1);
+/// archive.set(2);
+/// linera_views::save_all!(hot, archive).await.unwrap();
+/// # })
+/// ```
+#[macro_export]
+macro_rules! save_all {
+    ($first:expr $(, $view:expr)* $(,)?) => {
+        async {
+            use $crate::{
+                batch::Batch, context::Context as _, store::WritableKeyValueStore as _,
+                views::View as _,
+            };
+            $crate::views::check_disjoint_base_keys(&[
+                $first.context().base_key().bytes.as_slice(),
+                $($view.context().base_key().bytes.as_slice(),)*
+            ])?;
+            $first.pre_save().await?;
+            $($view.pre_save().await?;)*
+            let mut batch = Batch::new();
+            $first.flush(&mut batch)?;
+            $($view.flush(&mut batch)?;)*
+            if !batch.is_empty() {
+                $first.context().store().write_batch(batch).await?;
+            }
+            Ok::<(), $crate::views::ViewError>(())
+        }
+    };
+}
+
+/// Checks that none of the `base_keys` is a prefix of another one, as needed for the
+/// views using them to be saved together with [`save_all!`][crate::save_all].
+pub fn check_disjoint_base_keys(base_keys: &[&[u8]]) -> Result<(), ViewError> {
+    let mut base_keys = base_keys.to_vec();
+    base_keys.sort_unstable();
+    // A key between a prefix and one of its extensions also extends the prefix, so
+    // checking consecutive keys is enough.
+    for pair in base_keys.windows(2) {
+        if pair[1].starts_with(pair[0]) {
+            return Err(ViewError::OverlappingBaseKeys(
+                pair[0].to_vec(),
+                pair[1].to_vec(),
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// A [`View`] that also supports crypto hash
 #[cfg_attr(not(web), trait_variant::make(Send))]
 pub trait CryptoHashView<C>: HashableView<C> {
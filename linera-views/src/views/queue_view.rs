@@ -8,6 +8,10 @@ use std::{
     ops::Range,
 };
 
+use futures::{
+    stream::{self, Stream},
+    StreamExt as _, TryStreamExt as _,
+};
 use serde::{de::DeserializeOwned, Serialize};
 #[cfg(with_metrics)]
 use {
@@ -253,11 +257,30 @@ where
     /// # })
     /// ```
     pub fn delete_front(&mut self) {
-        if self.stored_count() > 0 {
-            self.front_delete_count += 1;
-        } else {
-            self.new_back_values.pop_front();
-        }
+        self.delete_front_count(1);
+    }
+
+    /// Deletes the `count` front values, or all of them if the queue is shorter.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::queue_view::QueueView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut queue = QueueView::load(context).await.unwrap();
+    /// queue.push_back(34);
+    /// queue.push_back(37);
+    /// queue.push_back(42);
+    /// queue.delete_front_count(2);
+    /// assert_eq!(queue.elements().await.unwrap(), vec![42]);
+    /// # })
+    /// ```
+    pub fn delete_front_count(&mut self, count: usize) {
+        // The stored values come first, followed by the values staged with `push_back`.
+        let stored_deletions = count.min(self.stored_count());
+        self.front_delete_count += stored_deletions;
+        let staged_deletions = (count - stored_deletions).min(self.new_back_values.len());
+        self.new_back_values.drain(..staged_deletions);
     }
 
     /// Pushes a value to the end of the queue.
@@ -414,6 +437,45 @@ where
         self.read_front(count).await
     }
 
+    /// Streams all the elements, reading the stored ones from storage in batches of
+    /// `batch_size` values.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use futures::TryStreamExt as _;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::queue_view::QueueView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut queue = QueueView::load(context).await.unwrap();
+    /// queue.push_back(34);
+    /// queue.push_back(37);
+    /// queue.push_back(42);
+    /// let elements = queue
+    ///     .stream_elements(2)
+    ///     .try_collect::<Vec<_>>()
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(elements, vec![34, 37, 42]);
+    /// # })
+    /// ```
+    pub fn stream_elements(
+        &self,
+        batch_size: usize,
+    ) -> impl Stream<Item = Result<T, ViewError>> + '_ {
+        let batch_size = batch_size.max(1);
+        let end = self.stored_indices.end;
+        let start = end - self.stored_count();
+        let batches = (start..end)
+            .step_by(batch_size)
+            .map(move |batch_start| batch_start..end.min(batch_start + batch_size));
+        let stored_values = stream::iter(batches)
+            .then(move |range| self.read_context(range))
+            .map_ok(|values| stream::iter(values.into_iter().map(Ok)))
+            .try_flatten();
+        let staged_values = stream::iter(self.new_back_values.iter().cloned().map(Ok));
+        stored_values.chain(staged_values)
+    }
+
     async fn load_all(&mut self) -> Result<(), ViewError> {
         if !self.delete_storage_first {
             let stored_remainder = self.stored_count();
@@ -5,7 +5,7 @@
 use std::sync::LazyLock;
 use std::{
     borrow::Borrow,
-    collections::{btree_map, BTreeMap},
+    collections::{btree_map, BTreeMap, BTreeSet},
     io::Write,
     marker::PhantomData,
     mem,
@@ -24,7 +24,7 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{CountLocation, CustomSerialize, EntryCounter, HasherOutput, Update},
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
     store::{KeyIterable, ReadableKeyValueStore as _},
@@ -72,6 +72,9 @@ impl<T> std::ops::DerefMut for WriteGuardedView<T> {
 
 /// A view that supports accessing a collection of views of the same kind, indexed by `Vec<u8>`,
 /// possibly several subviews at a time.
+///
+/// The number of entries is stored along with them, so that
+/// [`ReentrantByteCollectionView::count`] does not need to iterate over the indices.
 #[derive(Debug)]
 pub struct ReentrantByteCollectionView<C, W> {
     /// The view [`Context`].
@@ -82,6 +85,8 @@ pub struct ReentrantByteCollectionView<C, W> {
     updates: BTreeMap<Vec<u8>, Update<Arc<RwLock<W>>>>,
     /// Entries cached in memory that have the exact same state as in the persistent storage.
     cached_entries: Mutex<BTreeMap<Vec<u8>, Arc<RwLock<W>>>>,
+    /// The number of entries.
+    counter: EntryCounter,
 }
 
 /// We need to find new base keys in order to implement the collection view.
@@ -97,6 +102,8 @@ enum KeyTag {
     Index = MIN_VIEW_TAG,
     /// Prefix for specifying as the prefix for the sub-view.
     Subview,
+    /// Prefix for the number of entries.
+    Count,
 }
 
 impl<C, W> View<C> for ReentrantByteCollectionView<C, W>
@@ -105,27 +112,34 @@ where
     ViewError: From<C::Error>,
     W: View<C> + Send + Sync,
 {
-    const NUM_INIT_KEYS: usize = 0;
+    const NUM_INIT_KEYS: usize = 1;
 
     fn context(&self) -> &C {
         &self.context
     }
 
-    fn pre_load(_context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
-        Ok(Vec::new())
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        Ok(vec![context.base_key().base_tag(KeyTag::Count as u8)])
     }
 
-    fn post_load(context: C, _values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let counter = EntryCounter::post_load(
+            values.first().ok_or(ViewError::PostLoadValuesError)?,
+            CountLocation::Tagged,
+        )?;
         Ok(Self {
             context,
             delete_storage_first: false,
             updates: BTreeMap::new(),
             cached_entries: Mutex::new(BTreeMap::new()),
+            counter,
         })
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -140,11 +154,32 @@ where
         !self.updates.is_empty()
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.counter
+            .pre_save(
+                &self.index_context(),
+                self.delete_storage_first,
+                &self.updates,
+            )
+            .await?;
+        for update in self.updates.values_mut() {
+            if let Update::Set(view) = update {
+                let view = Arc::get_mut(view).ok_or(ViewError::CannotAcquireCollectionEntry)?;
+                view.get_mut().pre_save().await?;
+            }
+        }
+        Ok(())
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         let mut delete_view = false;
+        let count_key = self.context.base_key().base_tag(KeyTag::Count as u8);
         if self.delete_storage_first {
             delete_view = true;
             batch.delete_key_prefix(self.context.base_key().bytes.clone());
+            // The count is written after the deletion of the prefix that contains it.
+            let count = self.counter.known_count(true, &self.updates);
+            self.counter.flush(batch, count_key, true, count)?;
             for (index, update) in mem::take(&mut self.updates) {
                 if let Update::Set(view) = update {
                     let mut view = Arc::try_unwrap(view)
@@ -156,6 +191,8 @@ where
                 }
             }
         } else {
+            let count = self.counter.known_count(false, &self.updates);
+            self.counter.flush(batch, count_key, false, count)?;
             for (index, update) in mem::take(&mut self.updates) {
                 match update {
                     Update::Set(view) => {
@@ -215,6 +252,7 @@ where
             delete_storage_first: self.delete_storage_first,
             updates: cloned_updates,
             cached_entries: Mutex::new(BTreeMap::new()),
+            counter: self.counter.clone(),
         })
     }
 }
@@ -236,6 +274,12 @@ impl<C: Context, W> ReentrantByteCollectionView<C, W> {
         let key = self.get_index_key(index);
         batch.put_key_value_bytes(key, vec![]);
     }
+
+    /// The context whose keys are the indices of the entries, as used for counting them.
+    fn index_context(&self) -> C {
+        let key = self.context.base_key().base_tag(KeyTag::Index as u8);
+        self.context.clone_with_base_key(key)
+    }
 }
 
 impl<C, W> ReentrantByteCollectionView<C, W>
@@ -355,6 +399,100 @@ where
         ))
     }
 
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection and `init` is applied
+    /// to it. The index and, if present, the subview are read from storage at once.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::reentrant_collection_view::ReentrantByteCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: ReentrantByteCollectionView<_, RegisterView<_, String>> =
+    ///     ReentrantByteCollectionView::load(context).await.unwrap();
+    /// let init = |subview: &mut RegisterView<_, String>| {
+    ///     subview.set(String::from("Hello"));
+    ///     Ok(())
+    /// };
+    /// {
+    ///     let mut subview = view.try_load_entry_or_insert(&[0, 1], init).await.unwrap();
+    ///     subview.set(String::from("Bonjour"));
+    /// }
+    /// let subview = view.try_load_entry_or_insert(&[0, 1], init).await.unwrap();
+    /// assert_eq!(subview.get(), "Bonjour");
+    /// # })
+    /// ```
+    pub async fn try_load_entry_or_insert<F>(
+        &mut self,
+        short_key: &[u8],
+        init: F,
+    ) -> Result<WriteGuardedView<W>, ViewError>
+    where
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        use btree_map::Entry::*;
+        let view = match self.updates.entry(short_key.to_owned()) {
+            Occupied(mut entry) => match entry.get_mut() {
+                Update::Set(view) => view.clone(),
+                entry @ Update::Removed => {
+                    let view = Self::initialized_view(&self.context, short_key, init)?;
+                    *entry = Update::Set(view.clone());
+                    view
+                }
+            },
+            Vacant(entry) => {
+                let cached_view = self.cached_entries.get_mut().unwrap().remove(short_key);
+                let view = match cached_view {
+                    Some(view) => view,
+                    None if self.delete_storage_first => {
+                        Self::initialized_view(&self.context, short_key, init)?
+                    }
+                    None => {
+                        let key = self
+                            .context
+                            .base_key()
+                            .base_tag_index(KeyTag::Subview as u8, short_key);
+                        let context = self.context.clone_with_base_key(key);
+                        let mut keys = vec![self
+                            .context
+                            .base_key()
+                            .base_tag_index(KeyTag::Index as u8, short_key)];
+                        keys.extend(W::pre_load(&context)?);
+                        let values = self.context.store().read_multi_values_bytes(keys).await?;
+                        if values[0].is_some() {
+                            Arc::new(RwLock::new(W::post_load(context, &values[1..])?))
+                        } else {
+                            Self::initialized_view(&self.context, short_key, init)?
+                        }
+                    }
+                };
+                entry.insert(Update::Set(view.clone()));
+                view
+            }
+        };
+        Ok(WriteGuardedView(view.try_write_arc().ok_or_else(|| {
+            ViewError::TryLockError(short_key.to_vec())
+        })?))
+    }
+
+    /// Creates an empty view for the entry and applies `init` to it.
+    fn initialized_view<F>(
+        context: &C,
+        short_key: &[u8],
+        init: F,
+    ) -> Result<Arc<RwLock<W>>, ViewError>
+    where
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        let key = context
+            .base_key()
+            .base_tag_index(KeyTag::Subview as u8, short_key);
+        let mut view = W::new(context.clone_with_base_key(key))?;
+        init(&mut view)?;
+        Ok(Arc::new(RwLock::new(view)))
+    }
+
     /// Loads a subview at the given index in the collection and gives read-only access to the data.
     /// If an entry is absent then `None` is returned.
     /// ```rust
@@ -496,8 +634,38 @@ where
     ViewError: From<C::Error>,
     W: View<C> + Send + Sync + 'static,
 {
+    /// Hints that the given entries are about to be loaded, so that the store can start
+    /// reading the keys needed to load them in the background. The entries that are
+    /// staged or cached in memory are skipped.
+    pub fn prefetch_entries(&self, short_keys: Vec<Vec<u8>>) -> Result<(), ViewError> {
+        if self.delete_storage_first {
+            return Ok(());
+        }
+        let mut keys = Vec::new();
+        {
+            let cached_entries = self.cached_entries.lock().unwrap();
+            for short_key in short_keys {
+                if self.updates.contains_key(&short_key) || cached_entries.contains_key(&short_key)
+                {
+                    continue;
+                }
+                keys.push(self.get_index_key(&short_key));
+                let context = self
+                    .context
+                    .clone_with_base_key(self.get_subview_key(&short_key));
+                keys.extend(W::pre_load(&context)?);
+            }
+        }
+        if !keys.is_empty() {
+            self.context.prefetch(keys);
+        }
+        Ok(())
+    }
+
     /// Loads multiple entries for writing at once.
-    /// The entries in `short_keys` have to be all distinct.
+    /// The values of the entries missing from memory are read from storage with a
+    /// single query. The entries in `short_keys` have to be all distinct, otherwise
+    /// [`ViewError::DuplicateCollectionEntry`] is returned.
     /// ```rust
     /// # tokio_test::block_on(async {
     /// # use linera_views::context::MemoryContext;
@@ -523,6 +691,14 @@ where
         &mut self,
         short_keys: Vec<Vec<u8>>,
     ) -> Result<Vec<WriteGuardedView<W>>, ViewError> {
+        // A second write lock on the same entry could never be acquired, so the
+        // request is rejected before anything is staged.
+        let mut distinct_keys = BTreeSet::new();
+        for short_key in &short_keys {
+            if !distinct_keys.insert(short_key) {
+                return Err(ViewError::DuplicateCollectionEntry(short_key.clone()));
+            }
+        }
         let cached_entries = self.cached_entries.get_mut().unwrap();
         let mut short_keys_to_load = Vec::new();
         let mut keys = Vec::new();
@@ -868,7 +1044,8 @@ where
         Ok(keys)
     }
 
-    /// Returns the number of indices of the collection.
+    /// Returns the number of indices of the collection. This uses the stored number of
+    /// entries and only looks up the indices modified since the last flush.
     /// ```rust
     /// # tokio_test::block_on(async {
     /// # use linera_views::context::MemoryContext;
@@ -884,13 +1061,13 @@ where
     /// # })
     /// ```
     pub async fn count(&self) -> Result<usize, ViewError> {
-        let mut count = 0;
-        self.for_each_key(|_key| {
-            count += 1;
-            Ok(())
-        })
-        .await?;
-        Ok(count)
+        self.counter
+            .count(
+                &self.index_context(),
+                self.delete_storage_first,
+                &self.updates,
+            )
+            .await
     }
 
     /// Applies a function f on each index (aka key). Keys are visited in a
@@ -1125,7 +1302,9 @@ where
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -1136,6 +1315,10 @@ where
         self.collection.has_pending_changes().await
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.collection.pre_save().await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         self.collection.flush(batch)
     }
@@ -1196,6 +1379,42 @@ where
         self.collection.try_load_entry_mut(&short_key).await
     }
 
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection and `init` is applied
+    /// to it. The obtained view can then be modified.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::reentrant_collection_view::ReentrantCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: ReentrantCollectionView<_, u64, RegisterView<_, String>> =
+    ///     ReentrantCollectionView::load(context).await.unwrap();
+    /// let init = |subview: &mut RegisterView<_, String>| {
+    ///     subview.set(String::from("Hello"));
+    ///     Ok(())
+    /// };
+    /// let subview = view.try_load_entry_or_insert(&23, init).await.unwrap();
+    /// assert_eq!(subview.get(), "Hello");
+    /// # })
+    /// ```
+    pub async fn try_load_entry_or_insert<Q, F>(
+        &mut self,
+        index: &Q,
+        init: F,
+    ) -> Result<WriteGuardedView<W>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.collection
+            .try_load_entry_or_insert(&short_key, init)
+            .await
+    }
+
     /// Loads a subview at the given index in the collection and gives read-only access to the data.
     /// If an entry is absent then `None` is returned.
     /// ```rust
@@ -1392,6 +1611,37 @@ where
         self.collection.try_load_entries(short_keys).await
     }
 
+    /// Hints that the entries with the given indices are about to be loaded, so that the
+    /// store can start reading them in the background.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::reentrant_collection_view::ReentrantCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let view: ReentrantCollectionView<_, u64, RegisterView<_, String>> =
+    ///     ReentrantCollectionView::load(context).await.unwrap();
+    /// view.prefetch_entries(&[23, 42]).unwrap();
+    /// let subviews = view.try_load_entries(&[23, 42]).await.unwrap();
+    /// assert!(subviews[0].is_none());
+    /// # })
+    /// ```
+    pub fn prefetch_entries<'a, Q>(
+        &'a self,
+        indices: impl IntoIterator<Item = &'a Q>,
+    ) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + 'a,
+    {
+        let short_keys = indices
+            .into_iter()
+            .map(|index| BaseKey::derive_short_key(index))
+            .collect::<Result<_, _>>()?;
+        self.collection.prefetch_entries(short_keys)
+    }
+
     /// Loads all entries for writing at once.
     /// The entries in indices have to be all distinct.
     /// ```rust
@@ -1642,7 +1892,9 @@ where
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -1653,6 +1905,10 @@ where
         self.collection.has_pending_changes().await
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.collection.pre_save().await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
         self.collection.flush(batch)
     }
@@ -1712,6 +1968,42 @@ where
         self.collection.try_load_entry_mut(&short_key).await
     }
 
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection and `init` is applied
+    /// to it. The obtained view can then be modified.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::reentrant_collection_view::ReentrantCustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view: ReentrantCustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     ReentrantCustomCollectionView::load(context).await.unwrap();
+    /// let init = |subview: &mut RegisterView<_, String>| {
+    ///     subview.set(String::from("Hello"));
+    ///     Ok(())
+    /// };
+    /// let subview = view.try_load_entry_or_insert(&23, init).await.unwrap();
+    /// assert_eq!(subview.get(), "Hello");
+    /// # })
+    /// ```
+    pub async fn try_load_entry_or_insert<Q, F>(
+        &mut self,
+        index: &Q,
+        init: F,
+    ) -> Result<WriteGuardedView<W>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        F: FnOnce(&mut W) -> Result<(), ViewError>,
+    {
+        let short_key = index.to_custom_bytes()?;
+        self.collection
+            .try_load_entry_or_insert(&short_key, init)
+            .await
+    }
+
     /// Loads a subview at the given index in the collection and gives read-only access to the data.
     /// If an entry is absent then `None` is returned.
     /// ```rust
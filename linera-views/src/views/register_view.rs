@@ -4,6 +4,7 @@
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
 
+use linera_base::data_types::{Amount, ArithmeticError, BlockHeight, TimeDelta};
 use serde::{de::DeserializeOwned, Serialize};
 #[cfg(with_metrics)]
 use {
@@ -201,6 +202,69 @@ where
         }
     }
 
+    /// Modifies the value in the register in place.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = RegisterView::load(context).await.unwrap();
+    /// register.set(vec![1]);
+    /// register.update(|value| value.push(2));
+    /// assert_eq!(*register.get(), vec![1, 2]);
+    /// # })
+    /// ```
+    pub fn update<F: FnOnce(&mut T)>(&mut self, f: F) {
+        f(self.get_mut())
+    }
+
+    /// Sets the value in the register and returns the previous one.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = RegisterView::<_, u32>::load(context).await.unwrap();
+    /// assert_eq!(register.replace(5), 0);
+    /// assert_eq!(register.replace(7), 5);
+    /// assert_eq!(*register.get(), 7);
+    /// # })
+    /// ```
+    pub fn replace(&mut self, value: T) -> T {
+        self.delete_storage_first = false;
+        match self.update.replace(Box::new(value)) {
+            Some(previous) => *previous,
+            None => (*self.stored_value).clone(),
+        }
+    }
+
+    /// Sets the value in the register if it is currently equal to `expected`. Returns
+    /// whether the value was set.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = RegisterView::<_, u32>::load(context).await.unwrap();
+    /// assert!(!register.compare_and_set(&1, 5));
+    /// assert!(register.compare_and_set(&0, 5));
+    /// assert_eq!(*register.get(), 5);
+    /// # })
+    /// ```
+    pub fn compare_and_set(&mut self, expected: &T, value: T) -> bool
+    where
+        T: PartialEq,
+    {
+        if self.get() != expected {
+            return false;
+        }
+        self.set(value);
+        true
+    }
+
     fn compute_hash(&self) -> Result<<sha3::Sha3_256 as Hasher>::Output, ViewError> {
         #[cfg(with_metrics)]
         let _hash_latency = REGISTER_VIEW_HASH_RUNTIME.measure_latency();
@@ -210,6 +274,99 @@ where
     }
 }
 
+/// The numbers supporting the checked arithmetic of [`RegisterView::fetch_add`] and
+/// [`RegisterView::checked_sub`].
+pub trait CheckedNumber: Sized {
+    /// Adds `other`, or fails if the result overflows.
+    fn try_add(self, other: Self) -> Result<Self, ArithmeticError>;
+
+    /// Subtracts `other`, or fails if the result underflows.
+    fn try_sub(self, other: Self) -> Result<Self, ArithmeticError>;
+}
+
+macro_rules! impl_checked_number {
+    ($($name:ty),*) => {
+        $(
+            impl CheckedNumber for $name {
+                fn try_add(self, other: Self) -> Result<Self, ArithmeticError> {
+                    self.checked_add(other).ok_or(ArithmeticError::Overflow)
+                }
+
+                fn try_sub(self, other: Self) -> Result<Self, ArithmeticError> {
+                    self.checked_sub(other).ok_or(ArithmeticError::Underflow)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_number!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_checked_wrapped_number {
+    ($($name:ty),*) => {
+        $(
+            impl CheckedNumber for $name {
+                fn try_add(self, other: Self) -> Result<Self, ArithmeticError> {
+                    <$name>::try_add(self, other)
+                }
+
+                fn try_sub(self, other: Self) -> Result<Self, ArithmeticError> {
+                    <$name>::try_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_wrapped_number!(Amount, BlockHeight, TimeDelta);
+
+impl<C, T> RegisterView<C, T>
+where
+    C: Context,
+    T: CheckedNumber + Clone + Serialize,
+{
+    /// Adds `delta` to the value in the register and returns the previous value. The
+    /// register is left unchanged if the addition overflows.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = RegisterView::<_, u8>::load(context).await.unwrap();
+    /// assert_eq!(register.fetch_add(200).unwrap(), 0);
+    /// assert!(register.fetch_add(100).is_err());
+    /// assert_eq!(*register.get(), 200);
+    /// # })
+    /// ```
+    pub fn fetch_add(&mut self, delta: T) -> Result<T, ViewError> {
+        let previous = self.get().clone();
+        self.set(previous.clone().try_add(delta)?);
+        Ok(previous)
+    }
+
+    /// Subtracts `delta` from the value in the register and returns the new value. The
+    /// register is left unchanged if the subtraction underflows.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = RegisterView::<_, u8>::load(context).await.unwrap();
+    /// register.set(10);
+    /// assert_eq!(register.checked_sub(4).unwrap(), 6);
+    /// assert!(register.checked_sub(7).is_err());
+    /// assert_eq!(*register.get(), 6);
+    /// # })
+    /// ```
+    pub fn checked_sub(&mut self, delta: T) -> Result<T, ViewError> {
+        let value = self.get().clone().try_sub(delta)?;
+        self.set(value.clone());
+        Ok(value)
+    }
+}
+
 impl<C, T> HashableView<C> for RegisterView<C, T>
 where
     C: Context + Send + Sync,
@@ -3,7 +3,14 @@
 
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
-use std::{borrow::Borrow, collections::BTreeMap, marker::PhantomData, mem};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::{btree_map, BTreeMap, VecDeque},
+    iter::Peekable,
+    marker::PhantomData,
+    mem,
+};
 
 use serde::{de::DeserializeOwned, Serialize};
 #[cfg(with_metrics)]
@@ -16,10 +23,10 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{CountLocation, CustomSerialize, EntryCounter, HasherOutput, Update, KEY_PAGE_SIZE},
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
-    store::{KeyIterable, ReadableKeyValueStore as _},
+    store::{KeyIterable, ReadableKeyValueStore},
     views::{ClonableView, HashableView, Hasher, View, ViewError},
 };
 
@@ -40,6 +47,9 @@ pub struct ByteSetView<C> {
     context: C,
     delete_storage_first: bool,
     updates: BTreeMap<Vec<u8>, Update<()>>,
+    /// Whether the empty key holds the number of entries of the [`SetView`] wrapping this
+    /// view, and is therefore not an entry.
+    empty_key_is_count: bool,
 }
 
 impl<C> View<C> for ByteSetView<C>
@@ -62,6 +72,7 @@ where
             context,
             delete_storage_first: false,
             updates: BTreeMap::new(),
+            empty_key_is_count: false,
         })
     }
 
@@ -122,6 +133,7 @@ where
             context: self.context.clone(),
             delete_storage_first: self.delete_storage_first,
             updates: self.updates.clone(),
+            empty_key_is_count: self.empty_key_is_count,
         })
     }
 }
@@ -280,52 +292,80 @@ where
     where
         F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
     {
-        let mut updates = self.updates.iter();
-        let mut update = updates.next();
-        if !self.delete_storage_first {
-            let base = &self.context.base_key().bytes;
-            for index in self
-                .context
-                .store()
-                .find_keys_by_prefix(base)
-                .await?
-                .iterator()
-            {
-                let index = index?;
-                loop {
-                    match update {
-                        Some((key, value)) if key.as_slice() <= index => {
-                            if let Update::Set(_) = value {
-                                if !f(key)? {
-                                    return Ok(());
-                                }
-                            }
-                            update = updates.next();
-                            if key == index {
-                                break;
-                            }
-                        }
-                        _ => {
-                            if !f(index)? {
-                                return Ok(());
-                            }
-                            break;
-                        }
-                    }
-                }
+        let stored_keys = self.stored_keys().await?;
+        let keys = MergedKeys::new(
+            stored_keys.as_ref().map(|keys| keys.iterator()),
+            &self.updates,
+            self.empty_key_is_count,
+        )?;
+        for key in keys {
+            if !f(key?)? {
+                break;
             }
         }
-        while let Some((key, value)) = update {
-            if let Update::Set(_) = value {
-                if !f(key)? {
-                    return Ok(());
-                }
+        Ok(())
+    }
+
+    /// Applies a function f on each key of the set, along with whether `other` contains
+    /// it. Keys are visited in lexicographic order, and both sets are traversed only
+    /// once, loading their keys from storage in pages as the traversal advances. If the
+    /// function returns false, then the loop ends prematurely.
+    async fn for_each_key_with_membership_while<C2, F>(
+        &self,
+        other: &ByteSetView<C2>,
+        mut f: F,
+    ) -> Result<(), ViewError>
+    where
+        C2: Context,
+        ViewError: From<C2::Error>,
+        F: FnMut(&[u8], bool) -> Result<bool, ViewError>,
+    {
+        let mut keys = PagedKeys::new(self, KEY_PAGE_SIZE);
+        let mut other_keys = PagedKeys::new(other, KEY_PAGE_SIZE);
+        let mut other_key = other_keys.next().await?;
+        while let Some(key) = keys.next().await? {
+            while other_key.as_ref().is_some_and(|other_key| *other_key < key) {
+                other_key = other_keys.next().await?;
+            }
+            if !f(&key, other_key.as_ref() == Some(&key))? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a function f on each key of the set, like
+    /// [`ByteSetView::for_each_key_while`], but loading the keys from storage in pages of
+    /// `page_size` keys, only when they are needed. This bounds the number of keys read
+    /// beyond the one where the loop ends.
+    pub(crate) async fn for_each_key_in_pages_while<F>(
+        &self,
+        page_size: usize,
+        mut f: F,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError>,
+    {
+        let mut keys = PagedKeys::new(self, page_size.max(1));
+        while let Some(key) = keys.next().await? {
+            if !f(&key)? {
+                break;
             }
-            update = updates.next();
         }
         Ok(())
     }
 
+    /// Returns the keys in storage, unless they are to be deleted.
+    async fn stored_keys(
+        &self,
+    ) -> Result<Option<<C::Store as ReadableKeyValueStore>::Keys>, ViewError> {
+        if self.delete_storage_first {
+            return Ok(None);
+        }
+        let base = &self.context.base_key().bytes;
+        Ok(Some(self.context.store().find_keys_by_prefix(base).await?))
+    }
+
     /// Applies a function f on each serialized index (aka key). Keys are visited in a
     /// lexicographic order.
     /// ```rust
@@ -359,6 +399,166 @@ where
     }
 }
 
+/// The keys of a [`ByteSetView`] in lexicographic order, merging the keys in storage with
+/// the pending updates.
+struct MergedKeys<'a, S> {
+    stored_keys: Option<S>,
+    next_stored_key: Option<&'a [u8]>,
+    updates: Peekable<btree_map::Iter<'a, Vec<u8>, Update<()>>>,
+}
+
+impl<'a, S, E> MergedKeys<'a, S>
+where
+    S: Iterator<Item = Result<&'a [u8], E>>,
+    ViewError: From<E>,
+{
+    /// Merges the `stored_keys` with the `updates`. If `skip_empty_key` is set, the empty
+    /// key in storage holds the number of entries and is skipped.
+    fn new(
+        stored_keys: Option<S>,
+        updates: &'a BTreeMap<Vec<u8>, Update<()>>,
+        skip_empty_key: bool,
+    ) -> Result<Self, ViewError> {
+        let mut keys = MergedKeys {
+            stored_keys,
+            next_stored_key: None,
+            updates: updates.iter().peekable(),
+        };
+        keys.advance_stored_keys()?;
+        // The empty key comes first, if present.
+        if skip_empty_key && keys.next_stored_key.is_some_and(<[u8]>::is_empty) {
+            keys.advance_stored_keys()?;
+        }
+        Ok(keys)
+    }
+
+    fn advance_stored_keys(&mut self) -> Result<(), ViewError> {
+        self.next_stored_key = match self.stored_keys.as_mut().and_then(Iterator::next) {
+            Some(key) => Some(key?),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+impl<'a, S, E> Iterator for MergedKeys<'a, S>
+where
+    S: Iterator<Item = Result<&'a [u8], E>>,
+    ViewError: From<E>,
+{
+    type Item = Result<&'a [u8], ViewError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.next_stored_key, self.updates.peek()) {
+                (None, None) => return None,
+                (Some(stored_key), None) => {
+                    return Some(self.advance_stored_keys().map(|()| stored_key));
+                }
+                (Some(stored_key), Some((key, _))) if stored_key < key.as_slice() => {
+                    return Some(self.advance_stored_keys().map(|()| stored_key));
+                }
+                (stored_key, Some(_)) => {
+                    let (key, update) = self.updates.next().unwrap();
+                    // The update overrides the stored key, if any.
+                    if stored_key == Some(key.as_slice()) {
+                        if let Err(error) = self.advance_stored_keys() {
+                            return Some(Err(error));
+                        }
+                    }
+                    if let Update::Set(()) = update {
+                        return Some(Ok(key));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The keys of a [`ByteSetView`] in lexicographic order, like [`MergedKeys`], but loading
+/// the keys in storage in pages, only when they are needed.
+struct PagedKeys<'a, C> {
+    context: &'a C,
+    /// The maximal number of keys loaded from storage at once.
+    page_size: usize,
+    /// The loaded keys from storage that were not visited yet, or `None` if the keys in
+    /// storage are to be deleted.
+    stored_keys: Option<VecDeque<Vec<u8>>>,
+    /// The last key loaded from storage, after which the next page starts.
+    last_stored_key: Option<Vec<u8>>,
+    /// Whether all the keys in storage were loaded.
+    all_loaded: bool,
+    /// Whether the empty key in storage holds the number of entries.
+    skip_empty_key: bool,
+    updates: Peekable<btree_map::Iter<'a, Vec<u8>, Update<()>>>,
+}
+
+impl<'a, C> PagedKeys<'a, C>
+where
+    C: Context,
+    ViewError: From<C::Error>,
+{
+    fn new(set: &'a ByteSetView<C>, page_size: usize) -> Self {
+        PagedKeys {
+            context: &set.context,
+            page_size,
+            stored_keys: (!set.delete_storage_first).then(VecDeque::new),
+            last_stored_key: None,
+            all_loaded: false,
+            skip_empty_key: set.empty_key_is_count,
+            updates: set.updates.iter().peekable(),
+        }
+    }
+
+    /// Loads the next page of keys from storage if all the loaded ones were visited.
+    async fn load_stored_keys(&mut self) -> Result<(), ViewError> {
+        let Some(stored_keys) = &mut self.stored_keys else {
+            return Ok(());
+        };
+        while stored_keys.is_empty() && !self.all_loaded {
+            let base = &self.context.base_key().bytes;
+            let page = self
+                .context
+                .store()
+                .find_keys_by_prefix_after(base, self.last_stored_key.as_deref(), self.page_size)
+                .await?;
+            self.all_loaded = page.len() < self.page_size;
+            // The empty key comes first, if present.
+            let skip = self.skip_empty_key
+                && self.last_stored_key.is_none()
+                && page.first().is_some_and(Vec::is_empty);
+            self.last_stored_key = page.last().cloned();
+            stored_keys.extend(page.into_iter().skip(usize::from(skip)));
+        }
+        Ok(())
+    }
+
+    /// Returns the next key of the set, if any.
+    async fn next(&mut self) -> Result<Option<Vec<u8>>, ViewError> {
+        loop {
+            self.load_stored_keys().await?;
+            let next_stored_key = self.stored_keys.as_ref().and_then(VecDeque::front);
+            let ordering = match (next_stored_key, self.updates.peek()) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(stored_key), Some((key, _))) => stored_key.cmp(key),
+            };
+            if ordering != Ordering::Greater {
+                let stored_key = self.stored_keys.as_mut().and_then(VecDeque::pop_front);
+                if ordering == Ordering::Less {
+                    return Ok(stored_key);
+                }
+            }
+            // The update overrides the stored key, if any.
+            let (key, update) = self.updates.next().unwrap();
+            if let Update::Set(()) = update {
+                return Ok(Some(key.clone()));
+            }
+        }
+    }
+}
+
 impl<C> HashableView<C> for ByteSetView<C>
 where
     C: Context + Send + Sync,
@@ -387,9 +587,19 @@ where
 }
 
 /// A [`View`] implementing the set functionality with the index `I` being any serializable type.
+///
+/// The number of entries is stored along with them, at the base key itself, so that
+/// [`SetView::count`] does not need to iterate over the keys. Indices must therefore not
+/// have an empty serialization, unless they are zero-sized, e.g. `()`: there is then at
+/// most one entry, and the count is not persisted.
+///
+/// Indices are visited in the lexicographic order of their BCS serialization, which is not
+/// always the order of `I`: integers are serialized in little-endian, so that `256_u32`
+/// comes before `1_u32`.
 #[derive(Debug)]
 pub struct SetView<C, I> {
     set: ByteSetView<C>,
+    counter: EntryCounter,
     _phantom: PhantomData<I>,
 }
 
@@ -399,26 +609,40 @@ where
     ViewError: From<C::Error>,
     I: Send + Sync + Serialize,
 {
-    const NUM_INIT_KEYS: usize = ByteSetView::<C>::NUM_INIT_KEYS;
+    const NUM_INIT_KEYS: usize = 1 + ByteSetView::<C>::NUM_INIT_KEYS;
 
     fn context(&self) -> &C {
         self.set.context()
     }
 
     fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
-        ByteSetView::<C>::pre_load(context)
+        let mut v = vec![context.base_key().bytes.clone()];
+        v.extend(ByteSetView::<C>::pre_load(context)?);
+        Ok(v)
     }
 
     fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
-        let set = ByteSetView::post_load(context, values)?;
+        let location = CountLocation::for_index::<I>();
+        let counter = EntryCounter::post_load(
+            values.first().ok_or(ViewError::PostLoadValuesError)?,
+            location,
+        )?;
+        let mut set = ByteSetView::post_load(
+            context,
+            values.get(1..).ok_or(ViewError::PostLoadValuesError)?,
+        )?;
+        set.empty_key_is_count = location == CountLocation::EmptyKey;
         Ok(Self {
             set,
+            counter,
             _phantom: PhantomData,
         })
     }
 
     async fn load(context: C) -> Result<Self, ViewError> {
-        Self::post_load(context, &[])
+        let keys = Self::pre_load(&context)?;
+        let values = context.store().read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
     }
 
     fn rollback(&mut self) {
@@ -429,8 +653,23 @@ where
         self.set.has_pending_changes().await
     }
 
+    async fn pre_save(&mut self) -> Result<(), ViewError> {
+        self.counter
+            .pre_save(
+                self.set.context(),
+                self.set.delete_storage_first,
+                &self.set.updates,
+            )
+            .await
+    }
+
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
-        self.set.flush(batch)
+        let cleared = self.set.delete_storage_first;
+        let count = self.counter.known_count(cleared, &self.set.updates);
+        let delete_view = self.set.flush(batch)?;
+        let key = self.set.context().base_key().bytes.clone();
+        self.counter.flush(batch, key, cleared, count)?;
+        Ok(delete_view)
     }
 
     fn clear(&mut self) {
@@ -447,6 +686,7 @@ where
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
         Ok(SetView {
             set: self.set.clone_unchecked()?,
+            counter: self.counter.clone(),
             _phantom: PhantomData,
         })
     }
@@ -475,11 +715,30 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.set.insert(short_key);
         Ok(())
     }
 
+    /// Inserts all the given values.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::set_view::SetView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, u32>::load(context).await.unwrap();
+    /// set.extend([34, 37, 34]).unwrap();
+    /// assert_eq!(set.indices().await.unwrap(), vec![34, 37]);
+    /// # })
+    /// ```
+    pub fn extend(&mut self, indices: impl IntoIterator<Item = I>) -> Result<(), ViewError> {
+        for index in indices {
+            self.insert(&index)?;
+        }
+        Ok(())
+    }
+
     /// Removes a value. If absent then nothing is done.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -496,7 +755,7 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.set.remove(short_key);
         Ok(())
     }
@@ -530,7 +789,7 @@ where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
     {
-        let short_key = BaseKey::derive_short_key(index)?;
+        let short_key = self.counter.derive_short_key(index)?;
         self.set.contains(&short_key).await
     }
 }
@@ -562,7 +821,9 @@ where
         Ok(indices)
     }
 
-    /// Returns the number of entries in the set.
+    /// Returns the number of entries in the set. This uses the stored number of entries
+    /// and only looks up the keys modified since the last flush. If no number is stored,
+    /// as for sets saved before it was maintained, it is rebuilt from the keys in storage.
     /// ```rust
     /// # tokio_test::block_on(async {
     /// # use linera_views::{context::MemoryContext, set_view::SetView};
@@ -574,7 +835,35 @@ where
     /// # })
     /// ```
     pub async fn count(&self) -> Result<usize, ViewError> {
-        self.set.count().await
+        self.counter
+            .count(
+                self.set.context(),
+                self.set.delete_storage_first,
+                &self.set.updates,
+            )
+            .await
+    }
+
+    /// Rebuilds the stored number of entries by iterating over the keys in storage.
+    /// The result is persisted by the next flush.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::SetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set: SetView<_, u32> = SetView::load(context).await.unwrap();
+    /// set.insert(&(34 as u32));
+    /// assert_eq!(set.recount().await.unwrap(), 1);
+    /// # })
+    /// ```
+    pub async fn recount(&mut self) -> Result<usize, ViewError> {
+        self.counter
+            .recount(
+                self.set.context(),
+                self.set.delete_storage_first,
+                &self.set.updates,
+            )
+            .await
     }
 
     /// Applies a function f on each index. Indices are visited in an order
@@ -649,6 +938,122 @@ where
     }
 }
 
+impl<C, I> SetView<C, I>
+where
+    C: Context,
+    ViewError: From<C::Error>,
+    I: Serialize + DeserializeOwned,
+{
+    /// Removes the values that are not in `other`. Both sets are traversed once, in the
+    /// order of the serialized indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::set_view::SetView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, u32>::load(context).await.unwrap();
+    /// set.extend([34, 37, 42]).unwrap();
+    /// let context = MemoryContext::new_for_testing(());
+    /// let mut other = SetView::<_, u32>::load(context).await.unwrap();
+    /// other.extend([37, 42, 55]).unwrap();
+    /// set.retain_in(&other).await.unwrap();
+    /// assert_eq!(set.indices().await.unwrap(), vec![37, 42]);
+    /// # })
+    /// ```
+    pub async fn retain_in<C2>(&mut self, other: &SetView<C2, I>) -> Result<(), ViewError>
+    where
+        C2: Context,
+        ViewError: From<C2::Error>,
+    {
+        let mut removed_keys = Vec::new();
+        self.set
+            .for_each_key_with_membership_while(&other.set, |key, in_other| {
+                if !in_other {
+                    removed_keys.push(key.to_vec());
+                }
+                Ok(true)
+            })
+            .await?;
+        for key in removed_keys {
+            self.set.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Returns the values that are also in `other`, in the order of the serialized
+    /// indices. Both sets are traversed once, and only the common values are
+    /// deserialized.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::set_view::SetView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, u32>::load(context).await.unwrap();
+    /// set.extend([34, 37, 42]).unwrap();
+    /// let context = MemoryContext::new_for_testing(());
+    /// let mut other = SetView::<_, u32>::load(context).await.unwrap();
+    /// other.extend([37, 42, 55]).unwrap();
+    /// assert_eq!(
+    ///     set.intersection_indices(&other).await.unwrap(),
+    ///     vec![37, 42]
+    /// );
+    /// # })
+    /// ```
+    pub async fn intersection_indices<C2>(
+        &self,
+        other: &SetView<C2, I>,
+    ) -> Result<Vec<I>, ViewError>
+    where
+        C2: Context,
+        ViewError: From<C2::Error>,
+    {
+        let mut indices = Vec::new();
+        self.set
+            .for_each_key_with_membership_while(&other.set, |key, in_other| {
+                if in_other {
+                    indices.push(BaseKey::deserialize_value(key)?);
+                }
+                Ok(true)
+            })
+            .await?;
+        Ok(indices)
+    }
+
+    /// Returns true if all the values of the set are in `other`. The traversal stops at
+    /// the first value missing from `other`.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::set_view::SetView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, u32>::load(context).await.unwrap();
+    /// set.extend([37, 42]).unwrap();
+    /// let context = MemoryContext::new_for_testing(());
+    /// let mut other = SetView::<_, u32>::load(context).await.unwrap();
+    /// other.extend([37, 42, 55]).unwrap();
+    /// assert!(set.is_subset_of(&other).await.unwrap());
+    /// assert!(!other.is_subset_of(&set).await.unwrap());
+    /// # })
+    /// ```
+    pub async fn is_subset_of<C2>(&self, other: &SetView<C2, I>) -> Result<bool, ViewError>
+    where
+        C2: Context,
+        ViewError: From<C2::Error>,
+    {
+        let mut is_subset = true;
+        self.set
+            .for_each_key_with_membership_while(&other.set, |_key, in_other| {
+                is_subset = in_other;
+                Ok(in_other)
+            })
+            .await?;
+        Ok(is_subset)
+    }
+}
+
 impl<C, I> HashableView<C> for SetView<C, I>
 where
     C: Context + Send + Sync,
@@ -0,0 +1,531 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `SortedMapView` implements a map whose entries are ordered by the order of their keys.
+//!
+//! Unlike the [`MapView`][class1], whose entries are ordered by their serialized keys, the
+//! keys of a [`SortedMapView`][class2] are stored with an order-preserving encoding given
+//! by the [`SortedKey`] trait. This makes it possible to query the entries within a range of
+//! keys, or the smallest and largest entries, as with a `BTreeMap`.
+//!
+//! [class1]: crate::map_view::MapView
+//! [class2]: sorted_map_view::SortedMapView
+
+use std::{marker::PhantomData, ops::Range};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    batch::Batch,
+    common::HasherOutput,
+    context::Context,
+    hashable_wrapper::WrappedHashableContainerView,
+    map_view::ByteMapView,
+    views::{ClonableView, HashableView, Hasher, View, ViewError},
+};
+
+/// A key whose encoding preserves its order: for all keys `a` and `b`, `a < b` if and only
+/// if `a.to_sorted_bytes()` is lexicographically smaller than `b.to_sorted_bytes()`.
+pub trait SortedKey: Ord + Sized {
+    /// Encodes the key.
+    fn to_sorted_bytes(&self) -> Vec<u8>;
+
+    /// Decodes a key encoded by [`SortedKey::to_sorted_bytes`].
+    fn from_sorted_bytes(bytes: &[u8]) -> Result<Self, ViewError>;
+}
+
+/// Unsigned integers are encoded in big-endian order.
+macro_rules! impl_sorted_key_for_unsigned {
+    ($($int:ty),*) => {
+        $(
+            impl SortedKey for $int {
+                fn to_sorted_bytes(&self) -> Vec<u8> {
+                    self.to_be_bytes().to_vec()
+                }
+
+                fn from_sorted_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+                    let bytes = bytes
+                        .try_into()
+                        .map_err(|_| ViewError::InconsistentEntries)?;
+                    Ok(<$int>::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+/// Signed integers are encoded in big-endian order with the sign bit flipped, so that
+/// negative numbers come before positive ones.
+macro_rules! impl_sorted_key_for_signed {
+    ($($int:ty),*) => {
+        $(
+            impl SortedKey for $int {
+                fn to_sorted_bytes(&self) -> Vec<u8> {
+                    (*self ^ <$int>::MIN).to_be_bytes().to_vec()
+                }
+
+                fn from_sorted_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+                    let bytes = bytes
+                        .try_into()
+                        .map_err(|_| ViewError::InconsistentEntries)?;
+                    Ok(<$int>::from_be_bytes(bytes) ^ <$int>::MIN)
+                }
+            }
+        )*
+    };
+}
+
+impl_sorted_key_for_unsigned!(u8, u16, u32, u64, u128);
+impl_sorted_key_for_signed!(i8, i16, i32, i64, i128);
+
+/// Byte strings are stored as they are, which preserves their lexicographic order.
+impl SortedKey for Vec<u8> {
+    fn to_sorted_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_sorted_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// A map view whose entries are ordered by their keys and that supports range queries.
+#[derive(Debug)]
+pub struct SortedMapView<C, K, V> {
+    map: ByteMapView<C, V>,
+    _phantom: PhantomData<K>,
+}
+
+impl<C, K, V> View<C> for SortedMapView<C, K, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    K: Send + Sync + SortedKey,
+    V: Send + Sync + Serialize,
+{
+    const NUM_INIT_KEYS: usize = ByteMapView::<C, V>::NUM_INIT_KEYS;
+
+    fn context(&self) -> &C {
+        self.map.context()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        ByteMapView::<C, V>::pre_load(context)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let map = ByteMapView::post_load(context, values)?;
+        Ok(SortedMapView {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        Self::post_load(context, &[])
+    }
+
+    fn rollback(&mut self) {
+        self.map.rollback()
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.map.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        self.map.flush(batch)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+impl<C, K, V> ClonableView<C> for SortedMapView<C, K, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    K: Send + Sync + SortedKey,
+    V: Clone + Send + Sync + Serialize,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(SortedMapView {
+            map: self.map.clone_unchecked()?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<C, K, V> SortedMapView<C, K, V>
+where
+    C: Context + Sync,
+    ViewError: From<C::Error>,
+    K: SortedKey,
+{
+    /// Inserts or resets the value of a key.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u32, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&24, String::from("Hello"));
+    /// assert_eq!(map.get(&24).await.unwrap(), Some(String::from("Hello")));
+    /// # })
+    /// ```
+    pub fn insert(&mut self, key: &K, value: V) {
+        self.map.insert(key.to_sorted_bytes(), value);
+    }
+
+    /// Removes a key. If absent then nothing is done.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u32, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&37, String::from("Hello"));
+    /// map.remove(&37);
+    /// assert_eq!(map.get(&37).await.unwrap(), None);
+    /// # })
+    /// ```
+    pub fn remove(&mut self, key: &K) {
+        self.map.remove(key.to_sorted_bytes());
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.map.extra()
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, i64, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&-37, String::from("Hello"));
+    /// assert!(map.contains_key(&-37).await.unwrap());
+    /// assert!(!map.contains_key(&37).await.unwrap());
+    /// # })
+    /// ```
+    pub async fn contains_key(&self, key: &K) -> Result<bool, ViewError> {
+        self.map.contains_key(&key.to_sorted_bytes()).await
+    }
+}
+
+impl<C, K, V> SortedMapView<C, K, V>
+where
+    C: Context + Sync,
+    ViewError: From<C::Error>,
+    K: Send + SortedKey,
+    V: Clone + Send + Serialize + DeserializeOwned + 'static,
+{
+    /// Reads the value of a key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u32, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&37, String::from("Hello"));
+    /// assert_eq!(map.get(&37).await.unwrap(), Some(String::from("Hello")));
+    /// assert_eq!(map.get(&34).await.unwrap(), None);
+    /// # })
+    /// ```
+    pub async fn get(&self, key: &K) -> Result<Option<V>, ViewError> {
+        self.map.get(&key.to_sorted_bytes()).await
+    }
+
+    /// Obtains a mutable reference to the value of a key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u32, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&37, String::from("Hello"));
+    /// *map.get_mut(&37).await.unwrap().unwrap() = String::from("Hola");
+    /// assert_eq!(map.get(&37).await.unwrap(), Some(String::from("Hola")));
+    /// # })
+    /// ```
+    pub async fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, ViewError> {
+        self.map.get_mut(&key.to_sorted_bytes()).await
+    }
+
+    /// Returns the entries whose keys are in the given range, in increasing order of keys.
+    ///
+    /// Only the keys sharing the common prefix of the encodings of the range bounds are
+    /// visited.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, i32, _> = SortedMapView::load(context).await.unwrap();
+    /// for price in [-10, 5, 20, 300] {
+    ///     map.insert(&price, price * 2);
+    /// }
+    /// assert_eq!(map.range(0..300).await.unwrap(), vec![(5, 10), (20, 40)]);
+    /// assert_eq!(map.range(-10..-5).await.unwrap(), vec![(-10, -20)]);
+    /// assert!(map.range(20..20).await.unwrap().is_empty());
+    /// # })
+    /// ```
+    pub async fn range(&self, range: Range<K>) -> Result<Vec<(K, V)>, ViewError> {
+        let start = range.start.to_sorted_bytes();
+        let end = range.end.to_sorted_bytes();
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        // Every key in the range starts with the common prefix of its bounds.
+        let prefix_len = start
+            .iter()
+            .zip(&end)
+            .take_while(|(start_byte, end_byte)| start_byte == end_byte)
+            .count();
+        let prefix = start[..prefix_len].to_vec();
+        let mut entries = Vec::new();
+        self.map
+            .for_each_key_value_while(
+                |suffix, value| {
+                    let mut key = prefix.clone();
+                    key.extend_from_slice(suffix);
+                    if key >= end {
+                        return Ok(false);
+                    }
+                    if key >= start {
+                        entries.push((K::from_sorted_bytes(&key)?, value.into_owned()));
+                    }
+                    Ok(true)
+                },
+                prefix.clone(),
+            )
+            .await?;
+        Ok(entries)
+    }
+
+    /// Returns the entry with the smallest key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u64, _> = SortedMapView::load(context).await.unwrap();
+    /// assert_eq!(map.first_key_value().await.unwrap(), None);
+    /// map.insert(&256, String::from("Hello"));
+    /// map.insert(&3, String::from("Hola"));
+    /// assert_eq!(
+    ///     map.first_key_value().await.unwrap(),
+    ///     Some((3, String::from("Hola")))
+    /// );
+    /// # })
+    /// ```
+    pub async fn first_key_value(&self) -> Result<Option<(K, V)>, ViewError> {
+        let mut first = None;
+        self.map
+            .for_each_key_value_while(
+                |key, value| {
+                    first = Some((K::from_sorted_bytes(key)?, value.into_owned()));
+                    Ok(false)
+                },
+                Vec::new(),
+            )
+            .await?;
+        Ok(first)
+    }
+
+    /// Returns the entry with the largest key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u64, _> = SortedMapView::load(context).await.unwrap();
+    /// assert_eq!(map.last_key_value().await.unwrap(), None);
+    /// map.insert(&256, String::from("Hello"));
+    /// map.insert(&3, String::from("Hola"));
+    /// assert_eq!(
+    ///     map.last_key_value().await.unwrap(),
+    ///     Some((256, String::from("Hello")))
+    /// );
+    /// # })
+    /// ```
+    pub async fn last_key_value(&self) -> Result<Option<(K, V)>, ViewError> {
+        let mut last_key = None;
+        self.map
+            .for_each_key(
+                |key| {
+                    last_key = Some(key.to_vec());
+                    Ok(())
+                },
+                Vec::new(),
+            )
+            .await?;
+        let Some(last_key) = last_key else {
+            return Ok(None);
+        };
+        let value = self
+            .map
+            .get(&last_key)
+            .await?
+            .ok_or(ViewError::InconsistentEntries)?;
+        Ok(Some((K::from_sorted_bytes(&last_key)?, value)))
+    }
+
+    /// Removes and returns the entry with the smallest key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u64, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&256, String::from("Hello"));
+    /// map.insert(&3, String::from("Hola"));
+    /// assert_eq!(
+    ///     map.pop_first().await.unwrap(),
+    ///     Some((3, String::from("Hola")))
+    /// );
+    /// assert_eq!(map.count().await.unwrap(), 1);
+    /// # })
+    /// ```
+    pub async fn pop_first(&mut self) -> Result<Option<(K, V)>, ViewError> {
+        let first = self.first_key_value().await?;
+        if let Some((key, _)) = &first {
+            self.remove(key);
+        }
+        Ok(first)
+    }
+
+    /// Returns all the entries, in increasing order of keys.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, i8, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&1, String::from("Hello"));
+    /// map.insert(&-1, String::from("Hola"));
+    /// assert_eq!(
+    ///     map.key_values().await.unwrap(),
+    ///     vec![(-1, String::from("Hola")), (1, String::from("Hello"))]
+    /// );
+    /// # })
+    /// ```
+    pub async fn key_values(&self) -> Result<Vec<(K, V)>, ViewError> {
+        let mut entries = Vec::new();
+        self.map
+            .for_each_key_value(
+                |key, value| {
+                    entries.push((K::from_sorted_bytes(key)?, value.into_owned()));
+                    Ok(())
+                },
+                Vec::new(),
+            )
+            .await?;
+        Ok(entries)
+    }
+
+    /// Obtains the number of entries in the map.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::sorted_map_view::SortedMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: SortedMapView<_, u32, _> = SortedMapView::load(context).await.unwrap();
+    /// map.insert(&1, String::from("Hello"));
+    /// map.insert(&2, String::from("Hola"));
+    /// assert_eq!(map.count().await.unwrap(), 2);
+    /// # })
+    /// ```
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.map.count().await
+    }
+}
+
+impl<C, K, V> HashableView<C> for SortedMapView<C, K, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    K: Send + Sync + SortedKey,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.map.hash_mut().await
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.map.hash().await
+    }
+}
+
+/// Type wrapping `SortedMapView` while memoizing the hash.
+pub type HashedSortedMapView<C, K, V> =
+    WrappedHashableContainerView<C, SortedMapView<C, K, V>, HasherOutput>;
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use rand::{distributions::Standard, prelude::Distribution, Rng};
+
+    use super::SortedKey;
+
+    fn check_order_preservation<K>()
+    where
+        K: SortedKey + Copy + Debug,
+        Standard: Distribution<K>,
+    {
+        let mut rng = crate::random::make_deterministic_rng();
+        for _ in 0..1000 {
+            let key1 = rng.gen::<K>();
+            let key2 = rng.gen::<K>();
+            let bytes1 = key1.to_sorted_bytes();
+            let bytes2 = key2.to_sorted_bytes();
+            assert_eq!(key1.cmp(&key2), bytes1.cmp(&bytes2), "{key1:?} vs {key2:?}");
+            assert_eq!(K::from_sorted_bytes(&bytes1).unwrap(), key1);
+        }
+    }
+
+    #[test]
+    fn test_integer_order_preservation() {
+        check_order_preservation::<u8>();
+        check_order_preservation::<u16>();
+        check_order_preservation::<u32>();
+        check_order_preservation::<u64>();
+        check_order_preservation::<u128>();
+        check_order_preservation::<i8>();
+        check_order_preservation::<i16>();
+        check_order_preservation::<i32>();
+        check_order_preservation::<i64>();
+        check_order_preservation::<i128>();
+    }
+
+    #[test]
+    fn test_signed_integer_bounds() {
+        let keys = [i64::MIN, -1, 0, 1, i64::MAX];
+        for pair in keys.windows(2) {
+            assert!(pair[0].to_sorted_bytes() < pair[1].to_sorted_bytes());
+        }
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        assert!(u32::from_sorted_bytes(&[0, 1]).is_err());
+        assert!(i16::from_sorted_bytes(&[0, 1, 2]).is_err());
+    }
+}
@@ -0,0 +1,533 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `TtlMapView` implements a map whose entries expire after a deadline.
+//!
+//! Every entry is stored with its expiry time, and entries whose expiry time is not after
+//! the current timestamp are treated as absent. The current timestamp is obtained from
+//! the extra data of the context, which has to implement [`TimestampProvider`].
+//!
+//! Expired entries still occupy storage until they are removed by
+//! [`TtlMapView::purge_expired`], which visits the entries in the order of their expiry
+//! times and can be bounded so that the cleanup fits in the execution of a block.
+//!
+//! ```rust
+//! # tokio_test::block_on(async {
+//! # use linera_base::data_types::Timestamp;
+//! # use linera_views::context::MemoryContext;
+//! # use linera_views::ttl_map_view::TtlMapView;
+//! # use linera_views::views::View;
+//! # let context = MemoryContext::new_for_testing(Timestamp::from(100));
+//! let mut sessions = TtlMapView::<_, String, u64>::load(context).await.unwrap();
+//! sessions
+//!     .insert_with_ttl("alice", 1, Timestamp::from(150))
+//!     .unwrap();
+//! sessions
+//!     .insert_with_ttl("bob", 2, Timestamp::from(50))
+//!     .unwrap();
+//! assert_eq!(sessions.get("alice").await.unwrap(), Some(1));
+//! assert_eq!(sessions.get("bob").await.unwrap(), None);
+//! assert_eq!(
+//!     sessions
+//!         .purge_expired(Timestamp::from(100), 10)
+//!         .await
+//!         .unwrap(),
+//!     1
+//! );
+//! # })
+//! ```
+
+use std::{borrow::Borrow, marker::PhantomData};
+
+use linera_base::data_types::Timestamp;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    batch::Batch,
+    common::KEY_PAGE_SIZE,
+    context::{BaseKey, Context},
+    map_view::ByteMapView,
+    set_view::ByteSetView,
+    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+};
+
+/// The extra data of a context providing the timestamp of the block being executed.
+pub trait TimestampProvider {
+    /// Returns the current time, against which the expiry of entries is checked.
+    fn timestamp(&self) -> Timestamp;
+}
+
+impl TimestampProvider for Timestamp {
+    fn timestamp(&self) -> Timestamp {
+        *self
+    }
+}
+
+/// Key tags to create the sub-keys of a `TtlMapView` on top of the base key.
+#[repr(u8)]
+enum KeyTag {
+    /// Prefix for the entries, along with their expiry time.
+    Entries = MIN_VIEW_TAG,
+    /// Prefix for the index of the entries by expiry time.
+    Expiries,
+}
+
+/// The length of the big-endian expiry time at the start of the keys of the expiry index.
+const EXPIRY_LENGTH: usize = 8;
+
+/// A map view whose entries are removed from reads once their expiry time has passed.
+///
+/// The expiry index is not updated when an entry is overwritten or removed, so that these
+/// operations do not need to read storage: the outdated index entries are dropped by
+/// [`TtlMapView::purge_expired`].
+#[derive(Debug)]
+pub struct TtlMapView<C, K, V> {
+    entries: ByteMapView<C, (Timestamp, V)>,
+    expiries: ByteSetView<C>,
+    _phantom: PhantomData<K>,
+}
+
+impl<C, K, V> View<C> for TtlMapView<C, K, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    K: Send + Sync,
+    V: Send + Sync + Serialize,
+{
+    const NUM_INIT_KEYS: usize =
+        ByteMapView::<C, (Timestamp, V)>::NUM_INIT_KEYS + ByteSetView::<C>::NUM_INIT_KEYS;
+
+    fn context(&self) -> &C {
+        self.entries.context()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        let (entries_context, expiries_context) = Self::sub_contexts(context);
+        let mut keys = ByteMapView::<C, (Timestamp, V)>::pre_load(&entries_context)?;
+        keys.extend(ByteSetView::<C>::pre_load(&expiries_context)?);
+        Ok(keys)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let (entries_context, expiries_context) = Self::sub_contexts(&context);
+        let split = ByteMapView::<C, (Timestamp, V)>::NUM_INIT_KEYS;
+        let entries = ByteMapView::post_load(
+            entries_context,
+            values.get(..split).ok_or(ViewError::PostLoadValuesError)?,
+        )?;
+        let expiries = ByteSetView::post_load(
+            expiries_context,
+            values.get(split..).ok_or(ViewError::PostLoadValuesError)?,
+        )?;
+        Ok(Self {
+            entries,
+            expiries,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        let (entries_context, expiries_context) = Self::sub_contexts(&context);
+        Ok(Self {
+            entries: ByteMapView::load(entries_context).await?,
+            expiries: ByteSetView::load(expiries_context).await?,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.entries.rollback();
+        self.expiries.rollback();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.entries.has_pending_changes().await || self.expiries.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let entries_deleted = self.entries.flush(batch)?;
+        let expiries_deleted = self.expiries.flush(batch)?;
+        Ok(entries_deleted && expiries_deleted)
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.expiries.clear();
+    }
+}
+
+impl<C, K, V> ClonableView<C> for TtlMapView<C, K, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    K: Send + Sync,
+    V: Clone + Send + Sync + Serialize,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(TtlMapView {
+            entries: self.entries.clone_unchecked()?,
+            expiries: self.expiries.clone_unchecked()?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<C, K, V> TtlMapView<C, K, V>
+where
+    C: Context,
+{
+    fn sub_contexts(context: &C) -> (C, C) {
+        let entries_key = context.base_key().base_tag(KeyTag::Entries as u8);
+        let expiries_key = context.base_key().base_tag(KeyTag::Expiries as u8);
+        (
+            context.clone_with_base_key(entries_key),
+            context.clone_with_base_key(expiries_key),
+        )
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.entries.extra()
+    }
+}
+
+/// Returns the key of the expiry index for the entry at `short_key`.
+fn expiry_key(expires_at: Timestamp, short_key: &[u8]) -> Vec<u8> {
+    let mut key = expires_at.micros().to_be_bytes().to_vec();
+    key.extend_from_slice(short_key);
+    key
+}
+
+/// Splits a key of the expiry index into the expiry time and the key of the entry.
+fn split_expiry_key(key: &[u8]) -> Result<(Timestamp, &[u8]), ViewError> {
+    if key.len() < EXPIRY_LENGTH {
+        return Err(ViewError::InconsistentEntries);
+    }
+    let (expiry, short_key) = key.split_at(EXPIRY_LENGTH);
+    let micros = u64::from_be_bytes(expiry.try_into().expect("the length was checked"));
+    Ok((Timestamp::from(micros), short_key))
+}
+
+impl<C, K, V> TtlMapView<C, K, V>
+where
+    C: Context,
+    ViewError: From<C::Error>,
+    K: Serialize,
+{
+    /// Inserts or resets the value at `index`, expiring at `expires_at`.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::Timestamp;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::ttl_map_view::TtlMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(Timestamp::from(100));
+    /// let mut map = TtlMapView::<_, u32, String>::load(context).await.unwrap();
+    /// map.insert_with_ttl(&7, String::from("Hello"), Timestamp::from(200))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     map.expires_at(&7).await.unwrap(),
+    ///     Some(Timestamp::from(200))
+    /// );
+    /// # })
+    /// ```
+    pub fn insert_with_ttl<Q>(
+        &mut self,
+        index: &Q,
+        value: V,
+        expires_at: Timestamp,
+    ) -> Result<(), ViewError>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.expiries.insert(expiry_key(expires_at, &short_key));
+        self.entries.insert(short_key, (expires_at, value));
+        Ok(())
+    }
+
+    /// Removes the value at `index`, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::Timestamp;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::ttl_map_view::TtlMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(Timestamp::from(100));
+    /// let mut map = TtlMapView::<_, u32, String>::load(context).await.unwrap();
+    /// map.insert_with_ttl(&7, String::from("Hello"), Timestamp::from(200))
+    ///     .unwrap();
+    /// map.remove(&7).unwrap();
+    /// assert!(!map.contains_key(&7).await.unwrap());
+    /// # })
+    /// ```
+    pub fn remove<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.entries.remove(short_key);
+        Ok(())
+    }
+}
+
+impl<C, K, V> TtlMapView<C, K, V>
+where
+    C: Context + Sync,
+    C::Extra: TimestampProvider,
+    ViewError: From<C::Error>,
+    K: Serialize,
+    V: Clone + DeserializeOwned + 'static,
+{
+    /// Reads the entry at `index` with its expiry time, if it has not expired.
+    async fn get_live_entry<Q>(&self, index: &Q) -> Result<Option<(Timestamp, V)>, ViewError>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        let now = self.extra().timestamp();
+        Ok(self
+            .entries
+            .get(&short_key)
+            .await?
+            .filter(|(expires_at, _)| *expires_at > now))
+    }
+
+    /// Reads the value at `index`, if there is one and it has not expired.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::Timestamp;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::ttl_map_view::TtlMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(Timestamp::from(100));
+    /// let mut map = TtlMapView::<_, u32, String>::load(context).await.unwrap();
+    /// map.insert_with_ttl(&7, String::from("Hello"), Timestamp::from(200))
+    ///     .unwrap();
+    /// map.insert_with_ttl(&8, String::from("Bonjour"), Timestamp::from(100))
+    ///     .unwrap();
+    /// assert_eq!(map.get(&7).await.unwrap(), Some(String::from("Hello")));
+    /// assert_eq!(map.get(&8).await.unwrap(), None);
+    /// # })
+    /// ```
+    pub async fn get<Q>(&self, index: &Q) -> Result<Option<V>, ViewError>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        Ok(self.get_live_entry(index).await?.map(|(_, value)| value))
+    }
+
+    /// Returns the expiry time of the entry at `index`, if there is one and it has not
+    /// expired.
+    pub async fn expires_at<Q>(&self, index: &Q) -> Result<Option<Timestamp>, ViewError>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        Ok(self
+            .get_live_entry(index)
+            .await?
+            .map(|(expires_at, _)| expires_at))
+    }
+
+    /// Returns `true` if there is an entry at `index` that has not expired.
+    pub async fn contains_key<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        K: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        Ok(self.get_live_entry(index).await?.is_some())
+    }
+}
+
+impl<C, K, V> TtlMapView<C, K, V>
+where
+    C: Context + Sync,
+    C::Extra: TimestampProvider,
+    ViewError: From<C::Error>,
+    K: Serialize + DeserializeOwned + Send,
+    V: Clone + Sync + Send + Serialize + DeserializeOwned + 'static,
+{
+    /// Returns the indices of the entries that have not expired, in the order of their
+    /// serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::Timestamp;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::ttl_map_view::TtlMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(Timestamp::from(100));
+    /// let mut map = TtlMapView::<_, u32, String>::load(context).await.unwrap();
+    /// map.insert_with_ttl(&7, String::from("Hello"), Timestamp::from(200))
+    ///     .unwrap();
+    /// map.insert_with_ttl(&8, String::from("Bonjour"), Timestamp::from(100))
+    ///     .unwrap();
+    /// assert_eq!(map.indices().await.unwrap(), vec![7]);
+    /// # })
+    /// ```
+    pub async fn indices(&self) -> Result<Vec<K>, ViewError> {
+        let now = self.extra().timestamp();
+        let mut indices = Vec::new();
+        self.entries
+            .for_each_key_value(
+                |short_key, entry| {
+                    if entry.0 > now {
+                        indices.push(BaseKey::deserialize_value(short_key)?);
+                    }
+                    Ok(())
+                },
+                Vec::new(),
+            )
+            .await?;
+        Ok(indices)
+    }
+}
+
+impl<C, K, V> TtlMapView<C, K, V>
+where
+    C: Context + Sync,
+    ViewError: From<C::Error>,
+    V: Clone + DeserializeOwned + 'static,
+{
+    /// Removes the entries whose expiry time is not after `now`, visiting at most `limit`
+    /// entries of the expiry index, earliest first. Returns the number of entries
+    /// removed. The outdated index entries of overwritten or removed entries are dropped
+    /// as well, and count towards `limit`.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_base::data_types::Timestamp;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::ttl_map_view::TtlMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(Timestamp::from(0));
+    /// let mut map = TtlMapView::<_, u32, String>::load(context).await.unwrap();
+    /// for index in 0..5 {
+    ///     map.insert_with_ttl(
+    ///         &index,
+    ///         String::from("Hello"),
+    ///         Timestamp::from(10 * index as u64),
+    ///     )
+    ///     .unwrap();
+    /// }
+    /// assert_eq!(map.purge_expired(Timestamp::from(30), 2).await.unwrap(), 2);
+    /// assert_eq!(map.purge_expired(Timestamp::from(30), 2).await.unwrap(), 2);
+    /// assert_eq!(map.purge_expired(Timestamp::from(30), 2).await.unwrap(), 0);
+    /// # })
+    /// ```
+    pub async fn purge_expired(
+        &mut self,
+        now: Timestamp,
+        limit: usize,
+    ) -> Result<usize, ViewError> {
+        let mut expired = Vec::new();
+        if limit > 0 {
+            // The index is read in pages of at most `limit` keys, so that the keys expiring
+            // after `now` are not loaded beyond the first page that reaches them.
+            self.expiries
+                .for_each_key_in_pages_while(limit.min(KEY_PAGE_SIZE), |key| {
+                    let (expires_at, _) = split_expiry_key(key)?;
+                    if expires_at > now {
+                        return Ok(false);
+                    }
+                    expired.push(key.to_vec());
+                    Ok(expired.len() < limit)
+                })
+                .await?;
+        }
+        let mut purged = 0;
+        for key in expired {
+            let (expires_at, short_key) = split_expiry_key(&key)?;
+            // The entry may have been overwritten with another expiry time, or removed.
+            if let Some((entry_expires_at, _)) = self.entries.get(short_key).await? {
+                if entry_expires_at == expires_at {
+                    self.entries.remove(short_key.to_vec());
+                    purged += 1;
+                }
+            }
+            self.expiries.remove(key);
+        }
+        Ok(purged)
+    }
+}
+
+impl<C, K, V> HashableView<C> for TtlMapView<C, K, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    K: Send + Sync,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let mut hasher = sha3::Sha3_256::default();
+        hasher.update_with_bytes(&self.entries.hash_mut().await?)?;
+        hasher.update_with_bytes(&self.expiries.hash_mut().await?)?;
+        Ok(hasher.finalize())
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let mut hasher = sha3::Sha3_256::default();
+        hasher.update_with_bytes(&self.entries.hash().await?)?;
+        hasher.update_with_bytes(&self.expiries.hash().await?)?;
+        Ok(hasher.finalize())
+    }
+}
+
+#[cfg(with_graphql)]
+mod graphql {
+    use std::borrow::Cow;
+
+    use super::{TimestampProvider, TtlMapView};
+    use crate::{
+        context::Context,
+        graphql::{hash_name, mangle},
+    };
+
+    impl<C: Send + Sync, K: async_graphql::OutputType, V: async_graphql::OutputType>
+        async_graphql::TypeName for TtlMapView<C, K, V>
+    {
+        fn type_name() -> Cow<'static, str> {
+            format!(
+                "TtlMapView_{}_{}_{:08x}",
+                mangle(K::type_name()),
+                mangle(V::type_name()),
+                hash_name::<(K, V)>(),
+            )
+            .into()
+        }
+    }
+
+    #[async_graphql::Object(cache_control(no_cache), name_type)]
+    impl<C, K, V> TtlMapView<C, K, V>
+    where
+        C: Context + Send + Sync,
+        C::Extra: TimestampProvider,
+        K: async_graphql::OutputType
+            + async_graphql::InputType
+            + serde::ser::Serialize
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync,
+        V: async_graphql::OutputType
+            + serde::ser::Serialize
+            + serde::de::DeserializeOwned
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+    {
+        #[graphql(derived(name = "indices"))]
+        async fn indices_(&self) -> Result<Vec<K>, async_graphql::Error> {
+            Ok(self.indices().await?)
+        }
+
+        async fn value(&self, key: K) -> Result<Option<V>, async_graphql::Error> {
+            Ok(self.get(&key).await?)
+        }
+    }
+}
@@ -3,6 +3,9 @@
 
 use std::{collections::VecDeque, fmt::Debug, marker::PhantomData};
 
+use assert_matches::assert_matches;
+use futures::TryStreamExt as _;
+use linera_base::data_types::{BlockHeight, Timestamp};
 use serde::{de::DeserializeOwned, Serialize};
 use test_case::test_case;
 
@@ -16,16 +19,22 @@ use crate::scylla_db::ScyllaDbStore;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::Batch,
+    collection_view::CollectionView,
     context::{Context, MemoryContext},
+    historical_register_view::HistoricalRegisterView,
+    log_view::LogView,
+    map_view::{ByteMapView, MapView},
     queue_view::QueueView,
     reentrant_collection_view::ReentrantCollectionView,
     register_view::{HashedRegisterView, RegisterView},
-    store::WritableKeyValueStore as _,
+    set_view::SetView,
+    store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
     test_utils::test_views::{
         TestBucketQueueView, TestCollectionView, TestLogView, TestMapView, TestQueueView,
         TestRegisterView, TestSetView, TestView,
     },
-    views::{HashableView, View, ViewError},
+    ttl_map_view::TtlMapView,
+    views::{HashableView, View, ViewError, MIN_VIEW_TAG},
 };
 #[cfg(any(with_rocksdb, with_scylladb, with_dynamodb))]
 use crate::{context::ViewContext, random::generate_test_namespace, store::AdminKeyValueStore};
@@ -56,6 +65,7 @@ async fn test_queue_operations_with_scylla_db_context() -> Result<(), anyhow::Er
 #[derive(Clone, Copy, Debug)]
 pub enum Operation {
     DeleteFront,
+    DeleteFrontCount(usize),
     PushBack(usize),
     CommitAndReload,
 }
@@ -112,6 +122,34 @@ where
             PushBack(3),
             DeleteFront,
         ],
+        vec![DeleteFrontCount(0), PushBack(1), DeleteFrontCount(5)],
+        vec![
+            PushBack(1),
+            PushBack(2),
+            PushBack(3),
+            DeleteFrontCount(2),
+            PushBack(4),
+        ],
+        // Deleting across the boundary between stored and staged values.
+        vec![
+            PushBack(1),
+            PushBack(2),
+            PushBack(3),
+            PushBack(4),
+            DeleteFrontCount(3),
+            PushBack(5),
+            PushBack(6),
+            DeleteFrontCount(2),
+        ],
+        vec![
+            PushBack(1),
+            PushBack(2),
+            DeleteFront,
+            PushBack(3),
+            PushBack(4),
+            DeleteFrontCount(10),
+            PushBack(5),
+        ],
     ];
 
     for test_case in test_cases {
@@ -151,6 +189,10 @@ where
                 queue.delete_front();
                 expected_state.pop_front();
             }
+            Operation::DeleteFrontCount(count) => {
+                queue.delete_front_count(count);
+                expected_state.drain(..count.min(expected_state.len()));
+            }
             Operation::CommitAndReload => {
                 save_view(&context, &mut queue).await?;
                 queue = QueueView::load(context.clone()).await?;
@@ -179,6 +221,24 @@ where
 
     check_contents(queue.read_front(count).await?, expected_state);
     check_contents(queue.read_back(count).await?, expected_state);
+    check_contents(queue.read_front(count + 1).await?, expected_state);
+    check_contents(queue.read_back(count + 1).await?, expected_state);
+    for batch_size in [1, 2, count.max(1)] {
+        check_contents(
+            queue.stream_elements(batch_size).try_collect().await?,
+            expected_state,
+        );
+    }
+
+    for partial_count in 0..count {
+        let front = expected_state.range(..partial_count).copied().collect();
+        let back = expected_state
+            .range((count - partial_count)..)
+            .copied()
+            .collect();
+        check_contents(queue.read_front(partial_count).await?, &front);
+        check_contents(queue.read_back(partial_count).await?, &back);
+    }
 
     Ok(())
 }
@@ -187,6 +247,674 @@ fn check_contents(contents: Vec<usize>, expected: &VecDeque<usize>) {
     assert_eq!(&contents.into_iter().collect::<VecDeque<_>>(), expected);
 }
 
+/// Checks that bulk deletions after clearing or loading the whole queue only remove the
+/// staged values.
+#[tokio::test]
+async fn test_queue_delete_front_count_with_staged_values() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut queue = QueueView::load(context.clone()).await?;
+    queue.push_back(1_usize);
+    queue.push_back(2);
+    queue.push_back(3);
+    save_view(&context, &mut queue).await?;
+
+    let mut queue = QueueView::load(context.clone()).await?;
+    queue.clear();
+    queue.push_back(4);
+    queue.push_back(5);
+    queue.delete_front_count(1);
+    assert_eq!(queue.elements().await?, vec![5]);
+    save_view(&context, &mut queue).await?;
+    let mut queue = QueueView::<_, usize>::load(context.clone()).await?;
+    assert_eq!(queue.elements().await?, vec![5]);
+
+    queue.push_back(6);
+    queue.push_back(7);
+    for value in queue.iter_mut().await? {
+        *value *= 10;
+    }
+    queue.delete_front_count(2);
+    assert_eq!(queue.elements().await?, vec![70]);
+    save_view(&context, &mut queue).await?;
+    let queue = QueueView::<_, usize>::load(context).await?;
+    assert_eq!(queue.elements().await?, vec![70]);
+    assert_eq!(
+        queue.stream_elements(1).try_collect::<Vec<_>>().await?,
+        vec![70]
+    );
+
+    Ok(())
+}
+
+/// Checks range reads on a [`LogView`] mixing persisted and staged entries.
+#[tokio::test]
+async fn test_log_range_reads_with_staged_values() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut log = LogView::load(context.clone()).await?;
+    assert!(log.read_last(3).await?.is_empty());
+    log.push(0_usize);
+    log.push(1);
+    log.push(2);
+    save_view(&context, &mut log).await?;
+
+    let mut log = LogView::load(context.clone()).await?;
+    log.push(3);
+    log.push(4);
+    assert_eq!(log.count(), 5);
+    assert_eq!(log.read_range(0..2).await?, vec![0, 1]);
+    assert_eq!(log.read_range(1..4).await?, vec![1, 2, 3]);
+    assert_eq!(log.read_range(3..5).await?, vec![3, 4]);
+    assert_eq!(log.read_range(2..100).await?, vec![2, 3, 4]);
+    assert!(log.read_range(4..2).await?.is_empty());
+    assert!(log.read_range(5..8).await?.is_empty());
+    assert_eq!(log.read_last(0).await?, Vec::<usize>::new());
+    assert_eq!(log.read_last(1).await?, vec![4]);
+    assert_eq!(log.read_last(3).await?, vec![2, 3, 4]);
+    assert_eq!(log.read_last(10).await?, vec![0, 1, 2, 3, 4]);
+
+    // After clearing, only the newly staged entries are visible.
+    log.clear();
+    log.push(5);
+    assert_eq!(log.count(), 1);
+    assert_eq!(log.read_range(0..3).await?, vec![5]);
+    assert_eq!(log.read_last(2).await?, vec![5]);
+    save_view(&context, &mut log).await?;
+
+    let log = LogView::<_, usize>::load(context).await?;
+    assert_eq!(log.count(), 1);
+    assert_eq!(log.read_last(2).await?, vec![5]);
+
+    Ok(())
+}
+
+/// Checks that the number of entries of a [`MapView`] stays correct when overwriting
+/// or removing keys that are only in storage.
+#[tokio::test]
+async fn test_map_count_with_stored_values() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    map.insert(&1, 1)?;
+    map.insert(&2, 2)?;
+    map.insert(&3, 3)?;
+    save_view(&context, &mut map).await?;
+
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 3);
+    // Overwriting an existing key and removing an absent one leave the count unchanged.
+    map.insert(&1, 10)?;
+    map.remove(&4)?;
+    assert_eq!(map.count().await?, 3);
+    map.remove(&2)?;
+    map.insert(&5, 5)?;
+    map.insert(&6, 6)?;
+    assert_eq!(map.count().await?, 4);
+    save_view(&context, &mut map).await?;
+
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 4);
+    // The modified keys are looked up before flushing, so the stored count stays exact.
+    map.insert(&3, 30)?;
+    map.insert(&7, 7)?;
+    save_view(&context, &mut map).await?;
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 5);
+    assert_eq!(map.recount().await?, 5);
+
+    // Clearing does not need to look up anything.
+    map.clear();
+    map.insert(&8, 8)?;
+    save_view(&context, &mut map).await?;
+    let map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 1);
+    assert_eq!(map.indices().await?, vec![8]);
+
+    Ok(())
+}
+
+/// Checks that the entries of a [`MapView`] saved before it counted them are still readable,
+/// and that their count is rebuilt.
+#[tokio::test]
+async fn test_map_count_of_legacy_entries() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut legacy_map = ByteMapView::<_, u8>::load(context.clone()).await?;
+    for index in 1..=3u8 {
+        legacy_map.insert(bcs::to_bytes(&index)?, 10 * index);
+    }
+    save_view(&context, &mut legacy_map).await?;
+
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    assert_eq!(map.get(&2).await?, Some(20));
+    assert_eq!(map.indices().await?, vec![1, 2, 3]);
+    assert_eq!(map.count().await?, 3);
+    map.insert(&4, 40)?;
+    save_view(&context, &mut map).await?;
+
+    let map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 4);
+    assert_eq!(map.indices().await?, vec![1, 2, 3, 4]);
+    Ok(())
+}
+
+/// Checks that a [`MapView`] and a [`SetView`] with zero-sized indices count their only
+/// possible entry, which is stored at the empty key.
+#[tokio::test]
+async fn test_count_with_zero_sized_indices() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut map = MapView::<_, (), u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 0);
+    map.insert(&(), 1)?;
+    assert_eq!(map.count().await?, 1);
+    save_view(&context, &mut map).await?;
+
+    let mut map = MapView::<_, (), u8>::load(context.clone()).await?;
+    assert_eq!(map.get(&()).await?, Some(1));
+    assert_eq!(map.count().await?, 1);
+    map.remove(&())?;
+    save_view(&context, &mut map).await?;
+    let map = MapView::<_, (), u8>::load(context.clone()).await?;
+    assert_eq!(map.count().await?, 0);
+
+    let context = MemoryContext::new_for_testing(());
+    let mut set = SetView::<_, ()>::load(context.clone()).await?;
+    set.insert(&())?;
+    save_view(&context, &mut set).await?;
+    let set = SetView::<_, ()>::load(context.clone()).await?;
+    assert!(set.contains(&()).await?);
+    assert_eq!(set.count().await?, 1);
+    Ok(())
+}
+
+/// Checks that [`SetView::intersection_indices`] and [`SetView::retain_in`] traverse sets
+/// whose keys in storage span several pages.
+#[tokio::test]
+async fn test_set_intersection_across_pages() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut set = SetView::<_, u32>::load(context.clone()).await?;
+    set.extend(0..2500)?;
+    save_view(&context, &mut set).await?;
+    let other_context = MemoryContext::new_for_testing(());
+    let mut other = SetView::<_, u32>::load(other_context.clone()).await?;
+    other.extend((0..5000).step_by(2))?;
+    save_view(&other_context, &mut other).await?;
+
+    let mut set = SetView::<_, u32>::load(context.clone()).await?;
+    let other = SetView::<_, u32>::load(other_context).await?;
+    // Pending updates are merged with the keys in storage.
+    set.remove(&2)?;
+    set.insert(&4000)?;
+    let mut expected = (4..2500).step_by(2).chain([0, 4000]).collect::<Vec<_>>();
+    expected.sort_by_key(|value| bcs::to_bytes(value).unwrap());
+    assert_eq!(set.intersection_indices(&other).await?, expected);
+    assert!(!set.is_subset_of(&other).await?);
+    set.retain_in(&other).await?;
+    assert!(set.is_subset_of(&other).await?);
+    assert_eq!(set.indices().await?, expected);
+    Ok(())
+}
+
+/// Checks that a [`MapView`] rejects indices that are not zero-sized but have an empty
+/// serialization, since the empty key holds the number of entries.
+#[tokio::test]
+async fn test_map_rejects_empty_index_keys() -> Result<(), anyhow::Error> {
+    #[derive(Serialize)]
+    struct Unserialized(#[serde(skip)] u8);
+
+    let context = MemoryContext::new_for_testing(());
+    let mut map = MapView::<_, Unserialized, u8>::load(context).await?;
+    assert_matches!(map.insert(&Unserialized(1), 1), Err(ViewError::EmptyIndex));
+    assert_matches!(map.get(&Unserialized(1)).await, Err(ViewError::EmptyIndex));
+    assert_eq!(map.count().await?, 0);
+    Ok(())
+}
+
+/// Checks that [`CollectionView::contains_key`] and [`CollectionView::count`] account for
+/// staged removals of entries that are only in storage.
+#[tokio::test]
+async fn test_collection_count_with_staged_removals() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    for index in 1..=3 {
+        view.load_entry_mut(&index).await?.set(index);
+    }
+    save_view(&context, &mut view).await?;
+
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    view.remove_entry(&2)?;
+    view.remove_entry(&4)?;
+    assert!(!view.contains_key(&2).await?);
+    assert!(view.contains_key(&3).await?);
+    assert_eq!(view.count().await?, 2);
+    // Initializing a removed entry starts from an empty view.
+    let entry = view
+        .try_load_entry_or_insert(&2, |entry| {
+            assert_eq!(*entry.get(), 0);
+            entry.set(20);
+            Ok(())
+        })
+        .await?;
+    assert_eq!(*entry.get(), 20);
+    // Existing entries are not initialized again.
+    let entry = view
+        .try_load_entry_or_insert(&3, |_| unreachable!())
+        .await?;
+    assert_eq!(*entry.get(), 3);
+    view.remove_entry(&1)?;
+    assert_eq!(view.count().await?, 2);
+    save_view(&context, &mut view).await?;
+
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 2);
+    assert_eq!(view.indices().await?, vec![2, 3]);
+    view.clear();
+    view.load_entry_mut(&5).await?;
+    assert!(!view.contains_key(&2).await?);
+    assert_eq!(view.count().await?, 1);
+    save_view(&context, &mut view).await?;
+    let view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 1);
+
+    Ok(())
+}
+
+/// Checks that the count of a [`CollectionView`] is persisted once it was requested, and
+/// rebuilt for collections saved before it was maintained.
+#[tokio::test]
+async fn test_collection_count_without_stored_count() -> Result<(), anyhow::Error> {
+    // The key of the count of a collection at the root of the storage.
+    let count_key = vec![MIN_VIEW_TAG + 2];
+    let context = MemoryContext::new_for_testing(());
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    for index in 1..=3 {
+        view.load_entry_mut(&index).await?.set(index);
+    }
+    save_view(&context, &mut view).await?;
+    // Counting is opt-in: the count is only persisted after it was requested.
+    assert!(!context.store().contains_key(&count_key).await?);
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    save_view(&context, &mut view).await?;
+    assert!(context.store().contains_key(&count_key).await?);
+
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    view.remove_entry(&1)?;
+    view.load_entry_mut(&4).await?.set(4);
+    view.load_entry_mut(&5).await?.set(5);
+    save_view(&context, &mut view).await?;
+    let view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 4);
+
+    // Collections saved before the count was maintained have no count key.
+    let mut batch = Batch::new();
+    batch.delete_key(count_key.clone());
+    context.store().write_batch(batch).await?;
+    let mut view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 4);
+    view.remove_entry(&2)?;
+    save_view(&context, &mut view).await?;
+    assert!(context.store().contains_key(&count_key).await?);
+    let view = CollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    assert_eq!(view.indices().await?, vec![3, 4, 5]);
+
+    Ok(())
+}
+
+/// Checks that [`ReentrantCollectionView::count`] accounts for staged removals of entries
+/// that are only in storage.
+#[tokio::test]
+async fn test_reentrant_collection_count_with_staged_removals() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    populate_reentrant_collection_view(&mut view, [(1, 1), (2, 2), (3, 3)]).await?;
+    save_view(&context, &mut view).await?;
+
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    view.remove_entry(&2)?;
+    view.remove_entry(&4)?;
+    assert!(!view.contains_key(&2).await?);
+    assert_eq!(view.count().await?, 2);
+    {
+        let entry = view
+            .try_load_entry_or_insert(&2, |entry| {
+                entry.set(20);
+                Ok(())
+            })
+            .await?;
+        assert_eq!(*entry.get(), 20);
+    }
+    {
+        let entry = view
+            .try_load_entry_or_insert(&3, |_| unreachable!())
+            .await?;
+        assert_eq!(*entry.get(), 3);
+    }
+    assert_eq!(view.count().await?, 3);
+    save_view(&context, &mut view).await?;
+
+    let view = ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    assert_eq!(view.indices().await?, vec![1, 2, 3]);
+
+    Ok(())
+}
+
+/// Checks that the count of a [`ReentrantCollectionView`] saved before it was maintained is
+/// rebuilt when requested, and persisted again when saving.
+#[tokio::test]
+async fn test_reentrant_collection_count_without_stored_count() -> Result<(), anyhow::Error> {
+    // The key of the count of a collection at the root of the storage.
+    let count_key = vec![MIN_VIEW_TAG + 2];
+    let context = MemoryContext::new_for_testing(());
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    populate_reentrant_collection_view(&mut view, [(1, 1), (2, 2), (3, 3)]).await?;
+    assert_eq!(view.count().await?, 3);
+    save_view(&context, &mut view).await?;
+    assert!(context.store().contains_key(&count_key).await?);
+
+    let mut batch = Batch::new();
+    batch.delete_key(count_key.clone());
+    context.store().write_batch(batch).await?;
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    view.remove_entry(&1)?;
+    populate_reentrant_collection_view(&mut view, [(4, 4)]).await?;
+    save_view(&context, &mut view).await?;
+    assert!(!context.store().contains_key(&count_key).await?);
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    save_view(&context, &mut view).await?;
+    assert!(context.store().contains_key(&count_key).await?);
+    let view = ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_eq!(view.count().await?, 3);
+    assert_eq!(view.indices().await?, vec![2, 3, 4]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reentrant_collection_load_entries_mut() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    populate_reentrant_collection_view(&mut view, [(1, 1), (2, 2), (3, 3)]).await?;
+    save_view(&context, &mut view).await?;
+
+    let mut view =
+        ReentrantCollectionView::<_, u8, RegisterView<_, u8>>::load(context.clone()).await?;
+    assert_matches!(
+        view.try_load_entries_mut(&[1, 2, 1]).await,
+        Err(ViewError::DuplicateCollectionEntry(_))
+    );
+    assert!(!view.has_pending_changes().await);
+    {
+        let entries = view.try_load_entries_mut(&[3, 1, 4]).await?;
+        let values = entries.iter().map(|entry| *entry.get()).collect::<Vec<_>>();
+        assert_eq!(values, vec![3, 1, 0]);
+    }
+    assert_eq!(view.indices().await?, vec![1, 2, 3, 4]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_map_pagination_with_insertions() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    for index in [10, 20, 30, 40] {
+        map.insert(&index, index)?;
+    }
+    save_view(&context, &mut map).await?;
+
+    let mut map = MapView::<_, u8, u8>::load(context.clone()).await?;
+    let (indices, next) = map.keys_paginated(None, 2).await?;
+    assert_eq!(indices, vec![10, 20]);
+    assert_eq!(next, Some(20));
+    // Entries inserted before the continuation are skipped, later ones are included.
+    map.insert(&15, 15)?;
+    map.insert(&25, 25)?;
+    map.remove(&30)?;
+    let (index_values, next) = map.index_values_paginated(next.as_ref(), 2).await?;
+    assert_eq!(index_values, vec![(25, 25), (40, 40)]);
+    assert_eq!(next, None);
+    // The continuation does not need to be present in the map.
+    let (indices, next) = map.keys_paginated(Some(&30), 10).await?;
+    assert_eq!(indices, vec![40]);
+    assert_eq!(next, None);
+    Ok(())
+}
+
+#[cfg(with_merkle_map)]
+#[tokio::test]
+async fn test_map_membership_proofs() -> Result<(), anyhow::Error> {
+    use crate::merkle_proof::{verify_proof, MembershipProof};
+
+    let context = MemoryContext::new_for_testing(());
+    let mut map = MapView::<_, u16, u16>::load(context.clone()).await?;
+    for index in 0..100 {
+        map.insert(&index, 2 * index)?;
+    }
+    save_view(&context, &mut map).await?;
+
+    let mut map = MapView::<_, u16, u16>::load(context.clone()).await?;
+    // The proofs cover both stored and staged entries.
+    map.insert(&1000, 7)?;
+    map.remove(&3)?;
+    let root_hash = map.hash().await?;
+    assert!(map.hash_proof(&3).await?.is_none());
+    for (index, value) in [(0u16, 0u16), (57, 114), (99, 198), (1000, 7)] {
+        let proof = map.hash_proof(&index).await?.unwrap();
+        let proof = bcs::from_bytes::<MembershipProof>(&bcs::to_bytes(&proof)?)?;
+        let key_bytes = bcs::to_bytes(&index)?;
+        assert!(verify_proof(
+            &root_hash,
+            &key_bytes,
+            &bcs::to_bytes(&value)?,
+            &proof
+        ));
+        assert!(!verify_proof(
+            &root_hash,
+            &key_bytes,
+            &bcs::to_bytes(&(value + 1))?,
+            &proof
+        ));
+    }
+
+    // The hash only depends on the entries.
+    save_view(&context, &mut map).await?;
+    let map = MapView::<_, u16, u16>::load(context.clone()).await?;
+    assert_eq!(map.hash().await?, root_hash);
+    Ok(())
+}
+
+/// Checks that a [`TtlMapView`] hides expired entries and that purging them skips the
+/// outdated expiry times of overwritten and removed entries.
+#[tokio::test]
+async fn test_ttl_map_view_expiry_and_purge() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(Timestamp::from(0));
+    let at_time = |micros| {
+        crate::context::ViewContext::new_unsafe(
+            context.store().clone(),
+            Vec::new(),
+            Timestamp::from(micros),
+        )
+    };
+
+    let mut map = TtlMapView::<_, u8, String>::load(at_time(10)).await?;
+    map.insert_with_ttl(&1, "one".to_string(), Timestamp::from(20))?;
+    map.insert_with_ttl(&2, "two".to_string(), Timestamp::from(30))?;
+    map.insert_with_ttl(&3, "three".to_string(), Timestamp::from(40))?;
+    save_view(&context, &mut map).await?;
+
+    let mut map = TtlMapView::<_, u8, String>::load(at_time(30)).await?;
+    assert_eq!(map.get(&1).await?, None);
+    assert!(!map.contains_key(&2).await?);
+    assert_eq!(map.get(&3).await?, Some("three".to_string()));
+    assert_eq!(map.indices().await?, vec![3]);
+    // Extending the first entry leaves its previous expiry time in the index.
+    map.insert_with_ttl(&1, "one again".to_string(), Timestamp::from(50))?;
+    map.remove(&3)?;
+    assert_eq!(map.expires_at(&1).await?, Some(Timestamp::from(50)));
+    save_view(&context, &mut map).await?;
+
+    let mut map = TtlMapView::<_, u8, String>::load(at_time(30)).await?;
+    // The outdated index entry of the first entry counts towards the limit.
+    assert_eq!(map.purge_expired(Timestamp::from(30), 1).await?, 0);
+    assert_eq!(map.purge_expired(Timestamp::from(45), 10).await?, 1);
+    assert_eq!(map.get(&1).await?, Some("one again".to_string()));
+    save_view(&context, &mut map).await?;
+
+    let mut map = TtlMapView::<_, u8, String>::load(at_time(60)).await?;
+    assert!(map.indices().await?.is_empty());
+    assert_eq!(map.purge_expired(Timestamp::from(60), 10).await?, 1);
+    save_view(&context, &mut map).await?;
+    assert!(context.store().find_keys_by_prefix(&[]).await?.is_empty());
+
+    Ok(())
+}
+
+/// Checks that purging a [`TtlMapView`] in several calls, which read the expiry index in
+/// pages, visits the stored and the pending entries in the order of their expiry times.
+#[tokio::test]
+async fn test_ttl_map_view_purge_in_pages() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(Timestamp::from(0));
+    let mut map = TtlMapView::<_, u8, u8>::load(context.clone()).await?;
+    for index in 1..=10 {
+        map.insert_with_ttl(&index, index, Timestamp::from(u64::from(index) * 10))?;
+    }
+    save_view(&context, &mut map).await?;
+
+    let mut map = TtlMapView::<_, u8, u8>::load(context.clone()).await?;
+    map.insert_with_ttl(&11, 11, Timestamp::from(15))?;
+    map.insert_with_ttl(&12, 12, Timestamp::from(200))?;
+    assert_eq!(map.purge_expired(Timestamp::from(55), 2).await?, 2);
+    assert!(!map.contains_key(&1).await?);
+    assert!(!map.contains_key(&11).await?);
+    assert!(map.contains_key(&2).await?);
+    assert_eq!(map.purge_expired(Timestamp::from(55), 2).await?, 2);
+    assert_eq!(map.purge_expired(Timestamp::from(55), 10).await?, 2);
+    assert_eq!(map.purge_expired(Timestamp::from(55), 10).await?, 0);
+    save_view(&context, &mut map).await?;
+
+    let map = TtlMapView::<_, u8, u8>::load(context).await?;
+    assert_eq!(map.indices().await?, vec![6, 7, 8, 9, 10, 12]);
+    Ok(())
+}
+
+/// Checks that a [`HistoricalRegisterView`] records one value per block and prunes the
+/// oldest ones.
+#[tokio::test]
+async fn test_historical_register_view() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(BlockHeight(0));
+    let at_height = |height| {
+        crate::context::ViewContext::new_unsafe(
+            context.store().clone(),
+            Vec::new(),
+            BlockHeight(height),
+        )
+    };
+
+    // Several sets in the same block only record the last value.
+    let mut register = HistoricalRegisterView::<_, u32, 3>::load(at_height(1)).await?;
+    assert!(register.history(10).is_empty());
+    register.set(10);
+    register.set(11);
+    assert_eq!(register.history(10), vec![(BlockHeight(1), 11)]);
+    save_view(&context, &mut register).await?;
+
+    // A later flush in the same block replaces the value recorded for that block.
+    let mut register = HistoricalRegisterView::<_, u32, 3>::load(at_height(1)).await?;
+    assert_eq!(*register.get(), 11);
+    register.set(12);
+    save_view(&context, &mut register).await?;
+
+    for height in 2..5 {
+        let mut register = HistoricalRegisterView::<_, u32, 3>::load(at_height(height)).await?;
+        register.set(height as u32 * 10);
+        save_view(&context, &mut register).await?;
+    }
+
+    // Only the last three values are kept.
+    let mut register = HistoricalRegisterView::<_, u32, 3>::load(at_height(5)).await?;
+    assert_eq!(*register.get(), 40);
+    assert_eq!(
+        register.history(10),
+        vec![
+            (BlockHeight(2), 20),
+            (BlockHeight(3), 30),
+            (BlockHeight(4), 40)
+        ]
+    );
+    assert_eq!(register.history(1), vec![(BlockHeight(4), 40)]);
+    register.set(50);
+    assert_eq!(
+        register.history(3),
+        vec![
+            (BlockHeight(3), 30),
+            (BlockHeight(4), 40),
+            (BlockHeight(5), 50)
+        ]
+    );
+    register.rollback();
+    assert_eq!(*register.get(), 40);
+    assert_eq!(register.history(1), vec![(BlockHeight(4), 40)]);
+
+    // Clearing removes the value and its history.
+    register.clear();
+    assert_eq!(*register.get(), 0);
+    assert!(register.history(10).is_empty());
+    save_view(&context, &mut register).await?;
+    let register = HistoricalRegisterView::<_, u32, 3>::load(at_height(6)).await?;
+    assert_eq!(*register.get(), 0);
+    assert!(register.history(10).is_empty());
+
+    Ok(())
+}
+
+/// Checks the atomic update helpers of [`RegisterView`] on staged and persisted values.
+#[tokio::test]
+async fn test_register_update_helpers() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut register = RegisterView::<_, u64>::load(context.clone()).await?;
+    assert_eq!(register.fetch_add(10)?, 0);
+    assert_eq!(register.replace(20), 10);
+    save_view(&context, &mut register).await?;
+
+    let mut register = RegisterView::<_, u64>::load(context.clone()).await?;
+    // The previous value is the persisted one when nothing is staged.
+    assert_eq!(register.replace(30), 20);
+    register.rollback();
+    assert_eq!(*register.get(), 20);
+    assert!(!register.has_pending_changes().await);
+
+    // Failed operations stage nothing.
+    assert!(register.checked_sub(21).is_err());
+    assert!(register.fetch_add(u64::MAX).is_err());
+    assert!(!register.compare_and_set(&19, 0));
+    assert!(!register.has_pending_changes().await);
+
+    assert_eq!(register.checked_sub(5)?, 15);
+    register.update(|value| *value *= 2);
+    assert!(register.compare_and_set(&30, 31));
+    assert_eq!(register.fetch_add(1)?, 31);
+    register.rollback();
+    assert_eq!(*register.get(), 20);
+
+    // Clearing stages the default value.
+    register.clear();
+    assert_eq!(register.fetch_add(3)?, 0);
+    save_view(&context, &mut register).await?;
+    let register = RegisterView::<_, u64>::load(context.clone()).await?;
+    assert_eq!(*register.get(), 3);
+
+    Ok(())
+}
+
 trait TestContextFactory {
     type Context: Context<Error: Send + Sync> + Clone + Send + Sync + 'static;
 
@@ -507,6 +1235,7 @@ async fn save_view<C>(context: &C, view: &mut impl View<C>) -> anyhow::Result<()
 where
     C: Context<Error: Send + Sync>,
 {
+    view.pre_save().await?;
     let mut batch = Batch::new();
     view.flush(&mut batch)?;
     context.store().write_batch(batch).await?;
@@ -531,3 +1260,111 @@ where
 
     Ok(())
 }
+
+/// A register whose flush fails on demand, to check that views saved together are
+/// written all at once.
+struct FailingFlushView<C> {
+    register: RegisterView<C, u64>,
+    fail_flush: bool,
+}
+
+impl<C> View<C> for FailingFlushView<C>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    const NUM_INIT_KEYS: usize = RegisterView::<C, u64>::NUM_INIT_KEYS;
+
+    fn context(&self) -> &C {
+        self.register.context()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        RegisterView::<C, u64>::pre_load(context)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        Ok(Self {
+            register: RegisterView::post_load(context, values)?,
+            fail_flush: false,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        Ok(Self {
+            register: RegisterView::load(context).await?,
+            fail_flush: false,
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.register.rollback();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.register.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        if self.fail_flush {
+            return Err(ViewError::PostLoadValuesError);
+        }
+        self.register.flush(batch)
+    }
+
+    fn clear(&mut self) {
+        self.register.clear();
+    }
+}
+
+#[tokio::test]
+async fn test_save_all_is_atomic() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let hot_context = context.clone_with_base_key(vec![0]);
+    let archive_context = context.clone_with_base_key(vec![1]);
+
+    let mut hot = RegisterView::<_, u64>::load(hot_context.clone()).await?;
+    let mut archive = FailingFlushView::load(archive_context.clone()).await?;
+    hot.set(1);
+    archive.register.set(2);
+    crate::save_all!(hot, archive).await?;
+
+    let mut hot = RegisterView::<_, u64>::load(hot_context.clone()).await?;
+    let mut archive = FailingFlushView::load(archive_context.clone()).await?;
+    assert_eq!((*hot.get(), *archive.register.get()), (1, 2));
+    hot.set(10);
+    archive.register.set(20);
+    archive.fail_flush = true;
+    assert!(crate::save_all!(hot, archive).await.is_err());
+
+    // Neither view was persisted.
+    let hot = RegisterView::<_, u64>::load(hot_context.clone()).await?;
+    let archive = FailingFlushView::load(archive_context.clone()).await?;
+    assert_eq!((*hot.get(), *archive.register.get()), (1, 2));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save_all_rejects_overlapping_base_keys() -> Result<(), anyhow::Error> {
+    let context = MemoryContext::new_for_testing(());
+    let mut outer = RegisterView::<_, u64>::load(context.clone_with_base_key(vec![0])).await?;
+    let mut inner = RegisterView::<_, u64>::load(context.clone_with_base_key(vec![0, 1])).await?;
+    let mut other = RegisterView::<_, u64>::load(context.clone_with_base_key(vec![1])).await?;
+    outer.set(1);
+    inner.set(2);
+    other.set(3);
+
+    assert_matches!(
+        crate::save_all!(other, inner, outer).await,
+        Err(ViewError::OverlappingBaseKeys(..))
+    );
+    assert!(outer.has_pending_changes().await);
+    assert!(context.store().find_keys_by_prefix(&[]).await?.is_empty());
+
+    crate::save_all!(inner, other).await?;
+    assert!(!inner.has_pending_changes().await);
+    assert_eq!(context.store().find_keys_by_prefix(&[]).await?.len(), 2);
+
+    Ok(())
+}
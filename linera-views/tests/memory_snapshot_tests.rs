@@ -0,0 +1,118 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests of the snapshot files of the memory stores. They are kept apart from the other
+//! tests because loading a snapshot file replaces the data of the namespaces it contains
+//! for the whole process.
+
+use assert_matches::assert_matches;
+use linera_views::{
+    batch::Batch,
+    memory::{MemoryStore, MemoryStoreConfig, MemoryStoreError, TEST_MEMORY_MAX_STREAM_QUERIES},
+    store::{AdminKeyValueStore as _, ReadableKeyValueStore as _, WritableKeyValueStore as _},
+};
+
+async fn write_entries(store: &MemoryStore, entries: &[(&[u8], &[u8])]) {
+    let mut batch = Batch::new();
+    for (key, value) in entries {
+        batch.put_key_value_bytes(key.to_vec(), value.to_vec());
+    }
+    store.write_batch(batch).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_memory_store_snapshot_file_round_trip() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("linera.snapshot");
+
+    let first = MemoryStore::new(TEST_MEMORY_MAX_STREAM_QUERIES, "snapshot_first").unwrap();
+    let first_child = first.clone_with_root_key(&[1, 2]).unwrap();
+    let second = MemoryStore::new(TEST_MEMORY_MAX_STREAM_QUERIES, "snapshot_second").unwrap();
+    write_entries(&first, &[(b"a", b"1"), (b"b", b"2")]).await;
+    write_entries(&first_child, &[(b"a", b"child")]).await;
+    write_entries(&second, &[(b"c", &[0; 1000])]).await;
+    MemoryStore::dump_to_file(&path).unwrap();
+
+    // Changes made after the dump are discarded by the load.
+    write_entries(&first, &[(b"a", b"changed"), (b"z", b"new")]).await;
+    let new_child = first.clone_with_root_key(&[3]).unwrap();
+    write_entries(&new_child, &[(b"a", b"3")]).await;
+    let mut batch = Batch::new();
+    batch.delete_key(b"c".to_vec());
+    second.write_batch(batch).await.unwrap();
+
+    MemoryStore::load_from_file(&path).unwrap();
+
+    assert_eq!(
+        first.read_value_bytes(b"a").await.unwrap(),
+        Some(b"1".to_vec())
+    );
+    assert_eq!(
+        first.read_value_bytes(b"b").await.unwrap(),
+        Some(b"2".to_vec())
+    );
+    assert_eq!(first.read_value_bytes(b"z").await.unwrap(), None);
+    assert_eq!(
+        first_child.read_value_bytes(b"a").await.unwrap(),
+        Some(b"child".to_vec())
+    );
+    assert_eq!(new_child.read_value_bytes(b"a").await.unwrap(), None);
+    assert_eq!(
+        second.read_value_bytes(b"c").await.unwrap(),
+        Some(vec![0; 1000])
+    );
+
+    // Stores connected after the load see the loaded data as well.
+    let config = MemoryStoreConfig::new(TEST_MEMORY_MAX_STREAM_QUERIES);
+    let reconnected = MemoryStore::connect(&config, "snapshot_first")
+        .await
+        .unwrap()
+        .clone_with_root_key(&[1, 2])
+        .unwrap();
+    assert_eq!(
+        reconnected.read_value_bytes(b"a").await.unwrap(),
+        Some(b"child".to_vec())
+    );
+    let root_keys = MemoryStore::list_root_keys(&config, "snapshot_first")
+        .await
+        .unwrap();
+    assert!(root_keys.contains(&vec![1, 2]));
+}
+
+#[tokio::test]
+async fn test_invalid_memory_store_snapshot_files_are_rejected() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("linera.snapshot");
+    let store = MemoryStore::new(TEST_MEMORY_MAX_STREAM_QUERIES, "snapshot_invalid").unwrap();
+    write_entries(&store, &[(b"key", b"value")]).await;
+    MemoryStore::dump_to_file(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+
+    let truncated = &bytes[..bytes.len() - 1];
+    let mut corrupted = bytes.clone();
+    let middle = corrupted.len() / 2;
+    corrupted[middle] ^= 1;
+    let mut extended = bytes.clone();
+    extended.push(0);
+
+    let invalid_files = [
+        truncated,
+        corrupted.as_slice(),
+        extended.as_slice(),
+        &bytes[..4],
+        b"not a snapshot".as_slice(),
+    ];
+    for invalid in invalid_files {
+        std::fs::write(&path, invalid).unwrap();
+        assert_matches!(
+            MemoryStore::load_from_file(&path),
+            Err(MemoryStoreError::InvalidSnapshotFile(_))
+        );
+    }
+
+    std::fs::remove_file(&path).unwrap();
+    assert_matches!(
+        MemoryStore::load_from_file(&path),
+        Err(MemoryStoreError::SnapshotFileIo(_))
+    );
+}
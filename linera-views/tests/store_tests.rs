@@ -64,6 +64,23 @@ async fn test_reads_memory() {
     }
 }
 
+#[cfg(with_compression)]
+async fn new_compressed_memory_store() -> linera_views::compression::CompressedStore<MemoryStore> {
+    use linera_views::compression::{CompressedStore, ValueCompression};
+    let store = MemoryStore::new_test_store().await.unwrap();
+    let compression = ValueCompression::zstd(3).with_threshold(16);
+    CompressedStore::new(store, Some(compression))
+}
+
+#[cfg(with_compression)]
+#[tokio::test]
+async fn test_reads_compressed_memory() {
+    for scenario in get_random_test_scenarios() {
+        let store = new_compressed_memory_store().await;
+        run_reads(store, scenario).await;
+    }
+}
+
 #[cfg(with_rocksdb)]
 #[tokio::test]
 async fn test_reads_rocks_db() {
@@ -156,6 +173,13 @@ async fn test_memory_writes_from_blank() {
     run_writes_from_blank(&store).await;
 }
 
+#[cfg(with_compression)]
+#[tokio::test]
+async fn test_compressed_memory_writes_from_blank() {
+    let store = new_compressed_memory_store().await;
+    run_writes_from_blank(&store).await;
+}
+
 #[tokio::test]
 async fn test_key_value_store_view_memory_writes_from_blank() {
     let context = MemoryContext::new_for_testing(());
@@ -314,6 +338,215 @@ async fn test_rocks_db_writes_from_state() {
     run_writes_from_state(&store).await;
 }
 
+#[cfg(with_rocksdb)]
+async fn new_tuned_rocks_db_test_store(
+    tuning: linera_views::rocks_db::RocksDbTuning,
+) -> linera_views::rocks_db::RocksDbStore {
+    use linera_views::{
+        random::generate_test_namespace, rocks_db::RocksDbStore, store::AdminKeyValueStore as _,
+    };
+    let config = RocksDbStore::new_test_config()
+        .await
+        .unwrap()
+        .with_tuning(tuning);
+    RocksDbStore::recreate_and_connect(&config, &generate_test_namespace())
+        .await
+        .unwrap()
+}
+
+#[cfg(with_rocksdb)]
+fn column_families_tuning() -> linera_views::rocks_db::RocksDbTuning {
+    use linera_views::rocks_db::{RocksDbColumnFamily, RocksDbProfile, RocksDbTuning};
+    RocksDbTuning {
+        column_families: vec![
+            RocksDbColumnFamily::new("first", vec![0]),
+            RocksDbColumnFamily::new("second", vec![1, 0]),
+            RocksDbColumnFamily::new("third", vec![1, 2]),
+        ],
+        ..RocksDbTuning::new(RocksDbProfile::WriteHeavy)
+    }
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_profiles() {
+    use linera_views::rocks_db::{RocksDbCompression, RocksDbProfile, RocksDbTuning};
+    for profile in [
+        RocksDbProfile::Mixed,
+        RocksDbProfile::PointLookup,
+        RocksDbProfile::WriteHeavy,
+    ] {
+        let store = new_tuned_rocks_db_test_store(RocksDbTuning::new(profile)).await;
+        run_writes_from_blank(&store).await;
+    }
+    let tuning = RocksDbTuning {
+        block_cache_size: Some(1 << 20),
+        bloom_filter_bits_per_key: Some(0),
+        compression_per_level: Some(vec![RocksDbCompression::None, RocksDbCompression::Zstd]),
+        ..RocksDbTuning::new(RocksDbProfile::PointLookup)
+    };
+    let store = new_tuned_rocks_db_test_store(tuning).await;
+    run_writes_from_state(&store).await;
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_column_families() {
+    for scenario in get_random_test_scenarios() {
+        let store = new_tuned_rocks_db_test_store(column_families_tuning()).await;
+        run_reads(store, scenario).await;
+    }
+    let store = new_tuned_rocks_db_test_store(column_families_tuning()).await;
+    run_writes_from_blank(&store).await;
+    let store = new_tuned_rocks_db_test_store(column_families_tuning()).await;
+    run_writes_from_state(&store).await;
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_column_families_span_prefixes() {
+    use linera_views::{rocks_db::RocksDbStore, store::KeyIterable as _};
+
+    async fn find_keys(store: &RocksDbStore, key_prefix: &[u8]) -> Vec<Vec<u8>> {
+        let keys = store.find_keys_by_prefix(key_prefix).await.unwrap();
+        keys.iterator().map(|key| key.unwrap().to_vec()).collect()
+    }
+
+    let store = new_tuned_rocks_db_test_store(column_families_tuning()).await;
+    let mut batch = Batch::new();
+    for key in [
+        vec![0, 5],
+        vec![1],
+        vec![1, 0, 3],
+        vec![1, 1],
+        vec![1, 2],
+        vec![2],
+    ] {
+        batch.put_key_value_bytes(key.clone(), key);
+    }
+    store.write_batch(batch).await.unwrap();
+
+    // The keys starting with 1 are in three column families, and are merged in order.
+    assert_eq!(
+        find_keys(&store, &[1]).await,
+        vec![vec![], vec![0, 3], vec![1], vec![2]]
+    );
+    let key_values = store.find_key_values_by_prefix(&[]).await.unwrap();
+    let keys = key_values
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        vec![
+            vec![0, 5],
+            vec![1],
+            vec![1, 0, 3],
+            vec![1, 1],
+            vec![1, 2],
+            vec![2]
+        ]
+    );
+    assert_eq!(
+        store.read_value_bytes(&[1, 0, 3]).await.unwrap(),
+        Some(vec![1, 0, 3])
+    );
+    assert_eq!(
+        store
+            .contains_keys(vec![vec![0, 5], vec![1, 2], vec![3]])
+            .await
+            .unwrap(),
+        vec![true, true, false]
+    );
+
+    let mut batch = Batch::new();
+    batch.delete_key_prefix(vec![1]);
+    store.write_batch(batch).await.unwrap();
+    assert_eq!(find_keys(&store, &[]).await, vec![vec![0, 5], vec![2]]);
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_overlapping_column_families_are_rejected() {
+    use linera_views::{
+        random::generate_test_namespace,
+        rocks_db::{RocksDbColumnFamily, RocksDbStore, RocksDbTuning},
+        store::AdminKeyValueStore as _,
+    };
+    let tuning = RocksDbTuning {
+        column_families: vec![
+            RocksDbColumnFamily::new("first", vec![1]),
+            RocksDbColumnFamily::new("second", vec![1, 2]),
+        ],
+        ..RocksDbTuning::default()
+    };
+    let config = RocksDbStore::new_test_config()
+        .await
+        .unwrap()
+        .with_tuning(tuning);
+    assert!(
+        RocksDbStore::recreate_and_connect(&config, &generate_test_namespace())
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_column_families_cannot_change() {
+    use linera_views::{
+        random::generate_test_namespace,
+        rocks_db::{RocksDbColumnFamily, RocksDbStore, RocksDbTuning},
+        store::AdminKeyValueStore as _,
+    };
+    let config = RocksDbStore::new_test_config()
+        .await
+        .unwrap()
+        .with_tuning(column_families_tuning());
+    let namespace = generate_test_namespace();
+    let store = RocksDbStore::recreate_and_connect(&config, &namespace)
+        .await
+        .unwrap();
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![1, 2, 3], vec![4]);
+    store.write_batch(batch).await.unwrap();
+    drop(store);
+
+    let other_tunings = [
+        RocksDbTuning::default(),
+        RocksDbTuning {
+            column_families: vec![RocksDbColumnFamily::new("first", vec![0])],
+            ..RocksDbTuning::default()
+        },
+        RocksDbTuning {
+            column_families: vec![
+                RocksDbColumnFamily::new("first", vec![0]),
+                RocksDbColumnFamily::new("second", vec![1, 0]),
+                RocksDbColumnFamily::new("third", vec![1, 3]),
+            ],
+            ..RocksDbTuning::default()
+        },
+    ];
+    for tuning in other_tunings {
+        let other_config = config.clone().with_tuning(tuning);
+        assert!(RocksDbStore::connect(&other_config, &namespace)
+            .await
+            .is_err());
+    }
+    // The profile and the other settings can still be changed.
+    let same_config = config.clone().with_tuning(RocksDbTuning {
+        bloom_filter_bits_per_key: Some(0),
+        ..column_families_tuning()
+    });
+    let store = RocksDbStore::connect(&same_config, &namespace)
+        .await
+        .unwrap();
+    assert_eq!(
+        store.read_value_bytes(&[1, 2, 3]).await.unwrap(),
+        Some(vec![4])
+    );
+}
+
 #[cfg(with_indexeddb)]
 #[wasm_bindgen_test]
 async fn test_indexed_db_writes_from_state() {
@@ -339,12 +572,159 @@ async fn test_scylla_db_writes_from_state() {
     run_writes_from_state(&store).await;
 }
 
+#[cfg(with_scylladb)]
+async fn new_scylla_db_test_store(
+    max_concurrent_multi_key_queries: Option<usize>,
+) -> linera_views::scylla_db::ScyllaDbStore {
+    use linera_views::{
+        random::generate_test_namespace, scylla_db::ScyllaDbStore, store::AdminKeyValueStore as _,
+    };
+    let config = ScyllaDbStore::new_test_config()
+        .await
+        .unwrap()
+        .with_max_concurrent_multi_key_queries(max_concurrent_multi_key_queries);
+    ScyllaDbStore::recreate_and_connect(&config, &generate_test_namespace())
+        .await
+        .unwrap()
+}
+
+#[cfg(with_scylladb)]
+#[tokio::test]
+async fn test_scylla_db_concurrent_multi_key_reads() {
+    use linera_views::store::AdminKeyValueStore as _;
+
+    for limit in [None, Some(1), Some(3)] {
+        let store = new_scylla_db_test_store(limit).await;
+        let store = store.clone_with_root_key(&[7]).unwrap();
+        let mut batch = Batch::new();
+        for i in 0..500u32 {
+            if i % 3 != 0 {
+                batch.put_key_value_bytes(i.to_be_bytes().to_vec(), vec![i as u8; 10]);
+            }
+        }
+        store.write_batch(batch).await.unwrap();
+
+        // Enough keys for several chunks, with duplicates and missing keys.
+        let keys = (0..700u32)
+            .map(|i| (i % 550).to_be_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let values = store.read_multi_values_bytes(keys.clone()).await.unwrap();
+        let contained = store.contains_keys(keys.clone()).await.unwrap();
+        assert_eq!(values.len(), keys.len());
+        assert_eq!(contained.len(), keys.len());
+        for ((key, value), contains) in keys.iter().zip(values).zip(contained) {
+            assert_eq!(value, store.read_value_bytes(key).await.unwrap());
+            assert_eq!(contains, value.is_some());
+        }
+    }
+}
+
+#[cfg(with_scylladb)]
+#[tokio::test]
+async fn test_scylla_db_journaled_multi_key_reads() {
+    let store = new_scylla_db_test_store(Some(2)).await;
+    // The batch is too big to be written at once, so it goes through the journal.
+    let mut batch = Batch::new();
+    for i in 0..6000u32 {
+        batch.put_key_value_bytes(i.to_be_bytes().to_vec(), i.to_le_bytes().to_vec());
+    }
+    batch.delete_key_prefix(vec![0, 0, 0]);
+    store.write_batch(batch).await.unwrap();
+
+    let keys = (0..6000u32)
+        .map(|i| i.to_be_bytes().to_vec())
+        .collect::<Vec<_>>();
+    let values = store.read_multi_values_bytes(keys).await.unwrap();
+    for (i, value) in (0..6000u32).zip(values) {
+        let expected = (i >= 256).then(|| i.to_le_bytes().to_vec());
+        assert_eq!(value, expected);
+    }
+}
+
 #[cfg(with_scylladb)]
 #[tokio::test]
 async fn test_scylladb_access() {
     access_admin_test::<linera_views::scylla_db::ScyllaDbStore>().await
 }
 
+#[cfg(with_dynamodb)]
+#[tokio::test]
+async fn test_dynamo_db_spilled_values() {
+    use linera_views::{
+        dynamo_db::DynamoDbStoreInternal, journaling::JournalingKeyValueStore,
+        store::KeyValueIterable as _,
+    };
+
+    let store = JournalingKeyValueStore::<DynamoDbStoreInternal>::new_test_store()
+        .await
+        .unwrap();
+    // Larger than a DynamoDB item, which is limited to 400 KB.
+    let big_value = (0..1000000u32).map(|i| i as u8).collect::<Vec<_>>();
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![1, 1], big_value.clone());
+    batch.put_key_value_bytes(vec![1, 2], vec![42]);
+    store.write_batch(batch).await.unwrap();
+
+    assert_eq!(
+        store.read_value_bytes(&[1, 1]).await.unwrap(),
+        Some(big_value.clone())
+    );
+    assert_eq!(
+        store
+            .read_multi_values_bytes(vec![vec![1, 2], vec![1, 1], vec![1, 3]])
+            .await
+            .unwrap(),
+        vec![Some(vec![42]), Some(big_value.clone()), None]
+    );
+    assert!(store.contains_key(&[1, 1]).await.unwrap());
+    let key_values = store.find_key_values_by_prefix(&[1]).await.unwrap();
+    let key_values = key_values
+        .iterator()
+        .map(|entry| {
+            let (key, value) = entry.unwrap();
+            (key.to_vec(), value.to_vec())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(key_values, vec![(vec![1], big_value), (vec![2], vec![42])]);
+
+    // Overwriting and deleting spilled values.
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![1, 1], vec![7]);
+    store.write_batch(batch).await.unwrap();
+    assert_eq!(
+        store.read_value_bytes(&[1, 1]).await.unwrap(),
+        Some(vec![7])
+    );
+    let mut batch = Batch::new();
+    batch.delete_key(vec![1, 1]);
+    store.write_batch(batch).await.unwrap();
+    assert_eq!(store.read_value_bytes(&[1, 1]).await.unwrap(), None);
+}
+
+#[cfg(with_dynamodb)]
+#[tokio::test]
+async fn test_dynamo_db_ten_thousand_keys() {
+    use linera_views::{dynamo_db::DynamoDbStore, store::KeyIterable as _};
+
+    let store = DynamoDbStore::new_test_store().await.unwrap();
+    let mut batch = Batch::new();
+    for i in 0..10000u32 {
+        batch.put_key_value_bytes(i.to_be_bytes().to_vec(), i.to_le_bytes().to_vec());
+    }
+    store.write_batch(batch).await.unwrap();
+
+    let keys = store.find_keys_by_prefix(&[]).await.unwrap();
+    assert_eq!(keys.iterator().count(), 10000);
+    let keys = (0..10000u32)
+        .step_by(97)
+        .map(|i| i.to_be_bytes().to_vec())
+        .collect::<Vec<_>>();
+    let values = store.read_multi_values_bytes(keys).await.unwrap();
+    for (i, value) in (0..10000u32).step_by(97).zip(values) {
+        assert_eq!(value, Some(i.to_le_bytes().to_vec()));
+    }
+}
+
 #[cfg(with_dynamodb)]
 #[tokio::test]
 async fn test_dynamodb_access() {
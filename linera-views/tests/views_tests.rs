@@ -19,22 +19,23 @@ use linera_views::{
     },
     collection_view::HashedCollectionView,
     context::{Context, MemoryContext, ViewContext},
+    enum_view::EnumView,
     key_value_store_view::{KeyValueStoreView, ViewContainer},
     log_view::HashedLogView,
     lru_caching::{LruCachingMemoryStore, LruCachingStore, DEFAULT_STORAGE_CACHE_CONFIG},
-    map_view::{ByteMapView, HashedMapView},
+    map_view::{ByteMapView, HashedMapView, MapView},
     memory::MemoryStore,
     queue_view::HashedQueueView,
     random::make_deterministic_rng,
     reentrant_collection_view::HashedReentrantCollectionView,
-    register_view::HashedRegisterView,
+    register_view::{HashedRegisterView, RegisterView},
     set_view::HashedSetView,
-    store::{TestKeyValueStore as _, WritableKeyValueStore as _},
+    store::{ReadableKeyValueStore as _, TestKeyValueStore as _, WritableKeyValueStore as _},
     test_utils::{
         get_random_byte_vector, get_random_key_value_operations, get_random_key_values,
         span_random_reordering_put_delete,
     },
-    views::{CryptoHashRootView, HashableView, Hasher, RootView, View, ViewError},
+    views::{ClonableView, CryptoHashRootView, HashableView, Hasher, RootView, View, ViewError},
 };
 use rand::{Rng, RngCore};
 
@@ -644,6 +645,274 @@ async fn test_byte_map_view() -> Result<()> {
     Ok(())
 }
 
+#[derive(View)]
+pub enum GameState<C> {
+    Uninitialized,
+    Active { players: MapView<C, String, u32> },
+    Finished { winner: RegisterView<C, String> },
+}
+
+#[derive(RootView)]
+pub struct GameStateView<C> {
+    pub state: EnumView<C, GameState<C>>,
+    pub round: RegisterView<C, u64>,
+}
+
+#[tokio::test]
+async fn test_enum_view_round_trip() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    // Only the index of the variant is read with the other views.
+    assert_eq!(GameStateView::<MemoryContext<()>>::NUM_INIT_KEYS, 2);
+    {
+        let mut view = GameStateView::load(context.clone()).await?;
+        assert_eq!(
+            view.state.discriminant(),
+            GameStateDiscriminant::Uninitialized
+        );
+        let GameState::Active { players } = view.state.switch_to(GameStateDiscriminant::Active)?
+        else {
+            unreachable!();
+        };
+        players.insert("Alice", 3)?;
+        players.insert("Bob", 5)?;
+        view.round.set(1);
+        view.save().await?;
+    }
+    {
+        let mut view = GameStateView::load(context.clone()).await?;
+        let GameState::Active { players } = view.state.get_mut().await? else {
+            panic!("the active variant should be loaded");
+        };
+        assert_eq!(players.get("Bob").await?, Some(5));
+        players.insert("Bob", 6)?;
+        view.save().await?;
+    }
+    {
+        let mut view = GameStateView::load(context.clone()).await?;
+        let GameState::Active { players } = view.state.get().await? else {
+            panic!("the active variant should be loaded");
+        };
+        assert_eq!(
+            players.indices().await?,
+            vec!["Alice".to_string(), "Bob".to_string()]
+        );
+        assert_eq!(players.get("Bob").await?, Some(6));
+        let GameState::Finished { winner } =
+            view.state.switch_to(GameStateDiscriminant::Finished)?
+        else {
+            unreachable!();
+        };
+        winner.set("Bob".to_string());
+        view.save().await?;
+    }
+    {
+        let mut view = GameStateView::load(context.clone()).await?;
+        let GameState::Finished { winner } = view.state.get().await? else {
+            panic!("the finished variant should be loaded");
+        };
+        assert_eq!(winner.get(), "Bob");
+        // The players of the previous variant were deleted.
+        let GameState::Active { players } = view.state.switch_to(GameStateDiscriminant::Active)?
+        else {
+            unreachable!();
+        };
+        assert_eq!(players.count().await?, 0);
+        view.rollback();
+        assert_eq!(view.state.discriminant(), GameStateDiscriminant::Finished);
+        assert!(!view.has_pending_changes().await);
+        view.clear();
+        view.save().await?;
+    }
+    let view = GameStateView::load(context.clone()).await?;
+    assert_eq!(
+        view.state.discriminant(),
+        GameStateDiscriminant::Uninitialized
+    );
+    assert_eq!(*view.round.get(), 0);
+    assert!(context.store().find_keys_by_prefix(&[]).await?.is_empty());
+    Ok(())
+}
+
+#[derive(HashableView, ClonableView)]
+pub enum Slot<C> {
+    Empty,
+    Value {
+        value: RegisterView<C, u64>,
+    },
+    Pair {
+        first: RegisterView<C, u64>,
+        second: RegisterView<C, u64>,
+    },
+}
+
+#[tokio::test]
+async fn test_enum_view_hash_and_clone() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    let mut view = EnumView::<_, Slot<_>>::load(context.clone()).await?;
+    let empty_hash = view.hash().await?;
+    let Slot::Value { value } = view.switch_to(SlotDiscriminant::Value)? else {
+        unreachable!();
+    };
+    value.set(0);
+    // The variant is part of the hash, even if its views are empty.
+    let value_hash = view.hash().await?;
+    assert_ne!(value_hash, empty_hash);
+    let Slot::Pair { .. } = view.switch_to(SlotDiscriminant::Pair)? else {
+        unreachable!();
+    };
+    assert_ne!(view.hash().await?, value_hash);
+    let Slot::Value { value } = view.switch_to(SlotDiscriminant::Value)? else {
+        unreachable!();
+    };
+    value.set(7);
+    let hash = view.hash_mut().await?;
+    assert_ne!(hash, value_hash);
+    assert_eq!(view.hash().await?, hash);
+
+    let mut batch = Batch::new();
+    view.flush(&mut batch)?;
+    context.store().write_batch(batch).await?;
+    let mut view = EnumView::<_, Slot<_>>::load(context.clone()).await?;
+    assert_eq!(view.hash().await?, hash);
+
+    let mut clone = view.clone_unchecked()?;
+    let Slot::Value { value } = clone.get_mut().await? else {
+        panic!("the clone should have the stored variant");
+    };
+    assert_eq!(*value.get(), 7);
+    value.set(8);
+    assert_ne!(clone.hash().await?, hash);
+    assert_eq!(view.hash().await?, hash);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_enum_view_clear() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    let mut view = EnumView::<_, Slot<_>>::load(context.clone()).await?;
+    let empty_hash = view.hash().await?;
+    let Slot::Pair { first, .. } = view.switch_to(SlotDiscriminant::Pair)? else {
+        unreachable!();
+    };
+    first.set(1);
+    let mut batch = Batch::new();
+    view.flush(&mut batch)?;
+    context.store().write_batch(batch).await?;
+
+    let mut view = EnumView::<_, Slot<_>>::load(context.clone()).await?;
+    view.clear();
+    assert_eq!(view.discriminant(), SlotDiscriminant::Empty);
+    assert!(view.has_pending_changes().await);
+    // Cloning the view before the first variant is created keeps it cleared.
+    let clone = view.clone_unchecked()?;
+    assert_eq!(clone.discriminant(), SlotDiscriminant::Empty);
+    assert_eq!(clone.hash().await?, empty_hash);
+    let mut batch = Batch::new();
+    assert!(view.flush(&mut batch)?);
+    context.store().write_batch(batch).await?;
+    assert!(context.store().find_keys_by_prefix(&[]).await?.is_empty());
+
+    let view = EnumView::<_, Slot<_>>::load(context).await?;
+    assert_eq!(view.discriminant(), SlotDiscriminant::Empty);
+    assert_eq!(view.hash().await?, empty_hash);
+    Ok(())
+}
+
+#[derive(View)]
+pub struct PairView<C, V> {
+    pub first: V,
+    pub second: V,
+    pub count: linera_views::register_view::RegisterView<C, u64>,
+}
+
+#[derive(RootView)]
+pub struct GenericStateView<C, V> {
+    pub pair: PairView<C, V>,
+    pub inner: V,
+}
+
+#[tokio::test]
+async fn test_generic_view_round_trip() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    {
+        let mut view =
+            GenericStateView::<_, RegisterView<_, String>>::load(context.clone()).await?;
+        view.pair.first.set("first".to_string());
+        view.pair.second.set("second".to_string());
+        view.pair.count.set(2);
+        view.inner.set("inner".to_string());
+        view.save().await?;
+    }
+    let view = GenericStateView::<_, RegisterView<_, String>>::load(context).await?;
+    assert_eq!(view.pair.first.get(), "first");
+    assert_eq!(view.pair.second.get(), "second");
+    assert_eq!(*view.pair.count.get(), 2);
+    assert_eq!(view.inner.get(), "inner");
+    Ok(())
+}
+
+#[cfg(with_metrics)]
+#[derive(RootView)]
+pub struct StatsStateView<C> {
+    pub register: RegisterView<C, u64>,
+    pub map: MapView<C, u32, String>,
+}
+
+#[cfg(with_metrics)]
+#[tokio::test]
+async fn test_storage_stats_per_view() -> Result<()> {
+    use linera_views::{
+        memory::create_test_memory_store,
+        storage_stats::{StorageStats, StorageStatsStore},
+    };
+
+    let store = StorageStatsStore::new(create_test_memory_store());
+    let storage_stats = store.storage_stats().unwrap().clone();
+    let context = ViewContext::create_root_context(store, ()).await?;
+    let (register_key, map_key) = {
+        let mut view = StatsStateView::load(context.clone()).await?;
+        view.register.set(7);
+        for index in 0..3u32 {
+            view.map.insert(&index, format!("value {index}"))?;
+        }
+        view.save().await?;
+        (
+            view.register.context().base_key().bytes.clone(),
+            view.map.context().base_key().bytes.clone(),
+        )
+    };
+    let stats = storage_stats.stats();
+    assert_eq!(stats[&register_key].keys_written, 1);
+    assert_eq!(stats[&map_key].keys_written, 3);
+    assert!(stats[&map_key].bytes_written > stats[&register_key].bytes_written);
+
+    storage_stats.reset();
+    let view = StatsStateView::load(context.clone()).await?;
+    assert_eq!(view.map.get(&1u32).await?, Some("value 1".to_string()));
+    assert_eq!(view.map.get(&5u32).await?, None);
+    let stats = storage_stats.stats();
+    assert_eq!(
+        stats[&register_key],
+        StorageStats {
+            keys_read: 1,
+            bytes_read: 8,
+            ..StorageStats::default()
+        }
+    );
+    assert_eq!(stats[&map_key].keys_read, 2);
+    assert_eq!(stats[&map_key].keys_written, 0);
+    assert_eq!(storage_stats.total().keys_read, 3);
+
+    // Paginated scans are accounted to the view of the prefix.
+    let page = context
+        .store()
+        .find_keys_by_prefix_after(&map_key, None, 2)
+        .await?;
+    assert_eq!(page.len(), 2);
+    assert_eq!(storage_stats.stats()[&map_key].keys_read, 4);
+    Ok(())
+}
+
 #[cfg(test)]
 async fn test_views_in_lru_memory_param(config: &TestConfig) -> Result<()> {
     tracing::warn!("Testing config {:?} with lru memory", config);
@@ -1197,3 +1466,60 @@ async fn check_large_write_memory() -> Result<()> {
     let mut store = MemoryTestStorage::new().await;
     check_large_write(&mut store, vector).await
 }
+
+#[derive(RootView, async_graphql::SimpleObject)]
+#[view(context = MemoryContext<()>)]
+pub struct GraphQlStateView {
+    pub map: MapView<MemoryContext<()>, u32, String>,
+}
+
+/// Checks that the `entriesConnection` field of a map in a view deriving
+/// `async_graphql::SimpleObject` returns bounded pages whose cursors lead to the next ones.
+#[tokio::test]
+async fn check_map_entries_connection() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    let mut view = GraphQlStateView::load(context).await?;
+    for index in 0..150 {
+        view.map.insert(&index, index.to_string())?;
+    }
+    view.save().await?;
+    let schema = async_graphql::Schema::new(
+        view,
+        async_graphql::EmptyMutation,
+        async_graphql::EmptySubscription,
+    );
+
+    let query = |arguments: &str| {
+        format!(
+            "{{ map {{ entriesConnection{arguments} {{ \
+                pageInfo {{ hasNextPage endCursor }} nodes {{ key value }} \
+            }} }} }}"
+        )
+    };
+    // Without `first`, a page has the default size.
+    let response = schema.execute(query("")).await.into_result().unwrap();
+    let data = response.data.into_json()?;
+    let page = &data["map"]["entriesConnection"];
+    assert_eq!(page["nodes"].as_array().unwrap().len(), 100);
+    assert_eq!(page["nodes"][99]["value"], "99");
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    let cursor = page["pageInfo"]["endCursor"].as_str().unwrap();
+    let arguments = format!("(after: \"{cursor}\", first: 10000)");
+    let response = schema
+        .execute(query(&arguments))
+        .await
+        .into_result()
+        .unwrap();
+    let data = response.data.into_json()?;
+    let page = &data["map"]["entriesConnection"];
+    let keys = page["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["key"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, (100..150).collect::<Vec<_>>());
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+    Ok(())
+}